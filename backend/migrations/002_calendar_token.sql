@@ -0,0 +1,16 @@
+-- Locate918 Database Schema
+-- Migration 002: Calendar subscription tokens
+--
+-- Calendar apps (Google, Apple, Outlook) poll subscription feeds by URL and
+-- can't send auth headers, so each user gets a secret token that goes in the
+-- feed's query string: /api/users/:id/saved.ics?token=...
+
+-- =============================================================================
+-- USERS TABLE
+-- =============================================================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS calendar_token TEXT NOT NULL
+        DEFAULT replace(gen_random_uuid()::text, '-', '');
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_calendar_token ON users(calendar_token);
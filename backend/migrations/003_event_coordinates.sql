@@ -0,0 +1,19 @@
+-- Locate918 Database Schema
+-- Migration 003: Event coordinates
+--
+-- Adds optional latitude/longitude so events can be searched by distance
+-- ("within 5 miles of me"). Events without coordinates are skipped by radius
+-- searches but otherwise behave as before.
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION,   -- WGS84 decimal degrees
+    ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION;
+
+-- Only geocoded events participate in radius searches
+CREATE INDEX IF NOT EXISTS idx_events_coordinates
+    ON events(latitude, longitude)
+    WHERE latitude IS NOT NULL AND longitude IS NOT NULL;
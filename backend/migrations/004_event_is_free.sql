@@ -0,0 +1,22 @@
+-- Locate918 Database Schema
+-- Migration 004: Free events
+--
+-- "Is it free?" is one of the most common questions, so events get an
+-- explicit flag instead of relying on price_min/price_max being zero.
+-- Sources that say "Free" set it directly; otherwise it's derived from a
+-- $0 price when the event is created.
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS is_free BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Backfill from existing prices
+UPDATE events
+SET is_free = TRUE
+WHERE price_max = 0
+   OR (price_min = 0 AND price_max IS NULL);
+
+CREATE INDEX IF NOT EXISTS idx_events_is_free ON events(is_free) WHERE is_free = TRUE;
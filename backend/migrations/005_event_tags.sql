@@ -0,0 +1,21 @@
+-- Locate918 Database Schema
+-- Migration 005: Event tags
+--
+-- Categories are too coarse on their own: a brewery trivia night is both
+-- "nightlife" and "community", and qualities like "outdoor" or
+-- "family-friendly" aren't categories at all. Tags are free-form labels,
+-- any number per event, filterable with AND semantics.
+
+-- =============================================================================
+-- EVENT TAGS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS event_tags (
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,  -- Lowercased on write: 'outdoor', 'family-friendly'
+
+    PRIMARY KEY (event_id, tag)
+);
+
+-- Lookups by tag for search filters (the PK covers lookups by event)
+CREATE INDEX IF NOT EXISTS idx_event_tags_tag ON event_tags(tag);
@@ -0,0 +1,28 @@
+-- Locate918 Database Schema
+-- Migration 006: Event status
+--
+-- Scrapers regularly find that an event was cancelled, postponed or sold
+-- out. Rather than deleting the row (and losing interactions) or leaving it
+-- stale, the event keeps a status. Cancelled events are hidden from lists
+-- and search unless explicitly requested.
+
+-- =============================================================================
+-- EVENT STATUS TYPE
+-- =============================================================================
+
+DO $$
+BEGIN
+    CREATE TYPE event_status AS ENUM ('scheduled', 'cancelled', 'postponed', 'sold_out');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS status event_status NOT NULL DEFAULT 'scheduled';
+
+CREATE INDEX IF NOT EXISTS idx_events_cancelled ON events(status) WHERE status = 'cancelled';
@@ -0,0 +1,47 @@
+-- Locate918 Database Schema
+-- Migration 007: Venue records linked to events
+--
+-- `events.venue` is free text, so "Cain's Ballroom", "Cains Ballroom" and
+-- "CAIN'S BALLROOM" are three different places. Events now also point at a
+-- row in `venues` via `venue_id`. The text column stays populated so older
+-- clients keep working.
+--
+-- Venues are matched on a normalized name: lowercased, apostrophes removed,
+-- every other run of non-alphanumerics collapsed to one space, trimmed.
+--   "Cain's Ballroom"  -> "cains ballroom"
+--   " CAINS  BALLROOM" -> "cains ballroom"
+
+-- =============================================================================
+-- NAME NORMALIZATION
+-- =============================================================================
+
+CREATE OR REPLACE FUNCTION normalize_venue_name(name TEXT) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE STRICT AS $$
+    SELECT trim(regexp_replace(
+        regexp_replace(lower(name), '[''’]', '', 'g'),
+        '[^a-z0-9]+', ' ', 'g'
+    ))
+$$;
+
+-- =============================================================================
+-- VENUES TABLE
+-- =============================================================================
+
+ALTER TABLE venues
+    ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN IF NOT EXISTS normalized_name TEXT
+        GENERATED ALWAYS AS (normalize_venue_name(name)) STORED;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_venues_normalized_name ON venues(normalized_name);
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+-- Deleting a venue unlinks its events; the legacy text column is kept
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS venue_id UUID REFERENCES venues(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_events_venue_id ON events(venue_id) WHERE venue_id IS NOT NULL;
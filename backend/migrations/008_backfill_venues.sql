@@ -0,0 +1,32 @@
+-- Locate918 Database Schema
+-- Migration 008: Backfill venues from existing events
+--
+-- Creates one venue per distinct normalized `events.venue` string and links
+-- the events to it. When spellings differ, the most common one becomes the
+-- venue name. Safe to re-run: existing venues and links are left alone.
+
+-- =============================================================================
+-- CREATE VENUES
+-- =============================================================================
+
+INSERT INTO venues (name, address)
+SELECT DISTINCT ON (normalize_venue_name(venue))
+    trim(venue),
+    MAX(venue_address) OVER (PARTITION BY normalize_venue_name(venue))
+FROM (
+    SELECT venue, venue_address, COUNT(*) OVER (PARTITION BY trim(venue)) AS uses
+    FROM events
+    WHERE venue IS NOT NULL AND normalize_venue_name(venue) <> ''
+) spellings
+ORDER BY normalize_venue_name(venue), uses DESC, trim(venue)
+ON CONFLICT (normalized_name) DO NOTHING;
+
+-- =============================================================================
+-- LINK EVENTS
+-- =============================================================================
+
+UPDATE events
+SET venue_id = venues.id
+FROM venues
+WHERE events.venue_id IS NULL
+  AND normalize_venue_name(events.venue) = venues.normalized_name;
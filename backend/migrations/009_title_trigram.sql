@@ -0,0 +1,18 @@
+-- Locate918 Database Schema
+-- Migration 009: Trigram index on event titles
+--
+-- "Related events" falls back to fuzzy title matching when an event has no
+-- categories ("Jazz Night at Cain's" ~ "Jazz Night: Holiday Edition").
+-- pg_trgm provides similarity() and an index that speeds it up.
+
+-- =============================================================================
+-- EXTENSIONS
+-- =============================================================================
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+CREATE INDEX IF NOT EXISTS idx_events_title_trgm ON events USING GIN (title gin_trgm_ops);
@@ -0,0 +1,17 @@
+-- Locate918 Database Schema
+-- Migration 010: Soft-archived events
+--
+-- Past events pile up and slow down every scan ordered by start_time. A
+-- background job stamps `archived_at` on events that ended more than
+-- ARCHIVE_AFTER_DAYS ago; list and search queries skip archived rows unless
+-- asked. Rows are kept (not deleted) so interaction history still joins.
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;  -- NULL = live
+
+-- Lists filter on archived_at IS NULL and sort by start_time
+CREATE INDEX IF NOT EXISTS idx_events_archived_start ON events(archived_at, start_time);
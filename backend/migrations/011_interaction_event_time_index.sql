@@ -0,0 +1,13 @@
+-- Locate918 Database Schema
+-- Migration 011: Index interactions by event and time
+--
+-- Per-event stats and trending both filter user_interactions by event_id
+-- and a created_at window. The single-column event_id index from 001 still
+-- has to visit every row for the event; this one serves the range directly.
+
+-- =============================================================================
+-- USER INTERACTIONS TABLE
+-- =============================================================================
+
+CREATE INDEX IF NOT EXISTS idx_user_interactions_event_created
+    ON user_interactions(event_id, created_at);
@@ -0,0 +1,24 @@
+-- Locate918 Database Schema
+-- Migration 012: Additional event sources
+--
+-- The same show often gets scraped from two sites (the venue page and
+-- Eventbrite, say). When duplicates are merged, the surviving event keeps
+-- its own `source_url`; every other URL it was listed under goes here so
+-- the scraper recognizes it next time instead of re-creating the duplicate.
+
+-- =============================================================================
+-- EVENT SOURCES TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS event_sources (
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    source_url TEXT NOT NULL,
+    source_name TEXT,
+    merged_from UUID,  -- id of the deleted duplicate (no FK: it no longer exists)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (event_id, source_url)
+);
+
+-- "Have we seen this URL before?" lookups from the scraper
+CREATE INDEX IF NOT EXISTS idx_event_sources_source_url ON event_sources(source_url);
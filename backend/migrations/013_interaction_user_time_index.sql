@@ -0,0 +1,14 @@
+-- Locate918 Database Schema
+-- Migration 013: Index interactions by user and time
+--
+-- GET /api/users/:id/interactions pages newest-first with a
+-- (created_at, id) cursor. The single-column user_id index from 001 makes
+-- Postgres sort every interaction the user has; this one is already in
+-- page order.
+
+-- =============================================================================
+-- USER INTERACTIONS TABLE
+-- =============================================================================
+
+CREATE INDEX IF NOT EXISTS idx_user_interactions_user_created
+    ON user_interactions(user_id, created_at DESC, id DESC);
@@ -0,0 +1,26 @@
+-- Locate918 Database Schema
+-- Migration 014: Explicit vs inferred preferences
+--
+-- The preference learning job writes category weights it infers from a
+-- user's interactions. `source` tells those apart from weights the user
+-- set themselves, so the job never overwrites an explicit choice.
+-- Existing rows all came from the preferences endpoint, hence 'explicit'.
+
+-- =============================================================================
+-- PREFERENCE SOURCE TYPE
+-- =============================================================================
+
+DO $$
+BEGIN
+    CREATE TYPE preference_source AS ENUM ('explicit', 'inferred');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+-- =============================================================================
+-- USER PREFERENCES TABLE
+-- =============================================================================
+
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS source preference_source NOT NULL DEFAULT 'explicit';
@@ -0,0 +1,14 @@
+-- Locate918 Database Schema
+-- Migration 015: User passwords
+--
+-- Argon2 PHC strings ("$argon2id$v=19$..."), never plaintext. NULL means
+-- the account has no password yet (created before passwords, or a guest);
+-- those accounts keep signing in by email until they set one.
+-- The column is deliberately not in USER_COLUMNS, so it never reaches a
+-- `User` or an API response.
+
+-- =============================================================================
+-- USERS TABLE
+-- =============================================================================
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS password_hash TEXT;
@@ -0,0 +1,19 @@
+-- Locate918 Database Schema
+-- Migration 016: OAuth identities
+--
+-- "Sign in with Google" matches returning users on the provider's stable
+-- account id (`sub`), not their email, so changing the Gmail address on
+-- the Google side still lands in the same Locate918 account.
+-- Both columns are NULL for email/password accounts.
+
+-- =============================================================================
+-- USERS TABLE
+-- =============================================================================
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS oauth_provider TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS oauth_subject TEXT;
+
+-- One Locate918 account per provider account
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_oauth_identity
+    ON users(oauth_provider, oauth_subject)
+    WHERE oauth_subject IS NOT NULL;
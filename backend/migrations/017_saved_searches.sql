@@ -0,0 +1,48 @@
+-- Locate918 Database Schema
+-- Migration 017: Saved searches and notifications
+--
+-- "Tell me whenever a new jazz event downtown appears." A background job
+-- runs each saved search against events created since it last ran and
+-- writes a notification per match. Delivery (push, email) comes later;
+-- for now notifications are rows the app can list.
+
+-- =============================================================================
+-- SAVED SEARCHES TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- Same meaning as the /api/events/search parameters
+    q TEXT,
+    category TEXT,
+    location TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_notified_at TIMESTAMPTZ  -- NULL until the matcher first runs it
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_searches_user_id ON saved_searches(user_id);
+
+-- =============================================================================
+-- NOTIFICATIONS TABLE
+-- =============================================================================
+-- Written by background producers (saved-search matcher, reminders, ...).
+-- `kind` is free text so a new producer doesn't need a migration.
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,  -- 'saved_search', ...
+    event_id UUID REFERENCES events(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    body TEXT,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One notification of each kind per user and event, however many times a
+-- producer runs (or however many saved searches match)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notifications_once
+    ON notifications(user_id, event_id, kind);
@@ -0,0 +1,11 @@
+-- Locate918 Database Schema
+-- Migration 018: Notification listing index
+--
+-- The notifications list pages newest first on (created_at, id) per user,
+-- and the unread badge counts rows with no read_at.
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created
+    ON notifications(user_id, created_at DESC, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_unread
+    ON notifications(user_id) WHERE read_at IS NULL;
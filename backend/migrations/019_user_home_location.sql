@@ -0,0 +1,15 @@
+-- Locate918 Database Schema
+-- Migration 019: Structured user location
+--
+-- location_preference ("Downtown") is kept for display, but nothing can
+-- compute a distance from it. A home point and radius let recommendations
+-- and the LLM prefer nearby events once those have coordinates.
+
+-- =============================================================================
+-- USERS TABLE
+-- =============================================================================
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS home_latitude DOUBLE PRECISION,   -- WGS84 decimal degrees
+    ADD COLUMN IF NOT EXISTS home_longitude DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS preferred_radius_km DOUBLE PRECISION;
@@ -0,0 +1,36 @@
+-- Locate918 Database Schema
+-- Migration 020: Followed venues and categories
+--
+-- Preferences are weights ("I like jazz, +3"); follows are subscriptions
+-- ("always show me everything at Cain's Ballroom"). New events at a
+-- followed venue or in a followed category become notifications, and
+-- recommendations rank them above weighted matches.
+
+-- =============================================================================
+-- FOLLOW TARGET TYPE
+-- =============================================================================
+
+DO $$
+BEGIN
+    CREATE TYPE follow_target AS ENUM ('venue', 'category');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+-- =============================================================================
+-- USER FOLLOWS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS user_follows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    target_type follow_target NOT NULL,
+    target_value TEXT NOT NULL,  -- venue id (as text) or category name
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_notified_at TIMESTAMPTZ,  -- NULL until the matcher first runs it
+
+    -- Following twice is a no-op
+    UNIQUE (user_id, target_type, target_value)
+);
@@ -0,0 +1,13 @@
+-- Locate918 Database Schema
+-- Migration 021: Guest accounts
+--
+-- People try the chat before signing up. A guest is an ordinary user row
+-- with a placeholder email (guest-<id>@guest.locate918.invalid) so their
+-- preferences and interactions are kept; claiming the account later swaps
+-- in a real email or merges the guest into an existing account.
+
+-- =============================================================================
+-- USERS TABLE
+-- =============================================================================
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_guest BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,17 @@
+-- Locate918 Database Schema
+-- Migration 022: Email verification
+--
+-- Accounts used to trust whatever email they were created with. Now a
+-- signed link is mailed on signup and `email_verified_at` is set when it
+-- is opened. Unverified accounts keep working; the frontend nags them.
+
+-- =============================================================================
+-- USERS TABLE
+-- =============================================================================
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+
+-- Accounts linked through Google were only linked for verified emails
+UPDATE users
+SET email_verified_at = created_at
+WHERE oauth_subject IS NOT NULL AND email_verified_at IS NULL;
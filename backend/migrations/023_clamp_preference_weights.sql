@@ -0,0 +1,14 @@
+-- Locate918 Database Schema
+-- Migration 023: Clamp preference weights
+--
+-- Preference weights are now limited to -5..=5 (MAX_PREFERENCE_WEIGHT in
+-- services/preferences.rs). Bring rows written before the check into range
+-- so they can't swamp the LLM prompt or the recommendation scores.
+
+-- =============================================================================
+-- USER_PREFERENCES TABLE
+-- =============================================================================
+
+UPDATE user_preferences
+SET weight = GREATEST(-5, LEAST(5, weight))
+WHERE weight NOT BETWEEN -5 AND 5;
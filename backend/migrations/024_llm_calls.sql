@@ -0,0 +1,29 @@
+-- Locate918 Database Schema
+-- Migration 024: LLM call log
+--
+-- One row per request to the chat model, so we can see what chat costs
+-- (tokens) and how it feels (latency). A chat message can take several
+-- calls (tool rounds); they share a request_id.
+
+-- =============================================================================
+-- LLM_CALLS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS llm_calls (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    request_id UUID NOT NULL,  -- groups the calls behind one chat message
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,  -- NULL when anonymous
+    purpose TEXT NOT NULL,     -- 'chat' or 'intent'
+    model TEXT NOT NULL,
+
+    -- NULL when the call failed or the provider didn't report usage
+    tokens_in INTEGER,
+    tokens_out INTEGER,
+
+    latency_ms INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_llm_calls_created_at ON llm_calls(created_at);
+CREATE INDEX IF NOT EXISTS idx_llm_calls_request_id ON llm_calls(request_id);
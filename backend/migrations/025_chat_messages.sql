@@ -0,0 +1,23 @@
+-- Locate918 Database Schema
+-- Migration 025: Chat history
+--
+-- The turns of each chat session, so a follow-up ("more like the second
+-- one") reaches the model with what came before it. A session is a
+-- client-held UUID; anonymous sessions have no user_id.
+
+-- =============================================================================
+-- CHAT_MESSAGES TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS chat_messages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,  -- NULL when anonymous
+    session_id UUID NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('user', 'assistant')),
+    content TEXT NOT NULL,
+    tool_calls JSONB,  -- the tools the assistant ran for this reply
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_messages_session ON chat_messages(session_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_chat_messages_user_id ON chat_messages(user_id);
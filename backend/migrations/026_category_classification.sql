@@ -0,0 +1,26 @@
+-- Locate918 Database Schema
+-- Migration 026: LLM category classification
+--
+-- Scraped events often arrive without categories, which hides them from
+-- category filters and preference scoring. A background job asks the
+-- model for one; category_source marks the events it filled in, and the
+-- cache keeps a re-scraped listing from being paid for twice.
+
+-- =============================================================================
+-- EVENTS: CATEGORY SOURCE
+-- =============================================================================
+
+-- NULL: from the source or an editor; 'llm': set by the classifier
+ALTER TABLE events ADD COLUMN IF NOT EXISTS category_source TEXT;
+
+-- =============================================================================
+-- CATEGORY_CLASSIFICATIONS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS category_classifications (
+    -- md5 of title, a newline, and the description ('' when NULL)
+    content_hash TEXT PRIMARY KEY,
+    category TEXT NOT NULL,  -- a base category or 'other'
+    model TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,41 @@
+-- Locate918 Database Schema
+-- Migration 027: Chat feedback
+--
+-- Thumbs up/down on assistant replies, so bad answers can be found and the
+-- prompts fixed. Each assistant turn also keeps the profile text the model
+-- saw, since the user's profile may have changed by the time anyone looks.
+
+-- =============================================================================
+-- CHAT MESSAGES: PROFILE SNAPSHOT
+-- =============================================================================
+
+-- The profile section of the system prompt (NULL for anonymous chat)
+ALTER TABLE chat_messages ADD COLUMN IF NOT EXISTS profile_snapshot TEXT;
+
+-- =============================================================================
+-- CHAT RATING TYPE
+-- =============================================================================
+
+DO $$
+BEGIN
+    CREATE TYPE chat_rating AS ENUM ('up', 'down');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+-- =============================================================================
+-- CHAT FEEDBACK TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS chat_feedback (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- One rating per reply; rating again replaces it
+    message_id UUID NOT NULL UNIQUE REFERENCES chat_messages(id) ON DELETE CASCADE,
+    rating chat_rating NOT NULL,
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_chat_feedback_rating ON chat_feedback(rating, updated_at);
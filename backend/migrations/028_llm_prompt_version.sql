@@ -0,0 +1,9 @@
+-- Locate918 Database Schema
+-- Migration 028: Prompt version on LLM calls
+--
+-- The chat system prompt can now be changed without a deploy
+-- (LLM_SYSTEM_PROMPT_FILE), so each call records which prompt it used.
+-- NULL for calls with a fixed prompt (intent parsing, classification) and
+-- for rows from before this migration.
+
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS prompt_version TEXT;
@@ -0,0 +1,42 @@
+-- Locate918 Database Schema
+-- Migration 029: Scrape runs
+--
+-- Scrapes started from the admin API run in the background; each gets a
+-- row here so the caller can follow it (status, how many scrapers are
+-- done, what each found).
+
+-- =============================================================================
+-- SCRAPE RUN STATUS TYPE
+-- =============================================================================
+
+DO $$
+BEGIN
+    CREATE TYPE scrape_run_status AS ENUM ('running', 'completed', 'failed');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+-- =============================================================================
+-- SCRAPE RUNS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS scrape_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source TEXT,                -- the scraper's source_id; NULL for all of them
+    dry_run BOOLEAN NOT NULL DEFAULT FALSE,
+    status scrape_run_status NOT NULL DEFAULT 'running',
+
+    -- Progress: scrapers in the run, and finished so far
+    sources_total INTEGER NOT NULL,
+    sources_done INTEGER NOT NULL DEFAULT 0,
+
+    -- One ScrapeSummary per finished scraper, in run order
+    summaries JSONB NOT NULL DEFAULT '[]',
+
+    error TEXT,                 -- why the run stopped early, if it did
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_scrape_runs_started_at ON scrape_runs(started_at);
@@ -0,0 +1,63 @@
+-- Locate918 Database Schema
+-- Migration 030: Scrape run history per source
+--
+-- A scrape run was one row per admin trigger, covering one source or all
+-- of them, with each scraper's counts in a JSON array. That made "when did
+-- Cain's last work?" a JSON query. Now:
+-- - `scrape_batches` is the trigger (the old table, renamed): what was
+--   asked for and how far along it is
+-- - `scrape_runs` is one scraper's run: one row per source per batch (or
+--   per run outside a batch), with the counts as columns
+
+-- =============================================================================
+-- SCRAPE BATCHES: THE OLD SCRAPE RUNS
+-- =============================================================================
+
+ALTER TABLE scrape_runs RENAME TO scrape_batches;
+ALTER INDEX idx_scrape_runs_started_at RENAME TO idx_scrape_batches_started_at;
+
+-- =============================================================================
+-- SCRAPE RUNS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS scrape_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    batch_id UUID REFERENCES scrape_batches(id) ON DELETE CASCADE,  -- NULL outside the admin API
+    source TEXT NOT NULL,       -- the scraper's source_id
+    dry_run BOOLEAN NOT NULL DEFAULT FALSE,
+    status scrape_run_status NOT NULL DEFAULT 'running',
+
+    -- Events the scraper returned, and what became of them; the rest
+    -- were unchanged
+    events_found INTEGER NOT NULL DEFAULT 0,
+    events_created INTEGER NOT NULL DEFAULT 0,
+    events_updated INTEGER NOT NULL DEFAULT 0,
+    events_skipped INTEGER NOT NULL DEFAULT 0,  -- invalid, or failed to save
+
+    error_message TEXT,         -- why the scrape failed (site down, markup changed)
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+-- History per source, newest first
+CREATE INDEX IF NOT EXISTS idx_scrape_runs_source ON scrape_runs(source, started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_scrape_runs_batch_id ON scrape_runs(batch_id);
+
+-- =============================================================================
+-- BACKFILL
+-- =============================================================================
+
+-- Each batch's summaries become its runs (a run with an error failed)
+INSERT INTO scrape_runs (
+    batch_id, source, dry_run, status,
+    events_found, events_created, events_updated, events_skipped,
+    error_message, started_at, finished_at
+)
+SELECT b.id, s->>'source', b.dry_run,
+       CASE WHEN s->>'error' IS NULL THEN 'completed' ELSE 'failed' END::scrape_run_status,
+       (s->>'found')::INTEGER, (s->>'created')::INTEGER, (s->>'updated')::INTEGER, (s->>'failed')::INTEGER,
+       s->>'error', b.started_at, COALESCE(b.finished_at, b.started_at)
+FROM scrape_batches b
+CROSS JOIN LATERAL jsonb_array_elements(b.summaries) s;
+
+ALTER TABLE scrape_batches DROP COLUMN summaries;
@@ -0,0 +1,39 @@
+-- Locate918 Database Schema
+-- Migration 031: One row per scraped show
+--
+-- The same show can turn up under a new URL (a venue re-slugs its pages)
+-- or from a second source. Scraped events are now also matched on their
+-- title and venue, normalized, and start time (see scraper/persist.rs).
+--
+-- Titles are normalized like venue names are in migration 007, but only
+-- trimmed, whitespace-collapsed and lowercased: punctuation in a title
+-- can tell two shows apart ("Hamlet" vs "Hamlet!?").
+--   "  Jazz   Night " -> "jazz night"
+
+-- =============================================================================
+-- TITLE NORMALIZATION
+-- =============================================================================
+
+CREATE OR REPLACE FUNCTION normalize_event_title(title TEXT) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE STRICT AS $$
+    SELECT lower(trim(regexp_replace(title, '\s+', ' ', 'g')))
+$$;
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+-- Rows the scraper inserted. Only these are held to one row per show:
+-- events created through the API may still be duplicates (and merged)
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS scraped BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- "Is this show already listed?" lookups from the scraper
+CREATE INDEX IF NOT EXISTS idx_events_dedup_lookup
+    ON events (normalize_event_title(title), normalize_venue_name(venue), start_time)
+    WHERE venue IS NOT NULL;
+
+-- Never two scraped rows for the same show in the same hour (UTC)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_events_scraped_show
+    ON events (normalize_event_title(title), normalize_venue_name(venue), date_trunc('hour', start_time AT TIME ZONE 'UTC'))
+    WHERE scraped AND venue IS NOT NULL;
@@ -0,0 +1,31 @@
+-- Locate918 Database Schema
+-- Migration 032: Possible duplicates awaiting review
+--
+-- Two sources rarely title a show the same way ("Jazz Night at The Blue
+-- Note" on Eventbrite, "Blue Note Jazz Night" on the venue's site). After
+-- each scrape, events at the same venue starting close together are
+-- compared by title similarity (pg_trgm, see services/duplicates.rs):
+-- near-certain pairs are merged, likely ones are listed here for an admin.
+
+-- =============================================================================
+-- DUPLICATE CANDIDATES TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS duplicate_candidates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- The listing we had first, which would survive a merge
+    event_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    duplicate_id UUID NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    similarity REAL NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (event_id <> duplicate_id),
+    UNIQUE (event_id, duplicate_id)
+);
+
+-- The review queue, most alike first
+CREATE INDEX IF NOT EXISTS idx_duplicate_candidates_similarity
+    ON duplicate_candidates(similarity DESC, created_at);
+
+-- Merging or deleting either event drops the pair (cascade)
+CREATE INDEX IF NOT EXISTS idx_duplicate_candidates_duplicate_id ON duplicate_candidates(duplicate_id);
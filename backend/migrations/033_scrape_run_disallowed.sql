@@ -0,0 +1,13 @@
+-- Locate918 Database Schema
+-- Migration 033: URLs robots.txt kept a scrape from fetching
+--
+-- Scrapers now check robots.txt before every request (scraper/robots.rs).
+-- A disallowed URL isn't fetched; each run records how many there were,
+-- so a site that starts shutting us out shows up in the run history.
+
+-- =============================================================================
+-- SCRAPE RUNS TABLE
+-- =============================================================================
+
+ALTER TABLE scrape_runs
+    ADD COLUMN IF NOT EXISTS urls_disallowed INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- Locate918 Database Schema
+-- Migration 034: Pages a scrape couldn't fetch
+--
+-- Scraper requests are now retried when a site fails for a moment
+-- (scraper/fetch.rs). A page that still fails is skipped and the run goes
+-- on; each run keeps the URLs and errors, so a half-broken source shows up
+-- in the run history instead of quietly listing fewer events.
+
+-- =============================================================================
+-- SCRAPE RUNS TABLE
+-- =============================================================================
+
+-- [{ "url": "...", "error": "..." }, ...]
+ALTER TABLE scrape_runs
+    ADD COLUMN IF NOT EXISTS failed_urls JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,15 @@
+-- Locate918 Database Schema
+-- Migration 035: What a dry run would have stored
+--
+-- A dry run (POST /api/admin/scrape with "dry_run": true) stores nothing,
+-- so its counts alone don't say which events it would create or what it
+-- would change. Each dry run now keeps that, event by event
+-- (scraper/persist.rs, ScrapeDiff), for reviewing a new or changed scraper.
+
+-- =============================================================================
+-- SCRAPE RUNS TABLE
+-- =============================================================================
+
+-- { "created": [...], "updated": [...], "duplicates": [...] }; NULL for real runs
+ALTER TABLE scrape_runs
+    ADD COLUMN IF NOT EXISTS diff JSONB;
@@ -0,0 +1,24 @@
+-- Locate918 Database Schema
+-- Migration 036: Scraped events that failed validation
+--
+-- An event a scraper found but we couldn't store (blank title, end before
+-- start, a source URL that isn't one) used to be logged and dropped. It
+-- is now kept here as the scraper reported it, with why, until an admin
+-- fixes and retries it, deletes it, or it ages out (scraper/quarantine.rs).
+
+-- =============================================================================
+-- SCRAPE QUARANTINE TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS scrape_quarantine (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source_name TEXT NOT NULL,      -- the scraper's name(), as on its events
+    raw JSONB NOT NULL,             -- the ScrapedEvent, field for field
+    error TEXT NOT NULL,            -- why it failed (the latest try)
+    page_url TEXT NOT NULL,         -- its source_url as scraped
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The review queue, newest first; the purge, oldest first
+CREATE INDEX IF NOT EXISTS idx_scrape_quarantine_created_at ON scrape_quarantine(created_at);
@@ -0,0 +1,34 @@
+-- Locate918 Database Schema
+-- Migration 037: When a scraper last listed each event
+--
+-- A venue that cancels a show usually just takes it off its site; our
+-- copy lived on. Every scrape now stamps the events it finds, and a
+-- scraped event its source hasn't listed for several good runs in a row
+-- is marked cancelled (see scraper/stale.rs).
+
+-- =============================================================================
+-- EVENTS TABLE
+-- =============================================================================
+
+-- NULL for events no scraper has listed (created through the API)
+ALTER TABLE events
+    ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ;
+
+-- Scraped rows count as just seen, so none is cancelled before its
+-- source has had its runs since this migration
+UPDATE events SET last_seen_at = NOW() WHERE scraped AND last_seen_at IS NULL;
+
+-- Stamping last_seen_at alone isn't an edit: it leaves updated_at (which
+-- the archive job waits on) alone
+DROP TRIGGER IF EXISTS update_events_updated_at ON events;
+CREATE TRIGGER update_events_updated_at
+    BEFORE UPDATE ON events
+    FOR EACH ROW
+    WHEN (OLD.last_seen_at IS NOT DISTINCT FROM NEW.last_seen_at
+          OR (to_jsonb(OLD) - 'last_seen_at') IS DISTINCT FROM (to_jsonb(NEW) - 'last_seen_at'))
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- "Which of this source's upcoming events has it stopped listing?"
+CREATE INDEX IF NOT EXISTS idx_events_scraped_last_seen
+    ON events(source_name, last_seen_at)
+    WHERE scraped;
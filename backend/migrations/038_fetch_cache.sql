@@ -0,0 +1,29 @@
+-- Locate918 Database Schema
+-- Migration 038: Conditional GETs for scraped pages
+--
+-- Most venue pages don't change between runs. For each page scraped into
+-- events we keep its validators (ETag, Last-Modified), a hash of its body
+-- and the events it held. The next run asks with If-None-Match /
+-- If-Modified-Since; a 304, or the same bytes again, skips parsing and
+-- just marks those events seen (see scraper/cache.rs).
+
+-- =============================================================================
+-- FETCH CACHE TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS fetch_cache (
+    url TEXT PRIMARY KEY,
+    etag TEXT,
+    last_modified TEXT,                      -- the header as sent, for If-Modified-Since
+    body_hash TEXT NOT NULL,                 -- SHA-256 of the body, hex
+    event_urls TEXT[] NOT NULL DEFAULT '{}', -- source_url of each event parsed from it
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- =============================================================================
+-- SCRAPE RUNS TABLE
+-- =============================================================================
+
+ALTER TABLE scrape_runs
+    ADD COLUMN IF NOT EXISTS pages_fetched INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS pages_unchanged INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,48 @@
+-- Locate918 Database Schema
+-- Migration 039: Scrape sources as data
+--
+-- Adding an iCal feed or changing how often a scraper runs used to mean
+-- editing ICAL_FEEDS or SCRAPE_INTERVAL_MINUTES_* and restarting. Each
+-- source is now a row: generic kinds (an iCal feed, a page with JSON-LD
+-- events) are built from their row alone; scrapers written in code
+-- (`custom`) get a row so they can be turned off or rescheduled too.
+-- Managed through /api/admin/sources (see scraper/sources.rs).
+
+-- =============================================================================
+-- SOURCE KIND TYPE
+-- =============================================================================
+
+DO $$
+BEGIN
+    CREATE TYPE scrape_source_kind AS ENUM ('ical', 'jsonld', 'eventbrite', 'custom');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+-- =============================================================================
+-- SCRAPE SOURCES TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS scrape_sources (
+    -- The source_id: scrape_runs.source, SCRAPE_INTERVAL_MINUTES_<ID>
+    id TEXT PRIMARY KEY CHECK (id ~ '^[a-z0-9_]+$'),
+    name TEXT NOT NULL,               -- credited as the events' source_name
+    kind scrape_source_kind NOT NULL,
+    url TEXT,                         -- the feed or page; NULL for custom
+    default_category TEXT,
+    default_venue TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    interval_minutes INTEGER CHECK (interval_minutes > 0),  -- NULL: the environment's
+    config JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (kind = 'custom' OR url IS NOT NULL)
+);
+
+DROP TRIGGER IF EXISTS update_scrape_sources_updated_at ON scrape_sources;
+CREATE TRIGGER update_scrape_sources_updated_at
+    BEFORE UPDATE ON scrape_sources
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,26 @@
+-- Locate918 Database Schema
+-- Migration 040: Geocoded addresses
+--
+-- Radius search needs coordinates, but scrapers only see address text.
+-- Addresses are looked up with a geocoder (Nominatim, one request a
+-- second) and every answer is kept here, keyed by the normalized address
+-- (see services/geocoding.rs), so each place is only asked about once:
+--   "423 N. Main St., Tulsa"  -> "423 n main st tulsa"
+--
+-- An address the geocoder doesn't know is kept too (no coordinates) and
+-- asked about again once the answer is old. A geocoder that fails isn't
+-- cached at all: the next backfill tries again.
+
+-- =============================================================================
+-- GEOCODE CACHE TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS geocode_cache (
+    address_key TEXT PRIMARY KEY,
+    query TEXT NOT NULL,                 -- the address as first asked
+    latitude DOUBLE PRECISION,           -- both NULL: not found
+    longitude DOUBLE PRECISION,
+    geocoded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
@@ -0,0 +1,47 @@
+-- Locate918 Database Schema
+-- Migration 041: Per-source category mappings
+--
+-- Every source has its own words for what an event is: Eventbrite says
+-- "Music", the city calendar "Parks & Recreation". A mapping turns one
+-- source's label into one of our categories as its events are stored
+-- (see scraper/categories.rs). Labels with no mapping are counted, so an
+-- admin can see which ones are worth mapping; their events are stored
+-- without a category and left to the classifier (migration 026).
+--
+-- Labels are compared trimmed and lowercased ("Parks & Recreation " =
+-- "parks & recreation").
+--
+-- events.category_source gains a value, 'scraper': categories from the
+-- listing, which a later scrape (after a new mapping, say) may replace.
+-- NULL is now an editor's categories (or older rows'), which scrapes
+-- leave alone; 'llm' is still the classifier's, which a scraped category
+-- replaces.
+
+-- =============================================================================
+-- CATEGORY MAPPINGS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS category_mappings (
+    source TEXT NOT NULL,                -- the source_id (scrape_sources.id)
+    external_category TEXT NOT NULL,     -- the source's label, normalized
+    internal_category TEXT NOT NULL,     -- ours
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (source, external_category)
+);
+
+-- =============================================================================
+-- UNMAPPED CATEGORIES TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS unmapped_categories (
+    source TEXT NOT NULL,
+    external_category TEXT NOT NULL,     -- normalized, as in category_mappings
+    seen_count BIGINT NOT NULL DEFAULT 0, -- events stored with it
+    first_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (source, external_category)
+);
+
+CREATE INDEX IF NOT EXISTS idx_unmapped_categories_last_seen ON unmapped_categories(last_seen_at DESC);
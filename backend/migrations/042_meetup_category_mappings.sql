@@ -0,0 +1,21 @@
+-- Locate918 Database Schema
+-- Migration 042: Meetup's topic categories
+--
+-- The Meetup scraper (scraper/platforms/meetup.rs) reports each event's
+-- group topic category ("Technology", "Community & Environment") and
+-- guesses none of ours, so these mappings are what categorize its events.
+-- Only the obvious ones are here; the rest show up in
+-- GET /api/admin/categories/unmapped and can be added there. An admin's
+-- mapping for the same label is kept.
+
+INSERT INTO category_mappings (source, external_category, internal_category) VALUES
+    ('meetup', 'art & culture', 'art'),
+    ('meetup', 'community & environment', 'community'),
+    ('meetup', 'dancing', 'dance'),
+    ('meetup', 'music', 'music'),
+    ('meetup', 'parents & family', 'family'),
+    ('meetup', 'science & education', 'education'),
+    ('meetup', 'sports & fitness', 'sports'),
+    ('meetup', 'technology', 'education'),
+    ('meetup', 'travel & outdoor', 'outdoors')
+ON CONFLICT (source, external_category) DO NOTHING;
@@ -0,0 +1,32 @@
+-- Locate918 Database Schema
+-- Migration 043: Every place an event is listed
+--
+-- The same show is often listed on the venue's site and on Eventbrite,
+-- and we must credit every listing, not just the one we link to.
+-- event_sources (migration 012) held only the URLs a merge absorbed; it
+-- now holds every listing of an event, its own source_url included:
+-- written as scrapers find them, unioned by merges (see
+-- services/attribution.rs).
+--
+-- events.source_url stays the "primary" listing, the one we link to: the
+-- venue's own site before other listings, and those before aggregators
+-- (Eventbrite, Meetup, ...).
+
+-- =============================================================================
+-- EVENT SOURCES TABLE
+-- =============================================================================
+
+ALTER TABLE event_sources RENAME COLUMN created_at TO first_seen_at;
+
+ALTER TABLE event_sources
+    ADD COLUMN IF NOT EXISTS external_id TEXT,      -- the source's own id for it (a Meetup event id, an iCal UID)
+    ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- A merged-in listing was last seen when it was merged
+UPDATE event_sources SET last_seen_at = first_seen_at WHERE merged_from IS NOT NULL;
+
+-- Every event's own listing
+INSERT INTO event_sources (event_id, source_url, source_name, first_seen_at, last_seen_at)
+SELECT id, source_url, source_name, created_at, COALESCE(last_seen_at, created_at)
+FROM events
+ON CONFLICT (event_id, source_url) DO NOTHING;
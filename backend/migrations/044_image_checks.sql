@@ -0,0 +1,18 @@
+-- Locate918 Database Schema
+-- Migration 044: Checked event images
+--
+-- Scrapers find an image for most events, and some of them are broken
+-- links or not images at all. Before a run stores its events, each image
+-- is asked for with a HEAD; the answer is kept here so the same image
+-- isn't asked about again every run (see scraper/images.rs).
+
+-- =============================================================================
+-- IMAGE CHECKS TABLE
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS image_checks (
+    url TEXT PRIMARY KEY,           -- normalized, as stored in events.image_url
+    ok BOOLEAN NOT NULL,            -- answered 2xx with an image/* content type
+    content_type TEXT,              -- as answered, if it was
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
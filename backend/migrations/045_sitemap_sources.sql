@@ -0,0 +1,13 @@
+-- Locate918 Database Schema
+-- Migration 045: Sitemap sources
+--
+-- A site with a page per event and no listing worth scraping is read
+-- through its sitemap: a `sitemap` source finds the pages whose path
+-- matches its config's `pattern` and reads each as JSON-LD or Open Graph
+-- (see scraper/platforms/sitemap.rs).
+
+-- =============================================================================
+-- SOURCE KIND
+-- =============================================================================
+
+ALTER TYPE scrape_source_kind ADD VALUE IF NOT EXISTS 'sitemap';
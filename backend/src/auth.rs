@@ -0,0 +1,419 @@
+//! # Authentication
+//!
+//! Signed session tokens for the user-scoped endpoints. Without them,
+//! anyone who knows a user's UUID could read their history or record
+//! interactions in their name.
+//!
+//! ## Flow
+//! 1. `POST /api/auth/register` or `POST /api/auth/login` returns a token
+//! 2. The client sends it on every user-scoped request:
+//!    `Authorization: Bearer <token>`
+//! 3. `require_path_user` checks the token and that its user is the `:id`
+//!    in the path
+//!
+//! Tokens are HS256 JWTs carrying the user id (`sub`) and an expiry
+//! (`exp`). Nothing is stored server-side; a token is valid until it
+//! expires or `JWT_SECRET` changes.
+//!
+//! ## Passwords
+//! Stored as Argon2id PHC strings in `users.password_hash`. Hashing and
+//! verifying run on the blocking pool (they take tens of milliseconds by
+//! design), and `argon2`'s verify compares in constant time. Accounts with
+//! no password can't log in with one; they sign in with Google.
+//!
+//! ## Environment Variables
+//! ```text
+//! JWT_SECRET=...          # signing key (required; auth answers 503 without it)
+//! JWT_TTL_HOURS=168       # how long a token stays valid
+//! ADMIN_API_KEY=...       # X-Admin-Key for admin routes (503 without it)
+//! ```
+//!
+//! ## Admin Key
+//! Event writes and `/api/admin/*` are for scrapers and operators, not
+//! users. `require_admin_key` checks an `X-Admin-Key` header against
+//! `ADMIN_API_KEY` in constant time. With no `ADMIN_API_KEY` set, those
+//! routes answer 503 instead of being open.
+//!
+//! ## Public Routes
+//! Events, venues, account creation and the `saved.ics` feed (calendar
+//! apps can't send headers; it has its own `calendar_token`) need no token.
+//! Event listing and search take one optionally (`MaybeAuthUser`) to hide
+//! events the user dismissed.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+
+use std::collections::HashMap;
+
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path, Request},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{FieldError, Password};
+use crate::services::scheduler;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default hours a token stays valid (one week).
+pub const DEFAULT_TOKEN_TTL_HOURS: u64 = 24 * 7;
+
+/// Shortest password accepted.
+pub const MIN_PASSWORD_CHARS: usize = 8;
+
+/// Header carrying the admin key.
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// Signing key from `JWT_SECRET`.
+///
+/// Fails closed: without a secret no token can be issued or accepted.
+pub(crate) fn secret() -> Result<Vec<u8>, AppError> {
+    match std::env::var("JWT_SECRET") {
+        Ok(secret) if !secret.is_empty() => Ok(secret.into_bytes()),
+        _ => {
+            tracing::error!("JWT_SECRET is not set; rejecting authenticated requests");
+            Err(AppError::Unavailable("authentication is not configured".to_string()))
+        }
+    }
+}
+
+// =============================================================================
+// TOKENS
+// =============================================================================
+
+/// What a token says about its bearer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    /// User id
+    pub sub: Uuid,
+    /// Issued at (unix seconds)
+    pub iat: i64,
+    /// Expires at (unix seconds)
+    pub exp: i64,
+}
+
+/// Signs a token for `user_id`, valid for `ttl` from `now`.
+/// Returns the token and its expiry.
+pub fn sign_token(user_id: Uuid, secret: &[u8], now: DateTime<Utc>, ttl: Duration) -> (String, DateTime<Utc>) {
+    let expires_at = now + ttl;
+    let claims = Claims {
+        sub: user_id,
+        iat: now.timestamp(),
+        exp: expires_at.timestamp(),
+    };
+
+    // HS256 over a plain struct can't fail
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret))
+        .expect("HS256 signing is infallible");
+
+    (token, expires_at)
+}
+
+/// Checks a token's signature and expiry and returns its user id.
+pub fn verify_token(token: &str, secret: &[u8]) -> Result<Uuid, AppError> {
+    decode::<Claims>(token, &DecodingKey::from_secret(secret), &Validation::default())
+        .map(|data| data.claims.sub)
+        .map_err(|_| AppError::Unauthorized("invalid or expired token".to_string()))
+}
+
+/// Issues a session token for `user_id` using the environment configuration.
+pub fn issue_token(user_id: Uuid) -> Result<(String, DateTime<Utc>), AppError> {
+    let hours = scheduler::env_u64("JWT_TTL_HOURS", DEFAULT_TOKEN_TTL_HOURS);
+    Ok(sign_token(user_id, &secret()?, Utc::now(), Duration::hours(hours as i64)))
+}
+
+// =============================================================================
+// PASSWORDS
+// =============================================================================
+
+/// Checks a new password before it is hashed. `field` names it in the 422.
+pub fn validate_password(field: &str, password: &Password) -> Result<(), Vec<FieldError>> {
+    if password.0.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(vec![FieldError::new(
+            field,
+            format!("must be at least {} characters", MIN_PASSWORD_CHARS),
+        )]);
+    }
+    Ok(())
+}
+
+/// Hashes a password with Argon2id and a random salt.
+pub async fn hash_password(password: Password) -> String {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.0.as_bytes(), &salt)
+            .expect("argon2 hashing with default parameters")
+            .to_string()
+    })
+        .await
+        .expect("password hashing task panicked")
+}
+
+/// True if `password` matches the stored `hash`.
+///
+/// A hash that doesn't parse is logged and treated as a mismatch.
+pub async fn verify_password(password: Password, hash: String) -> bool {
+    tokio::task::spawn_blocking(move || match PasswordHash::new(&hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.0.as_bytes(), &parsed)
+            .is_ok(),
+        Err(e) => {
+            tracing::error!(error = %e, "stored password hash is malformed");
+            false
+        }
+    })
+        .await
+        .expect("password verification task panicked")
+}
+
+// =============================================================================
+// EXTRACTOR
+// =============================================================================
+
+/// The user a request's bearer token belongs to.
+///
+/// Add it to a handler's arguments to require a valid token:
+/// ```rust
+/// async fn handler(AuthUser(user_id): AuthUser) { ... }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AuthUser(pub Uuid);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::Unauthorized("missing bearer token".to_string()))?;
+
+        verify_token(token.trim(), &secret()?).map(AuthUser)
+    }
+}
+
+/// The signed-in user on routes that also work anonymously.
+///
+/// `None` without an `Authorization` header; a header with a bad or
+/// expired token is still a 401, so a client never silently gets
+/// anonymous results.
+#[derive(Debug, Clone, Copy)]
+pub struct MaybeAuthUser(pub Option<Uuid>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for MaybeAuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(header::AUTHORIZATION) {
+            return Ok(MaybeAuthUser(None));
+        }
+
+        let AuthUser(user_id) = AuthUser::from_request_parts(parts, state).await?;
+        Ok(MaybeAuthUser(Some(user_id)))
+    }
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+
+/// Route layer for `/users/:id/...`: the token's user must be `:id`.
+///
+/// # Returns
+/// - `401 Unauthorized` for a missing, malformed or expired token
+/// - `403 Forbidden` for a valid token belonging to another user
+pub async fn require_path_user(
+    Path(params): Path<HashMap<String, String>>,
+    AuthUser(user_id): AuthUser,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let owns_path = params
+        .get("id")
+        .and_then(|id| id.parse::<Uuid>().ok())
+        .is_some_and(|id| id == user_id);
+
+    if !owns_path {
+        return Err(AppError::Forbidden("token does not grant access to this user".to_string()));
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Route layer for admin-only routes: `X-Admin-Key` must equal
+/// `ADMIN_API_KEY`.
+///
+/// # Returns
+/// - `401 Unauthorized` for a missing or wrong key
+/// - `503 Service Unavailable` if `ADMIN_API_KEY` isn't set
+pub async fn require_admin_key(headers: HeaderMap, request: Request, next: Next) -> Result<Response, AppError> {
+    let expected = std::env::var("ADMIN_API_KEY").ok();
+    let provided = headers.get(ADMIN_KEY_HEADER).and_then(|value| value.to_str().ok());
+
+    check_admin_key(expected.as_deref(), provided)?;
+
+    Ok(next.run(request).await)
+}
+
+/// Compares a provided admin key with the configured one.
+///
+/// Fails closed when no key is configured. The comparison takes the same
+/// time wherever the keys differ, so the key can't be guessed byte by byte.
+pub fn check_admin_key(expected: Option<&str>, provided: Option<&str>) -> Result<(), AppError> {
+    let expected = match expected {
+        Some(key) if !key.is_empty() => key,
+        _ => {
+            tracing::error!("ADMIN_API_KEY is not set; rejecting admin requests");
+            return Err(AppError::Unavailable("admin access is not configured".to_string()));
+        }
+    };
+
+    let provided = provided.ok_or_else(|| AppError::Unauthorized("missing X-Admin-Key header".to_string()))?;
+
+    if bool::from(provided.as_bytes().ct_eq(expected.as_bytes())) {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized("invalid admin key".to_string()))
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    const TEST_SECRET: &str = "test-secret";
+
+    /// `/users/:id` behind `require_path_user`, like the real users router.
+    fn app() -> Router {
+        std::env::set_var("JWT_SECRET", TEST_SECRET);
+        Router::new()
+            .route("/users/:id", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn(require_path_user))
+    }
+
+    async fn status_for(path_user: Uuid, token: Option<String>) -> StatusCode {
+        let mut request = Request::get(format!("/users/{}", path_user));
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    fn token_for(user_id: Uuid, issued: DateTime<Utc>) -> String {
+        sign_token(user_id, TEST_SECRET.as_bytes(), issued, Duration::hours(1)).0
+    }
+
+    #[tokio::test]
+    async fn verifies_only_the_right_password() {
+        let hash = hash_password(Password("correct horse".to_string())).await;
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(!hash.contains("correct horse"));
+        assert!(verify_password(Password("correct horse".to_string()), hash.clone()).await);
+        assert!(!verify_password(Password("battery staple".to_string()), hash).await);
+        assert!(!verify_password(Password("correct horse".to_string()), "not a hash".to_string()).await);
+    }
+
+    #[test]
+    fn short_passwords_are_rejected() {
+        assert!(validate_password("password", &Password("1234567".to_string())).is_err());
+        assert!(validate_password("password", &Password("12345678".to_string())).is_ok());
+    }
+
+    #[tokio::test]
+    async fn accepts_the_owner() {
+        let user = Uuid::new_v4();
+        assert_eq!(status_for(user, Some(token_for(user, Utc::now()))).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_missing_token() {
+        assert_eq!(status_for(Uuid::new_v4(), None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_an_expired_token() {
+        let user = Uuid::new_v4();
+        let yesterday = Utc::now() - Duration::days(1);
+
+        assert_eq!(status_for(user, Some(token_for(user, yesterday))).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_token_signed_with_another_secret() {
+        let user = Uuid::new_v4();
+        let (forged, _) = sign_token(user, b"guessed", Utc::now(), Duration::hours(1));
+
+        assert_eq!(status_for(user, Some(forged)).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn admin_key_must_match() {
+        assert!(check_admin_key(Some("s3cret"), Some("s3cret")).is_ok());
+
+        let wrong = check_admin_key(Some("s3cret"), Some("s3creT")).unwrap_err();
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+
+        let missing = check_admin_key(Some("s3cret"), None).unwrap_err();
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn admin_routes_fail_closed_without_a_key() {
+        for configured in [None, Some("")] {
+            let error = check_admin_key(configured, Some("")).unwrap_err();
+            assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_cross_user_access() {
+        let me = Uuid::new_v4();
+        let someone_else = Uuid::new_v4();
+
+        assert_eq!(status_for(someone_else, Some(token_for(me, Utc::now()))).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn optional_auth_allows_anonymous_but_not_bad_tokens() {
+        std::env::set_var("JWT_SECRET", TEST_SECRET);
+        let user_id = Uuid::new_v4();
+        let extract = |authorization: Option<String>| async move {
+            let mut request = Request::get("/events");
+            if let Some(value) = authorization {
+                request = request.header(header::AUTHORIZATION, value);
+            }
+            let (mut parts, _) = request.body(Body::empty()).unwrap().into_parts();
+            MaybeAuthUser::from_request_parts(&mut parts, &()).await.map(|MaybeAuthUser(user)| user)
+        };
+
+        assert_eq!(extract(None).await.unwrap(), None);
+        let token = token_for(user_id, Utc::now());
+        assert_eq!(extract(Some(format!("Bearer {}", token))).await.unwrap(), Some(user_id));
+        assert!(matches!(extract(Some("Bearer junk".to_string())).await, Err(AppError::Unauthorized(_))));
+    }
+}
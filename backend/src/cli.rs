@@ -0,0 +1,277 @@
+//! # Command Line
+//!
+//! The backend binary's subcommands, so scrapes, backfills and seeding can
+//! be run from a shell (or cron, or CI) without curling the admin API.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Commands
+//! ```text
+//! locate918-backend [serve]                     # the API server (the default)
+//! locate918-backend scrape [--source ID] [--dry-run [--json]]
+//! locate918-backend backfill-geocode [--limit N]
+//! locate918-backend classify-categories
+//! locate918-backend seed
+//! ```
+//!
+//! Each connects and migrates like the server (`DATABASE_URL`, `.env`) and
+//! does what its admin endpoint does, through the same code: a scrape is a
+//! `scrape_batches` batch started by `ScrapeRunner` (see `scraper/runs.rs`),
+//! so it shows up in the run history like any other. What it did is
+//! printed as JSON, as the endpoint would answer; a dry run prints a
+//! status line and a diff table per source instead (`persist::ScrapeDiff`),
+//! or the JSON with `--json`.
+//!
+//! ## Exit Codes
+//! - `0` if everything worked
+//! - `1` if anything failed: any scraper in the run, the geocoder or model
+//!   being off or down, the database
+//! - `2` for arguments it doesn't understand
+//!
+//! Events a command-line scrape creates aren't geocoded before the process
+//! exits; `backfill-geocode` picks them up. The one-run-per-source lock is
+//! per process, so a command-line scrape doesn't wait for the server's.
+
+use std::error::Error;
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::scraper::registry::ScraperRegistry;
+use crate::scraper::runs::{self, ScrapeRun, ScrapeRunStatus, ScrapeRunner};
+use crate::services::{classification, geocoding, llm_provider, rate_limit};
+
+// =============================================================================
+// ARGUMENTS
+// =============================================================================
+
+/// Locate918 backend: the API server, and maintenance from the shell.
+#[derive(Debug, Parser)]
+#[command(name = "locate918-backend")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Run the API server (the default)
+    Serve,
+    /// Scrape every enabled source, or one
+    Scrape {
+        /// Only this source (its source id, e.g. cains_ballroom)
+        #[arg(long)]
+        source: Option<String>,
+        /// Report what would be stored without storing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Print a dry run's batch as JSON instead of tables
+        #[arg(long, requires = "dry_run")]
+        json: bool,
+    },
+    /// Locate venues and events without coordinates
+    BackfillGeocode {
+        /// Most addresses to send to the geocoder; cached answers don't count
+        #[arg(long, default_value_t = geocoding::DEFAULT_BACKFILL_LOOKUPS)]
+        limit: usize,
+    },
+    /// Categorize uncategorized events with the model
+    ClassifyCategories,
+    /// Add the scrapers in code and ICAL_FEEDS to scrape_sources
+    Seed,
+}
+
+// =============================================================================
+// COMMANDS
+// =============================================================================
+
+/// Runs one of the maintenance commands (everything but `serve`) with the
+/// scrapers in `registry`, writing what it did to `out` (stdout, from
+/// `main`).
+///
+/// # Errors
+/// A database error, or the geocoder or model being off or unavailable.
+pub async fn run(
+    command: Command,
+    pool: &PgPool,
+    registry: ScraperRegistry,
+    out: &mut dyn Write,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let scrapers = Arc::new(ScrapeRunner::new(registry));
+    match command {
+        Command::Serve => unreachable!("main runs the server itself"),
+        Command::Scrape { source, dry_run, json } => {
+            scrape(pool, &scrapers, source.as_deref(), dry_run, json, out).await
+        }
+        Command::BackfillGeocode { limit } => {
+            let geocoder = scrapers.geocoder().ok_or("geocoding is off (GEOCODER)")?;
+            print(out, &geocoding::backfill(pool, geocoder.as_ref(), limit).await?)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::ClassifyCategories => {
+            let llm = rate_limit::with_daily_budget(llm_provider::from_env()?);
+            print(out, &classification::classify_uncategorized(pool, &llm).await?)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Seed => {
+            let sources = scrapers.reload(pool).await?;
+            print(out, &sources)?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+/// Scrapes `source` (or every enabled source) as one batch and waits for
+/// it; fails if any of its runs did.
+async fn scrape(
+    pool: &PgPool,
+    scrapers: &Arc<ScrapeRunner>,
+    source: Option<&str>,
+    dry_run: bool,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<ExitCode, Box<dyn Error>> {
+    scrapers.reload(pool).await?;
+    let (batch, task) = scrapers.start(pool, source, dry_run).await?;
+    task.await?;
+
+    let batch = runs::find_batch(pool, batch.id).await?.ok_or("the scrape batch disappeared")?;
+    if dry_run && !json {
+        for run in &batch.runs {
+            writeln!(out, "{}", status_line(run))?;
+            if let Some(diff) = &run.diff {
+                writeln!(out, "{}", diff.0)?;
+            }
+        }
+    } else {
+        print(out, &batch)?;
+    }
+    Ok(match batch.status {
+        ScrapeRunStatus::Completed => ExitCode::SUCCESS,
+        ScrapeRunStatus::Running | ScrapeRunStatus::Failed => ExitCode::FAILURE,
+    })
+}
+
+/// One run of a dry run, above its diff table:
+/// `cains_ballroom: completed, 24 found, 3 to create, 1 to update, 0 skipped`.
+fn status_line(run: &ScrapeRun) -> String {
+    let status = match run.status {
+        ScrapeRunStatus::Running => "running",
+        ScrapeRunStatus::Completed => "completed",
+        ScrapeRunStatus::Failed => "failed",
+    };
+    let mut line = format!(
+        "{}: {}, {} found, {} to create, {} to update, {} skipped",
+        run.source, status, run.events_found, run.events_created, run.events_updated, run.events_skipped
+    );
+    if let Some(error) = &run.error_message {
+        line.push_str(&format!(" ({})", error));
+    }
+    line
+}
+
+/// Writes `value` to `out` as pretty JSON.
+fn print(out: &mut dyn Write, value: &impl Serialize) -> Result<(), Box<dyn Error>> {
+    writeln!(out, "{}", serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use crate::scraper::fetch::FetchPool;
+    use crate::scraper::fixture::FixtureScraper;
+    use crate::scraper::traits::ScrapedEvent;
+
+    fn parse(args: &[&str]) -> Option<Command> {
+        Cli::try_parse_from(std::iter::once("locate918-backend").chain(args.iter().copied())).unwrap().command
+    }
+
+    #[test]
+    fn subcommands_and_their_flags() {
+        assert_eq!(parse(&[]), None);
+        assert_eq!(parse(&["serve"]), Some(Command::Serve));
+        assert_eq!(
+            parse(&["scrape", "--source", "cains_ballroom", "--dry-run"]),
+            Some(Command::Scrape { source: Some("cains_ballroom".to_string()), dry_run: true, json: false })
+        );
+        assert_eq!(
+            parse(&["scrape", "--dry-run", "--json"]),
+            Some(Command::Scrape { source: None, dry_run: true, json: true })
+        );
+        // Only a dry run prints tables to begin with
+        assert!(Cli::try_parse_from(["locate918-backend", "scrape", "--json"]).is_err());
+        assert_eq!(parse(&["backfill-geocode"]), Some(Command::BackfillGeocode { limit: geocoding::DEFAULT_BACKFILL_LOOKUPS }));
+        assert_eq!(parse(&["classify-categories"]), Some(Command::ClassifyCategories));
+        assert!(Cli::try_parse_from(["locate918-backend", "scrape", "--everything"]).is_err());
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn scrape_exits_nonzero_when_a_scraper_fails() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let id = Uuid::new_v4().simple().to_string();
+        let (works, fails) = (format!("cli_{}", &id[..12]), format!("cli_failing_{}", &id[..12]));
+        let source_url = format!("https://cli.example/{}", id);
+        let event = ScrapedEvent::new(&format!("Command Line Jam {}", id), &source_url, Utc::now() + Duration::days(3));
+        let registry = || {
+            ScraperRegistry::new(FetchPool::for_tests())
+                .register(FixtureScraper::new(&works, vec![event.clone()]))
+                .register(FixtureScraper::failing(&fails))
+        };
+
+        let scrape = |source: &str| parse(&["scrape", "--source", source]).unwrap();
+
+        // A dry run prints its diff as a table and stores nothing
+        let mut stdout = Vec::new();
+        let dry_run = Command::Scrape { source: Some(works.clone()), dry_run: true, json: false };
+        assert_eq!(run(dry_run, &pool, registry(), &mut stdout).await.unwrap(), ExitCode::SUCCESS);
+        let stdout = String::from_utf8(stdout).unwrap();
+        assert!(stdout.starts_with(&format!("{}: completed, 1 found, 1 to create", works)), "{}", stdout);
+        assert!(stdout.contains("ACTION"), "{}", stdout);
+        assert!(stdout.contains(&format!("create     {}", event.start_time.format("%Y-%m-%d %H:%M"))), "{}", stdout);
+        assert!(!stdout.trim_start().starts_with('{'));
+
+        assert_eq!(run(scrape(&works), &pool, registry(), &mut std::io::sink()).await.unwrap(), ExitCode::SUCCESS);
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE source_url = $1")
+            .bind(&source_url)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 1);
+
+        assert_eq!(run(scrape(&fails), &pool, registry(), &mut std::io::sink()).await.unwrap(), ExitCode::FAILURE);
+        // A source nobody has is an error, so a failure too
+        assert!(run(scrape("no_such_source"), &pool, registry(), &mut std::io::sink()).await.is_err());
+
+        sqlx::query("DELETE FROM events WHERE source_url = $1")
+            .bind(&source_url)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM scrape_sources WHERE id = $1 OR id = $2")
+            .bind(&works)
+            .bind(&fails)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -1,20 +1,29 @@
 //! # Database Utilities
 //!
-//! This module contains database helper functions and utilities.
-//! Currently a placeholder for future enhancements.
+//! This module contains database helper functions and utilities,
+//! including SQL fragments shared between route handlers and services.
+//!
+//! ## Current Contents
+//! - `EVENT_COLUMNS` - The one column list for selecting `Event` rows
+//! - `USER_COLUMNS` - Column list for `User` rows
+//! - `PREFERENCE_COLUMNS` - Column list for `UserPreference` rows
+//! - `SAVED_SEARCH_COLUMNS` - Column list for `SavedSearch` rows
+//! - `NOTIFICATION_COLUMNS` - Column list for `Notification` rows
+//! - `FOLLOW_COLUMNS` / `FOLLOWS_FROM` - Select list and source for `UserFollow` rows
+//! - `UPCOMING_FILTER` - Shared "hasn't ended yet" condition for events
+//! - `NOT_CANCELLED_FILTER` - Hides cancelled events
+//! - `NOT_ARCHIVED_FILTER` - Hides soft-archived events
+//! - `not_dismissed_by` - Hides events a user dismissed (and didn't save since)
+//! - `Pagination` - Classic page/per_page (LIMIT/OFFSET) parameters
+//! - `Cursor` - Keyset pagination on `(start_time, id)`
+//! - `health_check` / `migrations_check` - Readiness probes (`GET /api/ready`)
 //!
 //! ## Potential Future Contents
 //!
 //! ### Connection Helpers
 //! - Custom connection pool configuration
-//! - Connection health checks
 //! - Retry logic for transient failures
 //!
-//! ### Query Builders
-//! - Dynamic query construction for complex filters
-//! - Pagination helpers (LIMIT/OFFSET or cursor-based)
-//! - Sorting utilities
-//!
 //! ### Transaction Helpers
 //! - Multi-step operations that need atomicity
 //! - Rollback handling
@@ -22,35 +31,381 @@
 //! ## Owner
 //! Will (Coordinator/Backend Lead)
 //!
-//! ## Example Future Implementation
-//! ```rust
-//! use sqlx::PgPool;
-//!
-//! /// Check if the database connection is healthy
-//! pub async fn health_check(pool: &PgPool) -> bool {
-//!     sqlx::query("SELECT 1")
-//!         .execute(pool)
-//!         .await
-//!         .is_ok()
-//! }
-//!
-//! /// Pagination parameters
-//! pub struct Pagination {
-//!     pub page: u32,
-//!     pub per_page: u32,
-//! }
-//!
-//! impl Pagination {
-//!     pub fn offset(&self) -> u32 {
-//!         (self.page - 1) * self.per_page
-//!     }
-//! }
+//! ## Why Cursor Pagination?
+//! Scrapers insert events continuously. With LIMIT/OFFSET, a row inserted
+//! before the current offset shifts every later page by one, so clients see
+//! duplicates or miss events. A cursor remembers the last `(start_time, id)`
+//! the client saw and resumes strictly after it, which is stable under
+//! concurrent inserts and uses the start_time index instead of scanning
+//! and discarding OFFSET rows.
+//!
+//! ```text
+//! GET /api/events                      -> { events: [...], next_cursor: "AAYx..." }
+//! GET /api/events?cursor=AAYx...       -> { events: [...], next_cursor: null }
 //! ```
 
-// Database utilities will go here
-//
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::PgPool;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+// =============================================================================
+// SHARED QUERY FRAGMENTS
+// =============================================================================
+
+/// All columns to select from the events table (matches the `Event` struct).
+///
+/// Use this in every `query_as::<_, Event>` instead of spelling the columns
+/// out, so adding a field to `Event` means changing one place:
+/// ```rust
+/// let sql = format!("SELECT {} FROM events WHERE id = $1", EVENT_COLUMNS);
+/// ```
+///
+/// `tags` lives in its own table and is aggregated per row, so queries using
+/// this list must select `FROM events` without a table alias.
+pub const EVENT_COLUMNS: &str = "id, title, description, venue, venue_id, venue_address, location, \
+    source_url, source_name, start_time, end_time, categories, \
+    ARRAY(SELECT tag FROM event_tags WHERE event_tags.event_id = events.id ORDER BY tag) AS tags, \
+    price_min, price_max, is_free, outdoor, family_friendly, image_url, status, \
+    latitude, longitude, archived_at, created_at, updated_at";
+
+/// All columns to select from the venues table (matches the `Venue` struct).
+///
+/// `normalized_name` is generated by the database and only used for matching.
+pub const VENUE_COLUMNS: &str = "id, name, address, city, capacity, venue_type, noise_level, \
+    parking_info, accessibility_info, website, latitude, longitude, created_at, updated_at";
+
+/// All columns to select from the users table (matches the `User` struct).
+/// `password_hash` is left out on purpose so it can't end up in a response.
+pub const USER_COLUMNS: &str = "id, email, name, location_preference, radius_miles, home_latitude, \
+    home_longitude, preferred_radius_km, price_max, family_friendly_only, is_guest, email_verified_at, calendar_token, created_at, updated_at";
+
+/// All columns to select from the user_preferences table (matches the
+/// `UserPreference` struct).
+pub const PREFERENCE_COLUMNS: &str = "id, user_id, category, weight, source, created_at";
+
+/// All columns to select from the saved_searches table (matches the
+/// `SavedSearch` struct).
+pub const SAVED_SEARCH_COLUMNS: &str = "id, user_id, q, category, location, created_at, last_notified_at";
+
+/// All columns to select from the notifications table (matches the
+/// `Notification` struct).
+pub const NOTIFICATION_COLUMNS: &str = "id, user_id, kind, event_id, title, body, read_at, created_at";
+
+/// Columns for `UserFollow` rows, selected from `FOLLOWS_FROM` (the venue
+/// join supplies `target_name`).
+pub const FOLLOW_COLUMNS: &str = "f.id, f.user_id, f.target_type, f.target_value, \
+    COALESCE(v.name, f.target_value) AS target_name, f.created_at";
+
+/// `FROM` clause for `FOLLOW_COLUMNS`.
+pub const FOLLOWS_FROM: &str =
+    "user_follows f LEFT JOIN venues v ON f.target_type = 'venue' AND v.id::text = f.target_value";
+
+/// SQL condition matching events that haven't finished yet.
+///
+/// An event counts as upcoming until its `end_time` passes, so something that
+/// started an hour ago and runs until tonight is still included. Events with
+/// no `end_time` fall back to their `start_time`.
+pub const UPCOMING_FILTER: &str = "COALESCE(end_time, start_time) >= NOW()";
+
+/// SQL condition hiding cancelled events.
+///
+/// Postponed and sold-out events stay visible so users can see the status.
+pub const NOT_CANCELLED_FILTER: &str = "status <> 'cancelled'";
+
+/// SQL condition hiding soft-archived events (see `services::archive`).
+///
+/// Archived events are long over, so any query that already applies
+/// `UPCOMING_FILTER` excludes them anyway; this one matters when past
+/// events are requested.
+pub const NOT_ARCHIVED_FILTER: &str = "archived_at IS NULL";
+
+/// SQL condition hiding events the user dismissed, unless they saved the
+/// event after their latest dismiss ("not interested" is undone by a save).
+///
+/// `user` is a SQL expression for the user's id: a bind parameter (`"$2"`)
+/// or a quoted UUID literal. `dismiss` and `save` are text arrays of every
+/// spelling of those interactions: bind parameters holding
+/// `analytics::spellings_of("dismiss")` and `("save")`, or in a query built
+/// without parameters, `analytics::spellings_sql`. Expects the events table
+/// as `events`.
+pub fn not_dismissed_by(user: &str, dismiss: &str, save: &str) -> String {
+    format!(
+        "NOT EXISTS (SELECT 1 FROM user_interactions d \
+         WHERE d.user_id = {} AND d.event_id = events.id \
+         AND d.interaction_type = ANY({}) \
+         AND NOT EXISTS (SELECT 1 FROM user_interactions s \
+         WHERE s.user_id = d.user_id AND s.event_id = d.event_id \
+         AND s.interaction_type = ANY({}) AND s.created_at > d.created_at))",
+        user, dismiss, save
+    )
+}
+
+/// ORDER BY clause that matches the `Cursor` key.
+///
+/// `id` breaks ties between events sharing a `start_time`, so the ordering
+/// is total and a cursor always identifies a single position.
+pub const KEYSET_ORDER: &str = "ORDER BY start_time ASC, id ASC";
+
+// =============================================================================
+// OFFSET PAGINATION
+// =============================================================================
+
+/// Default number of rows per page.
+pub const DEFAULT_PER_PAGE: u32 = 100;
+
+/// Largest page a client may request.
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// Classic page-number pagination parameters.
+///
+/// Pages are 1-indexed. `per_page` is clamped to `1..=MAX_PER_PAGE`.
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl Pagination {
+    /// Builds pagination from optional query parameters, applying defaults.
+    pub fn new(page: Option<u32>, per_page: Option<u32>) -> Self {
+        Self {
+            page: page.unwrap_or(1).max(1),
+            per_page: per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE),
+        }
+    }
+
+    /// Number of rows to skip for the current page.
+    pub fn offset(&self) -> i64 {
+        (self.page as i64 - 1) * self.per_page as i64
+    }
+}
+
+// =============================================================================
+// CURSOR (KEYSET) PAGINATION
+// =============================================================================
+
+/// Position in the `(start_time, id)` ordering of events.
+///
+/// The interaction history reuses it for its `(created_at, id)` ordering;
+/// the encoding only cares that it's a timestamp and a UUID.
+///
+/// Field order matters: the derived `Ord` compares `start_time` first and
+/// `id` second, matching `KEYSET_ORDER` and Postgres row comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    pub start_time: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    /// Encodes the cursor as an opaque, URL-safe string.
+    ///
+    /// Layout before base64: 8 bytes of big-endian microseconds since the
+    /// Unix epoch (Postgres TIMESTAMPTZ precision) followed by the 16 UUID bytes.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(24);
+        bytes.extend_from_slice(&self.start_time.timestamp_micros().to_be_bytes());
+        bytes.extend_from_slice(self.id.as_bytes());
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decodes a cursor produced by `encode`.
+    ///
+    /// Returns `None` for anything malformed; handlers turn that into a 400.
+    pub fn decode(value: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+        if bytes.len() != 24 {
+            return None;
+        }
+
+        let micros = i64::from_be_bytes(bytes[..8].try_into().ok()?);
+        let start_time = DateTime::<Utc>::from_timestamp_micros(micros)?;
+        let id = Uuid::from_slice(&bytes[8..]).ok()?;
+
+        Some(Self { start_time, id })
+    }
+
+    /// SQL condition selecting rows strictly after this cursor.
+    ///
+    /// `first_param` is the placeholder number to use for the timestamp;
+    /// the id is bound as the next one. Bind `start_time` then `id`.
+    pub fn after_condition(first_param: usize) -> String {
+        format!("(start_time, id) > (${}, ${})", first_param, first_param + 1)
+    }
+}
+
+/// Trims an over-fetched page and returns the cursor for the next one.
+///
+/// Callers fetch `limit + 1` rows; if the extra row came back there is
+/// another page, so it's dropped and the last kept row becomes the cursor.
+pub fn take_page<T>(rows: &mut Vec<T>, limit: usize, key: impl Fn(&T) -> Cursor) -> Option<String> {
+    if rows.len() <= limit {
+        return None;
+    }
+
+    rows.truncate(limit);
+    rows.last().map(|row| key(row).encode())
+}
+
+// =============================================================================
+// HEALTH CHECKS
+// =============================================================================
+
+/// The migrations this build expects, embedded at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Longest a health check may take before it counts as failed.
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One database check's result.
+///
+/// # Example JSON
+/// ```json
+/// { "ok": false, "latency_ms": 2000, "error": "no answer within 2s (database down, or every connection busy)" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DbHealth {
+    pub ok: bool,
+    /// How long the check took (until the timeout, if it hung)
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Whether the database answers `SELECT 1` within `HEALTH_CHECK_TIMEOUT`.
+///
+/// A pool with every connection busy can't lend one in time, so it fails
+/// too; as the pool reconnects on its own, the check passes again once the
+/// database is back, without a restart.
+pub async fn health_check(pool: &PgPool) -> DbHealth {
+    timed(async {
+        sqlx::query("SELECT 1").execute(pool).await?;
+        Ok::<_, sqlx::Error>(())
+    })
+    .await
+}
+
+/// Whether every migration in `MIGRATOR` has been applied: code newer than
+/// its schema shouldn't take traffic.
+pub async fn migrations_check(pool: &PgPool) -> DbHealth {
+    timed(async {
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let applied: HashSet<i64> = applied.into_iter().collect();
+        let pending: Vec<i64> = MIGRATOR
+            .iter()
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect();
+        match pending.first() {
+            Some(first) => Err(format!("{} migration(s) not applied, from {:03}", pending.len(), first)),
+            None => Ok(()),
+        }
+    })
+    .await
+}
+
+/// Runs `check` under `HEALTH_CHECK_TIMEOUT`, timing it.
+async fn timed<F, E>(check: F) -> DbHealth
+where
+    F: std::future::Future<Output = Result<(), E>>,
+    E: ToString,
+{
+    let started = Instant::now();
+    let result = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!(
+            "no answer within {}s (database down, or every connection busy)",
+            HEALTH_CHECK_TIMEOUT.as_secs()
+        )),
+    };
+    DbHealth {
+        ok: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
 // Ideas for future implementation:
-// - health_check(pool) -> bool
-// - Pagination struct with offset/limit helpers
 // - Transaction wrappers
-// - Query logging/metrics
\ No newline at end of file
+// - Query logging/metrics
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn cursor(hour: u32, id: u128) -> Cursor {
+        Cursor {
+            start_time: Utc.with_ymd_and_hms(2026, 1, 25, hour, 0, 0).unwrap(),
+            id: Uuid::from_u128(id),
+        }
+    }
+
+    #[test]
+    fn cursor_roundtrip() {
+        let original = Cursor {
+            start_time: Utc.timestamp_micros(1_769_371_200_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+
+        let decoded = Cursor::decode(&original.encode()).unwrap();
+
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn cursor_rejects_malformed_input() {
+        assert_eq!(Cursor::decode(""), None);
+        assert_eq!(Cursor::decode("not a cursor!"), None);
+        // Valid base64, wrong length
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode([0u8; 10])), None);
+    }
+
+    #[test]
+    fn pages_split_events_sharing_a_start_time() {
+        // Three events at 20:00 and one at 21:00, already in KEYSET_ORDER
+        let rows = [cursor(20, 1), cursor(20, 2), cursor(20, 3), cursor(21, 1)];
+
+        // Simulates `WHERE (start_time, id) > cursor ORDER BY ... LIMIT n + 1`
+        let fetch = |after: Option<Cursor>, limit: usize| -> Vec<Cursor> {
+            rows.iter()
+                .copied()
+                .filter(|row| after.is_none_or(|c| *row > c))
+                .take(limit + 1)
+                .collect()
+        };
+
+        let mut first = fetch(None, 2);
+        let next = take_page(&mut first, 2, |c| *c).unwrap();
+        assert_eq!(first, vec![cursor(20, 1), cursor(20, 2)]);
+
+        let mut second = fetch(Cursor::decode(&next), 2);
+        let next = take_page(&mut second, 2, |c| *c);
+        assert_eq!(second, vec![cursor(20, 3), cursor(21, 1)]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn pagination_offset_and_clamping() {
+        assert_eq!(Pagination::new(None, None).offset(), 0);
+        assert_eq!(Pagination::new(Some(3), Some(20)).offset(), 40);
+        assert_eq!(Pagination::new(Some(0), Some(1000)).per_page, MAX_PER_PAGE);
+    }
+}
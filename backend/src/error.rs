@@ -0,0 +1,370 @@
+//! # API Errors
+//!
+//! `AppError` is the error type for every route handler. It turns into an
+//! HTTP response with the same JSON shape everywhere, so the frontend can
+//! show the real reason instead of "something went wrong":
+//! ```json
+//! { "error": { "code": "conflict", "message": "email already registered" } }
+//! ```
+//!
+//! Validation errors add the invalid fields:
+//! ```json
+//! {
+//!   "error": {
+//!     "code": "validation",
+//!     "message": "invalid fields: title",
+//!     "fields": [{ "field": "title", "message": "must not be empty" }]
+//!   }
+//! }
+//! ```
+//!
+//! ## Codes
+//! | Variant | Status | `code` |
+//! |---------|--------|--------|
+//! | `BadRequest` | 400 | `bad_request` |
+//! | `Unauthorized` | 401 | `unauthorized` |
+//! | `Forbidden` | 403 | `forbidden` |
+//! | `NotFound` | 404 | `not_found` |
+//! | `Conflict` | 409 | `conflict` |
+//! | `Validation` | 422 | `validation` |
+//! | `TooManyRequests` | 429 | `rate_limited` |
+//! | `Upstream` | 502 | `upstream` |
+//! | `Unavailable` | 503 | `unavailable` |
+//! | `Database` | 500 | `database` |
+//!
+//! ## Conversions
+//! `?` works directly on `sqlx::Error`, `reqwest::Error`, `LlmError`,
+//! `MergeError` and `PromptError`. Database errors are inspected first:
+//! - unique violation -> `409` ("email already registered")
+//! - foreign key violation -> `404` ("event not found")
+//! - anything else -> `500`
+//!
+//! An `LlmError` is a `502`, except a missing `GEMINI_API_KEY`, a down
+//! LLM service or a spent daily budget, which are `503`.
+//!
+//! `TooManyRequests` also sets `Retry-After` (whole seconds).
+//!
+//! `Database` and `Upstream` errors are logged with `tracing` when the
+//! response is built; the client only gets a generic message.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::error::ErrorKind;
+
+use crate::models::FieldError;
+use crate::services::llm::LlmError;
+use crate::services::merge::MergeError;
+use crate::services::prompt::PromptError;
+
+// =============================================================================
+// ERROR TYPE
+// =============================================================================
+
+/// Everything a handler can fail with.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// The request itself is malformed (bad cursor, bad month, ...).
+    #[error("{0}")]
+    BadRequest(String),
+
+    /// Missing or wrong credentials.
+    #[error("{0}")]
+    Unauthorized(String),
+
+    /// Valid credentials for someone who may not do this.
+    #[error("{0}")]
+    Forbidden(String),
+
+    /// The resource in the path, or one the body refers to, doesn't exist.
+    #[error("{0}")]
+    NotFound(String),
+
+    /// The write clashes with an existing record.
+    #[error("{0}")]
+    Conflict(String),
+
+    /// The body is well-formed but some fields are unusable.
+    #[error("invalid fields: {}", field_names(.0))]
+    Validation(Vec<FieldError>),
+
+    /// The caller is over a rate limit; retry after this many seconds.
+    #[error("too many requests; try again in {0} seconds")]
+    TooManyRequests(u64),
+
+    /// A service we call (the LLM service, a scraped site) failed.
+    #[error("upstream error: {0}")]
+    Upstream(String),
+
+    /// The server is missing configuration this request needs.
+    #[error("{0}")]
+    Unavailable(String),
+
+    /// Unexpected database failure.
+    #[error("database error: {0}")]
+    Database(sqlx::Error),
+}
+
+impl AppError {
+    /// `NotFound` for a kind of record: `AppError::not_found("event")`.
+    pub fn not_found(what: &str) -> Self {
+        AppError::NotFound(format!("{} not found", what))
+    }
+
+    /// `Validation` for a single field.
+    pub fn invalid(field: &str, message: &str) -> Self {
+        AppError::Validation(vec![FieldError::new(field, message)])
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine-readable `code` for the response body.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Validation(_) => "validation",
+            AppError::TooManyRequests(_) => "rate_limited",
+            AppError::Upstream(_) => "upstream",
+            AppError::Unavailable(_) => "unavailable",
+            AppError::Database(_) => "database",
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let message = match self {
+            AppError::Database(ref e) => {
+                tracing::error!(error = %e, "database error");
+                "internal server error".to_string()
+            }
+            AppError::Upstream(ref e) => {
+                tracing::error!(error = %e, "upstream service error");
+                "upstream service unavailable".to_string()
+            }
+            ref other => other.to_string(),
+        };
+
+        let mut body = json!({ "code": self.code(), "message": message });
+        if let AppError::Validation(ref fields) = self {
+            body["fields"] = json!(fields);
+        }
+
+        let mut response = (self.status(), Json(json!({ "error": body }))).into_response();
+        if let AppError::TooManyRequests(seconds) = self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+fn field_names(fields: &[FieldError]) -> String {
+    fields.iter().map(|f| f.field.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+// =============================================================================
+// CONVERSIONS
+// =============================================================================
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(ref db) = e {
+            let constraint = db.constraint().unwrap_or_default();
+            match db.kind() {
+                ErrorKind::UniqueViolation => return AppError::Conflict(conflict_message(constraint)),
+                ErrorKind::ForeignKeyViolation => return AppError::NotFound(missing_message(constraint)),
+                _ => {}
+            }
+        }
+        AppError::Database(e)
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::Upstream(e.to_string())
+    }
+}
+
+impl From<LlmError> for AppError {
+    fn from(e: LlmError) -> Self {
+        match e {
+            LlmError::MissingApiKey | LlmError::UnknownProvider(_) | LlmError::ServiceUnavailable => {
+                tracing::error!(error = %e, "LLM is not available");
+                AppError::Unavailable("chat is not available right now".to_string())
+            }
+            LlmError::RateLimited { .. } => {
+                tracing::warn!(error = %e, "LLM is rate limited");
+                AppError::Unavailable("the assistant is busy right now; try again in a moment".to_string())
+            }
+            LlmError::BudgetExhausted => {
+                tracing::warn!(error = %e, "LLM budget is spent for today");
+                AppError::Unavailable("the assistant is resting until tomorrow".to_string())
+            }
+            LlmError::Database(e) => e.into(),
+            _ => AppError::Upstream(e.to_string()),
+        }
+    }
+}
+
+impl From<Vec<FieldError>> for AppError {
+    fn from(fields: Vec<FieldError>) -> Self {
+        AppError::Validation(fields)
+    }
+}
+
+impl From<MergeError> for AppError {
+    fn from(e: MergeError) -> Self {
+        match e {
+            MergeError::SameEvent => AppError::BadRequest(e.to_string()),
+            MergeError::NotFound(_) => AppError::NotFound(e.to_string()),
+            MergeError::Database(e) => e.into(),
+        }
+    }
+}
+
+impl From<PromptError> for AppError {
+    fn from(e: PromptError) -> Self {
+        match e {
+            PromptError::NoFile => AppError::Conflict(e.to_string()),
+            PromptError::Missing(_) => AppError::invalid("prompt", &e.to_string()),
+            PromptError::Read(_) => {
+                tracing::error!(error = %e, "could not reload the system prompt");
+                AppError::Unavailable(e.to_string())
+            }
+        }
+    }
+}
+
+/// Message for a unique violation, from the constraint's name.
+fn conflict_message(constraint: &str) -> String {
+    match constraint {
+        "users_email_key" => "email already registered".to_string(),
+        "unique_source_url" => "an event with this source_url already exists".to_string(),
+        "idx_events_scraped_show" => "this show is already listed at that venue and time".to_string(),
+        "idx_users_oauth_identity" => "this sign-in account is already linked to a user".to_string(),
+        "scrape_sources_pkey" => "a scrape source with this id already exists".to_string(),
+        "category_mappings_pkey" => "this source's category already has a mapping".to_string(),
+        "venues_name_key" | "idx_venues_normalized_name" => {
+            "a venue with this name already exists".to_string()
+        }
+        _ => "record already exists".to_string(),
+    }
+}
+
+/// Message for a foreign key violation.
+///
+/// Postgres names these `<table>_<column>_fkey`, and every column we
+/// reference is `<thing>_id`, so the column names the missing record.
+fn missing_message(constraint: &str) -> String {
+    ["event", "user", "venue"]
+        .iter()
+        .find(|thing| constraint.ends_with(&format!("{}_id_fkey", thing)))
+        .map(|thing| format!("{} not found", thing))
+        .unwrap_or_else(|| "referenced record not found".to_string())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Status and JSON body of the response an error turns into.
+    async fn render(error: AppError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn names_the_clashing_field() {
+        assert_eq!(conflict_message("users_email_key"), "email already registered");
+        assert_eq!(conflict_message("some_new_key"), "record already exists");
+    }
+
+    #[test]
+    fn names_the_missing_record() {
+        assert_eq!(missing_message("user_interactions_event_id_fkey"), "event not found");
+        assert_eq!(missing_message("user_preferences_user_id_fkey"), "user not found");
+        assert_eq!(missing_message("events_venue_id_fkey"), "venue not found");
+        assert_eq!(missing_message(""), "referenced record not found");
+    }
+
+    #[test]
+    fn maps_variants_to_status_codes() {
+        assert_eq!(AppError::NotFound("x".into()).status(), StatusCode::NOT_FOUND);
+        assert_eq!(AppError::Conflict("x".into()).status(), StatusCode::CONFLICT);
+        assert_eq!(AppError::Unauthorized("x".into()).status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(AppError::Forbidden("x".into()).status(), StatusCode::FORBIDDEN);
+        assert_eq!(AppError::Upstream("x".into()).status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(AppError::Unavailable("x".into()).status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(AppError::from(sqlx::Error::RowNotFound).status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(AppError::from(MergeError::SameEvent).status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn renders_code_and_message() {
+        let (status, body) = render(AppError::not_found("event")).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, json!({ "error": { "code": "not_found", "message": "event not found" } }));
+    }
+
+    #[tokio::test]
+    async fn renders_invalid_fields() {
+        let (status, body) = render(AppError::invalid("title", "must not be empty")).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "validation");
+        assert_eq!(body["error"]["message"], "invalid fields: title");
+        assert_eq!(
+            body["error"]["fields"],
+            json!([{ "field": "title", "message": "must not be empty" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limits_say_when_to_retry() {
+        let response = AppError::TooManyRequests(12).into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "12");
+        let (_, body) = render(AppError::TooManyRequests(12)).await;
+        assert_eq!(body["error"]["code"], "rate_limited");
+    }
+
+    #[tokio::test]
+    async fn hides_database_details() {
+        let (status, body) = render(AppError::Database(sqlx::Error::PoolTimedOut)).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["message"], "internal server error");
+    }
+}
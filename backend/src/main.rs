@@ -4,6 +4,9 @@
 //! It initializes the database connection, runs migrations, sets up
 //! CORS (Cross-Origin Resource Sharing), and starts the HTTP server.
 //!
+//! The same binary runs maintenance from the shell (`scrape`,
+//! `backfill-geocode`, `classify-categories`, `seed`); see cli.rs.
+//!
 //! ## Architecture Overview
 //! - Framework: Axum (async web framework for Rust)
 //! - Database: PostgreSQL (via SQLx)
@@ -15,7 +18,10 @@
 // These declare submodules that contain different parts of our application.
 // Each module is a separate file or folder in the src/ directory.
 
-mod db;          // Database utilities (future: connection helpers, queries)
+mod auth;        // Bearer tokens for user-scoped endpoints
+mod cli;         // Subcommands: serve (the default), scrape, backfills, seed
+mod db;          // Database utilities: shared SQL, pagination, health checks
+mod error;       // API error type (AppError -> JSON error responses)
 mod models;      // Data structures (Event, User, UserPreference, etc.)
 mod routes;      // API endpoint handlers (events, users, chat)
 mod scraper;     // Web scraping for event data (Skylar's domain)
@@ -26,8 +32,10 @@ mod services;    // Business logic and LLM integration (Ben's domain)
 // =============================================================================
 
 use axum::Router;                         // Axum's router for defining API routes
+use clap::Parser;                         // Reads the subcommand from the arguments
 use sqlx::postgres::PgPoolOptions;        // PostgreSQL connection pool configuration
 use std::net::SocketAddr;                 // IP address + port representation
+use std::process::ExitCode;               // What the process exits with (0 = success)
 use tower_http::cors::{Any, CorsLayer};   // CORS middleware for cross-origin requests
 
 // =============================================================================
@@ -39,11 +47,20 @@ use tower_http::cors::{Any, CorsLayer};   // CORS middleware for cross-origin re
 /// #[tokio::main] is a macro that sets up the Tokio async runtime.
 /// This allows us to use async/await throughout our application.
 ///
-/// Returns Result<(), Box<dyn std::error::Error>> which means:
-/// - Ok(()) on success (empty tuple = nothing to return)
-/// - Err(...) on failure (any error type that implements std::error::Error)
+/// Returns Result<ExitCode, Box<dyn std::error::Error>> which means:
+/// - Ok(code) when the command finished: success, or a scrape where a
+///   scraper failed (so cron and CI can tell)
+/// - Err(...) on failure (any error type that implements std::error::Error);
+///   the process exits with 1
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+
+    // -------------------------------------------------------------------------
+    // STEP 0: Read the Command
+    // -------------------------------------------------------------------------
+    // No subcommand (or `serve`) runs the server; the others run one job
+    // and exit. `--help` lists them. See cli.rs.
+    let command = cli::Cli::parse().command.unwrap_or(cli::Command::Serve);
 
     // -------------------------------------------------------------------------
     // STEP 1: Load Environment Variables
@@ -53,6 +70,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // This is where DATABASE_URL and other secrets are stored.
     dotenvy::dotenv().ok();
 
+    // Errors and job output are logged with `tracing`. RUST_LOG picks the
+    // level (default "info"), e.g. RUST_LOG=locate918_backend=debug
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "info".into()),
+        )
+        .init();
+
     // -------------------------------------------------------------------------
     // STEP 2: Get Database URL
     // -------------------------------------------------------------------------
@@ -81,13 +107,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // STEP 4: Run Database Migrations
     // -------------------------------------------------------------------------
     // Migrations are SQL scripts that set up or modify the database schema.
-    // sqlx::migrate!() is a macro that embeds migration files at compile time.
+    // db::MIGRATOR embeds the migration files at compile time (sqlx::migrate!).
     // It looks in ./migrations folder for .sql files and runs them in order.
-    // This ensures the database schema matches what our code expects.
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    // This ensures the database schema matches what our code expects;
+    // GET /api/ready checks the same list.
+    db::MIGRATOR.run(&pool).await?;
+
+    // -------------------------------------------------------------------------
+    // STEP 4b: Run the Command
+    // -------------------------------------------------------------------------
+    // Every command gets the same scrapers the server has.
+    match command {
+        cli::Command::Serve => {
+            serve(pool).await?;
+            Ok(ExitCode::SUCCESS)
+        }
+        command => cli::run(command, &pool, scraper_registry()?, &mut std::io::stdout()).await,
+    }
+}
+
+/// Runs the API server, and the background jobs and scrapers alongside it,
+/// until Ctrl+C or SIGTERM.
+async fn serve(pool: sqlx::PgPool) -> Result<(), Box<dyn std::error::Error>> {
+
+    // -------------------------------------------------------------------------
+    // STEP 5: Choose the LLM Provider
+    // -------------------------------------------------------------------------
+    // LLM_PROVIDER picks the model behind chat (gemini, openai or mock).
+    // A missing API key doesn't stop the server; chat answers 503 until
+    // it's set. See services/llm_provider.rs.
+    // LLM_DAILY_CALL_BUDGET, if set, caps its calls per day; past it chat
+    // answers with a plain search (see services/rate_limit.rs).
+    let llm = services::rate_limit::with_daily_budget(services::llm_provider::from_env()?);
+
+    // -------------------------------------------------------------------------
+    // STEP 6: Start Background Jobs
+    // -------------------------------------------------------------------------
+    // Maintenance tasks that run on a timer alongside the web server.
+    // Each gets its own clone of the pool (clones share the same connections).
+    // See services/scheduler.rs.
+    services::archive::spawn_archiver(pool.clone());
+    services::preferences::spawn_preference_learner(pool.clone());
+    services::saved_searches::spawn_saved_search_notifier(pool.clone());
+    services::reminders::spawn_reminder_sender(pool.clone());
+    // The mock provider echoes, which would file every event under "other"
+    if llm.name() != "mock" {
+        services::classification::spawn_classifier(pool.clone(), llm.clone());
+    }
 
     // -------------------------------------------------------------------------
-    // STEP 5: Configure CORS (Cross-Origin Resource Sharing)
+    // STEP 6b: Register the Scrapers
+    // -------------------------------------------------------------------------
+    // Every scraper written in code, then the sources in scrape_sources
+    // (iCal feeds, JSON-LD pages; ICAL_FEEDS entries are copied there), all
+    // of which the admin API can run (POST /api/admin/scrape) and manage
+    // (/api/admin/sources). A run left unfinished by the last shutdown is
+    // marked failed. Requests to one site are SCRAPE_REQUEST_DELAY_MS apart
+    // (or its robots.txt Crawl-delay), with at most
+    // SCRAPE_MAX_CONCURRENT_REQUESTS in flight. See scraper/fetch.rs and
+    // scraper/sources.rs.
+    scraper::runs::mark_interrupted(&pool).await?;
+    let scrapers = std::sync::Arc::new(scraper::runs::ScrapeRunner::new(scraper_registry()?));
+    scrapers.reload(&pool).await?;
+    // Each scraper also runs on its own timer (its source's interval_minutes,
+    // else SCRAPE_INTERVAL_MINUTES), following changes to the sources;
+    // SCRAPER_ENABLED=false turns that off. See scraper/schedule.rs.
+    let scrape_scheduler = scraper::schedule::ScrapeScheduler::start(scrapers.clone(), pool.clone());
+
+    // -------------------------------------------------------------------------
+    // STEP 7: Configure CORS (Cross-Origin Resource Sharing)
     // -------------------------------------------------------------------------
     // CORS controls which websites can make requests to our API.
     // For development, we allow everything (Any). In production, you'd
@@ -102,7 +190,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .allow_headers(Any);
 
     // -------------------------------------------------------------------------
-    // STEP 6: Build the Application Router
+    // STEP 8: Build the Application Router
     // -------------------------------------------------------------------------
     // Router is Axum's way of mapping URLs to handler functions.
     //
@@ -113,16 +201,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // .layer(cors)
     //   - Apply the CORS middleware to all routes
     //
-    // .with_state(pool)
-    //   - Make the database pool available to all route handlers
-    //   - Handlers can then use State<PgPool> to access the database
+    // .with_state(AppState { pool, llm, intent_cache, chat_limiter, chat_suggestions, prompt, llm_health, scrapers })
+    //   - Make the database pool, LLM provider, the chat services
+    //     (intent cache, rate limits, suggestions, system prompt, health
+    //     pings) and the scrapers available to all handlers
+    //   - Handlers can then use State<PgPool> to access the database, or
+    //     State<SharedProvider> for the model
     let app = Router::new()
         .nest("/api", routes::create_routes())
         .layer(cors)
-        .with_state(pool);
+        .with_state(routes::AppState {
+            pool,
+            llm,
+            intent_cache: Default::default(),
+            chat_limiter: std::sync::Arc::new(services::rate_limit::ChatRateLimiter::from_env()),
+            chat_suggestions: Default::default(),
+            prompt: std::sync::Arc::new(services::prompt::PromptStore::from_env()),
+            llm_health: Default::default(),
+            scrapers,
+        });
 
     // -------------------------------------------------------------------------
-    // STEP 7: Define Server Address
+    // STEP 9: Define Server Address
     // -------------------------------------------------------------------------
     // SocketAddr combines an IP address and port number.
     // [127, 0, 0, 1] = localhost (only accessible from this machine)
@@ -133,14 +233,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Server running on http://{}", addr);
 
     // -------------------------------------------------------------------------
-    // STEP 8: Start the Server
+    // STEP 10: Start the Server
     // -------------------------------------------------------------------------
     // TcpListener binds to the address and listens for incoming connections.
     // axum::serve() starts handling requests using our app router.
-    // .await? blocks until the server shuts down (or errors).
+    // The connect info gives handlers the client's address (chat rate
+    // limits for anonymous users).
+    // .await? blocks until the server shuts down (or errors). Ctrl+C or
+    // SIGTERM stops accepting requests and lets the ones in flight finish.
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    // -------------------------------------------------------------------------
+    // STEP 11: Stop the Scrapers
+    // -------------------------------------------------------------------------
+    // No new scheduled scrapes; running ones get a few seconds to finish.
+    scrape_scheduler.shutdown(scraper::schedule::SHUTDOWN_GRACE).await;
 
     // If we get here, the server shut down cleanly
     Ok(())
+}
+
+/// Every scraper written in code, sharing one fetch pool (configured from
+/// the environment); `reload` adds the sources in scrape_sources.
+fn scraper_registry() -> Result<scraper::registry::ScraperRegistry, reqwest::Error> {
+    let fetch = scraper::fetch::FetchPool::new(scraper::fetch::FetchConfig::from_env())?;
+    // SCRAPE_PARALLELISM scrapers run at once (see scraper/registry.rs),
+    // their images are checked unless SCRAPE_CHECK_IMAGES=false (see
+    // scraper/images.rs), and the events they create are geocoded in the
+    // background (GEOCODER, see services/geocoding.rs).
+    let parallelism = services::scheduler::env_u64("SCRAPE_PARALLELISM", scraper::registry::DEFAULT_PARALLELISM as u64);
+    let check_images = scraper::images::checks_enabled(std::env::var("SCRAPE_CHECK_IMAGES").ok().as_deref());
+    let registry = scraper::registry::ScraperRegistry::new(fetch)
+        .with_parallelism(parallelism as usize)
+        .with_image_checks(check_images)
+        .with_geocoder(services::geocoding::from_env())
+        .register(scraper::venues::cains_ballroom::CainsBallroomScraper::new())
+        .register(scraper::city::tulsa_calendar::TulsaCalendarScraper::new());
+    // Meetup needs an API token (MEETUP_TOKEN); without one it's left out.
+    // See scraper/platforms/meetup.rs.
+    Ok(match scraper::platforms::meetup::MeetupConfig::from_env() {
+        Some(config) => registry.register(scraper::platforms::meetup::MeetupScraper::new(config)),
+        None => {
+            tracing::info!("MEETUP_TOKEN not set; the Meetup scraper is off");
+            registry
+        }
+    })
+}
+
+/// Resolves on Ctrl+C, or SIGTERM (what `docker stop` and systemd send).
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    println!("Shutting down");
 }
\ No newline at end of file
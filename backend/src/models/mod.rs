@@ -17,11 +17,15 @@
 // IMPORTS
 // =============================================================================
 
-use chrono::{DateTime, Utc};           // Timestamp handling (timezone-aware)
+use chrono::{DateTime, NaiveDate, Utc}; // Timestamp handling (timezone-aware)
 use serde::{Deserialize, Serialize};   // JSON serialization/deserialization
+use std::collections::BTreeMap;        // Ordered maps (calendar days)
 use sqlx::FromRow;                     // Maps database rows to structs
 use uuid::Uuid;                        // Universally unique identifiers
 
+mod validation;
+pub use validation::{is_http_url, FieldError};
+
 // =============================================================================
 // EVENT MODELS
 // =============================================================================
@@ -29,6 +33,26 @@ use uuid::Uuid;                        // Universally unique identifiers
 // want to discover. Events are created either manually (for testing) or by
 // Skylar's scraper service (in production).
 
+/// Lifecycle status of an event.
+///
+/// Stored as the Postgres enum `event_status` and serialized in snake_case
+/// (`"sold_out"`). Unknown values are rejected when deserializing, so a
+/// typo in a scraper payload fails loudly instead of being stored.
+///
+/// Cancelled events are hidden from list/search by default
+/// (`?include_cancelled=true` to show them). The others are returned
+/// normally so the UI and chat can flag them ("note: this show is sold out").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "event_status", rename_all = "snake_case")]
+pub enum EventStatus {
+    #[default]
+    Scheduled,
+    Cancelled,
+    Postponed,
+    SoldOut,
+}
+
 /// Represents an event in the database.
 ///
 /// # Database Table
@@ -48,11 +72,16 @@ use uuid::Uuid;                        // Universally unique identifiers
 ///   "start_time": "2026-01-25T20:00:00Z",
 ///   "end_time": "2026-01-25T23:00:00Z",
 ///   "categories": ["concerts", "jazz", "live music"],
+///   "tags": ["21+", "live music", "nightlife"],
 ///   "price_min": 15.00,
 ///   "price_max": 25.00,
+///   "is_free": false,
 ///   "outdoor": false,
 ///   "family_friendly": false,
 ///   "image_url": "https://example.com/image.jpg",
+///   "status": "scheduled",
+///   "latitude": 36.1540,
+///   "longitude": -95.9928,
 ///   "created_at": "2026-01-17T12:00:00Z",
 ///   "updated_at": "2026-01-17T12:00:00Z"
 /// }
@@ -71,6 +100,9 @@ pub struct Event {
     /// Specific venue name (optional)
     pub venue: Option<String>,
 
+    /// Linked venue record (optional, see `Venue`)
+    pub venue_id: Option<Uuid>,
+
     /// Venue street address (optional)
     pub venue_address: Option<String>,
 
@@ -97,12 +129,21 @@ pub struct Event {
     /// Stored as TEXT[] in PostgreSQL
     pub categories: Option<Vec<String>>,
 
+    /// Free-form descriptive tags (lowercase, sorted)
+    /// Examples: ["outdoor", "family-friendly", "community"]
+    /// Stored in the `event_tags` table, one row per tag
+    pub tags: Vec<String>,
+
     /// Minimum ticket price (optional)
     pub price_min: Option<f64>,
 
     /// Maximum ticket price (optional)
     pub price_max: Option<f64>,
 
+    /// Whether the event is free to attend (default: false)
+    /// Set explicitly by the source, or derived from a $0 price on create
+    pub is_free: bool,
+
     /// Whether the event is outdoors (default: false)
     pub outdoor: bool,
 
@@ -110,8 +151,21 @@ pub struct Event {
     pub family_friendly: bool,
 
     /// URL to event image (optional)
+    /// Must be an absolute http(s) URL; `data:` URIs are rejected on create
     pub image_url: Option<String>,
 
+    /// Whether the event is still on (default: scheduled)
+    pub status: EventStatus,
+
+    /// Venue latitude in decimal degrees (optional, WGS84)
+    pub latitude: Option<f64>,
+
+    /// Venue longitude in decimal degrees (optional, WGS84)
+    pub longitude: Option<f64>,
+
+    /// When the event was archived for being long over (None = live)
+    pub archived_at: Option<DateTime<Utc>>,
+
     /// When this record was created in our database
     pub created_at: DateTime<Utc>,
 
@@ -134,14 +188,17 @@ pub struct Event {
 ///   "title": "Jazz Night",
 ///   "source_url": "https://example.com/event",
 ///   "start_time": "2026-01-25T20:00:00Z",
-///   "categories": ["concerts", "jazz"]
+///   "categories": ["concerts", "jazz"],
+///   "tags": ["nightlife", "21+"]
 /// }
 /// ```
 #[derive(Debug, Deserialize)]
 pub struct CreateEvent {
     pub title: String,
     pub description: Option<String>,
+    /// Venue name; matched to (or creates) a `Venue` when `venue_id` is absent
     pub venue: Option<String>,
+    pub venue_id: Option<Uuid>,
     pub venue_address: Option<String>,
     pub location: Option<String>,
     pub source_url: String,
@@ -149,13 +206,260 @@ pub struct CreateEvent {
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub categories: Option<Vec<String>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub price_min: Option<f64>,
     pub price_max: Option<f64>,
+    /// Explicit free flag; when omitted it's derived from the prices
+    pub is_free: Option<bool>,
     #[serde(default)]
     pub outdoor: bool,
     #[serde(default)]
     pub family_friendly: bool,
     pub image_url: Option<String>,
+    #[serde(default)]
+    pub status: EventStatus,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// An event with its distance from a search point.
+///
+/// Returned by radius searches (`/api/events/search?lat=..&lng=..`).
+/// Serializes as a flat Event object with an extra `distance_km` field.
+#[derive(Debug, Serialize, FromRow)]
+pub struct EventWithDistance {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub event: Event,
+
+    /// Great-circle distance from the search point, in kilometers
+    pub distance_km: f64,
+}
+
+/// An event with its trending score.
+///
+/// Returned by `/api/events/trending`. Serializes as a flat Event object
+/// with an extra `score` field (see `services::analytics` for weights).
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrendingEvent {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub event: Event,
+
+    /// Weighted sum of recent interactions
+    pub score: i64,
+}
+
+/// One place an event is listed (see `services::attribution`).
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "source_name": "Cain's Ballroom",
+///   "source_url": "https://www.cainsballroom.com/events/turnpike-troubadours",
+///   "external_id": null,
+///   "primary": true,
+///   "first_seen_at": "2026-02-01T15:00:00Z",
+///   "last_seen_at": "2026-03-01T15:00:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct EventSource {
+    pub source_name: Option<String>,
+    pub source_url: String,
+    /// The source's own id for the event, if it has one
+    pub external_id: Option<String>,
+    /// The listing we link to (the event's `source_url`)
+    pub primary: bool,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// An event with every place it's listed.
+///
+/// Returned by `/api/events/:id`. Serializes as a flat Event object with
+/// an extra `sources` array, the primary listing first.
+#[derive(Debug, Serialize)]
+pub struct EventDetail {
+    #[serde(flatten)]
+    pub event: Event,
+
+    pub sources: Vec<EventSource>,
+}
+
+/// A page of events returned by the list endpoint.
+///
+/// `next_cursor` is an opaque string; pass it back as `?cursor=` to fetch the
+/// following page. It is `null` when there are no more events.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "events": [ { "id": "...", "title": "Jazz Night", ... } ],
+///   "next_cursor": "AAYxxVd3wABVDoQA4ptB1KcWRGZVRAAA"
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    pub next_cursor: Option<String>,
+}
+
+/// Event totals for one local calendar day.
+#[derive(Debug, Default, Serialize)]
+pub struct CalendarDay {
+    pub count: i64,
+
+    /// Titles of the first (up to) three events that day, by start time
+    pub top_titles: Vec<String>,
+}
+
+/// Per-day event counts for a month, returned by `/api/events/calendar`.
+///
+/// Every day of the month is present, so the frontend can draw the grid
+/// straight from `days` without filling gaps.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "month": "2026-02",
+///   "days": {
+///     "2026-02-01": { "count": 0, "top_titles": [] },
+///     "2026-02-02": { "count": 2, "top_titles": ["Jazz Night", "Trivia"] }
+///   }
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct CalendarMonth {
+    pub month: String,
+    pub days: BTreeMap<NaiveDate, CalendarDay>,
+}
+
+/// Request payload for partially updating an event (`PATCH /api/events/:id`).
+///
+/// Only the fields present are changed; omitted fields keep their value.
+/// `archived: false` unarchives an event, `archived: true` archives it now.
+#[derive(Debug, Deserialize)]
+pub struct UpdateEvent {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub venue_address: Option<String>,
+    pub location: Option<String>,
+    pub source_name: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub categories: Option<Vec<String>>,
+    pub price_min: Option<f64>,
+    pub price_max: Option<f64>,
+    pub is_free: Option<bool>,
+    pub outdoor: Option<bool>,
+    pub family_friendly: Option<bool>,
+    pub image_url: Option<String>,
+    pub status: Option<EventStatus>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub archived: Option<bool>,
+}
+
+/// Request payload for merging a duplicate into an event.
+#[derive(Debug, Deserialize)]
+pub struct MergeEventsRequest {
+    /// The event to fold in and delete
+    pub duplicate_id: Uuid,
+}
+
+/// Result of `POST /api/events/:id/merge`.
+#[derive(Debug, Serialize)]
+pub struct MergeReport {
+    /// The surviving event after the merge
+    pub event: Event,
+    /// Id of the deleted duplicate
+    pub merged_from: Uuid,
+    /// The duplicate's source URL, now recorded in `event_sources`
+    pub merged_source_url: String,
+    /// Number of user interactions moved onto the surviving event
+    pub interactions_moved: u64,
+}
+
+impl CreateEvent {
+    /// Whether the new event should be marked free.
+    ///
+    /// An explicit `is_free` wins. Otherwise an event is free when its
+    /// maximum price is $0, or its minimum is $0 with no maximum.
+    pub fn resolved_is_free(&self) -> bool {
+        self.is_free.unwrap_or(match (self.price_min, self.price_max) {
+            (_, Some(max)) => max == 0.0,
+            (Some(min), None) => min == 0.0,
+            (None, None) => false,
+        })
+    }
+}
+
+// =============================================================================
+// VENUE MODELS
+// =============================================================================
+// Venues are the places events happen. Events link to them via `venue_id`;
+// the legacy `events.venue` text column is kept in sync for older clients.
+
+/// Represents a venue in the database.
+///
+/// # Database Table
+/// `venues` - See migrations/001_initial.sql and 007_venues.sql
+///
+/// Names are unique after normalization (case, apostrophes and punctuation
+/// are ignored), so "Cain's Ballroom" and "CAINS BALLROOM" are one venue.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "id": "7d0c1c0e-3c61-4d4e-9f55-7f0c1e0b9a11",
+///   "name": "Cain's Ballroom",
+///   "address": "423 N Main St, Tulsa, OK 74103",
+///   "city": "Tulsa",
+///   "capacity": 1700,
+///   "venue_type": "club",
+///   "website": "https://www.cainsballroom.com",
+///   "latitude": 36.1591,
+///   "longitude": -95.9936,
+///   ...
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Venue {
+    pub id: Uuid,
+    pub name: String,
+    pub address: Option<String>,
+    pub city: Option<String>,
+
+    /// Attributes the LLM can use ("Is Cain's loud?")
+    pub capacity: Option<i32>,
+    pub venue_type: Option<String>,
+    pub noise_level: Option<String>,
+    pub parking_info: Option<String>,
+    pub accessibility_info: Option<String>,
+
+    pub website: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request payload for creating or replacing a venue.
+#[derive(Debug, Deserialize)]
+pub struct CreateVenue {
+    pub name: String,
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub capacity: Option<i32>,
+    pub venue_type: Option<String>,
+    pub noise_level: Option<String>,
+    pub parking_info: Option<String>,
+    pub accessibility_info: Option<String>,
+    pub website: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
 }
 
 // =============================================================================
@@ -180,11 +484,12 @@ pub struct CreateEvent {
 ///   "radius_miles": 15,
 ///   "price_max": 50.00,
 ///   "family_friendly_only": false,
+///   "calendar_token": "3f2c9a7e0b6d4e1f8a5c2b9d7e4f1a3c",
 ///   "created_at": "2026-01-17T19:34:01Z",
 ///   "updated_at": "2026-01-17T19:34:01Z"
 /// }
 /// ```
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     /// Unique identifier (UUID v4, generated by server)
     pub id: Uuid,
@@ -201,12 +506,28 @@ pub struct User {
     /// How far the user is willing to travel (miles)
     pub radius_miles: Option<i32>,
 
+    /// Home point for distance ranking (WGS84; both or neither)
+    pub home_latitude: Option<f64>,
+    pub home_longitude: Option<f64>,
+
+    /// How far from home events should be, in kilometers
+    pub preferred_radius_km: Option<f64>,
+
     /// Maximum price the user wants to pay
     pub price_max: Option<f64>,
 
     /// Only show family-friendly events
     pub family_friendly_only: bool,
 
+    /// Created by `POST /api/users/guest` and not claimed yet
+    pub is_guest: bool,
+
+    /// When the current email was confirmed (None = unverified)
+    pub email_verified_at: Option<DateTime<Utc>>,
+
+    /// Secret for the saved-events calendar feed (`saved.ics?token=...`)
+    pub calendar_token: String,
+
     /// When the account was created
     pub created_at: DateTime<Utc>,
 
@@ -215,15 +536,108 @@ pub struct User {
 }
 
 /// Request payload for creating a new user.
+///
+/// `password` is optional: accounts without one sign in by email only.
+/// It is hashed before it's stored and never returned.
 #[derive(Debug, Deserialize)]
 pub struct CreateUser {
     pub email: String,
     pub name: Option<String>,
     pub location_preference: Option<String>,
     pub radius_miles: Option<i32>,
+    pub home_latitude: Option<f64>,
+    pub home_longitude: Option<f64>,
+    pub preferred_radius_km: Option<f64>,
     pub price_max: Option<f64>,
     #[serde(default)]
     pub family_friendly_only: bool,
+    #[serde(default)]
+    pub password: Option<Password>,
+}
+
+/// A plaintext password from a request body.
+///
+/// Only lives long enough to be hashed or verified; `Debug` hides it so it
+/// can't leak into logs.
+#[derive(Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Password(pub String);
+
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Password(..)")
+    }
+}
+
+/// Request payload for `PUT /api/users/:id/password`.
+///
+/// `current_password` is required when the account already has one.
+#[derive(Debug, Deserialize)]
+pub struct SetPassword {
+    pub password: Password,
+    pub current_password: Option<Password>,
+}
+
+/// Request payload for `POST /api/auth/login`.
+///
+/// `password` is required for accounts that have one.
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: Option<Password>,
+}
+
+/// Request payload for `POST /api/users/:id/claim`.
+///
+/// `password` is the new account's password, or the existing account's
+/// password when `email` is already registered.
+#[derive(Debug, Deserialize)]
+pub struct ClaimGuest {
+    pub email: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub password: Option<Password>,
+}
+
+/// Request payload for `POST /api/users/:id/verify`: the token from the
+/// emailed link.
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmail {
+    pub token: String,
+}
+
+/// Returned by register and login: a bearer token and who it's for.
+#[derive(Debug, Serialize)]
+pub struct AuthResponse {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: User,
+}
+
+/// Request payload for editing a user's profile.
+///
+/// Every field is optional; fields left out keep their current value.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateUser {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub location_preference: Option<String>,
+    /// Sent together with `home_longitude`
+    pub home_latitude: Option<f64>,
+    pub home_longitude: Option<f64>,
+    pub preferred_radius_km: Option<f64>,
+}
+
+impl UpdateUser {
+    /// True if the payload doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.email.is_none()
+            && self.location_preference.is_none()
+            && self.home_latitude.is_none()
+            && self.home_longitude.is_none()
+            && self.preferred_radius_km.is_none()
+    }
 }
 
 /// Request payload for updating user preferences.
@@ -241,6 +655,20 @@ pub struct UpdateUserPreferences {
 // Preferences track what categories a user likes or dislikes.
 // This is EXPLICIT preference data - the user told us directly.
 
+/// Where a preference came from.
+///
+/// Stored as the Postgres enum `preference_source`. `Explicit` rows were set
+/// by the user and are never touched by the learning job; `Inferred` rows
+/// are recomputed from interactions (see `services::preferences`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "preference_source", rename_all = "snake_case")]
+pub enum PreferenceSource {
+    #[default]
+    Explicit,
+    Inferred,
+}
+
 /// Represents a user's preference for a category.
 ///
 /// # Weight Scale
@@ -256,6 +684,7 @@ pub struct UserPreference {
     pub user_id: Uuid,
     pub category: String,
     pub weight: i32,
+    pub source: PreferenceSource,
     pub created_at: DateTime<Utc>,
 }
 
@@ -279,6 +708,8 @@ pub struct CreateUserPreference {
 /// - `"saved"` - User bookmarked the event
 /// - `"dismissed"` - User clicked "not interested"
 /// - `"attended"` - User marked as attending
+///
+/// The calendar feed also accepts the short forms `"save"` and `"dismiss"`.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct UserInteraction {
     pub id: Uuid,
@@ -292,6 +723,29 @@ pub struct UserInteraction {
     pub created_at: DateTime<Utc>,
 }
 
+/// Outcome of one preference learning run.
+#[derive(Debug, Default, Serialize)]
+pub struct LearningReport {
+    /// Users with at least one interaction in the window
+    pub users: usize,
+
+    /// Inferred preferences written (inserted or re-weighted)
+    pub upserted: u64,
+
+    /// Inferred preferences removed because the signal faded
+    pub removed: u64,
+}
+
+/// One page of a user's interactions, newest first.
+///
+/// Pass `next_cursor` back as `?before=` for the next (older) page; it is
+/// `null` on the last page.
+#[derive(Debug, Serialize)]
+pub struct InteractionPage {
+    pub interactions: Vec<UserInteraction>,
+    pub next_cursor: Option<String>,
+}
+
 /// Request payload for recording an interaction.
 #[derive(Debug, Deserialize)]
 pub struct CreateUserInteraction {
@@ -299,6 +753,88 @@ pub struct CreateUserInteraction {
     pub interaction_type: String,
 }
 
+/// Interaction totals by type. Both spellings of a type count together
+/// (`save` and `saved` are both saves); unknown types are ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct InteractionCounts {
+    pub views: i64,
+    pub saves: i64,
+    pub attends: i64,
+    pub dismisses: i64,
+}
+
+/// Interaction totals for one local calendar day.
+#[derive(Debug, Serialize)]
+pub struct DailyInteractionCounts {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub counts: InteractionCounts,
+}
+
+/// Per-event analytics, returned by `/api/events/:id/stats`.
+///
+/// Every type and every day in the window is present, with zeros where
+/// nothing happened, so charts don't need to fill gaps.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "event_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "totals": { "views": 120, "saves": 14, "attends": 6, "dismisses": 3 },
+///   "daily": [
+///     { "date": "2026-01-04", "views": 0, "saves": 0, "attends": 0, "dismisses": 0 },
+///     ...
+///   ]
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct EventStats {
+    pub event_id: Uuid,
+    /// All-time totals
+    pub totals: InteractionCounts,
+    /// Oldest day first, ending today
+    pub daily: Vec<DailyInteractionCounts>,
+}
+
+/// Interactions with events in one category.
+#[derive(Debug, Serialize, FromRow)]
+pub struct CategoryEngagement {
+    pub category: String,
+    pub interactions: i64,
+}
+
+/// Aggregate activity for one user, returned by `/api/users/:id/stats`.
+///
+/// A user with no interactions gets zeros, an empty `top_categories` and
+/// null timestamps.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "user_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "totals": { "views": 42, "saves": 7, "attends": 3, "dismisses": 5 },
+///   "top_categories": [{ "category": "jazz", "interactions": 18 }],
+///   "events_attended": 3,
+///   "weekly_streak": 4,
+///   "first_activity_at": "2026-01-02T19:04:11Z",
+///   "last_activity_at": "2026-02-14T02:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct UserStats {
+    pub user_id: Uuid,
+    /// All-time interaction totals
+    pub totals: InteractionCounts,
+    /// Most-engaged categories (dismissals excluded), highest first
+    pub top_categories: Vec<CategoryEngagement>,
+    /// Distinct events marked as attended
+    pub events_attended: i64,
+    /// Consecutive weeks with activity, up to this week or last week
+    pub weekly_streak: i64,
+    pub first_activity_at: Option<DateTime<Utc>>,
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
 // =============================================================================
 // COMPOSITE MODELS (FOR LLM CONTEXT)
 // =============================================================================
@@ -306,11 +842,14 @@ pub struct CreateUserInteraction {
 /// Complete user profile for LLM personalization.
 ///
 /// This is the primary data structure for chat personalization.
-/// Contains basic user info, category preferences, and recent interactions.
+/// Contains basic user info, category preferences, follows, and recent
+/// interactions.
 #[derive(Debug, Serialize)]
 pub struct UserProfile {
     pub user: User,
     pub preferences: Vec<UserPreference>,
+    /// Followed venues and categories (stronger than any preference)
+    pub follows: Vec<UserFollow>,
     pub recent_interactions: Vec<UserInteractionWithEvent>,
 }
 
@@ -325,11 +864,401 @@ pub struct UserInteractionWithEvent {
     pub created_at: DateTime<Utc>,
 }
 
+/// One interaction in a user's data export, with the event's title so the
+/// export reads on its own.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ExportedInteraction {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub event_title: String,
+    pub interaction_type: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// =============================================================================
+// FOLLOW MODELS
+// =============================================================================
+// Follows are hard subscriptions to a venue or category, stronger than
+// any preference weight.
+
+/// What a follow points at.
+///
+/// Stored as the Postgres enum `follow_target`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "follow_target", rename_all = "snake_case")]
+pub enum FollowTarget {
+    /// `target_value` is a venue id
+    Venue,
+    /// `target_value` is a category name, matched like `?category=`
+    Category,
+}
+
+/// A venue or category a user follows.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserFollow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub target_type: FollowTarget,
+    pub target_value: String,
+    /// Something to show or put in a prompt: the venue's name, or the
+    /// category itself
+    pub target_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request payload for following a venue or category.
+#[derive(Debug, Deserialize)]
+pub struct CreateFollow {
+    pub target_type: FollowTarget,
+    pub target_value: String,
+}
+
+// =============================================================================
+// SAVED SEARCH MODELS
+// =============================================================================
+// A saved search is a search the user wants to hear about again: the
+// background matcher checks it against newly created events.
+
+/// A user's saved search. Fields mean the same as the
+/// `/api/events/search` parameters of the same name.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub q: Option<String>,
+    pub category: Option<String>,
+    pub location: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the matcher last checked this search (null until its first run)
+    pub last_notified_at: Option<DateTime<Utc>>,
+}
+
+/// Request payload for creating or replacing a saved search.
+/// At least one field must be non-blank.
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateSavedSearch {
+    pub q: Option<String>,
+    pub category: Option<String>,
+    pub location: Option<String>,
+}
+
+// =============================================================================
+// NOTIFICATION MODELS
+// =============================================================================
+// Notifications are written by background producers (saved-search matcher,
+// reminders, ...) and read through /api/users/:id/notifications.
+
+/// Something a user should hear about.
+///
+/// `kind` is an open string naming the producer (`"saved_search"`, ...),
+/// so adding a producer needs no migration.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub event_id: Option<Uuid>,
+    pub title: String,
+    pub body: Option<String>,
+    /// When the user marked it read (null while unread)
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One page of a user's notifications, newest first.
+///
+/// Pass `next_cursor` back as `?before=` for the next (older) page; it is
+/// `null` on the last page.
+#[derive(Debug, Serialize)]
+pub struct NotificationPage {
+    pub notifications: Vec<Notification>,
+    pub next_cursor: Option<String>,
+}
+
+/// Unread badge count.
+#[derive(Debug, Serialize)]
+pub struct UnreadCount {
+    pub unread: i64,
+}
+
+// =============================================================================
+// CHAT MODELS
+// =============================================================================
+// Stored chat turns. A session is a UUID the client keeps between messages
+// (the chat endpoint makes one when none is sent).
+
+/// One turn of a chat session.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChatMessage {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    /// `"user"` or `"assistant"`
+    pub role: String,
+    pub content: String,
+    /// Tools the assistant ran for this reply, as `[{"name", "args"}]`
+    /// (null for user turns and fallback replies)
+    pub tool_calls: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A chat session's turns, oldest first.
+#[derive(Debug, Serialize)]
+pub struct ChatHistory {
+    pub session_id: Uuid,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Thumbs up or down on an assistant reply.
+///
+/// Stored as the Postgres enum `chat_rating`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "chat_rating", rename_all = "snake_case")]
+pub enum ChatRating {
+    Up,
+    Down,
+}
+
+/// Request payload for rating a reply. Exactly one of `message_id` (a
+/// `ChatResponse.message_id`) and `session_id` (rates the session's
+/// latest reply) must be given.
+#[derive(Debug, Deserialize)]
+pub struct CreateChatFeedback {
+    pub message_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub rating: ChatRating,
+    pub comment: Option<String>,
+}
+
+/// A rating as stored.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChatFeedback {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub rating: ChatRating,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A starter prompt for an empty chat box (`GET /api/chat/suggestions`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChatSuggestion {
+    /// What the chip shows, e.g. "🎵 Concerts: 12 events this week — see them?"
+    pub label: String,
+    /// What to send as the chat message when it's tapped
+    pub message: String,
+}
+
+impl ChatSuggestion {
+    /// A suggestion shown as the message it sends.
+    pub fn plain(message: &str) -> Self {
+        Self { label: message.to_string(), message: message.to_string() }
+    }
+}
+
+// =============================================================================
+// ADMIN MODELS
+// =============================================================================
+// What operators see about accounts. Deliberately not `User`: that carries
+// the calendar feed token, which admins have no business reading.
+
+/// One account in the admin user list, with activity counts.
+#[derive(Debug, Serialize, FromRow)]
+pub struct AdminUser {
+    pub id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub is_guest: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub preference_count: i64,
+    pub interaction_count: i64,
+    /// Time of the latest interaction
+    pub last_active_at: Option<DateTime<Utc>>,
+}
+
+/// How `GET /api/admin/users` orders accounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminUserSort {
+    /// Newest accounts first
+    #[default]
+    CreatedAt,
+    /// Most interactions first
+    Activity,
+}
+
+/// One page of the admin user list.
+#[derive(Debug, Serialize)]
+pub struct AdminUserPage {
+    pub users: Vec<AdminUser>,
+    /// Accounts matching the search, across all pages
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+/// What the chat model has been used for since `since`, returned by
+/// `/api/admin/llm/usage`.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "since": "2026-01-01T00:00:00Z",
+///   "totals": { "calls": 310, "failed": 4, "requests": 120, "tokens_in": 412000,
+///               "tokens_out": 38100, "avg_latency_ms": 1840.5 },
+///   "daily": [{ "date": "2026-01-02", "calls": 12, ... }],
+///   "intent_cache": { "hits": 85, "misses": 35, "entries": 20 }
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct LlmUsageReport {
+    pub since: DateTime<Utc>,
+    pub totals: LlmUsageCounts,
+    /// Local calendar days with at least one call, oldest first
+    pub daily: Vec<DailyLlmUsage>,
+    /// Intent cache counters since the server started
+    pub intent_cache: IntentCacheStats,
+}
+
+/// How often parsed intents were reused (see `services::intent_cache`).
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct IntentCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Intents cached right now
+    pub entries: usize,
+}
+
+/// Aggregates over a set of rows in `llm_calls`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct LlmUsageCounts {
+    /// Calls to the model
+    pub calls: i64,
+    /// Calls that failed (after the provider's retries)
+    pub failed: i64,
+    /// Chat messages and intent parses behind the calls
+    pub requests: i64,
+    pub tokens_in: i64,
+    pub tokens_out: i64,
+    /// `null` when there were no calls
+    pub avg_latency_ms: Option<f64>,
+}
+
+/// LLM usage on one local calendar day.
+#[derive(Debug, Serialize, FromRow)]
+pub struct DailyLlmUsage {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub counts: LlmUsageCounts,
+}
+
+/// A rated reply with what produced it, for `/api/admin/chat/feedback`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ChatFeedbackReview {
+    pub id: Uuid,
+    pub rating: ChatRating,
+    pub comment: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    pub message_id: Uuid,
+    pub session_id: Uuid,
+    /// `null` for anonymous chat
+    pub user_id: Option<Uuid>,
+    /// The user's message the reply answered
+    pub question: Option<String>,
+    pub reply: String,
+    /// Tools the model ran for the reply
+    pub tool_calls: Option<serde_json::Value>,
+    /// The profile section of the system prompt, as the model saw it
+    pub profile_snapshot: Option<String>,
+}
+
+/// One page of rated replies, newest rating first.
+#[derive(Debug, Serialize)]
+pub struct ChatFeedbackPage {
+    pub feedback: Vec<ChatFeedbackReview>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+/// Outcome of one category classification run (see
+/// `services::classification`).
+#[derive(Debug, Default, Serialize)]
+pub struct ClassificationReport {
+    /// Uncategorized events looked at
+    pub examined: usize,
+
+    /// Events given a category (including `other`)
+    pub classified: u64,
+
+    /// Of those, how many reused an earlier answer instead of asking
+    pub cached: usize,
+
+    /// Events the model couldn't classify this time
+    pub failed: usize,
+}
+
+/// Outcome of one geocoding pass (see `services::geocoding`).
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct GeocodeReport {
+    /// Venues and events without coordinates looked at
+    pub examined: usize,
+
+    /// Given coordinates (from the cache, the geocoder, or their venue)
+    pub located: usize,
+
+    /// Addresses the geocoder doesn't know
+    pub not_found: usize,
+
+    /// Lookups that failed this time; tried again next pass
+    pub failed: usize,
+}
+
+/// A pair of events that may be the same show, for `/api/admin/duplicates`.
+/// Merge with `POST /api/events/{event.id}/merge`.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCandidate {
+    pub id: Uuid,
+    /// Title similarity, 0 to 1 (pg_trgm)
+    pub similarity: f32,
+    pub created_at: DateTime<Utc>,
+    /// The listing we had first
+    pub event: Event,
+    pub duplicate: Event,
+}
+
+/// One page of possible duplicates, most alike first.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCandidatePage {
+    pub candidates: Vec<DuplicateCandidate>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+/// Outcome of one fuzzy duplicate pass (see `services::duplicates`).
+#[derive(Debug, Default, Serialize)]
+pub struct DuplicateReport {
+    /// Pairs looked at (same venue, close start, similar enough to review)
+    pub examined: usize,
+
+    /// Pairs merged automatically
+    pub merged: usize,
+
+    /// Pairs queued for review
+    pub flagged: u64,
+}
+
 // =============================================================================
 // SEARCH MODELS
 // =============================================================================
 
 /// Parameters for searching events (used by LLM tools).
+#[allow(dead_code)] // Not constructed until the chat tool-calling flow lands
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EventSearchParams {
     /// Text search in title/description
@@ -350,4 +1279,28 @@ pub struct EventSearchParams {
     pub family_friendly: Option<bool>,
     /// Maximum results to return
     pub limit: Option<i32>,
-}
\ No newline at end of file
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_status_uses_snake_case() {
+        assert_eq!(serde_json::to_string(&EventStatus::SoldOut).unwrap(), "\"sold_out\"");
+        assert_eq!(
+            serde_json::from_str::<EventStatus>("\"postponed\"").unwrap(),
+            EventStatus::Postponed
+        );
+    }
+
+    #[test]
+    fn event_status_rejects_unknown_values() {
+        assert!(serde_json::from_str::<EventStatus>("\"canceled\"").is_err());
+        assert!(serde_json::from_str::<EventStatus>("\"SCHEDULED\"").is_err());
+    }
+}
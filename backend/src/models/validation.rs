@@ -0,0 +1,501 @@
+//! # Payload Validation
+//!
+//! Field-level checks for event and user payloads. Every path that writes
+//! events (the create/update endpoints, bulk import, the scraper) calls
+//! `CreateEvent::validate` / `UpdateEvent::validate`, so the rules live in
+//! one place. User accounts go through `CreateUser::validate` /
+//! `UpdateUser::validate`.
+//!
+//! Errors are collected rather than returned at the first failure, so a
+//! client sees every problem in one response. `?` turns the list into
+//! `AppError::Validation`, a 422 with the fields under `error.fields`:
+//! ```json
+//! [
+//!   { "field": "title", "message": "must not be empty" },
+//!   { "field": "end_time", "message": "must not be before start_time" }
+//! ]
+//! ```
+//!
+//! ## Rules
+//! | Field | Rule |
+//! |-------|------|
+//! | `title` | not blank, at most `MAX_TITLE_CHARS` characters |
+//! | `source_url`, `image_url` | absolute http(s) URL |
+//! | `start_time` | within `MAX_PAST_DAYS` before and `MAX_FUTURE_DAYS` after now |
+//! | `end_time` | not before `start_time` (equal is fine) |
+//! | `price_min`, `price_max` | not negative, min not above max |
+//! | `latitude`, `longitude` | both or neither, within WGS84 ranges |
+//! | `home_latitude`, `home_longitude` | same, for a user's home point |
+//! | `preferred_radius_km` | a positive number |
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use super::{CreateEvent, CreateUser, Event, UpdateEvent, UpdateUser};
+use crate::services::geo;
+
+// =============================================================================
+// LIMITS
+// =============================================================================
+
+/// Longest accepted event title.
+pub const MAX_TITLE_CHARS: usize = 300;
+
+/// How far in the past an event may start. Scrapers pick up events that
+/// started earlier today or this week; years-old dates are parsing bugs.
+pub const MAX_PAST_DAYS: i64 = 365;
+
+/// How far in the future an event may start.
+pub const MAX_FUTURE_DAYS: i64 = 5 * 365;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// One invalid field and why.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Returns true if `value` is an absolute http:// or https:// URL with a host.
+///
+/// Used for `image_url` (and venue websites): the frontend puts it straight
+/// into an `<img src>`, so `data:`, `javascript:` and relative URLs are rejected.
+pub fn is_http_url(value: &str) -> bool {
+    match reqwest::Url::parse(value) {
+        Ok(url) => matches!(url.scheme(), "http" | "https") && url.host_str().is_some(),
+        Err(_) => false,
+    }
+}
+
+// =============================================================================
+// EVENT VALIDATION
+// =============================================================================
+
+impl CreateEvent {
+    /// Checks every field, returning all problems found.
+    ///
+    /// `now` is a parameter so tests can pin the clock.
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        check_title(&self.title, &mut errors);
+        check_url("source_url", &self.source_url, &mut errors);
+        if let Some(ref url) = self.image_url {
+            check_url("image_url", url, &mut errors);
+        }
+        check_times(self.start_time, self.end_time, now, &mut errors);
+        check_prices(self.price_min, self.price_max, &mut errors);
+        check_coordinates(("latitude", self.latitude), ("longitude", self.longitude), &mut errors);
+
+        finish(errors)
+    }
+}
+
+impl UpdateEvent {
+    /// Checks the fields present, as they would combine with `current`.
+    ///
+    /// Cross-field rules use the resulting values, so sending only an
+    /// `end_time` earlier than the stored `start_time` is rejected.
+    pub fn validate(&self, current: &Event, now: DateTime<Utc>) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        if let Some(ref title) = self.title {
+            check_title(title, &mut errors);
+        }
+        if let Some(ref url) = self.image_url {
+            check_url("image_url", url, &mut errors);
+        }
+
+        // Only re-check the start window when it changes; an event that was
+        // valid when created doesn't become un-editable as it ages
+        let start_time = self.start_time.unwrap_or(current.start_time);
+        let end_time = self.end_time.or(current.end_time);
+        if self.start_time.is_some() {
+            check_times(start_time, end_time, now, &mut errors);
+        } else {
+            check_end_time(start_time, end_time, &mut errors);
+        }
+
+        check_prices(
+            self.price_min.or(current.price_min),
+            self.price_max.or(current.price_max),
+            &mut errors,
+        );
+        if self.latitude.is_some() || self.longitude.is_some() {
+            check_coordinates(
+                ("latitude", self.latitude.or(current.latitude)),
+                ("longitude", self.longitude.or(current.longitude)),
+                &mut errors,
+            );
+        }
+
+        finish(errors)
+    }
+}
+
+// =============================================================================
+// USER VALIDATION
+// =============================================================================
+
+impl CreateUser {
+    /// Checks the home location fields. (Email and password have their own
+    /// checks in the handlers.)
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        check_home(self.home_latitude, self.home_longitude, self.preferred_radius_km, &mut errors);
+
+        finish(errors)
+    }
+}
+
+impl UpdateUser {
+    /// Checks the home location fields present. The home point is replaced
+    /// as a whole, so latitude and longitude must be sent together.
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        check_home(self.home_latitude, self.home_longitude, self.preferred_radius_km, &mut errors);
+
+        finish(errors)
+    }
+}
+
+// =============================================================================
+// RULES
+// =============================================================================
+
+fn finish(errors: Vec<FieldError>) -> Result<(), Vec<FieldError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_title(title: &str, errors: &mut Vec<FieldError>) {
+    if title.trim().is_empty() {
+        errors.push(FieldError::new("title", "must not be empty"));
+    } else if title.chars().count() > MAX_TITLE_CHARS {
+        errors.push(FieldError::new(
+            "title",
+            format!("must be at most {} characters", MAX_TITLE_CHARS),
+        ));
+    }
+}
+
+fn check_url(field: &str, value: &str, errors: &mut Vec<FieldError>) {
+    if !is_http_url(value) {
+        errors.push(FieldError::new(field, "must be an absolute http(s) URL"));
+    }
+}
+
+fn check_times(
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    errors: &mut Vec<FieldError>,
+) {
+    if start_time < now - Duration::days(MAX_PAST_DAYS) {
+        errors.push(FieldError::new(
+            "start_time",
+            format!("must not be more than {} days in the past", MAX_PAST_DAYS),
+        ));
+    } else if start_time > now + Duration::days(MAX_FUTURE_DAYS) {
+        errors.push(FieldError::new(
+            "start_time",
+            format!("must not be more than {} days in the future", MAX_FUTURE_DAYS),
+        ));
+    }
+    check_end_time(start_time, end_time, errors);
+}
+
+fn check_end_time(
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    errors: &mut Vec<FieldError>,
+) {
+    if end_time.is_some_and(|end| end < start_time) {
+        errors.push(FieldError::new("end_time", "must not be before start_time"));
+    }
+}
+
+fn check_prices(price_min: Option<f64>, price_max: Option<f64>, errors: &mut Vec<FieldError>) {
+    for (field, price) in [("price_min", price_min), ("price_max", price_max)] {
+        if price.is_some_and(|p| !p.is_finite() || p < 0.0) {
+            errors.push(FieldError::new(field, "must be a non-negative amount"));
+        }
+    }
+    if let (Some(min), Some(max)) = (price_min, price_max) {
+        if min > max {
+            errors.push(FieldError::new("price_min", "must not be greater than price_max"));
+        }
+    }
+}
+
+fn check_coordinates(
+    (lat_field, latitude): (&str, Option<f64>),
+    (lng_field, longitude): (&str, Option<f64>),
+    errors: &mut Vec<FieldError>,
+) {
+    match (latitude, longitude) {
+        (Some(lat), Some(lng)) => {
+            if !geo::is_valid_coordinate(lat, lng) {
+                errors.push(FieldError::new(lat_field, "coordinates are out of range"));
+            }
+        }
+        (Some(_), None) => errors.push(FieldError::new(lng_field, format!("required with {}", lat_field))),
+        (None, Some(_)) => errors.push(FieldError::new(lat_field, format!("required with {}", lng_field))),
+        (None, None) => {}
+    }
+}
+
+fn check_home(
+    home_latitude: Option<f64>,
+    home_longitude: Option<f64>,
+    preferred_radius_km: Option<f64>,
+    errors: &mut Vec<FieldError>,
+) {
+    check_coordinates(("home_latitude", home_latitude), ("home_longitude", home_longitude), errors);
+    if preferred_radius_km.is_some_and(|r| !r.is_finite() || r <= 0.0) {
+        errors.push(FieldError::new("preferred_radius_km", "must be greater than 0"));
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventStatus;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 17, 12, 0, 0).unwrap()
+    }
+
+    fn valid_event() -> CreateEvent {
+        CreateEvent {
+            title: "Jazz Night".to_string(),
+            description: None,
+            venue: None,
+            venue_id: None,
+            venue_address: None,
+            location: None,
+            source_url: "https://example.com/jazz".to_string(),
+            source_name: None,
+            start_time: now() + Duration::days(7),
+            end_time: None,
+            categories: None,
+            tags: vec![],
+            price_min: None,
+            price_max: None,
+            is_free: None,
+            outdoor: false,
+            family_friendly: false,
+            image_url: None,
+            status: EventStatus::Scheduled,
+            latitude: None,
+            longitude: None,
+        }
+    }
+
+    /// Field names of the errors, in order.
+    fn failing_fields(event: &CreateEvent) -> Vec<String> {
+        match event.validate(now()) {
+            Ok(()) => vec![],
+            Err(errors) => errors.into_iter().map(|e| e.field).collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_a_valid_event() {
+        assert_eq!(valid_event().validate(now()), Ok(()));
+    }
+
+    #[test]
+    fn title_must_not_be_blank_or_huge() {
+        let blank = CreateEvent { title: "   ".to_string(), ..valid_event() };
+        let huge = CreateEvent { title: "a".repeat(MAX_TITLE_CHARS + 1), ..valid_event() };
+        let longest = CreateEvent { title: "a".repeat(MAX_TITLE_CHARS), ..valid_event() };
+
+        assert_eq!(failing_fields(&blank), ["title"]);
+        assert_eq!(failing_fields(&huge), ["title"]);
+        assert!(failing_fields(&longest).is_empty());
+    }
+
+    #[test]
+    fn urls_must_be_http() {
+        let event = CreateEvent {
+            source_url: "not a url".to_string(),
+            image_url: Some("javascript:alert(1)".to_string()),
+            ..valid_event()
+        };
+
+        assert_eq!(failing_fields(&event), ["source_url", "image_url"]);
+    }
+
+    #[test]
+    fn accepts_http_and_https_urls() {
+        assert!(is_http_url("https://example.com/poster.jpg"));
+        assert!(is_http_url("http://cdn.example.com/a/b.png?w=600"));
+    }
+
+    #[test]
+    fn rejects_data_and_other_urls() {
+        assert!(!is_http_url("data:image/png;base64,iVBORw0KGgo="));
+        assert!(!is_http_url("javascript:alert(1)"));
+        assert!(!is_http_url("/images/poster.jpg"));
+        assert!(!is_http_url("ftp://example.com/poster.jpg"));
+        assert!(!is_http_url(""));
+    }
+
+    #[test]
+    fn start_time_must_be_in_a_sane_window() {
+        let ancient = CreateEvent { start_time: now() - Duration::days(3650), ..valid_event() };
+        let far_future = CreateEvent {
+            start_time: now() + Duration::days(MAX_FUTURE_DAYS + 1),
+            ..valid_event()
+        };
+        let earlier_today = CreateEvent { start_time: now() - Duration::hours(3), ..valid_event() };
+
+        assert_eq!(failing_fields(&ancient), ["start_time"]);
+        assert_eq!(failing_fields(&far_future), ["start_time"]);
+        assert!(failing_fields(&earlier_today).is_empty());
+    }
+
+    #[test]
+    fn end_time_may_equal_but_not_precede_start_time() {
+        let start = valid_event().start_time;
+        let equal = CreateEvent { end_time: Some(start), ..valid_event() };
+        let before = CreateEvent {
+            end_time: Some(start - Duration::seconds(1)),
+            ..valid_event()
+        };
+
+        assert!(failing_fields(&equal).is_empty());
+        assert_eq!(failing_fields(&before), ["end_time"]);
+    }
+
+    #[test]
+    fn prices_must_be_non_negative_and_ordered() {
+        let negative = CreateEvent { price_min: Some(-5.0), ..valid_event() };
+        let reversed = CreateEvent {
+            price_min: Some(20.0),
+            price_max: Some(10.0),
+            ..valid_event()
+        };
+        let free = CreateEvent {
+            price_min: Some(0.0),
+            price_max: Some(0.0),
+            ..valid_event()
+        };
+
+        assert_eq!(failing_fields(&negative), ["price_min"]);
+        assert_eq!(failing_fields(&reversed), ["price_min"]);
+        assert!(failing_fields(&free).is_empty());
+    }
+
+    #[test]
+    fn coordinates_come_in_valid_pairs() {
+        let lat_only = CreateEvent { latitude: Some(36.15), ..valid_event() };
+        let out_of_range = CreateEvent {
+            latitude: Some(136.15),
+            longitude: Some(-95.99),
+            ..valid_event()
+        };
+
+        assert_eq!(failing_fields(&lat_only), ["longitude"]);
+        assert_eq!(failing_fields(&out_of_range), ["latitude"]);
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let event = CreateEvent {
+            title: String::new(),
+            source_url: "nope".to_string(),
+            end_time: Some(now()),
+            ..valid_event()
+        };
+
+        assert_eq!(failing_fields(&event), ["title", "source_url", "end_time"]);
+    }
+
+    #[test]
+    fn update_checks_against_stored_values() {
+        let created = now() - Duration::days(30);
+        let current = Event {
+            id: Uuid::nil(),
+            title: "Jazz Night".to_string(),
+            description: None,
+            venue: None,
+            venue_id: None,
+            venue_address: None,
+            location: None,
+            source_url: "https://example.com/jazz".to_string(),
+            source_name: None,
+            start_time: now() + Duration::days(7),
+            end_time: None,
+            categories: None,
+            tags: vec![],
+            price_min: Some(10.0),
+            price_max: None,
+            is_free: false,
+            outdoor: false,
+            family_friendly: false,
+            image_url: None,
+            status: EventStatus::Scheduled,
+            latitude: None,
+            longitude: None,
+            archived_at: None,
+            created_at: created,
+            updated_at: created,
+        };
+        let update = |json: &str| serde_json::from_str::<UpdateEvent>(json).unwrap();
+
+        let early_end = update(r#"{"end_time": "2026-01-20T00:00:00Z"}"#);
+        let cheap_max = update(r#"{"price_max": 5.0}"#);
+        let blank_title = update(r#"{"title": ""}"#);
+        let unarchive = update(r#"{"archived": false}"#);
+
+        let fields = |u: &UpdateEvent| match u.validate(&current, now()) {
+            Ok(()) => vec![],
+            Err(errors) => errors.into_iter().map(|e| e.field).collect::<Vec<_>>(),
+        };
+        assert_eq!(fields(&early_end), ["end_time"]);
+        assert_eq!(fields(&cheap_max), ["price_min"]);
+        assert_eq!(fields(&blank_title), ["title"]);
+        assert!(fields(&unarchive).is_empty());
+    }
+
+    #[test]
+    fn user_home_needs_a_valid_pair_and_positive_radius() {
+        let fields = |json: &str| -> Vec<String> {
+            match serde_json::from_str::<UpdateUser>(json).unwrap().validate() {
+                Ok(()) => vec![],
+                Err(errors) => errors.into_iter().map(|e| e.field).collect(),
+            }
+        };
+
+        assert!(fields(r#"{"home_latitude": 36.15, "home_longitude": -95.99, "preferred_radius_km": 15}"#).is_empty());
+        assert_eq!(fields(r#"{"home_latitude": 36.15}"#), ["home_longitude"]);
+        assert_eq!(fields(r#"{"home_latitude": 91, "home_longitude": 0}"#), ["home_latitude"]);
+        assert_eq!(fields(r#"{"preferred_radius_km": 0}"#), ["preferred_radius_km"]);
+        assert_eq!(fields(r#"{"preferred_radius_km": -3}"#), ["preferred_radius_km"]);
+    }
+}
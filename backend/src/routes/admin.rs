@@ -0,0 +1,1512 @@
+//! # Admin Routes
+//!
+//! Operational endpoints: running background jobs on demand instead of
+//! waiting for their next scheduled tick, and looking up accounts.
+//!
+//! ## Endpoints
+//! - `POST /api/admin/preferences/learn` - Recompute inferred preferences now
+//! - `GET  /api/admin/users`             - Search and page through accounts
+//! - `GET  /api/admin/llm/usage`         - LLM calls, tokens and latency
+//! - `POST /api/admin/llm/prompt/reload` - Re-read the chat system prompt file
+//! - `POST /api/admin/events/classify`   - Categorize uncategorized events now
+//! - `POST /api/admin/events/geocode`    - Locate venues and events without coordinates
+//! - `GET  /api/admin/chat/feedback`     - Rated chat replies and what produced them
+//! - `GET  /api/admin/duplicates`        - Possible duplicate events awaiting review
+//! - `POST /api/admin/scrape`            - Start a scrape in the background
+//! - `POST /api/admin/scrape/url`        - Read one event from any page, optionally storing it
+//! - `GET  /api/admin/scrape/batches/:id` - A triggered scrape's progress and runs
+//! - `GET  /api/admin/scrape/runs`       - Recent scrape runs, per source
+//! - `GET  /api/admin/scrape/runs/:id`   - One scrape run
+//! - `GET  /api/admin/scrape/sources`    - Each source's last run and failure streak
+//! - `GET  /api/admin/scrape/health`     - Each source's status: ok, warning (yield drop) or failing
+//! - `GET  /api/admin/sources`           - Configured scrape sources
+//! - `POST /api/admin/sources`           - Add a scrape source (an iCal feed, a JSON-LD page)
+//! - `GET  /api/admin/sources/:id`       - One scrape source
+//! - `PATCH /api/admin/sources/:id`      - Enable, disable or reschedule a source
+//! - `DELETE /api/admin/sources/:id`     - Remove a scrape source
+//! - `POST /api/admin/sources/reload`    - Re-read the sources (and ICAL_FEEDS)
+//! - `GET  /api/admin/categories/unmapped` - Sources' category labels seen without a mapping
+//! - `GET  /api/admin/categories/mappings` - Per-source category mappings
+//! - `POST /api/admin/categories/mappings` - Map a source's label to one of our categories
+//! - `GET  /api/admin/quarantine`        - Scraped events that failed validation
+//! - `POST /api/admin/quarantine/:id/retry` - Fix a quarantined event's fields and store it
+//! - `DELETE /api/admin/quarantine/:id`  - Drop a quarantined event
+//!
+//! ## Authentication
+//! Every route here needs `X-Admin-Key` (see `auth::require_admin_key`).
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::Pagination;
+use crate::error::AppError;
+use crate::models::{
+    AdminUser, AdminUserPage, AdminUserSort, ChatFeedbackPage, ChatFeedbackReview, ChatRating, ClassificationReport,
+    DuplicateCandidatePage, GeocodeReport, LearningReport, LlmUsageReport,
+};
+use crate::routes::AppState;
+use crate::scraper::categories::{self as category_mappings, CategoryMapping, CreateCategoryMapping, UnmappedCategory};
+use crate::scraper::extract::{self, PageScrape};
+use crate::scraper::health::{self, ScraperHealth};
+use crate::scraper::quarantine::{self, QuarantinePage, RetryResult};
+use crate::scraper::runs::{self, ScrapeBatch, ScrapeRun, ScrapeRunner, SourceHealth};
+use crate::scraper::sources::{self, CreateScrapeSource, ScrapeSource, ScrapeSourceKind, UpdateScrapeSource};
+use crate::scraper::traits::ScraperError;
+use crate::services::intent_cache::IntentCache;
+use crate::services::llm_provider::SharedProvider;
+use crate::services::prompt::{PromptStore, SystemPrompt};
+use crate::services::{categories, classification, duplicates, geocoding, llm_usage, preferences, scheduler};
+
+// =============================================================================
+// ROUTE DEFINITIONS
+// =============================================================================
+
+/// Creates the router for all admin endpoints.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/preferences/learn", post(learn_preferences))
+        .route("/users", get(list_users))
+        .route("/llm/usage", get(llm_usage_report))
+        .route("/llm/prompt/reload", post(reload_prompt))
+        .route("/events/classify", post(classify_events))
+        .route("/events/geocode", post(geocode_events))
+        .route("/chat/feedback", get(list_chat_feedback))
+        .route("/duplicates", get(list_duplicates))
+        .route("/scrape", post(trigger_scrape))
+        .route("/scrape/url", post(scrape_url))
+        .route("/scrape/batches/:id", get(get_scrape_batch))
+        .route("/scrape/runs", get(list_scrape_runs))
+        .route("/scrape/runs/:id", get(get_scrape_run))
+        .route("/scrape/sources", get(scrape_source_health))
+        .route("/scrape/health", get(scraper_health))
+        .route("/quarantine", get(list_quarantine))
+        .route("/quarantine/:id/retry", post(retry_quarantined))
+        .route("/quarantine/:id", delete(delete_quarantined))
+        .route("/sources", get(list_sources).post(create_source))
+        .route("/sources/reload", post(reload_sources))
+        .route("/sources/:id", get(get_source).patch(update_source).delete(delete_source))
+        .route("/categories/unmapped", get(list_unmapped_categories))
+        .route("/categories/mappings", get(list_category_mappings).post(create_category_mapping))
+        .route_layer(middleware::from_fn(auth::require_admin_key))
+}
+
+// =============================================================================
+// HANDLER: LEARN PREFERENCES
+// =============================================================================
+
+/// Runs the preference learning job once (see `services::preferences`).
+///
+/// # Endpoint
+/// `POST /api/admin/preferences/learn`
+///
+/// # Returns
+/// `200 OK` with a `LearningReport`:
+/// ```json
+/// { "users": 12, "upserted": 30, "removed": 2 }
+/// ```
+async fn learn_preferences(State(pool): State<PgPool>) -> Result<Json<LearningReport>, AppError> {
+    let report = preferences::learn_preferences(&pool).await?;
+    Ok(Json(report))
+}
+
+// =============================================================================
+// HANDLER: LIST USERS
+// =============================================================================
+
+/// Query parameters for the admin user list.
+#[derive(Debug, Default, Deserialize)]
+pub struct UserListQuery {
+    /// Case-insensitive substring of the email or name
+    pub q: Option<String>,
+
+    /// `created_at` (newest first, default) or `activity`
+    #[serde(default)]
+    pub sort: AdminUserSort,
+
+    /// Page number (1-indexed, default: 1)
+    pub page: Option<u32>,
+
+    /// Results per page (default: 100, max: 100)
+    pub per_page: Option<u32>,
+}
+
+/// Lists accounts with their preference and interaction counts.
+///
+/// # Endpoint
+/// `GET /api/admin/users?q=&sort=&page=&per_page=`
+///
+/// `q` matches anywhere in the email or name (`%` and `_` are literal).
+/// Guests are included; their emails end in `guest.locate918.invalid`.
+/// No password hashes or tokens are returned.
+///
+/// # Returns
+/// `200 OK` with an `AdminUserPage`:
+/// ```json
+/// { "users": [{ "email": "sam@example.com", "interaction_count": 42, ... }],
+///   "total": 130, "page": 1, "per_page": 20 }
+/// ```
+async fn list_users(
+    State(pool): State<PgPool>,
+    Query(params): Query<UserListQuery>,
+) -> Result<Json<AdminUserPage>, AppError> {
+    let pagination = Pagination::new(params.page, params.per_page);
+    let pattern = params
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(like_pattern);
+
+    let matches = "($1::text IS NULL OR u.email ILIKE $1 OR u.name ILIKE $1)";
+    let order_by = match params.sort {
+        AdminUserSort::CreatedAt => "u.created_at DESC, u.id",
+        AdminUserSort::Activity => "i.interaction_count DESC, i.last_active_at DESC NULLS LAST, u.id",
+    };
+
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM users u WHERE {}", matches))
+        .bind(&pattern)
+        .fetch_one(&pool)
+        .await?;
+
+    let users = sqlx::query_as::<_, AdminUser>(&format!(
+        r#"
+        SELECT u.id, u.email, u.name, u.is_guest, u.email_verified_at, u.created_at,
+               p.preference_count, i.interaction_count, i.last_active_at
+        FROM users u
+        CROSS JOIN LATERAL (
+            SELECT COUNT(*) AS preference_count FROM user_preferences WHERE user_id = u.id
+        ) p
+        CROSS JOIN LATERAL (
+            SELECT COUNT(*) AS interaction_count, MAX(created_at) AS last_active_at
+            FROM user_interactions WHERE user_id = u.id
+        ) i
+        WHERE {}
+        ORDER BY {}
+        LIMIT $2 OFFSET $3
+        "#,
+        matches, order_by
+    ))
+        .bind(&pattern)
+        .bind(pagination.per_page as i64)
+        .bind(pagination.offset())
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(AdminUserPage {
+        users,
+        total,
+        page: pagination.page,
+        per_page: pagination.per_page,
+    }))
+}
+
+/// `ILIKE` pattern matching `q` anywhere, with its wildcards escaped.
+fn like_pattern(q: &str) -> String {
+    let escaped = q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+// =============================================================================
+// HANDLER: LLM USAGE
+// =============================================================================
+
+/// How far back the usage report goes without `since`.
+const DEFAULT_USAGE_DAYS: i64 = 30;
+
+/// Query parameters for the LLM usage report.
+#[derive(Debug, Deserialize)]
+pub struct LlmUsageQuery {
+    /// Start of the window (RFC 3339; default: 30 days ago)
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Sums the `llm_calls` log: calls, failures, tokens and latency, in
+/// total and per local day, plus the intent cache's hit counts.
+///
+/// # Endpoint
+/// `GET /api/admin/llm/usage?since=2026-01-01T00:00:00Z`
+///
+/// # Returns
+/// `200 OK` with an `LlmUsageReport`:
+/// ```json
+/// { "since": "2026-01-01T00:00:00Z",
+///   "totals": { "calls": 310, "failed": 4, "requests": 120, "tokens_in": 412000, ... },
+///   "daily": [{ "date": "2026-01-02", "calls": 12, ... }] }
+/// ```
+async fn llm_usage_report(
+    State(pool): State<PgPool>,
+    State(intent_cache): State<Arc<IntentCache>>,
+    Query(params): Query<LlmUsageQuery>,
+) -> Result<Json<LlmUsageReport>, AppError> {
+    let since = params
+        .since
+        .unwrap_or_else(|| Utc::now() - Duration::days(DEFAULT_USAGE_DAYS));
+
+    Ok(Json(llm_usage::usage_report(&pool, since, intent_cache.stats()).await?))
+}
+
+// =============================================================================
+// HANDLER: RELOAD PROMPT
+// =============================================================================
+
+/// Re-reads `LLM_SYSTEM_PROMPT_FILE` and uses it for new chat messages
+/// (see `services::prompt`).
+///
+/// # Endpoint
+/// `POST /api/admin/llm/prompt/reload`
+///
+/// # Returns
+/// - `200 OK` with the prompt now in use:
+///   ```json
+///   { "text": "You are Tully, ...", "version": "file-3f9a0c12" }
+///   ```
+/// - `409 Conflict` if no prompt file is configured
+/// - `422 Unprocessable Entity` if the file no longer describes the chat
+///   tools; the current prompt stays
+/// - `503 Service Unavailable` if the file can't be read; the current
+///   prompt stays
+async fn reload_prompt(State(prompt): State<Arc<PromptStore>>) -> Result<Json<SystemPrompt>, AppError> {
+    let reloaded = prompt.reload()?;
+    Ok(Json(SystemPrompt::clone(&reloaded)))
+}
+
+// =============================================================================
+// HANDLER: CLASSIFY EVENTS
+// =============================================================================
+
+/// Runs the category classification job once (see
+/// `services::classification`).
+///
+/// # Endpoint
+/// `POST /api/admin/events/classify`
+///
+/// # Returns
+/// - `200 OK` with a `ClassificationReport`:
+///   ```json
+///   { "examined": 40, "classified": 38, "cached": 12, "failed": 2 }
+///   ```
+/// - `503 Service Unavailable` if the model is unavailable
+async fn classify_events(
+    State(pool): State<PgPool>,
+    State(llm): State<SharedProvider>,
+) -> Result<Json<ClassificationReport>, AppError> {
+    Ok(Json(classification::classify_uncategorized(&pool, &llm).await?))
+}
+
+// =============================================================================
+// HANDLER: GEOCODE EVENTS
+// =============================================================================
+
+/// Most addresses one backfill request may send to the geocoder (about
+/// five minutes at Nominatim's one a second).
+const MAX_GEOCODE_LOOKUPS: usize = 300;
+
+/// Query parameters for `POST /api/admin/events/geocode`.
+#[derive(Debug, Default, Deserialize)]
+pub struct GeocodeQuery {
+    /// Most addresses to send to the geocoder (default: 50, max: 300);
+    /// cached answers don't count
+    pub limit: Option<usize>,
+}
+
+/// Gives venues, then events, without coordinates some (see
+/// `services::geocoding`): events at a located venue take its, the rest
+/// are looked up by address. Anything not reached, or whose lookup
+/// failed, is left null for the next backfill.
+///
+/// # Endpoint
+/// `POST /api/admin/events/geocode?limit=`
+///
+/// # Returns
+/// - `200 OK` with a `GeocodeReport`:
+///   ```json
+///   { "examined": 42, "located": 37, "not_found": 4, "failed": 1 }
+///   ```
+/// - `503 Service Unavailable` if geocoding is off (`GEOCODER=off`)
+async fn geocode_events(
+    State(pool): State<PgPool>,
+    State(scrapers): State<Arc<ScrapeRunner>>,
+    Query(query): Query<GeocodeQuery>,
+) -> Result<Json<GeocodeReport>, AppError> {
+    let geocoder = scrapers
+        .geocoder()
+        .ok_or_else(|| AppError::Unavailable("geocoding is off".to_string()))?;
+    let lookups = query.limit.unwrap_or(geocoding::DEFAULT_BACKFILL_LOOKUPS).min(MAX_GEOCODE_LOOKUPS);
+    Ok(Json(geocoding::backfill(&pool, geocoder.as_ref(), lookups).await?))
+}
+
+// =============================================================================
+// HANDLER: CHAT FEEDBACK
+// =============================================================================
+
+/// Query parameters for the chat feedback review.
+#[derive(Debug, Default, Deserialize)]
+pub struct ChatFeedbackQuery {
+    /// Only `up` or only `down` ratings (default: both)
+    pub rating: Option<ChatRating>,
+
+    /// Page number (1-indexed, default: 1)
+    pub page: Option<u32>,
+
+    /// Results per page (default: 100, max: 100)
+    pub per_page: Option<u32>,
+}
+
+/// Rated chat replies, newest rating first, each with the question it
+/// answered, the tools the model ran, and the profile it was given.
+///
+/// # Endpoint
+/// `GET /api/admin/chat/feedback?rating=down&page=&per_page=`
+///
+/// # Returns
+/// `200 OK` with a `ChatFeedbackPage`:
+/// ```json
+/// { "feedback": [{ "rating": "down", "comment": "That was last week",
+///                  "question": "jazz tonight?", "reply": "...",
+///                  "tool_calls": [{ "name": "search_events", "args": { ... } }],
+///                  "profile_snapshot": "About this user ...", ... }],
+///   "total": 7, "page": 1, "per_page": 100 }
+/// ```
+async fn list_chat_feedback(
+    State(pool): State<PgPool>,
+    Query(params): Query<ChatFeedbackQuery>,
+) -> Result<Json<ChatFeedbackPage>, AppError> {
+    let pagination = Pagination::new(params.page, params.per_page);
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM chat_feedback WHERE $1::chat_rating IS NULL OR rating = $1")
+        .bind(params.rating)
+        .fetch_one(&pool)
+        .await?;
+
+    let feedback = sqlx::query_as::<_, ChatFeedbackReview>(
+        r#"
+        SELECT f.id, f.rating, f.comment, f.updated_at,
+               m.id AS message_id, m.session_id, m.user_id, q.content AS question,
+               m.content AS reply, m.tool_calls, m.profile_snapshot
+        FROM chat_feedback f
+        JOIN chat_messages m ON m.id = f.message_id
+        LEFT JOIN LATERAL (
+            SELECT content FROM chat_messages
+            WHERE session_id = m.session_id
+              AND user_id IS NOT DISTINCT FROM m.user_id
+              AND role = 'user'
+              AND created_at < m.created_at
+            ORDER BY created_at DESC
+            LIMIT 1
+        ) q ON TRUE
+        WHERE $1::chat_rating IS NULL OR f.rating = $1
+        ORDER BY f.updated_at DESC, f.id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+        .bind(params.rating)
+        .bind(pagination.per_page as i64)
+        .bind(pagination.offset())
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(ChatFeedbackPage {
+        feedback,
+        total,
+        page: pagination.page,
+        per_page: pagination.per_page,
+    }))
+}
+
+// =============================================================================
+// HANDLER: DUPLICATES
+// =============================================================================
+
+/// Query parameters for `GET /api/admin/duplicates`.
+#[derive(Debug, Deserialize)]
+pub struct DuplicateQuery {
+    /// Page number (1-indexed, default: 1)
+    pub page: Option<u32>,
+
+    /// Results per page (default: 100, max: 100)
+    pub per_page: Option<u32>,
+}
+
+/// Pairs of events that may be the same show, most alike first, queued
+/// by the fuzzy duplicate pass (see `services::duplicates`). Merge a pair
+/// with `POST /api/events/{event.id}/merge`; the pair leaves the queue.
+///
+/// # Endpoint
+/// `GET /api/admin/duplicates?page=&per_page=`
+///
+/// # Returns
+/// `200 OK` with a `DuplicateCandidatePage`:
+/// ```json
+/// { "candidates": [{ "id": "...", "similarity": 0.74,
+///                    "event": { "title": "Jazz Night at The Blue Note", "source_name": "Eventbrite", ... },
+///                    "duplicate": { "title": "Blue Note Jazz Night", "source_name": "The Blue Note", ... } }],
+///   "total": 3, "page": 1, "per_page": 100 }
+/// ```
+async fn list_duplicates(
+    State(pool): State<PgPool>,
+    Query(params): Query<DuplicateQuery>,
+) -> Result<Json<DuplicateCandidatePage>, AppError> {
+    let pagination = Pagination::new(params.page, params.per_page);
+    Ok(Json(duplicates::list_candidates(&pool, pagination).await?))
+}
+
+// =============================================================================
+// HANDLER: SCRAPE
+// =============================================================================
+
+/// Body of `POST /api/admin/scrape`; the body itself is optional.
+#[derive(Debug, Default, Deserialize)]
+pub struct TriggerScrape {
+    /// A scraper's `source_id` (default: every scraper)
+    pub source: Option<String>,
+
+    /// Scrape and compare without saving (default: false)
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Starts a scrape in the background (see `scraper::runs`).
+///
+/// # Endpoint
+/// `POST /api/admin/scrape`
+///
+/// # Request Body (optional)
+/// ```json
+/// { "source": "cains_ballroom", "dry_run": false }
+/// ```
+///
+/// # Returns
+/// - `202 Accepted` with the new `ScrapeBatch`; poll
+///   `GET /api/admin/scrape/batches/:id` with its `id`:
+///   ```json
+///   { "id": "7d0c9a7e-...", "source": "cains_ballroom", "status": "running",
+///     "sources_total": 1, "sources_done": 0, "runs": [], ... }
+///   ```
+/// - `400 Bad Request` for a body that isn't a `TriggerScrape`
+/// - `404 Not Found` for an unknown source
+/// - `409 Conflict` if a requested source is already being scraped
+async fn trigger_scrape(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ScrapeBatch>), AppError> {
+    // An empty body scrapes everything; a malformed one mustn't
+    let request: TriggerScrape = match body.iter().all(u8::is_ascii_whitespace) {
+        true => TriggerScrape::default(),
+        false => serde_json::from_slice(&body).map_err(|e| AppError::BadRequest(format!("invalid body: {}", e)))?,
+    };
+
+    let (batch, _) = runner.start(&pool, request.source.as_deref(), request.dry_run).await?;
+    Ok((StatusCode::ACCEPTED, Json(batch)))
+}
+
+/// Body of `POST /api/admin/scrape/url`.
+#[derive(Debug, Deserialize)]
+pub struct ScrapeUrl {
+    pub url: String,
+
+    /// Store the event if one is found (default: false, just show it)
+    #[serde(default)]
+    pub persist: bool,
+}
+
+/// Reads the event on a page someone sent us (see `scraper::extract`):
+/// JSON-LD, then meta tags, then headings and dates. The page is fetched
+/// like any scraper's, robots.txt and politeness included.
+///
+/// # Endpoint
+/// `POST /api/admin/scrape/url`
+///
+/// # Request Body
+/// ```json
+/// { "url": "https://www.gatheringplace.org/events/lantern-walk", "persist": false }
+/// ```
+///
+/// # Returns
+/// - `200 OK` with a `PageScrape`: the `event` read and the `extractor`
+///   that found it, and with `persist`, what storing it did (`saved`):
+///   ```json
+///   { "extractor": "open_graph", "source_name": "Gathering Place",
+///     "event": { "title": "Lantern Walk", "start_time": "2026-12-06T00:00:00Z", ... },
+///     "missing": [], "saved": null }
+///   ```
+/// - `422 Unprocessable Entity` with the same body if no title or start
+///   was found: the fields that were, and which are `missing`, to finish
+///   by hand and `POST /api/events`; or with a validation error if the
+///   `url` isn't http(s), or the event read doesn't pass validation
+/// - `403 Forbidden` if the site's robots.txt disallows the page
+/// - `502 Bad Gateway` if the page can't be fetched
+async fn scrape_url(
+    State(pool): State<PgPool>,
+    State(scrapers): State<Arc<ScrapeRunner>>,
+    Json(payload): Json<ScrapeUrl>,
+) -> Result<(StatusCode, Json<PageScrape>), AppError> {
+    let mut page = extract::scrape_url(&scrapers.fetcher(), &payload.url).await.map_err(|e| match e {
+        ScraperError::Validation(_) => AppError::invalid("url", "must be an http(s) URL"),
+        ScraperError::Disallowed(url) => AppError::Forbidden(format!("robots.txt disallows {}", url)),
+        e => AppError::Upstream(e.to_string()),
+    })?;
+    if !page.missing.is_empty() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(page)));
+    }
+    if payload.persist {
+        extract::store(&pool, &mut page).await?;
+    }
+    Ok((StatusCode::OK, Json(page)))
+}
+
+/// A triggered scrape: its status, progress and each source's run so far.
+///
+/// # Endpoint
+/// `GET /api/admin/scrape/batches/:id`
+///
+/// # Returns
+/// - `200 OK` with the `ScrapeBatch`:
+///   ```json
+///   { "id": "7d0c9a7e-...", "status": "completed", "sources_total": 3, "sources_done": 3,
+///     "runs": [{ "source": "cains_ballroom", "events_found": 24, "events_created": 3, ... }], ... }
+///   ```
+///   A dry run's runs carry a `diff` of what they would have stored
+///   (`scraper::persist::ScrapeDiff`).
+/// - `404 Not Found` if there's no such batch
+async fn get_scrape_batch(State(pool): State<PgPool>, Path(id): Path<Uuid>) -> Result<Json<ScrapeBatch>, AppError> {
+    let batch = runs::find_batch(&pool, id).await?.ok_or_else(|| AppError::not_found("scrape batch"))?;
+    Ok(Json(batch))
+}
+
+/// Most runs `GET /api/admin/scrape/runs` returns.
+const MAX_SCRAPE_RUNS_LIMIT: i64 = 200;
+
+/// Query parameters for `GET /api/admin/scrape/runs`.
+#[derive(Debug, Deserialize)]
+pub struct ScrapeRunQuery {
+    /// Only this `source_id`'s runs (default: every source)
+    pub source: Option<String>,
+
+    /// How many runs (default: 50, max: 200)
+    pub limit: Option<i64>,
+}
+
+/// Recent scrape runs, newest first, dry runs included.
+///
+/// # Endpoint
+/// `GET /api/admin/scrape/runs?source=cains_ballroom&limit=20`
+///
+/// # Returns
+/// `200 OK` with the `ScrapeRun`s:
+/// ```json
+/// [{ "source": "cains_ballroom", "status": "failed", "events_found": 0,
+///    "error_message": "nothing matched `article.event-card` (...)", ... }]
+/// ```
+async fn list_scrape_runs(
+    State(pool): State<PgPool>,
+    Query(params): Query<ScrapeRunQuery>,
+) -> Result<Json<Vec<ScrapeRun>>, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, MAX_SCRAPE_RUNS_LIMIT);
+    let source = params.source.as_deref().map(str::trim).filter(|source| !source.is_empty());
+    Ok(Json(runs::list_runs(&pool, source, limit).await?))
+}
+
+/// One scraper's run: what it found, saved and skipped, or why it failed.
+///
+/// # Endpoint
+/// `GET /api/admin/scrape/runs/:id`
+///
+/// # Returns
+/// - `200 OK` with the `ScrapeRun`
+/// - `404 Not Found` if there's no such run
+async fn get_scrape_run(State(pool): State<PgPool>, Path(id): Path<Uuid>) -> Result<Json<ScrapeRun>, AppError> {
+    let run = runs::find_run(&pool, id).await?.ok_or_else(|| AppError::not_found("scrape run"))?;
+    Ok(Json(run))
+}
+
+/// Every registered source's last run, last success and failure streak.
+/// A source is `failing` after `SCRAPE_FAILING_AFTER` (default: 3)
+/// failed runs in a row; dry runs don't count.
+///
+/// # Endpoint
+/// `GET /api/admin/scrape/sources`
+///
+/// # Returns
+/// `200 OK` with a `SourceHealth` per source, in registry order:
+/// ```json
+/// [{ "source": "cains_ballroom", "last_status": "failed", "consecutive_failures": 3,
+///    "failing": true, ... },
+///  { "source": "tulsa_city_calendar", "last_status": "completed", "consecutive_failures": 0,
+///    "failing": false, ... }]
+/// ```
+async fn scrape_source_health(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+) -> Result<Json<Vec<SourceHealth>>, AppError> {
+    let failing_after = scheduler::env_u64("SCRAPE_FAILING_AFTER", runs::DEFAULT_FAILING_AFTER);
+    let sources = runner.sources();
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    Ok(Json(runs::source_health(&pool, &sources, failing_after).await?))
+}
+
+/// Every registered source's status: `failing` after `SCRAPE_FAILING_AFTER`
+/// (default: 3) failed runs in a row, `warning` when its latest run found
+/// over 70% fewer events than its trailing average (a selector that broke
+/// without failing), else `ok`. See `scraper::health`.
+///
+/// # Endpoint
+/// `GET /api/admin/scrape/health`
+///
+/// # Returns
+/// `200 OK` with a `ScraperHealth` per source, in registry order:
+/// ```json
+/// [{ "source": "cains_ballroom", "status": "warning", "consecutive_failures": 0,
+///    "events_per_run": 21.6, "latest_events": 2, "trailing_events_per_run": 23.8,
+///    "dropped": true, ... }]
+/// ```
+async fn scraper_health(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+) -> Result<Json<Vec<ScraperHealth>>, AppError> {
+    let failing_after = scheduler::env_u64("SCRAPE_FAILING_AFTER", runs::DEFAULT_FAILING_AFTER);
+    let sources = runner.sources();
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    Ok(Json(health::scraper_health(&pool, &sources, failing_after).await?))
+}
+
+// =============================================================================
+// HANDLER: QUARANTINE
+// =============================================================================
+
+/// Query parameters for `GET /api/admin/quarantine`.
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    /// Page number (1-indexed, default: 1)
+    pub page: Option<u32>,
+
+    /// Results per page (default: 100, max: 100)
+    pub per_page: Option<u32>,
+}
+
+/// Scraped events that failed validation, newest first, as the scraper
+/// reported them (see `scraper::quarantine`). Rows untouched for
+/// `QUARANTINE_KEEP_DAYS` (default: 30) are purged.
+///
+/// # Endpoint
+/// `GET /api/admin/quarantine?page=&per_page=`
+///
+/// # Returns
+/// `200 OK` with a `QuarantinePage`:
+/// ```json
+/// { "items": [{ "id": "...", "source_name": "Cain's Ballroom",
+///               "raw": { "title": "", "start_time": "2026-03-14T01:00:00Z", ... },
+///               "error": "invalid fields: title", "page_url": "https://...", ... }],
+///   "total": 2, "page": 1, "per_page": 100 }
+/// ```
+async fn list_quarantine(
+    State(pool): State<PgPool>,
+    Query(params): Query<QuarantineQuery>,
+) -> Result<Json<QuarantinePage>, AppError> {
+    let pagination = Pagination::new(params.page, params.per_page);
+    Ok(Json(quarantine::list(&pool, pagination).await?))
+}
+
+/// Fixes a quarantined event's fields and validates it again; if it
+/// passes, it's stored like any scraped event and leaves the quarantine.
+///
+/// # Endpoint
+/// `POST /api/admin/quarantine/:id/retry`
+///
+/// # Request Body (optional)
+/// The `ScrapedEvent` fields to replace; none retries the event as is:
+/// ```json
+/// { "title": "Turnpike Troubadours", "end_time": null }
+/// ```
+///
+/// # Returns
+/// - `200 OK` with what was stored:
+///   ```json
+///   { "event_id": "...", "outcome": "created" }
+///   ```
+///   `outcome` is `updated` or `unchanged` if it matched an event we have.
+/// - `400 Bad Request` if the body isn't a JSON object of `ScrapedEvent`
+///   fields, or a value has the wrong type
+/// - `404 Not Found` if there's no such row
+/// - `422 Unprocessable Entity` if the event is still invalid; the fixes
+///   and the new error are kept on the row
+async fn retry_quarantined(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    body: Bytes,
+) -> Result<Json<RetryResult>, AppError> {
+    let fixes: serde_json::Map<String, serde_json::Value> = match body.iter().all(u8::is_ascii_whitespace) {
+        true => serde_json::Map::new(),
+        false => serde_json::from_slice(&body).map_err(|e| AppError::BadRequest(format!("invalid body: {}", e)))?,
+    };
+    Ok(Json(quarantine::retry(&pool, id, fixes).await?))
+}
+
+/// Drops a quarantined event.
+///
+/// # Endpoint
+/// `DELETE /api/admin/quarantine/:id`
+///
+/// # Returns
+/// - `204 No Content`
+/// - `404 Not Found` if there's no such row
+async fn delete_quarantined(State(pool): State<PgPool>, Path(id): Path<Uuid>) -> Result<StatusCode, AppError> {
+    match quarantine::delete(&pool, id).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(AppError::not_found("quarantined event")),
+    }
+}
+
+// =============================================================================
+// HANDLER: SCRAPE SOURCES
+// =============================================================================
+
+/// Every configured scrape source, by id (see `scraper::sources`).
+///
+/// # Endpoint
+/// `GET /api/admin/sources`
+///
+/// # Returns
+/// `200 OK` with the `ScrapeSource`s:
+/// ```json
+/// [{ "id": "cains_ballroom", "name": "Cain's Ballroom", "kind": "custom", "url": null,
+///    "enabled": true, "interval_minutes": 120, ... },
+///  { "id": "guthrie_green", "name": "Guthrie Green", "kind": "ical",
+///    "url": "https://www.guthriegreen.com/events.ics", "default_category": "community",
+///    "enabled": true, "interval_minutes": null, ... }]
+/// ```
+async fn list_sources(State(pool): State<PgPool>) -> Result<Json<Vec<ScrapeSource>>, AppError> {
+    Ok(Json(sources::list(&pool).await?))
+}
+
+/// One scrape source.
+///
+/// # Endpoint
+/// `GET /api/admin/sources/:id`
+///
+/// # Returns
+/// - `200 OK` with the `ScrapeSource`
+/// - `404 Not Found` if there's no such source
+async fn get_source(State(pool): State<PgPool>, Path(id): Path<String>) -> Result<Json<ScrapeSource>, AppError> {
+    let source = sources::find(&pool, &id).await?.ok_or_else(|| AppError::not_found("scrape source"))?;
+    Ok(Json(source))
+}
+
+/// Adds a scrape source. An enabled one can be scraped, and is on the
+/// schedule, as soon as this returns.
+///
+/// # Endpoint
+/// `POST /api/admin/sources`
+///
+/// # Request Body
+/// ```json
+/// { "id": "guthrie_green", "name": "Guthrie Green", "kind": "ical",
+///   "url": "https://www.guthriegreen.com/events.ics",
+///   "default_category": "community", "default_venue": "Guthrie Green",
+///   "interval_minutes": 720 }
+/// ```
+/// `enabled` defaults to true; `interval_minutes` to the environment's.
+/// A `sitemap` source may set `"config": { "pattern": "/events/",
+/// "max_urls": 50 }`.
+///
+/// # Returns
+/// - `201 Created` with the `ScrapeSource`
+/// - `409 Conflict` if the id is taken
+/// - `422 Unprocessable Entity` for invalid fields: an `ical`, `jsonld` or
+///   `sitemap` source without an http(s) `url`, a `sitemap` source's
+///   `config` it can't use, an `eventbrite` source, or a `custom` one with
+///   no scraper in code
+async fn create_source(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+    Json(payload): Json<CreateScrapeSource>,
+) -> Result<(StatusCode, Json<ScrapeSource>), AppError> {
+    payload.validate()?;
+    if payload.kind == ScrapeSourceKind::Custom && !runner.is_registered(&payload.id) {
+        return Err(AppError::invalid("kind", "no custom scraper has this id"));
+    }
+
+    let source = sources::create(&pool, &payload).await?;
+    reload(&pool, &runner).await;
+    Ok((StatusCode::CREATED, Json(source)))
+}
+
+/// Changes a scrape source: `"enabled": false` takes it off the schedule
+/// (a run in flight finishes) and out of manual runs, without a restart.
+///
+/// # Endpoint
+/// `PATCH /api/admin/sources/:id`
+///
+/// # Request Body
+/// Any of `name`, `url`, `default_category`, `default_venue`, `enabled`,
+/// `interval_minutes` (0 goes back to the environment's) and `config`:
+/// ```json
+/// { "enabled": false }
+/// ```
+///
+/// # Returns
+/// - `200 OK` with the updated `ScrapeSource`
+/// - `404 Not Found` if there's no such source
+/// - `422 Unprocessable Entity` for invalid fields
+async fn update_source(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateScrapeSource>,
+) -> Result<Json<ScrapeSource>, AppError> {
+    payload.validate()?;
+    let source = sources::update(&pool, &id, &payload).await?.ok_or_else(|| AppError::not_found("scrape source"))?;
+    reload(&pool, &runner).await;
+    Ok(Json(source))
+}
+
+/// Removes a scrape source; its events and run history stay. A `custom`
+/// source or an `ICAL_FEEDS` entry comes back at the next reload, enabled;
+/// disable those instead.
+///
+/// # Endpoint
+/// `DELETE /api/admin/sources/:id`
+///
+/// # Returns
+/// - `204 No Content`
+/// - `404 Not Found` if there's no such source
+async fn delete_source(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    if !sources::delete(&pool, &id).await? {
+        return Err(AppError::not_found("scrape source"));
+    }
+    reload(&pool, &runner).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-reads `scrape_sources` (after editing it by hand, or `ICAL_FEEDS`
+/// on a restart-free deploy) and updates the schedule.
+///
+/// # Endpoint
+/// `POST /api/admin/sources/reload`
+///
+/// # Returns
+/// `200 OK` with the `source_id`s that now run:
+/// ```json
+/// ["cains_ballroom", "tulsa_city_calendar", "guthrie_green"]
+/// ```
+async fn reload_sources(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+) -> Result<Json<Vec<String>>, AppError> {
+    Ok(Json(runner.reload(&pool).await?))
+}
+
+/// Reloads after a change that's already saved; a failure is logged
+/// rather than failing the request, and `POST /sources/reload` retries.
+async fn reload(pool: &PgPool, runner: &ScrapeRunner) {
+    if let Err(e) = runner.reload(pool).await {
+        tracing::warn!(error = %e, "couldn't reload scrape sources");
+    }
+}
+
+// =============================================================================
+// HANDLER: CATEGORY MAPPINGS
+// =============================================================================
+
+/// Most labels `GET /api/admin/categories/unmapped` returns.
+const MAX_UNMAPPED_LIMIT: i64 = 500;
+
+/// Query parameters for `GET /api/admin/categories/unmapped`.
+#[derive(Debug, Default, Deserialize)]
+pub struct UnmappedCategoryQuery {
+    /// Only labels seen in the last this many days (default: 30)
+    pub days: Option<i64>,
+
+    /// How many labels (default: 100, max: 500)
+    pub limit: Option<i64>,
+}
+
+/// Category labels sources used recently that have no mapping, most
+/// events first. Their events were stored without a category (unless the
+/// scraper guessed one) for the classifier to fill in.
+///
+/// # Endpoint
+/// `GET /api/admin/categories/unmapped?days=30&limit=100`
+///
+/// # Returns
+/// - `200 OK` with the `UnmappedCategory`s:
+///   ```json
+///   [{ "source": "tulsa_city", "external_category": "parks & recreation", "seen_count": 41,
+///      "first_seen_at": "2026-02-01T06:00:00Z", "last_seen_at": "2026-03-01T06:00:00Z" }]
+///   ```
+/// - `422 Unprocessable Entity` if `days` isn't positive
+async fn list_unmapped_categories(
+    State(pool): State<PgPool>,
+    Query(params): Query<UnmappedCategoryQuery>,
+) -> Result<Json<Vec<UnmappedCategory>>, AppError> {
+    let days = params.days.unwrap_or(30);
+    if days <= 0 {
+        return Err(AppError::invalid("days", "must be positive"));
+    }
+    let limit = params.limit.unwrap_or(100).clamp(1, MAX_UNMAPPED_LIMIT);
+    let since = Utc::now() - Duration::days(days);
+    Ok(Json(category_mappings::list_unmapped(&pool, since, limit).await?))
+}
+
+/// Query parameters for `GET /api/admin/categories/mappings`.
+#[derive(Debug, Default, Deserialize)]
+pub struct CategoryMappingQuery {
+    /// Only this `source_id`'s mappings (default: every source)
+    pub source: Option<String>,
+}
+
+/// Every category mapping, by source and label.
+///
+/// # Endpoint
+/// `GET /api/admin/categories/mappings?source=tulsa_city`
+///
+/// # Returns
+/// `200 OK` with the `CategoryMapping`s:
+/// ```json
+/// [{ "source": "tulsa_city", "external_category": "parks & recreation",
+///    "internal_category": "outdoors", "created_at": "2026-03-01T18:00:00Z" }]
+/// ```
+async fn list_category_mappings(
+    State(pool): State<PgPool>,
+    Query(params): Query<CategoryMappingQuery>,
+) -> Result<Json<Vec<CategoryMapping>>, AppError> {
+    let source = params.source.as_deref().map(str::trim).filter(|source| !source.is_empty());
+    Ok(Json(category_mappings::list(&pool, source).await?))
+}
+
+/// Maps one source's category label to one of ours. Its events get the
+/// category from their next scrape on, including ones already stored
+/// (unless an editor set their categories).
+///
+/// # Endpoint
+/// `POST /api/admin/categories/mappings`
+///
+/// # Request Body
+/// ```json
+/// { "source": "tulsa_city", "external_category": "Parks & Recreation", "internal_category": "outdoors" }
+/// ```
+/// Both categories are stored trimmed and lowercased.
+///
+/// # Returns
+/// - `201 Created` with the `CategoryMapping`
+/// - `409 Conflict` if the label already has a mapping for that source
+/// - `422 Unprocessable Entity` for a malformed source id, a blank label,
+///   or an `internal_category` that isn't one of ours
+async fn create_category_mapping(
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateCategoryMapping>,
+) -> Result<(StatusCode, Json<CategoryMapping>), AppError> {
+    let known = categories::known_categories(&mut *pool.acquire().await?).await?;
+    payload.validate(&known)?;
+    let mapping = category_mappings::create(&pool, &payload).await?;
+    Ok((StatusCode::CREATED, Json(mapping)))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use crate::scraper::fetch::FetchPool;
+    use crate::scraper::fixture::FixtureScraper;
+    use crate::scraper::registry::ScraperRegistry;
+    use crate::scraper::runs::ScrapeRunStatus;
+    use crate::scraper::traits::ScrapedEvent;
+    use crate::services::chat_history;
+    use crate::services::llm_provider::ToolCall;
+
+    #[tokio::test]
+    async fn geocoding_needs_a_geocoder() {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let runner = Arc::new(ScrapeRunner::new(ScraperRegistry::new(FetchPool::for_tests())));
+        let error = geocode_events(State(pool), State(runner), Query(GeocodeQuery::default())).await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn prompt_reloads_need_a_prompt_file() {
+        let error = reload_prompt(State(Arc::new(PromptStore::default()))).await.unwrap_err();
+        assert_eq!(error.status(), axum::http::StatusCode::CONFLICT);
+
+        let path = std::env::temp_dir().join(format!("admin-prompt-{}.txt", Uuid::new_v4()));
+        std::fs::write(&path, "You are Tully.").unwrap();
+        let store = Arc::new(PromptStore::new(Some(path.clone()), SystemPrompt::builtin()));
+        let error = reload_prompt(State(store.clone())).await.unwrap_err();
+        assert_eq!(error.status(), axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(*store.current(), SystemPrompt::builtin());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("sam"), "%sam%");
+        assert_eq!(like_pattern("100%_off"), "%100\\%\\_off%");
+    }
+
+    #[tokio::test]
+    async fn searches_and_sorts_users_with_counts() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4().simple().to_string();
+        let mut ids = Vec::new();
+        for name in ["quiet", "busy"] {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (email, name, calendar_token) VALUES ($1, $2, $3) RETURNING id",
+            )
+                .bind(format!("{}-{}@example.com", name, run))
+                .bind(name)
+                .bind(Uuid::new_v4().simple().to_string())
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        for kind in ["view", "save"] {
+            sqlx::query("INSERT INTO user_interactions (user_id, event_id, interaction_type) VALUES ($1, $2, $3)")
+                .bind(ids[1])
+                .bind(event)
+                .bind(kind)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let list = |sort: AdminUserSort| {
+            let query = UserListQuery { q: Some(run.to_uppercase()), sort, ..Default::default() };
+            list_users(State(pool.clone()), Query(query))
+        };
+
+        let Json(newest) = list(AdminUserSort::CreatedAt).await.unwrap();
+        assert_eq!(newest.total, 2);
+        assert_eq!(newest.users[0].name.as_deref(), Some("busy"));
+
+        let Json(active) = list(AdminUserSort::Activity).await.unwrap();
+        assert_eq!(active.users[0].interaction_count, 2);
+        assert_eq!(active.users[1].interaction_count, 0);
+        assert!(active.users[0].last_active_at.is_some());
+
+        let body = serde_json::to_string(&active).unwrap();
+        assert!(!body.contains("calendar_token") && !body.contains("password"));
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)").bind(&ids).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rated_replies_come_with_their_question_and_tools() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let session = Uuid::new_v4();
+        let search = ToolCall { id: None, name: "search_events".to_string(), args: serde_json::json!({ "query": "jazz" }) };
+        let exchange = chat_history::Exchange {
+            asked_at: Utc::now(),
+            message: "jazz tonight?",
+            reply: "Nothing tonight.",
+            tool_calls: &[search],
+            profile_snapshot: Some("About this user:\n- Likes: jazz"),
+        };
+        let reply = chat_history::append(&pool, session, None, exchange).await.unwrap();
+        sqlx::query("INSERT INTO chat_feedback (message_id, rating, comment) VALUES ($1, 'down', 'wrong')")
+            .bind(reply)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let query = ChatFeedbackQuery { rating: Some(ChatRating::Down), ..Default::default() };
+        let Json(page) = list_chat_feedback(State(pool.clone()), Query(query)).await.unwrap();
+        let review = page.feedback.iter().find(|f| f.message_id == reply).unwrap();
+        assert_eq!(review.question.as_deref(), Some("jazz tonight?"));
+        assert_eq!(review.reply, "Nothing tonight.");
+        assert_eq!(review.tool_calls.as_ref().unwrap()[0]["name"], "search_events");
+        assert_eq!(review.profile_snapshot.as_deref(), Some("About this user:\n- Likes: jazz"));
+
+        let query = ChatFeedbackQuery { rating: Some(ChatRating::Up), ..Default::default() };
+        let Json(page) = list_chat_feedback(State(pool.clone()), Query(query)).await.unwrap();
+        assert!(page.feedback.iter().all(|f| f.message_id != reply));
+
+        chat_history::clear(&pool, session, None).await.unwrap();
+    }
+
+    /// Polls a scrape batch until it's no longer running.
+    async fn finished(pool: &PgPool, id: Uuid) -> ScrapeBatch {
+        for _ in 0..50 {
+            let Json(batch) = get_scrape_batch(State(pool.clone()), Path(id)).await.unwrap();
+            if batch.status != ScrapeRunStatus::Running {
+                return batch;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("scrape batch {} didn't finish", id);
+    }
+
+    #[tokio::test]
+    async fn scrapes_run_in_the_background_one_at_a_time() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let start = Utc::now() + Duration::days(2);
+        let events = vec![
+            ScrapedEvent::new("Trivia Night", &format!("https://fixture.example/{}/1", run), start),
+            ScrapedEvent::new("Karaoke", &format!("https://fixture.example/{}/2", run), start),
+        ];
+        let registry = ScraperRegistry::new(FetchPool::for_tests())
+            .register(FixtureScraper::new("slow", events).with_delay(std::time::Duration::from_millis(300)))
+            .register(FixtureScraper::failing("broken"));
+        let runner = Arc::new(ScrapeRunner::new(registry));
+
+        let trigger = |body: &str| trigger_scrape(State(pool.clone()), State(runner.clone()), Bytes::from(body.to_string()));
+        // A dry run saves nothing; the source is busy until it's done
+        let (status, Json(dry)) = trigger(r#"{ "source": "slow", "dry_run": true }"#).await.unwrap();
+        assert_eq!((status, dry.status, dry.sources_total), (StatusCode::ACCEPTED, ScrapeRunStatus::Running, 1));
+        assert_eq!(trigger(r#"{ "source": "slow" }"#).await.unwrap_err().status(), StatusCode::CONFLICT);
+        assert_eq!(trigger("").await.unwrap_err().status(), StatusCode::CONFLICT);
+
+        let dry = finished(&pool, dry.id).await;
+        assert_eq!(dry.status, ScrapeRunStatus::Completed);
+        assert_eq!((dry.runs[0].events_found, dry.runs[0].events_created, dry.runs[0].dry_run), (2, 2, true));
+        assert_eq!(dry.runs[0].diff.as_ref().map(|diff| diff.created.len()), Some(2));
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://fixture.example/{}/%", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+
+        // Everything, for real: one failing scraper fails the batch
+        let (_, Json(all)) = trigger("").await.unwrap();
+        let all = finished(&pool, all.id).await;
+        assert_eq!((all.status, all.source, all.sources_done), (ScrapeRunStatus::Failed, None, 2));
+        assert_eq!((all.runs[0].status, all.runs[0].events_created), (ScrapeRunStatus::Completed, 2));
+        assert!(all.runs[0].diff.is_none());
+        assert_eq!(all.runs[1].status, ScrapeRunStatus::Failed);
+        assert!(all.runs[1].error_message.is_some());
+        assert!(all.finished_at.is_some());
+
+        // Each run is on its own too
+        let Json(broken) = get_scrape_run(State(pool.clone()), Path(all.runs[1].id)).await.unwrap();
+        assert_eq!((broken.source.as_str(), broken.batch_id), ("broken", Some(all.id)));
+
+        assert_eq!(trigger(r#"{ "source": "nope" }"#).await.unwrap_err().status(), StatusCode::NOT_FOUND);
+        assert_eq!(trigger(r#"{ "source": 3 }"#).await.unwrap_err().status(), StatusCode::BAD_REQUEST);
+        let missing = get_scrape_batch(State(pool.clone()), Path(Uuid::new_v4())).await.unwrap_err();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let missing = get_scrape_run(State(pool.clone()), Path(Uuid::new_v4())).await.unwrap_err();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        sqlx::query("DELETE FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://fixture.example/{}/%", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn sources_are_added_run_and_disabled_through_the_api() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4().simple().to_string();
+        let start = (Utc::now() + Duration::days(5)).format("%Y%m%dT%H%M%SZ");
+        let ics = format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:{run}-1\r\nSUMMARY:Drum Circle\r\nDTSTART:{start}\r\n\
+             URL:https://feed.example/{run}/drum-circle\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n"
+        );
+        let page = format!(
+            r#"<script type="application/ld+json">{{"@type": "Event", "name": "Poetry Slam {run}",
+               "startDate": "{}", "url": "https://page.example/{run}/slam"}}</script>"#,
+            (Utc::now() + Duration::days(6)).to_rfc3339()
+        );
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/{}/events.ics", run)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(ics))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/{}/events", run)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(page))
+            .mount(&server)
+            .await;
+
+        let registry = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new("cains_fixture", Vec::new()));
+        let runner = Arc::new(ScrapeRunner::new(registry));
+        let (feed, page) = (format!("feed_{}", run), format!("page_{}", run));
+        let create = |body: serde_json::Value| {
+            create_source(State(pool.clone()), State(runner.clone()), Json(serde_json::from_value(body).unwrap()))
+        };
+
+        // Added: scraped at once, and credited to the row's name
+        let (status, Json(added)) = create(serde_json::json!({
+            "id": feed, "name": "Drum Feed", "kind": "ical", "url": format!("{}/{}/events.ics", server.uri(), run),
+            "default_category": "music", "interval_minutes": 30
+        }))
+        .await
+        .unwrap();
+        assert_eq!((status, added.enabled, added.interval_minutes), (StatusCode::CREATED, true, Some(30)));
+        let (status, _) = create(serde_json::json!({
+            "id": page, "name": "Slam Page", "kind": "jsonld", "url": format!("{}/{}/events", server.uri(), run)
+        }))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(runner.sources().contains(&feed) && runner.sources().contains(&page));
+
+        let trigger = |source: &str| {
+            let body = Bytes::from(serde_json::json!({ "source": source }).to_string());
+            trigger_scrape(State(pool.clone()), State(runner.clone()), body)
+        };
+        for source in [&feed, &page] {
+            let (_, Json(batch)) = trigger(source).await.unwrap();
+            let batch = finished(&pool, batch.id).await;
+            assert_eq!((batch.status, batch.runs[0].events_created), (ScrapeRunStatus::Completed, 1));
+        }
+        let (name, categories): (Option<String>, Option<Vec<String>>) =
+            sqlx::query_as("SELECT source_name, categories FROM events WHERE source_url = $1")
+                .bind(format!("https://feed.example/{}/drum-circle", run))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((name.as_deref(), categories), (Some("Drum Feed"), Some(vec!["music".to_string()])));
+
+        // Disabled: gone from the runner (and so the schedule)
+        let disable = UpdateScrapeSource { enabled: Some(false), ..Default::default() };
+        let Json(disabled) =
+            update_source(State(pool.clone()), State(runner.clone()), Path(feed.clone()), Json(disable)).await.unwrap();
+        assert!(!disabled.enabled);
+        assert!(!runner.sources().contains(&feed));
+        assert_eq!(trigger(&feed).await.unwrap_err().status(), StatusCode::NOT_FOUND);
+
+        // Code scrapers get a row, can be turned off, and can't be invented
+        let Json(code) = get_source(State(pool.clone()), Path("cains_fixture".to_string())).await.unwrap();
+        assert_eq!(code.kind, ScrapeSourceKind::Custom);
+        let error = create(serde_json::json!({ "id": format!("code_{}", run), "name": "Code", "kind": "custom" }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let error = create(serde_json::json!({ "id": page, "name": "Again", "kind": "jsonld", "url": "https://x.example" }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+
+        for id in [&feed, &page, &"cains_fixture".to_string()] {
+            let status = delete_source(State(pool.clone()), State(runner.clone()), Path(id.clone())).await.unwrap();
+            assert_eq!(status, StatusCode::NO_CONTENT);
+        }
+        assert!(!runner.sources().contains(&page));
+        let missing = get_source(State(pool.clone()), Path(feed.clone())).await.unwrap_err();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        sqlx::query("DELETE FROM events WHERE source_url LIKE ANY($1)")
+            .bind(vec![format!("https://feed.example/{}/%", run), format!("https://page.example/{}/%", run)])
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_new_mapping_recategorizes_the_next_scrape() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let source = format!("mapped_{}", run.simple());
+        let source_url = format!("https://fixture.example/{}/parks", run);
+        let event = ScrapedEvent {
+            external_category: Some("Parks & Recreation".to_string()),
+            ..ScrapedEvent::new("Trail Cleanup", &source_url, Utc::now() + Duration::days(4))
+        };
+        let registry = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new(&source, vec![event]));
+        let stored = || {
+            let pool = pool.clone();
+            let source_url = source_url.clone();
+            async move {
+                sqlx::query_as::<_, (Option<Vec<String>>, Option<String>)>(
+                    "SELECT categories, category_source FROM events WHERE source_url = $1",
+                )
+                    .bind(source_url)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        let unmapped = || async {
+            let Json(unmapped) = list_unmapped_categories(State(pool.clone()), Query(UnmappedCategoryQuery::default()))
+                .await
+                .unwrap();
+            unmapped.into_iter().filter(|label| label.source == source).collect::<Vec<_>>()
+        };
+
+        // No mapping: stored without a category, and the label is counted
+        registry.run_one(&pool, &source).await.unwrap();
+        assert_eq!(stored().await, (None, None));
+        let labels = unmapped().await;
+        assert_eq!(
+            labels.iter().map(|label| (label.external_category.as_str(), label.seen_count)).collect::<Vec<_>>(),
+            [("parks & recreation", 1)]
+        );
+
+        let mapping = |internal: &str| CreateCategoryMapping {
+            source: source.clone(),
+            external_category: "PARKS & RECREATION ".to_string(),
+            internal_category: internal.to_string(),
+        };
+        let error = create_category_mapping(State(pool.clone()), Json(mapping("stuff"))).await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, Json(created)) = create_category_mapping(State(pool.clone()), Json(mapping("Outdoors"))).await.unwrap();
+        assert_eq!((status, created.external_category.as_str(), created.internal_category.as_str()),
+                   (StatusCode::CREATED, "parks & recreation", "outdoors"));
+        let error = create_category_mapping(State(pool.clone()), Json(mapping("outdoors"))).await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+        let Json(listed) = list_category_mappings(State(pool.clone()), Query(CategoryMappingQuery { source: Some(source.clone()) }))
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![created]);
+
+        // The same listing again now gets the mapped category
+        let summary = registry.run_one(&pool, &source).await.unwrap();
+        assert_eq!(summary.updated, 1);
+        assert_eq!(stored().await, (Some(vec!["outdoors".to_string()]), Some("scraper".to_string())));
+        assert!(unmapped().await.is_empty());
+
+        // An editor's categories are kept
+        sqlx::query("UPDATE events SET categories = ARRAY['family'], category_source = NULL WHERE source_url = $1")
+            .bind(&source_url)
+            .execute(&pool)
+            .await
+            .unwrap();
+        registry.run_one(&pool, &source).await.unwrap();
+        assert_eq!(stored().await, (Some(vec!["family".to_string()]), None));
+
+        sqlx::query("DELETE FROM events WHERE source_url = $1").bind(&source_url).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM category_mappings WHERE source = $1").bind(&source).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM unmapped_categories WHERE source = $1").bind(&source).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_sent_link_is_shown_then_stored() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4().simple().to_string();
+        let page = format!(
+            r#"<meta property="og:title" content="Sound Bath {run}"><meta property="og:site_name" content="Mayo Hotel">
+               <meta property="event:start_time" content="{}">"#,
+            (Utc::now() + Duration::days(9)).to_rfc3339()
+        );
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/{}/sound-bath", run)))
+            .respond_with(ResponseTemplate::new(200).set_body_string(page))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/{}/coming-soon", run)))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<h1>Something big is coming</h1>"))
+            .mount(&server)
+            .await;
+
+        let runner = Arc::new(ScrapeRunner::new(ScraperRegistry::new(FetchPool::for_tests())));
+        let link = format!("{}/{}/sound-bath", server.uri(), run);
+        let send = |url: &str, persist: bool| {
+            scrape_url(State(pool.clone()), State(runner.clone()), Json(ScrapeUrl { url: url.to_string(), persist }))
+        };
+        let stored = || {
+            sqlx::query_scalar::<_, Option<String>>("SELECT source_name FROM events WHERE source_url = $1")
+                .bind(&link)
+                .fetch_optional(&pool)
+        };
+
+        // Shown, not stored
+        let (status, Json(shown)) = send(&link, false).await.unwrap();
+        assert_eq!((status, shown.extractor, shown.saved), (StatusCode::OK, Some(extract::Extractor::OpenGraph), None));
+        assert_eq!(shown.event.title, Some(format!("Sound Bath {}", run)));
+        assert_eq!(stored().await.unwrap(), None);
+
+        let (status, Json(saved)) = send(&link, true).await.unwrap();
+        assert_eq!((status, saved.saved.map(|saved| saved.outcome)), (StatusCode::OK, Some(quarantine::RetryOutcome::Created)));
+        assert_eq!(stored().await.unwrap(), Some(Some("Mayo Hotel".to_string())));
+
+        // No date anywhere: what there is, to finish by hand
+        let (status, Json(partial)) = send(&format!("{}/{}/coming-soon", server.uri(), run), true).await.unwrap();
+        assert_eq!((status, partial.missing.as_slice(), partial.saved), (StatusCode::UNPROCESSABLE_ENTITY, &["start_time"][..], None));
+        assert_eq!(partial.event.title.as_deref(), Some("Something big is coming"));
+        assert_eq!(send("mailto:events@example.com", false).await.unwrap_err().status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        sqlx::query("DELETE FROM events WHERE source_url = $1")
+            .bind(&link)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
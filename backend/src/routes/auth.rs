@@ -0,0 +1,568 @@
+//! # Auth Routes
+//!
+//! Account creation and login. Both hand back a bearer token for the
+//! user-scoped `/api/users/:id/...` endpoints (see `crate::auth`).
+//!
+//! ## Endpoints
+//! - `POST /api/auth/register`        - Create an account and get a token
+//! - `POST /api/auth/login`           - Get a token for an existing account
+//! - `GET  /api/auth/google`          - Start "Sign in with Google"
+//! - `GET  /api/auth/google/callback` - Google redirects back here
+//!
+//! ## Google Sign-In
+//! `/google` sets a short-lived `oauth_state` cookie and sends the same
+//! random value to Google as `state`; the callback only proceeds when they
+//! match, so a sign-in can't be started from another site. On success the
+//! browser is sent to `FRONTEND_URL/auth/callback#token=...&expires_at=...`
+//! (a fragment, so the token stays out of server logs).
+//!
+//! A Google account maps to a user by its subject id. On first sign-in it
+//! links to an existing account with the same email, if both Google and we
+//! have verified that email, or creates a new one.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use super::users::{insert_user, queue_verification};
+use crate::auth;
+use crate::db::USER_COLUMNS;
+use crate::error::AppError;
+use crate::models::{AuthResponse, CreateUser, LoginRequest, Password, User};
+use crate::routes::AppState;
+use crate::services::oauth::{GoogleOAuth, OAuthIdentity, OAuthProvider};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Cookie holding the `state` sent to the OAuth provider.
+const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
+/// Seconds the user has to finish the provider's consent screen.
+const OAUTH_STATE_MAX_AGE_SECS: u32 = 600;
+
+/// Where the browser lands after a successful OAuth sign-in (and where
+/// verification links point).
+pub(crate) fn frontend_url() -> String {
+    std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string())
+}
+
+// =============================================================================
+// ROUTE DEFINITIONS
+// =============================================================================
+
+/// Creates the router for all auth endpoints.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/register", post(register))
+        .route("/login", post(login))
+        .route("/google", get(google_start))
+        .route("/google/callback", get(google_callback))
+}
+
+// =============================================================================
+// HANDLER: REGISTER
+// =============================================================================
+
+/// Creates a user and signs them in.
+///
+/// # Endpoint
+/// `POST /api/auth/register` with the same body as `POST /api/users`
+///
+/// Like `POST /api/users`, mails a verification link in the background.
+///
+/// # Returns
+/// - `201 Created` with an `AuthResponse`
+/// - `409 Conflict` if the email is already registered
+/// - `422 Unprocessable Entity` if `password` is too short
+/// - `503 Service Unavailable` if `JWT_SECRET` isn't set
+async fn register(
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateUser>,
+) -> Result<(StatusCode, Json<AuthResponse>), AppError> {
+    let user = insert_user(&pool, payload).await?;
+    let (token, expires_at) = auth::issue_token(user.id)?;
+    queue_verification(&user);
+
+    Ok((StatusCode::CREATED, Json(AuthResponse { token, expires_at, user })))
+}
+
+// =============================================================================
+// HANDLER: LOGIN
+// =============================================================================
+
+/// A user row plus the password hash, which `User` deliberately lacks.
+#[derive(sqlx::FromRow)]
+pub(crate) struct UserCredentials {
+    #[sqlx(flatten)]
+    pub user: User,
+    pub password_hash: Option<String>,
+}
+
+/// Issues a token for an existing account.
+///
+/// # Endpoint
+/// `POST /api/auth/login` with `{"email": "...", "password": "..."}`
+///
+/// A password is always required. Accounts without one (created before
+/// passwords, or through Google) can't log in here; they sign in with
+/// Google. Guests can't log in either: their token from
+/// `POST /api/users/guest` is the only way back in until they claim.
+///
+/// # Returns
+/// - `200 OK` with an `AuthResponse`
+/// - `401 Unauthorized` for an unknown email or a missing/wrong password
+///   (same message for both, so emails can't be probed)
+/// - `503 Service Unavailable` if `JWT_SECRET` isn't set
+async fn login(
+    State(pool): State<PgPool>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<AuthResponse>, AppError> {
+    let invalid = || AppError::Unauthorized("invalid credentials".to_string());
+
+    let Some(password) = payload.password else {
+        return Err(invalid());
+    };
+
+    let UserCredentials { user, password_hash } = sqlx::query_as::<_, UserCredentials>(&format!(
+        "SELECT {}, password_hash FROM users WHERE email = $1 AND NOT is_guest",
+        USER_COLUMNS
+    ))
+        .bind(payload.email.trim())
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(invalid)?;
+
+    if !password_matches(Some(password), password_hash).await {
+        return Err(invalid());
+    }
+
+    let (token, expires_at) = auth::issue_token(user.id)?;
+
+    Ok(Json(AuthResponse { token, expires_at, user }))
+}
+
+/// True if the account has a password and `password` is it. An account
+/// without one never matches.
+pub(crate) async fn password_matches(password: Option<Password>, password_hash: Option<String>) -> bool {
+    match (password, password_hash) {
+        (Some(password), Some(hash)) => auth::verify_password(password, hash).await,
+        _ => false,
+    }
+}
+
+// =============================================================================
+// HANDLER: GOOGLE SIGN-IN
+// =============================================================================
+
+/// Sends the browser to Google's consent screen.
+///
+/// # Endpoint
+/// `GET /api/auth/google`
+///
+/// # Returns
+/// - `303 See Other` to Google, with the `oauth_state` cookie set
+/// - `503 Service Unavailable` if Google credentials aren't configured
+async fn google_start() -> Result<impl IntoResponse, AppError> {
+    let google = GoogleOAuth::from_env()?;
+    let state = Uuid::new_v4().simple().to_string();
+
+    let cookie = format!(
+        "{}={}; Path=/api/auth/google; Max-Age={}; HttpOnly; SameSite=Lax",
+        OAUTH_STATE_COOKIE, state, OAUTH_STATE_MAX_AGE_SECS
+    );
+
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&google.authorize_url(&state))))
+}
+
+/// Query parameters Google sends back.
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set instead of `code` when the user cancels
+    pub error: Option<String>,
+}
+
+/// Finishes Google sign-in and hands the session token to the frontend.
+///
+/// # Endpoint
+/// `GET /api/auth/google/callback?code=...&state=...`
+///
+/// # Returns
+/// - `303 See Other` to `FRONTEND_URL/auth/callback#token=...`
+/// - `400 Bad Request` if the user cancelled or `code` is missing
+/// - `401 Unauthorized` if `state` doesn't match the cookie, or Google
+///   reports the email as unverified
+/// - `409 Conflict` if the email belongs to another Google account, or to
+///   an account that hasn't verified it
+/// - `502 Bad Gateway` if Google rejects the code
+async fn google_callback(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Query(params): Query<OAuthCallbackQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let google = GoogleOAuth::from_env()?;
+
+    if let Some(error) = params.error {
+        return Err(AppError::BadRequest(format!("Google sign-in was not completed: {}", error)));
+    }
+
+    let expected = cookie_value(&headers, OAUTH_STATE_COOKIE);
+    let state_matches = match (expected, params.state.as_deref()) {
+        (Some(expected), Some(given)) => bool::from(expected.as_bytes().ct_eq(given.as_bytes())),
+        _ => false,
+    };
+    if !state_matches {
+        return Err(AppError::Unauthorized("sign-in expired or was started elsewhere; try again".to_string()));
+    }
+
+    let code = params
+        .code
+        .ok_or_else(|| AppError::BadRequest("missing code".to_string()))?;
+
+    let user = sign_in_with(&pool, &google, &code).await?;
+    let (token, expires_at) = auth::issue_token(user.id)?;
+
+    let clear_cookie = format!(
+        "{}=; Path=/api/auth/google; Max-Age=0; HttpOnly; SameSite=Lax",
+        OAUTH_STATE_COOKIE
+    );
+    let target = format!(
+        "{}/auth/callback#token={}&expires_at={}",
+        frontend_url().trim_end_matches('/'),
+        token,
+        expires_at.timestamp()
+    );
+
+    Ok(([(header::SET_COOKIE, clear_cookie)], Redirect::to(&target)))
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// Value of one cookie from the `Cookie` header.
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Exchanges `code` with the provider and returns the matching user,
+/// linking or creating one on first sign-in.
+async fn sign_in_with(pool: &PgPool, provider: &dyn OAuthProvider, code: &str) -> Result<User, AppError> {
+    let identity = provider.exchange_code(code).await?;
+    find_or_create_oauth_user(pool, provider.name(), identity).await
+}
+
+/// Maps a provider identity to a user.
+///
+/// 1. Already linked (same provider and subject): that user, even if the
+///    email has changed since.
+/// 2. An unlinked account with the same email: link it. Only for emails
+///    the provider has verified, so nobody can claim someone else's account,
+///    and only if the account's owner has verified it too: otherwise whoever
+///    registered the address first (and knows its password) would share the
+///    real owner's account.
+/// 3. Otherwise create a new passwordless user and link it.
+///
+/// Either way the email is marked verified; the provider vouched for it.
+async fn find_or_create_oauth_user(
+    pool: &PgPool,
+    provider: &str,
+    identity: OAuthIdentity,
+) -> Result<User, AppError> {
+    let linked = sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM users WHERE oauth_provider = $1 AND oauth_subject = $2",
+        USER_COLUMNS
+    ))
+        .bind(provider)
+        .bind(&identity.subject)
+        .fetch_optional(pool)
+        .await?;
+
+    if let Some(user) = linked {
+        return Ok(user);
+    }
+
+    if !identity.email_verified {
+        return Err(AppError::Unauthorized("email address is not verified".to_string()));
+    }
+
+    let existing = sqlx::query_as::<_, (Uuid, Option<String>, bool)>(
+        "SELECT id, oauth_subject, email_verified_at IS NOT NULL FROM users WHERE email = $1"
+    )
+        .bind(&identity.email)
+        .fetch_optional(pool)
+        .await?;
+
+    let user_id = match existing {
+        Some((_, Some(_), _)) => {
+            return Err(AppError::Conflict(
+                "this email is linked to a different sign-in account".to_string(),
+            ));
+        }
+        Some((_, None, false)) => {
+            return Err(AppError::Conflict(
+                "an account with this email hasn't verified it; verify it before linking Google".to_string(),
+            ));
+        }
+        Some((id, None, true)) => id,
+        None => {
+            let payload = CreateUser {
+                email: identity.email,
+                name: identity.name,
+                location_preference: None,
+                radius_miles: None,
+                home_latitude: None,
+                home_longitude: None,
+                preferred_radius_km: None,
+                price_max: None,
+                family_friendly_only: false,
+                password: None,
+            };
+            insert_user(pool, payload).await?.id
+        }
+    };
+
+    let user = sqlx::query_as::<_, User>(&format!(
+        r#"
+        UPDATE users
+        SET oauth_provider = $2, oauth_subject = $3,
+            email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW()
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        USER_COLUMNS
+    ))
+        .bind(user_id)
+        .bind(provider)
+        .bind(&identity.subject)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(user)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::async_trait;
+    use std::sync::Mutex;
+
+    /// Stands in for Google: every code yields the identity it holds.
+    struct MockProvider {
+        identity: Mutex<OAuthIdentity>,
+    }
+
+    impl MockProvider {
+        fn new(subject: &str, email: &str) -> Self {
+            Self {
+                identity: Mutex::new(OAuthIdentity {
+                    subject: subject.to_string(),
+                    email: email.to_string(),
+                    email_verified: true,
+                    name: Some("Sam".to_string()),
+                }),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl OAuthProvider for MockProvider {
+        fn name(&self) -> &'static str {
+            "google"
+        }
+
+        fn authorize_url(&self, state: &str) -> String {
+            format!("https://accounts.example/auth?state={}", state)
+        }
+
+        async fn exchange_code(&self, code: &str) -> Result<OAuthIdentity, AppError> {
+            if code == "bad" {
+                return Err(AppError::Upstream("invalid_grant".to_string()));
+            }
+            Ok(self.identity.lock().unwrap().clone())
+        }
+    }
+
+    #[test]
+    fn reads_one_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, "theme=dark; oauth_state=abc123".parse().unwrap());
+
+        assert_eq!(cookie_value(&headers, "oauth_state"), Some("abc123"));
+        assert_eq!(cookie_value(&headers, "missing"), None);
+    }
+
+    #[tokio::test]
+    async fn google_accounts_map_to_one_user() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let google = MockProvider::new(&format!("sub-{}", run), &format!("{}@gmail.example", run));
+
+        // First sign-in creates the user
+        let first = sign_in_with(&pool, &google, "code").await.unwrap();
+        assert_eq!(first.name.as_deref(), Some("Sam"));
+
+        // Same Google account with a new email is still the same user
+        google.identity.lock().unwrap().email = format!("renamed-{}@gmail.example", run);
+        let again = sign_in_with(&pool, &google, "code").await.unwrap();
+        assert_eq!(again.id, first.id);
+
+        // A rejected code creates nothing
+        let rejected = sign_in_with(&pool, &google, "bad").await.unwrap_err();
+        assert_eq!(rejected.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn links_existing_accounts_only_for_verified_emails() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let email = format!("{}@example.com", run);
+        let existing: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token, email_verified_at) VALUES ($1, $2, NOW()) RETURNING id",
+        )
+            .bind(&email)
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let unverified = MockProvider::new(&format!("sub-{}", run), &email);
+        unverified.identity.lock().unwrap().email_verified = false;
+        let error = sign_in_with(&pool, &unverified, "code").await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNAUTHORIZED);
+
+        let verified = MockProvider::new(&format!("sub-{}", run), &email);
+        let user = sign_in_with(&pool, &verified, "code").await.unwrap();
+        assert_eq!(user.id, existing);
+
+        // A second Google account can't take over the linked email
+        let intruder = MockProvider::new(&format!("other-{}", run), &email);
+        let error = sign_in_with(&pool, &intruder, "code").await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(existing).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unverified_accounts_with_a_password_arent_linked() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Someone registered the owner's address before they did
+        let run = Uuid::new_v4();
+        let email = format!("{}@gmail.example", run);
+        let squatter: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token, password_hash) VALUES ($1, $2, $3) RETURNING id",
+        )
+            .bind(&email)
+            .bind(run.simple().to_string())
+            .bind(auth::hash_password(Password("hunter2hunter2".to_string())).await)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let owner = MockProvider::new(&format!("sub-{}", run), &email);
+        let error = sign_in_with(&pool, &owner, "code").await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+
+        let linked: Option<String> = sqlx::query_scalar("SELECT oauth_subject FROM users WHERE id = $1")
+            .bind(squatter)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(linked, None);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(squatter).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn login_needs_a_password() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let email = format!("{}@example.com", run);
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(&email)
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let email_only = LoginRequest { email: email.clone(), password: None };
+        let error = login(State(pool.clone()), Json(email_only)).await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNAUTHORIZED);
+
+        // An account without a password matches no password at all
+        let guess = LoginRequest { email: email.clone(), password: Some(Password("anything at all".to_string())) };
+        let error = login(State(pool.clone()), Json(guess)).await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNAUTHORIZED);
+
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+            .bind(user)
+            .bind(auth::hash_password(Password("hunter2hunter2".to_string())).await)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let wrong = LoginRequest { email: email.clone(), password: Some(Password("hunter3hunter3".to_string())) };
+        let error = login(State(pool.clone()), Json(wrong)).await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::UNAUTHORIZED);
+        let right = LoginRequest { email: email.clone(), password: Some(Password("hunter2hunter2".to_string())) };
+        let Json(signed_in) = login(State(pool.clone()), Json(right)).await.unwrap();
+        assert_eq!(signed_in.user.id, user);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+    }
+}
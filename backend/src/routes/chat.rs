@@ -9,8 +9,12 @@
 //! ## Owner
 //! Ben (AI Engineer)
 //!
-//! ## Endpoint
-//! `POST /api/chat`
+//! ## Endpoints
+//! - `POST /api/chat`
+//! - `GET /api/chat/suggestions?user_id=`
+//! - `GET /api/chat/history?session_id=`
+//! - `DELETE /api/chat/history?session_id=`
+//! - `POST /api/chat/feedback`
 //!
 //! ## How It Works
 //! ```text
@@ -27,10 +31,11 @@
 //!    - Recent interactions (viewed jazz events)
 //!    - Location preference (downtown)
 //!
-//! 3. Send to LLM (Gemini) with context
+//! 3. Send to the LLM (`LLM_PROVIDER`, Gemini by default) with context
 //!    - System prompt (how to behave)
 //!    - User profile (personalization)
 //!    - Available tools (search_events, etc.)
+//!    - Earlier turns of the session
 //!    - User message
 //!
 //! 4. LLM decides what to do
@@ -45,17 +50,21 @@
 //! 6. LLM formats final response
 //!    "I found 3 concerts this weekend! Here's what's happening..."
 //!
-//! 7. Return response to user
-//!    { "reply": "I found 3 concerts...", "events": [...] }
+//! 7. Store both turns in the session, return response to user
+//!    { "reply": "I found 3 concerts...", "events": [...], "session_id": "..." }
 //! ```
 //!
 //! ## Request Format
 //! ```json
 //! {
 //!   "message": "What's happening this weekend?",
-//!   "user_id": "94c99eb0-21f3-4f7e-afee-f533b964a2d4"  // Optional
+//!   "user_id": "94c99eb0-21f3-4f7e-afee-f533b964a2d4",  // Optional
+//!   "session_id": "5d0c7c43-..."                         // Optional
 //! }
 //! ```
+//! Personalization needs `Authorization: Bearer <token>`. A `user_id` in
+//! the body is optional and must be the token's user; the token alone is
+//! enough.
 //!
 //! ## Response Format
 //! ```json
@@ -68,12 +77,38 @@
 //!       "start_time": "2026-01-24T20:00:00Z",
 //!       ...
 //!     }
-//!   ]
+//!   ],
+//!   "degraded": false,
+//!   "needs_clarification": false,
+//!   "suggested_replies": [],
+//!   "session_id": "5d0c7c43-...",
+//!   "message_id": "0b6a3f1e-..."
 //! }
 //! ```
 //!
+//! ## Sessions
+//! Send back the `session_id` of the last response to continue the
+//! conversation ("more like the second one"); leave it out to start a new
+//! one. The recent turns of the session go to the model, trimmed to a
+//! token budget (`services::chat_history`). A session belongs to whoever
+//! started it, signed in or not; history lookups with someone else's
+//! session id find nothing.
+//!
+//! ## Suggestions
+//! `GET /api/chat/suggestions` gives 4-6 starter prompts for an empty chat
+//! box, built from what's on this week (no model calls; see
+//! `services::suggestions`). Each has a `label` to show and a `message`
+//! to send when tapped.
+//!
+//! ## Feedback
+//! `POST /api/chat/feedback` rates a reply up or down, by the response's
+//! `message_id` or (for the latest reply) its `session_id`. Rating the
+//! same reply again replaces the rating. Admins review ratings, with the
+//! question, tool calls and profile behind each reply, at
+//! `GET /api/admin/chat/feedback`.
+//!
 //! ## Personalization
-//! If `user_id` is provided, the response will be personalized:
+//! If the user is signed in, the response will be personalized:
 //! - Events matching liked categories are highlighted
 //! - Events in disliked categories are deprioritized
 //! - User's location preference is considered
@@ -108,220 +143,1197 @@
 //!       - Any particular vibe? (Chill, energetic, family-friendly)
 //!       - Indoor or outdoor?"
 //! ```
+//! When the model answers without searching and the message names neither
+//! a date nor a category (per the intent parser), the response has
+//! `needs_clarification: true` and two or three `suggested_replies`, e.g.
+//! `["Live music this weekend", "Food this weekend", "Anything tonight"]`,
+//! led by the user's favorite categories when signed in.
+//!
+//! ## When the Model Is Unavailable
+//! A missing API key (`GEMINI_API_KEY`), an unreachable API, or a model
+//! still rate limited after the provider's retries doesn't fail the
+//! request: `llm::fallback_answer` pulls a category, a relative date and
+//! keywords out of the message, runs the same event search, and answers
+//! with a template ("Here's what I found for 'jazz' this weekend:").
+//! The response has `degraded: true` so the frontend can badge it.
 //!
-//! ## Implementation Status
-//! 🚧 **NOT YET IMPLEMENTED** - Ben to build this out
+//! ## Rate Limits
+//! `POST /api/chat` allows `CHAT_REQUESTS_PER_MINUTE` per signed-in user
+//! with a verified email, or per IP address otherwise (anyone can mint
+//! guest tokens and register new emails, so those can't get a bucket of
+//! their own); past that it answers `429` with a `Retry-After` header. The admin key skips the limit. Once
+//! the day's `LLM_DAILY_CALL_BUDGET` is spent, chat keeps answering,
+//! degraded as above. See `services::rate_limit`.
+//!
+//! ## Debugging
+//! An admin (`X-Admin-Key`) can send `"debug": true` to get a `trace`
+//! with the response: the search filters used, each tool run with its
+//! row count and latency, each model call with its tokens and latency,
+//! the model name, the system prompt's version and the total time.
+//! Without a valid key the request is refused (`401`, or `503` if
+//! `ADMIN_API_KEY` isn't set) rather than answered without the trace. The trace is collected for every message
+//! (the model calls also go to `llm_calls`); only showing it is gated.
+//!
+//! ## Errors
+//! If the model fails any other way, the response is a `502` with the
+//! usual `{"error": {...}}` body and the cause is logged.
 //!
 //! ## Dependencies
 //! - `services::llm` - LLM integration functions
+//! - `services::llm_provider` - The model, from the router state
+//! - `services::chat_history` - Stored session turns
 //! - `models::Event` - Event data structure
 //! - `models::UserProfile` - User preferences and history
 
 // =============================================================================
-// IMPORTS (uncomment when implementing)
+// IMPORTS
+// =============================================================================
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts, Query, State},
+    http::{request::Parts, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::users::load_profile;
+use crate::auth::{self, MaybeAuthUser};
+use crate::error::AppError;
+use crate::models::{ChatFeedback, ChatHistory, ChatSuggestion, CreateChatFeedback, Event};
+use crate::routes::AppState;
+use crate::services::chat_history;
+use crate::services::intent_cache::IntentCache;
+use crate::services::llm::{self, ChatTrace};
+use crate::services::llm_provider::{LlmProvider, SharedProvider};
+use crate::services::prompt::{PromptStore, SystemPrompt};
+use crate::services::rate_limit::{ChatRateLimiter, ClientKey};
+use crate::services::suggestions::{self, SuggestionCache};
+
+// =============================================================================
+// CONFIGURATION
 // =============================================================================
 
-// use axum::{
-//     extract::State,
-//     http::StatusCode,
-//     routing::post,
-//     Json, Router,
-// };
-// use serde::{Deserialize, Serialize};
-// use sqlx::PgPool;
-// use uuid::Uuid;
-//
-// use crate::models::Event;
-// use crate::services::llm;
+/// Longest message accepted, in characters.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// Longest feedback comment accepted, in characters.
+const MAX_COMMENT_CHARS: usize = 2000;
 
 // =============================================================================
 // REQUEST/RESPONSE TYPES
 // =============================================================================
 
-// /// Incoming chat request from the frontend.
-// ///
-// /// # Fields
-// /// - `message`: The user's natural language query (required)
-// /// - `user_id`: User's UUID for personalization (optional)
-// ///
-// /// # Example
-// /// ```json
-// /// {
-// ///   "message": "What concerts are happening this weekend?",
-// ///   "user_id": "94c99eb0-21f3-4f7e-afee-f533b964a2d4"
-// /// }
-// /// ```
-// #[derive(Deserialize)]
-// pub struct ChatRequest {
-//     /// The user's natural language message
-//     pub message: String,
-//
-//     /// Optional user ID for personalized recommendations
-//     /// If provided, we fetch their profile and use it for context
-//     pub user_id: Option<Uuid>,
-// }
-
-// /// Response from the chat endpoint.
-// ///
-// /// # Fields
-// /// - `reply`: The conversational response from the LLM
-// /// - `events`: Array of events that match the query (may be empty)
-// ///
-// /// # Why Both?
-// /// - `reply` is for display in the chat UI
-// /// - `events` allows the frontend to render event cards/links
-// ///
-// /// # Example
-// /// ```json
-// /// {
-// ///   "reply": "I found 3 concerts this weekend! 🎵\n\n1. Jazz Night...",
-// ///   "events": [
-// ///     { "id": "...", "title": "Jazz Night", ... },
-// ///     { "id": "...", "title": "Rock Festival", ... }
-// ///   ]
-// /// }
-// /// ```
-// #[derive(Serialize)]
-// pub struct ChatResponse {
-//     /// Conversational reply from the LLM
-//     pub reply: String,
-//
-//     /// Events matching the query (for frontend to display as cards)
-//     pub events: Vec<Event>,
-// }
+/// Incoming chat request from the frontend.
+///
+/// # Fields
+/// - `message`: The user's natural language query (required)
+/// - `user_id`: User's UUID for personalization (optional; must match the
+///   bearer token)
+/// - `session_id`: Session to continue (optional; a new one is started
+///   without it)
+/// - `debug`: Return the `trace` of the answer (admins only)
+///
+/// # Example
+/// ```json
+/// {
+///   "message": "What concerts are happening this weekend?",
+///   "user_id": "94c99eb0-21f3-4f7e-afee-f533b964a2d4",
+///   "session_id": "5d0c7c43-2f0e-4a43-9f0b-6f1c8a3e9b21"
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct ChatRequest {
+    /// The user's natural language message
+    pub message: String,
+
+    /// Optional user ID for personalized recommendations.
+    /// If provided, we fetch their profile and use it for context
+    pub user_id: Option<Uuid>,
+
+    /// The `session_id` of an earlier response, to continue that
+    /// conversation
+    pub session_id: Option<Uuid>,
+
+    /// Include the answer's `trace`; needs `X-Admin-Key`
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// Query string of the suggestions endpoint.
+#[derive(Debug, Deserialize)]
+pub struct SuggestionsQuery {
+    /// Personalize for this user (optional; must match the bearer token)
+    pub user_id: Option<Uuid>,
+}
+
+/// Query string of the history endpoints.
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub session_id: Uuid,
+}
+
+/// Response from the chat endpoint.
+///
+/// # Fields
+/// - `reply`: The conversational response from the LLM
+/// - `events`: Array of events that match the query (may be empty)
+/// - `degraded`: `true` when the model was unavailable and `reply` is a
+///   template around a plain search
+/// - `session_id`: Send it with the next message to continue the
+///   conversation
+/// - `message_id`: The stored reply, for `POST /api/chat/feedback`
+///
+/// # Why Both?
+/// - `reply` is for display in the chat UI
+/// - `events` allows the frontend to render event cards/links
+///
+/// # Example
+/// ```json
+/// {
+///   "reply": "I found 3 concerts this weekend! 🎵\n\n1. Jazz Night...",
+///   "events": [
+///     { "id": "...", "title": "Jazz Night", ... },
+///     { "id": "...", "title": "Rock Festival", ... }
+///   ],
+///   "degraded": false,
+///   "session_id": "5d0c7c43-2f0e-4a43-9f0b-6f1c8a3e9b21",
+///   "message_id": "0b6a3f1e-7c2d-4e55-8a1b-2f9d4c6e8a10"
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct ChatResponse {
+    /// Conversational reply from the LLM
+    pub reply: String,
+
+    /// Events matching the query (for frontend to display as cards): each
+    /// once, in the order `reply` mentions them, at most 20
+    pub events: Vec<Event>,
+
+    /// The model was unavailable; `reply` comes from a template
+    pub degraded: bool,
+
+    /// `reply` asks the user to narrow the request down
+    pub needs_clarification: bool,
+
+    /// Quick replies to show as chips with a clarifying question (empty
+    /// otherwise); send one back as the next message
+    pub suggested_replies: Vec<String>,
+
+    /// The session this exchange was stored in
+    pub session_id: Uuid,
+
+    /// The reply's stored turn (`null` if it couldn't be stored)
+    pub message_id: Option<Uuid>,
+
+    /// How the answer came about; only with `debug: true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<ChatTrace>,
+}
 
 // =============================================================================
 // ROUTE DEFINITIONS
 // =============================================================================
 
-// /// Creates the router for chat endpoints.
-// ///
-// /// # Routes
-// /// - `POST /` -> `chat()` - Process a chat message
-// ///
-// /// # Future Routes
-// /// - `GET /history` - Get chat history for a user
-// /// - `DELETE /history` - Clear chat history
-// pub fn routes() -> Router<PgPool> {
-//     Router::new()
-//         .route("/", post(chat))
-// }
+/// Creates the router for chat endpoints.
+///
+/// # Routes
+/// - `POST /` -> `chat()` - Process a chat message
+/// - `GET /suggestions` -> `chat_suggestions()` - Starter prompts
+/// - `GET /history` -> `history()` - A session's stored turns
+/// - `DELETE /history` -> `clear_history()` - Forget a session
+/// - `POST /feedback` -> `feedback()` - Rate a reply
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(chat))
+        .route("/suggestions", get(chat_suggestions))
+        .route("/history", get(history).delete(clear_history))
+        .route("/feedback", post(feedback))
+}
 
 // =============================================================================
 // HANDLER: CHAT
 // =============================================================================
 
-// /// Processes a natural language chat message and returns event recommendations.
-// ///
-// /// # Endpoint
-// /// `POST /api/chat`
-// ///
-// /// # Request Body
-// /// ```json
-// /// {
-// ///   "message": "What's happening this weekend?",
-// ///   "user_id": "94c99eb0-..."  // optional
-// /// }
-// /// ```
-// ///
-// /// # Returns
-// /// - `200 OK` with ChatResponse containing reply and events
-// /// - `500 Internal Server Error` if LLM or database fails
-// ///
-// /// # Implementation Steps
-// /// 1. Extract user profile if user_id provided
-// /// 2. Call LLM service with message and context
-// /// 3. Return formatted response with matching events
-// async fn chat(
-//     State(pool): State<PgPool>,
-//     Json(payload): Json<ChatRequest>,
-// ) -> Result<Json<ChatResponse>, StatusCode> {
-//
-//     // Step 1: Fetch user profile for personalization (if user_id provided)
-//     let user_profile = if let Some(user_id) = payload.user_id {
-//         // Fetch profile using existing endpoint logic
-//         // This gives us preferences and recent interactions
-//         fetch_user_profile(&pool, user_id).await.ok()
-//     } else {
-//         None
-//     };
-//
-//     // Step 2: Process the message with LLM
-//     // This handles the full conversation loop:
-//     // - Sending to Gemini
-//     // - Executing any tool calls (searches)
-//     // - Formatting the response
-//     let (reply, events) = llm::process_chat_message(
-//         payload.user_id.unwrap_or_default(),
-//         &payload.message,
-//         &pool,
-//     )
-//     .await
-//     .map_err(|e| {
-//         eprintln!("LLM error: {}", e);
-//         StatusCode::INTERNAL_SERVER_ERROR
-//     })?;
-//
-//     // Step 3: Return the response
-//     Ok(Json(ChatResponse { reply, events }))
-// }
-
-// /// Helper function to fetch user profile for personalization.
-// async fn fetch_user_profile(
-//     pool: &PgPool,
-//     user_id: Uuid,
-// ) -> Result<crate::models::UserProfile, sqlx::Error> {
-//     // Reuse the profile query logic from users.rs
-//     // This is a simplified version - in production, consider
-//     // extracting this to a shared service
-//
-//     let user = sqlx::query_as::<_, crate::models::User>(
-//         "SELECT id, email, name, location_preference, created_at FROM users WHERE id = $1"
-//     )
-//     .bind(user_id)
-//     .fetch_one(pool)
-//     .await?;
-//
-//     let preferences = sqlx::query_as::<_, crate::models::UserPreference>(
-//         "SELECT id, user_id, category, weight, created_at FROM user_preferences WHERE user_id = $1"
-//     )
-//     .bind(user_id)
-//     .fetch_all(pool)
-//     .await?;
-//
-//     let recent_interactions = sqlx::query_as::<_, crate::models::UserInteractionWithEvent>(
-//         r#"
-//         SELECT ui.interaction_type, e.title as event_title, e.category as event_category, ui.created_at
-//         FROM user_interactions ui
-//         JOIN events e ON ui.event_id = e.id
-//         WHERE ui.user_id = $1
-//         ORDER BY ui.created_at DESC
-//         LIMIT 20
-//         "#
-//     )
-//     .bind(user_id)
-//     .fetch_all(pool)
-//     .await?;
-//
-//     Ok(crate::models::UserProfile {
-//         user,
-//         preferences,
-//         recent_interactions,
-//     })
-// }
+/// Processes a natural language chat message and returns event recommendations.
+///
+/// # Endpoint
+/// `POST /api/chat`
+///
+/// # Request Body
+/// ```json
+/// {
+///   "message": "What's happening this weekend?",
+///   "user_id": "94c99eb0-...",    // optional
+///   "session_id": "5d0c7c43-...", // optional
+///   "debug": true                 // optional, admins only
+/// }
+/// ```
+///
+/// # Returns
+/// - `200 OK` with ChatResponse containing reply and events (and `trace`
+///   when debugging)
+/// - `401 Unauthorized` if `user_id` is sent without a valid token, or
+///   `debug` without a valid `X-Admin-Key`
+/// - `403 Forbidden` if `user_id` isn't the token's user
+/// - `422 Unprocessable Entity` if the message is blank or too long
+/// - `429 Too Many Requests` with `Retry-After` past the caller's rate
+///   limit (`services::rate_limit`)
+/// - `502 Bad Gateway` if the model fails (other than being unavailable,
+///   which gives a `degraded` 200)
+/// - `503 Service Unavailable` for `debug` if `ADMIN_API_KEY` isn't set
+async fn chat(
+    quota: ChatQuota,
+    State(pool): State<PgPool>,
+    State(llm): State<SharedProvider>,
+    State(intent_cache): State<Arc<IntentCache>>,
+    State(prompt): State<Arc<PromptStore>>,
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    Json(payload): Json<ChatRequest>,
+) -> Result<Json<ChatResponse>, AppError> {
+    if payload.debug {
+        let expected = std::env::var("ADMIN_API_KEY").ok();
+        auth::check_admin_key(expected.as_deref(), quota.admin_key.as_deref())?;
+    }
+
+    let user_id = personalization_user(payload.user_id, viewer)?;
+    let prompt = prompt.current();
+    let mut response = respond(&pool, llm.as_ref(), &intent_cache, &prompt, user_id, payload.session_id, &payload.message).await?;
+    if !payload.debug {
+        response.trace = None;
+    }
+    Ok(Json(response))
+}
+
+/// The chat flow behind the handler, with the provider passed in.
+///
+/// 1. Fetch the user's profile (preferences, follows, recent activity) if
+///    they are signed in
+/// 2. Load the session's earlier turns (none if that fails)
+/// 3. Hand the message, history and profile to `llm::process_chat_message`,
+///    which lets the model search events through tool calls and write the
+///    reply (or falls back to a plain search if the model is unavailable)
+/// 4. Store the exchange in the session (best effort: a failed insert is
+///    logged and the reply still goes out)
+async fn respond(
+    pool: &PgPool,
+    provider: &dyn LlmProvider,
+    intent_cache: &IntentCache,
+    prompt: &SystemPrompt,
+    user_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+    message: &str,
+) -> Result<ChatResponse, AppError> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(AppError::invalid("message", "must not be empty"));
+    }
+    if message.chars().count() > MAX_MESSAGE_CHARS {
+        return Err(AppError::invalid(
+            "message",
+            &format!("must be at most {} characters", MAX_MESSAGE_CHARS),
+        ));
+    }
+
+    let profile = match user_id {
+        Some(id) => Some(load_profile(pool, id).await?),
+        None => None,
+    };
+
+    let session_id = session_id.unwrap_or_else(Uuid::new_v4);
+    let asked_at = Utc::now();
+    let history = chat_history::context(pool, session_id, user_id).await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, session_id = %session_id, "could not load chat history");
+        Vec::new()
+    });
+
+    let answer = llm::process_chat_message(provider, pool, message, &history, profile.as_ref(), intent_cache, prompt)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, user_id = ?user_id, "chat failed");
+            AppError::from(e)
+        })?;
+
+    let profile_snapshot = profile.as_ref().map(|p| llm::format_profile_for_llm(p, &llm::ContextBudget::from_env()));
+    let exchange = chat_history::Exchange {
+        asked_at,
+        message,
+        reply: &answer.reply,
+        tool_calls: &answer.tool_calls,
+        profile_snapshot: profile_snapshot.as_deref(),
+    };
+    let message_id = match chat_history::append(pool, session_id, user_id, exchange).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::warn!(error = %e, session_id = %session_id, "could not store chat turns");
+            None
+        }
+    };
+
+    Ok(ChatResponse {
+        reply: answer.reply,
+        events: answer.events,
+        degraded: answer.degraded,
+        needs_clarification: answer.needs_clarification,
+        suggested_replies: answer.suggested_replies,
+        session_id,
+        message_id,
+        trace: Some(answer.trace),
+    })
+}
+
+// =============================================================================
+// HANDLER: SUGGESTIONS
+// =============================================================================
+
+/// Starter prompts for an empty chat box.
+///
+/// # Endpoint
+/// `GET /api/chat/suggestions?user_id=<uuid>`
+///
+/// Without `user_id` (or a bearer token) the prompts aren't personalized.
+/// With no upcoming events they are static defaults. Cached per user for
+/// 15 minutes.
+///
+/// # Returns
+/// - `200 OK` with 4-6 `ChatSuggestion`s
+/// - `401 Unauthorized` if `user_id` is sent without a valid token
+/// - `403 Forbidden` if `user_id` isn't the token's user
+async fn chat_suggestions(
+    State(pool): State<PgPool>,
+    State(cache): State<Arc<SuggestionCache>>,
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    Query(query): Query<SuggestionsQuery>,
+) -> Result<Json<Vec<ChatSuggestion>>, AppError> {
+    let user_id = personalization_user(query.user_id, viewer)?;
+    Ok(Json(suggestions::suggestions(&pool, &cache, user_id).await?))
+}
+
+// =============================================================================
+// HANDLERS: HISTORY
+// =============================================================================
+
+/// A chat session's turns, oldest first.
+///
+/// # Endpoint
+/// `GET /api/chat/history?session_id=<uuid>`
+///
+/// # Returns
+/// - `200 OK` with `ChatHistory`; `messages` is empty for an unknown
+///   session or one that belongs to someone else
+async fn history(
+    State(pool): State<PgPool>,
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<ChatHistory>, AppError> {
+    let messages = chat_history::load(&pool, query.session_id, viewer).await?;
+    Ok(Json(ChatHistory { session_id: query.session_id, messages }))
+}
+
+/// Deletes a chat session's turns.
+///
+/// # Endpoint
+/// `DELETE /api/chat/history?session_id=<uuid>`
+///
+/// # Returns
+/// - `204 No Content`, whether or not there was anything to delete
+async fn clear_history(
+    State(pool): State<PgPool>,
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    Query(query): Query<HistoryQuery>,
+) -> Result<StatusCode, AppError> {
+    chat_history::clear(&pool, query.session_id, viewer).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// HANDLER: FEEDBACK
+// =============================================================================
+
+/// Rates an assistant reply up or down.
+///
+/// # Endpoint
+/// `POST /api/chat/feedback`
+///
+/// # Request Body
+/// ```json
+/// { "message_id": "0b6a3f1e-...", "rating": "down", "comment": "That show was last week" }
+/// ```
+/// Send `session_id` instead of `message_id` to rate the session's latest
+/// reply. The reply must be the caller's (same user, or anonymous for
+/// anonymous chat).
+///
+/// # Returns
+/// - `200 OK` with the `ChatFeedback`; rating again replaces the rating
+///   and comment
+/// - `404 Not Found` if there's no such reply for the caller
+/// - `422 Unprocessable Entity` unless exactly one of `message_id` and
+///   `session_id` is given, or if the comment is too long
+async fn feedback(
+    State(pool): State<PgPool>,
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    Json(payload): Json<CreateChatFeedback>,
+) -> Result<Json<ChatFeedback>, AppError> {
+    let comment = payload.comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > MAX_COMMENT_CHARS) {
+        return Err(AppError::invalid(
+            "comment",
+            &format!("must be at most {} characters", MAX_COMMENT_CHARS),
+        ));
+    }
+
+    let mine = "role = 'assistant' AND user_id IS NOT DISTINCT FROM $2";
+    let message_id: Option<Uuid> = match (payload.message_id, payload.session_id) {
+        (Some(id), None) => {
+            sqlx::query_scalar(&format!("SELECT id FROM chat_messages WHERE id = $1 AND {}", mine))
+                .bind(id)
+                .bind(viewer)
+                .fetch_optional(&pool)
+                .await?
+        }
+        (None, Some(session_id)) => {
+            sqlx::query_scalar(&format!(
+                "SELECT id FROM chat_messages WHERE session_id = $1 AND {} ORDER BY created_at DESC LIMIT 1",
+                mine
+            ))
+                .bind(session_id)
+                .bind(viewer)
+                .fetch_optional(&pool)
+                .await?
+        }
+        _ => return Err(AppError::invalid("message_id", "give exactly one of message_id and session_id")),
+    };
+    let message_id = message_id.ok_or_else(|| AppError::not_found("chat message"))?;
+
+    let feedback = sqlx::query_as::<_, ChatFeedback>(
+        r#"
+        INSERT INTO chat_feedback (message_id, rating, comment)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (message_id) DO UPDATE
+        SET rating = EXCLUDED.rating, comment = EXCLUDED.comment, updated_at = NOW()
+        RETURNING id, message_id, rating, comment, created_at, updated_at
+        "#,
+    )
+        .bind(message_id)
+        .bind(payload.rating)
+        .bind(comment)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok(Json(feedback))
+}
+
+/// Whose profile personalizes the reply.
+///
+/// The bearer token decides; a `user_id` in the body is only accepted when
+/// it names the same user, so nobody can chat with someone else's history.
+fn personalization_user(requested: Option<Uuid>, viewer: Option<Uuid>) -> Result<Option<Uuid>, AppError> {
+    match (requested, viewer) {
+        (None, viewer) => Ok(viewer),
+        (Some(requested), Some(viewer)) if requested == viewer => Ok(Some(viewer)),
+        (Some(_), Some(_)) => Err(AppError::Forbidden("token does not grant access to this user".to_string())),
+        (Some(_), None) => Err(AppError::Unauthorized("missing bearer token".to_string())),
+    }
+}
+
+// =============================================================================
+// RATE LIMIT
+// =============================================================================
+
+/// One request from the caller's chat rate limit: the signed-in user's,
+/// or the remote address's when anonymous, a guest or unverified.
+/// Requests with a valid `X-Admin-Key` aren't counted.
+///
+/// Rejects with `429 Too Many Requests` and `Retry-After` when the
+/// caller's bucket is empty.
+struct ChatQuota {
+    /// The `X-Admin-Key` sent, valid or not, for admin-only options
+    admin_key: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ChatQuota
+where
+    Arc<ChatRateLimiter>: FromRef<S>,
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Only checked when sent: without ADMIN_API_KEY every request
+        // would log the missing configuration
+        let admin_key = parts
+            .headers
+            .get(auth::ADMIN_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        if admin_key.is_some() {
+            let expected = std::env::var("ADMIN_API_KEY").ok();
+            if auth::check_admin_key(expected.as_deref(), admin_key.as_deref()).is_ok() {
+                return Ok(ChatQuota { admin_key });
+            }
+        }
+
+        let MaybeAuthUser(viewer) = MaybeAuthUser::from_request_parts(parts, state).await?;
+        let ip = ClientKey::Ip(
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |ConnectInfo(addr)| addr.ip()),
+        );
+        let client = match viewer {
+            Some(user_id) if has_own_bucket(&PgPool::from_ref(state), user_id).await => ClientKey::User(user_id),
+            _ => ip,
+        };
+
+        Arc::<ChatRateLimiter>::from_ref(state)
+            .check(client, Instant::now())
+            .map(|()| ChatQuota { admin_key })
+            .map_err(|wait| {
+                tracing::warn!(client = ?client, "chat rate limit reached");
+                AppError::TooManyRequests(wait.as_secs_f64().ceil() as u64)
+            })
+    }
+}
+
+/// Whether `user_id` is limited on their own: a signed-up user who has
+/// verified their email. Guests and unverified accounts cost nothing to
+/// make, so they share their address's bucket, as does a user that can't
+/// be looked up (an error never buys a bucket of its own).
+async fn has_own_bucket(pool: &PgPool, user_id: Uuid) -> bool {
+    let own = sqlx::query_scalar::<_, bool>(
+        "SELECT NOT is_guest AND email_verified_at IS NOT NULL FROM users WHERE id = $1",
+    )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await;
+    match own {
+        Ok(own) => own.unwrap_or(false),
+        Err(e) => {
+            tracing::warn!(error = %e, "couldn't look up chat user; limiting by address");
+            false
+        }
+    }
+}
 
 // =============================================================================
-// PLACEHOLDER - Ben to implement
+// TESTS
 // =============================================================================
 
-// When ready to implement:
-// 1. Uncomment the imports and types above
-// 2. Uncomment the routes() function
-// 3. Uncomment the chat() handler
-// 4. Implement the LLM service functions in services/llm.rs
-// 5. Uncomment the route registration in routes/mod.rs:
-//    .nest("/chat", chat::routes())
-// 6. Test with:
-//    curl -X POST http://localhost:3000/api/chat \
-//      -H "Content-Type: application/json" \
-//      -d '{"message": "What events are happening this weekend?"}'
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{Request, StatusCode}};
+    use serde_json::json;
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    use crate::services::llm::LlmError;
+    use crate::services::llm_provider::{GeminiProvider, LlmMessage, LlmResponse, MockProvider, Part, TokenUsage};
+    use crate::services::rate_limit::{BudgetedProvider, DailyCallBudget};
+
+    /// A model whose first turn calls `search_events` for `keyword`; once
+    /// the search results come back it answers with the profile's name (or
+    /// "anonymous") and how many events the tool returned.
+    fn searching_model(keyword: &'static str) -> MockProvider {
+        MockProvider::new(move |messages: &[LlmMessage], _| {
+            match messages.last().unwrap().parts.first() {
+                Some(Part::ToolResult(result)) => {
+                    let who = if messages[0].text().contains("- Name: Sam") { "Sam" } else { "anonymous" };
+                    Ok(LlmResponse::text(format!("{}: {} events", who, result.response["count"])))
+                }
+                _ => Ok(LlmResponse::tool_call("search_events", json!({ "query": keyword }))),
+            }
+        })
+    }
+
+    /// An upcoming event titled "<keyword> Night".
+    async fn insert_event(pool: &PgPool, keyword: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ($1, $2, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("{} Night", keyword))
+            .bind(format!("https://venue.example/{}", Uuid::new_v4()))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn body_user_must_be_the_token_user() {
+        let me = Uuid::new_v4();
+
+        assert_eq!(personalization_user(None, None).unwrap(), None);
+        assert_eq!(personalization_user(None, Some(me)).unwrap(), Some(me));
+        assert_eq!(personalization_user(Some(me), Some(me)).unwrap(), Some(me));
+        assert_eq!(personalization_user(Some(me), None).unwrap_err().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            personalization_user(Some(Uuid::new_v4()), Some(me)).unwrap_err().status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn answers_with_the_provider_from_the_router_state() {
+        // No database: history and the call log fail fast and are only logged
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let state = AppState {
+            pool,
+            llm: Arc::new(MockProvider::scripted(vec![LlmResponse::text("Hi, I'm Tully!")])),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let request = Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "message": "hello" }).to_string()))
+            .unwrap();
+
+        let response = routes().with_state(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["reply"], "Hi, I'm Tully!");
+        assert_eq!(body["events"], json!([]));
+        assert_eq!(body["degraded"], false);
+        assert!(body["session_id"].as_str().unwrap().parse::<Uuid>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn chat_is_rate_limited_per_client() {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let state = AppState {
+            pool,
+            llm: Arc::new(MockProvider::echo()),
+            intent_cache: Default::default(),
+            chat_limiter: Arc::new(ChatRateLimiter::new(2)),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+        let send = |admin_key: Option<&str>| {
+            let mut request = Request::post("/").header("content-type", "application/json");
+            if let Some(key) = admin_key {
+                request = request.header(auth::ADMIN_KEY_HEADER, key);
+            }
+            app.clone().oneshot(request.body(Body::from(json!({ "message": "hello" }).to_string())).unwrap())
+        };
+
+        assert_eq!(send(None).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send(None).await.unwrap().status(), StatusCode::OK);
+        let limited = send(None).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()["retry-after"], "30");
+
+        // The admin key skips the limit; a wrong one doesn't
+        std::env::set_var("ADMIN_API_KEY", "chat-test-admin-key");
+        assert_eq!(send(Some("chat-test-admin-key")).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send(Some("guess")).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn guests_and_unverified_users_share_their_address_limit() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Two guests, an account that never verified its email, and one that did
+        let mut users = Vec::new();
+        for (is_guest, verified) in [(true, false), (true, false), (false, false), (false, true)] {
+            let run = Uuid::new_v4();
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (email, calendar_token, is_guest, email_verified_at) \
+                 VALUES ($1, $2, $3, CASE WHEN $4 THEN NOW() END) RETURNING id",
+            )
+                .bind(format!("{}@example.com", run))
+                .bind(run.simple().to_string())
+                .bind(is_guest)
+                .bind(verified)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            users.push(id);
+        }
+        let state = AppState {
+            pool: pool.clone(),
+            llm: Arc::new(MockProvider::echo()),
+            intent_cache: Default::default(),
+            chat_limiter: Arc::new(ChatRateLimiter::new(2)),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+        let send = |user: Uuid| {
+            let (token, _) = auth::issue_token(user).unwrap();
+            let request = Request::post("/")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::from(json!({ "message": "hello" }).to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        // A fresh guest token doesn't get a fresh bucket
+        assert_eq!(send(users[0]).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send(users[1]).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send(users[1]).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+        // Nor does a fresh registration
+        assert_eq!(send(users[2]).await.unwrap().status(), StatusCode::TOO_MANY_REQUESTS);
+        // A verified user has their own
+        assert_eq!(send(users[3]).await.unwrap().status(), StatusCode::OK);
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)").bind(&users).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn the_trace_is_only_shown_to_admins_who_ask() {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        // Answers without searching; its intent parse names a category
+        let model = MockProvider::new(|_, options| {
+            let mut response = match options.json {
+                true => LlmResponse::text(r#"{"category": "music", "when": "tonight"}"#),
+                false => LlmResponse::text("Hi, I'm Tully!"),
+            };
+            response.usage = Some(TokenUsage { tokens_in: 120, tokens_out: 8 });
+            Ok(response)
+        });
+        let state = AppState {
+            pool,
+            llm: Arc::new(model),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+        let send = |body: serde_json::Value, admin_key: Option<&str>| {
+            let mut request = Request::post("/").header("content-type", "application/json");
+            if let Some(key) = admin_key {
+                request = request.header(auth::ADMIN_KEY_HEADER, key);
+            }
+            app.clone().oneshot(request.body(Body::from(body.to_string())).unwrap())
+        };
+        let json_body = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        std::env::set_var("ADMIN_API_KEY", "chat-test-admin-key");
+
+        let plain = json_body(send(json!({ "message": "music tonight" }), None).await.unwrap()).await;
+        assert_eq!(plain["reply"], "Hi, I'm Tully!");
+        assert!(plain.get("trace").is_none());
+        // The admin key alone doesn't add it
+        let keyed = json_body(send(json!({ "message": "music tonight" }), Some("chat-test-admin-key")).await.unwrap()).await;
+        assert!(keyed.get("trace").is_none());
+
+        let anonymous = send(json!({ "message": "music tonight", "debug": true }), None).await.unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+        let guessed = send(json!({ "message": "music tonight", "debug": true }), Some("guess")).await.unwrap();
+        assert_eq!(guessed.status(), StatusCode::UNAUTHORIZED);
+
+        // A new message, so the intent parse isn't answered from the cache
+        let debug = send(json!({ "message": "live music tonight", "debug": true }), Some("chat-test-admin-key")).await.unwrap();
+        assert_eq!(debug.status(), StatusCode::OK);
+        let trace = json_body(debug).await["trace"].clone();
+        assert_eq!(trace["provider"], "mock");
+        assert!(trace["prompt_version"].as_str().unwrap().starts_with("builtin-"));
+        assert_eq!(trace["model"], "mock");
+        assert_eq!(trace["search_params"][0]["category"], "music");
+        assert_eq!(trace["tool_calls"], json!([]));
+        let purposes: Vec<&str> = trace["model_calls"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|call| call["purpose"].as_str().unwrap())
+            .collect();
+        assert_eq!(purposes, ["chat", "intent"]);
+        assert_eq!(trace["model_calls"][0]["tokens_out"], 8);
+        assert!(trace["model_calls"][0]["latency_ms"].is_u64());
+        assert_eq!(trace["prompt_tokens"], 240);
+        assert!(trace["total_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn the_trace_records_each_search() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let keyword: &'static str = Box::leak(format!("mariachi{}", Uuid::new_v4().simple()).into_boxed_str());
+        let event = insert_event(&pool, keyword).await;
+
+        let searched = respond(&pool, &searching_model(keyword), &IntentCache::default(), &SystemPrompt::default(), None, None, "mariachi?").await.unwrap();
+        let trace = searched.trace.as_ref().unwrap();
+        assert_eq!(trace.search_params.len(), 1);
+        assert_eq!(trace.search_params[0].query.as_deref(), Some(keyword));
+        assert_eq!(trace.tool_calls.len(), 1);
+        assert_eq!(trace.tool_calls[0].name, "search_events");
+        assert_eq!(trace.tool_calls[0].args, json!({ "query": keyword }));
+        assert_eq!(trace.tool_calls[0].rows, Some(1));
+        // A tool round, then the answer
+        assert_eq!(trace.model_calls.len(), 2);
+        assert!(trace.model_calls.iter().all(|call| call.success && call.purpose == "chat"));
+
+        // The fallback search is traced the same way
+        let offline = MockProvider::new(|_, _| Err(LlmError::ServiceUnavailable));
+        let degraded = respond(&pool, &offline, &IntentCache::default(), &SystemPrompt::default(), None, None, &format!("any {}?", keyword)).await.unwrap();
+        let trace = degraded.trace.as_ref().unwrap();
+        assert_eq!(trace.tool_calls[0].rows, Some(1));
+        assert_eq!(trace.search_params[0].query.as_deref(), Some(keyword));
+        assert!(!trace.model_calls[0].success);
+
+        for response in [searched, degraded] {
+            chat_history::clear(&pool, response.session_id, None).await.unwrap();
+        }
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_spent_daily_budget_answers_with_a_plain_search() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let model: SharedProvider = Arc::new(MockProvider::new(|_, _| Ok(LlmResponse::text("Try the zoo."))));
+        let budgeted = BudgetedProvider::new(model, DailyCallBudget::new(Some(1)));
+
+        let first = respond(&pool, &budgeted, &IntentCache::default(), &SystemPrompt::default(), None, None, "anything zzznothing?").await.unwrap();
+        assert_eq!(first.reply, "Try the zoo.");
+        assert!(!first.degraded);
+
+        let second = respond(&pool, &budgeted, &IntentCache::default(), &SystemPrompt::default(), None, None, "anything zzznothing?").await.unwrap();
+        assert!(second.degraded);
+        assert!(second.reply.starts_with("I couldn't find anything for 'zzznothing'"));
+
+        for response in [first, second] {
+            chat_history::clear(&pool, response.session_id, None).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn vague_requests_get_a_clarifying_question_with_quick_replies() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Asks back instead of searching; its intent parse finds dates and
+        // categories only in "jazz this weekend"
+        let model = MockProvider::new(|messages: &[LlmMessage], options| {
+            let message = messages.last().unwrap().text();
+            if !options.json {
+                return Ok(LlmResponse::text("Happy to help! Today or this weekend? Any particular vibe?"));
+            }
+            Ok(LlmResponse::text(match message.as_str() {
+                "jazz this weekend" => r#"{"category": "music", "when": "this weekend"}"#,
+                _ => r#"{"query": "fun"}"#,
+            }))
+        });
+
+        let vague = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "find me something fun").await.unwrap();
+        assert!(vague.needs_clarification);
+        assert_eq!(vague.suggested_replies, ["Live music this weekend", "Food this weekend", "Anything tonight"]);
+        assert!(!vague.degraded);
+
+        let specific = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "jazz this weekend").await.unwrap();
+        assert!(!specific.needs_clarification);
+        assert!(specific.suggested_replies.is_empty());
+
+        // A model that searched isn't asking anything, however vague the message
+        let searched = respond(&pool, &searching_model("fun"), &IntentCache::default(), &SystemPrompt::default(), None, None, "find me something fun")
+            .await
+            .unwrap();
+        assert!(!searched.needs_clarification);
+
+        for response in [vague, specific, searched] {
+            chat_history::clear(&pool, response.session_id, None).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn llm_failures_are_a_bad_gateway() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+
+        let refusing = MockProvider::new(|_, _| Err(LlmError::Api { status: 400, message: "bad schema".to_string() }));
+        let error = respond(&pool, &refusing, &IntentCache::default(), &SystemPrompt::default(), None, None, "jazz tonight?").await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+
+        let blocked = MockProvider::new(|_, _| Err(LlmError::EmptyResponse("SAFETY".to_string())));
+        let error = respond(&pool, &blocked, &IntentCache::default(), &SystemPrompt::default(), None, None, "jazz tonight?").await.unwrap_err();
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn unavailable_models_fall_back_to_a_plain_search() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let keyword = format!("klezmer{}", Uuid::new_v4().simple());
+        let event = insert_event(&pool, &keyword).await;
+        let message = format!("any {} events?", keyword);
+
+        let unavailable = MockProvider::new(|_, _| Err(LlmError::ServiceUnavailable));
+        let busy = MockProvider::new(|_, _| Err(LlmError::RateLimited { attempts: 3, status: 429 }));
+        // Nothing listens on port 9 (discard)
+        let unreachable = GeminiProvider::with_endpoint("http://127.0.0.1:9/", Some("test-key".to_string()));
+        let unconfigured = GeminiProvider::with_endpoint("http://127.0.0.1:9/", None);
+        let providers: [&dyn LlmProvider; 4] = [&unavailable, &busy, &unreachable, &unconfigured];
+
+        for provider in providers {
+            let response = respond(&pool, provider, &IntentCache::default(), &SystemPrompt::default(), None, None, &message).await.unwrap();
+            assert!(response.degraded);
+            assert_eq!(response.reply, format!("Here's what I found for '{}':", keyword));
+            assert_eq!(response.events.iter().map(|e| e.id).collect::<Vec<_>>(), [event]);
+        }
+
+        let nothing = respond(&pool, &unavailable, &IntentCache::default(), &SystemPrompt::default(), None, None, "any zzzunheardof events tonight?").await.unwrap();
+        assert!(nothing.degraded && nothing.events.is_empty());
+        assert!(nothing.reply.starts_with("I couldn't find anything for 'zzzunheardof' tonight."));
+
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn searches_through_a_tool_call_then_answers() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let keyword: &'static str = Box::leak(format!("zydeco{}", run.simple()).into_boxed_str());
+        let event = insert_event(&pool, keyword).await;
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, name, calendar_token) VALUES ($1, 'Sam', $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let model = searching_model(keyword);
+
+        let anonymous = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "anything zydeco?").await.unwrap();
+        assert_eq!(anonymous.reply, "anonymous: 1 events");
+        assert_eq!(anonymous.events.iter().map(|e| e.id).collect::<Vec<_>>(), [event]);
+
+        let personal = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), Some(user), None, "anything zydeco?").await.unwrap();
+        assert_eq!(personal.reply, "Sam: 1 events");
+        assert_eq!(model.calls(), 4);
+
+        let blank = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "   ").await.unwrap_err();
+        assert_eq!(blank.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn sessions_carry_earlier_turns_and_can_be_cleared() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Replies with what it was sent before the new message
+        let model = MockProvider::new(|messages: &[LlmMessage], _| {
+            let earlier: Vec<String> = messages[1..messages.len() - 1].iter().map(|m| m.text()).collect();
+            Ok(LlmResponse::text(format!("earlier: [{}]", earlier.join(" | "))))
+        });
+
+        let first = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "any jazz?").await.unwrap();
+        assert_eq!(first.reply, "earlier: []");
+        let second = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, Some(first.session_id), "the second one?").await.unwrap();
+        assert_eq!(second.session_id, first.session_id);
+        assert_eq!(second.reply, "earlier: [any jazz? | earlier: []]");
+        let fresh = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "the second one?").await.unwrap();
+        assert_ne!(fresh.session_id, first.session_id);
+        assert_eq!(fresh.reply, "earlier: []");
+
+        let state = AppState {
+            pool: pool.clone(),
+            llm: Arc::new(model),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+        let uri = format!("/history?session_id={}", first.session_id);
+        let get_history = || Request::get(&uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get_history()).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let turns: Vec<(&str, &str)> = body["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| (m["role"].as_str().unwrap(), m["content"].as_str().unwrap()))
+            .collect();
+        assert_eq!(turns, [
+            ("user", "any jazz?"),
+            ("assistant", "earlier: []"),
+            ("user", "the second one?"),
+            ("assistant", "earlier: [any jazz? | earlier: []]"),
+        ]);
+
+        let cleared = app.clone().oneshot(Request::delete(&uri).body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(cleared.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(get_history()).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["messages"], json!([]));
+
+        chat_history::clear(&pool, fresh.session_id, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn feedback_is_one_rating_per_reply() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let model = MockProvider::new(|_, _| Ok(LlmResponse::text("Try the zoo.")));
+        let answer = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "something fun?").await.unwrap();
+        let message_id = answer.message_id.unwrap();
+
+        let state = AppState {
+            pool: pool.clone(),
+            llm: Arc::new(model),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+        let rate = |body: serde_json::Value| {
+            let request = Request::post("/feedback")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let json_body = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        let missing = rate(json!({ "message_id": Uuid::new_v4(), "rating": "down" })).await.unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let ambiguous = rate(json!({ "message_id": message_id, "session_id": answer.session_id, "rating": "up" })).await.unwrap();
+        assert_eq!(ambiguous.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let first = rate(json!({ "message_id": message_id, "rating": "down", "comment": " closed today " })).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let first = json_body(first).await;
+        assert_eq!(first["rating"], "down");
+        assert_eq!(first["comment"], "closed today");
+
+        // By session: the same (latest) reply, so the rating is replaced
+        let second = json_body(rate(json!({ "session_id": answer.session_id, "rating": "up" })).await.unwrap()).await;
+        assert_eq!(second["id"], first["id"]);
+        assert_eq!(second["rating"], "up");
+        assert_eq!(second["comment"], serde_json::Value::Null);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM chat_feedback WHERE message_id = $1")
+            .bind(message_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        chat_history::clear(&pool, answer.session_id, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn tool_calls_stop_after_the_limit() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let keyword: &'static str = Box::leak(format!("polka{}", Uuid::new_v4().simple()).into_boxed_str());
+        let event = insert_event(&pool, keyword).await;
+
+        // A model that searches forever unless tools are switched off
+        let model = MockProvider::new(move |messages: &[LlmMessage], options| {
+            if options.tools_disabled {
+                // system + user, then a call and its results per round
+                let rounds = (messages.len() - 2) / 2;
+                Ok(LlmResponse::text(format!("gave up after {} rounds", rounds)))
+            } else {
+                Ok(LlmResponse::tool_call("search_events", json!({ "query": keyword })))
+            }
+        });
+
+        let response = respond(&pool, &model, &IntentCache::default(), &SystemPrompt::default(), None, None, "polka?").await.unwrap();
+        assert_eq!(response.reply, format!("gave up after {} rounds", llm::MAX_TOOL_ITERATIONS));
+        // Found by every round, returned once
+        assert_eq!(response.events.iter().map(|e| e.id).collect::<Vec<_>>(), [event]);
+
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+}
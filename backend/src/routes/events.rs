@@ -5,10 +5,25 @@
 //! local happenings that users want to discover.
 //!
 //! ## Endpoints
-//! - `GET  /api/events`         - List all events (sorted by start time)
+//! - `GET  /api/events`         - List upcoming events (cursor or page pagination)
 //! - `POST /api/events`         - Create a new event
-//! - `GET  /api/events/:id`     - Get a single event by UUID
-//! - `GET  /api/events/search`  - Search with multiple filters
+//! - `GET  /api/events/:id`     - Get a single event by UUID, with every place it's listed
+//! - `PATCH /api/events/:id`    - Update some fields of an event (incl. unarchive)
+//! - `GET  /api/events/search`  - Search with multiple filters (incl. radius)
+//! - `GET  /api/events/trending` - Most popular upcoming events this week
+//! - `POST /api/events/batch`   - Fetch up to 100 events by UUID, in order
+//! - `GET  /api/events/now`     - Events happening right now
+//! - `GET  /api/events/tonight` - Events between 5 PM and 4 AM local time
+//! - `GET  /api/events/calendar` - Event counts per day for a month
+//! - `GET  /api/events/:id/ics` - Download an event as an iCalendar file
+//! - `GET  /api/events/:id/jsonld` - Event as a schema.org JSON-LD object
+//! - `GET  /api/events/:id/related` - Upcoming events like this one
+//! - `GET  /api/events/:id/stats` - Interaction counts, total and per day
+//! - `POST /api/events/:id/merge` - Merge a duplicate event into this one
+//!
+//! ## Authentication
+//! Create, update and merge need `X-Admin-Key` (401 without a valid key,
+//! 503 if `ADMIN_API_KEY` isn't set). Everything else is public.
 //!
 //! ## Owner
 //! Will (Coordinator/Backend Lead)
@@ -19,97 +34,912 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    routing::get,
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, patch, post},
     Json,
     Router,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::Deserialize;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
-use crate::models::{Event, CreateEvent};
+use crate::auth::{self, MaybeAuthUser};
+use crate::db::{
+    not_dismissed_by, take_page, Cursor, Pagination, EVENT_COLUMNS, KEYSET_ORDER, NOT_ARCHIVED_FILTER, NOT_CANCELLED_FILTER,
+    UPCOMING_FILTER,
+};
+use crate::error::AppError;
+use crate::models::{
+    CalendarDay, CalendarMonth, CreateEvent, Event, EventDetail, EventPage, EventStats, EventWithDistance,
+    MergeEventsRequest, MergeReport, TrendingEvent, UpdateEvent,
+};
+use crate::routes::AppState;
+use crate::services::analytics::{self, TRENDING_WINDOW_DAYS};
+use crate::services::attribution;
+use crate::services::dates;
+use crate::services::geo;
+use crate::services::ics::IcsCalendar;
+use crate::services::jsonld::JsonLdEvent;
+use crate::services::merge;
+use crate::services::search::{self, filter_conditions, normalize_tags, SearchQuery};
+use super::venues::find_or_create_venue;
 
 // =============================================================================
 // ROUTE DEFINITIONS
 // =============================================================================
 
 /// Creates the router for all event endpoints.
-pub fn routes() -> Router<PgPool> {
+///
+/// Reads are public. Writes (create, update, merge) are for scrapers and
+/// admins and need `X-Admin-Key` (see `auth::require_admin_key`).
+pub fn routes() -> Router<AppState> {
+    let admin = Router::new()
+        .route("/", post(create_event))
+        .route("/:id", patch(update_event))
+        .route("/:id/merge", post(merge_event))
+        .route_layer(middleware::from_fn(auth::require_admin_key));
+
     Router::new()
-        .route("/", get(list_events).post(create_event))
+        .route("/", get(list_events))
         .route("/search", get(search_events))
+        .route("/trending", get(trending_events))
+        .route("/batch", post(batch_events))
+        .route("/now", get(happening_now))
+        .route("/tonight", get(tonight_events))
+        .route("/calendar", get(event_calendar))
         .route("/:id", get(get_event))
+        .route("/:id/ics", get(get_event_ics))
+        .route("/:id/jsonld", get(get_event_jsonld))
+        .route("/:id/related", get(related_events))
+        .route("/:id/stats", get(event_stats))
+        .merge(admin)
 }
 
 // =============================================================================
 // HANDLER: LIST ALL EVENTS
 // =============================================================================
 
-/// Returns all upcoming events, sorted by start time.
+/// Query parameters for the list endpoint.
+///
+/// Supports two pagination styles:
+/// - **Cursor**: pass the `next_cursor` from the previous response as `cursor`.
+///   Stable while scrapers are inserting events; preferred for infinite scroll.
+/// - **Page**: `page` (1-indexed) and `per_page`, for clients that need
+///   numbered pages. Ignored when `cursor` is present.
+///
+/// # Examples
+/// - `/events` - First page of upcoming and in-progress events
+/// - `/events?cursor=AAYxxVd3...` - Resume after the previous page
+/// - `/events?page=2&per_page=20` - Offset-based paging
+/// - `/events?include_past=true` - Include events that have already ended
+/// - `/events?include_cancelled=true` - Include cancelled events
+/// - `/events?include_dismissed=true` - Signed in, but show dismissed events too
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// Include events that have already ended (default: false)
+    #[serde(default)]
+    pub include_past: bool,
+
+    /// Include cancelled events (default: false)
+    #[serde(default)]
+    pub include_cancelled: bool,
+
+    /// Include soft-archived events (default: false)
+    #[serde(default)]
+    pub include_archived: bool,
+
+    /// Include events the signed-in user dismissed (default: false)
+    #[serde(default)]
+    pub include_dismissed: bool,
+
+    /// Opaque cursor from a previous response's `next_cursor`
+    pub cursor: Option<String>,
+
+    /// Page number for offset pagination (1-indexed, default: 1)
+    pub page: Option<u32>,
+
+    /// Results per page (default: 100, max: 100)
+    pub per_page: Option<u32>,
+}
+
+/// Returns a page of events sorted by start time.
 ///
 /// # Endpoint
 /// `GET /api/events`
+///
+/// By default only events that haven't finished yet are returned
+/// (see `db::UPCOMING_FILTER`), and cancelled events are hidden.
+/// Pass `include_past=true` / `include_cancelled=true` to include them.
+/// Events that ended long ago are archived and also need
+/// `include_archived=true`.
+///
+/// With a bearer token, events the user dismissed (and hasn't saved
+/// since) are left out unless `include_dismissed=true`.
+///
+/// # Returns
+/// - `200 OK` with an `EventPage`
+/// - `400 Bad Request` if `cursor` is malformed
+/// - `401 Unauthorized` if a bearer token is sent but invalid
 async fn list_events(
     State(pool): State<PgPool>,
-) -> Result<Json<Vec<Event>>, StatusCode> {
-    let events = sqlx::query_as::<_, Event>(
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    Query(params): Query<ListQuery>,
+) -> Result<Json<EventPage>, AppError> {
+    let pagination = Pagination::new(params.page, params.per_page);
+    let cursor = match params.cursor {
+        Some(ref raw) => Some(Cursor::decode(raw).ok_or_else(|| AppError::BadRequest("invalid cursor".into()))?),
+        None => None,
+    };
+
+    let mut conditions: Vec<String> = vec![];
+    if !params.include_past {
+        conditions.push(UPCOMING_FILTER.to_string());
+    }
+    if !params.include_cancelled {
+        conditions.push(NOT_CANCELLED_FILTER.to_string());
+    }
+    if !params.include_archived {
+        conditions.push(NOT_ARCHIVED_FILTER.to_string());
+    }
+    let hide_dismissed = viewer.filter(|_| !params.include_dismissed);
+    if hide_dismissed.is_some() {
+        conditions.push(not_dismissed_by("$1", "$2", "$3"));
+    }
+    if cursor.is_some() {
+        let first_param = if hide_dismissed.is_some() { 4 } else { 1 };
+        conditions.push(Cursor::after_condition(first_param));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    // A cursor already encodes the position, so OFFSET only applies without one
+    let offset = if cursor.is_some() { 0 } else { pagination.offset() };
+
+    // Fetch one extra row to find out whether there's a next page
+    let query = format!(
         r#"
-        SELECT id, title, description, venue, venue_address, location,
-               source_url, source_name, start_time, end_time, categories,
-               price_min, price_max, outdoor, family_friendly, image_url,
-               created_at, updated_at
+        SELECT {}
         FROM events
-        WHERE start_time >= NOW()
-        ORDER BY start_time ASC
-        LIMIT 100
-        "#
-    )
+        {}
+        {}
+        LIMIT {} OFFSET {}
+        "#,
+        EVENT_COLUMNS,
+        where_clause,
+        KEYSET_ORDER,
+        pagination.per_page + 1,
+        offset
+    );
+
+    let mut sql = sqlx::query_as::<_, Event>(&query);
+    if let Some(user_id) = hide_dismissed {
+        sql = sql
+            .bind(user_id)
+            .bind(analytics::spellings_of("dismiss"))
+            .bind(analytics::spellings_of("save"));
+    }
+    if let Some(c) = cursor {
+        sql = sql.bind(c.start_time).bind(c.id);
+    }
+
+    let mut events = sql
+        .fetch_all(&pool)
+        .await?;
+
+    let next_cursor = take_page(&mut events, pagination.per_page as usize, |e| Cursor {
+        start_time: e.start_time,
+        id: e.id,
+    });
+
+    Ok(Json(EventPage { events, next_cursor }))
+}
+
+// =============================================================================
+// HANDLER: TRENDING EVENTS
+// =============================================================================
+
+/// Query parameters for the trending endpoint.
+#[derive(Debug, Deserialize)]
+pub struct TrendingQuery {
+    /// Maximum number of results (default: 20, max: 50)
+    pub limit: Option<i64>,
+}
+
+/// Returns upcoming events ranked by recent interaction activity.
+///
+/// # Endpoint
+/// `GET /api/events/trending?limit=20`
+///
+/// Each interaction from the last `TRENDING_WINDOW_DAYS` days adds its
+/// weight from `services::analytics` (attend +5, save +3, view +1,
+/// dismiss -2). Events with no interactions, or a net score of zero or
+/// less, are left out.
+///
+/// # Returns
+/// A list of events, highest score first, each with a `score` field.
+async fn trending_events(
+    State(pool): State<PgPool>,
+    Query(params): Query<TrendingQuery>,
+) -> Result<Json<Vec<TrendingEvent>>, AppError> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 50);
+
+    let query = format!(
+        r#"
+        SELECT {}, scores.score
+        FROM events
+        JOIN (
+            SELECT event_id, SUM({})::BIGINT AS score
+            FROM user_interactions
+            WHERE created_at >= NOW() - make_interval(days => {})
+            GROUP BY event_id
+        ) scores ON scores.event_id = events.id
+        WHERE scores.score > 0 AND {} AND {} AND {}
+        ORDER BY scores.score DESC, start_time ASC
+        LIMIT $1
+        "#,
+        EVENT_COLUMNS,
+        analytics::weight_sql("interaction_type"),
+        TRENDING_WINDOW_DAYS,
+        UPCOMING_FILTER,
+        NOT_CANCELLED_FILTER,
+        NOT_ARCHIVED_FILTER
+    );
+
+    let events = sqlx::query_as::<_, TrendingEvent>(&query)
+        .bind(limit)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(events))
+}
+
+// =============================================================================
+// HANDLERS: HAPPENING NOW / TONIGHT
+// =============================================================================
+
+/// Hours an event without an `end_time` is assumed to run for "now".
+const ASSUMED_DURATION_HOURS: i32 = 3;
+
+/// Returns events that are in progress right now.
+///
+/// # Endpoint
+/// `GET /api/events/now`
+///
+/// An event is in progress when `start_time <= NOW()` and it hasn't ended.
+/// Events without an `end_time` are assumed to last
+/// `ASSUMED_DURATION_HOURS` hours. Cancelled events are left out.
+async fn happening_now(State(pool): State<PgPool>) -> Result<Json<Vec<Event>>, AppError> {
+    let query = format!(
+        "SELECT {} FROM events \
+         WHERE start_time <= NOW() \
+           AND NOW() < COALESCE(end_time, start_time + make_interval(hours => {})) \
+           AND {} AND {} \
+         ORDER BY start_time ASC, id ASC",
+        EVENT_COLUMNS, ASSUMED_DURATION_HOURS, NOT_CANCELLED_FILTER, NOT_ARCHIVED_FILTER
+    );
+
+    let events = sqlx::query_as::<_, Event>(&query)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(events))
+}
+
+/// Returns events starting tonight, 5 PM to 4 AM in local time.
+///
+/// # Endpoint
+/// `GET /api/events/tonight`
+///
+/// The timezone comes from `LOCAL_TIMEZONE` (default America/Chicago).
+/// Between midnight and 4 AM "tonight" still means the night in progress;
+/// see `services::dates::tonight_window`. Cancelled events are left out.
+async fn tonight_events(State(pool): State<PgPool>) -> Result<Json<Vec<Event>>, AppError> {
+    let (start, end) = dates::tonight_window(Utc::now(), dates::local_timezone());
+
+    let query = format!(
+        "SELECT {} FROM events \
+         WHERE start_time >= $1 AND start_time < $2 AND {} AND {} \
+         ORDER BY start_time ASC, id ASC",
+        EVENT_COLUMNS, NOT_CANCELLED_FILTER, NOT_ARCHIVED_FILTER
+    );
+
+    let events = sqlx::query_as::<_, Event>(&query)
+        .bind(start)
+        .bind(end)
         .fetch_all(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     Ok(Json(events))
 }
 
+// =============================================================================
+// HANDLER: CALENDAR
+// =============================================================================
+
+/// Query parameters for the calendar endpoint.
+///
+/// Filters mean the same thing as on `/api/events/search`.
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    /// Month to summarize, as `YYYY-MM` (required)
+    pub month: String,
+
+    pub category: Option<String>,
+
+    #[serde(default)]
+    pub tag: Vec<String>,
+
+    pub location: Option<String>,
+
+    #[serde(alias = "max_price")]
+    pub price_max: Option<f64>,
+
+    #[serde(default)]
+    pub free_only: bool,
+
+    pub outdoor: Option<bool>,
+
+    pub family_friendly: Option<bool>,
+
+    /// Count events that have already ended (default: false)
+    #[serde(default)]
+    pub include_past: bool,
+
+    #[serde(default)]
+    pub include_cancelled: bool,
+
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+impl CalendarQuery {
+    /// The equivalent search filters, so both endpoints share one WHERE builder.
+    fn as_search(&self) -> SearchQuery {
+        SearchQuery {
+            category: self.category.clone(),
+            tag: self.tag.clone(),
+            location: self.location.clone(),
+            price_max: self.price_max,
+            free_only: self.free_only,
+            outdoor: self.outdoor,
+            family_friendly: self.family_friendly,
+            include_past: self.include_past,
+            include_cancelled: self.include_cancelled,
+            include_archived: self.include_archived,
+            ..Default::default()
+        }
+    }
+}
+
+/// One row of the per-day aggregation.
+#[derive(sqlx::FromRow)]
+struct CalendarRow {
+    day: NaiveDate,
+    count: i64,
+    top_titles: Vec<String>,
+}
+
+/// Returns the number of events on each local day of a month.
+///
+/// # Endpoint
+/// `GET /api/events/calendar?month=2026-02&category=music`
+///
+/// Days are local to `LOCAL_TIMEZONE` (default America/Chicago), so an
+/// 11 PM Friday show counts toward Friday even though it's Saturday in UTC.
+/// Every day of the month is returned, including days with no events.
+///
+/// Past events are hidden unless `include_past=true`, so a month that has
+/// already ended returns all-zero days without touching the database.
+///
+/// # Errors
+/// - `400 Bad Request` - `month` is not a valid `YYYY-MM`, or `price_max`
+///   isn't a non-negative number
+async fn event_calendar(
+    State(pool): State<PgPool>,
+    axum_extra::extract::Query(params): axum_extra::extract::Query<CalendarQuery>,
+) -> Result<Json<CalendarMonth>, AppError> {
+    let first = dates::parse_month(&params.month)
+        .ok_or_else(|| AppError::BadRequest("month must be YYYY-MM".into()))?;
+    check_price_max(params.price_max)?;
+    let tz = dates::local_timezone();
+    let (start, end) = dates::month_window(first, tz);
+
+    let mut days = empty_month(first);
+
+    if params.include_past || end > Utc::now() {
+        let mut conditions = filter_conditions(&params.as_search());
+        conditions.push("start_time >= $1 AND start_time < $2".to_string());
+
+        let query = format!(
+            r#"
+            SELECT
+                date_trunc('day', start_time AT TIME ZONE $3)::date AS day,
+                COUNT(*) AS count,
+                (ARRAY_AGG(title ORDER BY start_time, id))[1:3] AS top_titles
+            FROM events
+            WHERE {}
+            GROUP BY day
+            "#,
+            conditions.join(" AND ")
+        );
+
+        let rows = sqlx::query_as::<_, CalendarRow>(&query)
+            .bind(start)
+            .bind(end)
+            .bind(tz.name())
+            .fetch_all(&pool)
+            .await?;
+
+        for row in rows {
+            days.insert(
+                row.day,
+                CalendarDay {
+                    count: row.count,
+                    top_titles: row.top_titles,
+                },
+            );
+        }
+    }
+
+    Ok(Json(CalendarMonth {
+        month: params.month,
+        days,
+    }))
+}
+
+/// A zero-count entry for every day of the month starting at `first`.
+fn empty_month(first: NaiveDate) -> BTreeMap<NaiveDate, CalendarDay> {
+    let next = dates::next_month(first);
+    std::iter::successors(Some(first), |day| Some(*day + Duration::days(1)))
+        .take_while(|day| *day < next)
+        .map(|day| (day, CalendarDay::default()))
+        .collect()
+}
+
 // =============================================================================
 // HANDLER: GET SINGLE EVENT
 // =============================================================================
 
-/// Returns a single event by its UUID.
+/// Returns a single event by its UUID, with every place it's listed.
 ///
 /// # Endpoint
 /// `GET /api/events/:id`
+///
+/// # Returns
+/// - `200 OK` with the event's fields plus `sources`: each listing, the
+///   one `source_url` links to (`"primary": true`) first
+/// - `404 Not Found` if the event doesn't exist
+///
+/// ```json
+/// {
+///   "id": "550e8400-e29b-41d4-a716-446655440000",
+///   "title": "Turnpike Troubadours",
+///   "source_url": "https://www.cainsballroom.com/events/turnpike-troubadours",
+///   ...
+///   "sources": [
+///     { "source_name": "Cain's Ballroom", "source_url": "https://www.cainsballroom.com/events/turnpike-troubadours",
+///       "external_id": null, "primary": true,
+///       "first_seen_at": "2026-02-03T15:00:00Z", "last_seen_at": "2026-03-01T15:00:00Z" },
+///     { "source_name": "Eventbrite", "source_url": "https://www.eventbrite.com/e/turnpike-troubadours-tickets-1234",
+///       "external_id": "1234", "primary": false,
+///       "first_seen_at": "2026-02-01T15:00:00Z", "last_seen_at": "2026-03-01T15:00:00Z" }
+///   ]
+/// }
+/// ```
 async fn get_event(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Event>, StatusCode> {
-    let event = sqlx::query_as::<_, Event>(
+) -> Result<Json<EventDetail>, AppError> {
+    let event = sqlx::query_as::<_, Event>(&format!(
+        "SELECT {} FROM events WHERE id = $1",
+        EVENT_COLUMNS
+    ))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
+
+    let sources = attribution::list(&pool, id).await?;
+    Ok(Json(EventDetail { event, sources }))
+}
+
+// =============================================================================
+// HANDLER: UPDATE EVENT
+// =============================================================================
+
+/// Updates the fields present in the payload and returns the new event.
+///
+/// # Endpoint
+/// `PATCH /api/events/:id`
+///
+/// Admin use: correcting scraped data, marking an event cancelled, or
+/// bringing back an archived event with `{"archived": false}`.
+///
+/// # Returns
+/// - `200 OK` with the updated event
+/// - `404 Not Found` if the event doesn't exist
+/// - `422 Unprocessable Entity` listing the invalid fields if the resulting
+///   event would be invalid (see `models::validation`)
+async fn update_event(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateEvent>,
+) -> Result<Json<Event>, AppError> {
+    // Cross-field rules (end after start, min below max) need the stored row
+    let current = sqlx::query_as::<_, Event>(&format!("SELECT {} FROM events WHERE id = $1", EVENT_COLUMNS))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
+
+    payload.validate(&current, chrono::Utc::now())?;
+
+    // COALESCE keeps the current value for every field left out
+    let query = format!(
         r#"
-        SELECT id, title, description, venue, venue_address, location,
-               source_url, source_name, start_time, end_time, categories,
-               price_min, price_max, outdoor, family_friendly, image_url,
-               created_at, updated_at
-        FROM events
+        UPDATE events
+        SET title = COALESCE($2, title),
+            description = COALESCE($3, description),
+            venue_address = COALESCE($4, venue_address),
+            location = COALESCE($5, location),
+            source_name = COALESCE($6, source_name),
+            start_time = COALESCE($7, start_time),
+            end_time = COALESCE($8, end_time),
+            categories = COALESCE($9, categories),
+            category_source = CASE WHEN $9::text[] IS NULL THEN category_source END,
+            price_min = COALESCE($10, price_min),
+            price_max = COALESCE($11, price_max),
+            is_free = COALESCE($12, is_free),
+            outdoor = COALESCE($13, outdoor),
+            family_friendly = COALESCE($14, family_friendly),
+            image_url = COALESCE($15, image_url),
+            status = COALESCE($16, status),
+            latitude = COALESCE($17, latitude),
+            longitude = COALESCE($18, longitude),
+            archived_at = CASE
+                WHEN $19::boolean IS NULL THEN archived_at
+                WHEN $19 THEN COALESCE(archived_at, NOW())
+                ELSE NULL
+            END,
+            updated_at = NOW()
         WHERE id = $1
-        "#
-    )
+        RETURNING {}
+        "#,
+        EVENT_COLUMNS
+    );
+
+    let event = sqlx::query_as::<_, Event>(&query)
+        .bind(id)
+        .bind(&payload.title)
+        .bind(&payload.description)
+        .bind(&payload.venue_address)
+        .bind(&payload.location)
+        .bind(&payload.source_name)
+        .bind(payload.start_time)
+        .bind(payload.end_time)
+        .bind(&payload.categories)
+        .bind(payload.price_min)
+        .bind(payload.price_max)
+        .bind(payload.is_free)
+        .bind(payload.outdoor)
+        .bind(payload.family_friendly)
+        .bind(&payload.image_url)
+        .bind(payload.status)
+        .bind(payload.latitude)
+        .bind(payload.longitude)
+        .bind(payload.archived)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
+
+    Ok(Json(event))
+}
+
+// =============================================================================
+// HANDLER: RELATED EVENTS
+// =============================================================================
+
+/// Minimum pg_trgm `similarity()` for a title-only match.
+const TITLE_SIMILARITY_THRESHOLD: f32 = 0.3;
+
+/// Query parameters for the related-events endpoint.
+#[derive(Debug, Deserialize)]
+pub struct RelatedQuery {
+    /// Maximum number of results (default: 10, max: 50)
+    pub limit: Option<i64>,
+}
+
+/// Returns upcoming events similar to the given one ("you might also like").
+///
+/// # Endpoint
+/// `GET /api/events/:id/related?limit=10`
+///
+/// Matches events that share a category or the venue, closest in start
+/// time first. When the event has no categories, titles are compared with
+/// pg_trgm `similarity()` instead (plus same-venue matches), most similar
+/// first. The event itself and cancelled events are never included.
+///
+/// # Returns
+/// - `200 OK` with a list of events (possibly empty)
+/// - `404 Not Found` if the base event doesn't exist
+async fn related_events(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RelatedQuery>,
+) -> Result<Json<Vec<Event>>, AppError> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 50);
+
+    let base = sqlx::query_as::<_, Event>(&format!(
+        "SELECT {} FROM events WHERE id = $1",
+        EVENT_COLUMNS
+    ))
         .bind(id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    match event {
-        Some(e) => Ok(Json(e)),
-        None => Err(StatusCode::NOT_FOUND),
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
+
+    let categories = base.categories.clone().unwrap_or_default();
+    let closeness = "ABS(EXTRACT(EPOCH FROM (start_time - $4)))";
+
+    let (matches, order_by) = if categories.is_empty() {
+        (
+            format!("similarity(title, $5) >= {}", TITLE_SIMILARITY_THRESHOLD),
+            format!("similarity(title, $5) DESC, {} ASC", closeness),
+        )
+    } else {
+        ("categories && $2".to_string(), format!("{} ASC", closeness))
+    };
+
+    let query = format!(
+        r#"
+        SELECT {}
+        FROM events
+        WHERE id <> $1
+          AND ({} OR ($3::uuid IS NOT NULL AND venue_id = $3))
+          AND {} AND {} AND {}
+        ORDER BY {}, id ASC
+        LIMIT $6
+        "#,
+        EVENT_COLUMNS, matches, UPCOMING_FILTER, NOT_CANCELLED_FILTER, NOT_ARCHIVED_FILTER, order_by
+    );
+
+    let events = sqlx::query_as::<_, Event>(&query)
+        .bind(base.id)
+        .bind(&categories)
+        .bind(base.venue_id)
+        .bind(base.start_time)
+        .bind(&base.title)
+        .bind(limit)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(events))
+}
+
+// =============================================================================
+// HANDLER: EVENT STATS
+// =============================================================================
+
+/// Returns interaction counts for an event: all-time totals and a daily
+/// breakdown for the last `STATS_WINDOW_DAYS` days.
+///
+/// # Endpoint
+/// `GET /api/events/:id/stats`
+///
+/// Days are local to `LOCAL_TIMEZONE`. Every type and day is present, with
+/// zeros where there were no interactions.
+///
+/// # Returns
+/// - `200 OK` with `EventStats`
+/// - `404 Not Found` if the event doesn't exist
+async fn event_stats(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EventStats>, AppError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM events WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&pool)
+        .await?;
+
+    if !exists {
+        return Err(AppError::not_found("event"));
     }
+
+    let tz = dates::local_timezone();
+
+    let rows: Vec<(String, NaiveDate, i64)> = sqlx::query_as(
+        r#"
+        SELECT interaction_type, (created_at AT TIME ZONE $2)::date AS day, COUNT(*)
+        FROM user_interactions
+        WHERE event_id = $1
+        GROUP BY interaction_type, day
+        "#,
+    )
+        .bind(id)
+        .bind(tz.name())
+        .fetch_all(&pool)
+        .await?;
+
+    let today = Utc::now().with_timezone(&tz).date_naive();
+
+    Ok(Json(analytics::build_event_stats(id, &rows, today)))
+}
+
+// =============================================================================
+// HANDLER: MERGE DUPLICATE EVENTS
+// =============================================================================
+
+/// Merges a duplicate listing into this event and deletes the duplicate.
+///
+/// # Endpoint
+/// `POST /api/events/:id/merge` with `{"duplicate_id": "..."}`
+///
+/// Admin use. Interactions and notifications move to this event, empty
+/// fields are filled from the duplicate, and the duplicate's source URL is
+/// kept in `event_sources`. See `services::merge` for the details.
+///
+/// # Returns
+/// - `200 OK` with a `MergeReport`
+/// - `400 Bad Request` if `duplicate_id` is this event
+/// - `404 Not Found` if either event doesn't exist
+async fn merge_event(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<MergeEventsRequest>,
+) -> Result<Json<MergeReport>, AppError> {
+    let report = merge::merge_events(&pool, id, payload.duplicate_id).await?;
+    Ok(Json(report))
+}
+
+// =============================================================================
+// HANDLER: BATCH FETCH EVENTS
+// =============================================================================
+
+/// Maximum number of IDs accepted by the batch endpoint.
+const MAX_BATCH_IDS: usize = 100;
+
+/// Request payload for fetching several events at once.
+///
+/// IDs are taken as strings so a malformed entry can be reported by position
+/// instead of failing the whole body with a generic deserialization error.
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    pub ids: Vec<String>,
+}
+
+/// Returns the events for a list of UUIDs in one query.
+///
+/// # Endpoint
+/// `POST /api/events/batch`
+///
+/// # Request Body
+/// ```json
+/// { "ids": ["550e8400-e29b-41d4-a716-446655440000", "..."] }
+/// ```
+///
+/// Events come back in the order requested. IDs that don't exist are
+/// silently left out, and a repeated ID is only returned once.
+///
+/// # Returns
+/// - `200 OK` with a list of events
+/// - `400 Bad Request` naming the first malformed UUID and its index, or
+///   if more than 100 IDs are sent
+async fn batch_events(
+    State(pool): State<PgPool>,
+    Json(payload): Json<BatchRequest>,
+) -> Result<Json<Vec<Event>>, AppError> {
+    if payload.ids.len() > MAX_BATCH_IDS {
+        return Err(AppError::BadRequest(format!("at most {} ids per request", MAX_BATCH_IDS)));
+    }
+
+    let ids = parse_ids(&payload.ids).map_err(|(index, value)| {
+        AppError::BadRequest(format!("ids[{}] is not a valid UUID: {:?}", index, value))
+    })?;
+
+    let events = sqlx::query_as::<_, Event>(&format!(
+        "SELECT {} FROM events WHERE id = ANY($1)",
+        EVENT_COLUMNS
+    ))
+        .bind(&ids)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(order_by_ids(&ids, events)))
+}
+
+/// Parses every entry as a UUID, returning the first bad `(index, value)`.
+fn parse_ids(raw: &[String]) -> Result<Vec<Uuid>, (usize, String)> {
+    raw.iter()
+        .enumerate()
+        .map(|(i, value)| Uuid::parse_str(value.trim()).map_err(|_| (i, value.clone())))
+        .collect()
+}
+
+/// Arranges `events` in the order of `ids`, skipping missing and repeated IDs.
+fn order_by_ids(ids: &[Uuid], events: Vec<Event>) -> Vec<Event> {
+    let mut by_id: HashMap<Uuid, Event> = events.into_iter().map(|e| (e.id, e)).collect();
+    ids.iter().filter_map(|id| by_id.remove(id)).collect()
+}
+
+// =============================================================================
+// HANDLER: EXPORT EVENT AS ICS
+// =============================================================================
+
+/// Returns a single event as an iCalendar file for "Add to calendar".
+///
+/// # Endpoint
+/// `GET /api/events/:id/ics`
+///
+/// # Returns
+/// - `200 OK` with `Content-Type: text/calendar` and an attachment filename
+/// - `404 Not Found` if the event doesn't exist
+async fn get_event_ics(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let event = sqlx::query_as::<_, Event>(&format!(
+        "SELECT {} FROM events WHERE id = $1",
+        EVENT_COLUMNS
+    ))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
+
+    let body = IcsCalendar::new().add_event(&event).build();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"event-{}.ics\"", event.id),
+            ),
+        ],
+        body,
+    ))
+}
+
+// =============================================================================
+// HANDLER: EXPORT EVENT AS JSON-LD
+// =============================================================================
+
+/// Returns a single event as a schema.org `Event` (JSON-LD).
+///
+/// # Endpoint
+/// `GET /api/events/:id/jsonld`
+///
+/// The frontend embeds this in a `<script type="application/ld+json">` tag
+/// on event pages so search engines can index them.
+///
+/// # Returns
+/// - `200 OK` with `Content-Type: application/ld+json`
+/// - `404 Not Found` if the event doesn't exist
+async fn get_event_jsonld(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let event = sqlx::query_as::<_, Event>(&format!(
+        "SELECT {} FROM events WHERE id = $1",
+        EVENT_COLUMNS
+    ))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/ld+json")],
+        Json(JsonLdEvent::from(&event)),
+    ))
 }
 
 // =============================================================================
@@ -120,54 +950,116 @@ async fn get_event(
 ///
 /// # Endpoint
 /// `POST /api/events`
+///
+/// # Returns
+/// - `201 Created` with the new event
+/// - `409 Conflict` if an event with this `source_url` already exists
+/// - `422 Unprocessable Entity` listing every invalid field (see
+///   `models::validation`), including a `venue_id` that doesn't exist
 async fn create_event(
     State(pool): State<PgPool>,
     Json(payload): Json<CreateEvent>,
-) -> Result<(StatusCode, Json<Event>), StatusCode> {
-    let id = Uuid::new_v4();
+) -> Result<(StatusCode, Json<Event>), AppError> {
     let now = chrono::Utc::now();
+    payload.validate(now)?;
+
+    // Event, its venue link and its tags are written together or not at all
+    let mut tx = pool.begin().await?;
+    let event = insert_event(&mut tx, payload, now).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Inserts an already validated event with its venue link, tags and its
+/// own listing (`event_sources`).
+///
+/// Shared by `create_event` and the scraper registry. Takes a connection
+/// so callers can run it inside their own transaction.
+///
+/// # Returns
+/// - The stored event
+/// - `AppError::Validation` if `venue_id` doesn't match a venue
+/// - `AppError::Conflict` if the `source_url` is already taken
+pub(crate) async fn insert_event(
+    conn: &mut PgConnection,
+    payload: CreateEvent,
+    now: DateTime<Utc>,
+) -> Result<Event, AppError> {
+    let id = Uuid::new_v4();
+    let is_free = payload.resolved_is_free();
+    let tags = normalize_tags(&payload.tags);
+
+    // Link to a venue record: an explicit venue_id must exist (its name fills
+    // in the legacy `venue` text), otherwise match or create by venue name.
+    let (venue, venue_id) = match (payload.venue_id, payload.venue.clone()) {
+        (Some(venue_id), venue) => {
+            let name: String = sqlx::query_scalar("SELECT name FROM venues WHERE id = $1")
+                .bind(venue_id)
+                .fetch_optional(&mut *conn)
+                .await?
+                .ok_or_else(|| AppError::invalid("venue_id", "does not match a venue"))?;
+            (Some(venue.unwrap_or(name)), Some(venue_id))
+        }
+        (None, Some(venue)) => {
+            let venue_id = find_or_create_venue(conn, &venue, payload.venue_address.as_deref())
+                .await?;
+            (Some(venue), venue_id)
+        }
+        (None, None) => (None, None),
+    };
 
     sqlx::query(
         r#"
         INSERT INTO events (
-            id, title, description, venue, venue_address, location,
+            id, title, description, venue, venue_id, venue_address, location,
             source_url, source_name, start_time, end_time, categories,
-            price_min, price_max, outdoor, family_friendly, image_url,
-            created_at, updated_at
+            price_min, price_max, is_free, outdoor, family_friendly, image_url,
+            status, latitude, longitude, created_at, updated_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)
         "#,
     )
-        .bind(&id)
+        .bind(id)
         .bind(&payload.title)
         .bind(&payload.description)
-        .bind(&payload.venue)
+        .bind(&venue)
+        .bind(venue_id)
         .bind(&payload.venue_address)
         .bind(&payload.location)
         .bind(&payload.source_url)
         .bind(&payload.source_name)
-        .bind(&payload.start_time)
-        .bind(&payload.end_time)
+        .bind(payload.start_time)
+        .bind(payload.end_time)
         .bind(&payload.categories)
-        .bind(&payload.price_min)
-        .bind(&payload.price_max)
-        .bind(&payload.outdoor)
-        .bind(&payload.family_friendly)
+        .bind(payload.price_min)
+        .bind(payload.price_max)
+        .bind(is_free)
+        .bind(payload.outdoor)
+        .bind(payload.family_friendly)
         .bind(&payload.image_url)
-        .bind(&now)
-        .bind(&now)
-        .execute(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .bind(payload.status)
+        .bind(payload.latitude)
+        .bind(payload.longitude)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("INSERT INTO event_tags (event_id, tag) SELECT $1, UNNEST($2::text[])")
+        .bind(id)
+        .bind(&tags)
+        .execute(&mut *conn)
+        .await?;
+
+    attribution::record(conn, id, &payload.source_url, payload.source_name.as_deref(), None, now).await?;
 
     let event = Event {
         id,
         title: payload.title,
         description: payload.description,
-        venue: payload.venue,
+        venue,
+        venue_id,
         venue_address: payload.venue_address,
         location: payload.location,
         source_url: payload.source_url,
@@ -175,66 +1067,31 @@ async fn create_event(
         start_time: payload.start_time,
         end_time: payload.end_time,
         categories: payload.categories,
+        tags,
         price_min: payload.price_min,
         price_max: payload.price_max,
+        is_free,
         outdoor: payload.outdoor,
         family_friendly: payload.family_friendly,
         image_url: payload.image_url,
+        status: payload.status,
+        latitude: payload.latitude,
+        longitude: payload.longitude,
+        archived_at: None,
         created_at: now,
         updated_at: now,
     };
 
-    Ok((StatusCode::CREATED, Json(event)))
-}
-
-// =============================================================================
-// SEARCH QUERY PARAMETERS
-// =============================================================================
-
-/// Query parameters for the search endpoint.
-///
-/// All fields are optional, allowing flexible search combinations.
-///
-/// # Examples
-/// - `/search?q=jazz` - Text search
-/// - `/search?category=concerts` - Filter by category
-/// - `/search?outdoor=true&family_friendly=true` - Filter by attributes
-/// - `/search?price_max=25` - Filter by price
-/// - `/search?start_date=2026-01-25&end_date=2026-01-26` - Date range
-#[derive(Debug, Deserialize)]
-pub struct SearchQuery {
-    /// Text to search for in event title and description
-    pub q: Option<String>,
-
-    /// Category to filter by (matches any category in the array)
-    pub category: Option<String>,
-
-    /// Start of date range (ISO 8601 format)
-    pub start_date: Option<DateTime<Utc>>,
-
-    /// End of date range (ISO 8601 format)
-    pub end_date: Option<DateTime<Utc>>,
-
-    /// Filter by location
-    pub location: Option<String>,
-
-    /// Maximum price filter
-    pub price_max: Option<f64>,
-
-    /// Only show outdoor events
-    pub outdoor: Option<bool>,
-
-    /// Only show family-friendly events
-    pub family_friendly: Option<bool>,
-
-    /// Maximum number of results (default: 50)
-    pub limit: Option<i32>,
+    Ok(event)
 }
 
 // =============================================================================
 // HANDLER: SEARCH EVENTS
 // =============================================================================
 
+/// Radius used when `lat`/`lng` are given without `radius_km`.
+const DEFAULT_RADIUS_KM: f64 = 10.0;
+
 /// Searches events with multiple filter options.
 ///
 /// # Endpoint
@@ -243,108 +1100,324 @@ pub struct SearchQuery {
 /// # Query Parameters
 /// - `q` - Text search in title/description
 /// - `category` - Filter by category
+/// - `tag` - Filter by tag, repeatable (events must have every tag)
 /// - `start_date` - Start of date range
 /// - `end_date` - End of date range
 /// - `location` - Filter by location
-/// - `price_max` - Maximum price
+/// - `price_max` / `max_price` - Maximum price (free events always match)
+/// - `free_only` - Only free events (true/false)
 /// - `outdoor` - Only outdoor events (true/false)
 /// - `family_friendly` - Only family-friendly events (true/false)
 /// - `limit` - Max results (default 50)
+/// - `include_past` - Include events that have already ended (default false)
+/// - `include_cancelled` - Include cancelled events (default false)
+/// - `include_dismissed` - Keep events the signed-in user dismissed (default false)
+/// - `lat`, `lng` - Search point for a radius search (both required)
+/// - `radius_km` - Radius around the search point (default 10)
+///
+/// # Radius Search
+/// When `lat`/`lng` are present, only events with coordinates within
+/// `radius_km` are returned, nearest first, and each result carries a
+/// `distance_km` field. Events without coordinates are excluded.
+///
+/// # Returns
+/// - `200 OK` with a list of events
+/// - `400 Bad Request` if only one of `lat`/`lng` is given, either is out
+///   of range, `radius_km` isn't positive, or `price_max` isn't a
+///   non-negative number
+///
+/// With a bearer token, events the user dismissed (and hasn't saved since)
+/// are hidden unless `include_dismissed=true`; a bad token is a 401.
 ///
 /// This endpoint is called by the LLM's `search_events` tool.
 async fn search_events(
     State(pool): State<PgPool>,
-    Query(params): Query<SearchQuery>,
-) -> Result<Json<Vec<Event>>, StatusCode> {
+    MaybeAuthUser(viewer): MaybeAuthUser,
+    // axum-extra's Query supports repeated keys (`?tag=a&tag=b`)
+    axum_extra::extract::Query(mut params): axum_extra::extract::Query<SearchQuery>,
+) -> Result<Response, AppError> {
+    params.viewer = viewer;
+    check_price_max(params.price_max)?;
 
-    // Build dynamic query
-    let mut conditions: Vec<String> = vec![];
-    let limit = params.limit.unwrap_or(50).min(100);
-
-    // Text search
-    if let Some(ref q) = params.q {
-        conditions.push(format!(
-            "(title ILIKE '%{}%' OR description ILIKE '%{}%')",
-            q.replace('\'', "''"), // Basic SQL injection prevention
-            q.replace('\'', "''")
-        ));
+    // Radius search point (both coordinates or neither)
+    let origin = match (params.lat, params.lng) {
+        (Some(lat), Some(lng)) if geo::is_valid_coordinate(lat, lng) => Some((lat, lng)),
+        (None, None) => None,
+        _ => return Err(AppError::BadRequest("lat and lng must be given together and in range".into())),
+    };
+
+    let response = match origin {
+        Some((lat, lng)) => {
+            let radius = params.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+            if !radius.is_finite() || radius <= 0.0 {
+                return Err(AppError::BadRequest("radius_km must be positive".into()));
+            }
+
+            let events = sqlx::query_as::<_, EventWithDistance>(&search::search_sql(&params, Some((lat, lng, radius))))
+                .fetch_all(&pool)
+                .await?;
+            Json(events).into_response()
+        }
+        None => Json(search::search_events(&pool, &params).await?).into_response(),
+    };
+
+    Ok(response)
+}
+
+/// Rejects a `price_max` that can't go into the SQL: `f64` parses "NaN"
+/// and "inf" too, and a negative budget matches nothing but free events.
+fn check_price_max(price_max: Option<f64>) -> Result<(), AppError> {
+    match price_max {
+        Some(price) if !price.is_finite() || price < 0.0 => {
+            Err(AppError::BadRequest("price_max must be a non-negative number".into()))
+        }
+        _ => Ok(()),
     }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Category filter (check if category is in the categories array)
-    if let Some(ref cat) = params.category {
-        conditions.push(format!(
-            "'{}' = ANY(categories)",
-            cat.replace('\'', "''")
-        ));
+    /// Parses a query string with the same extractor the handler uses.
+    fn search(query: &str) -> SearchQuery {
+        use axum::extract::FromRequestParts;
+
+        let request = axum::http::Request::builder()
+            .uri(format!("/search?{}", query))
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime
+            .block_on(axum_extra::extract::Query::<SearchQuery>::from_request_parts(&mut parts, &()))
+            .unwrap()
+            .0
     }
 
-    // Date range
-    if let Some(start) = params.start_date {
-        conditions.push(format!("start_time >= '{}'", start.to_rfc3339()));
-    } else {
-        // Default: only future events
-        conditions.push("start_time >= NOW()".to_string());
+    fn event(id: u128) -> Event {
+        let now = chrono::Utc::now();
+        Event {
+            id: Uuid::from_u128(id),
+            title: format!("Event {}", id),
+            description: None,
+            venue: None,
+            venue_id: None,
+            venue_address: None,
+            location: None,
+            source_url: format!("https://example.com/{}", id),
+            source_name: None,
+            start_time: now,
+            end_time: None,
+            categories: None,
+            tags: vec![],
+            price_min: None,
+            price_max: None,
+            is_free: false,
+            outdoor: false,
+            family_friendly: false,
+            image_url: None,
+            status: Default::default(),
+            latitude: None,
+            longitude: None,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        }
     }
 
-    if let Some(end) = params.end_date {
-        conditions.push(format!("start_time <= '{}'", end.to_rfc3339()));
+    #[test]
+    fn batch_reports_first_malformed_id() {
+        let raw = vec![
+            Uuid::from_u128(1).to_string(),
+            "not-a-uuid".to_string(),
+            "also-bad".to_string(),
+        ];
+        assert_eq!(parse_ids(&raw), Err((1, "not-a-uuid".to_string())));
     }
 
-    // Location filter
-    if let Some(ref loc) = params.location {
-        conditions.push(format!(
-            "location ILIKE '%{}%'",
-            loc.replace('\'', "''")
-        ));
+    #[test]
+    fn batch_preserves_requested_order_and_skips_missing() {
+        let ids: Vec<Uuid> = [3, 1, 99, 2, 1].into_iter().map(Uuid::from_u128).collect();
+        // Database returns rows in arbitrary order; 99 doesn't exist
+        let ordered = order_by_ids(&ids, vec![event(1), event(2), event(3)]);
+
+        let got: Vec<u128> = ordered.iter().map(|e| e.id.as_u128()).collect();
+        assert_eq!(got, vec![3, 1, 2]);
     }
 
-    // Price filter (check if at least one price is within budget)
-    if let Some(max_price) = params.price_max {
-        conditions.push(format!(
-            "(price_min IS NULL OR price_min <= {})",
-            max_price
-        ));
+    #[test]
+    fn normalizes_tags() {
+        let tags = vec![" Outdoor".to_string(), "outdoor".to_string(), "".to_string(), "Free".to_string()];
+        assert_eq!(normalize_tags(&tags), vec!["free", "outdoor"]);
     }
 
-    // Outdoor filter
-    if let Some(outdoor) = params.outdoor {
-        conditions.push(format!("outdoor = {}", outdoor));
+    #[test]
+    fn parses_repeated_tags() {
+        let params = search("tag=outdoor&tag=family-friendly");
+        assert_eq!(params.tag, vec!["outdoor", "family-friendly"]);
     }
 
-    // Family-friendly filter
-    if let Some(ff) = params.family_friendly {
-        conditions.push(format!("family_friendly = {}", ff));
+    #[test]
+    fn tag_filters_combine_with_category() {
+        let conditions = filter_conditions(&search("category=concerts&tag=Outdoor&tag=free"));
+
+        assert!(conditions.contains(&"'concerts' = ANY(categories)".to_string()));
+        let tag_conditions: Vec<_> = conditions.iter().filter(|c| c.contains("event_tags")).collect();
+        assert_eq!(tag_conditions.len(), 2);
+        assert!(tag_conditions[0].contains("t.tag = 'free'"));
+        assert!(tag_conditions[1].contains("t.tag = 'outdoor'"));
     }
 
-    // Build WHERE clause
-    let where_clause = if conditions.is_empty() {
-        "WHERE start_time >= NOW()".to_string()
-    } else {
-        format!("WHERE {}", conditions.join(" AND "))
-    };
+    #[test]
+    fn tag_values_are_escaped() {
+        let conditions = filter_conditions(&search("tag=o%27brien"));
+        assert!(conditions.iter().any(|c| c.contains("t.tag = 'o''brien'")));
+    }
 
-    // Execute query
-    let query = format!(
-        r#"
-        SELECT id, title, description, venue, venue_address, location,
-               source_url, source_name, start_time, end_time, categories,
-               price_min, price_max, outdoor, family_friendly, image_url,
-               created_at, updated_at
-        FROM events
-        {}
-        ORDER BY start_time ASC
-        LIMIT {}
-        "#,
-        where_clause, limit
-    );
+    #[test]
+    fn empty_month_has_every_day() {
+        let feb = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let days = empty_month(feb);
 
-    let events = sqlx::query_as::<_, Event>(&query)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        assert_eq!(days.len(), 28);
+        assert_eq!(days.keys().next(), Some(&feb));
+        assert!(days.values().all(|day| day.count == 0));
+    }
 
-    Ok(Json(events))
-}
\ No newline at end of file
+    #[test]
+    fn calendar_shares_search_filters() {
+        let calendar = CalendarQuery {
+            month: "2026-02".to_string(),
+            category: Some("music".to_string()),
+            tag: vec!["outdoor".to_string()],
+            location: Some("Brady".to_string()),
+            price_max: None,
+            free_only: true,
+            outdoor: None,
+            family_friendly: None,
+            include_past: false,
+            include_cancelled: false,
+            include_archived: false,
+        };
+
+        assert_eq!(
+            filter_conditions(&calendar.as_search()),
+            filter_conditions(&search("category=music&tag=outdoor&location=Brady&free_only=true"))
+        );
+    }
+
+    #[test]
+    fn archived_events_are_hidden_unless_requested() {
+        let archived = NOT_ARCHIVED_FILTER.to_string();
+
+        assert!(filter_conditions(&search("include_past=true")).contains(&archived));
+        assert!(!filter_conditions(&search("include_past=true&include_archived=true")).contains(&archived));
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn dismissed_events_stay_hidden_across_pages() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Far enough ahead that no other event sits between ours
+        let run = Uuid::new_v4();
+        let first = Utc::now() + Duration::days(36_500) + Duration::minutes((run.as_u128() % 1_000_000) as i64);
+        let mut ids = Vec::new();
+        for (i, title) in ["Dismissed", "Kept", "Also Kept"].into_iter().enumerate() {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO events (title, source_url, start_time) VALUES ($1, $2, $3) RETURNING id",
+            )
+                .bind(title)
+                .bind(format!("https://venue.example/{}/{}", run, i))
+                .bind(first + Duration::seconds(i as i64))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        let user: Uuid = sqlx::query_scalar("INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id")
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        // The past-tense spelling counts too
+        sqlx::query("INSERT INTO user_interactions (user_id, event_id, interaction_type) VALUES ($1, $2, 'dismissed')")
+            .bind(user)
+            .bind(ids[0])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let page = |viewer: Option<Uuid>| {
+            let pool = pool.clone();
+            let cursor = Cursor { start_time: first - Duration::seconds(1), id: Uuid::nil() }.encode();
+            async move {
+                let params = ListQuery {
+                    include_past: false,
+                    include_cancelled: false,
+                    include_archived: false,
+                    include_dismissed: false,
+                    cursor: Some(cursor),
+                    page: None,
+                    per_page: Some(2),
+                };
+                let Json(page) = list_events(State(pool), MaybeAuthUser(viewer), Query(params)).await.unwrap();
+                page.events.into_iter().map(|event| event.id).collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(page(None).await, ids[..2]);
+        assert_eq!(page(Some(user)).await, ids[1..]);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = ANY($1)").bind(&ids).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn prices_that_arent_numbers_are_rejected() {
+        use axum::{body::Body, http::{Request, StatusCode}};
+        use sqlx::postgres::PgPoolOptions;
+        use std::sync::Arc;
+        use tower::ServiceExt;
+
+        use crate::services::llm_provider::MockProvider;
+
+        // Rejected before the (unreachable) database is asked anything
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let state = AppState {
+            pool,
+            llm: Arc::new(MockProvider::echo()),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+
+        for uri in [
+            "/search?max_price=NaN",
+            "/search?price_max=inf",
+            "/search?price_max=-5",
+            "/calendar?month=2026-02&max_price=NaN",
+        ] {
+            let response = app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        }
+    }
+}
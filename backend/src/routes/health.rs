@@ -0,0 +1,402 @@
+//! # Health Routes
+//!
+//! Probes for load balancers and ops.
+//!
+//! ## Endpoints
+//! - `GET /api/health`     - Liveness: the process is up and serving
+//! - `GET /api/ready`      - Readiness, with a check per dependency
+//! - `GET /api/health/llm` - Whether chat's model answers
+//! - `GET /api/metrics`    - Prometheus metrics (scraper health)
+//!
+//! ## Liveness vs Readiness
+//! `/api/health` touches nothing but the process, so a database outage
+//! doesn't get every instance restarted. `/api/ready` answers `503` while
+//! the database is unreachable (or every pooled connection is busy) or
+//! its schema is behind this build's migrations (`db::health_check`,
+//! `db::migrations_check`); the pool reconnects on its own, so it turns
+//! `200` again when the database is back.
+//!
+//! ## Degraded Is Still Ready
+//! Chat falls back to a plain search when the model is down, so a failing
+//! LLM check marks readiness `degraded` with a warning but still answers
+//! `200`: taking the app out of rotation would take search down with it.
+//!
+//! ## Metrics
+//! `/api/metrics` is unauthenticated, for a scraper on the private
+//! network; keep it off the public proxy like the other probes.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead) - readiness
+//! Ben (AI Engineer) - LLM check
+//! Skylar (Data Engineer) - scraper metrics
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::db::{self, DbHealth};
+use crate::error::AppError;
+use crate::routes::AppState;
+use crate::scraper::health;
+use crate::scraper::runs::{self, ScrapeRunner};
+use crate::services::scheduler;
+use crate::services::llm_health::{LlmHealth, LlmHealthCheck};
+use crate::services::llm_provider::SharedProvider;
+
+// =============================================================================
+// RESPONSE TYPES
+// =============================================================================
+
+/// The process is up.
+///
+/// # Example
+/// ```json
+/// { "status": "ok", "version": "0.1.0" }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct Liveness {
+    pub status: &'static str,
+    pub version: &'static str,
+}
+
+/// Readiness of the app and each dependency.
+///
+/// # Example
+/// ```json
+/// {
+///   "status": "degraded",
+///   "warnings": ["llm: chat is answering with plain search"],
+///   "checks": {
+///     "database": { "ok": true, "latency_ms": 2, "error": null },
+///     "migrations": { "ok": true, "latency_ms": 3, "error": null },
+///     "llm": { "provider": "gemini", "ok": false, ... }
+///   }
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    /// `"ok"`; `"degraded"` when a non-fatal check fails; `"unavailable"`
+    /// when a fatal one does
+    pub status: &'static str,
+    /// One line per failing check
+    pub warnings: Vec<String>,
+    pub checks: ReadinessChecks,
+}
+
+/// The individual checks behind `Readiness`.
+#[derive(Debug, Serialize)]
+pub struct ReadinessChecks {
+    /// Fatal: `SELECT 1` within two seconds
+    pub database: DbHealth,
+    /// Fatal: every migration applied
+    pub migrations: DbHealth,
+    pub llm: LlmHealth,
+}
+
+// =============================================================================
+// ROUTE DEFINITIONS
+// =============================================================================
+
+/// Creates the router for the health endpoints.
+///
+/// # Routes
+/// - `GET /health` -> `health()`
+/// - `GET /ready` -> `ready()`
+/// - `GET /health/llm` -> `llm_health()`
+/// - `GET /metrics` -> `metrics()`
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/health/llm", get(llm_health))
+        .route("/metrics", get(metrics))
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+/// Whether the process is up, for a liveness probe. Checks nothing else.
+///
+/// # Endpoint
+/// `GET /api/health`
+///
+/// # Returns
+/// `200 OK` with `Liveness`
+async fn health() -> Json<Liveness> {
+    Json(Liveness { status: "ok", version: env!("CARGO_PKG_VERSION") })
+}
+
+/// Whether the app can serve traffic.
+///
+/// # Endpoint
+/// `GET /api/ready`
+///
+/// # Returns
+/// - `200 OK` with `Readiness`; `status` is `"degraded"` (with a warning)
+///   if the LLM check fails
+/// - `503 Service Unavailable` with `Readiness` (`status`
+///   `"unavailable"`) if the database or migrations check fails
+async fn ready(
+    State(pool): State<PgPool>,
+    State(llm): State<SharedProvider>,
+    State(health): State<Arc<LlmHealthCheck>>,
+) -> (StatusCode, Json<Readiness>) {
+    let (database, migrations, llm) =
+        tokio::join!(db::health_check(&pool), db::migrations_check(&pool), health.check(llm.as_ref()));
+
+    let mut warnings = Vec::new();
+    for (name, check) in [("database", &database), ("migrations", &migrations)] {
+        if let Some(error) = &check.error {
+            warnings.push(format!("{}: {}", name, error));
+        }
+    }
+    let fatal = !warnings.is_empty();
+    if !llm.ok {
+        warnings.push("llm: chat is answering with plain search".to_string());
+    }
+
+    let (code, status) = match (fatal, warnings.is_empty()) {
+        (true, _) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+        (false, true) => (StatusCode::OK, "ok"),
+        (false, false) => (StatusCode::OK, "degraded"),
+    };
+    (code, Json(Readiness { status, warnings, checks: ReadinessChecks { database, migrations, llm } }))
+}
+
+/// The configured model's health, from a ping at most five minutes old
+/// (see `services::llm_health`).
+///
+/// # Endpoint
+/// `GET /api/health/llm`
+///
+/// # Returns
+/// `200 OK`, healthy or not:
+/// ```json
+/// { "provider": "gemini", "model": "gemini-1.5-flash", "ok": true,
+///   "latency_ms": 412, "last_error": null, "checked_at": "2026-01-23T18:00:00Z" }
+/// ```
+async fn llm_health(
+    State(llm): State<SharedProvider>,
+    State(health): State<Arc<LlmHealthCheck>>,
+) -> Json<LlmHealth> {
+    Json(health.check(llm.as_ref()).await)
+}
+
+/// Each scraper's health as Prometheus metrics (see `scraper::health`).
+///
+/// # Endpoint
+/// `GET /api/metrics`
+///
+/// # Returns
+/// `200 OK` in the Prometheus text format:
+/// ```text
+/// # TYPE locate918_scraper_consecutive_failures gauge
+/// locate918_scraper_consecutive_failures{source="cains_ballroom"} 0
+/// ...
+/// ```
+///
+/// # Errors
+/// `500` if the database can't be read
+async fn metrics(
+    State(pool): State<PgPool>,
+    State(runner): State<Arc<ScrapeRunner>>,
+) -> Result<impl IntoResponse, AppError> {
+    let failing_after = scheduler::env_u64("SCRAPE_FAILING_AFTER", runs::DEFAULT_FAILING_AFTER);
+    let sources = runner.sources();
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    let scrapers = health::scraper_health(&pool, &sources, failing_after).await?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], health::render_metrics(&scrapers)))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use axum::{body::Body, http::Request};
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinHandle;
+    use tower::ServiceExt;
+
+    use crate::services::llm::LlmError;
+    use crate::services::llm_provider::{LlmResponse, MockProvider};
+
+    /// State with a pool whose database never answers.
+    fn state(llm: MockProvider) -> AppState {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        state_with(pool, llm)
+    }
+
+    fn state_with(pool: PgPool, llm: MockProvider) -> AppState {
+        AppState {
+            pool,
+            llm: Arc::new(llm),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        }
+    }
+
+    fn answering() -> MockProvider {
+        MockProvider::new(|_, _| Ok(LlmResponse::text("ok")))
+    }
+
+    fn failing() -> MockProvider {
+        MockProvider::new(|_, _| Err(LlmError::MissingApiKey))
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app.oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn every_check_is_reported_and_the_database_is_fatal() {
+        let healthy = routes().with_state(state(answering()));
+        // Liveness doesn't care about the database
+        let (status, body) = get_json(healthy.clone(), "/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = get_json(healthy.clone(), "/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["checks"]["database"]["ok"], false);
+        assert_eq!(body["checks"]["migrations"]["ok"], false);
+        assert!(body["checks"]["database"]["latency_ms"].as_u64().unwrap() < 2000);
+        assert!(body["warnings"][0].as_str().unwrap().starts_with("database: "));
+        assert_eq!(body["checks"]["llm"]["ok"], true);
+
+        let (_, llm) = get_json(healthy, "/health/llm").await;
+        assert_eq!(llm["provider"], "mock");
+        assert_eq!(llm["last_error"], serde_json::Value::Null);
+
+        let failing = routes().with_state(state(failing()));
+        let (_, body) = get_json(failing.clone(), "/ready").await;
+        assert_eq!(body["warnings"][2], "llm: chat is answering with plain search");
+
+        let (status, llm) = get_json(failing, "/health/llm").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(llm["ok"], false);
+        assert!(llm["last_error"].as_str().is_some());
+    }
+
+    /// A TCP proxy in front of the test database whose connections can be
+    /// cut, to take the database away from a pool and give it back.
+    struct Proxy {
+        port: u16,
+        up: Arc<AtomicBool>,
+        connections: Arc<std::sync::Mutex<Vec<JoinHandle<()>>>>,
+    }
+
+    impl Proxy {
+        async fn start(upstream: SocketAddr) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let up = Arc::new(AtomicBool::new(true));
+            let connections = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let (accepting, open) = (up.clone(), connections.clone());
+            tokio::spawn(async move {
+                while let Ok((mut client, _)) = listener.accept().await {
+                    // Down: hang up at once, like a database that's gone
+                    if !accepting.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    open.lock().unwrap().push(tokio::spawn(async move {
+                        if let Ok(mut server) = TcpStream::connect(upstream).await {
+                            let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                        }
+                    }));
+                }
+            });
+            Self { port, up, connections }
+        }
+
+        /// Drops every connection, and refuses new ones.
+        fn cut(&self) {
+            self.up.store(false, Ordering::SeqCst);
+            for connection in self.connections.lock().unwrap().drain(..) {
+                connection.abort();
+            }
+        }
+
+        fn restore(&self) {
+            self.up.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn readiness_follows_the_database_down_and_back_up() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let options = PgConnectOptions::from_str(&url).unwrap();
+        let direct = PgPool::connect_with(options.clone()).await.unwrap();
+        db::MIGRATOR.run(&direct).await.unwrap();
+
+        let upstream = tokio::net::lookup_host((options.get_host(), options.get_port())).await.unwrap().next().unwrap();
+        let proxy = Proxy::start(upstream).await;
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .connect_with(options.host("127.0.0.1").port(proxy.port))
+            .await
+            .unwrap();
+        let app = routes().with_state(state_with(pool.clone(), failing()));
+
+        // Up: a failing model only degrades it
+        let (status, body) = get_json(app.clone(), "/ready").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!((body["checks"]["database"]["ok"].clone(), body["checks"]["migrations"]["ok"].clone()), (true.into(), true.into()));
+
+        proxy.cut();
+        let (status, body) = get_json(app.clone(), "/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["database"]["ok"], false);
+        let (status, _) = get_json(app.clone(), "/health").await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Back, without a new pool
+        proxy.restore();
+        let (status, body) = get_json(app.clone(), "/ready").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["checks"]["database"]["ok"], true);
+
+        // Every connection busy: not ready either
+        let held = pool.acquire().await.unwrap();
+        let busy = db::health_check(&pool).await;
+        assert!(!busy.ok && busy.error.is_some());
+        drop(held);
+        assert!(db::health_check(&pool).await.ok);
+    }
+}
@@ -7,22 +7,96 @@
 //! ## Current Endpoints
 //!
 //! ### Events (`/api/events`)
-//! - `GET  /api/events`           - List all events
+//! - `GET  /api/events`           - List upcoming events
 //! - `POST /api/events`           - Create a new event
 //! - `GET  /api/events/:id`       - Get a single event by ID
+//! - `PATCH /api/events/:id`      - Update an event (incl. unarchive)
 //! - `GET  /api/events/search`    - Search events by query/category
+//! - `GET  /api/events/trending`  - Most popular upcoming events this week
+//! - `POST /api/events/batch`     - Fetch up to 100 events by UUID
+//! - `GET  /api/events/now`       - Events happening right now
+//! - `GET  /api/events/tonight`   - Events tonight (5 PM - 4 AM local)
+//! - `GET  /api/events/calendar`  - Event counts per day for a month
+//! - `GET  /api/events/:id/ics`   - Download an event as an iCalendar file
+//! - `GET  /api/events/:id/jsonld` - Event as schema.org JSON-LD
+//! - `GET  /api/events/:id/related` - "You might also like" events
+//! - `GET  /api/events/:id/stats`  - Interaction counts per type and day
+//! - `POST /api/events/:id/merge`  - Merge a duplicate into an event (admin)
+//!
+//! ### Auth (`/api/auth`)
+//! - `POST /api/auth/register`            - Create an account, returns a token
+//! - `POST /api/auth/login`               - Get a token for an account
+//! - `GET  /api/auth/google`              - Start "Sign in with Google"
+//! - `GET  /api/auth/google/callback`     - Finish Google sign-in
 //!
 //! ### Users (`/api/users`)
+//! Routes under `/api/users/:id` need `Authorization: Bearer <token>` for
+//! that user, except `saved.ics`.
 //! - `POST /api/users`                    - Create a new user
 //! - `GET  /api/users/:id`                - Get user by ID
+//! - `PATCH /api/users/:id`               - Edit name, email, location
+//! - `PUT  /api/users/:id/password`       - Set or change the password
 //! - `GET  /api/users/:id/profile`        - Get full user profile (for LLM)
 //! - `GET  /api/users/:id/preferences`    - Get user's category preferences
 //! - `POST /api/users/:id/preferences`    - Add/update a preference
-//! - `GET  /api/users/:id/interactions`   - Get user's event interactions
+//! - `POST /api/users/:id/preferences/bulk` - Add/update several preferences
+//! - `DELETE /api/users/:id/preferences/:category` - Remove a preference
+//! - `GET  /api/users/:id/interactions`   - Get user's event interactions (filtered, paged)
 //! - `POST /api/users/:id/interactions`   - Record a new interaction
+//! - `DELETE /api/users/:id/interactions?event_id=&type=` - Remove the latest match
+//! - `DELETE /api/users/:id/interactions/:interaction_id` - Remove one interaction
+//! - `GET  /api/users/:id/saved`          - Events the user has saved
+//! - `GET  /api/users/:id/saved.ics`      - Calendar feed of saved events
+//! - `GET  /api/users/:id/stats`          - Aggregate activity counts
+//! - `GET  /api/users/:id/export`         - Download all of a user's data (JSON)
+//!
+//! ### Venues (`/api/venues`)
+//! - `GET    /api/venues`            - List venues
+//! - `POST   /api/venues`            - Create a venue
+//! - `GET    /api/venues/:id`        - Get a single venue
+//! - `PUT    /api/venues/:id`        - Replace a venue's details
+//! - `DELETE /api/venues/:id`        - Delete a venue
+//! - `GET    /api/venues/:id/events` - Upcoming events at a venue
+//!
+//! ### Admin (`/api/admin`)
+//! Needs `X-Admin-Key` (as do event create, update and merge, and venue
+//! create, replace and delete).
+//! - `POST /api/admin/preferences/learn` - Recompute inferred preferences now
+//! - `GET  /api/admin/users?q=&sort=&page=` - Search accounts with activity counts
+//! - `GET  /api/admin/llm/usage?since=`     - LLM calls, tokens and latency
+//! - `POST /api/admin/llm/prompt/reload`   - Re-read the chat system prompt file
+//! - `POST /api/admin/events/classify`     - Categorize uncategorized events now
+//! - `GET  /api/admin/chat/feedback?rating=` - Rated chat replies to review
+//! - `GET  /api/admin/duplicates`          - Possible duplicate events to review
+//! - `POST /api/admin/scrape`              - Start a scrape (one source or all)
+//! - `POST /api/admin/scrape/url`          - Read one event from any page (and store it)
+//! - `GET  /api/admin/scrape/batches/:id`  - A started scrape's progress and runs
+//! - `GET  /api/admin/scrape/runs`         - Recent scraper runs, per source
+//! - `GET  /api/admin/scrape/runs/:id`     - One scraper run
+//! - `GET  /api/admin/scrape/sources`      - Each scraper's last run and failure streak
+//! - `GET  /api/admin/scrape/health`       - Each scraper's status (failing, yield drop, ok)
+//! - `GET  /api/admin/quarantine`          - Scraped events that failed validation
+//! - `POST /api/admin/quarantine/:id/retry` - Fix and store a quarantined event
+//! - `DELETE /api/admin/quarantine/:id`    - Drop a quarantined event
+//! - `GET  /api/admin/sources`             - Configured scrape sources
+//! - `POST /api/admin/sources`             - Add a scrape source
+//! - `GET  /api/admin/sources/:id`         - One scrape source
+//! - `PATCH /api/admin/sources/:id`        - Enable, disable or reschedule a source
+//! - `DELETE /api/admin/sources/:id`       - Remove a scrape source
+//! - `POST /api/admin/sources/reload`      - Re-read the scrape sources
 //!
-//! ### Chat (`/api/chat`) - Coming Soon
-//! - `POST /api/chat`             - Natural language event search (Ben's task)
+//! ### Chat (`/api/chat`)
+//! - `POST /api/chat`             - Natural language event search
+//! - `GET /api/chat/suggestions`  - Starter prompts (`?user_id=` to personalize)
+//! - `GET /api/chat/history`      - A session's stored turns (`?session_id=`)
+//! - `DELETE /api/chat/history`   - Forget a session
+//! - `POST /api/chat/feedback`    - Rate a reply up or down
+//!
+//! ### Health
+//! - `GET  /api/health`           - Liveness (the process is up)
+//! - `GET  /api/ready`            - Readiness: 503 without the database or its migrations (LLM failures only degrade it)
+//! - `GET  /api/health/llm`       - Cached ping of the chat model
+//! - `GET  /api/metrics`          - Prometheus metrics (scraper health)
 
 // =============================================================================
 // SUBMODULE DECLARATIONS
@@ -30,16 +104,107 @@
 // Each submodule handles a specific resource/feature area.
 // The actual route handlers are defined in these files.
 
-mod events;  // Event-related endpoints (CRUD + search)
+pub(crate) mod events;  // Event-related endpoints (CRUD + search)
 mod chat;    // LLM-powered natural language chat (Ben - AI Engineer)
 mod users;   // User management, preferences, and interactions
+mod auth;    // Register/login, issues bearer tokens
+mod venues;  // Venue records and events-by-venue
+mod admin;   // On-demand runs of background jobs
+mod health;  // Liveness, readiness and LLM health probes
 
 // =============================================================================
 // IMPORTS
 // =============================================================================
 
-use axum::Router;   // Axum's router type for building route trees
-use sqlx::PgPool;   // PostgreSQL connection pool type (passed as state)
+use axum::extract::FromRef;  // Lets handlers extract one field of the state
+use axum::Router;            // Axum's router type for building route trees
+use sqlx::PgPool;            // PostgreSQL connection pool type (part of the state)
+use std::sync::Arc;          // Shared ownership of in-memory state
+
+use crate::scraper::runs::ScrapeRunner;
+use crate::services::intent_cache::IntentCache;
+use crate::services::llm_health::LlmHealthCheck;
+use crate::services::llm_provider::SharedProvider;
+use crate::services::prompt::PromptStore;
+use crate::services::rate_limit::ChatRateLimiter;
+use crate::services::suggestions::SuggestionCache;
+
+// =============================================================================
+// SHARED STATE
+// =============================================================================
+
+/// Everything handlers share, built once in main.rs.
+///
+/// Handlers extract just the part they need: `State<PgPool>` for the
+/// database, `State<SharedProvider>` for the chat model. Tests build one
+/// with a `MockProvider` to run the chat routes without an API key.
+#[derive(Clone)]
+pub struct AppState {
+    /// Database connection pool
+    pub pool: PgPool,
+    /// The LLM behind chat (`LLM_PROVIDER`)
+    pub llm: SharedProvider,
+    /// Parsed intents reused across requests
+    pub intent_cache: Arc<IntentCache>,
+    /// Per-client limits on `POST /api/chat`
+    pub chat_limiter: Arc<ChatRateLimiter>,
+    /// Starter prompts for the chat box, per user
+    pub chat_suggestions: Arc<SuggestionCache>,
+    /// The chat system prompt (`LLM_SYSTEM_PROMPT_FILE`)
+    pub prompt: Arc<PromptStore>,
+    /// Cached pings of the chat model
+    pub llm_health: Arc<LlmHealthCheck>,
+    /// The scrapers, and which are running
+    pub scrapers: Arc<ScrapeRunner>,
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedProvider {
+    fn from_ref(state: &AppState) -> Self {
+        state.llm.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<IntentCache> {
+    fn from_ref(state: &AppState) -> Self {
+        state.intent_cache.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ChatRateLimiter> {
+    fn from_ref(state: &AppState) -> Self {
+        state.chat_limiter.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<SuggestionCache> {
+    fn from_ref(state: &AppState) -> Self {
+        state.chat_suggestions.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<PromptStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.prompt.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<LlmHealthCheck> {
+    fn from_ref(state: &AppState) -> Self {
+        state.llm_health.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ScrapeRunner> {
+    fn from_ref(state: &AppState) -> Self {
+        state.scrapers.clone()
+    }
+}
 
 // =============================================================================
 // ROUTE FACTORY
@@ -48,8 +213,9 @@ use sqlx::PgPool;   // PostgreSQL connection pool type (passed as state)
 /// Creates and returns the main API router with all endpoints.
 ///
 /// # Type Parameter
-/// - `Router<PgPool>`: A router that carries a PostgreSQL connection pool
-///   as shared state. This allows all handlers to access the database.
+/// - `Router<AppState>`: A router that carries the shared state (database
+///   pool and LLM provider). Handlers extract `State<PgPool>` or
+///   `State<SharedProvider>` from it.
 ///
 /// # How Nesting Works
 /// - `.nest("/events", events::routes())` takes all routes from `events::routes()`
@@ -67,11 +233,11 @@ use sqlx::PgPool;   // PostgreSQL connection pool type (passed as state)
 /// ```
 ///
 /// # Adding New Route Groups
-/// To add a new feature area (e.g., venues):
-/// 1. Create `routes/venues.rs` with a `pub fn routes() -> Router<PgPool>`
-/// 2. Add `mod venues;` above
-/// 3. Add `.nest("/venues", venues::routes())` below
-pub fn create_routes() -> Router<PgPool> {
+/// To add a new feature area (e.g., organizers):
+/// 1. Create `routes/organizers.rs` with a `pub fn routes() -> Router<AppState>`
+/// 2. Add `mod organizers;` above
+/// 3. Add `.nest("/organizers", organizers::routes())` below
+pub fn create_routes() -> Router<AppState> {
     Router::new()
         // ---------------------------------------------------------------------
         // Events Routes
@@ -80,6 +246,14 @@ pub fn create_routes() -> Router<PgPool> {
         // Owner: Will (Coordinator/Backend Lead)
         .nest("/events", events::routes())
 
+        // ---------------------------------------------------------------------
+        // Auth Routes
+        // ---------------------------------------------------------------------
+        // Register, log in, or sign in with Google; all issue a bearer
+        // token for /users/:id.
+        // Owner: Will (Coordinator/Backend Lead)
+        .nest("/auth", auth::routes())
+
         // ---------------------------------------------------------------------
         // Users Routes
         // ---------------------------------------------------------------------
@@ -88,14 +262,34 @@ pub fn create_routes() -> Router<PgPool> {
         // Owner: Will (Coordinator/Backend Lead)
         .nest("/users", users::routes())
 
-    // ---------------------------------------------------------------------
-    // Chat Routes (Coming Soon)
-    // ---------------------------------------------------------------------
-    // Natural language interface powered by Gemini/LLM.
-    // Will interpret user queries like "What's happening downtown Friday?"
-    // and return personalized event recommendations.
-    // Owner: Ben (AI Engineer)
-    //
-    // Uncomment when Ben implements the chat functionality:
-    // .nest("/chat", chat::routes())
+        // ---------------------------------------------------------------------
+        // Venues Routes
+        // ---------------------------------------------------------------------
+        // Venue records that events link to, for "everything at Cain's".
+        // Owner: Will (Coordinator/Backend Lead)
+        .nest("/venues", venues::routes())
+
+        // ---------------------------------------------------------------------
+        // Admin Routes
+        // ---------------------------------------------------------------------
+        // Run background jobs (preference learning, scrapers) on demand.
+        // Owner: Will (Coordinator/Backend Lead)
+        .nest("/admin", admin::routes())
+
+        // ---------------------------------------------------------------------
+        // Chat Routes
+        // ---------------------------------------------------------------------
+        // Natural language interface powered by the configured LLM provider.
+        // Interprets user queries like "What's happening downtown Friday?"
+        // and returns personalized event recommendations.
+        // Owner: Ben (AI Engineer)
+        .nest("/chat", chat::routes())
+
+        // ---------------------------------------------------------------------
+        // Health Routes
+        // ---------------------------------------------------------------------
+        // Liveness and readiness for load balancers, whether the chat
+        // model answers, and Prometheus metrics.
+        // Owner: Will (Coordinator/Backend Lead), Ben (AI Engineer)
+        .merge(health::routes())
 }
\ No newline at end of file
@@ -5,13 +5,43 @@
 //!
 //! ## Endpoints
 //! - `POST /api/users`                    - Create a new user
+//! - `POST /api/users/guest`              - Start a guest session (user + token)
+//! - `POST /api/users/:id/claim`          - Turn a guest into a real account
 //! - `GET  /api/users/:id`                - Get user by ID
+//! - `PATCH /api/users/:id`               - Edit name, email, location
+//! - `PUT  /api/users/:id/password`       - Set or change the password
+//! - `POST /api/users/:id/verify`         - Confirm the email (token from the link)
+//! - `POST /api/users/:id/resend-verification` - Mail a new verification link
 //! - `GET  /api/users/:id/profile`        - Get full profile (for LLM)
 //! - `GET  /api/users/:id/preferences`    - Get category preferences
 //! - `POST /api/users/:id/preferences`    - Add/update a preference
 //! - `PUT  /api/users/:id/preferences`    - Update user settings
-//! - `GET  /api/users/:id/interactions`   - Get interaction history
+//! - `POST /api/users/:id/preferences/bulk` - Add/update several preferences
+//! - `DELETE /api/users/:id/preferences/:category` - Remove a preference
+//! - `GET  /api/users/:id/interactions`   - Get interaction history (filtered, paged)
 //! - `POST /api/users/:id/interactions`   - Record an interaction
+//! - `DELETE /api/users/:id/interactions?event_id=&type=` - Remove the latest match
+//! - `DELETE /api/users/:id/interactions/:interaction_id` - Remove one interaction
+//! - `GET  /api/users/:id/saved`          - Events the user has saved
+//! - `GET  /api/users/:id/saved.ics`      - Calendar feed of saved events
+//! - `GET  /api/users/:id/recommendations` - Upcoming events ranked for the user
+//! - `GET  /api/users/:id/stats`          - Aggregate activity counts
+//! - `GET  /api/users/:id/export`         - Download all of a user's data (JSON)
+//! - `GET  /api/users/:id/follows`        - List followed venues and categories
+//! - `POST /api/users/:id/follows`        - Follow a venue or category
+//! - `DELETE /api/users/:id/follows/:target_type/:target_value` - Unfollow
+//! - `GET  /api/users/:id/searches`       - List saved searches
+//! - `POST /api/users/:id/searches`       - Save a search
+//! - `PUT  /api/users/:id/searches/:search_id` - Replace a saved search
+//! - `DELETE /api/users/:id/searches/:search_id` - Remove a saved search
+//! - `GET  /api/users/:id/notifications`  - List notifications (`?unread=true`, paged)
+//! - `GET  /api/users/:id/notifications/unread-count` - Unread badge count
+//! - `POST /api/users/:id/notifications/:notification_id/read` - Mark one read
+//!
+//! ## Authentication
+//! Every `/:id` route except `saved.ics` and `verify` needs `Authorization: Bearer <token>`
+//! for that same user (tokens come from `/api/auth` or `/api/users/guest`).
+//! A missing or expired token is a 401; another user's token is a 403.
 //!
 //! ## Owner
 //! Will (Coordinator/Backend Lead)
@@ -21,31 +51,91 @@
 // =============================================================================
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::{get, post, put},
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use sqlx::PgPool;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+use super::auth::{frontend_url, password_matches, UserCredentials};
+use crate::auth;
+use crate::db::{
+    take_page, Cursor, EVENT_COLUMNS, FOLLOWS_FROM, FOLLOW_COLUMNS, NOTIFICATION_COLUMNS, PREFERENCE_COLUMNS, SAVED_SEARCH_COLUMNS,
+    UPCOMING_FILTER, USER_COLUMNS,
+};
+use crate::error::AppError;
 use crate::models::{
-    CreateUser, CreateUserInteraction, CreateUserPreference, UpdateUserPreferences,
-    User, UserInteraction, UserInteractionWithEvent, UserPreference, UserProfile,
+    AuthResponse, CategoryEngagement, ClaimGuest, CreateFollow, CreateSavedSearch, CreateUser, CreateUserInteraction, CreateUserPreference, Event,
+    FieldError, FollowTarget, InteractionCounts, InteractionPage, Notification, NotificationPage, SavedSearch, SetPassword,
+    UnreadCount, UserFollow, VerifyEmail, UpdateUser, UpdateUserPreferences, User, UserInteraction,
+    UserInteractionWithEvent, UserPreference, UserProfile, UserStats,
 };
+use crate::routes::AppState;
+use crate::services::analytics;
+use crate::services::categories;
+use crate::services::dates;
+use crate::services::export;
+use crate::services::guests;
+use crate::services::recommendation::{self, RecommendedEvent};
+use crate::services::preferences::MAX_PREFERENCE_WEIGHT;
+use crate::services::scheduler;
+use crate::services::verification;
+use crate::services::ics::IcsCalendar;
 
 // =============================================================================
 // ROUTE DEFINITIONS
 // =============================================================================
 
 /// Creates the router for all user endpoints.
-pub fn routes() -> Router<PgPool> {
-    Router::new()
-        .route("/", post(create_user))
-        .route("/:id", get(get_user))
+///
+/// Everything under `/:id` requires a bearer token for that user (see
+/// `auth::require_path_user`), except the `saved.ics` feed, which calendar
+/// apps fetch with the user's `calendar_token` instead, and `verify`,
+/// whose emailed token is the proof.
+pub fn routes() -> Router<AppState> {
+    let scoped = Router::new()
+        .route("/:id", get(get_user).patch(update_user))
+        .route("/:id/password", put(set_password))
+        .route("/:id/claim", post(claim_guest))
+        .route("/:id/resend-verification", post(resend_verification))
         .route("/:id/profile", get(get_user_profile))
         .route("/:id/preferences", get(get_preferences).post(add_preference).put(update_preferences))
-        .route("/:id/interactions", get(get_interactions).post(add_interaction))
+        .route("/:id/preferences/bulk", post(bulk_add_preferences))
+        .route("/:id/preferences/:category", delete(delete_preference))
+        .route(
+            "/:id/interactions",
+            get(get_interactions).post(add_interaction).delete(delete_latest_interaction),
+        )
+        .route("/:id/interactions/:interaction_id", delete(delete_interaction))
+        .route("/:id/saved", get(get_saved_events))
+        .route("/:id/recommendations", get(get_recommendations))
+        .route("/:id/stats", get(get_user_stats))
+        .route("/:id/export", get(export_user_data))
+        .route("/:id/follows", get(get_follows).post(follow))
+        .route("/:id/follows/:target_type/:target_value", delete(unfollow))
+        .route("/:id/searches", get(get_saved_searches).post(create_saved_search))
+        .route(
+            "/:id/searches/:search_id",
+            put(replace_saved_search).delete(delete_saved_search),
+        )
+        .route("/:id/notifications", get(get_notifications))
+        .route("/:id/notifications/unread-count", get(get_unread_count))
+        .route("/:id/notifications/:notification_id/read", post(mark_notification_read))
+        .route_layer(middleware::from_fn(auth::require_path_user));
+
+    Router::new()
+        .route("/", post(create_user))
+        .route("/guest", post(create_guest))
+        .route("/:id/saved.ics", get(get_saved_calendar))
+        .route("/:id/verify", post(verify_email))
+        .merge(scoped)
 }
 
 // =============================================================================
@@ -56,48 +146,149 @@ pub fn routes() -> Router<PgPool> {
 ///
 /// # Endpoint
 /// `POST /api/users`
+///
+/// A verification link is mailed to the new address in the background.
+///
+/// # Returns
+/// - `201 Created` with the new user
+/// - `409 Conflict` if the email is already registered
 async fn create_user(
     State(pool): State<PgPool>,
     Json(payload): Json<CreateUser>,
-) -> Result<(StatusCode, Json<User>), StatusCode> {
-    let id = Uuid::new_v4();
-    let now = chrono::Utc::now();
+) -> Result<(StatusCode, Json<User>), AppError> {
+    let user = insert_user(&pool, payload).await?;
+    queue_verification(&user);
+    Ok((StatusCode::CREATED, Json(user)))
+}
 
-    sqlx::query(
-        r#"
-        INSERT INTO users (id, email, name, location_preference, radius_miles, price_max, family_friendly_only, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        "#,
-    )
-        .bind(&id)
-        .bind(&payload.email)
-        .bind(&payload.name)
-        .bind(&payload.location_preference)
-        .bind(&payload.radius_miles)
-        .bind(&payload.price_max)
-        .bind(&payload.family_friendly_only)
-        .bind(&now)
-        .bind(&now)
-        .execute(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+// =============================================================================
+// HANDLER: GUEST SESSIONS
+// =============================================================================
 
-    let user = User {
-        id,
-        email: payload.email,
-        name: payload.name,
-        location_preference: payload.location_preference,
-        radius_miles: payload.radius_miles,
-        price_max: payload.price_max,
-        family_friendly_only: payload.family_friendly_only,
-        created_at: now,
-        updated_at: now,
+/// Creates a guest user and signs them in, so interactions and
+/// preferences are kept before signup.
+///
+/// # Endpoint
+/// `POST /api/users/guest` (no body)
+///
+/// The guest gets a placeholder email (see `services::guests`) and can't
+/// log in by email; the client keeps the token until it claims the account.
+///
+/// # Returns
+/// - `201 Created` with an `AuthResponse`
+/// - `503 Service Unavailable` if `JWT_SECRET` isn't set
+async fn create_guest(State(pool): State<PgPool>) -> Result<(StatusCode, Json<AuthResponse>), AppError> {
+    // Fail before creating a row nobody can use
+    auth::issue_token(Uuid::nil())?;
+
+    let user = sqlx::query_as::<_, User>(&format!(
+        "INSERT INTO users (email, calendar_token, is_guest) VALUES ($1, $2, TRUE) RETURNING {}",
+        USER_COLUMNS
+    ))
+        .bind(guests::guest_email(Uuid::new_v4()))
+        .bind(Uuid::new_v4().simple().to_string())
+        .fetch_one(&pool)
+        .await?;
+
+    let (token, expires_at) = auth::issue_token(user.id)?;
+    Ok((StatusCode::CREATED, Json(AuthResponse { token, expires_at, user })))
+}
+
+/// Upgrades a guest to a real account.
+///
+/// # Endpoint
+/// `POST /api/users/:id/claim` (guest's token)
+///
+/// # Request Body
+/// ```json
+/// { "email": "sam@example.com", "name": "Sam", "password": "..." }
+/// ```
+///
+/// - New email: the guest row keeps its id and becomes a normal account
+///   with that email, name and (optional) password. A verification link
+///   is mailed to it.
+/// - Registered email: the caller must pass that account's password, as at
+///   login. An account without a password (e.g. a Google sign-in) can't be
+///   claimed into this way; sign in to it and use its own token. The guest's preferences, interactions, follows
+///   and saved searches are merged into it in one transaction
+///   (`services::guests::merge_into`) and the guest is deleted. The token
+///   returned is for the existing account.
+///
+/// # Returns
+/// - `200 OK` with an `AuthResponse` for the resulting account
+/// - `401 Unauthorized` if the existing account's password is missing or
+///   wrong, or it has none
+/// - `409 Conflict` if the account isn't a guest
+/// - `422 Unprocessable Entity` for a bad email or too-short password
+async fn claim_guest(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ClaimGuest>,
+) -> Result<Json<AuthResponse>, AppError> {
+    let email = payload.email.trim();
+    if !email.contains('@') || email.ends_with(guests::GUEST_EMAIL_DOMAIN) {
+        return Err(AppError::invalid("email", "must be an email address"));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let is_guest: bool = sqlx::query_scalar("SELECT is_guest FROM users WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
+    if !is_guest {
+        return Err(AppError::Conflict("account is not a guest".to_string()));
+    }
+
+    let existing = sqlx::query_as::<_, UserCredentials>(&format!(
+        "SELECT {}, password_hash FROM users WHERE email = $1 FOR UPDATE",
+        USER_COLUMNS
+    ))
+        .bind(email)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let user = match existing {
+        Some(UserCredentials { user, password_hash }) => {
+            if !password_matches(payload.password, password_hash).await {
+                return Err(AppError::Unauthorized("invalid credentials".to_string()));
+            }
+            guests::merge_into(&mut tx, id, user.id).await?;
+            user
+        }
+        None => {
+            let password_hash = match payload.password {
+                Some(password) => {
+                    auth::validate_password("password", &password)?;
+                    Some(auth::hash_password(password).await)
+                }
+                None => None,
+            };
+
+            sqlx::query_as::<_, User>(&format!(
+                r#"
+                UPDATE users
+                SET email = $2, name = COALESCE($3, name), password_hash = $4, is_guest = FALSE
+                WHERE id = $1
+                RETURNING {}
+                "#,
+                USER_COLUMNS
+            ))
+                .bind(id)
+                .bind(email)
+                .bind(&payload.name)
+                .bind(&password_hash)
+                .fetch_one(&mut *tx)
+                .await?
+        }
     };
 
-    Ok((StatusCode::CREATED, Json(user)))
+    tx.commit().await?;
+    queue_verification(&user);
+
+    let (token, expires_at) = auth::issue_token(user.id)?;
+    Ok(Json(AuthResponse { token, expires_at, user }))
 }
 
 // =============================================================================
@@ -111,26 +302,219 @@ async fn create_user(
 async fn get_user(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<User>, StatusCode> {
-    let user = sqlx::query_as::<_, User>(
-        r#"
-        SELECT id, email, name, location_preference, radius_miles, price_max, family_friendly_only, created_at, updated_at
-        FROM users
-        WHERE id = $1
-        "#
-    )
+) -> Result<Json<User>, AppError> {
+    let user = sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM users WHERE id = $1",
+        USER_COLUMNS
+    ))
         .bind(id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     match user {
         Some(u) => Ok(Json(u)),
-        None => Err(StatusCode::NOT_FOUND),
+        None => Err(AppError::not_found("user")),
+    }
+}
+
+// =============================================================================
+// HANDLER: UPDATE USER
+// =============================================================================
+
+/// Updates the profile fields present in the payload.
+///
+/// # Endpoint
+/// `PATCH /api/users/:id`
+///
+/// # Request Body
+/// ```json
+/// {
+///   "name": "Sam", "email": "sam@example.com", "location_preference": "Downtown",
+///   "home_latitude": 36.15, "home_longitude": -95.99, "preferred_radius_km": 15
+/// }
+/// ```
+///
+/// Any subset of the fields may be sent; an empty body changes nothing.
+/// The home point is replaced as a whole, so `home_latitude` and
+/// `home_longitude` go together.
+///
+/// # Returns
+/// - `200 OK` with the updated user
+/// - `404 Not Found` if the user doesn't exist
+/// - `409 Conflict` if the new email belongs to another account
+/// - `422 Unprocessable Entity` if the email is blank or has no `@`, the
+///   home point is half-given or out of range, or the radius isn't positive
+async fn update_user(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateUser>,
+) -> Result<Json<User>, AppError> {
+    if payload.is_empty() {
+        return get_user(State(pool), Path(id)).await;
+    }
+    payload.validate()?;
+
+    let email = payload.email.as_deref().map(str::trim);
+    if email.is_some_and(|e| !e.contains('@')) {
+        return Err(AppError::invalid("email", "must be an email address"));
+    }
+
+    // COALESCE keeps the current value for every field left out; the unique
+    // index on email turns a clash into a 409 via AppError
+    let user = sqlx::query_as::<_, User>(&format!(
+        r#"
+        UPDATE users
+        SET name = COALESCE($2, name),
+            email = COALESCE($3, email),
+            email_verified_at = CASE WHEN $3 IS NULL OR $3 = email THEN email_verified_at END,
+            location_preference = COALESCE($4, location_preference),
+            home_latitude = COALESCE($5, home_latitude),
+            home_longitude = COALESCE($6, home_longitude),
+            preferred_radius_km = COALESCE($7, preferred_radius_km)
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        USER_COLUMNS
+    ))
+        .bind(id)
+        .bind(&payload.name)
+        .bind(email)
+        .bind(&payload.location_preference)
+        .bind(payload.home_latitude)
+        .bind(payload.home_longitude)
+        .bind(payload.preferred_radius_km)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
+
+    if email.is_some() {
+        queue_verification(&user);
+    }
+
+    Ok(Json(user))
+}
+
+// =============================================================================
+// HANDLER: EMAIL VERIFICATION
+// =============================================================================
+
+/// Marks the user's email verified.
+///
+/// # Endpoint
+/// `POST /api/users/:id/verify` with `{"token": "..."}` (no bearer token;
+/// the link may be opened on another device)
+///
+/// The token must be for this user and their current email, so links sent
+/// before an email change stop working. Verifying twice is fine.
+///
+/// # Returns
+/// - `200 OK` with the user
+/// - `400 Bad Request` for a bad, expired or outdated token
+/// - `503 Service Unavailable` if `JWT_SECRET` isn't set
+async fn verify_email(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<VerifyEmail>,
+) -> Result<Json<User>, AppError> {
+    let (user_id, email) = verification::verify_verification_token(payload.token.trim(), &auth::secret()?)?;
+
+    let user = sqlx::query_as::<_, User>(&format!(
+        r#"
+        UPDATE users
+        SET email_verified_at = COALESCE(email_verified_at, NOW())
+        WHERE id = $1 AND id = $2 AND email = $3
+        RETURNING {}
+        "#,
+        USER_COLUMNS
+    ))
+        .bind(id)
+        .bind(user_id)
+        .bind(&email)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("invalid or expired verification token".to_string()))?;
+
+    Ok(Json(user))
+}
+
+/// Mails the user a new verification link.
+///
+/// # Endpoint
+/// `POST /api/users/:id/resend-verification`
+///
+/// # Returns
+/// - `202 Accepted` once the message is handed to the mailer
+/// - `404 Not Found` if the user doesn't exist
+/// - `409 Conflict` if the email is already verified, or the user is a guest
+/// - `502 Bad Gateway` if the mail server refuses it
+async fn resend_verification(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let Json(user) = get_user(State(pool), Path(id)).await?;
+
+    if user.is_guest {
+        return Err(AppError::Conflict("guest accounts have no email to verify".to_string()));
+    }
+    if user.email_verified_at.is_some() {
+        return Err(AppError::Conflict("email is already verified".to_string()));
+    }
+
+    verification::send_verification(&user, &frontend_url()).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+// =============================================================================
+// HANDLER: SET PASSWORD
+// =============================================================================
+
+/// Sets a password on an account, or changes it.
+///
+/// # Endpoint
+/// `PUT /api/users/:id/password` with
+/// `{"password": "...", "current_password": "..."}`
+///
+/// `current_password` is only needed when the account already has one, so
+/// password-less accounts (e.g. Google sign-ins) can add one with just
+/// their token.
+///
+/// # Returns
+/// - `204 No Content` on success
+/// - `401 Unauthorized` if `current_password` is missing or wrong
+/// - `404 Not Found` if the user doesn't exist
+/// - `422 Unprocessable Entity` if the new password is too short
+async fn set_password(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SetPassword>,
+) -> Result<StatusCode, AppError> {
+    let (current_hash,): (Option<String>,) =
+        sqlx::query_as("SELECT password_hash FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| AppError::not_found("user"))?;
+
+    if let Some(hash) = current_hash {
+        let verified = match payload.current_password {
+            Some(current) => auth::verify_password(current, hash).await,
+            None => false,
+        };
+        if !verified {
+            return Err(AppError::Unauthorized("current password is incorrect".to_string()));
+        }
     }
+
+    auth::validate_password("password", &payload.password)?;
+    let new_hash = auth::hash_password(payload.password).await;
+
+    sqlx::query("UPDATE users SET password_hash = $2, updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(new_hash)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // =============================================================================
@@ -143,47 +527,49 @@ async fn get_user(
 /// `GET /api/users/:id/profile`
 ///
 /// Includes:
-/// - Basic user info
+/// - Basic user info, including the home point and `preferred_radius_km`
 /// - All category preferences
+/// - Followed venues and categories (with venue names)
 /// - Recent 20 interactions with event details
 async fn get_user_profile(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<UserProfile>, StatusCode> {
+) -> Result<Json<UserProfile>, AppError> {
+    Ok(Json(load_profile(&pool, id).await?))
+}
+
+/// Loads everything `UserProfile` holds. Shared with the chat route, which
+/// hands the profile to the LLM.
+pub(crate) async fn load_profile(pool: &PgPool, id: Uuid) -> Result<UserProfile, AppError> {
     // Fetch user
-    let user = sqlx::query_as::<_, User>(
-        r#"
-        SELECT id, email, name, location_preference, radius_miles, price_max, family_friendly_only, created_at, updated_at
-        FROM users
-        WHERE id = $1
-        "#
-    )
+    let user = sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM users WHERE id = $1",
+        USER_COLUMNS
+    ))
         .bind(id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
 
     // Fetch preferences
     let preferences = sqlx::query_as::<_, UserPreference>(
-        "SELECT id, user_id, category, weight, created_at FROM user_preferences WHERE user_id = $1"
-    )
+        &format!(
+        "SELECT {} FROM user_preferences WHERE user_id = $1",
+        PREFERENCE_COLUMNS
+    ))
         .bind(id)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .fetch_all(pool)
+        .await?;
+
+    let follows = fetch_follows(pool, id).await?;
 
-    // Fetch recent interactions with event details
+    // Fetch recent interactions with event details.
+    // Archived events are deliberately not filtered out: what a user went to
+    // last season is still a good signal for what they'll like next.
     let recent_interactions = sqlx::query_as::<_, UserInteractionWithEvent>(
         r#"
         SELECT ui.interaction_type, e.title as event_title,
-               (SELECT categories[1] FROM events WHERE id = ui.event_id) as event_category,
+               e.categories[1] as event_category,
                ui.created_at
         FROM user_interactions ui
         JOIN events e ON ui.event_id = e.id
@@ -193,18 +579,15 @@ async fn get_user_profile(
         "#
     )
         .bind(id)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .fetch_all(pool)
+        .await?;
 
-    Ok(Json(UserProfile {
+    Ok(UserProfile {
         user,
         preferences,
+        follows,
         recent_interactions,
-    }))
+    })
 }
 
 // =============================================================================
@@ -218,17 +601,15 @@ async fn get_user_profile(
 async fn get_preferences(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<UserPreference>>, StatusCode> {
+) -> Result<Json<Vec<UserPreference>>, AppError> {
     let preferences = sqlx::query_as::<_, UserPreference>(
-        "SELECT id, user_id, category, weight, created_at FROM user_preferences WHERE user_id = $1 ORDER BY weight DESC"
-    )
+        &format!(
+        "SELECT {} FROM user_preferences WHERE user_id = $1 ORDER BY weight DESC",
+        PREFERENCE_COLUMNS
+    ))
         .bind(id)
         .fetch_all(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     Ok(Json(preferences))
 }
@@ -243,39 +624,117 @@ async fn get_preferences(
 /// `POST /api/users/:id/preferences`
 ///
 /// Uses UPSERT - creates if new, updates if exists.
+///
+/// A weight of 0 is rejected rather than stored: a "neutral" row only adds
+/// noise to the LLM prompt. To clear a preference, DELETE it instead.
+///
+/// The category must be in the taxonomy (`services::categories`); it is
+/// stored lowercased.
+///
+/// # Returns
+/// - `201 Created` with the preference
+/// - `404 Not Found` if the user doesn't exist
+/// - `422 Unprocessable Entity` if the category is unknown or the weight is
+///   0 or outside ±`MAX_PREFERENCE_WEIGHT`
 async fn add_preference(
     State(pool): State<PgPool>,
     Path(user_id): Path<Uuid>,
-    Json(payload): Json<CreateUserPreference>,
-) -> Result<(StatusCode, Json<UserPreference>), StatusCode> {
-    let id = Uuid::new_v4();
-    let now = chrono::Utc::now();
+    Json(mut payload): Json<CreateUserPreference>,
+) -> Result<(StatusCode, Json<UserPreference>), AppError> {
+    payload.category = categories::normalize(&payload.category);
 
-    // Use INSERT ... ON CONFLICT for upsert behavior
-    let result = sqlx::query_as::<_, UserPreference>(
-        r#"
-        INSERT INTO user_preferences (id, user_id, category, weight, created_at)
-        VALUES ($1, $2, $3, $4, $5)
-        ON CONFLICT (user_id, category)
-        DO UPDATE SET weight = EXCLUDED.weight
-        RETURNING id, user_id, category, weight, created_at
-        "#
-    )
-        .bind(&id)
-        .bind(&user_id)
-        .bind(&payload.category)
-        .bind(&payload.weight)
-        .bind(&now)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let mut conn = pool.acquire().await?;
+    let known = categories::known_categories(&mut conn).await?;
+    validate_preference(&payload, &known)?;
+
+    let result = upsert_preference(&mut conn, user_id, &payload).await?;
 
     Ok((StatusCode::CREATED, Json(result)))
 }
 
+// =============================================================================
+// HANDLER: BULK ADD/UPDATE PREFERENCES
+// =============================================================================
+
+/// Upserts several category preferences at once (onboarding).
+///
+/// # Endpoint
+/// `POST /api/users/:id/preferences/bulk`
+///
+/// # Request Body
+/// ```json
+/// [{ "category": "music", "weight": 5 }, { "category": "sports", "weight": -3 }]
+/// ```
+///
+/// All preferences are written in one transaction with the same upsert as
+/// `add_preference`, so either all apply or none do. Categories not in the
+/// payload are left alone.
+///
+/// # Returns
+/// - `200 OK` with the user's full preference list, strongest first
+/// - `404 Not Found` if the user doesn't exist
+/// - `422 Unprocessable Entity` if any entry is invalid (same rules as
+///   `add_preference`) or a category appears more than once
+async fn bulk_add_preferences(
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Json(mut payload): Json<Vec<CreateUserPreference>>,
+) -> Result<Json<Vec<UserPreference>>, AppError> {
+    for preference in &mut payload {
+        preference.category = categories::normalize(&preference.category);
+    }
+
+    let mut tx = pool.begin().await?;
+    let known = categories::known_categories(&mut tx).await?;
+    validate_bulk_preferences(&payload, &known)?;
+
+    for preference in &payload {
+        upsert_preference(&mut tx, user_id, preference).await?;
+    }
+
+    let preferences = sqlx::query_as::<_, UserPreference>(
+        &format!(
+        "SELECT {} FROM user_preferences WHERE user_id = $1 ORDER BY weight DESC",
+        PREFERENCE_COLUMNS
+    ))
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(preferences))
+}
+
+// =============================================================================
+// HANDLER: DELETE PREFERENCE
+// =============================================================================
+
+/// Removes the user's preference for one category.
+///
+/// # Endpoint
+/// `DELETE /api/users/:id/preferences/:category`
+///
+/// # Returns
+/// - `204 No Content` on success
+/// - `404 Not Found` if the user has no preference for that category
+async fn delete_preference(
+    State(pool): State<PgPool>,
+    Path((user_id, category)): Path<(Uuid, String)>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM user_preferences WHERE user_id = $1 AND category = $2")
+        .bind(user_id)
+        .bind(&category)
+        .execute(&pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("preference"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // =============================================================================
 // HANDLER: UPDATE USER PREFERENCES (settings)
 // =============================================================================
@@ -288,7 +747,7 @@ async fn update_preferences(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateUserPreferences>,
-) -> Result<Json<User>, StatusCode> {
+) -> Result<Json<User>, AppError> {
     // Build dynamic update query
     let mut updates = vec!["updated_at = NOW()".to_string()];
 
@@ -310,20 +769,17 @@ async fn update_preferences(
         UPDATE users
         SET {}
         WHERE id = $1
-        RETURNING id, email, name, location_preference, radius_miles, price_max, family_friendly_only, created_at, updated_at
+        RETURNING {}
         "#,
-        updates.join(", ")
+        updates.join(", "),
+        USER_COLUMNS
     );
 
     let user = sqlx::query_as::<_, User>(&query)
         .bind(id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
 
     Ok(Json(user))
 }
@@ -332,32 +788,101 @@ async fn update_preferences(
 // HANDLER: GET INTERACTIONS
 // =============================================================================
 
-/// Returns all interactions for a user.
+/// Default number of interactions per page.
+const DEFAULT_INTERACTIONS_LIMIT: u32 = 100;
+
+/// Largest interactions page a client may request.
+const MAX_INTERACTIONS_LIMIT: u32 = 200;
+
+/// Query parameters for the interaction history.
+#[derive(Debug, Default, Deserialize)]
+pub struct InteractionsQuery {
+    /// Only this type; either spelling matches (`save` also returns `saved`)
+    #[serde(rename = "type")]
+    pub interaction_type: Option<String>,
+
+    /// Only interactions at or after this time
+    pub since: Option<DateTime<Utc>>,
+
+    /// Page size (default: 100, max: 200)
+    pub limit: Option<u32>,
+
+    /// `next_cursor` from the previous page
+    pub before: Option<String>,
+}
+
+/// Returns a user's interactions, newest first, one page at a time.
 ///
 /// # Endpoint
-/// `GET /api/users/:id/interactions`
+/// `GET /api/users/:id/interactions?type=view&since=2026-01-01T00:00:00Z&limit=50&before=...`
+///
+/// Pages are keyed on `(created_at, id)`, so interactions recorded while a
+/// client is paging never shift or repeat rows on later pages.
+///
+/// # Returns
+/// - `200 OK` with an `InteractionPage`
+/// - `400 Bad Request` if `before` isn't a valid cursor
 async fn get_interactions(
     State(pool): State<PgPool>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<UserInteraction>>, StatusCode> {
-    let interactions = sqlx::query_as::<_, UserInteraction>(
+    Query(params): Query<InteractionsQuery>,
+) -> Result<Json<InteractionPage>, AppError> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_INTERACTIONS_LIMIT)
+        .clamp(1, MAX_INTERACTIONS_LIMIT);
+    let cursor = match params.before {
+        Some(ref raw) => Some(Cursor::decode(raw).ok_or_else(|| AppError::BadRequest("invalid cursor".into()))?),
+        None => None,
+    };
+
+    let mut conditions = vec!["user_id = $1".to_string()];
+    let mut next_param = 2;
+    if params.interaction_type.is_some() {
+        conditions.push(format!("interaction_type = ANY(${})", next_param));
+        next_param += 1;
+    }
+    if params.since.is_some() {
+        conditions.push(format!("created_at >= ${}", next_param));
+        next_param += 1;
+    }
+    if cursor.is_some() {
+        conditions.push(format!("(created_at, id) < (${}, ${})", next_param, next_param + 1));
+    }
+
+    // Fetch one extra row to find out whether there's an older page
+    let query = format!(
         r#"
         SELECT id, user_id, event_id, interaction_type, event_category, event_venue, created_at
         FROM user_interactions
-        WHERE user_id = $1
-        ORDER BY created_at DESC
-        LIMIT 100
-        "#
-    )
-        .bind(id)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        WHERE {}
+        ORDER BY created_at DESC, id DESC
+        LIMIT {}
+        "#,
+        conditions.join(" AND "),
+        limit + 1
+    );
+
+    let mut sql = sqlx::query_as::<_, UserInteraction>(&query).bind(id);
+    if let Some(ref t) = params.interaction_type {
+        sql = sql.bind(analytics::spellings_of(t));
+    }
+    if let Some(since) = params.since {
+        sql = sql.bind(since);
+    }
+    if let Some(c) = cursor {
+        sql = sql.bind(c.start_time).bind(c.id);
+    }
+
+    let mut interactions = sql.fetch_all(&pool).await?;
 
-    Ok(Json(interactions))
+    // Cursor's time slot holds created_at here
+    let next_cursor = take_page(&mut interactions, limit as usize, |i| Cursor {
+        start_time: i.created_at,
+        id: i.id,
+    });
+
+    Ok(Json(InteractionPage { interactions, next_cursor }))
 }
 
 // =============================================================================
@@ -370,27 +895,43 @@ async fn get_interactions(
 /// `POST /api/users/:id/interactions`
 ///
 /// Automatically captures event category and venue for ML.
+///
+/// The event is looked up first (its details are copied onto the row), so
+/// an unknown `event_id` is a 404 before anything is written. An unknown
+/// user is caught by the foreign key and also comes back as a 404.
+///
+/// Views are deduplicated: if the user already viewed this event within
+/// the last `VIEW_DEDUPE_MINUTES` (default 30), that row's `created_at` is
+/// bumped instead of adding another. Saves, attends and dismisses are
+/// always recorded.
+///
+/// # Returns
+/// - `201 Created` with the new interaction
+/// - `200 OK` with the refreshed view when a recent one existed
+/// - `404 Not Found` with "event not found" or "user not found"
 async fn add_interaction(
     State(pool): State<PgPool>,
     Path(user_id): Path<Uuid>,
     Json(payload): Json<CreateUserInteraction>,
-) -> Result<(StatusCode, Json<UserInteraction>), StatusCode> {
+) -> Result<(StatusCode, Json<UserInteraction>), AppError> {
     let id = Uuid::new_v4();
     let now = chrono::Utc::now();
 
     // Fetch event details for denormalization
-    let event = sqlx::query_as::<_, (Option<String>, Option<String>)>(
-        "SELECT (SELECT categories[1] FROM events WHERE id = $1), venue FROM events WHERE id = $1"
+    let (event_category, event_venue) = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+        "SELECT categories[1], venue FROM events WHERE id = $1"
     )
-        .bind(&payload.event_id)
+        .bind(payload.event_id)
         .fetch_optional(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?
+        .ok_or_else(|| AppError::not_found("event"))?;
 
-    let (event_category, event_venue) = event.unwrap_or((None, None));
+    if analytics::is_view(&payload.interaction_type) {
+        let window = scheduler::env_u64("VIEW_DEDUPE_MINUTES", analytics::DEFAULT_VIEW_DEDUPE_MINUTES);
+        if let Some(view) = refresh_recent_view(&pool, user_id, payload.event_id, window).await? {
+            return Ok((StatusCode::OK, Json(view)));
+        }
+    }
 
     sqlx::query(
         r#"
@@ -398,19 +939,15 @@ async fn add_interaction(
         VALUES ($1, $2, $3, $4, $5, $6, $7)
         "#
     )
-        .bind(&id)
-        .bind(&user_id)
-        .bind(&payload.event_id)
+        .bind(id)
+        .bind(user_id)
+        .bind(payload.event_id)
         .bind(&payload.interaction_type)
         .bind(&event_category)
         .bind(&event_venue)
-        .bind(&now)
+        .bind(now)
         .execute(&pool)
-        .await
-        .map_err(|e| {
-            eprintln!("Database error: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     let interaction = UserInteraction {
         id,
@@ -423,4 +960,1897 @@ async fn add_interaction(
     };
 
     Ok((StatusCode::CREATED, Json(interaction)))
-}
\ No newline at end of file
+}
+
+// =============================================================================
+// HANDLER: DELETE INTERACTIONS
+// =============================================================================
+
+/// Deletes one interaction by id (e.g. an accidental save).
+///
+/// # Endpoint
+/// `DELETE /api/users/:id/interactions/:interaction_id`
+///
+/// The interaction must belong to the user in the path. Another user's
+/// interaction gets the same 404 as a missing one, so ids can't be probed.
+///
+/// # Returns
+/// - `204 No Content` on success
+/// - `404 Not Found` if the user has no such interaction
+async fn delete_interaction(
+    State(pool): State<PgPool>,
+    Path((user_id, interaction_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM user_interactions WHERE id = $1 AND user_id = $2")
+        .bind(interaction_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("interaction"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Query parameters for deleting the latest matching interaction.
+#[derive(Debug, Deserialize)]
+pub struct DeleteInteractionQuery {
+    pub event_id: Uuid,
+
+    /// Interaction type; either spelling matches (`save` also removes `saved`)
+    #[serde(rename = "type")]
+    pub interaction_type: String,
+}
+
+/// Deletes the user's most recent interaction of a type with an event.
+///
+/// # Endpoint
+/// `DELETE /api/users/:id/interactions?event_id=...&type=save`
+///
+/// Lets the frontend un-save without knowing the interaction id. Only the
+/// latest match is removed; if the user saved twice, one save remains.
+///
+/// # Returns
+/// - `204 No Content` on success
+/// - `404 Not Found` if there is no matching interaction
+async fn delete_latest_interaction(
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<DeleteInteractionQuery>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM user_interactions
+        WHERE id = (
+            SELECT id FROM user_interactions
+            WHERE user_id = $1 AND event_id = $2 AND interaction_type = ANY($3)
+            ORDER BY created_at DESC
+            LIMIT 1
+        )
+        "#,
+    )
+        .bind(user_id)
+        .bind(params.event_id)
+        .bind(analytics::spellings_of(&params.interaction_type))
+        .execute(&pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("interaction"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// HANDLER: SAVED EVENTS
+// =============================================================================
+
+/// Query parameters for the saved-events list.
+#[derive(Debug, Deserialize)]
+pub struct SavedEventsQuery {
+    /// Only events that haven't finished yet (default: true)
+    #[serde(default = "default_true")]
+    pub upcoming_only: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Returns the events a user has saved, soonest first ("My Events").
+///
+/// # Endpoint
+/// `GET /api/users/:id/saved?upcoming_only=true`
+///
+/// An event counts as saved when the user's latest save/dismiss
+/// interaction with it is a save, so save -> dismiss -> save shows it and
+/// save -> dismiss doesn't.
+///
+/// # Returns
+/// - `200 OK` with a list of events (possibly empty)
+/// - `404 Not Found` if the user doesn't exist
+async fn get_saved_events(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<SavedEventsQuery>,
+) -> Result<Json<Vec<Event>>, AppError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&pool)
+        .await?;
+
+    if !exists {
+        return Err(AppError::not_found("user"));
+    }
+
+    let events = fetch_saved_events(&pool, id, params.upcoming_only).await?;
+
+    Ok(Json(events))
+}
+
+// =============================================================================
+// HANDLER: SAVED EVENTS CALENDAR FEED
+// =============================================================================
+
+/// Query parameters for the calendar feed.
+#[derive(Debug, Deserialize)]
+pub struct CalendarFeedQuery {
+    /// The user's `calendar_token`
+    pub token: String,
+}
+
+/// Returns an iCalendar subscription feed of the user's saved events.
+///
+/// # Endpoint
+/// `GET /api/users/:id/saved.ics?token=...`
+///
+/// Calendar apps can't send auth headers, so the user's `calendar_token`
+/// goes in the query string instead.
+///
+/// An event is in the feed when the user's most recent save/dismiss
+/// interaction with it is a save - dismissing a saved event removes it.
+///
+/// # Returns
+/// - `200 OK` with `Content-Type: text/calendar`
+/// - `404 Not Found` if the user doesn't exist or the token doesn't match
+async fn get_saved_calendar(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CalendarFeedQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    // Same response for unknown user and wrong token, so ids can't be probed
+    let name = sqlx::query_as::<_, (Option<String>,)>(
+        "SELECT name FROM users WHERE id = $1 AND calendar_token = $2"
+    )
+        .bind(id)
+        .bind(&params.token)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?
+        .0;
+
+    let events = fetch_saved_events(&pool, id, false).await?;
+
+    let calendar_name = match name {
+        Some(n) => format!("{}'s Locate918 Events", n),
+        None => "Locate918 Saved Events".to_string(),
+    };
+
+    let body = events
+        .iter()
+        .fold(IcsCalendar::new().name(&calendar_name), |cal, e| cal.add_event(e))
+        .build();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "inline; filename=\"saved.ics\""),
+        ],
+        body,
+    ))
+}
+
+// =============================================================================
+// HANDLER: RECOMMENDATIONS
+// =============================================================================
+
+/// Query parameters for recommendations.
+#[derive(Debug, Deserialize)]
+pub struct RecommendationsQuery {
+    /// Number of events (default: 10, max: 50)
+    pub limit: Option<u32>,
+}
+
+/// Returns upcoming events ranked for the user, best first, each with its
+/// `score`.
+///
+/// # Endpoint
+/// `GET /api/users/:id/recommendations?limit=10`
+///
+/// Scoring is deterministic and doesn't call the LLM; see
+/// `services::recommendation` for the signals and their points.
+///
+/// # Returns
+/// - `200 OK` with a list of events
+/// - `404 Not Found` if the user doesn't exist
+async fn get_recommendations(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RecommendationsQuery>,
+) -> Result<Json<Vec<RecommendedEvent>>, AppError> {
+    let limit = params
+        .limit
+        .unwrap_or(recommendation::DEFAULT_LIMIT)
+        .clamp(1, recommendation::MAX_LIMIT);
+
+    let events = recommendation::recommend(&pool, id, limit as usize).await?;
+    Ok(Json(events))
+}
+
+// =============================================================================
+// HANDLER: USER STATS
+// =============================================================================
+
+/// One-row summary behind `UserStats`.
+#[derive(Debug, sqlx::FromRow)]
+struct ActivitySummary {
+    first_activity_at: Option<DateTime<Utc>>,
+    last_activity_at: Option<DateTime<Utc>>,
+    events_attended: i64,
+    /// Monday of each active week; NULL when there's no activity
+    active_weeks: Option<Vec<NaiveDate>>,
+}
+
+/// Returns aggregate activity for a user: totals per interaction type, top
+/// categories, events attended, weekly streak and first/last activity.
+///
+/// # Endpoint
+/// `GET /api/users/:id/stats`
+///
+/// Counts only; raw interaction rows stay behind `/interactions`. Weeks
+/// are local to `LOCAL_TIMEZONE`.
+///
+/// # Returns
+/// - `200 OK` with `UserStats` (zeros for a user with no activity)
+/// - `404 Not Found` if the user doesn't exist
+async fn get_user_stats(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UserStats>, AppError> {
+    let tz = dates::local_timezone();
+
+    // LEFT JOIN so a user without interactions still gets a row
+    let summary = sqlx::query_as::<_, ActivitySummary>(
+        r#"
+        SELECT
+            MIN(ui.created_at) AS first_activity_at,
+            MAX(ui.created_at) AS last_activity_at,
+            COUNT(DISTINCT ui.event_id) FILTER (WHERE ui.interaction_type = ANY($2)) AS events_attended,
+            ARRAY_AGG(DISTINCT date_trunc('week', ui.created_at AT TIME ZONE $3)::date)
+                FILTER (WHERE ui.id IS NOT NULL) AS active_weeks
+        FROM users u
+        LEFT JOIN user_interactions ui ON ui.user_id = u.id
+        WHERE u.id = $1
+        GROUP BY u.id
+        "#,
+    )
+        .bind(id)
+        .bind(analytics::spellings_of("attend"))
+        .bind(tz.name())
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
+
+    let by_type: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT interaction_type, COUNT(*) FROM user_interactions WHERE user_id = $1 GROUP BY interaction_type",
+    )
+        .bind(id)
+        .fetch_all(&pool)
+        .await?;
+
+    let mut totals = InteractionCounts::default();
+    for (interaction_type, count) in &by_type {
+        analytics::tally(&mut totals, interaction_type, *count);
+    }
+
+    let top_categories = sqlx::query_as::<_, CategoryEngagement>(
+        r#"
+        SELECT c.category, COUNT(*) AS interactions
+        FROM user_interactions ui
+        JOIN events e ON e.id = ui.event_id
+        CROSS JOIN LATERAL unnest(e.categories) AS c(category)
+        WHERE ui.user_id = $1 AND ui.interaction_type <> ALL($2)
+        GROUP BY c.category
+        ORDER BY interactions DESC, c.category ASC
+        LIMIT $3
+        "#,
+    )
+        .bind(id)
+        .bind(analytics::spellings_of("dismiss"))
+        .bind(analytics::TOP_CATEGORIES_LIMIT)
+        .fetch_all(&pool)
+        .await?;
+
+    let today = Utc::now().with_timezone(&tz).date_naive();
+
+    Ok(Json(UserStats {
+        user_id: id,
+        totals,
+        top_categories,
+        events_attended: summary.events_attended,
+        weekly_streak: analytics::weekly_streak(&summary.active_weeks.unwrap_or_default(), today),
+        first_activity_at: summary.first_activity_at,
+        last_activity_at: summary.last_activity_at,
+    }))
+}
+
+// =============================================================================
+// HANDLER: DATA EXPORT
+// =============================================================================
+
+/// Downloads everything stored about a user: the user row, preferences and
+/// full interaction history with event titles.
+///
+/// # Endpoint
+/// `GET /api/users/:id/export`
+///
+/// The body is streamed (see `services::export`), so long histories don't
+/// have to fit in memory. The document carries `schema_version` and
+/// `exported_at` for future imports.
+///
+/// # Returns
+/// - `200 OK` with `Content-Disposition: attachment` JSON
+/// - `404 Not Found` if the user doesn't exist
+async fn export_user_data(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM users WHERE id = $1",
+        USER_COLUMNS
+    ))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
+
+    let preferences = sqlx::query_as::<_, UserPreference>(&format!(
+        "SELECT {} FROM user_preferences WHERE user_id = $1 ORDER BY category",
+        PREFERENCE_COLUMNS
+    ))
+        .bind(id)
+        .fetch_all(&pool)
+        .await?;
+
+    let disposition = format!("attachment; filename=\"{}\"", export::export_filename(id));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        export::stream_user_export(pool, user, preferences),
+    ))
+}
+
+// =============================================================================
+// HANDLER: FOLLOWS
+// =============================================================================
+
+/// Lists the venues and categories a user follows, oldest first.
+///
+/// # Endpoint
+/// `GET /api/users/:id/follows`
+async fn get_follows(
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<UserFollow>>, AppError> {
+    let follows = fetch_follows(&pool, user_id).await?;
+    Ok(Json(follows))
+}
+
+/// Follows a venue (by id) or a category.
+///
+/// # Endpoint
+/// `POST /api/users/:id/follows`
+///
+/// # Request Body
+/// ```json
+/// { "target_type": "venue", "target_value": "6f1c...-venue-uuid" }
+/// { "target_type": "category", "target_value": "music" }
+/// ```
+///
+/// Following something already followed is fine and returns the existing
+/// follow. New events matching a follow become `follow` notifications.
+///
+/// # Returns
+/// - `201 Created` with the follow
+/// - `404 Not Found` if the user or venue doesn't exist
+/// - `422 Unprocessable Entity` if the value is blank or not a venue id
+async fn follow(
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<CreateFollow>,
+) -> Result<(StatusCode, Json<UserFollow>), AppError> {
+    let target_value = normalize_follow_target(&payload)?;
+
+    if payload.target_type == FollowTarget::Venue {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM venues WHERE id::text = $1)")
+            .bind(&target_value)
+            .fetch_one(&pool)
+            .await?;
+        if !exists {
+            return Err(AppError::not_found("venue"));
+        }
+    }
+
+    // The no-op update makes RETURNING hand back the existing row
+    let follow = sqlx::query_as::<_, UserFollow>(&format!(
+        r#"
+        WITH f AS (
+            INSERT INTO user_follows (user_id, target_type, target_value)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, target_type, target_value)
+            DO UPDATE SET target_value = EXCLUDED.target_value
+            RETURNING *
+        )
+        SELECT {}
+        FROM f LEFT JOIN venues v ON f.target_type = 'venue' AND v.id::text = f.target_value
+        "#,
+        FOLLOW_COLUMNS
+    ))
+        .bind(user_id)
+        .bind(payload.target_type)
+        .bind(&target_value)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(follow)))
+}
+
+/// Stops following a venue or category.
+///
+/// # Endpoint
+/// `DELETE /api/users/:id/follows/:target_type/:target_value`
+///
+/// # Returns
+/// - `204 No Content`, whether or not it was followed
+async fn unfollow(
+    State(pool): State<PgPool>,
+    Path((user_id, target_type, target_value)): Path<(Uuid, FollowTarget, String)>,
+) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM user_follows WHERE user_id = $1 AND target_type = $2 AND target_value = $3")
+        .bind(user_id)
+        .bind(target_type)
+        .bind(target_value.trim())
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// HANDLER: SAVED SEARCHES
+// =============================================================================
+
+/// Lists a user's saved searches, newest first.
+///
+/// # Endpoint
+/// `GET /api/users/:id/searches`
+async fn get_saved_searches(
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<SavedSearch>>, AppError> {
+    let searches = sqlx::query_as::<_, SavedSearch>(&format!(
+        "SELECT {} FROM saved_searches WHERE user_id = $1 ORDER BY created_at DESC, id",
+        SAVED_SEARCH_COLUMNS
+    ))
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(searches))
+}
+
+/// Saves a search. From now on, new events matching it show up in the
+/// user's notifications (see `services::saved_searches`).
+///
+/// # Endpoint
+/// `POST /api/users/:id/searches`
+///
+/// # Request Body
+/// ```json
+/// { "q": "jazz", "category": "music", "location": "Brady" }
+/// ```
+///
+/// # Returns
+/// - `201 Created` with the saved search
+/// - `404 Not Found` if the user doesn't exist
+/// - `422 Unprocessable Entity` if every field is blank
+async fn create_saved_search(
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<CreateSavedSearch>,
+) -> Result<(StatusCode, Json<SavedSearch>), AppError> {
+    let search = normalize_saved_search(payload)?;
+
+    let saved = sqlx::query_as::<_, SavedSearch>(&format!(
+        "INSERT INTO saved_searches (user_id, q, category, location) VALUES ($1, $2, $3, $4) RETURNING {}",
+        SAVED_SEARCH_COLUMNS
+    ))
+        .bind(user_id)
+        .bind(&search.q)
+        .bind(&search.category)
+        .bind(&search.location)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(saved)))
+}
+
+/// Replaces a saved search's filters.
+///
+/// # Endpoint
+/// `PUT /api/users/:id/searches/:search_id`
+///
+/// Only events created after the change are matched, so editing a search
+/// doesn't replay every existing event as new.
+///
+/// # Returns
+/// - `200 OK` with the updated search
+/// - `404 Not Found` if the user has no such search
+/// - `422 Unprocessable Entity` if every field is blank
+async fn replace_saved_search(
+    State(pool): State<PgPool>,
+    Path((user_id, search_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<CreateSavedSearch>,
+) -> Result<Json<SavedSearch>, AppError> {
+    let search = normalize_saved_search(payload)?;
+
+    let saved = sqlx::query_as::<_, SavedSearch>(&format!(
+        r#"
+        UPDATE saved_searches
+        SET q = $3, category = $4, location = $5, last_notified_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+        SAVED_SEARCH_COLUMNS
+    ))
+        .bind(search_id)
+        .bind(user_id)
+        .bind(&search.q)
+        .bind(&search.category)
+        .bind(&search.location)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("saved search"))?;
+
+    Ok(Json(saved))
+}
+
+/// Removes a saved search. Notifications it already produced are kept.
+///
+/// # Endpoint
+/// `DELETE /api/users/:id/searches/:search_id`
+///
+/// # Returns
+/// - `204 No Content` on success
+/// - `404 Not Found` if the user has no such search
+async fn delete_saved_search(
+    State(pool): State<PgPool>,
+    Path((user_id, search_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM saved_searches WHERE id = $1 AND user_id = $2")
+        .bind(search_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("saved search"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// HANDLER: NOTIFICATIONS
+// =============================================================================
+
+/// Default number of notifications per page.
+const DEFAULT_NOTIFICATIONS_LIMIT: u32 = 50;
+
+/// Largest notifications page a client may request.
+const MAX_NOTIFICATIONS_LIMIT: u32 = 200;
+
+/// Query parameters for the notification list.
+#[derive(Debug, Default, Deserialize)]
+pub struct NotificationsQuery {
+    /// Only notifications not yet marked read (default: false)
+    #[serde(default)]
+    pub unread: bool,
+
+    /// Page size (default: 50, max: 200)
+    pub limit: Option<u32>,
+
+    /// `next_cursor` from the previous page
+    pub before: Option<String>,
+}
+
+/// Returns a user's notifications, newest first, one page at a time.
+///
+/// # Endpoint
+/// `GET /api/users/:id/notifications?unread=true&limit=20&before=...`
+///
+/// Paged on `(created_at, id)` like the interaction history, so
+/// notifications arriving while a client pages don't shift later pages.
+///
+/// # Returns
+/// - `200 OK` with a `NotificationPage`
+/// - `400 Bad Request` if `before` isn't a valid cursor
+async fn get_notifications(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<NotificationsQuery>,
+) -> Result<Json<NotificationPage>, AppError> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_NOTIFICATIONS_LIMIT)
+        .clamp(1, MAX_NOTIFICATIONS_LIMIT);
+    let cursor = match params.before {
+        Some(ref raw) => Some(Cursor::decode(raw).ok_or_else(|| AppError::BadRequest("invalid cursor".into()))?),
+        None => None,
+    };
+
+    let mut conditions = vec!["user_id = $1".to_string()];
+    if params.unread {
+        conditions.push("read_at IS NULL".to_string());
+    }
+    if cursor.is_some() {
+        conditions.push("(created_at, id) < ($2, $3)".to_string());
+    }
+
+    // Fetch one extra row to find out whether there's an older page
+    let query = format!(
+        "SELECT {} FROM notifications WHERE {} ORDER BY created_at DESC, id DESC LIMIT {}",
+        NOTIFICATION_COLUMNS,
+        conditions.join(" AND "),
+        limit + 1
+    );
+
+    let mut sql = sqlx::query_as::<_, Notification>(&query).bind(id);
+    if let Some(c) = cursor {
+        sql = sql.bind(c.start_time).bind(c.id);
+    }
+
+    let mut notifications = sql.fetch_all(&pool).await?;
+
+    // Cursor's time slot holds created_at here
+    let next_cursor = take_page(&mut notifications, limit as usize, |n| Cursor {
+        start_time: n.created_at,
+        id: n.id,
+    });
+
+    Ok(Json(NotificationPage { notifications, next_cursor }))
+}
+
+/// Counts a user's unread notifications (for a badge).
+///
+/// # Endpoint
+/// `GET /api/users/:id/notifications/unread-count`
+async fn get_unread_count(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UnreadCount>, AppError> {
+    let unread: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL",
+    )
+        .bind(id)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok(Json(UnreadCount { unread }))
+}
+
+/// Marks one notification read.
+///
+/// # Endpoint
+/// `POST /api/users/:id/notifications/:notification_id/read`
+///
+/// Marking an already-read notification again is fine and keeps the
+/// original `read_at`.
+///
+/// # Returns
+/// - `200 OK` with the notification
+/// - `404 Not Found` if the user has no such notification
+async fn mark_notification_read(
+    State(pool): State<PgPool>,
+    Path((user_id, notification_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Notification>, AppError> {
+    let notification = sqlx::query_as::<_, Notification>(&format!(
+        r#"
+        UPDATE notifications
+        SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING {}
+        "#,
+        NOTIFICATION_COLUMNS
+    ))
+        .bind(notification_id)
+        .bind(user_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("notification"))?;
+
+    Ok(Json(notification))
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// Mails `user` a verification link without holding up the response.
+///
+/// Skips guests and verified emails. Failures (no mail server, no
+/// `JWT_SECRET`) are only logged; the user can ask for a resend.
+pub(crate) fn queue_verification(user: &User) {
+    if user.is_guest || user.email_verified_at.is_some() {
+        return;
+    }
+
+    let user = user.clone();
+    tokio::spawn(async move {
+        if let Err(e) = verification::send_verification(&user, &frontend_url()).await {
+            tracing::warn!(user_id = %user.id, error = %e, "verification email not sent");
+        }
+    });
+}
+
+/// Inserts a new user with a fresh `calendar_token`.
+///
+/// Shared by `POST /api/users` and `POST /api/auth/register`. A taken
+/// email is a `409 Conflict`; a too-short password is a `422`. The password,
+/// if any, is stored only as a hash.
+pub(crate) async fn insert_user(pool: &PgPool, payload: CreateUser) -> Result<User, AppError> {
+    payload.validate()?;
+
+    let password_hash = match payload.password {
+        Some(password) => {
+            auth::validate_password("password", &password)?;
+            Some(auth::hash_password(password).await)
+        }
+        None => None,
+    };
+
+    let id = Uuid::new_v4();
+    let now = chrono::Utc::now();
+    let calendar_token = Uuid::new_v4().simple().to_string();
+
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, email, name, location_preference, radius_miles, home_latitude, home_longitude, preferred_radius_km, price_max, family_friendly_only, calendar_token, password_hash, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        "#,
+    )
+        .bind(id)
+        .bind(&payload.email)
+        .bind(&payload.name)
+        .bind(&payload.location_preference)
+        .bind(payload.radius_miles)
+        .bind(payload.home_latitude)
+        .bind(payload.home_longitude)
+        .bind(payload.preferred_radius_km)
+        .bind(payload.price_max)
+        .bind(payload.family_friendly_only)
+        .bind(&calendar_token)
+        .bind(&password_hash)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    let user = User {
+        id,
+        email: payload.email,
+        name: payload.name,
+        location_preference: payload.location_preference,
+        radius_miles: payload.radius_miles,
+        home_latitude: payload.home_latitude,
+        home_longitude: payload.home_longitude,
+        preferred_radius_km: payload.preferred_radius_km,
+        price_max: payload.price_max,
+        family_friendly_only: payload.family_friendly_only,
+        is_guest: false,
+        email_verified_at: None,
+        calendar_token,
+        created_at: now,
+        updated_at: now,
+    };
+
+    Ok(user)
+}
+
+/// Loads a user's follows, oldest first.
+async fn fetch_follows(pool: &PgPool, user_id: Uuid) -> Result<Vec<UserFollow>, sqlx::Error> {
+    sqlx::query_as::<_, UserFollow>(&format!(
+        "SELECT {} FROM {} WHERE f.user_id = $1 ORDER BY f.created_at, f.id",
+        FOLLOW_COLUMNS, FOLLOWS_FROM
+    ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Trims a follow's target and checks it can match events: categories
+/// must not be blank and venues must be given by id (stored in the
+/// canonical hyphenated form `events.venue_id::text` produces).
+fn normalize_follow_target(payload: &CreateFollow) -> Result<String, AppError> {
+    let value = payload.target_value.trim();
+    match payload.target_type {
+        FollowTarget::Category if value.is_empty() => Err(AppError::invalid("target_value", "must not be empty")),
+        FollowTarget::Category => Ok(value.to_string()),
+        FollowTarget::Venue => Uuid::parse_str(value)
+            .map(|id| id.to_string())
+            .map_err(|_| AppError::invalid("target_value", "must be a venue id")),
+    }
+}
+
+/// Trims a saved search's fields and turns blank ones into `None`.
+///
+/// Rejects (422) a search with nothing left: it would match every event.
+fn normalize_saved_search(payload: CreateSavedSearch) -> Result<CreateSavedSearch, Vec<FieldError>> {
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let search = CreateSavedSearch {
+        q: clean(payload.q),
+        category: clean(payload.category),
+        location: clean(payload.location),
+    };
+
+    if search.q.is_none() && search.category.is_none() && search.location.is_none() {
+        return Err(vec![FieldError::new(
+            "q",
+            "give at least one of q, category or location",
+        )]);
+    }
+
+    Ok(search)
+}
+
+/// Checks a preference (with its category already normalized) before it
+/// is upserted.
+///
+/// Rejects (422) a blank or unknown category, a weight of 0 (see
+/// `add_preference`) and one beyond ±`MAX_PREFERENCE_WEIGHT`.
+fn validate_preference(payload: &CreateUserPreference, known: &BTreeSet<String>) -> Result<(), Vec<FieldError>> {
+    let mut errors = Vec::new();
+
+    if payload.category.is_empty() {
+        errors.push(FieldError::new("category", "must not be empty"));
+    } else if let Some(error) = categories::check_known("category", &payload.category, known) {
+        errors.push(error);
+    }
+    if payload.weight == 0 {
+        errors.push(FieldError::new(
+            "weight",
+            "must not be 0; delete the preference to clear it",
+        ));
+    } else if payload.weight.abs() > MAX_PREFERENCE_WEIGHT {
+        errors.push(FieldError::new(
+            "weight",
+            format!("must be between -{0} and {0}", MAX_PREFERENCE_WEIGHT),
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Checks every entry of a bulk payload, plus that no category repeats.
+///
+/// Field names are prefixed with the entry's index (`[2].weight`).
+fn validate_bulk_preferences(payload: &[CreateUserPreference], known: &BTreeSet<String>) -> Result<(), Vec<FieldError>> {
+    let mut errors = Vec::new();
+
+    for (index, preference) in payload.iter().enumerate() {
+        if let Err(entry_errors) = validate_preference(preference, known) {
+            errors.extend(entry_errors.into_iter().map(|e| FieldError {
+                field: format!("[{}].{}", index, e.field),
+                message: e.message,
+            }));
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    let duplicates: BTreeSet<&str> = payload
+        .iter()
+        .map(|p| p.category.as_str())
+        .filter(|category| !seen.insert(*category))
+        .collect();
+    if !duplicates.is_empty() {
+        let names: Vec<&str> = duplicates.into_iter().collect();
+        errors.push(FieldError::new(
+            "category",
+            format!("duplicate categories: {}", names.join(", ")),
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Events whose latest save/dismiss interaction from the user is a save,
+/// by start time. Shared by the saved list and the calendar feed.
+async fn fetch_saved_events(
+    pool: &PgPool,
+    user_id: Uuid,
+    upcoming_only: bool,
+) -> Result<Vec<Event>, sqlx::Error> {
+    let upcoming = if upcoming_only {
+        format!("AND {}", UPCOMING_FILTER)
+    } else {
+        String::new()
+    };
+
+    // DISTINCT ON keeps only the newest save/dismiss per event
+    let query = format!(
+        r#"
+        SELECT {}
+        FROM events
+        WHERE id IN (
+            SELECT event_id FROM (
+                SELECT DISTINCT ON (event_id) event_id, interaction_type
+                FROM user_interactions
+                WHERE user_id = $1
+                  AND (interaction_type = ANY($2) OR interaction_type = ANY($3))
+                ORDER BY event_id, created_at DESC
+            ) latest
+            WHERE latest.interaction_type = ANY($2)
+        )
+        {}
+        ORDER BY start_time ASC
+        "#,
+        EVENT_COLUMNS, upcoming
+    );
+
+    sqlx::query_as::<_, Event>(&query)
+        .bind(user_id)
+        .bind(analytics::spellings_of("save"))
+        .bind(analytics::spellings_of("dismiss"))
+        .fetch_all(pool)
+        .await
+}
+
+/// Moves the user's latest view of the event (either spelling) to now, if
+/// it happened within the last `window_minutes`. Returns the updated row,
+/// or `None` if there was no recent view.
+async fn refresh_recent_view(
+    pool: &PgPool,
+    user_id: Uuid,
+    event_id: Uuid,
+    window_minutes: u64,
+) -> Result<Option<UserInteraction>, sqlx::Error> {
+    sqlx::query_as::<_, UserInteraction>(
+        r#"
+        UPDATE user_interactions
+        SET created_at = NOW()
+        WHERE id = (
+            SELECT id FROM user_interactions
+            WHERE user_id = $1 AND event_id = $2 AND interaction_type = ANY($3)
+              AND created_at > NOW() - make_interval(mins => $4)
+            ORDER BY created_at DESC
+            LIMIT 1
+        )
+        RETURNING id, user_id, event_id, interaction_type, event_category, event_venue, created_at
+        "#,
+    )
+        .bind(user_id)
+        .bind(event_id)
+        .bind(analytics::spellings_of("view"))
+        .bind(window_minutes as i32)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Inserts a preference, or updates the weight if the user already has one
+/// for that category. ON CONFLICT makes concurrent writes safe.
+async fn upsert_preference(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    preference: &CreateUserPreference,
+) -> Result<UserPreference, sqlx::Error> {
+    // An explicit choice always replaces an inferred weight for the category
+    sqlx::query_as::<_, UserPreference>(&format!(
+        r#"
+        INSERT INTO user_preferences (id, user_id, category, weight, source, created_at)
+        VALUES ($1, $2, $3, $4, 'explicit', $5)
+        ON CONFLICT (user_id, category)
+        DO UPDATE SET weight = EXCLUDED.weight, source = 'explicit'
+        RETURNING {}
+        "#,
+        PREFERENCE_COLUMNS
+    ))
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(&preference.category)
+        .bind(preference.weight)
+        .bind(chrono::Utc::now())
+        .fetch_one(conn)
+        .await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preference(category: &str, weight: i32) -> CreateUserPreference {
+        CreateUserPreference { category: category.to_string(), weight }
+    }
+
+    fn known() -> BTreeSet<String> {
+        categories::BASE_CATEGORIES.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn fields(p: &CreateUserPreference) -> Vec<String> {
+        match validate_preference(p, &known()) {
+            Ok(()) => vec![],
+            Err(errors) => errors.into_iter().map(|e| e.field).collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_non_zero_weights() {
+        assert!(validate_preference(&preference("music", 5), &known()).is_ok());
+        assert!(validate_preference(&preference("sports", -3), &known()).is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_weight_and_blank_category() {
+        assert_eq!(fields(&preference("sports", 0)), ["weight"]);
+        assert_eq!(fields(&preference("", 0)), ["category", "weight"]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_weights_and_unknown_categories() {
+        assert_eq!(fields(&preference("music", MAX_PREFERENCE_WEIGHT + 1)), ["weight"]);
+        assert_eq!(fields(&preference("music", -MAX_PREFERENCE_WEIGHT - 1)), ["weight"]);
+        assert_eq!(fields(&preference("asdfgh", 9999)), ["category", "weight"]);
+    }
+
+    #[test]
+    fn bulk_names_bad_entries_and_duplicates() {
+        let payload = [
+            preference("music", 5),
+            preference("sports", 0),
+            preference("music", 3),
+            preference("art", 1),
+            preference("art", 2),
+        ];
+
+        let errors = validate_bulk_preferences(&payload, &known()).unwrap_err();
+
+        assert_eq!(errors[0].field, "[1].weight");
+        assert_eq!(errors[1].field, "category");
+        assert_eq!(errors[1].message, "duplicate categories: art, music");
+        assert!(validate_bulk_preferences(&payload[..1], &known()).is_ok());
+
+        let unknown = validate_bulk_preferences(&[preference("art", 1), preference("asdfgh", 2)], &known()).unwrap_err();
+        assert_eq!(unknown[0].field, "[1].category");
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set, e.g.
+    /// `TEST_DATABASE_URL=postgres://postgres@localhost/locate918_test cargo test`.
+    #[test]
+    fn saved_searches_drop_blank_fields_and_need_one() {
+        let search = normalize_saved_search(CreateSavedSearch {
+            q: Some("  jazz ".to_string()),
+            category: Some("   ".to_string()),
+            location: None,
+        })
+            .unwrap();
+        assert_eq!(search.q.as_deref(), Some("jazz"));
+        assert_eq!(search.category, None);
+
+        let errors = normalize_saved_search(CreateSavedSearch {
+            q: Some(String::new()),
+            ..Default::default()
+        })
+            .unwrap_err();
+        assert_eq!(errors[0].field, "q");
+    }
+
+    #[tokio::test]
+    async fn patch_only_touches_given_fields() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let create = |email: String| {
+            let pool = pool.clone();
+            async move {
+                let payload = CreateUser {
+                    email,
+                    name: Some("Sam".to_string()),
+                    location_preference: Some("Downtown".to_string()),
+                    radius_miles: None,
+                    home_latitude: None,
+                    home_longitude: None,
+                    preferred_radius_km: None,
+                    price_max: None,
+                    family_friendly_only: false,
+                    password: None,
+                };
+                let (_, Json(user)) = create_user(State(pool), Json(payload)).await.unwrap();
+                user
+            }
+        };
+        let user = create(format!("{}@example.com", run)).await;
+        let other = create(format!("other-{}@example.com", run)).await;
+        let patch = |payload: UpdateUser| update_user(State(pool.clone()), Path(user.id), Json(payload));
+
+        let unchanged = patch(UpdateUser::default()).await.unwrap().0;
+        assert_eq!(unchanged.name.as_deref(), Some("Sam"));
+        assert_eq!(unchanged.location_preference.as_deref(), Some("Downtown"));
+
+        let renamed = patch(UpdateUser { name: Some("Sammy".to_string()), ..Default::default() })
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(renamed.name.as_deref(), Some("Sammy"));
+        assert_eq!(renamed.email, user.email);
+        assert_eq!(renamed.location_preference.as_deref(), Some("Downtown"));
+
+        let clash = patch(UpdateUser { email: Some(other.email.clone()), ..Default::default() }).await;
+        assert!(matches!(clash, Err(AppError::Conflict(_))));
+
+        let missing = update_user(State(pool.clone()), Path(Uuid::nil()), Json(UpdateUser::default())).await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(vec![user.id, other.id])
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn password_hash_is_never_returned() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let payload = CreateUser {
+            email: format!("{}@example.com", Uuid::new_v4()),
+            name: None,
+            location_preference: None,
+            radius_miles: None,
+            home_latitude: None,
+            home_longitude: None,
+            preferred_radius_km: None,
+            price_max: None,
+            family_friendly_only: false,
+            password: Some(crate::models::Password("hunter2hunter2".to_string())),
+        };
+        let (_, Json(created)) = create_user(State(pool.clone()), Json(payload)).await.unwrap();
+        let Json(fetched) = get_user(State(pool.clone()), Path(created.id)).await.unwrap();
+
+        for user in [&created, &fetched] {
+            let body = serde_json::to_string(user).unwrap();
+            assert!(!body.contains("password"), "{}", body);
+            assert!(!body.contains("hunter2"), "{}", body);
+            assert!(!body.contains("$argon2"), "{}", body);
+        }
+
+        let (stored,): (Option<String>,) = sqlx::query_as("SELECT password_hash FROM users WHERE id = $1")
+            .bind(created.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(stored.is_some_and(|hash| hash.starts_with("$argon2id$")));
+    }
+
+    #[tokio::test]
+    async fn saved_events_follow_the_latest_save_or_dismiss() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // Interactions `minutes_ago` in the past, so their order is unambiguous
+        let record = |kind: &'static str, minutes_ago: i32| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query(
+                    "INSERT INTO user_interactions (user_id, event_id, interaction_type, created_at) \
+                     VALUES ($1, $2, $3, NOW() - make_interval(mins => $4))",
+                )
+                    .bind(user)
+                    .bind(event)
+                    .bind(kind)
+                    .bind(minutes_ago)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        };
+        let saved = || {
+            let pool = pool.clone();
+            async move {
+                let query = Query(SavedEventsQuery { upcoming_only: true });
+                let Json(events) = get_saved_events(State(pool), Path(user), query).await.unwrap();
+                events.iter().map(|e| e.id).collect::<Vec<_>>()
+            }
+        };
+
+        record("save", 30).await;
+        assert_eq!(saved().await, [event]);
+        record("dismiss", 20).await;
+        assert!(saved().await.is_empty());
+        record("view", 15).await;
+        record("saved", 10).await;
+        assert_eq!(saved().await, [event]);
+
+        let missing = get_saved_events(
+            State(pool.clone()),
+            Path(Uuid::nil()),
+            Query(SavedEventsQuery { upcoming_only: true }),
+        )
+            .await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn deleted_interactions_leave_the_profile() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let new_user = |suffix: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, Uuid>(
+                    "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+                )
+                    .bind(format!("{}-{}@example.com", suffix, run))
+                    .bind(format!("{}{}", suffix, run.simple()))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        let user = new_user("owner").await;
+        let stranger = new_user("stranger").await;
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let interact = |kind: &str| {
+            let payload = CreateUserInteraction { event_id: event, interaction_type: kind.to_string() };
+            add_interaction(State(pool.clone()), Path(user), Json(payload))
+        };
+        let recent = || async {
+            let Json(profile) = get_user_profile(State(pool.clone()), Path(user)).await.unwrap();
+            profile
+                .recent_interactions
+                .into_iter()
+                .map(|i| i.interaction_type)
+                .collect::<Vec<_>>()
+        };
+
+        let (_, Json(view)) = interact("view").await.unwrap();
+        let _ = interact("saved").await.unwrap();
+        assert_eq!(recent().await.len(), 2);
+
+        // Someone else's id is indistinguishable from a missing one
+        let foreign = delete_interaction(State(pool.clone()), Path((stranger, view.id))).await;
+        assert!(matches!(foreign, Err(AppError::NotFound(_))));
+
+        let status = delete_interaction(State(pool.clone()), Path((user, view.id))).await.unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(recent().await, ["saved"]);
+
+        // `type=save` also matches the past-tense spelling
+        let unsave = |user: Uuid| {
+            let query = DeleteInteractionQuery { event_id: event, interaction_type: "save".to_string() };
+            delete_latest_interaction(State(pool.clone()), Path(user), Query(query))
+        };
+        assert_eq!(unsave(user).await.unwrap(), StatusCode::NO_CONTENT);
+        assert!(recent().await.is_empty());
+        assert!(matches!(unsave(user).await, Err(AppError::NotFound(_))));
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+            .bind(vec![user, stranger])
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn interactions_need_a_real_user_and_event() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time, venue, categories) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day', 'The Colony', ARRAY['music']) RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let record = |user_id: Uuid, event_id: Uuid| {
+            let payload = CreateUserInteraction { event_id, interaction_type: "view".to_string() };
+            add_interaction(State(pool.clone()), Path(user_id), Json(payload))
+        };
+        let message = |result: Result<_, AppError>| match result {
+            Err(AppError::NotFound(message)) => message,
+            other => panic!("expected a 404, got {:?}", other.map(|_| ())),
+        };
+
+        assert_eq!(message(record(user, Uuid::nil()).await), "event not found");
+        assert_eq!(message(record(Uuid::nil(), event).await), "user not found");
+
+        let (status, Json(interaction)) = record(user, event).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(interaction.event_category.as_deref(), Some("music"));
+        assert_eq!(interaction.event_venue.as_deref(), Some("The Colony"));
+
+        let orphans: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user_interactions WHERE event_id = $1 OR user_id = $2")
+            .bind(Uuid::nil())
+            .bind(Uuid::nil())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(orphans, 0);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn repeat_views_within_the_window_are_merged() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let events: Vec<Uuid> = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             SELECT 'Jazz Night', $1 || n, NOW() + INTERVAL '1 day' FROM generate_series(1, 2) AS n \
+             RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}/", run))
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let (event, other_event) = (events[0], events[1]);
+
+        let record = |event_id: Uuid, kind: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let payload = CreateUserInteraction { event_id, interaction_type: kind.to_string() };
+                let (status, Json(interaction)) =
+                    add_interaction(State(pool), Path(user), Json(payload)).await.unwrap();
+                (status, interaction)
+            }
+        };
+        let age_all = |minutes: i32| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query("UPDATE user_interactions SET created_at = NOW() - make_interval(mins => $2) WHERE user_id = $1")
+                    .bind(user)
+                    .bind(minutes)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        };
+        let rows = |kind: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM user_interactions WHERE user_id = $1 AND interaction_type = $2",
+                )
+                    .bind(user)
+                    .bind(kind)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let (status, first) = record(event, "view").await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // Just inside the window: the same row comes back, moved to now
+        age_all(29).await;
+        let (status, again) = record(event, "view").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again.id, first.id);
+        assert!(again.created_at > chrono::Utc::now() - chrono::Duration::minutes(1));
+        assert_eq!(rows("view").await, 1);
+
+        // Another event is a separate view
+        assert_eq!(record(other_event, "view").await.0, StatusCode::CREATED);
+        assert_eq!(rows("view").await, 2);
+
+        // Just outside the window: a new row
+        age_all(31).await;
+        assert_eq!(record(event, "view").await.0, StatusCode::CREATED);
+        assert_eq!(rows("view").await, 3);
+
+        // Saves are never merged
+        record(event, "save").await;
+        record(event, "save").await;
+        assert_eq!(rows("save").await, 2);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = ANY($1)").bind(&events).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn interaction_pages_are_stable_under_inserts() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // 500 interactions, pairs of them sharing a timestamp so the id
+        // tie-break is exercised; every fifth one is a save
+        let original: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO user_interactions (user_id, event_id, interaction_type, created_at)
+            SELECT $1, $2,
+                   CASE WHEN n % 5 = 0 THEN 'save' ELSE 'view' END,
+                   NOW() - make_interval(secs => n / 2)
+            FROM generate_series(1, 500) AS n
+            RETURNING id
+            "#,
+        )
+            .bind(user)
+            .bind(event)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        let page = |before: Option<String>, interaction_type: Option<&str>| {
+            let query = InteractionsQuery {
+                interaction_type: interaction_type.map(str::to_string),
+                limit: Some(200),
+                before,
+                ..Default::default()
+            };
+            let pool = pool.clone();
+            async move {
+                let Json(page) = get_interactions(State(pool), Path(user), Query(query)).await.unwrap();
+                page
+            }
+        };
+
+        let mut seen = Vec::new();
+        let mut before = None;
+        loop {
+            let page = page(before, None).await;
+            assert!(page.interactions.len() <= 200);
+            assert!(page
+                .interactions
+                .windows(2)
+                .all(|w| (w[0].created_at, w[0].id) > (w[1].created_at, w[1].id)));
+            seen.extend(page.interactions.iter().map(|i| i.id));
+
+            // New activity between pages must not shift the next page
+            let (_, Json(_)) = add_interaction(
+                State(pool.clone()),
+                Path(user),
+                Json(CreateUserInteraction { event_id: event, interaction_type: "attend".to_string() }),
+            )
+                .await
+                .unwrap();
+
+            match page.next_cursor {
+                Some(cursor) => before = Some(cursor),
+                None => break,
+            }
+        }
+
+        // Exactly the original 500: nothing skipped, repeated, or added
+        let mut expected = original;
+        expected.sort();
+        seen.sort();
+        assert_eq!(seen, expected);
+
+        let saves = page(None, Some("saved")).await;
+        assert_eq!(saves.interactions.len(), 100);
+        assert!(saves.next_cursor.is_none());
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn notifications_page_count_and_mark_read() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO notifications (user_id, kind, title, created_at)
+            SELECT $1, 'test', 'Notification ' || n, NOW() - make_interval(secs => n)
+            FROM generate_series(1, 5) AS n
+            RETURNING id
+            "#,
+        )
+            .bind(user)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        let unread = || async { get_unread_count(State(pool.clone()), Path(user)).await.unwrap().0.unread };
+        assert_eq!(unread().await, 5);
+
+        // Newest is "Notification 1"; mark it read twice, read_at sticks
+        let Json(first) = mark_notification_read(State(pool.clone()), Path((user, ids[0]))).await.unwrap();
+        let Json(again) = mark_notification_read(State(pool.clone()), Path((user, ids[0]))).await.unwrap();
+        assert!(first.read_at.is_some());
+        assert_eq!(first.read_at, again.read_at);
+        assert_eq!(unread().await, 4);
+
+        let query = NotificationsQuery { unread: true, limit: Some(3), before: None };
+        let Json(page) = get_notifications(State(pool.clone()), Path(user), Query(query)).await.unwrap();
+        let titles: Vec<&str> = page.notifications.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, ["Notification 2", "Notification 3", "Notification 4"]);
+
+        let query = NotificationsQuery { unread: true, limit: Some(3), before: page.next_cursor };
+        let Json(page) = get_notifications(State(pool.clone()), Path(user), Query(query)).await.unwrap();
+        assert_eq!(page.notifications.len(), 1);
+        assert!(page.next_cursor.is_none());
+
+        // Someone else's notification is a 404, not a silent no-op
+        let err = mark_notification_read(State(pool.clone()), Path((Uuid::new_v4(), ids[1]))).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn claiming_a_guest_with_a_new_email_keeps_the_row() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let (status, Json(guest)) = create_guest(State(pool.clone())).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(guest.user.is_guest);
+        assert!(guest.user.email.ends_with(guests::GUEST_EMAIL_DOMAIN));
+
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let claim = ClaimGuest {
+            email: email.clone(),
+            name: Some("Riley".to_string()),
+            password: Some(crate::models::Password("hunter2hunter2".to_string())),
+        };
+        let Json(claimed) = claim_guest(State(pool.clone()), Path(guest.user.id), Json(claim)).await.unwrap();
+
+        assert_eq!(claimed.user.id, guest.user.id);
+        assert_eq!(claimed.user.email, email);
+        assert_eq!(claimed.user.name.as_deref(), Some("Riley"));
+        assert!(!claimed.user.is_guest);
+
+        // A claimed account can't be claimed again
+        let again = ClaimGuest { email: format!("x-{}", email), name: None, password: None };
+        let err = claim_guest(State(pool.clone()), Path(guest.user.id), Json(again)).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(guest.user.id).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn claiming_a_guest_into_an_existing_account_merges_it() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let payload = CreateUser {
+            email: format!("{}@example.com", run),
+            name: Some("Sam".to_string()),
+            location_preference: None,
+            radius_miles: None,
+            home_latitude: None,
+            home_longitude: None,
+            preferred_radius_km: None,
+            price_max: None,
+            family_friendly_only: false,
+            password: Some(crate::models::Password("hunter2hunter2".to_string())),
+        };
+        let (_, Json(account)) = create_user(State(pool.clone()), Json(payload)).await.unwrap();
+        let (_, Json(guest)) = create_guest(State(pool.clone())).await.unwrap();
+        let guest = guest.user.id;
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // jazz: the guest's -4 outweighs 2; art: the account's 3 beats 1; food: new
+        let preferences = [
+            (account.id, "jazz", 2),
+            (guest, "jazz", -4),
+            (account.id, "art", 3),
+            (guest, "art", 1),
+            (guest, "food", 5),
+        ];
+        for (user, category, weight) in preferences {
+            sqlx::query("INSERT INTO user_preferences (user_id, category, weight) VALUES ($1, $2, $3)")
+                .bind(user)
+                .bind(category)
+                .bind(weight)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO user_interactions (user_id, event_id, interaction_type) VALUES ($1, $2, 'save')")
+            .bind(guest)
+            .bind(event)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let claim = |password: &str| ClaimGuest {
+            email: account.email.clone(),
+            name: None,
+            password: Some(crate::models::Password(password.to_string())),
+        };
+        let err = claim_guest(State(pool.clone()), Path(guest), Json(claim("wrong password"))).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+
+        let Json(merged) = claim_guest(State(pool.clone()), Path(guest), Json(claim("hunter2hunter2"))).await.unwrap();
+        assert_eq!(merged.user.id, account.id);
+
+        let weights: Vec<(String, i32)> = sqlx::query_as(
+            "SELECT category, weight FROM user_preferences WHERE user_id = $1 ORDER BY category",
+        )
+            .bind(account.id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(weights, [("art".to_string(), 3), ("food".to_string(), 5), ("jazz".to_string(), -4)]);
+
+        let saves: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user_interactions WHERE user_id = $1 AND event_id = $2")
+            .bind(account.id)
+            .bind(event)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(saves, 1);
+
+        let guest_left: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(guest)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!guest_left);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(account.id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn claiming_into_an_account_without_a_password_is_refused() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let email = format!("{}@example.com", run);
+        let account: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(&email)
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let (_, Json(guest)) = create_guest(State(pool.clone())).await.unwrap();
+        let guest = guest.user.id;
+
+        for password in [None, Some(crate::models::Password("anything at all".to_string()))] {
+            let claim = ClaimGuest { email: email.clone(), name: None, password };
+            let err = claim_guest(State(pool.clone()), Path(guest), Json(claim)).await.unwrap_err();
+            assert!(matches!(err, AppError::Unauthorized(_)));
+        }
+
+        // Nothing was merged: the guest is still its own account
+        let still_guest: bool = sqlx::query_scalar("SELECT is_guest FROM users WHERE id = $1")
+            .bind(guest)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(still_guest);
+
+        sqlx::query("DELETE FROM users WHERE id = ANY($1)").bind(vec![account, guest]).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn verification_links_only_work_for_the_current_email() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        std::env::set_var("JWT_SECRET", "test-secret");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let link_for = |email: String| {
+            let token = verification::sign_verification_token(
+                user,
+                &email,
+                b"test-secret",
+                Utc::now(),
+                chrono::Duration::hours(1),
+            );
+            Json(VerifyEmail { token })
+        };
+        let old_link = link_for(format!("{}@example.com", run));
+
+        // Changing the email kills the old link
+        let new_email = format!("new-{}@example.com", run);
+        let patch = UpdateUser { email: Some(new_email.clone()), ..Default::default() };
+        let Json(changed) = update_user(State(pool.clone()), Path(user), Json(patch)).await.unwrap();
+        assert!(changed.email_verified_at.is_none());
+        let err = verify_email(State(pool.clone()), Path(user), old_link).await.unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+
+        // Someone else's id in the path doesn't work either
+        let err = verify_email(State(pool.clone()), Path(Uuid::new_v4()), link_for(new_email.clone())).await.unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+
+        let Json(verified) = verify_email(State(pool.clone()), Path(user), link_for(new_email.clone())).await.unwrap();
+        assert!(verified.email_verified_at.is_some());
+        let Json(again) = verify_email(State(pool.clone()), Path(user), link_for(new_email)).await.unwrap();
+        assert_eq!(again.email_verified_at, verified.email_verified_at);
+
+        let err = resend_verification(State(pool.clone()), Path(user)).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,517 @@
+//! # Venues Routes
+//!
+//! This module handles venue records and listing events by venue.
+//! Venues let "everything at Cain's Ballroom" work without the user
+//! spelling the venue exactly the way the scraper did.
+//!
+//! ## Endpoints
+//! - `GET    /api/venues`            - List venues (optional `?q=` name filter)
+//! - `POST   /api/venues`            - Create a venue
+//! - `GET    /api/venues/:id`        - Get a single venue
+//! - `PUT    /api/venues/:id`        - Replace a venue's details
+//! - `DELETE /api/venues/:id`        - Delete a venue (events are unlinked)
+//! - `GET    /api/venues/:id/events` - Upcoming events at a venue
+//!
+//! ## Authentication
+//! Create, replace and delete need `X-Admin-Key` (401 without a valid key,
+//! 503 if `ADMIN_API_KEY` isn't set). Reads are public.
+//!
+//! ## Matching
+//! Venue names are unique after normalization (see migrations/007_venues.sql),
+//! so `find_or_create_venue` maps "CAINS BALLROOM" onto "Cain's Ballroom".
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::{
+    EVENT_COLUMNS, KEYSET_ORDER, NOT_ARCHIVED_FILTER, NOT_CANCELLED_FILTER, UPCOMING_FILTER,
+    VENUE_COLUMNS,
+};
+use crate::error::AppError;
+use crate::models::{is_http_url, CreateVenue, Event, FieldError, Venue};
+use crate::routes::AppState;
+use crate::services::geo;
+
+// =============================================================================
+// ROUTE DEFINITIONS
+// =============================================================================
+
+/// Creates the router for all venue endpoints.
+///
+/// Reads are public. Writes need `X-Admin-Key` (see
+/// `auth::require_admin_key`).
+pub fn routes() -> Router<AppState> {
+    let admin = Router::new()
+        .route("/", post(create_venue))
+        .route("/:id", put(update_venue).delete(delete_venue))
+        .route_layer(middleware::from_fn(auth::require_admin_key));
+
+    Router::new()
+        .route("/", get(list_venues))
+        .route("/:id", get(get_venue))
+        .route("/:id/events", get(venue_events))
+        .merge(admin)
+}
+
+// =============================================================================
+// HANDLER: LIST VENUES
+// =============================================================================
+
+/// Query parameters for listing venues.
+#[derive(Debug, Deserialize)]
+pub struct VenueListQuery {
+    /// Case-insensitive substring of the venue name
+    pub q: Option<String>,
+}
+
+/// Returns all venues, alphabetically.
+///
+/// # Endpoint
+/// `GET /api/venues?q=cain`
+async fn list_venues(
+    State(pool): State<PgPool>,
+    Query(params): Query<VenueListQuery>,
+) -> Result<Json<Vec<Venue>>, AppError> {
+    let where_clause = match params.q {
+        Some(ref q) => format!("WHERE name ILIKE '%{}%'", q.replace('\'', "''")),
+        None => String::new(),
+    };
+
+    let query = format!(
+        "SELECT {} FROM venues {} ORDER BY name ASC",
+        VENUE_COLUMNS, where_clause
+    );
+
+    let venues = sqlx::query_as::<_, Venue>(&query)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(venues))
+}
+
+// =============================================================================
+// HANDLER: GET SINGLE VENUE
+// =============================================================================
+
+/// Returns a single venue by its UUID.
+///
+/// # Endpoint
+/// `GET /api/venues/:id`
+async fn get_venue(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Venue>, AppError> {
+    let venue = sqlx::query_as::<_, Venue>(&format!(
+        "SELECT {} FROM venues WHERE id = $1",
+        VENUE_COLUMNS
+    ))
+        .bind(id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("venue"))?;
+
+    Ok(Json(venue))
+}
+
+// =============================================================================
+// HANDLER: CREATE VENUE
+// =============================================================================
+
+/// Creates a new venue.
+///
+/// # Endpoint
+/// `POST /api/venues`
+///
+/// # Returns
+/// - `201 Created` with the venue
+/// - `409 Conflict` if a venue with the same normalized name exists
+/// - `422 Unprocessable Entity` listing the fields that fail `validate_venue`
+async fn create_venue(
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateVenue>,
+) -> Result<(StatusCode, Json<Venue>), AppError> {
+    validate_venue(&payload)?;
+
+    let venue = sqlx::query_as::<_, Venue>(&format!(
+        r#"
+        INSERT INTO venues (
+            name, address, city, capacity, venue_type, noise_level,
+            parking_info, accessibility_info, website, latitude, longitude
+        )
+        VALUES ($1, $2, COALESCE($3, 'Tulsa'), $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING {}
+        "#,
+        VENUE_COLUMNS
+    ))
+        .bind(payload.name.trim())
+        .bind(&payload.address)
+        .bind(&payload.city)
+        .bind(payload.capacity)
+        .bind(&payload.venue_type)
+        .bind(&payload.noise_level)
+        .bind(&payload.parking_info)
+        .bind(&payload.accessibility_info)
+        .bind(&payload.website)
+        .bind(payload.latitude)
+        .bind(payload.longitude)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(venue)))
+}
+
+// =============================================================================
+// HANDLER: UPDATE VENUE
+// =============================================================================
+
+/// Replaces a venue's details.
+///
+/// # Endpoint
+/// `PUT /api/venues/:id`
+///
+/// Renaming a venue also updates the legacy `venue` text on its events so
+/// older clients see the new name.
+async fn update_venue(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateVenue>,
+) -> Result<Json<Venue>, AppError> {
+    validate_venue(&payload)?;
+
+    let mut tx = pool.begin().await?;
+
+    let venue = sqlx::query_as::<_, Venue>(&format!(
+        r#"
+        UPDATE venues
+        SET name = $2, address = $3, city = COALESCE($4, 'Tulsa'), capacity = $5,
+            venue_type = $6, noise_level = $7, parking_info = $8,
+            accessibility_info = $9, website = $10, latitude = $11, longitude = $12,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        VENUE_COLUMNS
+    ))
+        .bind(id)
+        .bind(payload.name.trim())
+        .bind(&payload.address)
+        .bind(&payload.city)
+        .bind(payload.capacity)
+        .bind(&payload.venue_type)
+        .bind(&payload.noise_level)
+        .bind(&payload.parking_info)
+        .bind(&payload.accessibility_info)
+        .bind(&payload.website)
+        .bind(payload.latitude)
+        .bind(payload.longitude)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("venue"))?;
+
+    sqlx::query("UPDATE events SET venue = $2 WHERE venue_id = $1 AND venue IS DISTINCT FROM $2")
+        .bind(id)
+        .bind(&venue.name)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(venue))
+}
+
+// =============================================================================
+// HANDLER: DELETE VENUE
+// =============================================================================
+
+/// Deletes a venue.
+///
+/// # Endpoint
+/// `DELETE /api/venues/:id`
+///
+/// Events at the venue are kept; their `venue_id` is cleared by the
+/// foreign key and the legacy `venue` text stays as it was.
+async fn delete_venue(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM venues WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found("venue"));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// =============================================================================
+// HANDLER: EVENTS AT A VENUE
+// =============================================================================
+
+/// Query parameters for listing a venue's events.
+#[derive(Debug, Deserialize)]
+pub struct VenueEventsQuery {
+    /// Include events that have already ended (default: false)
+    #[serde(default)]
+    pub include_past: bool,
+
+    /// Include cancelled events (default: false)
+    #[serde(default)]
+    pub include_cancelled: bool,
+
+    /// Include soft-archived events (default: false)
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// Returns the events linked to a venue, soonest first.
+///
+/// # Endpoint
+/// `GET /api/venues/:id/events`
+///
+/// # Returns
+/// - `200 OK` with a list of events (possibly empty)
+/// - `404 Not Found` if the venue doesn't exist
+async fn venue_events(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<VenueEventsQuery>,
+) -> Result<Json<Vec<Event>>, AppError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM venues WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&pool)
+        .await?;
+
+    if !exists {
+        return Err(AppError::not_found("venue"));
+    }
+
+    let mut conditions = vec!["venue_id = $1".to_string()];
+    if !params.include_past {
+        conditions.push(UPCOMING_FILTER.to_string());
+    }
+    if !params.include_cancelled {
+        conditions.push(NOT_CANCELLED_FILTER.to_string());
+    }
+    if !params.include_archived {
+        conditions.push(NOT_ARCHIVED_FILTER.to_string());
+    }
+
+    let query = format!(
+        "SELECT {} FROM events WHERE {} {}",
+        EVENT_COLUMNS,
+        conditions.join(" AND "),
+        KEYSET_ORDER
+    );
+
+    let events = sqlx::query_as::<_, Event>(&query)
+        .bind(id)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(events))
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// Checks a venue payload before it is written.
+///
+/// Rejects (422) an empty name, a name with no letters or digits (it would
+/// normalize to an empty string), a non-http website, and out-of-range
+/// or half-given coordinates. Every problem is reported, not just the first.
+fn validate_venue(payload: &CreateVenue) -> Result<(), Vec<FieldError>> {
+    let mut errors = Vec::new();
+
+    if !has_matchable_name(&payload.name) {
+        errors.push(FieldError::new("name", "must contain a letter or digit"));
+    }
+
+    if let Some(ref website) = payload.website {
+        if !is_http_url(website) {
+            errors.push(FieldError::new("website", "must be an absolute http(s) URL"));
+        }
+    }
+
+    match (payload.latitude, payload.longitude) {
+        (Some(lat), Some(lng)) if !geo::is_valid_coordinate(lat, lng) => {
+            errors.push(FieldError::new("latitude", "coordinates are out of range"));
+        }
+        (Some(_), None) => errors.push(FieldError::new("longitude", "required with latitude")),
+        (None, Some(_)) => errors.push(FieldError::new("latitude", "required with longitude")),
+        _ => {}
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// True if the name survives `normalize_venue_name` (has an ASCII letter or digit).
+fn has_matchable_name(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_alphanumeric())
+}
+
+/// Returns the id of the venue matching `name`, creating it if needed.
+///
+/// Matching uses the normalized name, so "CAINS BALLROOM" finds an existing
+/// "Cain's Ballroom". A newly created venue keeps `name` as written. If the
+/// venue exists without an address, `address` fills it in.
+///
+/// Returns `Ok(None)` for names that can't be matched (blank or punctuation).
+/// Takes a connection so callers can run it inside their own transaction.
+pub(crate) async fn find_or_create_venue(
+    conn: &mut PgConnection,
+    name: &str,
+    address: Option<&str>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    if !has_matchable_name(name) {
+        return Ok(None);
+    }
+
+    let id = sqlx::query_scalar(
+        r#"
+        INSERT INTO venues (name, address)
+        VALUES ($1, $2)
+        ON CONFLICT (normalized_name)
+            DO UPDATE SET address = COALESCE(venues.address, EXCLUDED.address)
+        RETURNING id
+        "#,
+    )
+        .bind(name.trim())
+        .bind(address)
+        .fetch_one(conn)
+        .await?;
+
+    Ok(Some(id))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn venue(name: &str) -> CreateVenue {
+        CreateVenue {
+            name: name.to_string(),
+            address: None,
+            city: None,
+            capacity: None,
+            venue_type: None,
+            noise_level: None,
+            parking_info: None,
+            accessibility_info: None,
+            website: None,
+            latitude: None,
+            longitude: None,
+        }
+    }
+
+    fn failing_fields(payload: &CreateVenue) -> Vec<String> {
+        match validate_venue(payload) {
+            Ok(()) => vec![],
+            Err(errors) => errors.into_iter().map(|e| e.field).collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_a_plain_venue() {
+        assert_eq!(validate_venue(&venue("Cain's Ballroom")), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unmatchable_names() {
+        assert_eq!(failing_fields(&venue("")), ["name"]);
+        assert_eq!(failing_fields(&venue(" -- ")), ["name"]);
+    }
+
+    #[test]
+    fn rejects_bad_website_and_coordinates() {
+        let bad_site = CreateVenue {
+            website: Some("javascript:alert(1)".to_string()),
+            ..venue("BOK Center")
+        };
+        let half_coords = CreateVenue {
+            latitude: Some(36.15),
+            ..venue("BOK Center")
+        };
+        let bad_coords = CreateVenue {
+            latitude: Some(136.15),
+            longitude: Some(-95.99),
+            ..venue("BOK Center")
+        };
+
+        assert_eq!(failing_fields(&bad_site), ["website"]);
+        assert_eq!(failing_fields(&half_coords), ["longitude"]);
+        assert_eq!(failing_fields(&bad_coords), ["latitude"]);
+    }
+
+    #[tokio::test]
+    async fn writes_need_the_admin_key() {
+        use axum::{body::Body, http::Request};
+        use sqlx::postgres::PgPoolOptions;
+        use std::sync::Arc;
+        use tower::ServiceExt;
+
+        use crate::services::llm_provider::MockProvider;
+
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let state = AppState {
+            pool,
+            llm: Arc::new(MockProvider::echo()),
+            intent_cache: Default::default(),
+            chat_limiter: Default::default(),
+            chat_suggestions: Default::default(),
+            prompt: Default::default(),
+            llm_health: Default::default(),
+            scrapers: Default::default(),
+        };
+        let app = routes().with_state(state);
+        // Same key as the chat tests: the variable is shared by the process
+        std::env::set_var("ADMIN_API_KEY", "chat-test-admin-key");
+
+        let id = Uuid::new_v4();
+        let body = || Body::from(r#"{"name": "Cain's Ballroom"}"#);
+        let requests = [
+            Request::post("/").header("content-type", "application/json").body(body()),
+            Request::put(format!("/{}", id)).header("content-type", "application/json").body(body()),
+            Request::delete(format!("/{}", id)).body(Body::empty()),
+            Request::delete(format!("/{}", id)).header(auth::ADMIN_KEY_HEADER, "guess").body(Body::empty()),
+        ];
+        for request in requests {
+            let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        // Reads aren't guarded (this one fails on the unreachable database)
+        let read = app.oneshot(Request::get(format!("/{}", id)).body(Body::empty()).unwrap()).await.unwrap();
+        assert_ne!(read.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,211 @@
+//! # Fetch Cache
+//!
+//! What we know about each page scraped into events, so the next run can
+//! ask for it conditionally (`Fetcher::get_events`) and skip the ones
+//! that haven't changed.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Unchanged
+//! A page is unchanged if the site answers `304 Not Modified` to our
+//! `If-None-Match` / `If-Modified-Since`, or sends the same bytes again
+//! (sites that ignore conditional headers). Its events aren't parsed
+//! again; they're marked seen (`mark_seen`), so they aren't taken for
+//! shows the source dropped (`stale.rs`). A page a sitemap says hasn't
+//! changed since its entry was saved isn't even asked for
+//! (`lookup_since`).
+//!
+//! ## When It's Written
+//! Entries are only saved after a real run stored its events: a dry run,
+//! or a run that failed, must not make the next run skip the page. An
+//! entry older than `MAX_AGE` isn't used, so every page is parsed again
+//! at least that often (a fixed parser gets to see it).
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// How long an entry may skip parsing before the page is parsed anyway.
+pub const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+// =============================================================================
+// MODEL
+// =============================================================================
+
+/// One page as of its last parse, as stored in `fetch_cache`.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct CachedPage {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// `body_hash` of its body
+    pub body_hash: String,
+    /// The `source_url` of each event parsed from it
+    pub event_urls: Vec<String>,
+}
+
+/// SHA-256 of a page body, hex.
+pub fn body_hash(body: &str) -> String {
+    format!("{:x}", Sha256::digest(body.as_bytes()))
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// The entry for `url`, unless it's older than `MAX_AGE`.
+pub async fn lookup(pool: &PgPool, url: &str) -> Result<Option<CachedPage>, sqlx::Error> {
+    sqlx::query_as::<_, CachedPage>(
+        r#"
+        SELECT url, etag, last_modified, body_hash, event_urls FROM fetch_cache
+        WHERE url = $1 AND fetched_at > NOW() - make_interval(secs => $2)
+        "#,
+    )
+        .bind(url)
+        .bind(MAX_AGE.as_secs_f64())
+        .fetch_optional(pool)
+        .await
+}
+
+/// The entry for `url`, if it was saved at or after `since` (and isn't
+/// older than `MAX_AGE`).
+pub async fn lookup_since(pool: &PgPool, url: &str, since: DateTime<Utc>) -> Result<Option<CachedPage>, sqlx::Error> {
+    sqlx::query_as::<_, CachedPage>(
+        r#"
+        SELECT url, etag, last_modified, body_hash, event_urls FROM fetch_cache
+        WHERE url = $1 AND fetched_at > NOW() - make_interval(secs => $2) AND fetched_at >= $3
+        "#,
+    )
+        .bind(url)
+        .bind(MAX_AGE.as_secs_f64())
+        .bind(since)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The events `url` had when last parsed, however long ago (none if it
+/// never was).
+pub async fn event_urls(pool: &PgPool, url: &str) -> Result<Vec<String>, sqlx::Error> {
+    let event_urls: Option<Vec<String>> = sqlx::query_scalar("SELECT event_urls FROM fetch_cache WHERE url = $1")
+        .bind(url)
+        .fetch_optional(pool)
+        .await?;
+    Ok(event_urls.unwrap_or_default())
+}
+
+/// Saves `pages`, replacing their old entries.
+pub async fn store(pool: &PgPool, pages: &[CachedPage]) -> Result<(), sqlx::Error> {
+    for page in pages {
+        sqlx::query(
+            r#"
+            INSERT INTO fetch_cache (url, etag, last_modified, body_hash, event_urls)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (url) DO UPDATE
+            SET etag = EXCLUDED.etag, last_modified = EXCLUDED.last_modified,
+                body_hash = EXCLUDED.body_hash, event_urls = EXCLUDED.event_urls, fetched_at = NOW()
+            "#,
+        )
+            .bind(&page.url)
+            .bind(&page.etag)
+            .bind(&page.last_modified)
+            .bind(&page.body_hash)
+            .bind(&page.event_urls)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Marks the events listed at `event_urls` (theirs, or absorbed by a
+/// merge) as seen now. Returns how many.
+pub async fn mark_seen(pool: &PgPool, event_urls: &[String]) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE events SET last_seen_at = NOW()
+        WHERE source_url = ANY($1)
+           OR id IN (SELECT event_id FROM event_sources WHERE source_url = ANY($1))
+        "#,
+    )
+        .bind(event_urls)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_are_hex_sha256() {
+        assert_eq!(body_hash(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_ne!(body_hash("<p>Jazz</p>"), body_hash("<p>Jazz </p>"));
+    }
+
+    #[tokio::test]
+    async fn entries_are_replaced_and_expire() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let page = CachedPage {
+            url: format!("https://cache.example/{}", uuid::Uuid::new_v4()),
+            etag: Some("\"v1\"".into()),
+            last_modified: None,
+            body_hash: body_hash("one"),
+            event_urls: vec!["https://cache.example/events/1".into()],
+        };
+        store(&pool, std::slice::from_ref(&page)).await.unwrap();
+        let newer = CachedPage { etag: Some("\"v2\"".into()), body_hash: body_hash("two"), ..page.clone() };
+        store(&pool, std::slice::from_ref(&newer)).await.unwrap();
+        assert_eq!(lookup(&pool, &page.url).await.unwrap(), Some(newer));
+
+        sqlx::query("UPDATE fetch_cache SET fetched_at = NOW() - INTERVAL '2 days' WHERE url = $1")
+            .bind(&page.url)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(lookup(&pool, &page.url).await.unwrap(), None);
+        assert_eq!(event_urls(&pool, &page.url).await.unwrap(), page.event_urls);
+    }
+
+    #[tokio::test]
+    async fn entries_are_found_by_when_they_were_saved() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let page = CachedPage {
+            url: format!("https://cache.example/{}", uuid::Uuid::new_v4()),
+            etag: None,
+            last_modified: None,
+            body_hash: body_hash("one"),
+            event_urls: Vec::new(),
+        };
+        store(&pool, std::slice::from_ref(&page)).await.unwrap();
+        let hour = chrono::Duration::hours(1);
+        assert_eq!(lookup_since(&pool, &page.url, Utc::now() - hour).await.unwrap(), Some(page.clone()));
+        // Changed after we read it
+        assert_eq!(lookup_since(&pool, &page.url, Utc::now() + hour).await.unwrap(), None);
+        assert_eq!(event_urls(&pool, "https://cache.example/never-fetched").await.unwrap(), Vec::<String>::new());
+
+        sqlx::query("DELETE FROM fetch_cache WHERE url = $1").bind(&page.url).execute(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+//! # Category Mappings
+//!
+//! Each source's own category labels ("Parks & Recreation", "Music"),
+//! turned into ours as its events are stored (`persist.rs`).
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Which Category
+//! A scraper reports the source's label as `external_category`, and may
+//! guess one of ours as `category`. When the event is stored:
+//! 1. A mapping for (source, label) wins
+//! 2. Failing that, the scraper's `category`
+//! 3. Failing that, none: the classifier picks one later
+//!    (`services::classification`)
+//!
+//! A label with no mapping is counted in `unmapped_categories`, so
+//! `GET /api/admin/categories/unmapped` can show which are worth mapping.
+//! Labels are compared normalized (`categories::normalize`).
+//!
+//! ## Replacing
+//! Categories set from a listing are marked `category_source = 'scraper'`,
+//! so the next scrape may replace them (a new mapping applies to events
+//! already stored); so may the classifier's. An editor's are kept.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::models::FieldError;
+use crate::scraper::sources::MAX_ID_CHARS;
+use crate::services::categories;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// `events.category_source` of categories set from a listing.
+pub const CATEGORY_SOURCE_SCRAPER: &str = "scraper";
+
+/// Longest accepted external label.
+pub const MAX_LABEL_CHARS: usize = 100;
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// One mapping, as stored in `category_mappings`.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct CategoryMapping {
+    /// The `source_id` it applies to
+    pub source: String,
+    /// The source's label, normalized
+    pub external_category: String,
+    /// Ours
+    pub internal_category: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for adding a mapping.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateCategoryMapping {
+    pub source: String,
+    pub external_category: String,
+    pub internal_category: String,
+}
+
+/// A label stored without a mapping, and how often.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct UnmappedCategory {
+    pub source: String,
+    pub external_category: String,
+    /// Events stored with it, ever
+    pub seen_count: i64,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl CreateCategoryMapping {
+    /// Checks the fields, `internal_category` against `known` (our
+    /// categories); every problem is reported. Both categories are
+    /// compared normalized.
+    pub fn validate(&self, known: &BTreeSet<String>) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let external = categories::normalize(&self.external_category);
+
+        let id_chars = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+        if self.source.is_empty() || self.source.len() > MAX_ID_CHARS || !self.source.chars().all(id_chars) {
+            errors.push(FieldError::new("source", "must be a source id (lowercase letters, digits, underscores)"));
+        }
+        if external.is_empty() || external.chars().count() > MAX_LABEL_CHARS {
+            errors.push(FieldError::new(
+                "external_category",
+                format!("must be 1-{} characters", MAX_LABEL_CHARS),
+            ));
+        }
+        if let Some(error) = categories::check_known("internal_category", &categories::normalize(&self.internal_category), known) {
+            errors.push(error);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// =============================================================================
+// MAPPING
+// =============================================================================
+
+/// One source's mappings, loaded once per batch of events.
+#[derive(Debug, Clone, Default)]
+pub struct CategoryMap {
+    mappings: HashMap<String, String>,
+    /// Labels that had no mapping, and how many events had each
+    unmapped: BTreeMap<String, i64>,
+}
+
+impl CategoryMap {
+    /// The mappings for `source`.
+    pub async fn load(pool: &PgPool, source: &str) -> Result<Self, sqlx::Error> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT external_category, internal_category FROM category_mappings WHERE source = $1",
+        )
+            .bind(source)
+            .fetch_all(pool)
+            .await?;
+        Ok(Self { mappings: rows.into_iter().collect(), unmapped: BTreeMap::new() })
+    }
+
+    /// Our category for an event labelled `external` (see "Which Category"
+    /// above); `fallback` is the scraper's own guess. A label with no
+    /// mapping is noted for `record_unmapped`.
+    pub fn resolve(&mut self, external: Option<&str>, fallback: Option<String>) -> Option<String> {
+        let label = external.map(categories::normalize).filter(|label| !label.is_empty());
+        if let Some(label) = label {
+            match self.mappings.get(&label) {
+                Some(internal) => return Some(internal.clone()),
+                None => *self.unmapped.entry(label).or_default() += 1,
+            }
+        }
+        fallback
+    }
+
+    /// Adds the labels `resolve` found no mapping for to `source`'s counts.
+    pub async fn record_unmapped(&self, pool: &PgPool, source: &str) -> Result<(), sqlx::Error> {
+        for (label, seen) in &self.unmapped {
+            sqlx::query(
+                r#"
+                INSERT INTO unmapped_categories (source, external_category, seen_count)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (source, external_category) DO UPDATE
+                SET seen_count = unmapped_categories.seen_count + EXCLUDED.seen_count, last_seen_at = NOW()
+                "#,
+            )
+                .bind(source)
+                .bind(label)
+                .bind(seen)
+                .execute(pool)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// Every mapping (or `source`'s), by source and label.
+pub async fn list(pool: &PgPool, source: Option<&str>) -> Result<Vec<CategoryMapping>, sqlx::Error> {
+    sqlx::query_as::<_, CategoryMapping>(
+        r#"
+        SELECT source, external_category, internal_category, created_at FROM category_mappings
+        WHERE $1::text IS NULL OR source = $1
+        ORDER BY source, external_category
+        "#,
+    )
+        .bind(source)
+        .fetch_all(pool)
+        .await
+}
+
+/// Adds a validated mapping, normalized. Its label drops out of
+/// `list_unmapped`.
+pub async fn create(pool: &PgPool, payload: &CreateCategoryMapping) -> Result<CategoryMapping, sqlx::Error> {
+    sqlx::query_as::<_, CategoryMapping>(
+        r#"
+        INSERT INTO category_mappings (source, external_category, internal_category)
+        VALUES ($1, $2, $3)
+        RETURNING source, external_category, internal_category, created_at
+        "#,
+    )
+        .bind(&payload.source)
+        .bind(categories::normalize(&payload.external_category))
+        .bind(categories::normalize(&payload.internal_category))
+        .fetch_one(pool)
+        .await
+}
+
+/// Labels seen since `since` that still have no mapping, most seen first.
+pub async fn list_unmapped(pool: &PgPool, since: DateTime<Utc>, limit: i64) -> Result<Vec<UnmappedCategory>, sqlx::Error> {
+    sqlx::query_as::<_, UnmappedCategory>(
+        r#"
+        SELECT u.source, u.external_category, u.seen_count, u.first_seen_at, u.last_seen_at
+        FROM unmapped_categories u
+        WHERE u.last_seen_at >= $1
+          AND NOT EXISTS (SELECT 1 FROM category_mappings m
+                          WHERE m.source = u.source AND m.external_category = u.external_category)
+        ORDER BY u.seen_count DESC, u.source, u.external_category
+        LIMIT $2
+        "#,
+    )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mappings_beat_the_scrapers_guess_and_unmapped_labels_are_counted() {
+        let mut map = CategoryMap {
+            mappings: [("parks & recreation".to_string(), "outdoors".to_string())].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let community = || Some("community".to_string());
+        assert_eq!(map.resolve(Some(" Parks & Recreation "), community()).as_deref(), Some("outdoors"));
+        assert_eq!(map.resolve(Some("Government"), community()).as_deref(), Some("community"));
+        assert_eq!(map.resolve(Some("GOVERNMENT"), None), None);
+        assert_eq!(map.resolve(None, None), None);
+        assert_eq!(map.unmapped, BTreeMap::from([("government".to_string(), 2)]));
+    }
+
+    #[test]
+    fn mappings_need_a_source_a_label_and_one_of_our_categories() {
+        let known = ["music".to_string(), "outdoors".to_string()].into_iter().collect();
+        let mapping = CreateCategoryMapping {
+            source: "tulsa_city".to_string(),
+            external_category: " Parks & Recreation ".to_string(),
+            internal_category: "Outdoors".to_string(),
+        };
+        assert!(mapping.validate(&known).is_ok());
+
+        let bad = CreateCategoryMapping {
+            source: "Tulsa City".to_string(),
+            external_category: "  ".to_string(),
+            internal_category: "stuff".to_string(),
+        };
+        let fields: Vec<String> = bad.validate(&known).unwrap_err().into_iter().map(|error| error.field).collect();
+        assert_eq!(fields, ["source", "external_category", "internal_category"]);
+    }
+}
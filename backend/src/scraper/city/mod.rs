@@ -0,0 +1,9 @@
+//! # City Scrapers
+//!
+//! Official municipal calendars.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+
+/// The City of Tulsa events feed.
+pub mod tulsa_calendar;
@@ -0,0 +1,528 @@
+//! # City of Tulsa Calendar
+//!
+//! The city's official events calendar, read from its RSS feed rather
+//! than the listing HTML: the feed carries the same fields in stable
+//! elements, without the page's layout to break on.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## The Feed
+//! One month per request (`/calendar/rss?month=3&year=2026`), split into
+//! pages linked by `<atom:link rel="next">`. Each item looks like:
+//! ```xml
+//! <item>
+//!   <title>City Council Regular Meeting</title>
+//!   <link>https://www.cityoftulsa.org/calendar/event/4122</link>
+//!   <description>Agendas are posted 24 hours in advance.</description>
+//!   <category>Government</category>
+//!   <calendarEvent:EventDates>March 25, 2026</calendarEvent:EventDates>
+//!   <calendarEvent:EventTimes>5:00 PM - 7:00 PM</calendarEvent:EventTimes>
+//!   <calendarEvent:Location>City Hall, 175 E 2nd St</calendarEvent:Location>
+//! </item>
+//! ```
+//!
+//! ## A Run
+//! Every month from this one through `HORIZON_DAYS` out, following next
+//! links (at most `MAX_PAGES_PER_MONTH`). Events that already ended or
+//! start past the horizon are dropped, and an event listed on two pages
+//! is kept once. A page that can't be fetched (after the fetcher's
+//! retries) ends its month and the run goes on; only if no page at all
+//! could be read does the run fail.
+//!
+//! ## All-Day Events
+//! `EventTimes` of "All Day" (or none) start at noon local time
+//! (`dates::DEFAULT_START_HOUR`), with no
+//! end time. We have no all-day flag; noon keeps the event on the right
+//! day in every US timezone and doesn't claim an opening hour the city
+//! never gave (9am would read as "starts at 9").
+//!
+//! ## Categories
+//! "community", unless the title, feed category or description has a
+//! keyword from `CATEGORY_KEYWORDS` (first match wins). The feed's first
+//! `<category>` is reported as the `external_category`, so a mapping
+//! (`scraper/categories.rs`) can overrule the keywords.
+
+use std::collections::HashSet;
+
+use axum::async_trait;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::Reader;
+use reqwest::Url;
+use scraper::Html;
+
+use crate::scraper::dates::{parse_event_datetime, TULSA_TZ};
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// The live site.
+pub const BASE_URL: &str = "https://www.cityoftulsa.org";
+
+/// The feed, relative to the base URL.
+const FEED_PATH: &str = "/calendar/rss";
+
+/// How far ahead to look.
+pub const HORIZON_DAYS: i64 = 60;
+
+/// Stops a feed whose next links loop.
+const MAX_PAGES_PER_MONTH: usize = 10;
+
+/// The city's own name for its calendar, stored as `source_name`.
+const SOURCE_NAME: &str = "City of Tulsa";
+
+/// Categories other than "community", and the words that suggest them.
+pub const CATEGORY_KEYWORDS: &[(&str, &[&str])] = &[
+    ("music", &["concert", "concerts", "symphony", "orchestra", "band", "music", "jazz"]),
+    ("festival", &["festival", "fest"]),
+    ("sports", &["5k", "10k", "marathon", "race", "tournament"]),
+    ("outdoors", &["park", "parks", "trail", "trails", "nature", "garden", "hike", "hikes"]),
+    ("family", &["kids", "family", "children", "storytime"]),
+    ("education", &["class", "workshop", "lecture", "library"]),
+    ("art", &["art", "arts", "gallery", "exhibit", "museum"]),
+    ("film", &["film", "movie", "movies"]),
+];
+
+// =============================================================================
+// SCRAPER
+// =============================================================================
+
+/// Scraper for the City of Tulsa events feed.
+pub struct TulsaCalendarScraper {
+    base_url: String,
+}
+
+impl TulsaCalendarScraper {
+    pub fn new() -> Self {
+        Self::with_base_url(BASE_URL)
+    }
+
+    /// Points the scraper somewhere else (a mock server in tests).
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self { base_url: base_url.trim_end_matches('/').to_string() }
+    }
+
+    /// Every event from `today`'s month through the horizon.
+    async fn crawl(&self, fetcher: &Fetcher, today: NaiveDate) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        let horizon = today + Duration::days(HORIZON_DAYS);
+        let mut seen = HashSet::new();
+        let mut events = Vec::new();
+        let mut pages_read = 0;
+        let mut last_error = None;
+
+        for (year, month) in months_until(today, horizon) {
+            let mut url = feed_url(&self.base_url, year, month)?;
+            for _ in 0..MAX_PAGES_PER_MONTH {
+                // A page robots.txt rules out, or that can't be fetched, ends
+                // that month (the fetcher counts it); the next month still runs
+                let xml = match fetcher.get_text(url.as_str()).await {
+                    Ok(xml) => xml,
+                    Err(ScraperError::Disallowed(_)) => break,
+                    Err(e) => {
+                        last_error = Some(e);
+                        break;
+                    }
+                };
+                pages_read += 1;
+                let page = parse_feed(&xml)?;
+
+                for item in page.items {
+                    let Some(event) = item.into_event(today) else { continue };
+                    let ends = event.end_time.unwrap_or(event.start_time).with_timezone(&TULSA_TZ).date_naive();
+                    let starts = event.start_time.with_timezone(&TULSA_TZ).date_naive();
+                    if ends >= today && starts <= horizon && seen.insert(event.source_url.clone()) {
+                        events.push(event);
+                    }
+                }
+
+                match page.next.and_then(|next| url.join(&next).ok()) {
+                    Some(next) => url = next,
+                    None => break,
+                }
+            }
+        }
+
+        // Not one page: the feed is down, not a month missing
+        match last_error {
+            Some(e) if pages_read == 0 => Err(e),
+            _ => Ok(events),
+        }
+    }
+}
+
+impl Default for TulsaCalendarScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventScraper for TulsaCalendarScraper {
+    fn name(&self) -> &str {
+        SOURCE_NAME
+    }
+
+    fn source_id(&self) -> &str {
+        "tulsa_city_calendar"
+    }
+
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        let today = Utc::now().with_timezone(&TULSA_TZ).date_naive();
+        self.crawl(fetcher, today).await
+    }
+}
+
+fn feed_url(base_url: &str, year: i32, month: u32) -> Result<Url, ScraperError> {
+    let url = format!("{}{}?month={}&year={}", base_url, FEED_PATH, month, year);
+    Url::parse(&url).map_err(|e| ScraperError::Validation(format!("feed URL {}: {}", url, e)))
+}
+
+/// `(year, month)` for every month from `from`'s through `to`'s.
+fn months_until(from: NaiveDate, to: NaiveDate) -> Vec<(i32, u32)> {
+    let mut months = Vec::new();
+    let (mut year, mut month) = (from.year(), from.month());
+    while (year, month) <= (to.year(), to.month()) {
+        months.push((year, month));
+        (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    }
+    months
+}
+
+// =============================================================================
+// FEED PARSING
+// =============================================================================
+
+/// One page of the feed.
+#[derive(Debug, Default, PartialEq)]
+pub struct FeedPage {
+    pub items: Vec<FeedItem>,
+    /// The `rel="next"` link, as written (may be relative)
+    pub next: Option<String>,
+}
+
+/// One `<item>`, as text.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeedItem {
+    pub title: String,
+    pub link: String,
+    pub description: Option<String>,
+    pub categories: Vec<String>,
+    pub dates: String,
+    pub times: Option<String>,
+    pub location: Option<String>,
+}
+
+/// Parses one page of the feed.
+pub fn parse_feed(xml: &str) -> Result<FeedPage, ScraperError> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut page = FeedPage::default();
+    let mut item: Option<FeedItem> = None;
+    // Local name of the element whose text we're reading
+    let mut field = String::new();
+    let mut saw_channel = false;
+
+    loop {
+        let event = reader.read_event().map_err(|e| ScraperError::Parse {
+            selector: "rss".to_string(),
+            context: format!("City of Tulsa feed at byte {}: {}", reader.buffer_position(), e),
+        })?;
+        match event {
+            XmlEvent::Start(tag) => {
+                let name = String::from_utf8_lossy(tag.local_name().as_ref()).into_owned();
+                match name.as_str() {
+                    "channel" => saw_channel = true,
+                    "item" => item = Some(FeedItem::default()),
+                    _ => {}
+                }
+                field = name;
+            }
+            XmlEvent::Empty(tag) => {
+                let is_next = tag.local_name().as_ref() == b"link"
+                    && tag.try_get_attribute("rel").ok().flatten().is_some_and(|rel| rel.value.as_ref() == b"next");
+                if is_next && item.is_none() {
+                    page.next = tag
+                        .try_get_attribute("href")
+                        .ok()
+                        .flatten()
+                        .and_then(|href| href.unescape_value().ok())
+                        .map(|href| href.into_owned());
+                }
+            }
+            XmlEvent::Text(text) => {
+                if let (Some(item), Ok(text)) = (item.as_mut(), text.unescape()) {
+                    item.set(&field, &text);
+                }
+            }
+            XmlEvent::CData(data) => {
+                if let Some(item) = item.as_mut() {
+                    item.set(&field, &String::from_utf8_lossy(&data));
+                }
+            }
+            XmlEvent::End(tag) => {
+                if tag.local_name().as_ref() == b"item" {
+                    page.items.extend(item.take());
+                }
+                field.clear();
+            }
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !saw_channel {
+        return Err(ScraperError::Parse {
+            selector: "rss > channel".to_string(),
+            context: "City of Tulsa feed".to_string(),
+        });
+    }
+    Ok(page)
+}
+
+impl FeedItem {
+    fn set(&mut self, field: &str, text: &str) {
+        let text = text.trim().to_string();
+        match field {
+            "title" => self.title = text,
+            "link" => self.link = text,
+            "description" => self.description = Some(text),
+            "category" => self.categories.push(text),
+            "EventDates" => self.dates = text,
+            "EventTimes" => self.times = Some(text),
+            "Location" => self.location = Some(text),
+            _ => {}
+        }
+    }
+
+    /// The item as an event, or `None` (logged) without a title, link or
+    /// readable date. `today` (local) is the reference for
+    /// `parse_event_datetime`.
+    pub fn into_event(self, today: NaiveDate) -> Option<ScrapedEvent> {
+        if self.title.is_empty() || self.link.is_empty() {
+            tracing::warn!(title = %self.title, "City of Tulsa: item without a title or link");
+            return None;
+        }
+
+        // "March 16, 2026 - March 20, 2026" and "5:00 PM - 7:00 PM", read
+        // together: the times go with the first date and the last
+        let when = format!("{} {}", self.dates, self.times.as_deref().unwrap_or_default());
+        let (start, end) = match parse_event_datetime(&when, today, TULSA_TZ) {
+            Ok(times) => times,
+            Err(e) => {
+                tracing::warn!(title = %self.title, error = %e, "City of Tulsa: unreadable date");
+                return None;
+            }
+        };
+
+        let description = self.description.as_deref().map(plain_text).filter(|text| !text.is_empty());
+        let category = categorize(&[
+            self.title.as_str(),
+            &self.categories.join(" "),
+            description.as_deref().unwrap_or_default(),
+        ]);
+
+        // "City Hall, 175 E 2nd St": the venue, then its address
+        let (venue, venue_address) = match self.location.as_deref().map(|l| l.split_once(',')) {
+            Some(Some((venue, address))) => (Some(venue.trim().to_string()), Some(address.trim().to_string())),
+            Some(None) => (self.location.clone(), None),
+            None => (None, None),
+        };
+
+        Some(ScrapedEvent {
+            description,
+            venue,
+            venue_address,
+            location: self.location,
+            end_time: end,
+            category: Some(category.to_string()),
+            external_category: self.categories.first().cloned(),
+            ..ScrapedEvent::new(&self.title, &self.link, start)
+        })
+    }
+}
+
+/// Text content of an HTML description, whitespace collapsed.
+fn plain_text(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    fragment.root_element().text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+}
+
+/// The first `CATEGORY_KEYWORDS` category with a keyword among `texts`'
+/// words, else "community".
+fn categorize(texts: &[&str]) -> &'static str {
+    let words: HashSet<String> = texts
+        .iter()
+        .flat_map(|text| text.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    CATEGORY_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|keyword| words.contains(*keyword)))
+        .map_or("community", |(category, _)| category)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const MARCH: &str = include_str!("../../../tests/fixtures/tulsa_calendar/2026-03-page1.xml");
+    const MARCH_PAGE_2: &str = include_str!("../../../tests/fixtures/tulsa_calendar/2026-03-page2.xml");
+    const APRIL: &str = include_str!("../../../tests/fixtures/tulsa_calendar/2026-04.xml");
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_a_feed_page() {
+        let page = parse_feed(MARCH).unwrap();
+        assert_eq!(page.next.as_deref(), Some("/calendar/rss?month=3&year=2026&page=2"));
+        assert_eq!(page.items.len(), 3);
+
+        let camp = page.items[1].clone().into_event(date(2026, 3, 20)).unwrap();
+        assert_eq!(camp.title, "Spring Break Nature Camp");
+        assert_eq!(camp.source_url, "https://www.cityoftulsa.org/calendar/event/4117");
+        // 9 AM on the 16th through 3 PM on the 20th, CDT
+        assert_eq!(camp.start_time, utc("2026-03-16T14:00:00Z"));
+        assert_eq!(camp.end_time, Some(utc("2026-03-20T20:00:00Z")));
+        assert_eq!(camp.description.as_deref(), Some("Hikes, crafts and critters for ages 6–12. Registration required."));
+        assert_eq!(camp.venue.as_deref(), Some("Oxley Nature Center"));
+        assert_eq!(camp.venue_address.as_deref(), Some("6700 Mohawk Blvd"));
+        assert_eq!(camp.category.as_deref(), Some("outdoors"));
+
+        let council = page.items[2].clone().into_event(date(2026, 3, 20)).unwrap();
+        assert_eq!(council.category.as_deref(), Some("community"));
+        assert_eq!(council.start_time, utc("2026-03-25T22:00:00Z"));
+
+        let last = parse_feed(MARCH_PAGE_2).unwrap();
+        assert_eq!(last.next, None);
+    }
+
+    #[test]
+    fn all_day_events_start_at_noon() {
+        let cleanup = parse_feed(MARCH_PAGE_2).unwrap().items[1].clone().into_event(date(2026, 3, 20)).unwrap();
+        assert_eq!(cleanup.start_time, utc("2026-03-28T17:00:00Z"));
+        assert_eq!(cleanup.end_time, None);
+        assert_eq!(cleanup.venue.as_deref(), Some("Various locations"));
+    }
+
+    #[test]
+    fn keywords_pick_the_category() {
+        assert_eq!(categorize(&["Tulsa Symphony in the Park", "Arts & Culture", ""]), "music");
+        assert_eq!(categorize(&["Earth Day Trash Off", "", "Clean up city parks"]), "outdoors");
+        assert_eq!(categorize(&["Mayfest Planning", "", ""]), "community");
+        assert_eq!(categorize(&["Route 66 Marathon", "", ""]), "sports");
+    }
+
+    #[test]
+    fn times_and_ranges() {
+        let item = |times: &str| FeedItem {
+            title: "Parks Board".to_string(),
+            link: "https://www.cityoftulsa.org/calendar/event/1".to_string(),
+            dates: "April 12, 2026".to_string(),
+            times: Some(times.to_string()),
+            ..FeedItem::default()
+        };
+        let times = |times: &str| {
+            let event = item(times).into_event(date(2026, 3, 20)).unwrap();
+            (event.start_time, event.end_time)
+        };
+        assert_eq!(times("5:00 PM - 7:00 PM"), (utc("2026-04-12T22:00:00Z"), Some(utc("2026-04-13T00:00:00Z"))));
+        assert_eq!(times("6 - 7:30 PM"), (utc("2026-04-12T23:00:00Z"), Some(utc("2026-04-13T00:30:00Z"))));
+        assert_eq!(times("7:30 PM"), (utc("2026-04-13T00:30:00Z"), None));
+        assert_eq!(times("All Day"), (utc("2026-04-12T17:00:00Z"), None));
+        let undated = FeedItem { dates: "TBA".to_string(), ..item("7:30 PM") };
+        assert_eq!(undated.into_event(date(2026, 3, 20)), None);
+        assert_eq!(months_until(date(2026, 11, 20), date(2027, 1, 19)), [(2026, 11), (2026, 12), (2027, 1)]);
+    }
+
+    #[test]
+    fn a_page_without_a_channel_is_a_parse_error() {
+        assert!(matches!(parse_feed("<html><body>Down for maintenance</body></html>"), Err(ScraperError::Parse { .. })));
+        assert!(matches!(parse_feed("<rss><channel><item><title>Oops</rss>"), Err(ScraperError::Parse { .. })));
+    }
+
+    #[tokio::test]
+    async fn crawls_every_page_of_every_month_to_the_horizon() {
+        let server = MockServer::start().await;
+        let feed = |month: &str, page: Option<&str>, body: &str| {
+            let mut mock = Mock::given(method("GET")).and(path(FEED_PATH)).and(query_param("month", month));
+            if let Some(page) = page {
+                mock = mock.and(query_param("page", page));
+            }
+            mock.respond_with(ResponseTemplate::new(200).set_body_string(body.to_string()))
+        };
+        // Most specific first: wiremock uses the first mock that matches
+        feed("3", Some("2"), MARCH_PAGE_2).mount(&server).await;
+        feed("3", None, MARCH).mount(&server).await;
+        feed("4", None, APRIL).mount(&server).await;
+        let may = r#"<rss><channel><item><title>Route 66 Marathon Info Session</title>
+            <link>https://www.cityoftulsa.org/calendar/event/4250</link>
+            <EventDates>May 30, 2026</EventDates></item></channel></rss>"#;
+        feed("5", None, may).mount(&server).await;
+
+        let scraper = TulsaCalendarScraper::with_base_url(&server.uri());
+        let events = scraper.crawl(&Fetcher::for_tests(), date(2026, 3, 20)).await.unwrap();
+
+        // Winterfest already ended, the council meeting is listed twice, and
+        // the May 30 session is past the 60-day horizon
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Spring Break Nature Camp",
+                "City Council Regular Meeting",
+                "Neighborhood Cleanup Volunteer Day",
+                "Tulsa Symphony in the Park",
+                "Earth Day Trash Off",
+            ]
+        );
+        // Four feed pages, plus robots.txt (a 404 here: nothing disallowed)
+        assert_eq!(server.received_requests().await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn a_month_that_wont_load_is_skipped() {
+        let server = MockServer::start().await;
+        let feed = |month: &str, response: ResponseTemplate| {
+            Mock::given(method("GET")).and(path(FEED_PATH)).and(query_param("month", month)).respond_with(response)
+        };
+        feed("3", ResponseTemplate::new(200).set_body_string(MARCH_PAGE_2)).mount(&server).await;
+        feed("4", ResponseTemplate::new(404)).mount(&server).await;
+        feed("5", ResponseTemplate::new(200).set_body_string("<rss><channel></channel></rss>")).mount(&server).await;
+
+        let scraper = TulsaCalendarScraper::with_base_url(&server.uri());
+        let fetcher = Fetcher::for_tests();
+        let events = scraper.crawl(&fetcher, date(2026, 3, 20)).await.unwrap();
+        assert_eq!(events.len(), 2);
+        let failed = fetcher.failed_urls();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].url.contains("month=4"), "{}", failed[0].url);
+
+        // Every month down is the feed down
+        let down = MockServer::start().await;
+        feed("3", ResponseTemplate::new(404)).mount(&down).await;
+        feed("4", ResponseTemplate::new(404)).mount(&down).await;
+        feed("5", ResponseTemplate::new(404)).mount(&down).await;
+        let scraper = TulsaCalendarScraper::with_base_url(&down.uri());
+        assert!(matches!(scraper.crawl(&Fetcher::for_tests(), date(2026, 3, 20)).await, Err(ScraperError::Http(_))));
+    }
+}
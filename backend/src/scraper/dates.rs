@@ -0,0 +1,567 @@
+//! # Scraped Date Helpers
+//!
+//! Date and time reading shared by the site scrapers. Every scraper reads
+//! its page's date text with `parse_event_datetime` (or `parse_event_when`
+//! for its own default time) rather than parsing it itself.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## What `parse_event_when` Reads
+//! | Text                                          | Reads as                  |
+//! |-----------------------------------------------|---------------------------|
+//! | `Fri, Jan 24 · 8:00 PM`                       | Jan 24, 20:00             |
+//! | `01/24/2026 8pm`, `1/24/26 8pm`, `2026-01-24` | Jan 24 2026               |
+//! | `Saturday January 24th, Doors 7PM / Show 8PM` | Jan 24, 20:00 (the show)  |
+//! | `24 January 2026, noon`                       | Jan 24 2026, 12:00        |
+//! | `Jan 24 7–10 PM`                              | Jan 24, 19:00 to 22:00    |
+//! | `Jan 24, 9pm - midnight`                      | Jan 24 21:00 to Jan 25    |
+//! | `March 16, 2026 - March 20, 2026 5 - 7 PM`    | Mar 16 17:00 to Mar 20 19:00 |
+//!
+//! - A date without a year is its next occurrence on or after the
+//!   reference date, unless it was in the `RECENT_PAST_DAYS` before it
+//!   (a calendar not yet cleared of last week's shows)
+//! - Doors and show times: the show's, or the doors' if that's all there is
+//! - A time without am/pm is an evening one ("8:00" is 8 PM), or takes its
+//!   range's ("7–10 PM"); an end before its start is the next morning
+//! - Weekday names are ignored; no date at all is
+//!   `ScraperError::DateParse`, carrying the text
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::scraper::traits::ScraperError;
+
+/// Where every source we scrape is.
+pub const TULSA_TZ: Tz = chrono_tz::America::Chicago;
+
+/// Start hour (local) `parse_event_datetime` gives an event with no time.
+pub const DEFAULT_START_HOUR: u32 = 12;
+
+/// How far before the reference date a yearless date may be and still be
+/// taken as this year's, rather than next year's.
+pub const RECENT_PAST_DAYS: i64 = 7;
+
+/// 1-12 for a lowercase month name or abbreviation ("dec", "sept",
+/// "january").
+pub fn month_number(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june",
+        "july", "august", "september", "october", "november", "december",
+    ];
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS.iter().position(|month| month.starts_with(word)).map(|i| i as u32 + 1)
+}
+
+/// A local wall-clock time in `tz` as UTC; the earlier instant when DST
+/// makes it ambiguous or skips it.
+pub fn local_to_utc(tz: Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let local = date.and_time(time);
+    tz.from_local_datetime(&local)
+        .earliest()
+        .unwrap_or_else(|| tz.from_utc_datetime(&local))
+        .with_timezone(&Utc)
+}
+
+// =============================================================================
+// EVENT DATES
+// =============================================================================
+
+/// When an event is, as its page said it (local wall-clock).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventWhen {
+    pub date: NaiveDate,
+    /// `None` if the text gave no time
+    pub start: Option<NaiveTime>,
+    /// The end, on `date` or a later day, if the text gave an end time
+    pub end: Option<(NaiveDate, NaiveTime)>,
+}
+
+impl EventWhen {
+    /// In UTC, starting at `default_start` if the text gave no time.
+    pub fn to_utc(self, tz: Tz, default_start: NaiveTime) -> (DateTime<Utc>, Option<DateTime<Utc>>) {
+        let start = local_to_utc(tz, self.date, self.start.unwrap_or(default_start));
+        (start, self.end.map(|(date, time)| local_to_utc(tz, date, time)))
+    }
+}
+
+/// Reads `text` (see the table above) into UTC start and end times; an
+/// event with no time starts at `DEFAULT_START_HOUR`. `reference` (local)
+/// picks the year for dates without one.
+///
+/// # Errors
+/// `ScraperError::DateParse` with `text` if it has no date we can read.
+pub fn parse_event_datetime(
+    text: &str,
+    reference: NaiveDate,
+    tz: Tz,
+) -> Result<(DateTime<Utc>, Option<DateTime<Utc>>), ScraperError> {
+    let default_start = NaiveTime::from_hms_opt(DEFAULT_START_HOUR, 0, 0).expect("valid hour");
+    Ok(parse_event_when(text, reference)?.to_utc(tz, default_start))
+}
+
+/// Reads `text` into local dates and times, leaving the default time to
+/// the caller.
+///
+/// # Errors
+/// `ScraperError::DateParse` with `text` if it has no date we can read.
+pub fn parse_event_when(text: &str, reference: NaiveDate) -> Result<EventWhen, ScraperError> {
+    let tokens = tokenize(text);
+    let mut used = vec![false; tokens.len()];
+    let dates = find_dates(&tokens, &mut used, reference);
+    let Some(&date) = dates.first() else {
+        return Err(ScraperError::DateParse(text.to_string()));
+    };
+
+    let Some(slot) = pick_slot(&find_slots(&tokens, &used)) else {
+        return Ok(EventWhen { date, start: None, end: None });
+    };
+    let end = slot.end.map(|end| {
+        let end_date = match dates.get(1) {
+            Some(&end_date) if end_date > date => end_date,
+            // Past midnight: "9pm - 1am", "9pm - midnight"
+            _ if end <= slot.start => date + Duration::days(1),
+            _ => date,
+        };
+        (end_date, end)
+    });
+    Ok(EventWhen { date, start: Some(slot.start), end })
+}
+
+/// A piece of date text.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number { value: u32, digits: usize, ordinal: bool },
+    /// `/`, `:` or `-` (any dash)
+    Mark(char),
+    /// Other punctuation (`,`, `|`, `·`): ends a range, nothing else
+    Break,
+}
+
+/// Lowercase words, numbers ("24th" is an ordinal 24) and punctuation.
+fn tokenize(text: &str) -> Vec<Token> {
+    let text = text.to_lowercase().replace("a.m.", "am").replace("p.m.", "pm").replace("a.m", "am").replace("p.m", "pm");
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut at = 0;
+
+    while at < chars.len() {
+        let c = chars[at];
+        let run = |at: usize, keep: fn(&char) -> bool| chars[at..].iter().take_while(|c| keep(c)).count();
+
+        if c.is_ascii_digit() {
+            let len = run(at, char::is_ascii_digit);
+            let digits: String = chars[at..at + len].iter().collect();
+            at += len;
+            let suffix_len = run(at, |c| c.is_alphabetic());
+            let suffix: String = chars[at..at + suffix_len].iter().collect();
+            let ordinal = matches!(suffix.as_str(), "st" | "nd" | "rd" | "th");
+            if ordinal {
+                at += suffix_len;
+            }
+            // Too many digits for anything we read; skip it as a number
+            let value = digits.parse().unwrap_or(u32::MAX);
+            tokens.push(Token::Number { value, digits: len, ordinal });
+        } else if c.is_alphabetic() {
+            let len = run(at, |c| c.is_alphabetic());
+            tokens.push(Token::Word(chars[at..at + len].iter().collect()));
+            at += len;
+        } else {
+            match c {
+                '/' | ':' => tokens.push(Token::Mark(c)),
+                '-' | '–' | '—' => tokens.push(Token::Mark('-')),
+                // "Jan. 24" and "Jan 24" are the same
+                '.' | '\'' => {}
+                c if c.is_whitespace() => {}
+                _ => tokens.push(Token::Break),
+            }
+            at += 1;
+        }
+    }
+    tokens
+}
+
+fn number(token: Option<&Token>) -> Option<(u32, usize)> {
+    match token? {
+        Token::Number { value, digits, .. } => Some((*value, *digits)),
+        _ => None,
+    }
+}
+
+fn word(token: Option<&Token>) -> Option<&str> {
+    match token? {
+        Token::Word(word) => Some(word.as_str()),
+        _ => None,
+    }
+}
+
+fn month_at(tokens: &[Token], at: usize) -> Option<u32> {
+    word(tokens.get(at)).and_then(month_number)
+}
+
+/// A day of the month (1-31, no more than two digits).
+fn day_at(tokens: &[Token], at: usize) -> Option<u32> {
+    number(tokens.get(at)).filter(|&(day, digits)| digits <= 2 && (1..=31).contains(&day)).map(|(day, _)| day)
+}
+
+/// A four-digit year.
+fn year_at(tokens: &[Token], at: usize) -> Option<i32> {
+    number(tokens.get(at)).filter(|&(_, digits)| digits == 4).map(|(year, _)| year as i32)
+}
+
+/// Every date in `tokens`, in order, marking their tokens used.
+fn find_dates(tokens: &[Token], used: &mut [bool], reference: NaiveDate) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut at = 0;
+
+    while at < tokens.len() {
+        let Some((date, len)) = date_at(tokens, at, reference) else {
+            at += 1;
+            continue;
+        };
+        used[at..at + len].iter_mut().for_each(|used| *used = true);
+        dates.push(date);
+        at += len;
+    }
+    dates
+}
+
+/// A date starting at `tokens[at]`, and how many tokens it takes.
+fn date_at(tokens: &[Token], at: usize, reference: NaiveDate) -> Option<(NaiveDate, usize)> {
+    let mark = |offset: usize, c: char| tokens.get(at + offset) == Some(&Token::Mark(c));
+    // A year may follow after a comma: "Jan 24, 2026"
+    let year_after = |offset: usize| match tokens.get(at + offset) {
+        Some(Token::Break) => year_at(tokens, at + offset + 1).map(|year| (year, 2)),
+        _ => year_at(tokens, at + offset).map(|year| (year, 1)),
+    };
+
+    // 2026-01-24
+    if let (Some(year), true, Some((month, _)), true, Some(day)) =
+        (year_at(tokens, at), mark(1, '-'), number(tokens.get(at + 2)), mark(3, '-'), day_at(tokens, at + 4))
+    {
+        return NaiveDate::from_ymd_opt(year, month, day).map(|date| (date, 5));
+    }
+
+    // 01/24/2026, 1/24/26, 1/24
+    if let (Some((month, 1..=2)), true, Some(day)) = (number(tokens.get(at)), mark(1, '/'), day_at(tokens, at + 2)) {
+        let year = match (mark(3, '/'), number(tokens.get(at + 4))) {
+            (true, Some((year, 4))) => Some((year as i32, 5)),
+            (true, Some((year, 2))) => Some((2000 + year as i32, 5)),
+            _ => None,
+        };
+        return match year {
+            Some((year, len)) => NaiveDate::from_ymd_opt(year, month, day).map(|date| (date, len)),
+            None => next_occurrence(month, day, reference).map(|date| (date, 3)),
+        };
+    }
+
+    // Jan 24, January 24th 2026
+    if let (Some(month), Some(day)) = (month_at(tokens, at), day_at(tokens, at + 1)) {
+        return match year_after(2) {
+            Some((year, len)) => NaiveDate::from_ymd_opt(year, month, day).map(|date| (date, 2 + len)),
+            None => next_occurrence(month, day, reference).map(|date| (date, 2)),
+        };
+    }
+
+    // 24 January 2026, 24th of January
+    if let Some(day) = day_at(tokens, at) {
+        let of = usize::from(word(tokens.get(at + 1)) == Some("of"));
+        if let Some(month) = month_at(tokens, at + 1 + of) {
+            return match year_after(2 + of) {
+                Some((year, len)) => NaiveDate::from_ymd_opt(year, month, day).map(|date| (date, 2 + of + len)),
+                None => next_occurrence(month, day, reference).map(|date| (date, 2 + of)),
+            };
+        }
+    }
+
+    None
+}
+
+/// The first `month`/`day` on or after `reference` (or up to
+/// `RECENT_PAST_DAYS` before it).
+fn next_occurrence(month: u32, day: u32, reference: NaiveDate) -> Option<NaiveDate> {
+    let earliest = reference - Duration::days(RECENT_PAST_DAYS);
+    // Four years covers Feb 29
+    (reference.year() - 1..=reference.year() + 4)
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .find(|date| *date >= earliest)
+}
+
+/// Which time a time is, by the word before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Doors,
+    Show,
+    Plain,
+}
+
+/// A time of day as written, before am/pm is settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Clock {
+    hour: u32,
+    minute: u32,
+    /// `Some(true)` for pm
+    pm: Option<bool>,
+    /// A bare number ("7" in "7–10 PM"): only a time as part of a range
+    /// or after "doors"/"show"
+    bare: bool,
+}
+
+impl Clock {
+    /// As a time, reading a missing am/pm as `pm`.
+    fn resolve(&self, pm: bool) -> Option<NaiveTime> {
+        let hour = match (self.pm.unwrap_or(pm), self.hour) {
+            (_, hour) if hour > 12 => hour,
+            (true, hour) if (1..12).contains(&hour) => hour + 12,
+            (false, 12) => 0,
+            (_, hour) => hour,
+        };
+        NaiveTime::from_hms_opt(hour % 24, self.minute, 0)
+    }
+}
+
+/// A start time and maybe an end, as found in the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Slot {
+    label: Label,
+    start: NaiveTime,
+    end: Option<NaiveTime>,
+}
+
+/// A time at `tokens[at]` not already used by a date, and its length.
+fn clock_at(tokens: &[Token], used: &[bool], at: usize) -> Option<(Clock, usize)> {
+    if used.get(at).copied().unwrap_or(true) {
+        return None;
+    }
+    let meridiem_at = |at: usize| match word(tokens.get(at)) {
+        Some("pm") => Some(true),
+        Some("am") => Some(false),
+        _ => None,
+    };
+
+    match &tokens[at] {
+        Token::Word(word) if word == "noon" => Some((Clock { hour: 12, minute: 0, pm: Some(true), bare: false }, 1)),
+        Token::Word(word) if word == "midnight" => Some((Clock { hour: 12, minute: 0, pm: Some(false), bare: false }, 1)),
+        Token::Number { value: hour, digits: 1..=2, ordinal: false } if *hour <= 24 => {
+            let (minute, len) = match (tokens.get(at + 1), number(tokens.get(at + 2))) {
+                (Some(Token::Mark(':')), Some((minute, 2))) if minute < 60 && !used[at + 2] => (Some(minute), 3),
+                _ => (None, 1),
+            };
+            let pm = meridiem_at(at + len);
+            let clock = Clock { hour: *hour, minute: minute.unwrap_or(0), pm, bare: minute.is_none() && pm.is_none() };
+            Some((clock, len + usize::from(pm.is_some())))
+        }
+        _ => None,
+    }
+}
+
+/// Every time or range of times in the unused tokens, in order.
+fn find_slots(tokens: &[Token], used: &[bool]) -> Vec<Slot> {
+    let mut slots = Vec::new();
+    let mut label = Label::Plain;
+    let mut at = 0;
+
+    while at < tokens.len() {
+        let Some((start, len)) = clock_at(tokens, used, at) else {
+            match word(tokens.get(at)) {
+                Some("doors" | "door") => label = Label::Doors,
+                Some("show" | "shows" | "showtime") => label = Label::Show,
+                _ => {}
+            }
+            at += 1;
+            continue;
+        };
+        at += len;
+
+        // "7 - 10 pm", "7pm to 10pm", "9pm until midnight"
+        let joined = matches!(tokens.get(at), Some(Token::Mark('-')))
+            || matches!(word(tokens.get(at)), Some("to" | "until" | "till" | "til"));
+        let end = if joined { clock_at(tokens, used, at + 1) } else { None };
+        // Two bare numbers ("ages 18 - 21") aren't times
+        let end = end.filter(|(end, _)| !(start.bare && end.bare));
+        if let Some((_, end_len)) = end {
+            at += 1 + end_len;
+        }
+
+        if start.bare && end.is_none() && label == Label::Plain {
+            continue;
+        }
+        if let Some(slot) = settle(start, end.map(|(end, _)| end), label) {
+            slots.push(slot);
+        }
+        label = Label::Plain;
+    }
+    slots
+}
+
+/// Fills in am/pm: a start without one takes its end's (earlier if that
+/// would put it after the end), an end without one its start's (later
+/// if that would put it before), and a time alone is an evening one.
+fn settle(start: Clock, end: Option<Clock>, label: Label) -> Option<Slot> {
+    let Some(end) = end else {
+        return Some(Slot { label, start: start.resolve(true)?, end: None });
+    };
+
+    let (start, end) = match (start.pm, end.pm) {
+        (None, Some(pm)) => {
+            let end = end.resolve(pm)?;
+            let start = start.resolve(pm).filter(|start| *start <= end).or_else(|| start.resolve(!pm))?;
+            (start, end)
+        }
+        (Some(pm), None) => {
+            let start = start.resolve(pm)?;
+            let end = end.resolve(pm).filter(|end| *end >= start).or_else(|| end.resolve(!pm))?;
+            (start, end)
+        }
+        _ => (start.resolve(true)?, end.resolve(true)?),
+    };
+    Some(Slot { label, start, end: Some(end) })
+}
+
+/// The show's time if there is one, else the first that isn't doors,
+/// else the doors'.
+fn pick_slot(slots: &[Slot]) -> Option<Slot> {
+    let find = |label| slots.iter().find(|slot| slot.label == label);
+    find(Label::Show).or_else(|| find(Label::Plain)).or_else(|| find(Label::Doors)).copied()
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_clock_times() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        let cases = [
+            ("8:00 PM", time(20, 0)),
+            ("8pm", time(20, 0)),
+            ("8:00", time(20, 0)),
+            ("Doors 11:30 a.m.", time(11, 30)),
+            ("12 pm", time(12, 0)),
+            ("12:30 AM", time(0, 30)),
+            ("All ages, 21+ to drink", None),
+        ];
+        for (text, expected) in cases {
+            let when = parse_event_when(&format!("Jan 24 {}", text), NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+            assert_eq!(when.unwrap().start, expected, "{}", text);
+        }
+        assert_eq!(month_number("sept"), Some(9));
+        assert_eq!(month_number("ma"), None);
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn reads_the_date_formats_venues_use() {
+        // Read on Jan 10, 2026
+        let reference = date(2026, 1, 10);
+        let jan_24 = date(2026, 1, 24);
+        let cases = [
+            ("Fri, Jan 24 · 8:00 PM", jan_24, Some(at(20, 0))),
+            ("01/24/2026 8pm", jan_24, Some(at(20, 0))),
+            ("1/24/26 8 p.m.", jan_24, Some(at(20, 0))),
+            ("1/24 @ 7:30pm", jan_24, Some(at(19, 30))),
+            ("2026-01-24 20:00", jan_24, Some(at(20, 0))),
+            ("Saturday January 24th, Doors 7PM / Show 8PM", jan_24, Some(at(20, 0))),
+            ("Saturday, January 24th 2026", jan_24, None),
+            ("Jan. 24, 2026 | 9:30 PM", jan_24, Some(at(21, 30))),
+            ("24 January 2026, noon", jan_24, Some(at(12, 0))),
+            ("Saturday 24th of January", jan_24, None),
+            ("SAT JAN 24 8PM", jan_24, Some(at(20, 0))),
+            ("Sept 5 - 11:30 AM", date(2026, 9, 5), Some(at(11, 30))),
+            ("December 31 at midnight", date(2026, 12, 31), Some(at(0, 0))),
+            ("Jan 24 doors at 7", jan_24, Some(at(19, 0))),
+        ];
+        for (text, expected_date, expected_start) in cases {
+            let when = parse_event_when(text, reference).unwrap_or_else(|e| panic!("{}: {}", text, e));
+            assert_eq!((when.date, when.start), (expected_date, expected_start), "{}", text);
+        }
+    }
+
+    #[test]
+    fn yearless_dates_are_the_next_one() {
+        let cases = [
+            // December page in late December: January shows are next year
+            ("Fri, Jan 9", date(2026, 12, 28), date(2027, 1, 9)),
+            // Same page still up in early January: December shows just happened
+            ("Wed, Dec 30", date(2027, 1, 3), date(2026, 12, 30)),
+            // ...but not months ago
+            ("Oct 1", date(2027, 1, 3), date(2027, 10, 1)),
+            ("3/1", date(2026, 3, 1), date(2026, 3, 1)),
+            ("Feb 29", date(2026, 3, 1), date(2028, 2, 29)),
+        ];
+        for (text, reference, expected) in cases {
+            assert_eq!(parse_event_when(text, reference).unwrap().date, expected, "{} on {}", text, reference);
+        }
+    }
+
+    #[test]
+    fn reads_ranges_and_doors() {
+        let reference = date(2026, 1, 10);
+        let jan_24 = date(2026, 1, 24);
+        let cases = [
+            ("Jan 24 7–10 PM", at(19, 0), Some((jan_24, at(22, 0)))),
+            ("Jan 24, 7pm to 10pm", at(19, 0), Some((jan_24, at(22, 0)))),
+            ("Jan 24 11 - 2 PM", at(11, 0), Some((jan_24, at(14, 0)))),
+            ("Jan 24 11am - 2", at(11, 0), Some((jan_24, at(14, 0)))),
+            ("Jan 24 5:00 PM - 7:00 PM", at(17, 0), Some((jan_24, at(19, 0)))),
+            ("Jan 24, 9pm - midnight", at(21, 0), Some((date(2026, 1, 25), at(0, 0)))),
+            ("Jan 24 10pm - 2am", at(22, 0), Some((date(2026, 1, 25), at(2, 0)))),
+            ("Jan 24 Doors 6:30 | Show 7:30 - 11", at(19, 30), Some((jan_24, at(23, 0)))),
+            ("Jan 24 Doors 7PM", at(19, 0), None),
+            ("March 16, 2026 - March 20, 2026 5 - 7 PM", at(17, 0), Some((date(2026, 3, 20), at(19, 0)))),
+        ];
+        for (text, start, end) in cases {
+            let when = parse_event_when(text, reference).unwrap_or_else(|e| panic!("{}: {}", text, e));
+            assert_eq!((when.start, when.end), (Some(start), end), "{}", text);
+        }
+
+        // A second date without an end time isn't an end
+        let festival = parse_event_when("March 16, 2026 - March 20, 2026 All Day", reference).unwrap();
+        assert_eq!((festival.date, festival.start, festival.end), (date(2026, 3, 16), None, None));
+    }
+
+    #[test]
+    fn numbers_that_arent_times_are_ignored() {
+        let reference = date(2026, 1, 10);
+        for text in ["Jan 24 · All ages, 21+ to drink", "Jan 24 (ages 18 - 21)", "Jan 24 $35 - $55"] {
+            assert_eq!(parse_event_when(text, reference).unwrap().start, None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn no_date_is_a_date_parse_error_with_the_text() {
+        let reference = date(2026, 1, 10);
+        for text in ["Date TBD", "8:00 PM", "Feb 30", "13/45/2026", ""] {
+            match parse_event_datetime(text, reference, TULSA_TZ) {
+                Err(ScraperError::DateParse(original)) => assert_eq!(original, text),
+                other => panic!("{}: {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn converts_to_utc_with_the_default_start() {
+        let reference = date(2026, 1, 10);
+        let utc = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            parse_event_datetime("Jan 24 7-10 PM", reference, TULSA_TZ).unwrap(),
+            (utc("2026-01-25T01:00:00Z"), Some(utc("2026-01-25T04:00:00Z")))
+        );
+        // No time: DEFAULT_START_HOUR, in summer time
+        assert_eq!(parse_event_datetime("July 4th", reference, TULSA_TZ).unwrap(), (utc("2026-07-04T17:00:00Z"), None));
+    }
+}
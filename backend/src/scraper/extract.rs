@@ -0,0 +1,415 @@
+//! # One-Off Pages
+//!
+//! Reads a single event from any page, for links people send us
+//! (`POST /api/admin/scrape/url`): no source row, no site-specific
+//! selectors.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Extractors
+//! Three passes, in order; each fills only the fields the ones before it
+//! left empty:
+//! 1. **JSON-LD**: a schema.org `Event` on the page (`platforms::jsonld`),
+//!    the one at this URL if there are several
+//! 2. **Open Graph and meta tags**: `og:title`, `og:description`,
+//!    `og:image`, `event:start_time` / `event:end_time`, microdata
+//!    `startDate` / `endDate` (`platforms::opengraph`)
+//! 3. **Heuristics**: the first `<h1>` (else the `<title>`) as the title,
+//!    and the first `<time datetime>`, or date-looking element, as the start
+//!
+//! An event needs a title and a start; the pass that supplied the last of
+//! them is reported as the `extractor`. A page none of them can complete
+//! is reported with what was found and what's `missing`, for an admin to
+//! finish by hand.
+//!
+//! ## Fetching
+//! The page is fetched through the registry's `FetchPool`, like any
+//! scraper request: robots.txt, the per-host delay and the retries apply.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::error::AppError;
+use crate::scraper::dates::{self, TULSA_TZ};
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::persist;
+use crate::scraper::platforms::jsonld::{self, JsonLdPage};
+use crate::scraper::platforms::opengraph::{self, OpenGraph};
+use crate::scraper::quarantine::RetryResult;
+use crate::scraper::traits::{ScrapedEvent, ScraperError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Elements whose text may be the event's date, for the heuristic pass.
+const DATE_SELECTOR: &str = r#"time, [class*="date"], [class*="when"]"#;
+
+/// Longer text than this isn't a date line but a container of them.
+const MAX_DATE_TEXT_CHARS: usize = 200;
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// The pass that completed a page's event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Extractor {
+    JsonLd,
+    OpenGraph,
+    Heuristic,
+}
+
+/// A `ScrapedEvent` that may lack its required fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PageEvent {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub venue: Option<String>,
+    pub venue_address: Option<String>,
+    pub source_url: String,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub price_min: Option<f64>,
+    pub price_max: Option<f64>,
+    pub is_free: Option<bool>,
+    pub image_url: Option<String>,
+}
+
+/// What was read from a page, and (with `persist`) what storing it did.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "extractor": "open_graph",
+///   "source_name": "Gathering Place",
+///   "event": { "title": "Lantern Walk", "start_time": "2026-12-06T00:00:00Z",
+///              "source_url": "https://www.gatheringplace.org/events/lantern-walk", ... },
+///   "missing": [],
+///   "saved": { "event_id": "550e8400-e29b-41d4-a716-446655440000", "outcome": "created" }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PageScrape {
+    /// `None` if no pass completed the event
+    pub extractor: Option<Extractor>,
+    /// Credited as the event's source: `og:site_name`, else the host
+    pub source_name: String,
+    pub event: PageEvent,
+    /// Required fields no pass found (`title`, `start_time`)
+    pub missing: Vec<&'static str>,
+    pub saved: Option<RetryResult>,
+}
+
+impl PageEvent {
+    /// The required fields still empty.
+    fn missing(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.title.is_none() {
+            missing.push("title");
+        }
+        if self.start_time.is_none() {
+            missing.push("start_time");
+        }
+        missing
+    }
+
+    /// As a `ScrapedEvent`, if it has a title and a start.
+    pub fn to_scraped(&self) -> Option<ScrapedEvent> {
+        Some(ScrapedEvent {
+            description: self.description.clone(),
+            venue: self.venue.clone(),
+            venue_address: self.venue_address.clone(),
+            end_time: self.end_time,
+            tags: self.tags.clone(),
+            price_min: self.price_min,
+            price_max: self.price_max,
+            is_free: self.is_free,
+            image_url: self.image_url.clone(),
+            ..ScrapedEvent::new(self.title.as_deref()?, &self.source_url, self.start_time?)
+        })
+    }
+}
+
+impl From<ScrapedEvent> for PageEvent {
+    fn from(event: ScrapedEvent) -> Self {
+        Self {
+            title: Some(event.title),
+            description: event.description,
+            venue: event.venue,
+            venue_address: event.venue_address,
+            source_url: event.source_url,
+            start_time: Some(event.start_time),
+            end_time: event.end_time,
+            tags: event.tags,
+            price_min: event.price_min,
+            price_max: event.price_max,
+            is_free: event.is_free,
+            image_url: event.image_url,
+        }
+    }
+}
+
+// =============================================================================
+// SCRAPING
+// =============================================================================
+
+/// Fetches `url` and reads its event (see "Extractors" above).
+///
+/// # Errors
+/// - `ScraperError::Validation` if `url` isn't an http(s) URL
+/// - `ScraperError::Disallowed` if robots.txt doesn't let us fetch it
+/// - `ScraperError::Http` if the fetch fails
+pub async fn scrape_url(fetcher: &Fetcher, url: &str) -> Result<PageScrape, ScraperError> {
+    let url = url.trim();
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some() => {}
+        _ => return Err(ScraperError::Validation(format!("not an http(s) URL: {}", url))),
+    }
+    let html = fetcher.get_text(url).await?;
+    let today = Utc::now().with_timezone(&TULSA_TZ).date_naive();
+    Ok(extract(&html, url, today))
+}
+
+/// Validates and stores a completed page's event, credited to its
+/// `source_name`, and notes what happened in `saved`.
+///
+/// # Errors
+/// - `AppError::Validation` if the event is incomplete or fails
+///   `CreateEvent::validate`
+/// - `AppError::Database`
+pub async fn store(pool: &PgPool, page: &mut PageScrape) -> Result<(), AppError> {
+    let Some(event) = page.event.to_scraped() else {
+        return Err(AppError::invalid(page.missing.first().copied().unwrap_or("title"), "not found on the page"));
+    };
+    let now = Utc::now();
+    let event = event.into_create_event(&page.source_name);
+    event.validate(now)?;
+    let saved = persist::save(pool, event, None, now, false).await?;
+    page.saved = Some(RetryResult::from(&saved));
+    Ok(())
+}
+
+// =============================================================================
+// EXTRACTION
+// =============================================================================
+
+/// Reads the event on the page at `url`; `today` (Tulsa) picks the year of
+/// a date without one.
+pub fn extract(html: &str, url: &str, today: NaiveDate) -> PageScrape {
+    let document = Html::parse_document(html);
+    let meta = opengraph::read(&document, url);
+
+    let mut event = from_json_ld(html, url).unwrap_or_else(|| PageEvent { source_url: url.to_string(), ..Default::default() });
+    let mut extractor = event.missing().is_empty().then_some(Extractor::JsonLd);
+
+    fill_from_meta(&mut event, &meta);
+    if extractor.is_none() && event.missing().is_empty() {
+        extractor = Some(Extractor::OpenGraph);
+    }
+
+    fill_from_heuristics(&mut event, &document, today);
+    if extractor.is_none() && event.missing().is_empty() {
+        extractor = Some(Extractor::Heuristic);
+    }
+
+    let source_name = meta
+        .site_name
+        .or_else(|| Url::parse(url).ok()?.host_str().map(|host| host.trim_start_matches("www.").to_string()))
+        .unwrap_or_else(|| url.to_string());
+    PageScrape { extractor, source_name, missing: event.missing(), event, saved: None }
+}
+
+/// The page's JSON-LD event: the one at `url`, else the first. One with no
+/// `url` of its own is at the page's.
+fn from_json_ld(html: &str, url: &str) -> Option<PageEvent> {
+    let page = JsonLdPage {
+        source_id: String::new(),
+        name: String::new(),
+        url: url.to_string(),
+        default_category: None,
+        default_venue: None,
+    };
+    let mut events = jsonld::parse_json_ld(html, &page).ok()?;
+    let at = events.iter().position(|event| same_page(&event.source_url, url)).unwrap_or(0);
+    let mut event = events.drain(..).nth(at)?;
+    if event.source_url.starts_with(&format!("{}#", url)) {
+        event.source_url = url.to_string();
+    }
+    Some(event.into())
+}
+
+fn fill_from_meta(event: &mut PageEvent, meta: &OpenGraph) {
+    event.title = event.title.take().or_else(|| meta.title.clone());
+    event.description = event.description.take().or_else(|| meta.description.clone());
+    event.image_url = event.image_url.take().or_else(|| meta.image_url.clone());
+    event.start_time = event.start_time.or(meta.start_time);
+    event.end_time = event.end_time.or(meta.end_time);
+}
+
+fn fill_from_heuristics(event: &mut PageEvent, document: &Html, today: NaiveDate) {
+    if event.title.is_none() {
+        let h1 = Selector::parse("h1").expect("valid selector");
+        let title = Selector::parse("title").expect("valid selector");
+        event.title = document
+            .select(&h1)
+            .map(text_of)
+            .find(|text| !text.is_empty())
+            .or_else(|| {
+                // "Lantern Walk | Gathering Place": the site's name goes
+                let text = text_of(document.select(&title).next()?);
+                let name = text.split(" | ").next().unwrap_or_default().trim().to_string();
+                (!name.is_empty()).then_some(name)
+            });
+    }
+
+    if event.start_time.is_none() {
+        let candidates = Selector::parse(DATE_SELECTOR).expect("valid selector");
+        let found = document.select(&candidates).find_map(|element| {
+            if let Some(start) = element.value().attr("datetime").and_then(jsonld::read_date) {
+                return Some((start.parse().ok()?, None));
+            }
+            let text = text_of(element);
+            if text.is_empty() || text.chars().count() > MAX_DATE_TEXT_CHARS {
+                return None;
+            }
+            dates::parse_event_datetime(&text, today, TULSA_TZ).ok()
+        });
+        if let Some((start, end)) = found {
+            event.start_time = Some(start);
+            event.end_time = event.end_time.or(end);
+        }
+    }
+}
+
+/// An element's text, whitespace collapsed.
+fn text_of(element: ElementRef) -> String {
+    element.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether two URLs are the same page, give or take a trailing slash.
+fn same_page(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const JSON_LD: &str = include_str!("../../tests/fixtures/pages/jsonld-event.html");
+    const OG_ONLY: &str = include_str!("../../tests/fixtures/pages/og-only.html");
+    const HOPELESS: &str = include_str!("../../tests/fixtures/pages/hopeless.html");
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn json_ld_wins_over_the_meta_tags() {
+        let url = "https://thecolonytulsa.com/events/tiny-desk-night";
+        let page = extract(JSON_LD, url, today());
+        assert_eq!(page.extractor, Some(Extractor::JsonLd));
+        assert!(page.missing.is_empty());
+        assert_eq!(page.source_name, "The Colony");
+
+        let event = page.event.to_scraped().unwrap();
+        assert_eq!(event.title, "Tulsa Tiny Desk Night");
+        assert_eq!(event.source_url, url);
+        assert_eq!((event.start_time, event.end_time), (utc("2026-11-13T01:30:00Z"), Some(utc("2026-11-13T04:00:00Z"))));
+        assert_eq!(event.venue.as_deref(), Some("The Colony"));
+        assert_eq!(event.venue_address.as_deref(), Some("2809 S Harvard Ave, Tulsa, OK"));
+        assert_eq!((event.price_min, event.price_max), (Some(10.0), Some(10.0)));
+        assert_eq!(event.image_url.as_deref(), Some("https://thecolonytulsa.com/img/tiny-desk.jpg"));
+    }
+
+    #[test]
+    fn open_graph_tags_make_an_event_without_json_ld() {
+        let url = "https://www.gatheringplace.org/events/lantern-walk";
+        let page = extract(OG_ONLY, url, today());
+        assert_eq!(page.extractor, Some(Extractor::OpenGraph));
+        assert_eq!(page.source_name, "Gathering Place");
+
+        let event = page.event.to_scraped().unwrap();
+        // og:title beats the <h1>, og:description the plain description
+        assert_eq!(event.title, "Lantern Walk");
+        assert!(event.description.unwrap().starts_with("Bring a lantern"));
+        assert_eq!((event.start_time, event.end_time), (utc("2026-12-06T00:00:00Z"), Some(utc("2026-12-06T02:00:00Z"))));
+        assert_eq!(event.image_url.as_deref(), Some("https://www.gatheringplace.org/media/lantern-walk.jpg"));
+        assert_eq!(event.venue, None);
+    }
+
+    #[test]
+    fn headings_and_date_lines_are_the_last_resort() {
+        let html = r#"<html><head><title>Chili Cook-Off | Tulsa Fire Dept.</title></head><body>
+            <div class="event-listing">
+              <h1>  Fire Station Chili
+                    Cook-Off </h1>
+              <p class="event-date">Saturday, Nov 7 &middot; 11am - 2pm</p>
+            </div></body></html>"#;
+        let page = extract(html, "https://fire.example/chili", today());
+        assert_eq!(page.extractor, Some(Extractor::Heuristic));
+        assert_eq!(page.source_name, "fire.example");
+        assert_eq!(page.event.title.as_deref(), Some("Fire Station Chili Cook-Off"));
+        assert_eq!(page.event.start_time, Some(utc("2026-11-07T17:00:00Z")));
+        assert_eq!(page.event.end_time, Some(utc("2026-11-07T20:00:00Z")));
+    }
+
+    #[test]
+    fn a_hopeless_page_reports_what_it_found() {
+        let page = extract(HOPELESS, "https://tulsamakers.example/flea", today());
+        assert_eq!(page.extractor, None);
+        assert_eq!(page.missing, ["start_time"]);
+        assert_eq!(page.event.title.as_deref(), Some("Pop-Up Flea Market"));
+        assert_eq!(page.event.description.as_deref(), Some("Vintage, handmade and oddities from forty local vendors."));
+        assert_eq!(page.event.to_scraped(), None);
+
+        let empty = extract("<html><body></body></html>", "https://blank.example/", today());
+        assert_eq!(empty.missing, ["title", "start_time"]);
+    }
+
+    #[tokio::test]
+    async fn pages_are_fetched_politely() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /members\n"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/events/lantern-walk"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(OG_ONLY))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/members/lantern-walk"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(OG_ONLY))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let fetcher = Fetcher::for_tests();
+        let url = format!("{}/events/lantern-walk", server.uri());
+        let page = scrape_url(&fetcher, &url).await.unwrap();
+        assert_eq!((page.extractor, page.event.source_url.as_str()), (Some(Extractor::OpenGraph), url.as_str()));
+
+        let members = format!("{}/members/lantern-walk", server.uri());
+        assert!(matches!(scrape_url(&fetcher, &members).await, Err(ScraperError::Disallowed(_))));
+        assert!(matches!(scrape_url(&fetcher, "ftp://files.example/event").await, Err(ScraperError::Validation(_))));
+    }
+}
@@ -0,0 +1,1044 @@
+//! # Polite Fetching
+//!
+//! The one way scrapers make HTTP requests. Before every GET the fetcher
+//! checks the site's robots.txt (`robots.rs`) and waits out the gap since
+//! the last request to that host.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Shared Limits (`FetchPool`)
+//! The registry owns one pool; the HTTP client lives inside it, so a
+//! scraper can't make a request any other way. Across every run:
+//! - Requests to one host are at least `request_delay` apart, or the
+//!   site's `Crawl-delay` if longer
+//! - At most `max_concurrent` requests are in flight at once
+//! - Each request has `timeout` to finish, and says who we are
+//!   (`USER_AGENT`, with a contact URL)
+//!
+//! ## Per Run (`Fetcher`)
+//! The registry makes a fetcher from the pool for each scraper run.
+//! Within the run:
+//! - A URL robots.txt disallows isn't fetched: `get_text` returns
+//!   `ScraperError::Disallowed` and the URL is counted (`disallowed`)
+//! - A host whose robots.txt couldn't be read is closed for the run
+//! - A request that fails for a moment (timeout, dropped connection, 429,
+//!   502 or 503) is retried up to `MAX_RETRIES` times, waiting
+//!   `retry_backoff` and doubling, or the site's `Retry-After` if longer.
+//!   Anything else (404, 410, ...) fails at once.
+//! - A URL that still fails is recorded (`failed_urls`) for the run's
+//!   summary, so a scraper can skip that page and go on
+//!
+//! ## APIs
+//! `post_json` sends a JSON body with a bearer token, for platforms with
+//! an API (Meetup's GraphQL). It shares the spacing, the concurrency cap
+//! and the retries, but skips robots.txt, which is for crawlers.
+//!
+//! ## Unchanged Pages
+//! `get_events` fetches a page and parses it into events, conditionally:
+//! with a fetch cache (`with_cache`), a page that hasn't changed since the
+//! last run (`304`, or the same bytes) isn't parsed, and its events are
+//! marked seen instead (see `cache.rs`). The run counts pages fetched and
+//! pages skipped as unchanged.
+//!
+//! A crawler that knows when a page last changed (a sitemap's `lastmod`)
+//! asks `unchanged_since` first, and skips the request too. Pages it leaves
+//! for a later run are `defer`red: their events are still seen.
+//!
+//! ## Charsets
+//! Bodies are decoded in the charset the `Content-Type` header names,
+//! else the one a `<meta charset>` (or `http-equiv`) tag near the top
+//! names, else UTF-8; a byte-order mark beats both. Plenty of older venue
+//! sites are windows-1252 and only say so in the page.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::header::{HeaderMap, HeaderName, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::Instant;
+
+use crate::scraper::cache::{self, CachedPage};
+use crate::scraper::robots::{RobotsChecker, RobotsRules};
+use crate::scraper::traits::{ScrapedEvent, ScraperError};
+use crate::services::scheduler::env_u64;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Sent with every scraper request, so site owners can see who we are and
+/// how to reach us. Starts with `robots::ROBOTS_AGENT`.
+pub const USER_AGENT: &str = concat!(
+    "Locate918/",
+    env!("CARGO_PKG_VERSION"),
+    " (event listings for Tulsa; +https://github.com/BentNail86/locate918)"
+);
+
+/// Least time between two requests to one host, unless configured
+/// (`SCRAPE_REQUEST_DELAY_MS`).
+pub const DEFAULT_REQUEST_DELAY: Duration = Duration::from_secs(2);
+
+/// Most scraper requests in flight at once, unless configured
+/// (`SCRAPE_MAX_CONCURRENT_REQUESTS`).
+pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+
+/// How long one request may take, unless configured
+/// (`SCRAPE_REQUEST_TIMEOUT_SECS`).
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Times a failing request is retried before giving up.
+pub const MAX_RETRIES: u32 = 3;
+
+/// Wait before the first retry; each one after waits twice as long.
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The longest wait before a retry. A site whose `Retry-After` asks for
+/// more isn't retried this run.
+pub const MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// How scraper requests are limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchConfig {
+    pub request_delay: Duration,
+    pub max_concurrent: usize,
+    pub timeout: Duration,
+    pub retry_backoff: Duration,
+}
+
+impl FetchConfig {
+    /// `SCRAPE_REQUEST_DELAY_MS`, `SCRAPE_MAX_CONCURRENT_REQUESTS` and
+    /// `SCRAPE_REQUEST_TIMEOUT_SECS`, or the defaults.
+    pub fn from_env() -> Self {
+        Self {
+            request_delay: Duration::from_millis(env_u64("SCRAPE_REQUEST_DELAY_MS", DEFAULT_REQUEST_DELAY.as_millis() as u64)),
+            max_concurrent: env_u64("SCRAPE_MAX_CONCURRENT_REQUESTS", DEFAULT_MAX_CONCURRENT as u64) as usize,
+            timeout: Duration::from_secs(env_u64("SCRAPE_REQUEST_TIMEOUT_SECS", DEFAULT_REQUEST_TIMEOUT.as_secs())),
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+        }
+    }
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            request_delay: DEFAULT_REQUEST_DELAY,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+        }
+    }
+}
+
+// =============================================================================
+// POOL
+// =============================================================================
+
+/// What every scraper run shares: the client, the robots.txt cache, when
+/// each host may next be asked, and the cap on requests in flight.
+pub struct FetchPool {
+    client: Client,
+    robots: RobotsChecker,
+    request_delay: Duration,
+    retry_backoff: Duration,
+    /// When the next request to each origin may go out
+    next_at: Mutex<HashMap<String, Instant>>,
+    permits: Semaphore,
+}
+
+impl FetchPool {
+    /// A pool with its own client (our User-Agent and `config.timeout`).
+    ///
+    /// # Errors
+    /// If the HTTP client can't be built (no TLS backend).
+    pub fn new(config: FetchConfig) -> Result<Self, reqwest::Error> {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(config.timeout)
+            .build()?;
+        Ok(Self {
+            robots: RobotsChecker::new(client.clone()),
+            client,
+            request_delay: config.request_delay,
+            retry_backoff: config.retry_backoff,
+            next_at: Mutex::new(HashMap::new()),
+            permits: Semaphore::new(config.max_concurrent.max(1)),
+        })
+    }
+
+    /// A pool with no delay between requests or retries, for tests.
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        Self::new(FetchConfig {
+            request_delay: Duration::ZERO,
+            retry_backoff: Duration::ZERO,
+            ..FetchConfig::default()
+        })
+        .expect("client builds")
+    }
+
+    /// Books the next slot for `origin` and returns how long to wait for it.
+    async fn reserve(&self, origin: &str, crawl_delay: Option<Duration>) -> Duration {
+        let mut next_at = self.next_at.lock().await;
+        let now = Instant::now();
+        let at = next_at.get(origin).map_or(now, |next| (*next).max(now));
+        next_at.insert(origin.to_string(), at + gap(self.request_delay, crawl_delay));
+        at - now
+    }
+}
+
+// =============================================================================
+// FETCHER
+// =============================================================================
+
+/// A URL a scraper run couldn't fetch, even after retrying.
+///
+/// # Example JSON
+/// ```json
+/// { "url": "https://www.cainsballroom.com/events/?page=2", "error": "HTTP error: ... 503 Service Unavailable" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedUrl {
+    pub url: String,
+    pub error: String,
+}
+
+/// What a GET came back with.
+enum Fetched {
+    /// A 2xx response; `body` is empty for a HEAD
+    Body { body: Vec<u8>, etag: Option<String>, last_modified: Option<String>, content_type: Option<String> },
+    /// `304 Not Modified` to a conditional request
+    NotModified,
+}
+
+/// Why one attempt at a request failed.
+enum Failure {
+    /// Worth another try, after at least `retry_after` if the site said
+    Transient { error: ScraperError, retry_after: Option<Duration> },
+    Permanent(ScraperError),
+}
+
+/// What to send.
+enum Request<'a> {
+    /// A GET, conditional on the cached page's validators if given
+    Get(Option<&'a CachedPage>),
+    /// A POST of a JSON body, with an API token
+    PostJson { token: &'a str, body: &'a Value },
+    /// A HEAD (a GET whose body isn't read, where HEAD isn't allowed)
+    Head,
+}
+
+/// A scraper run's HTTP access: robots.txt-checked and rate-limited.
+pub struct Fetcher {
+    pool: Arc<FetchPool>,
+    /// The robots.txt rules for each origin, as of this run
+    rules: Mutex<HashMap<String, Arc<RobotsRules>>>,
+    disallowed: AtomicUsize,
+    failed: std::sync::Mutex<Vec<FailedUrl>>,
+    /// Where `get_events` looks pages up, if anywhere
+    cache: Option<PgPool>,
+    pages_fetched: AtomicUsize,
+    pages_unchanged: AtomicUsize,
+    /// Pages parsed this run, for `commit_cache`
+    parsed: std::sync::Mutex<Vec<CachedPage>>,
+    /// The events of the pages skipped as unchanged
+    unchanged_events: std::sync::Mutex<Vec<String>>,
+}
+
+impl Fetcher {
+    pub fn new(pool: Arc<FetchPool>) -> Self {
+        Self {
+            pool,
+            rules: Mutex::new(HashMap::new()),
+            disallowed: AtomicUsize::new(0),
+            failed: std::sync::Mutex::new(Vec::new()),
+            cache: None,
+            pages_fetched: AtomicUsize::new(0),
+            pages_unchanged: AtomicUsize::new(0),
+            parsed: std::sync::Mutex::new(Vec::new()),
+            unchanged_events: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Looks pages up in the fetch cache, so `get_events` can skip the
+    /// unchanged ones. Nothing is written to it until `commit_cache`.
+    pub fn with_cache(self, db: PgPool) -> Self {
+        Self { cache: Some(db), ..self }
+    }
+
+    /// A fetcher on its own `FetchPool::for_tests`.
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        Self::new(Arc::new(FetchPool::for_tests()))
+    }
+
+    /// GETs `url` and returns the body of a 2xx response, retrying
+    /// transient failures.
+    ///
+    /// # Errors
+    /// - `ScraperError::Disallowed` if robots.txt doesn't let us (nothing
+    ///   is sent)
+    /// - `ScraperError::Http` for a failed request, a timeout or an error
+    ///   status, once retrying has given up (the URL is in `failed_urls`)
+    pub async fn get_text(&self, url: &str) -> Result<String, ScraperError> {
+        match self.get(url, None).await? {
+            Fetched::Body { body, content_type, .. } => {
+                self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+                Ok(decode(&body, content_type.as_deref()))
+            }
+            Fetched::NotModified => unreachable!("only conditional requests come back 304"),
+        }
+    }
+
+    /// `get_text`, but the body as sent, undecoded: for files that aren't
+    /// text (a gzipped sitemap).
+    ///
+    /// # Errors
+    /// As `get_text`.
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, ScraperError> {
+        match self.get(url, None).await? {
+            Fetched::Body { body, .. } => {
+                self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+                Ok(body)
+            }
+            Fetched::NotModified => unreachable!("only conditional requests come back 304"),
+        }
+    }
+
+    /// POSTs `body` as JSON to an API that wants a bearer `token` (a
+    /// platform's GraphQL endpoint, say) and returns the body of a 2xx
+    /// response. Spaced out and retried like a GET, but not checked
+    /// against robots.txt: an API we hold a token for isn't crawled.
+    ///
+    /// # Errors
+    /// `ScraperError::Http`, as for `get_text`.
+    pub async fn post_json(&self, url: &str, token: &str, body: &Value) -> Result<String, ScraperError> {
+        let parsed = Url::parse(url).map_err(|e| ScraperError::Validation(format!("URL {}: {}", url, e)))?;
+        match self.send(url, &parsed, Request::PostJson { token, body }, None).await? {
+            Fetched::Body { body, content_type, .. } => {
+                self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+                Ok(decode(&body, content_type.as_deref()))
+            }
+            Fetched::NotModified => unreachable!("only conditional requests come back 304"),
+        }
+    }
+
+    /// GETs `url` and parses it into events with `parse`, unless it hasn't
+    /// changed since the last run: then its events are remembered as seen
+    /// and none are returned.
+    ///
+    /// # Errors
+    /// As `get_text`, or `parse`'s error.
+    pub async fn get_events<F>(&self, url: &str, parse: F) -> Result<Vec<ScrapedEvent>, ScraperError>
+    where
+        F: FnOnce(&str) -> Result<Vec<ScrapedEvent>, ScraperError>,
+    {
+        let cached = match &self.cache {
+            Some(db) => cache::lookup(db, url).await.unwrap_or_else(|e| {
+                tracing::warn!(url = %url, error = %e, "couldn't read the fetch cache");
+                None
+            }),
+            None => None,
+        };
+
+        match self.get(url, cached.as_ref()).await? {
+            Fetched::NotModified => Ok(self.unchanged(url, cached.expect("only cached pages are asked for conditionally"))),
+            Fetched::Body { body, etag, last_modified, content_type } => {
+                let text = decode(&body, content_type.as_deref());
+                let body_hash = cache::body_hash(&text);
+                if let Some(cached) = cached.filter(|cached| cached.body_hash == body_hash) {
+                    return Ok(self.unchanged(url, cached));
+                }
+                self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+                let events = parse(&text)?;
+                if self.cache.is_some() {
+                    self.parsed.lock().expect("parsed pages lock").push(CachedPage {
+                        url: url.to_string(),
+                        etag,
+                        last_modified,
+                        body_hash,
+                        event_urls: events.iter().map(|event| event.source_url.trim().to_string()).collect(),
+                    });
+                }
+                Ok(events)
+            }
+        }
+    }
+
+    /// Counts `cached`'s page as unchanged and remembers its events.
+    fn unchanged(&self, url: &str, cached: CachedPage) -> Vec<ScrapedEvent> {
+        tracing::debug!(url = %url, "page unchanged since the last run; not parsed");
+        self.pages_unchanged.fetch_add(1, Ordering::Relaxed);
+        self.unchanged_events.lock().expect("unchanged events lock").extend(cached.event_urls);
+        Vec::new()
+    }
+
+    /// Whether `url`, a page a sitemap says last changed at `lastmod`, was
+    /// parsed since (and within `cache::MAX_AGE`): then it's counted as
+    /// unchanged and its events are remembered as seen, without a request.
+    /// Always `false` without a fetch cache.
+    pub async fn unchanged_since(&self, url: &str, lastmod: DateTime<Utc>) -> bool {
+        let Some(db) = &self.cache else {
+            return false;
+        };
+        match cache::lookup_since(db, url, lastmod).await {
+            Ok(Some(cached)) => {
+                self.unchanged(url, cached);
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::warn!(url = %url, error = %e, "couldn't read the fetch cache");
+                false
+            }
+        }
+    }
+
+    /// Leaves `url` for a later run (a crawl's cap was reached): the
+    /// events it had when last parsed, however long ago, are remembered as
+    /// seen, so they aren't taken for shows the source dropped.
+    pub async fn defer(&self, url: &str) {
+        let Some(db) = &self.cache else {
+            return;
+        };
+        match cache::event_urls(db, url).await {
+            Ok(event_urls) => self.unchanged_events.lock().expect("unchanged events lock").extend(event_urls),
+            Err(e) => tracing::warn!(url = %url, error = %e, "couldn't read the fetch cache"),
+        }
+    }
+
+    /// Once the run's events are stored: marks the events of unchanged
+    /// pages seen, and saves the pages parsed to the fetch cache. Call it
+    /// only for a real run that succeeded.
+    pub async fn commit_cache(&self) -> Result<(), sqlx::Error> {
+        let Some(db) = &self.cache else {
+            return Ok(());
+        };
+        let unchanged = self.unchanged_events.lock().expect("unchanged events lock").clone();
+        if !unchanged.is_empty() {
+            cache::mark_seen(db, &unchanged).await?;
+        }
+        let parsed = self.parsed.lock().expect("parsed pages lock").clone();
+        cache::store(db, &parsed).await
+    }
+
+    /// The `Content-Type` `url` answers a HEAD with (an image's, say),
+    /// robots.txt-checked and retried like a GET. A server that won't take
+    /// a HEAD (`405`, `501`) is sent a GET whose body is never read.
+    ///
+    /// # Errors
+    /// As `get_text`.
+    pub async fn content_type(&self, url: &str) -> Result<Option<String>, ScraperError> {
+        let (parsed, crawl_delay) = self.allowed(url).await?;
+        match self.send(url, &parsed, Request::Head, crawl_delay).await? {
+            Fetched::Body { content_type, .. } => Ok(content_type),
+            Fetched::NotModified => unreachable!("only conditional requests come back 304"),
+        }
+    }
+
+    /// One GET, robots.txt-checked and retried (see `get_text`);
+    /// conditional on `cached`'s validators if given.
+    async fn get(&self, url: &str, cached: Option<&CachedPage>) -> Result<Fetched, ScraperError> {
+        let (parsed, crawl_delay) = self.allowed(url).await?;
+        self.send(url, &parsed, Request::Get(cached), crawl_delay).await
+    }
+
+    /// `url` parsed, and its site's `Crawl-delay`, if robots.txt lets us
+    /// fetch it.
+    ///
+    /// # Errors
+    /// `ScraperError::Disallowed` (counted) if it doesn't, or
+    /// `ScraperError::Validation` if `url` isn't a URL.
+    async fn allowed(&self, url: &str) -> Result<(Url, Option<Duration>), ScraperError> {
+        let parsed = Url::parse(url).map_err(|e| ScraperError::Validation(format!("URL {}: {}", url, e)))?;
+        let origin = parsed.origin().ascii_serialization();
+
+        let rules = {
+            let mut rules = self.rules.lock().await;
+            match rules.get(&origin) {
+                Some(known) => known.clone(),
+                None => {
+                    let fetched = self.pool.robots.rules_for(&parsed).await.unwrap_or_else(|| Arc::new(RobotsRules::deny_all()));
+                    rules.insert(origin.clone(), fetched.clone());
+                    fetched
+                }
+            }
+        };
+        if !rules.allows(&parsed) {
+            self.disallowed.fetch_add(1, Ordering::Relaxed);
+            tracing::info!(url = %url, "robots.txt disallows this URL; skipped");
+            return Err(ScraperError::Disallowed(url.to_string()));
+        }
+        Ok((parsed, rules.crawl_delay))
+    }
+
+    /// Sends `request` to `url`, spaced out and retried like every request
+    /// (see the module docs); a URL that still fails goes in `failed_urls`.
+    async fn send(&self, url: &str, parsed: &Url, request: Request<'_>, crawl_delay: Option<Duration>) -> Result<Fetched, ScraperError> {
+        let origin = parsed.origin().ascii_serialization();
+        let mut retries = 0;
+        let error = loop {
+            let wait = self.pool.reserve(&origin, crawl_delay).await;
+            tokio::time::sleep(wait).await;
+
+            let failure = match self.attempt(parsed.clone(), &request).await {
+                Ok(fetched) => return Ok(fetched),
+                Err(failure) => failure,
+            };
+            match failure {
+                Failure::Transient { error, retry_after } if retries < MAX_RETRIES => {
+                    let wait = retry_wait(self.pool.retry_backoff, retries, retry_after);
+                    if wait > MAX_RETRY_WAIT {
+                        break error;
+                    }
+                    retries += 1;
+                    tracing::warn!(url = %url, retry = retries, error = %error, "request failed; retrying in {:?}", wait);
+                    tokio::time::sleep(wait).await;
+                }
+                Failure::Transient { error, .. } | Failure::Permanent(error) => break error,
+            }
+        };
+
+        tracing::warn!(url = %url, error = %error, "couldn't fetch");
+        self.failed.lock().expect("failed URLs lock").push(FailedUrl { url: url.to_string(), error: error.to_string() });
+        Err(error)
+    }
+
+    /// One try at a request, holding a place under the concurrency cap.
+    async fn attempt(&self, url: Url, request: &Request<'_>) -> Result<Fetched, Failure> {
+        let _permit = self.pool.permits.acquire().await.expect("the semaphore is never closed");
+        let builder = match request {
+            Request::Get(cached) => {
+                let mut builder = self.pool.client.get(url);
+                if let Some(cached) = cached {
+                    if let Some(etag) = &cached.etag {
+                        builder = builder.header(IF_NONE_MATCH, etag);
+                    }
+                    if let Some(last_modified) = &cached.last_modified {
+                        builder = builder.header(IF_MODIFIED_SINCE, last_modified);
+                    }
+                }
+                builder
+            }
+            Request::PostJson { token, body } => self.pool.client.post(url).bearer_auth(token).json(body),
+            Request::Head => self.pool.client.head(url),
+        };
+        let mut response = builder.send().await.map_err(classify)?;
+        if matches!(request, Request::Head)
+            && matches!(response.status(), StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED)
+        {
+            response = self.pool.client.get(response.url().clone()).send().await.map_err(classify)?;
+        }
+
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED && matches!(request, Request::Get(Some(_))) {
+            return Ok(Fetched::NotModified);
+        }
+        if matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE) {
+            let retry_after = retry_after(response.headers(), Utc::now());
+            let error = response.error_for_status().expect_err("an error status");
+            return Err(Failure::Transient { error: error.into(), retry_after });
+        }
+        let response = response.error_for_status().map_err(|e| Failure::Permanent(e.into()))?;
+        let etag = header_text(response.headers(), ETAG);
+        let last_modified = header_text(response.headers(), LAST_MODIFIED);
+        let content_type = header_text(response.headers(), CONTENT_TYPE);
+        if matches!(request, Request::Head) {
+            return Ok(Fetched::Body { body: Vec::new(), etag, last_modified, content_type });
+        }
+        let body = response.bytes().await.map_err(classify)?.to_vec();
+        Ok(Fetched::Body { body, etag, last_modified, content_type })
+    }
+
+    /// URLs skipped for robots.txt so far this run.
+    pub fn disallowed(&self) -> usize {
+        self.disallowed.load(Ordering::Relaxed)
+    }
+
+    /// URLs that couldn't be fetched so far this run.
+    pub fn failed_urls(&self) -> Vec<FailedUrl> {
+        self.failed.lock().expect("failed URLs lock").clone()
+    }
+
+    /// Pages fetched and read so far this run.
+    pub fn pages_fetched(&self) -> usize {
+        self.pages_fetched.load(Ordering::Relaxed)
+    }
+
+    /// Pages skipped as unchanged so far this run.
+    pub fn pages_unchanged(&self) -> usize {
+        self.pages_unchanged.load(Ordering::Relaxed)
+    }
+}
+
+/// A header's value, if it's there and text.
+fn header_text(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers.get(name)?.to_str().ok().map(str::to_string)
+}
+
+/// How far into a body a `<meta charset>` is looked for.
+const CHARSET_SNIFF_BYTES: usize = 1024;
+
+/// A body as text (see "Charsets" above). Bytes the charset can't map
+/// become U+FFFD.
+fn decode(body: &[u8], content_type: Option<&str>) -> String {
+    let label = content_type
+        .and_then(charset_param)
+        .or_else(|| meta_charset(&body[..body.len().min(CHARSET_SNIFF_BYTES)]));
+    let encoding = label.and_then(|label| Encoding::for_label(label.as_bytes())).unwrap_or(UTF_8);
+    encoding.decode(body).0.into_owned()
+}
+
+/// The `charset` parameter of a `Content-Type`.
+fn charset_param(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset").then(|| value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// The charset a `<meta charset="...">` or `<meta http-equiv="Content-Type"
+/// content="...; charset=...">` tag names.
+fn meta_charset(head: &[u8]) -> Option<String> {
+    let head = String::from_utf8_lossy(head).to_lowercase();
+    head.split("<meta").skip(1).find_map(|tag| {
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        let value = &tag[tag.find("charset")? + "charset".len()..];
+        let value = value.trim_start().strip_prefix('=')?.trim_start().trim_start_matches(['"', '\'']);
+        let end = value.find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ';' | '/')).unwrap_or(value.len());
+        (end > 0).then(|| value[..end].to_string())
+    })
+}
+
+/// Timeouts and dropped connections are worth retrying; anything else
+/// (a bad URL, a redirect loop) isn't.
+fn classify(error: reqwest::Error) -> Failure {
+    if error.is_timeout() || error.is_connect() || error.is_request() || error.is_body() {
+        Failure::Transient { error: error.into(), retry_after: None }
+    } else {
+        Failure::Permanent(error.into())
+    }
+}
+
+/// The wait before retry number `retries + 1`: `backoff` doubled each
+/// time, or the site's `Retry-After` if longer.
+fn retry_wait(backoff: Duration, retries: u32, retry_after: Option<Duration>) -> Duration {
+    let backoff = backoff * 2u32.pow(retries);
+    retry_after.map_or(backoff, |retry_after| retry_after.max(backoff))
+}
+
+/// A `Retry-After` header: seconds, or an HTTP date.
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Time between requests to a host: ours, or the site's if it asks for more.
+fn gap(request_delay: Duration, crawl_delay: Option<Duration>) -> Duration {
+    crawl_delay.map_or(request_delay, |crawl_delay| crawl_delay.max(request_delay))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::join_all;
+    use wiremock::matchers::{header, method, path, path_regex};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn crawl_delay_only_ever_slows_us_down() {
+        let second = Duration::from_secs(1);
+        assert_eq!(gap(second, None), second);
+        assert_eq!(gap(second, Some(Duration::from_secs(5))), Duration::from_secs(5));
+        assert_eq!(gap(second, Some(Duration::from_millis(100))), second);
+    }
+
+    #[tokio::test]
+    async fn disallowed_urls_are_counted_and_never_fetched() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("calendar"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/private/list"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let fetcher = Fetcher::for_tests();
+        assert_eq!(fetcher.get_text(&format!("{}/events", server.uri())).await.unwrap(), "calendar");
+        let blocked = fetcher.get_text(&format!("{}/private/list", server.uri())).await.unwrap_err();
+        assert!(matches!(blocked, ScraperError::Disallowed(_)));
+        assert_eq!(fetcher.disallowed(), 1);
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_robots_closes_the_host_for_the_run() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("calendar"))
+            .mount(&server)
+            .await;
+
+        let pool = Arc::new(FetchPool::for_tests());
+        let url = format!("{}/events", server.uri());
+
+        let first = Fetcher::new(pool.clone());
+        assert!(matches!(first.get_text(&url).await, Err(ScraperError::Disallowed(_))));
+        assert!(matches!(first.get_text(&url).await, Err(ScraperError::Disallowed(_))));
+        assert_eq!(first.disallowed(), 2);
+
+        // The next run asks again (now a 404: no robots.txt, all allowed)
+        let next = Fetcher::new(pool);
+        assert_eq!(next.get_text(&url).await.unwrap(), "calendar");
+    }
+
+    /// A server with no robots.txt that answers `/slow/*` after `delay`.
+    async fn slow_server(delay: Duration) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex("^/slow/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok").set_delay(delay))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn requests_to_one_host_are_spaced_out() {
+        let server = slow_server(Duration::ZERO).await;
+        let config = FetchConfig { request_delay: Duration::from_millis(200), ..FetchConfig::default() };
+        let fetcher = Fetcher::new(Arc::new(FetchPool::new(config).unwrap()));
+
+        let started = Instant::now();
+        let urls: Vec<String> = (0..3).map(|i| format!("{}/slow/{}", server.uri(), i)).collect();
+        for body in join_all(urls.iter().map(|url| fetcher.get_text(url))).await {
+            assert_eq!(body.unwrap(), "ok");
+        }
+        // Three requests, two gaps between them
+        assert!(started.elapsed() >= Duration::from_millis(400), "took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn a_slow_crawl_delay_holds_across_runs() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nCrawl-delay: 0.3\n"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("calendar"))
+            .mount(&server)
+            .await;
+
+        // Two scrapers on the same site don't each get their own gap
+        let pool = Arc::new(FetchPool::for_tests());
+        let url = format!("{}/events", server.uri());
+        let started = Instant::now();
+        Fetcher::new(pool.clone()).get_text(&url).await.unwrap();
+        Fetcher::new(pool).get_text(&url).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(300), "took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn no_more_than_max_concurrent_requests_are_in_flight() {
+        let delay = Duration::from_millis(200);
+        let servers = join_all((0..4).map(|_| slow_server(delay))).await;
+        let config = FetchConfig { request_delay: Duration::ZERO, max_concurrent: 2, ..FetchConfig::default() };
+        let pool = Arc::new(FetchPool::new(config).unwrap());
+        // One host each, so only the cap keeps them from all going at once
+        let fetchers: Vec<Fetcher> = servers.iter().map(|_| Fetcher::new(pool.clone())).collect();
+        let urls: Vec<String> = servers.iter().map(|server| format!("{}/slow/page", server.uri())).collect();
+
+        let started = Instant::now();
+        let requests = fetchers.iter().zip(&urls).map(|(fetcher, url)| fetcher.get_text(url));
+        for body in join_all(requests).await {
+            assert_eq!(body.unwrap(), "ok");
+        }
+        // Four requests two at a time: two rounds
+        let elapsed = started.elapsed();
+        assert!(elapsed >= delay * 2, "took {:?}", elapsed);
+        assert!(elapsed < delay * 4, "took {:?}", elapsed);
+    }
+
+    #[test]
+    fn bodies_are_decoded_in_the_charset_they_name() {
+        // "Café Nights — 7pm" in windows-1252
+        let cp1252 = b"Caf\xe9 Nights \x97 7pm";
+        let page = [b"<html><head><meta charset=\"windows-1252\"><title>".as_slice(), cp1252, b"</title>"].concat();
+        assert!(decode(&page, Some("text/html")).contains("Café Nights — 7pm"));
+        let equiv = [b"<META HTTP-EQUIV='Content-Type' CONTENT='text/html; charset=ISO-8859-1'>".as_slice(), cp1252].concat();
+        assert!(decode(&equiv, None).ends_with("Café Nights — 7pm"));
+        // The header wins over the page
+        assert_eq!(decode(cp1252, Some("text/html; charset=\"Windows-1252\"")), "Café Nights — 7pm");
+        let utf8 = "<meta charset=windows-1252>Café".as_bytes();
+        assert!(decode(utf8, Some("text/html; charset=utf-8")).ends_with("Café"));
+        // Nothing named: UTF-8, with a BOM or without
+        assert_eq!(decode("Café".as_bytes(), None), "Café");
+        assert_eq!(decode(b"\xef\xbb\xbfCaf\xc3\xa9", Some("text/html; charset=windows-1252")), "Café");
+        assert_eq!(decode(b"Caf\xe9", None), "Caf\u{fffd}");
+    }
+
+    #[tokio::test]
+    async fn requests_say_who_we_are() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .and(header("user-agent", USER_AGENT))
+            .respond_with(ResponseTemplate::new(200).set_body_string("calendar"))
+            .mount(&server)
+            .await;
+
+        assert!(USER_AGENT.starts_with(crate::scraper::robots::ROBOTS_AGENT));
+        assert!(USER_AGENT.contains("+https://"));
+        let fetcher = Fetcher::for_tests();
+        assert_eq!(fetcher.get_text(&format!("{}/events", server.uri())).await.unwrap(), "calendar");
+    }
+
+    #[tokio::test]
+    async fn a_request_that_keeps_timing_out_fails() {
+        let server = slow_server(Duration::from_millis(500)).await;
+        let config = FetchConfig {
+            request_delay: Duration::ZERO,
+            timeout: Duration::from_millis(100),
+            retry_backoff: Duration::ZERO,
+            ..FetchConfig::default()
+        };
+        let fetcher = Fetcher::new(Arc::new(FetchPool::new(config).unwrap()));
+        let timed_out = fetcher.get_text(&format!("{}/slow/page", server.uri())).await.unwrap_err();
+        assert!(matches!(timed_out, ScraperError::Http(e) if e.is_timeout()));
+
+        let tries = server.received_requests().await.unwrap().iter().filter(|r| r.url.path() == "/slow/page").count();
+        assert_eq!(tries, 1 + MAX_RETRIES as usize);
+    }
+
+    #[test]
+    fn retries_back_off_unless_the_site_asks_for_longer() {
+        let half = Duration::from_millis(500);
+        assert_eq!(retry_wait(half, 0, None), half);
+        assert_eq!(retry_wait(half, 2, None), Duration::from_secs(2));
+        assert_eq!(retry_wait(half, 0, Some(Duration::from_secs(5))), Duration::from_secs(5));
+        assert_eq!(retry_wait(half, 2, Some(Duration::from_secs(1))), Duration::from_secs(2));
+
+        let now = "2026-03-01T15:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+        assert_eq!(retry_after(&headers("120"), now), Some(Duration::from_secs(120)));
+        assert_eq!(retry_after(&headers("Sun, 01 Mar 2026 15:00:30 GMT"), now), Some(Duration::from_secs(30)));
+        assert_eq!(retry_after(&headers("Sun, 01 Mar 2026 14:00:00 GMT"), now), Some(Duration::ZERO));
+        assert_eq!(retry_after(&headers("soon"), now), None);
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+    }
+
+    #[tokio::test]
+    async fn a_busy_site_is_retried_until_it_answers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "1"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(502))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("calendar"))
+            .mount(&server)
+            .await;
+
+        let fetcher = Fetcher::for_tests();
+        let started = Instant::now();
+        assert_eq!(fetcher.get_text(&format!("{}/events", server.uri())).await.unwrap(), "calendar");
+        assert!(started.elapsed() >= Duration::from_secs(1), "Retry-After wasn't honored");
+        assert!(fetcher.failed_urls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_site_that_stays_down_is_given_up_on() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/events"))
+            .respond_with(ResponseTemplate::new(429))
+            .expect(1 + MAX_RETRIES as u64)
+            .mount(&server)
+            .await;
+
+        let fetcher = Fetcher::for_tests();
+        let url = format!("{}/events", server.uri());
+        let error = fetcher.get_text(&url).await.unwrap_err();
+        assert!(matches!(&error, ScraperError::Http(e) if e.status() == Some(StatusCode::TOO_MANY_REQUESTS)));
+        assert_eq!(fetcher.failed_urls(), [FailedUrl { url, error: error.to_string() }]);
+    }
+
+    #[tokio::test]
+    async fn missing_pages_and_long_retry_afters_are_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/gone"))
+            .respond_with(ResponseTemplate::new(410))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/closed"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "3600"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let fetcher = Fetcher::for_tests();
+        for page in ["gone", "missing", "closed"] {
+            assert!(fetcher.get_text(&format!("{}/{}", server.uri(), page)).await.is_err());
+        }
+        assert_eq!(fetcher.failed_urls().len(), 3);
+    }
+
+    /// A database for the fetch cache, when `TEST_DATABASE_URL` is set.
+    async fn cache_db() -> Option<PgPool> {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return None;
+        };
+        let db = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        Some(db)
+    }
+
+    /// A page listing one event URL per line.
+    fn listing(body: &str) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        Ok(body.lines().map(|url| ScrapedEvent::new("Show", url, Utc::now())).collect())
+    }
+
+    fn not_parsed(_: &str) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        panic!("an unchanged page was parsed")
+    }
+
+    #[tokio::test]
+    async fn pages_answered_304_are_skipped_and_their_events_seen() {
+        let Some(db) = cache_db().await else { return };
+        let run = uuid::Uuid::new_v4();
+        let page = format!("/calendar/{}", run);
+        let event_url = format!("https://venue.example/{}/jazz", run);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(page.as_str()))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(page.as_str()))
+            .respond_with(ResponseTemplate::new(200).insert_header("etag", "\"v1\"").set_body_string(event_url.as_str()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let event: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time, scraped, last_seen_at) \
+             VALUES ('Jazz', $1, NOW() + INTERVAL '1 week', TRUE, NOW() - INTERVAL '1 week') RETURNING id",
+        )
+            .bind(&event_url)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        let pool = Arc::new(FetchPool::for_tests());
+        let url = format!("{}{}", server.uri(), page);
+        let first = Fetcher::new(pool.clone()).with_cache(db.clone());
+        assert_eq!(first.get_events(&url, listing).await.unwrap().len(), 1);
+        assert_eq!((first.pages_fetched(), first.pages_unchanged()), (1, 0));
+        first.commit_cache().await.unwrap();
+
+        let second = Fetcher::new(pool).with_cache(db.clone());
+        assert!(second.get_events(&url, not_parsed).await.unwrap().is_empty());
+        assert_eq!((second.pages_fetched(), second.pages_unchanged()), (0, 1));
+        second.commit_cache().await.unwrap();
+
+        let seen: bool = sqlx::query_scalar("SELECT last_seen_at > NOW() - INTERVAL '1 minute' FROM events WHERE id = $1")
+            .bind(event)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert!(seen, "the unchanged page's event wasn't marked seen");
+    }
+
+    #[tokio::test]
+    async fn the_same_bytes_again_count_as_unchanged() {
+        let Some(db) = cache_db().await else { return };
+        let page = format!("/calendar/{}", uuid::Uuid::new_v4());
+        let server = MockServer::start().await;
+        // Ignores conditional headers; no validators either
+        Mock::given(method("GET"))
+            .and(path(page.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_string("https://venue.example/a\nhttps://venue.example/b"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let pool = Arc::new(FetchPool::for_tests());
+        let url = format!("{}{}", server.uri(), page);
+
+        // Not committed (a dry run, a failed run): the next run parses it
+        let uncommitted = Fetcher::new(pool.clone()).with_cache(db.clone());
+        assert_eq!(uncommitted.get_events(&url, listing).await.unwrap().len(), 2);
+        let first = Fetcher::new(pool.clone()).with_cache(db.clone());
+        assert_eq!(first.get_events(&url, listing).await.unwrap().len(), 2);
+        first.commit_cache().await.unwrap();
+
+        let second = Fetcher::new(pool).with_cache(db.clone());
+        assert!(second.get_events(&url, not_parsed).await.unwrap().is_empty());
+        assert_eq!((second.pages_fetched(), second.pages_unchanged()), (0, 1));
+        assert_eq!(
+            *second.unchanged_events.lock().unwrap(),
+            ["https://venue.example/a", "https://venue.example/b"]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! # Fixture Scraper
+//!
+//! A scraper that returns canned events without touching the network, for
+//! testing the registry and everything downstream of it.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+
+use std::time::Duration;
+
+use axum::async_trait;
+
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+
+/// Returns `events` on every scrape, or fails like a redesigned page.
+pub struct FixtureScraper {
+    source_id: String,
+    name: String,
+    events: Vec<ScrapedEvent>,
+    fail: bool,
+    panic: bool,
+    delay: Duration,
+}
+
+impl FixtureScraper {
+    /// A scraper named `source_id` that finds `events`.
+    pub fn new(source_id: &str, events: Vec<ScrapedEvent>) -> Self {
+        Self {
+            source_id: source_id.to_string(),
+            name: format!("Fixture {}", source_id),
+            events,
+            fail: false,
+            panic: false,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// A scraper whose every scrape fails with a parse error.
+    pub fn failing(source_id: &str) -> Self {
+        Self { fail: true, ..Self::new(source_id, Vec::new()) }
+    }
+
+    /// A scraper whose every scrape panics, like a bug.
+    pub fn panicking(source_id: &str) -> Self {
+        Self { panic: true, ..Self::new(source_id, Vec::new()) }
+    }
+
+    /// Takes `delay` to scrape, like a slow site.
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+}
+
+#[async_trait]
+impl EventScraper for FixtureScraper {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    async fn scrape(&self, _fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        tokio::time::sleep(self.delay).await;
+        if self.panic {
+            panic!("fixture scraper {} panicked", self.source_id);
+        }
+        if self.fail {
+            return Err(ScraperError::Parse {
+                selector: ".event-card".to_string(),
+                context: "fixture page".to_string(),
+            });
+        }
+        Ok(self.events.clone())
+    }
+}
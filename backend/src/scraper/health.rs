@@ -0,0 +1,436 @@
+//! # Scraper Health
+//!
+//! One view of whether each source is working (`GET /api/admin/scrape/health`),
+//! and the same numbers for Prometheus (`GET /api/metrics`).
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Status
+//! From a source's real (not dry) runs:
+//! - `failing`: `SCRAPE_FAILING_AFTER` (default: 3) or more failed runs
+//!   in a row
+//! - `warning`: its latest run found more than `DROP_WARNING` (70%) fewer
+//!   events than its trailing average: the classic sign of a selector
+//!   that silently stopped matching, which doesn't fail the run
+//! - `ok`: otherwise, including a source that hasn't run yet
+//!
+//! ## Events per Run
+//! Averaged over the last `YIELD_WINDOW` completed runs. The trailing
+//! average the latest run is compared with is over the `YIELD_WINDOW`
+//! before it, and needs at least `MIN_TRAILING_RUNS` of them. Runs that
+//! skipped unchanged pages (`cache.rs`) don't report those pages' events,
+//! so they aren't sampled.
+//!
+//! ## Metrics
+//! `render_metrics` writes the Prometheus text format, one series per
+//! source (label `source`):
+//! ```text
+//! locate918_scraper_runs_total{source,status}          counter: completed and failed runs
+//! locate918_scraper_last_success_timestamp_seconds     gauge: 0 if it never succeeded
+//! locate918_scraper_consecutive_failures               gauge
+//! locate918_scraper_events_per_run                     gauge: the average above
+//! locate918_scraper_status{source,status}              gauge: 1 for its status, 0 for the others
+//! ```
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::scraper::runs;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Completed runs averaged for events per run.
+pub const YIELD_WINDOW: usize = 10;
+
+/// Earlier runs needed before a drop is called one.
+pub const MIN_TRAILING_RUNS: usize = 3;
+
+/// A latest run this much below the trailing average (70% fewer events)
+/// is a warning.
+pub const DROP_WARNING: f64 = 0.7;
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// A source's derived health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Failing,
+}
+
+impl HealthStatus {
+    pub const ALL: [HealthStatus; 3] = [HealthStatus::Ok, HealthStatus::Warning, HealthStatus::Failing];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HealthStatus::Ok => "ok",
+            HealthStatus::Warning => "warning",
+            HealthStatus::Failing => "failing",
+        }
+    }
+}
+
+/// What a source's recent yields say (see "Events per Run" above).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct YieldTrend {
+    /// Over the latest `YIELD_WINDOW` runs sampled
+    pub events_per_run: Option<f64>,
+    /// The latest run sampled
+    pub latest_events: Option<i64>,
+    /// Over the `YIELD_WINDOW` runs before the latest
+    pub trailing_events_per_run: Option<f64>,
+    /// The latest run fell more than `DROP_WARNING` below the trailing average
+    pub dropped: bool,
+}
+
+/// One source's health.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "source": "cains_ballroom",
+///   "status": "warning",
+///   "last_run_at": "2026-03-01T15:00:00Z",
+///   "last_success_at": "2026-03-01T15:00:04Z",
+///   "consecutive_failures": 0,
+///   "runs_completed": 212,
+///   "runs_failed": 4,
+///   "events_per_run": 21.6,
+///   "latest_events": 2,
+///   "trailing_events_per_run": 23.8,
+///   "dropped": true
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScraperHealth {
+    pub source: String,
+    pub status: HealthStatus,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// Failed runs since the last success
+    pub consecutive_failures: i64,
+    pub runs_completed: i64,
+    pub runs_failed: i64,
+    #[serde(flatten)]
+    pub trend: YieldTrend,
+}
+
+// =============================================================================
+// ASSESSMENT
+// =============================================================================
+
+/// The trend in `events_found`, the events of a source's sampled runs,
+/// newest first. Only the first `YIELD_WINDOW + 1` are looked at.
+pub fn yield_trend(events_found: &[i64]) -> YieldTrend {
+    let average = |runs: &[i64]| (!runs.is_empty()).then(|| runs.iter().sum::<i64>() as f64 / runs.len() as f64);
+    let recent = &events_found[..events_found.len().min(YIELD_WINDOW)];
+    let trailing = events_found.get(1..).map(|runs| &runs[..runs.len().min(YIELD_WINDOW)]).unwrap_or_default();
+
+    let latest_events = events_found.first().copied();
+    let trailing_events_per_run = average(trailing);
+    let dropped = match (latest_events, trailing_events_per_run) {
+        (Some(latest), Some(trailing_average)) if trailing.len() >= MIN_TRAILING_RUNS && trailing_average > 0.0 => {
+            (latest as f64) < trailing_average * (1.0 - DROP_WARNING)
+        }
+        _ => false,
+    };
+    YieldTrend { events_per_run: average(recent), latest_events, trailing_events_per_run, dropped }
+}
+
+/// The status of a source `consecutive_failures` failed runs into a
+/// streak, whose yields trend as `trend`.
+pub fn status(consecutive_failures: i64, failing_after: u64, trend: &YieldTrend) -> HealthStatus {
+    if consecutive_failures >= failing_after as i64 {
+        HealthStatus::Failing
+    } else if trend.dropped {
+        HealthStatus::Warning
+    } else {
+        HealthStatus::Ok
+    }
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// Health of each of `sources`, in that order.
+pub async fn scraper_health(pool: &PgPool, sources: &[&str], failing_after: u64) -> Result<Vec<ScraperHealth>, sqlx::Error> {
+    let streaks = runs::source_health(pool, sources, failing_after).await?;
+
+    let samples: Vec<(String, Vec<i64>)> = sqlx::query_as(
+        r#"
+        SELECT s.source, COALESCE(ARRAY_AGG(r.events_found::bigint ORDER BY r.started_at DESC)
+                                  FILTER (WHERE r.events_found IS NOT NULL), '{}')
+        FROM UNNEST($1::text[]) AS s(source)
+        LEFT JOIN LATERAL (
+            SELECT events_found, started_at FROM scrape_runs
+            WHERE source = s.source AND NOT dry_run AND status = 'completed' AND pages_unchanged = 0
+            ORDER BY started_at DESC LIMIT $2
+        ) r ON TRUE
+        GROUP BY s.source
+        "#,
+    )
+        .bind(sources)
+        .bind(YIELD_WINDOW as i64 + 1)
+        .fetch_all(pool)
+        .await?;
+
+    let totals: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT source, COUNT(*) FILTER (WHERE status = 'completed'), COUNT(*) FILTER (WHERE status = 'failed')
+        FROM scrape_runs
+        WHERE source = ANY($1) AND NOT dry_run
+        GROUP BY source
+        "#,
+    )
+        .bind(sources)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(streaks
+        .into_iter()
+        .map(|streak| {
+            let events = samples.iter().find(|(source, _)| *source == streak.source).map(|(_, events)| events.as_slice());
+            let trend = yield_trend(events.unwrap_or_default());
+            let (runs_completed, runs_failed) = totals
+                .iter()
+                .find(|(source, ..)| *source == streak.source)
+                .map_or((0, 0), |(_, completed, failed)| (*completed, *failed));
+            ScraperHealth {
+                status: status(streak.consecutive_failures, failing_after, &trend),
+                last_run_at: streak.last_run_at,
+                last_success_at: streak.last_success_at,
+                consecutive_failures: streak.consecutive_failures,
+                runs_completed,
+                runs_failed,
+                trend,
+                source: streak.source,
+            }
+        })
+        .collect())
+}
+
+// =============================================================================
+// METRICS
+// =============================================================================
+
+/// `health` in the Prometheus text exposition format (see "Metrics" above).
+pub fn render_metrics(health: &[ScraperHealth]) -> String {
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, samples: &mut dyn Iterator<Item = (String, f64)>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    };
+    let source = |health: &ScraperHealth| format!("source=\"{}\"", escape_label(&health.source));
+
+    family(
+        "locate918_scraper_runs_total",
+        "counter",
+        "Scraper runs finished, by outcome.",
+        &mut health.iter().flat_map(|health| {
+            [("completed", health.runs_completed), ("failed", health.runs_failed)]
+                .map(|(status, runs)| (format!("{},status=\"{}\"", source(health), status), runs as f64))
+        }),
+    );
+    family(
+        "locate918_scraper_last_success_timestamp_seconds",
+        "gauge",
+        "When the scraper last completed a run (0: never).",
+        &mut health.iter().map(|health| (source(health), health.last_success_at.map_or(0.0, |at| at.timestamp() as f64))),
+    );
+    family(
+        "locate918_scraper_consecutive_failures",
+        "gauge",
+        "Failed runs since the scraper's last success.",
+        &mut health.iter().map(|health| (source(health), health.consecutive_failures as f64)),
+    );
+    family(
+        "locate918_scraper_events_per_run",
+        "gauge",
+        "Average events found over the scraper's recent completed runs.",
+        &mut health.iter().map(|health| (source(health), health.trend.events_per_run.unwrap_or(0.0))),
+    );
+    family(
+        "locate918_scraper_status",
+        "gauge",
+        "The scraper's health: 1 for its status, 0 for the others.",
+        &mut health.iter().flat_map(|health| {
+            HealthStatus::ALL.map(|status| {
+                (format!("{},status=\"{}\"", source(health), status.as_str()), f64::from(u8::from(health.status == status)))
+            })
+        }),
+    );
+    out
+}
+
+/// A label value with `\`, `"` and newlines escaped.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_steady_source_is_ok() {
+        let trend = yield_trend(&[22, 25, 24, 23, 26, 24, 25, 22, 24, 23, 25]);
+        assert!(!trend.dropped);
+        assert_eq!(trend.latest_events, Some(22));
+        assert_eq!(trend.events_per_run, Some(23.8));
+        assert_eq!(trend.trailing_events_per_run, Some(24.1));
+        assert_eq!(status(0, 3, &trend), HealthStatus::Ok);
+    }
+
+    #[test]
+    fn a_run_finding_most_events_missing_is_a_warning() {
+        // 24 a run, then a redesign: 2
+        let trend = yield_trend(&[2, 24, 24, 24, 24]);
+        assert!(trend.dropped);
+        assert_eq!(status(0, 3, &trend), HealthStatus::Warning);
+
+        // 70% fewer is the line: 7.2 of 24 is within it, 7 isn't
+        assert!(yield_trend(&[7, 24, 24, 24]).dropped);
+        assert!(!yield_trend(&[8, 24, 24, 24]).dropped);
+        // And an empty run counts
+        assert!(yield_trend(&[0, 3, 4, 5]).dropped);
+    }
+
+    #[test]
+    fn drops_need_a_history_to_drop_from() {
+        // Too few earlier runs
+        assert!(!yield_trend(&[1, 30, 30]).dropped);
+        // Nothing to lose
+        assert!(!yield_trend(&[0, 0, 0, 0]).dropped);
+        // Never sampled
+        let none = yield_trend(&[]);
+        assert_eq!((none.events_per_run, none.latest_events, none.dropped), (None, None, false));
+        assert_eq!(status(0, 3, &none), HealthStatus::Ok);
+    }
+
+    #[test]
+    fn only_the_window_is_averaged() {
+        // An old, much busier season beyond the window doesn't count
+        let mut history = vec![10; YIELD_WINDOW + 1];
+        history.extend([500; 20]);
+        let trend = yield_trend(&history);
+        assert_eq!((trend.events_per_run, trend.trailing_events_per_run), (Some(10.0), Some(10.0)));
+        assert!(!trend.dropped);
+    }
+
+    #[test]
+    fn failing_beats_warning() {
+        let dropped = yield_trend(&[0, 20, 20, 20]);
+        assert_eq!(status(3, 3, &dropped), HealthStatus::Failing);
+        assert_eq!(status(2, 3, &dropped), HealthStatus::Warning);
+        assert_eq!(status(5, 3, &yield_trend(&[])), HealthStatus::Failing);
+    }
+
+    #[tokio::test]
+    async fn a_source_whose_yield_collapses_is_flagged() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let source = format!("health_{}", uuid::Uuid::new_v4().simple());
+        let broken = format!("{}_broken", source);
+        let quiet = format!("{}_quiet", source);
+        // Oldest first: a steady 20, a run that skipped unchanged pages,
+        // a dry run, then a redesign
+        let history: &[(&str, &str, i32, i32, bool)] = &[
+            (&source, "completed", 20, 0, false),
+            (&source, "completed", 20, 0, false),
+            (&source, "completed", 20, 0, false),
+            (&source, "completed", 20, 0, false),
+            (&source, "completed", 1, 6, false),
+            (&source, "completed", 30, 0, true),
+            (&source, "completed", 3, 0, false),
+            (&broken, "completed", 20, 0, false),
+            (&broken, "failed", 0, 0, false),
+            (&broken, "failed", 0, 0, false),
+            (&broken, "failed", 0, 0, false),
+        ];
+        for (minutes, (source, status, found, unchanged, dry_run)) in history.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO scrape_runs (source, status, events_found, pages_unchanged, dry_run, started_at, finished_at)
+                VALUES ($1, $2::scrape_run_status, $3, $4, $5, NOW() - INTERVAL '1 hour' + $6 * INTERVAL '1 minute',
+                        NOW() - INTERVAL '1 hour' + $6 * INTERVAL '1 minute')
+                "#,
+            )
+                .bind(source)
+                .bind(status)
+                .bind(found)
+                .bind(unchanged)
+                .bind(dry_run)
+                .bind(minutes as f64)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let health = scraper_health(&pool, &[&source, &broken, &quiet], 3).await.unwrap();
+        assert_eq!(health.iter().map(|h| h.source.as_str()).collect::<Vec<_>>(), [&source, &broken, &quiet]);
+
+        let dropped = &health[0];
+        assert_eq!(dropped.status, HealthStatus::Warning);
+        assert_eq!((dropped.trend.latest_events, dropped.trend.trailing_events_per_run), (Some(3), Some(20.0)));
+        assert_eq!(dropped.trend.events_per_run, Some(16.6));
+        assert_eq!((dropped.runs_completed, dropped.runs_failed, dropped.consecutive_failures), (6, 0, 0));
+
+        let failing = &health[1];
+        assert_eq!((failing.status, failing.consecutive_failures, failing.runs_failed), (HealthStatus::Failing, 3, 3));
+        assert!(failing.last_success_at.is_some());
+
+        let never = &health[2];
+        assert_eq!((never.status, never.runs_completed, never.trend.events_per_run), (HealthStatus::Ok, 0, None));
+
+        sqlx::query("DELETE FROM scrape_runs WHERE source LIKE $1")
+            .bind(format!("{}%", source))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn metrics_are_one_series_per_source() {
+        let health = ScraperHealth {
+            source: "cains_ballroom".to_string(),
+            status: HealthStatus::Warning,
+            last_run_at: None,
+            last_success_at: Some("2026-03-01T15:00:00Z".parse().unwrap()),
+            consecutive_failures: 0,
+            runs_completed: 212,
+            runs_failed: 4,
+            trend: yield_trend(&[2, 24, 24, 24]),
+        };
+        let text = render_metrics(&[health]);
+        assert!(text.contains("# TYPE locate918_scraper_runs_total counter\n"), "{}", text);
+        assert!(text.contains("locate918_scraper_runs_total{source=\"cains_ballroom\",status=\"failed\"} 4\n"), "{}", text);
+        assert!(text.contains("locate918_scraper_last_success_timestamp_seconds{source=\"cains_ballroom\"} 1772377200\n"), "{}", text);
+        assert!(text.contains("locate918_scraper_events_per_run{source=\"cains_ballroom\"} 18.5\n"), "{}", text);
+        assert!(text.contains("locate918_scraper_status{source=\"cains_ballroom\",status=\"warning\"} 1\n"), "{}", text);
+        assert!(text.contains("locate918_scraper_status{source=\"cains_ballroom\",status=\"ok\"} 0\n"), "{}", text);
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -0,0 +1,315 @@
+//! # Event Images
+//!
+//! Tidies the image URLs scrapers find, and checks they really are images
+//! before they're stored.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Normalizing (`normalize`)
+//! Every extractor passes the image it found through `normalize`, with the
+//! page's URL:
+//! - A relative URL is resolved against the page
+//! - A protocol-relative one (`//cdn.example/a.jpg`) gets `https:`
+//! - Tracking parameters (`utm_*`, `fbclid`, `gclid`, ...) are dropped, so
+//!   the same image doesn't look new every run; sizes and crops are kept
+//! - Anything but http(s) (`data:`, `javascript:`) isn't an image
+//!
+//! ## Checking (`check_images`)
+//! Before a run's events are stored, each image is asked for with a HEAD
+//! (`Fetcher::content_type`: robots.txt and the per-host spacing apply).
+//! - An image that answers `2xx` with an `image/*` type is kept; one that
+//!   answers anything else (a `404`, an HTML page) leaves `image_url` null
+//! - An image we couldn't ask about (timeout, `5xx`, robots.txt) is kept,
+//!   unchecked
+//! - Answers are kept in `image_checks` for `CHECK_TTL`, so an image is
+//!   asked about once a week at most, not every run; dry runs don't keep
+//!   them
+//! - At most `MAX_CHECKS_PER_RUN` images are asked about per run; the rest
+//!   are stored unchecked and asked about next run, so the first run of a
+//!   big calendar isn't held up (requests to a host are seconds apart)
+//!
+//! `SCRAPE_CHECK_IMAGES=false` turns checking off (`checks_enabled`, see
+//! `ScraperRegistry::with_image_checks`).
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use reqwest::Url;
+use sqlx::PgPool;
+
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::traits::{ScrapedEvent, ScraperError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Query parameters dropped from image URLs: only ever for tracking.
+pub const TRACKING_PARAMS: &[&str] = &[
+    "_ga", "_gl", "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "msclkid", "yclid",
+];
+
+/// Parameter prefixes dropped the same way.
+pub const TRACKING_PREFIXES: &[&str] = &["utm_"];
+
+/// How long an image's answer is trusted.
+pub const CHECK_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Images asked about per run, at most.
+pub const MAX_CHECKS_PER_RUN: usize = 25;
+
+// =============================================================================
+// NORMALIZING
+// =============================================================================
+
+/// `url` tidied (see "Normalizing" above), relative to the page at `base`;
+/// `None` if it isn't an http(s) URL.
+pub fn normalize(url: &str, base: Option<&str>) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+    let mut parsed = match url.strip_prefix("//") {
+        Some(rest) => Url::parse(&format!("https://{}", rest)).ok()?,
+        None => match Url::parse(url) {
+            Ok(absolute) => absolute,
+            Err(_) => Url::parse(base?).ok()?.join(url).ok()?,
+        },
+    };
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return None;
+    }
+
+    if parsed.query().is_some() {
+        let kept: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(name, _)| !is_tracking(name))
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect();
+        if kept.is_empty() {
+            parsed.set_query(None);
+        } else {
+            parsed.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+    Some(parsed.to_string())
+}
+
+fn is_tracking(param: &str) -> bool {
+    let param = param.to_lowercase();
+    TRACKING_PARAMS.contains(&param.as_str()) || TRACKING_PREFIXES.iter().any(|prefix| param.starts_with(prefix))
+}
+
+/// Whether `SCRAPE_CHECK_IMAGES` allows checking (unset: yes).
+pub fn checks_enabled(raw: Option<&str>) -> bool {
+    !matches!(
+        raw.map(|raw| raw.trim().to_lowercase()).as_deref(),
+        Some("false" | "0" | "off" | "no")
+    )
+}
+
+// =============================================================================
+// CHECKING
+// =============================================================================
+
+/// Normalizes `events`' images against their pages, checks them (see
+/// "Checking" above) and clears the ones that aren't images. A database
+/// error is logged and leaves the images unchecked.
+///
+/// # Returns
+/// How many images were cleared.
+pub async fn check_images(fetcher: &Fetcher, pool: &PgPool, events: &mut [ScrapedEvent], dry_run: bool) -> usize {
+    for event in events.iter_mut() {
+        event.image_url = event.image_url.as_deref().and_then(|url| normalize(url, Some(&event.source_url)));
+    }
+    let mut seen = HashSet::new();
+    let urls: Vec<String> = events
+        .iter()
+        .filter_map(|event| event.image_url.clone())
+        .filter(|url| seen.insert(url.clone()))
+        .collect();
+    if urls.is_empty() {
+        return 0;
+    }
+
+    let mut verdicts = match cached(pool, &urls).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            tracing::warn!(error = %e, "couldn't read the image checks; images stored unchecked");
+            return 0;
+        }
+    };
+    let unchecked: Vec<&String> = urls.iter().filter(|url| !verdicts.contains_key(*url)).collect();
+    if unchecked.len() > MAX_CHECKS_PER_RUN {
+        tracing::info!(unchecked = unchecked.len(), "more images than a run checks; the rest wait for the next run");
+    }
+    for url in unchecked.into_iter().take(MAX_CHECKS_PER_RUN) {
+        let Some((ok, content_type)) = ask(fetcher, url).await else {
+            continue;
+        };
+        if !dry_run {
+            if let Err(e) = record(pool, url, ok, content_type.as_deref()).await {
+                tracing::warn!(url = %url, error = %e, "couldn't record an image check");
+            }
+        }
+        verdicts.insert(url.clone(), ok);
+    }
+
+    let mut cleared = 0;
+    for event in events.iter_mut() {
+        if event.image_url.as_ref().is_some_and(|url| verdicts.get(url) == Some(&false)) {
+            tracing::info!(source_url = %event.source_url, image_url = ?event.image_url, "not an image; left out");
+            event.image_url = None;
+            cleared += 1;
+        }
+    }
+    cleared
+}
+
+/// Whether `url` answers as an image, and the type it answered with;
+/// `None` if it couldn't be asked.
+async fn ask(fetcher: &Fetcher, url: &str) -> Option<(bool, Option<String>)> {
+    match fetcher.content_type(url).await {
+        Ok(content_type) => Some((content_type.as_deref().is_some_and(is_image_type), content_type)),
+        // Missing (a 5xx after retrying may just be a bad moment)
+        Err(ScraperError::Http(e)) if e.status().is_some_and(|status| status.is_client_error()) => Some((false, None)),
+        Err(e) => {
+            tracing::debug!(url = %url, error = %e, "couldn't check an image; kept unchecked");
+            None
+        }
+    }
+}
+
+/// Whether a `Content-Type` is an image's.
+fn is_image_type(content_type: &str) -> bool {
+    content_type.trim().to_lowercase().starts_with("image/")
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// The answers for `urls` still within `CHECK_TTL`.
+async fn cached(pool: &PgPool, urls: &[String]) -> Result<HashMap<String, bool>, sqlx::Error> {
+    let rows: Vec<(String, bool)> = sqlx::query_as(
+        r#"
+        SELECT url, ok FROM image_checks
+        WHERE url = ANY($1) AND checked_at > NOW() - make_interval(secs => $2)
+        "#,
+    )
+        .bind(urls)
+        .bind(CHECK_TTL.as_secs_f64())
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+async fn record(pool: &PgPool, url: &str, ok: bool, content_type: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO image_checks (url, ok, content_type) VALUES ($1, $2, $3)
+        ON CONFLICT (url) DO UPDATE
+        SET ok = EXCLUDED.ok, content_type = EXCLUDED.content_type, checked_at = NOW()
+        "#,
+    )
+        .bind(url)
+        .bind(ok)
+        .bind(content_type)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn images_are_made_absolute_and_untracked() {
+        let page = Some("https://www.cainsballroom.com/events/");
+        assert_eq!(normalize("/img/show.jpg", page).as_deref(), Some("https://www.cainsballroom.com/img/show.jpg"));
+        assert_eq!(normalize("show.jpg", page).as_deref(), Some("https://www.cainsballroom.com/events/show.jpg"));
+        assert_eq!(normalize("//cdn.example/a.jpg", None).as_deref(), Some("https://cdn.example/a.jpg"));
+        assert_eq!(
+            normalize("https://cdn.example/a.jpg?utm_source=fb&w=800&fbclid=IwAR0&UTM_Medium=x", None).as_deref(),
+            Some("https://cdn.example/a.jpg?w=800")
+        );
+        assert_eq!(normalize("https://cdn.example/a.jpg?utm_source=fb", None).as_deref(), Some("https://cdn.example/a.jpg"));
+        assert_eq!(normalize("  http://cdn.example/a b.jpg ", None).as_deref(), Some("http://cdn.example/a%20b.jpg"));
+        // Not images
+        assert_eq!(normalize("data:image/png;base64,AAAA", page), None);
+        assert_eq!(normalize("javascript:void(0)", page), None);
+        assert_eq!(normalize("/img/show.jpg", None), None);
+        assert_eq!(normalize("", page), None);
+    }
+
+    #[test]
+    fn checking_is_on_unless_turned_off() {
+        assert!(checks_enabled(None));
+        assert!(checks_enabled(Some("true")));
+        assert!(!checks_enabled(Some(" False ")));
+        assert!(!checks_enabled(Some("0")));
+        assert!(is_image_type("image/webp") && is_image_type(" Image/JPEG; charset=binary"));
+        assert!(!is_image_type("text/html; charset=utf-8"));
+    }
+
+    #[tokio::test]
+    async fn broken_images_are_left_out_and_answers_kept() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let server = MockServer::start().await;
+        let image = |content_type: &str| ResponseTemplate::new(200).insert_header("content-type", content_type);
+        Mock::given(method("GET")).and(path("/robots.txt")).respond_with(ResponseTemplate::new(404)).mount(&server).await;
+        Mock::given(method("HEAD")).and(path("/poster.jpg")).respond_with(image("image/jpeg")).expect(1).mount(&server).await;
+        Mock::given(method("HEAD")).and(path("/event")).respond_with(image("text/html")).mount(&server).await;
+        Mock::given(method("HEAD")).and(path("/gone.jpg")).respond_with(ResponseTemplate::new(404)).mount(&server).await;
+        Mock::given(method("HEAD")).and(path("/nohead.png")).respond_with(ResponseTemplate::new(405)).mount(&server).await;
+        Mock::given(method("GET")).and(path("/nohead.png")).respond_with(image("image/png")).mount(&server).await;
+        let cleanup = || {
+            sqlx::query("DELETE FROM image_checks WHERE url LIKE $1").bind(format!("{}/%", server.uri())).execute(&pool)
+        };
+        cleanup().await.unwrap();
+
+        let page = format!("{}/events/", server.uri());
+        let event = |image: &str| ScrapedEvent {
+            image_url: Some(image.to_string()),
+            ..ScrapedEvent::new("Show", &page, Utc::now())
+        };
+        let scraped = || {
+            vec![
+                event("/poster.jpg?utm_campaign=fall"),
+                event("/poster.jpg"),
+                event("/event"),
+                event("/gone.jpg"),
+                event("/nohead.png"),
+            ]
+        };
+
+        let fetcher = Fetcher::for_tests();
+        let mut events = scraped();
+        assert_eq!(check_images(&fetcher, &pool, &mut events, false).await, 2);
+        let images: Vec<Option<String>> = events.into_iter().map(|event| event.image_url).collect();
+        let poster = Some(format!("{}/poster.jpg", server.uri()));
+        assert_eq!(images, [poster.clone(), poster, None, None, Some(format!("{}/nohead.png", server.uri()))]);
+
+        // The second run asks nothing: the poster's HEAD is expected once
+        let mut again = scraped();
+        assert_eq!(check_images(&fetcher, &pool, &mut again, false).await, 2);
+        assert_eq!(again[3].image_url, None);
+
+        cleanup().await.unwrap();
+    }
+}
@@ -31,8 +31,11 @@
 //!
 //! ### Legal/Ethical
 //! - Only scrape publicly available information
-//! - Respect robots.txt
-//! - Don't overload servers (rate limiting)
+//! - Respect robots.txt (enforced: every request goes through `Fetcher`,
+//!   which checks it first; see `robots.rs`)
+//! - Don't overload servers (rate limiting: `FetchPool` spaces requests to
+//!   a host by `SCRAPE_REQUEST_DELAY_MS` or the site's `Crawl-delay`, and
+//!   caps requests in flight at `SCRAPE_MAX_CONCURRENT_REQUESTS`)
 //! - Always link back to source (source_url field)
 //! - Generate original summaries, don't copy descriptions verbatim
 //!
@@ -47,129 +50,149 @@
 //! - `reqwest` - HTTP client for fetching web pages
 //! - `scraper` - HTML parsing and CSS selector queries
 //!
-//! ## Example Implementation Structure
+//! ## Writing a Scraper
+//! Implement `EventScraper` (see `traits.rs`) and register it:
 //! ```rust
-//! use reqwest::Client;
-//! use scraper::{Html, Selector};
-//! use crate::models::CreateEvent;
-//!
-//! /// Trait that all scrapers implement
-//! pub trait EventScraper {
-//!     /// Name of this scraper (for logging)
-//!     fn name(&self) -> &str;
-//!
-//!     /// Fetch and parse events from the source
-//!     async fn scrape(&self) -> Result<Vec<CreateEvent>, ScraperError>;
-//! }
-//!
-//! /// Scraper for a specific venue website
-//! pub struct BlueNoteVenueScraper {
-//!     client: Client,
-//!     base_url: String,
-//! }
-//!
-//! impl BlueNoteVenueScraper {
-//!     pub fn new() -> Self {
-//!         Self {
-//!             client: Client::new(),
-//!             base_url: "https://thebluenote.com/events".to_string(),
-//!         }
+//! #[async_trait]
+//! impl EventScraper for BlueNoteScraper {
+//!     fn name(&self) -> &str { "The Blue Note" }
+//!     fn source_id(&self) -> &str { "blue_note" }
+//!
+//!     async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+//!         let html = fetcher.get_text(&self.base_url).await?;
+//!         // Parse with `scraper::Html` and CSS selectors into ScrapedEvents
 //!     }
 //! }
 //!
-//! impl EventScraper for BlueNoteVenueScraper {
-//!     fn name(&self) -> &str {
-//!         "Blue Note Venue"
-//!     }
-//!
-//!     async fn scrape(&self) -> Result<Vec<CreateEvent>, ScraperError> {
-//!         // 1. Fetch the events page
-//!         let html = self.client.get(&self.base_url)
-//!             .send()
-//!             .await?
-//!             .text()
-//!             .await?;
-//!
-//!         // 2. Parse HTML
-//!         let document = Html::parse_document(&html);
-//!
-//!         // 3. Select event elements (CSS selectors specific to this site)
-//!         let event_selector = Selector::parse(".event-card").unwrap();
-//!         let title_selector = Selector::parse(".event-title").unwrap();
-//!         let date_selector = Selector::parse(".event-date").unwrap();
-//!
-//!         // 4. Extract data from each event
-//!         let mut events = Vec::new();
-//!         for element in document.select(&event_selector) {
-//!             let title = element.select(&title_selector)
-//!                 .next()
-//!                 .map(|e| e.text().collect::<String>())
-//!                 .unwrap_or_default();
-//!
-//!             // ... extract other fields ...
-//!
-//!             events.push(CreateEvent {
-//!                 title,
-//!                 description: None,
-//!                 location: Some("Tulsa, OK".to_string()),
-//!                 venue: Some("The Blue Note".to_string()),
-//!                 source_url: format!("{}/event/{}", self.base_url, event_id),
-//!                 start_time: parsed_date,
-//!                 end_time: None,
-//!                 category: Some("music".to_string()),
-//!             });
-//!         }
-//!
-//!         Ok(events)
-//!     }
-//! }
+//! let registry = ScraperRegistry::new(FetchPool::new(FetchConfig::from_env())?)
+//!     .register(BlueNoteScraper::new());
+//! let summaries = registry.run_all(&pool).await;  // found/created/updated/failed
 //! ```
 //!
+//! Keep the parsing in a pure function over the page text, so it can be
+//! tested against a saved copy of the page without the network. A scraper
+//! that reads one page can pass that function to `fetcher.get_events`
+//! instead, which skips the page when it hasn't changed (see `cache.rs`).
+//!
 //! ## Running Scrapers
 //! Scrapers can be run:
-//! 1. **Manually** - Admin endpoint to trigger a scrape
-//! 2. **Scheduled** - Background task that runs every few hours
+//! 1. **Manually** - `POST /api/admin/scrape` (see `runs.rs`); a single
+//!    page someone sent us with `POST /api/admin/scrape/url` (`extract.rs`)
+//! 2. **Scheduled** - Every few hours, per source (see `schedule.rs`)
 //! 3. **On-demand** - When event data is stale or missing
 //!
 //! ## Deduplication Strategy
-//! Events may appear on multiple sources. To avoid duplicates:
-//! 1. Check for existing event with same title + start_time + venue
-//! 2. If found, update the existing record (merge data)
+//! Events may appear on multiple sources. To avoid duplicates
+//! (`persist.rs`):
+//! 1. Check for an existing event with the same `source_url`, or the same
+//!    title + venue (normalized) starting within 15 minutes
+//! 2. If found, update the existing record in place
 //! 3. If not found, create new event
 //!
+//! A unique index keeps scraped rows to one per title, venue and hour.
+//!
 //! ## File Structure (Suggested)
 //! ```text
 //! scraper/
-//! ├── mod.rs          <- This file (module root, shared types)
-//! ├── traits.rs       <- EventScraper trait definition
+//! ├── mod.rs          <- This file (module root)
+//! ├── traits.rs       <- EventScraper, ScrapedEvent, ScraperError
+//! ├── registry.rs     <- ScraperRegistry: runs scrapers, stores events
+//! ├── sources.rs      <- scrape_sources: which sources run, and how often
+//! ├── fetch.rs        <- FetchPool/Fetcher: every scraper request (robots.txt, delays, limits)
+//! ├── robots.rs       <- robots.txt parsing and caching
+//! ├── cache.rs        <- fetch_cache: skipping pages unchanged since the last run
+//! ├── images.rs       <- Event image URLs: normalized, and checked they're images
+//! ├── persist.rs      <- Storing scraped events without duplicates
+//! ├── categories.rs   <- Per-source category mappings, unmapped labels
+//! ├── quarantine.rs   <- Scraped events that failed validation, for review
+//! ├── extract.rs      <- One event from any page: JSON-LD, meta tags, heuristics
+//! ├── stale.rs        <- Cancels events a source stopped listing
+//! ├── runs.rs         <- ScrapeRunner: background runs from the admin API
+//! ├── health.rs       <- Per-source health: failure streaks, yield drops, metrics
+//! ├── schedule.rs     <- ScrapeScheduler: every scraper on a timer
+//! ├── fixture.rs      <- Canned-event scraper for tests
+//! ├── price.rs        <- Price text parsing
+//! ├── dates.rs        <- parse_event_datetime: every scraper's date/time reading
 //! ├── venues/
 //! │   ├── mod.rs
-//! │   ├── blue_note.rs
-//! │   └── cains_ballroom.rs
+//! │   └── cains_ballroom.rs   <- Cain's Ballroom calendar
 //! ├── platforms/
 //! │   ├── mod.rs
+//! │   ├── ical.rs     <- Any iCalendar feed (ICAL_FEEDS or a source row)
+//! │   ├── jsonld.rs   <- Any page of schema.org JSON-LD events
+//! │   ├── opengraph.rs <- Open Graph / meta-tag events (pages without JSON-LD)
+//! │   ├── sitemap.rs  <- Event pages found through a site's sitemap
 //! │   ├── eventbrite.rs
-//! │   └── meetup.rs
+//! │   └── meetup.rs   <- Meetup's GraphQL API (MEETUP_TOKEN)
 //! └── city/
 //!     ├── mod.rs
-//!     └── tulsa_calendar.rs
+//!     └── tulsa_calendar.rs   <- City of Tulsa events feed (RSS)
 //! ```
 
 // =============================================================================
-// PLACEHOLDER - Skylar to implement
+// SUBMODULE DECLARATIONS
 // =============================================================================
 
-// Event scrapers will go here
-//
-// Getting started:
-// 1. Pick ONE source to scrape first (recommend a simple venue website)
-// 2. Implement the scraper to fetch and parse events
-// 3. Test by running it manually and checking the output
-// 4. Add an endpoint or CLI command to trigger the scrape
-// 5. Store results in the database via the existing Event model
-// 6. Repeat for additional sources
-//
-// Useful resources:
-// - reqwest docs: https://docs.rs/reqwest
-// - scraper docs: https://docs.rs/scraper
-// - CSS selectors: https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_Selectors
+/// `EventScraper`, `ScrapedEvent` and `ScraperError`.
+pub mod traits;
+
+/// `ScraperRegistry`: owns the HTTP client, runs scrapers, stores events.
+pub mod registry;
+
+/// Scrape sources as rows: admin CRUD, seeding, building generic scrapers.
+pub mod sources;
+
+/// `FetchPool` and `Fetcher`: robots.txt-checked, rate-limited GETs for scrapers.
+pub mod fetch;
+
+/// `RobotsChecker`: robots.txt rules per site, cached.
+pub mod robots;
+
+/// The fetch cache: validators and body hashes of scraped pages.
+pub mod cache;
+
+/// `normalize` and `check_images`: tidy image URLs, drop broken ones.
+pub mod images;
+
+/// `persist_scraped_events`: stores a scrape, one row per show.
+pub mod persist;
+
+/// Per-source category mappings, and the labels still unmapped.
+pub mod categories;
+
+/// Scraped events that failed validation, held for an admin to fix or drop.
+pub mod quarantine;
+
+/// `scrape_url`: one event from any page, for links sent in by hand.
+pub mod extract;
+
+/// `cancel_unlisted`: cancels upcoming events a source no longer lists.
+pub mod stale;
+
+/// `ScrapeRunner`: background runs started from the admin API.
+pub mod runs;
+
+/// `scraper_health`: per-source status, and its Prometheus metrics.
+pub mod health;
+
+/// `ScrapeScheduler`: runs every scraper on a timer.
+pub mod schedule;
+
+/// Official city calendars.
+pub mod city;
+
+/// Shared formats and platforms (iCalendar feeds, JSON-LD pages, sitemaps, Meetup).
+pub mod platforms;
+
+/// Scrapers for individual venue websites.
+pub mod venues;
+
+/// Scraper returning canned events, for tests.
+#[cfg(test)]
+pub mod fixture;
+
+/// `parse_event_datetime` and local-to-UTC conversion for scrapers.
+pub mod dates;
+
+/// Price text parsing ("$10–$15", "Free") into CreateEvent price fields.
+pub mod price;
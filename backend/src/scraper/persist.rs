@@ -0,0 +1,714 @@
+//! # Storing Scraped Events
+//!
+//! Turns a scraper's events into rows, without duplicating a show we
+//! already list.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Matching
+//! Each event is validated (one that fails is quarantined for an admin,
+//! see `quarantine.rs`), then matched against what we have:
+//! 1. By `source_url`: an event's own, or another of its listings
+//!    (`event_sources`)
+//! 2. Failing that, by title and venue (normalized, see migration 031)
+//!    starting within `MATCH_WINDOW` of it. Events without a venue are
+//!    matched by URL only: "Jazz Night" at 8 could be anywhere.
+//!
+//! A match is updated in place if anything changed: our `id`,
+//! `created_at` and `source_url` stay, blank scraped fields keep what we
+//! have, and `updated_at` is bumped. A URL absorbed by a merge is
+//! recognized but not written over, since the canonical event's values
+//! win. Anything else is inserted. Either way the event's `last_seen_at`
+//! is stamped: its source still lists it (see `stale.rs`).
+//!
+//! ## Sources
+//! The listing is recorded in `event_sources` (or its `last_seen_at`
+//! stamped), with the source's `external_id`. A listing new to a matched
+//! event may become its primary link, if it ranks above the current one:
+//! the venue's own site beats the city's calendar beats Eventbrite (see
+//! `services::attribution`).
+//!
+//! ## Categories
+//! Each event's category comes from the source's mapping for its label,
+//! else the scraper's guess, else none (see `categories.rs`). A match
+//! takes the scraped category unless an editor set its own.
+//!
+//! ## One Row per Show
+//! An event whose normalized key (title, venue, start hour) already came
+//! up earlier in the same batch is counted as a duplicate and not stored.
+//! Across batches, rows the scraper inserts are held to one per key by
+//! a unique index; two sources inserting the same new show at the same
+//! moment leave one of them skipped until its next run matches the other.
+//!
+//! ## Dry Runs
+//! A dry run rolls each event back, and reports what it would have done
+//! as a `ScrapeDiff`: the events it would create, the fields it would
+//! change on each event it would update (before and after), and the
+//! duplicates it would skip.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::CreateEvent;
+use crate::routes::events::insert_event;
+use crate::scraper::categories::{CategoryMap, CATEGORY_SOURCE_SCRAPER};
+use crate::scraper::quarantine;
+use crate::scraper::traits::ScrapedEvent;
+use crate::services::attribution;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// How far apart two listings of a show may start and still match.
+pub const MATCH_WINDOW: Duration = Duration::minutes(15);
+
+// =============================================================================
+// SUMMARY
+// =============================================================================
+
+/// What storing one batch of scraped events did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PersistSummary {
+    pub created: usize,
+    pub updated: usize,
+    /// Matched, and nothing had changed
+    pub unchanged: usize,
+    /// Another event in the batch was the same show
+    pub duplicates: usize,
+    /// Invalid (quarantined, see `quarantine.rs`), or failed to save
+    pub skipped: usize,
+    /// The events inserted, by id (none in a dry run)
+    pub created_ids: Vec<Uuid>,
+    /// Event by event, for a dry run
+    pub diff: Option<ScrapeDiff>,
+}
+
+/// What a dry run would have stored.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "created": [{ "title": "Late Show", "venue": "Cain's Ballroom",
+///                 "start_time": "2026-03-01T02:00:00Z", "source_url": "https://..." }],
+///   "updated": [{ "event_id": "3f6c...", "title": "Open Jam", "start_time": "2026-02-27T01:00:00Z",
+///                 "source_url": "https://...",
+///                 "changes": [{ "field": "description", "before": null, "after": "Sign-up at 7" }] }],
+///   "duplicates": []
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrapeDiff {
+    pub created: Vec<DiffEvent>,
+    pub updated: Vec<DiffUpdate>,
+    /// Another event in the batch was the same show
+    pub duplicates: Vec<DiffEvent>,
+}
+
+/// A scraped event, as a dry run reports it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffEvent {
+    pub title: String,
+    pub venue: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub source_url: String,
+}
+
+/// An event a dry run would update, and how.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffUpdate {
+    pub event_id: Uuid,
+    pub title: String,
+    /// As scraped (after the update)
+    pub start_time: DateTime<Utc>,
+    pub source_url: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// One field an update would change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+impl fmt::Display for ScrapeDiff {
+    /// A table, one event per row and one changed field per line under
+    /// its update:
+    /// ```text
+    /// ACTION     STARTS (UTC)      TITLE
+    /// create     2026-03-01 02:00  Late Show
+    /// update     2026-02-27 01:00  Open Jam
+    ///              description: null -> "Sign-up at 7"
+    /// duplicate  2026-03-01 02:00  LATE SHOW
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter<'_>, action: &str, start: DateTime<Utc>, title: &str| {
+            writeln!(f, "{:<10} {:<17} {}", action, start.format("%Y-%m-%d %H:%M"), title)
+        };
+        writeln!(f, "{:<10} {:<17} TITLE", "ACTION", "STARTS (UTC)")?;
+        for event in &self.created {
+            row(f, "create", event.start_time, &event.title)?;
+        }
+        for update in &self.updated {
+            row(f, "update", update.start_time, &update.title)?;
+            for change in &update.changes {
+                writeln!(f, "             {}: {} -> {}", change.field, change.before, change.after)?;
+            }
+        }
+        for event in &self.duplicates {
+            row(f, "duplicate", event.start_time, &event.title)?;
+        }
+        Ok(())
+    }
+}
+
+/// The fields a scrape updates, as stored.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+struct Updatable {
+    title: String,
+    description: Option<String>,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    price_min: Option<f64>,
+    price_max: Option<f64>,
+    is_free: bool,
+    image_url: Option<String>,
+    categories: Option<Vec<String>>,
+}
+
+const UPDATABLE_COLUMNS: &str =
+    "title, description, start_time, end_time, price_min, price_max, is_free, image_url, categories";
+
+impl Updatable {
+    /// The fields that differ in `after`, in column order.
+    fn changes(&self, after: &Updatable) -> Vec<FieldChange> {
+        let (Ok(Value::Object(before)), Ok(Value::Object(after))) = (serde_json::to_value(self), serde_json::to_value(after)) else {
+            return Vec::new();
+        };
+        UPDATABLE_COLUMNS
+            .split(", ")
+            .filter(|field| before.get(*field) != after.get(*field))
+            .map(|field| FieldChange {
+                field: field.to_string(),
+                before: before.get(field).cloned().unwrap_or_default(),
+                after: after.get(field).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// What saving one event did, and to which event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Saved {
+    Created(Uuid),
+    Updated { id: Uuid, changes: Vec<FieldChange> },
+    Unchanged(Uuid),
+}
+
+impl Saved {
+    /// The event stored, or matched.
+    pub fn id(&self) -> Uuid {
+        match self {
+            Saved::Created(id) | Saved::Updated { id, .. } | Saved::Unchanged(id) => *id,
+        }
+    }
+}
+
+// =============================================================================
+// PERSISTENCE
+// =============================================================================
+
+/// Stores `events` from the source `source_id`, credited to
+/// `source_name`. Each event is saved in its own transaction, so one bad
+/// event doesn't cost the rest; a dry run rolls each one back.
+pub async fn persist_scraped_events(
+    pool: &PgPool,
+    events: Vec<ScrapedEvent>,
+    source_id: &str,
+    source_name: &str,
+    dry_run: bool,
+) -> PersistSummary {
+    let mut summary = PersistSummary::default();
+    let mut diff = ScrapeDiff::default();
+    let mut seen = HashSet::new();
+    let now = Utc::now();
+    let mut category_map = CategoryMap::load(pool, source_id).await.unwrap_or_else(|e| {
+        tracing::warn!(source = %source_name, error = %e, "couldn't load category mappings");
+        CategoryMap::default()
+    });
+
+    for scraped in events {
+        let url = scraped.source_url.clone();
+        let mut event = scraped.clone().into_create_event(source_name);
+        let guess = event.categories.take().and_then(|categories| categories.into_iter().next());
+        event.categories = category_map
+            .resolve(scraped.external_category.as_deref(), guess)
+            .map(|category| vec![category]);
+        let reported = DiffEvent {
+            title: event.title.clone(),
+            venue: event.venue.clone(),
+            start_time: event.start_time,
+            source_url: event.source_url.clone(),
+        };
+        if let Err(errors) = event.validate(now) {
+            let e = AppError::from(errors);
+            tracing::warn!(source = %source_name, url = %url, error = %e, "quarantined a scraped event");
+            summary.skipped += 1;
+            if !dry_run {
+                if let Err(e) = quarantine::add(pool, source_name, &scraped, &e.to_string()).await {
+                    tracing::warn!(source = %source_name, url = %url, error = %e, "couldn't quarantine a scraped event");
+                }
+            }
+            continue;
+        }
+
+        if let Some(key) = show_key(&event) {
+            if !seen.insert(key) {
+                tracing::debug!(source = %source_name, url = %url, "same show twice in one scrape");
+                summary.duplicates += 1;
+                diff.duplicates.push(reported);
+                continue;
+            }
+        }
+
+        match save(pool, event, scraped.external_id.as_deref(), now, dry_run).await {
+            Ok(Saved::Created(id)) => {
+                summary.created += 1;
+                if !dry_run {
+                    summary.created_ids.push(id);
+                }
+                diff.created.push(reported);
+            }
+            Ok(Saved::Updated { id, changes }) => {
+                summary.updated += 1;
+                diff.updated.push(DiffUpdate {
+                    event_id: id,
+                    title: reported.title,
+                    start_time: reported.start_time,
+                    source_url: reported.source_url,
+                    changes,
+                });
+            }
+            Ok(Saved::Unchanged(_)) => summary.unchanged += 1,
+            Err(e) => {
+                tracing::warn!(source = %source_name, url = %url, error = %e, "skipped a scraped event");
+                summary.skipped += 1;
+            }
+        }
+    }
+
+    if !dry_run {
+        if let Err(e) = category_map.record_unmapped(pool, source_id).await {
+            tracing::warn!(source = %source_name, error = %e, "couldn't count unmapped categories");
+        }
+    }
+
+    summary.diff = dry_run.then_some(diff);
+    summary
+}
+
+/// Stores one validated event, listed by its source as `external_id` if
+/// it has one. A dry run rolls the store back.
+pub async fn save(
+    pool: &PgPool,
+    event: CreateEvent,
+    external_id: Option<&str>,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<Saved, AppError> {
+    let mut tx = pool.begin().await?;
+    let (source_url, source_name) = (event.source_url.clone(), event.source_name.clone());
+
+    // A listing a merge absorbed is recognized but not written over
+    let by_url: Option<(Uuid, bool)> = sqlx::query_as(
+        r#"
+        SELECT id, TRUE FROM events WHERE source_url = $1
+        UNION ALL
+        SELECT event_id, merged_from IS NULL FROM event_sources WHERE source_url = $1
+        ORDER BY 2 DESC
+        LIMIT 1
+        "#,
+    )
+        .bind(&event.source_url)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let existing = match (by_url, &event.venue) {
+        (Some(found), _) => Some(found),
+        // The closest start wins; a scraped row in the same hour would
+        // collide with this one in idx_events_scraped_show, so it's a match
+        (None, Some(venue)) => sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM events
+            WHERE venue IS NOT NULL
+              AND normalize_event_title(title) = normalize_event_title($1)
+              AND normalize_venue_name(venue) = normalize_venue_name($2)
+              AND (start_time BETWEEN $3 - $4 AND $3 + $4
+                   OR (scraped AND date_trunc('hour', start_time AT TIME ZONE 'UTC')
+                                   = date_trunc('hour', $3 AT TIME ZONE 'UTC')))
+            ORDER BY abs(extract(epoch FROM start_time - $3)), created_at
+            LIMIT 1
+            "#,
+        )
+            .bind(&event.title)
+            .bind(venue)
+            .bind(event.start_time)
+            .bind(MATCH_WINDOW)
+            .fetch_optional(&mut *tx)
+            .await?
+            .map(|id| (id, true)),
+        (None, None) => None,
+    };
+
+    let saved = match existing {
+        Some((id, true)) => {
+            let is_free = event.resolved_is_free();
+            let before: Updatable = sqlx::query_as(&format!("SELECT {} FROM events WHERE id = $1", UPDATABLE_COLUMNS))
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+            // Blank scraped fields keep what we have, and so do an editor's
+            // categories; only real changes count (and bump updated_at)
+            let categories = format!(
+                "CASE WHEN $10::text[] IS NOT NULL
+                           AND (categories IS NULL OR cardinality(categories) = 0
+                                OR category_source IN ('{}', 'llm'))
+                      THEN $10 ELSE categories END",
+                CATEGORY_SOURCE_SCRAPER
+            );
+            let query = format!(
+                r#"
+                UPDATE events
+                SET title = $2, description = COALESCE($3, description),
+                    start_time = $4, end_time = COALESCE($5, end_time),
+                    price_min = COALESCE($6, price_min), price_max = COALESCE($7, price_max),
+                    is_free = $8, image_url = COALESCE($9, image_url),
+                    category_source = CASE WHEN categories IS DISTINCT FROM {categories}
+                                           THEN '{scraper}' ELSE category_source END,
+                    categories = {categories}, updated_at = NOW()
+                WHERE id = $1
+                  AND (title, description, start_time, end_time, price_min, price_max, is_free, image_url, categories)
+                      IS DISTINCT FROM
+                      ($2, COALESCE($3, description), $4, COALESCE($5, end_time),
+                       COALESCE($6, price_min), COALESCE($7, price_max), $8, COALESCE($9, image_url), {categories})
+                RETURNING {columns}
+                "#,
+                categories = categories,
+                scraper = CATEGORY_SOURCE_SCRAPER,
+                columns = UPDATABLE_COLUMNS
+            );
+            let after: Option<Updatable> = sqlx::query_as(&query)
+                .bind(id)
+                .bind(&event.title)
+                .bind(&event.description)
+                .bind(event.start_time)
+                .bind(event.end_time)
+                .bind(event.price_min)
+                .bind(event.price_max)
+                .bind(is_free)
+                .bind(&event.image_url)
+                .bind(&event.categories)
+                .fetch_optional(&mut *tx)
+                .await?;
+            match after {
+                Some(after) => Saved::Updated { id, changes: before.changes(&after) },
+                None => Saved::Unchanged(id),
+            }
+        }
+        Some((id, false)) => Saved::Unchanged(id),
+        None => {
+            let created = insert_event(&mut tx, event, now).await?;
+            sqlx::query(
+                r#"
+                UPDATE events
+                SET scraped = TRUE, last_seen_at = $2,
+                    category_source = CASE WHEN cardinality(categories) > 0 THEN $3 END
+                WHERE id = $1
+                "#,
+            )
+                .bind(created.id)
+                .bind(now)
+                .bind(CATEGORY_SOURCE_SCRAPER)
+                .execute(&mut *tx)
+                .await?;
+            Saved::Created(created.id)
+        }
+    };
+
+    // Still listed (see `stale.rs`); stamping alone leaves updated_at be
+    if !matches!(saved, Saved::Created(_)) {
+        sqlx::query("UPDATE events SET last_seen_at = $2 WHERE id = $1")
+            .bind(saved.id())
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // One more place it's listed, which may be the better link
+    let listed = attribution::record(&mut tx, saved.id(), &source_url, source_name.as_deref(), external_id, now).await?;
+    if listed && !matches!(saved, Saved::Created(_)) {
+        attribution::choose_primary(&mut tx, saved.id()).await?;
+    }
+
+    // Dropping the transaction rolls it back
+    if !dry_run {
+        tx.commit().await?;
+    }
+    Ok(saved)
+}
+
+/// An event's normalized title, venue and start hour, as the unique index
+/// sees them; `None` without a venue.
+fn show_key(event: &CreateEvent) -> Option<(String, String, DateTime<Utc>)> {
+    let venue = normalize_venue_name(event.venue.as_deref()?);
+    let hour = event.start_time.duration_trunc(Duration::hours(1)).ok()?;
+    Some((normalize_title(&event.title), venue, hour))
+}
+
+/// Mirrors `normalize_event_title` (migration 031).
+fn normalize_title(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Mirrors `normalize_venue_name` (migration 007): lowercased, apostrophes
+/// dropped, other runs of non-alphanumerics collapsed to one space.
+fn normalize_venue_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace(['\'', '’'], "")
+        .split(|c: char| !c.is_ascii_lowercase() && !c.is_ascii_digit())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_ignore_case_spacing_and_punctuation_in_venues() {
+        let start: DateTime<Utc> = "2026-11-06T02:10:00Z".parse().unwrap();
+        let event = |title: &str, venue: &str, start| CreateEvent {
+            venue: Some(venue.to_string()),
+            ..ScrapedEvent::new(title, "https://example.com/e", start).into_create_event("Test")
+        };
+
+        let key = show_key(&event("Red Dirt  Revival", "Cain's Ballroom", start)).unwrap();
+        assert_eq!(key.0, "red dirt revival");
+        assert_eq!(key.1, "cains ballroom");
+        assert_eq!(key.2, "2026-11-06T02:00:00Z".parse::<DateTime<Utc>>().unwrap());
+
+        assert_eq!(show_key(&event("RED DIRT REVIVAL", " CAINS  BALLROOM ", start + Duration::minutes(40))), Some(key.clone()));
+        assert_ne!(show_key(&event("Red Dirt Revival", "Cain's Ballroom", start + Duration::hours(1))), Some(key));
+
+        let no_venue = ScrapedEvent::new("Jazz Night", "https://example.com/j", start).into_create_event("Test");
+        assert_eq!(show_key(&no_venue), None);
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn the_same_show_is_stored_once() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        // Ten past the hour, so five minutes later is the same hour
+        let start = (Utc::now() + Duration::days(4)).duration_trunc(Duration::hours(1)).unwrap() + Duration::minutes(10);
+        let url = |n: u32| format!("https://dedup.example/{}/{}", run, n);
+        let show = |title: &str, n: u32, minutes: i64| ScrapedEvent {
+            venue: Some("The Vanguard".to_string()),
+            ..ScrapedEvent::new(title, &url(n), start + Duration::minutes(minutes))
+        };
+        let title = format!("Trivia Night {}", run);
+        let batch = vec![
+            show(&title, 1, 0),
+            // Same show, listed twice on one page
+            show(&format!("  {}  ", title.to_uppercase()), 2, 5),
+            ScrapedEvent::new(&title, &url(3), start),
+        ];
+
+        let first = persist_scraped_events(&pool, batch.clone(), "dedup", "Dedup", false).await;
+        assert_eq!((first.created, first.duplicates, first.created_ids.len()), (2, 1, 2));
+
+        // The same batch again creates nothing
+        let again = persist_scraped_events(&pool, batch.clone(), "dedup", "Dedup", false).await;
+        assert_eq!(again, PersistSummary { unchanged: 2, duplicates: 1, ..Default::default() });
+
+        // Re-slugged page, a few minutes later, new description: same row
+        let moved = ScrapedEvent {
+            description: Some("Teams of up to six".to_string()),
+            ..show(&title, 4, 10)
+        };
+        let update = persist_scraped_events(&pool, vec![moved], "dedup", "Dedup", false).await;
+        assert_eq!(update, PersistSummary { updated: 1, ..Default::default() });
+
+        let rows: Vec<(String, Option<String>, DateTime<Utc>, bool)> = sqlx::query_as(
+            "SELECT source_url, description, start_time, scraped FROM events WHERE source_url LIKE $1 ORDER BY source_url",
+        )
+            .bind(format!("https://dedup.example/{}/%", run))
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, url(1));
+        assert_eq!(rows[0].1.as_deref(), Some("Teams of up to six"));
+        assert_eq!(rows[0].2.timestamp(), (start + Duration::minutes(10)).timestamp());
+        assert!(rows[0].3);
+
+        // An hour later is another show
+        let late = persist_scraped_events(&pool, vec![show(&title, 5, 75)], "dedup", "Dedup", true).await;
+        assert_eq!(late.created, 1);
+
+        sqlx::query("DELETE FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://dedup.example/{}/%", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn a_dry_run_reports_a_diff_and_writes_nothing() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let venue = format!("Diff Hall {}", run);
+        let start = (Utc::now() + Duration::days(5)).duration_trunc(Duration::hours(1)).unwrap();
+        let url = |n: u32| format!("https://diff.example/{}/{}", run, n);
+        let show = |title: &str, n: u32| ScrapedEvent {
+            venue: Some(venue.clone()),
+            ..ScrapedEvent::new(title, &url(n), start + Duration::hours(n as i64))
+        };
+        let stored = persist_scraped_events(&pool, vec![show("Open Jam", 1), show("Trivia", 2)], "diff", "Diff", false).await;
+        assert_eq!((stored.created, stored.diff), (2, None));
+
+        // Every byte of every row this test could touch
+        let snapshot = || {
+            sqlx::query_scalar::<_, Option<String>>(
+                "SELECT md5(string_agg(e::text, '|' ORDER BY e.id)) FROM events e WHERE venue = $1 OR source_url LIKE $2",
+            )
+                .bind(&venue)
+                .bind(format!("https://diff.example/{}/%", run))
+                .fetch_one(&pool)
+        };
+        let before = snapshot().await.unwrap();
+
+        let changed = ScrapedEvent { description: Some("Sign-up at 7".to_string()), ..show("Open Jam", 1) };
+        // Same hour as "Late Show"
+        let duplicate = ScrapedEvent { start_time: start + Duration::hours(3), ..show("LATE  SHOW", 4) };
+        let batch = vec![changed, show("Trivia", 2), show("Late Show", 3), duplicate];
+        let dry = persist_scraped_events(&pool, batch, "diff", "Diff", true).await;
+        assert_eq!((dry.created, dry.updated, dry.unchanged, dry.duplicates), (1, 1, 1, 1));
+        assert_eq!(snapshot().await.unwrap(), before);
+
+        let diff = dry.diff.unwrap();
+        assert_eq!(diff.created.len(), 1);
+        assert_eq!((diff.created[0].title.as_str(), diff.created[0].source_url.as_str()), ("Late Show", url(3).as_str()));
+        assert_eq!(diff.duplicates[0].source_url, url(4));
+        assert_eq!(diff.updated.len(), 1);
+        assert_eq!(diff.updated[0].source_url, url(1));
+        assert_eq!(
+            diff.updated[0].changes,
+            [FieldChange { field: "description".to_string(), before: Value::Null, after: "Sign-up at 7".into() }]
+        );
+
+        let table = diff.to_string();
+        assert!(table.starts_with("ACTION"), "{}", table);
+        assert!(table.contains("create     "), "{}", table);
+        assert!(table.contains("description: null -> \"Sign-up at 7\""), "{}", table);
+        assert!(table.contains("duplicate  "), "{}", table);
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn every_source_is_kept_and_the_best_one_is_primary() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4().simple().to_string();
+        let venue = format!("Attribution Hall {}", run);
+        let start = (Utc::now() + Duration::days(6)).duration_trunc(Duration::hours(1)).unwrap();
+        let show = |source_url: &str, external_id: Option<&str>| ScrapedEvent {
+            venue: Some(venue.clone()),
+            external_id: external_id.map(str::to_string),
+            ..ScrapedEvent::new("Songwriter Night", source_url, start)
+        };
+        let eventbrite = format!("https://www.eventbrite.com/e/songwriter-night-{}", run);
+        let city = format!("https://www.cityoftulsa.org/calendar/{}", run);
+        let venue_site = format!("https://attribution-{}.example/shows/songwriter-night", run);
+        let primary = || {
+            sqlx::query_as::<_, (Uuid, String, Option<String>)>("SELECT id, source_url, source_name FROM events WHERE venue = $1")
+                .bind(&venue)
+                .fetch_all(&pool)
+        };
+
+        // Found on Eventbrite first
+        let first = persist_scraped_events(&pool, vec![show(&eventbrite, Some("eb-1"))], "eventbrite", "Eventbrite", false).await;
+        assert_eq!(first.created, 1);
+        sqlx::query("UPDATE venues SET website = $2 WHERE id = (SELECT venue_id FROM events WHERE id = $1)")
+            .bind(first.created_ids[0])
+            .bind(format!("www.attribution-{}.example", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // The city's listing beats the aggregator's
+        persist_scraped_events(&pool, vec![show(&city, None)], "tulsa_city", "City of Tulsa", false).await;
+        let rows = primary().await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].1.as_str(), rows[0].2.as_deref()), (city.as_str(), Some("City of Tulsa")));
+
+        // The venue's own beats both, and seeing Eventbrite again changes nothing
+        persist_scraped_events(&pool, vec![show(&venue_site, None)], "attribution_hall", "Attribution Hall", false).await;
+        let again = persist_scraped_events(&pool, vec![show(&eventbrite, Some("eb-1"))], "eventbrite", "Eventbrite", false).await;
+        assert_eq!((again.created, again.unchanged), (0, 1));
+        let rows = primary().await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].1, venue_site);
+
+        let sources = attribution::list(&pool, rows[0].0).await.unwrap();
+        let listed: Vec<(&str, bool)> = sources.iter().map(|source| (source.source_url.as_str(), source.primary)).collect();
+        assert_eq!(listed, [(venue_site.as_str(), true), (eventbrite.as_str(), false), (city.as_str(), false)]);
+        assert_eq!(sources[1].external_id.as_deref(), Some("eb-1"));
+        assert_eq!(sources[1].source_name.as_deref(), Some("Eventbrite"));
+        assert!(sources[1].last_seen_at > sources[1].first_seen_at);
+
+        sqlx::query("DELETE FROM events WHERE venue = $1")
+            .bind(&venue)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM venues WHERE name = $1")
+            .bind(&venue)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,720 @@
+//! # iCalendar Feeds
+//!
+//! Ingests any `.ics` feed (RFC 5545) with no site-specific parsing, so a
+//! venue or organization that publishes one is a line of configuration.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Configuration
+//! `ICAL_FEEDS` lists the feeds, separated by `;`, each as
+//! `source_id|url|category|venue` (category and venue optional):
+//! ```text
+//! ICAL_FEEDS=guthrie_green|https://www.guthriegreen.com/events.ics|community|Guthrie Green
+//! ```
+//! The venue, when given, is every event's venue and the feed's
+//! `source_name`; otherwise each event's venue is its LOCATION up to the
+//! first comma.
+//!
+//! Feeds can also be added as `ical` rows in `scrape_sources` (see
+//! `scraper::sources`), which name the source themselves; `ICAL_FEEDS`
+//! entries are copied there at startup.
+//!
+//! ## Mapping
+//! | iCalendar | ScrapedEvent |
+//! |-----------|--------------|
+//! | SUMMARY | title |
+//! | DESCRIPTION | description |
+//! | DTSTART / DTEND or DURATION | start_time / end_time |
+//! | LOCATION | location (and venue, see above) |
+//! | URL | source_url (the feed URL + `#UID` without one) |
+//! | UID | external_id |
+//! | CATEGORIES | tags (the first is also the `external_category`) |
+//!
+//! ## Times
+//! - `...Z` is UTC; `TZID=` names an IANA zone; a floating time (neither)
+//!   is Tulsa local time. An unknown TZID is read as Tulsa time, logged.
+//! - All-day events (`VALUE=DATE`) start at noon, as in the city
+//!   calendar; one spanning several days ends at noon on its last day.
+//!
+//! ## Recurrence
+//! Simple RRULEs are expanded: FREQ=DAILY/WEEKLY/MONTHLY/YEARLY with
+//! INTERVAL, COUNT, UNTIL and (weekly) BYDAY, minus EXDATEs. Each
+//! occurrence is its own event, with the date as the URL fragment so its
+//! `source_url` is unique. Anything fancier ("first Friday") keeps only
+//! its first occurrence and is logged.
+//!
+//! ## Window
+//! Only events still running now and starting within `HORIZON_DAYS` are
+//! kept, recurring or not. Cancelled events (`STATUS:CANCELLED`) are
+//! skipped.
+
+use std::collections::HashSet;
+
+use axum::async_trait;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use reqwest::Url;
+
+use crate::scraper::dates::{local_to_utc, TULSA_TZ};
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// How far ahead events (and recurrences) are kept.
+pub const HORIZON_DAYS: i64 = 90;
+
+/// Start hour (local) for an all-day event.
+pub const ALL_DAY_HOUR: u32 = 12;
+
+/// Stops a recurrence that never ends from looping forever.
+const MAX_PERIODS: u32 = 10_000;
+
+/// One configured feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcalFeed {
+    pub source_id: String,
+    /// Credited as the events' `source_name`
+    pub name: String,
+    pub url: String,
+    pub default_category: Option<String>,
+    pub default_venue: Option<String>,
+}
+
+impl IcalFeed {
+    /// Feeds from `ICAL_FEEDS` (none when unset).
+    pub fn from_env() -> Vec<Self> {
+        std::env::var("ICAL_FEEDS").map(|list| Self::parse_list(&list)).unwrap_or_default()
+    }
+
+    /// Parses an `ICAL_FEEDS` value; malformed entries are logged and
+    /// skipped.
+    pub fn parse_list(list: &str) -> Vec<Self> {
+        list.split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let fields: Vec<&str> = entry.split('|').map(str::trim).collect();
+                let optional = |i: usize| fields.get(i).filter(|field| !field.is_empty()).map(|field| field.to_string());
+                match (fields.first(), fields.get(1)) {
+                    (Some(id), Some(url)) if !id.is_empty() && Url::parse(url).is_ok() => Some(Self {
+                        source_id: id.to_string(),
+                        name: optional(3).unwrap_or_else(|| id.to_string()),
+                        url: url.to_string(),
+                        default_category: optional(2),
+                        default_venue: optional(3),
+                    }),
+                    _ => {
+                        tracing::warn!(entry = %entry, "ICAL_FEEDS: expected source_id|url|category|venue");
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+// =============================================================================
+// SCRAPER
+// =============================================================================
+
+/// Scraper for one iCalendar feed.
+pub struct IcalScraper {
+    feed: IcalFeed,
+}
+
+impl IcalScraper {
+    pub fn new(feed: IcalFeed) -> Self {
+        Self { feed }
+    }
+}
+
+#[async_trait]
+impl EventScraper for IcalScraper {
+    fn name(&self) -> &str {
+        &self.feed.name
+    }
+
+    fn source_id(&self) -> &str {
+        &self.feed.source_id
+    }
+
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        let now = Utc::now();
+        fetcher.get_events(&self.feed.url, |ics| parse_calendar(ics, &self.feed, now)).await
+    }
+}
+
+// =============================================================================
+// CONTENT LINES
+// =============================================================================
+
+/// One unfolded content line: `NAME;PARAM=value:VALUE`.
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    /// Uppercased
+    name: String,
+    /// Uppercased names, unquoted values
+    params: Vec<(String, String)>,
+    /// Raw: still escaped
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// The value as TEXT, unescaped.
+    fn text(&self) -> String {
+        unescape(&self.value)
+    }
+}
+
+/// Joins folded lines: a line starting with a space or tab continues the
+/// previous one, minus that one character.
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits a content line at the first `:` outside quotes, then its head
+/// at `;`s outside quotes.
+fn parse_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (at, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parts.push(&line[start..at]);
+                start = at + 1;
+            }
+            ':' if !quoted => {
+                parts.push(&line[start..at]);
+                let name = parts.first()?.trim().to_uppercase();
+                let params = parts[1..]
+                    .iter()
+                    .filter_map(|param| param.split_once('='))
+                    .map(|(key, value)| (key.trim().to_uppercase(), value.trim().trim_matches('"').to_string()))
+                    .collect();
+                return Some(Property { name, params, value: line[at + 1..].to_string() });
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Undoes TEXT escaping: `\\`, `\;`, `\,` and `\n` / `\N`.
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+// =============================================================================
+// CALENDAR
+// =============================================================================
+
+/// Parses a feed into events running between `now` and the horizon.
+pub fn parse_calendar(ics: &str, feed: &IcalFeed, now: DateTime<Utc>) -> Result<Vec<ScrapedEvent>, ScraperError> {
+    let lines = unfold(ics);
+    if !lines.first().is_some_and(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(ScraperError::Parse {
+            selector: "BEGIN:VCALENDAR".to_string(),
+            context: feed.url.clone(),
+        });
+    }
+
+    // VEVENTs' own properties; nested components (VALARM) are skipped
+    let mut vevents: Vec<Vec<Property>> = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    let mut nested = 0;
+    for property in lines.iter().filter_map(|line| parse_property(line)) {
+        let component = property.value.trim().to_uppercase();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if component == "VEVENT" => current = Some(Vec::new()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if component == "VEVENT" => vevents.extend(current.take()),
+            (_, Some(properties)) if nested == 0 => properties.push(property),
+            _ => {}
+        }
+    }
+
+    let horizon = now + Duration::days(HORIZON_DAYS);
+    Ok(vevents.iter().flat_map(|vevent| expand(vevent, feed, now, horizon)).collect())
+}
+
+/// A DTSTART/DTEND/EXDATE value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IcalTime {
+    /// Wall-clock time in `zone` (noon for all-day dates)
+    naive: NaiveDateTime,
+    /// `None` for UTC
+    zone: Option<Tz>,
+    all_day: bool,
+}
+
+impl IcalTime {
+    fn parse(property: &Property) -> Option<Self> {
+        let value = property.value.trim();
+        if property.param("VALUE") == Some("DATE") || value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            return Some(Self { naive: noon(date), zone: Some(TULSA_TZ), all_day: true });
+        }
+
+        if let Some(utc) = value.strip_suffix('Z') {
+            let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            return Some(Self { naive, zone: None, all_day: false });
+        }
+
+        let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        let zone = match property.param("TZID") {
+            Some(tzid) => tzid.trim_start_matches('/').parse::<Tz>().unwrap_or_else(|_| {
+                tracing::warn!(tzid = %tzid, "iCal: unknown TZID, reading it as Tulsa time");
+                TULSA_TZ
+            }),
+            None => TULSA_TZ,
+        };
+        Some(Self { naive, zone: Some(zone), all_day: false })
+    }
+
+    /// `naive` (an occurrence of this time) in UTC.
+    fn to_utc(self, naive: NaiveDateTime) -> DateTime<Utc> {
+        match self.zone {
+            Some(zone) => local_to_utc(zone, naive.date(), naive.time()),
+            None => naive.and_utc(),
+        }
+    }
+}
+
+fn noon(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_opt(ALL_DAY_HOUR, 0, 0).expect("valid hour"))
+}
+
+/// A VEVENT's occurrences in the window, as events.
+fn expand(vevent: &[Property], feed: &IcalFeed, now: DateTime<Utc>, horizon: DateTime<Utc>) -> Vec<ScrapedEvent> {
+    let get = |name: &str| vevent.iter().find(|property| property.name == name);
+
+    let title = get("SUMMARY").map(Property::text).unwrap_or_default();
+    let uid = get("UID").map(Property::text).unwrap_or_default();
+    if get("STATUS").is_some_and(|status| status.value.trim().eq_ignore_ascii_case("CANCELLED")) {
+        tracing::debug!(uid = %uid, "iCal: skipping a cancelled event");
+        return Vec::new();
+    }
+    let Some(start) = get("DTSTART").and_then(IcalTime::parse) else {
+        tracing::warn!(uid = %uid, title = %title, "iCal: event without a readable DTSTART");
+        return Vec::new();
+    };
+    if title.trim().is_empty() {
+        tracing::warn!(uid = %uid, "iCal: event without a SUMMARY");
+        return Vec::new();
+    }
+
+    // Wall-clock length, so every occurrence lasts as long as the first
+    let length = match (get("DTEND").and_then(IcalTime::parse), get("DURATION")) {
+        // DTEND of an all-day event is the (exclusive) day after
+        (Some(end), _) if start.all_day => Some(end.naive - start.naive - Duration::days(1)).filter(|d| *d > Duration::zero()),
+        (Some(end), _) => Some(end.naive - start.naive),
+        (None, Some(duration)) => parse_duration(duration.value.trim()),
+        (None, None) => None,
+    };
+
+    let rule = get("RRULE").and_then(|rrule| {
+        let rule = Rule::parse(&rrule.value);
+        if rule.is_none() {
+            tracing::info!(uid = %uid, rrule = %rrule.value, "iCal: RRULE too complex, keeping the first occurrence");
+        }
+        rule
+    });
+    let occurrences = match &rule {
+        Some(rule) => rule.occurrences(start, length.unwrap_or_default(), now, horizon),
+        None => vec![start.naive],
+    };
+
+    let excluded: HashSet<DateTime<Utc>> = vevent
+        .iter()
+        .filter(|property| property.name == "EXDATE")
+        .flat_map(|exdate| {
+            exdate.value.split(',').filter_map(|value| {
+                let single = Property { value: value.to_string(), ..exdate.clone() };
+                IcalTime::parse(&single).map(|time| time.to_utc(time.naive))
+            })
+        })
+        .collect();
+
+    let location = get("LOCATION").map(Property::text).filter(|text| !text.trim().is_empty());
+    let (venue, venue_address) = match (&feed.default_venue, location.as_deref().map(|l| l.split_once(','))) {
+        (Some(venue), Some(Some((_, address)))) => (Some(venue.clone()), Some(address.trim().to_string())),
+        (Some(venue), _) => (Some(venue.clone()), None),
+        (None, Some(Some((venue, address)))) => (Some(venue.trim().to_string()), Some(address.trim().to_string())),
+        (None, Some(None)) => (location.clone(), None),
+        (None, None) => (None, None),
+    };
+    let base_url = get("URL")
+        .and_then(|url| Url::parse(url.value.trim()).ok())
+        .or_else(|| {
+            let mut url = Url::parse(&feed.url).ok()?;
+            url.set_fragment(Some(&uid));
+            Some(url)
+        });
+    let Some(base_url) = base_url else { return Vec::new() };
+    let tags: Vec<String> = get("CATEGORIES")
+        .map(|categories| categories.text().split(',').map(|c| c.trim().to_lowercase()).filter(|c| !c.is_empty()).collect())
+        .unwrap_or_default();
+
+    occurrences
+        .into_iter()
+        .filter_map(|occurrence| {
+            let start_time = start.to_utc(occurrence);
+            let end_time = length.map(|length| start.to_utc(occurrence + length));
+            let running = end_time.unwrap_or(start_time) >= now && start_time <= horizon;
+            if !running || excluded.contains(&start_time) {
+                return None;
+            }
+
+            let mut source_url = base_url.clone();
+            if rule.is_some() {
+                source_url.set_fragment(Some(&occurrence.date().to_string()));
+            }
+
+            Some(ScrapedEvent {
+                description: get("DESCRIPTION").map(Property::text),
+                venue: venue.clone(),
+                venue_address: venue_address.clone(),
+                location: location.clone(),
+                end_time,
+                category: feed.default_category.clone(),
+                external_category: tags.first().cloned(),
+                external_id: Some(uid.clone()).filter(|uid| !uid.is_empty()),
+                tags: tags.clone(),
+                ..ScrapedEvent::new(&title, source_url.as_str(), start_time)
+            })
+        })
+        .collect()
+}
+
+/// `P1W`, `PT2H30M`, `P1DT12H` (sign ignored).
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim_start_matches(['+', '-']).strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            'T' => {}
+            '0'..='9' => number.push(c),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    number.is_empty().then_some(total)
+}
+
+// =============================================================================
+// RECURRENCE
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A simple RRULE.
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    freq: Freq,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Utc>>,
+    /// Weekly only
+    by_day: Vec<Weekday>,
+}
+
+impl Rule {
+    /// `None` for rules we don't expand (unknown parts, "1FR"-style days).
+    fn parse(value: &str) -> Option<Self> {
+        let mut rule = Rule { freq: Freq::Daily, interval: 1, count: None, until: None, by_day: Vec::new() };
+        let mut has_freq = false;
+
+        for part in value.trim().split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part.split_once('=')?;
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    has_freq = true;
+                    rule.freq = match value.to_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        _ => return None,
+                    };
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|n| *n > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => {
+                    let until = Property { name: "UNTIL".to_string(), params: Vec::new(), value: value.to_string() };
+                    let until = IcalTime::parse(&until)?;
+                    // A date-only UNTIL includes that whole day
+                    let naive = if until.all_day { until.naive.date().and_hms_opt(23, 59, 59)? } else { until.naive };
+                    rule.until = Some(until.to_utc(naive));
+                }
+                "BYDAY" => {
+                    rule.by_day = value.split(',').map(weekday).collect::<Option<_>>()?;
+                }
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+
+        let by_day_ok = rule.by_day.is_empty() || rule.freq == Freq::Weekly;
+        (has_freq && by_day_ok).then_some(rule)
+    }
+
+    /// Occurrence start times (wall clock) that are still running at `now`
+    /// and start by `horizon`. COUNT counts from DTSTART, as the RFC says,
+    /// including occurrences already past.
+    fn occurrences(&self, start: IcalTime, length: Duration, now: DateTime<Utc>, horizon: DateTime<Utc>) -> Vec<NaiveDateTime> {
+        let mut kept = Vec::new();
+        let mut produced = 0;
+
+        for period in 0..MAX_PERIODS {
+            let step = period * self.interval;
+            for candidate in self.candidates(start.naive, step) {
+                let at = start.to_utc(candidate);
+                if at > horizon || self.until.is_some_and(|until| at > until) {
+                    return kept;
+                }
+
+                produced += 1;
+                if start.to_utc(candidate + length) >= now {
+                    kept.push(candidate);
+                }
+                if self.count.is_some_and(|count| produced >= count) {
+                    return kept;
+                }
+            }
+        }
+        kept
+    }
+
+    /// Occurrences in the period `step` periods after DTSTART's.
+    fn candidates(&self, start: NaiveDateTime, step: u32) -> Vec<NaiveDateTime> {
+        match self.freq {
+            Freq::Daily => vec![start + Duration::days(i64::from(step))],
+            Freq::Weekly if self.by_day.is_empty() => vec![start + Duration::weeks(i64::from(step))],
+            Freq::Weekly => {
+                let monday = start.date() - Duration::days(i64::from(start.weekday().num_days_from_monday()));
+                let week = monday + Duration::weeks(i64::from(step));
+                let mut days: Vec<NaiveDateTime> = self
+                    .by_day
+                    .iter()
+                    .map(|day| (week + Duration::days(i64::from(day.num_days_from_monday()))).and_time(start.time()))
+                    .filter(|candidate| *candidate >= start)
+                    .collect();
+                days.sort();
+                days
+            }
+            // The 31st only in months that have one (no clamping)
+            Freq::Monthly | Freq::Yearly => {
+                let months = if self.freq == Freq::Monthly { step } else { step * 12 };
+                start
+                    .checked_add_months(Months::new(months))
+                    .filter(|candidate| candidate.day() == start.day())
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code.trim().to_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEEKLY: &str = include_str!("../../../tests/fixtures/ical/weekly.ics");
+    const MIXED: &str = include_str!("../../../tests/fixtures/ical/mixed.ics");
+
+    fn feed(venue: Option<&str>) -> IcalFeed {
+        IcalFeed {
+            source_id: "test_feed".to_string(),
+            name: venue.unwrap_or("test_feed").to_string(),
+            url: "https://example.org/events.ics".to_string(),
+            default_category: Some("community".to_string()),
+            default_venue: venue.map(str::to_string),
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn unfolds_and_unescapes() {
+        let lines = unfold("DESCRIPTION:Bring a mat\\, water\r\n  and a friend.\\nAll le\r\n\tvels\\; welcome\r\n");
+        assert_eq!(lines.len(), 1);
+        let property = parse_property(&lines[0]).unwrap();
+        assert_eq!(property.text(), "Bring a mat, water and a friend.\nAll levels; welcome");
+
+        let quoted = parse_property("DTSTART;TZID=\"America/New_York\";X-NOTE=\"a:b;c\":20260320T200000").unwrap();
+        assert_eq!(quoted.param("TZID"), Some("America/New_York"));
+        assert_eq!(quoted.param("X-NOTE"), Some("a:b;c"));
+        assert_eq!(quoted.value, "20260320T200000");
+    }
+
+    #[test]
+    fn expands_weekly_rules_across_dst() {
+        let events = parse_calendar(WEEKLY, &feed(Some("Guthrie Green")), utc("2026-03-01T00:00:00Z")).unwrap();
+
+        let yoga: Vec<&ScrapedEvent> = events.iter().filter(|e| e.title == "Yoga on the Green").collect();
+        let starts: Vec<DateTime<Utc>> = yoga.iter().map(|e| e.start_time).collect();
+        // Tuesdays at 6 PM until March 31, minus the EXDATE on the 17th;
+        // 6 PM is CST on the 3rd and CDT after March 8
+        assert_eq!(
+            starts,
+            [
+                utc("2026-03-04T00:00:00Z"),
+                utc("2026-03-10T23:00:00Z"),
+                utc("2026-03-24T23:00:00Z"),
+                utc("2026-03-31T23:00:00Z"),
+            ]
+        );
+        assert_eq!(yoga[0].end_time, Some(utc("2026-03-04T01:00:00Z")));
+        assert_eq!(yoga[1].source_url, "https://www.guthriegreen.com/events/yoga-on-the-green#2026-03-10");
+        assert_eq!(
+            yoga[0].description.as_deref(),
+            Some("Free community yoga. Bring a mat, water and a friend.\nAll levels welcome; no registration.")
+        );
+        assert_eq!(yoga[0].venue.as_deref(), Some("Guthrie Green"));
+        assert_eq!(yoga[0].venue_address.as_deref(), Some("111 E Reconciliation Way, Tulsa, OK 74103"));
+        assert_eq!(yoga[0].tags, ["fitness", "outdoors"]);
+        assert_eq!(yoga[0].category.as_deref(), Some("community"));
+
+        // Every other Sunday for 10 occurrences, cut off by the 90-day horizon
+        let market: Vec<&ScrapedEvent> = events.iter().filter(|e| e.title == "Sunday Market").collect();
+        assert_eq!(market.len(), 6);
+        assert_eq!(market[0].end_time.unwrap() - market[0].start_time, Duration::hours(4));
+        assert_eq!(market[5].start_time, utc("2026-05-17T15:00:00Z"));
+
+        // "1FR" isn't expanded: just the first occurrence
+        let crawl: Vec<&ScrapedEvent> = events.iter().filter(|e| e.title == "First Friday Art Crawl").collect();
+        assert_eq!(crawl.len(), 1);
+        assert_eq!(crawl[0].source_url, "https://example.org/events.ics#first-friday-2026@guthriegreen.com");
+    }
+
+    #[test]
+    fn reads_utc_floating_zoned_and_all_day_times() {
+        let events = parse_calendar(MIXED, &feed(None), utc("2026-03-01T00:00:00Z")).unwrap();
+        let by_title = |title: &str| events.iter().find(|e| e.title == title).unwrap();
+
+        // Cancelled, past and beyond-the-horizon events are gone
+        assert_eq!(events.len(), 4);
+
+        let launch = by_title("Zine Launch Party");
+        assert_eq!(launch.start_time, utc("2026-03-05T01:30:00Z"));
+        assert_eq!(launch.end_time, Some(utc("2026-03-05T03:30:00Z")));
+        assert_eq!(launch.venue.as_deref(), Some("The Vanguard"));
+
+        // Floating: Tulsa local (CDT by March 10)
+        let lecture = by_title("History Lecture: Route 66 in Tulsa");
+        assert_eq!(lecture.start_time, utc("2026-03-11T00:00:00Z"));
+        assert_eq!(lecture.source_url, "https://example.org/events.ics#route-66-lecture@tulsaartsdistrict.org");
+
+        let fair = by_title("Spring Craft Fair");
+        assert_eq!(fair.start_time, utc("2026-03-14T17:00:00Z"));
+        assert_eq!(fair.end_time, Some(utc("2026-03-15T17:00:00Z")));
+
+        let stream = by_title("Livestream Watch Party");
+        assert_eq!(stream.start_time, utc("2026-03-21T00:00:00Z"));
+    }
+
+    #[test]
+    fn rules_and_durations() {
+        assert_eq!(parse_duration("PT2H30M"), Some(Duration::minutes(150)));
+        assert_eq!(parse_duration("P1DT12H"), Some(Duration::hours(36)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("2H"), None);
+
+        let rule = Rule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=5").unwrap();
+        assert_eq!((rule.interval, rule.count, rule.by_day.len()), (2, Some(5), 2));
+        assert!(Rule::parse("FREQ=MONTHLY;BYDAY=1FR").is_none());
+        assert!(Rule::parse("FREQ=MONTHLY;BYMONTHDAY=15").is_none());
+        assert!(Rule::parse("INTERVAL=2").is_none());
+
+        // Jan 31 monthly: only months with a 31st
+        let start = IcalTime { naive: noon(NaiveDate::from_ymd_opt(2026, 1, 31).unwrap()), zone: None, all_day: false };
+        let monthly = Rule::parse("FREQ=MONTHLY;COUNT=3").unwrap();
+        let dates: Vec<NaiveDate> = monthly
+            .occurrences(start, Duration::zero(), utc("2026-01-01T00:00:00Z"), utc("2027-01-01T00:00:00Z"))
+            .iter()
+            .map(NaiveDateTime::date)
+            .collect();
+        assert_eq!(dates, ["2026-01-31", "2026-03-31", "2026-05-31"].map(|d| d.parse::<NaiveDate>().unwrap()));
+    }
+
+    #[test]
+    fn feed_list_from_the_environment_format() {
+        let feeds = IcalFeed::parse_list(
+            "guthrie_green|https://www.guthriegreen.com/events.ics|community|Guthrie Green; \
+             bare|https://example.org/cal.ics ; broken|not a url",
+        );
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(feeds[0].default_venue.as_deref(), Some("Guthrie Green"));
+        assert_eq!((feeds[1].default_category.as_deref(), feeds[1].default_venue.as_deref()), (None, None));
+        assert!(matches!(parse_calendar("<html></html>", &feeds[1], Utc::now()), Err(ScraperError::Parse { .. })));
+    }
+}
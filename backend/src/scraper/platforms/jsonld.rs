@@ -0,0 +1,396 @@
+//! # JSON-LD Event Pages
+//!
+//! Ingests any page that lists its events as schema.org `Event` objects in
+//! `<script type="application/ld+json">` blocks (most ticketing widgets
+//! and WordPress event plugins do), with no site-specific selectors.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Configuration
+//! A `jsonld` row in `scrape_sources` (see `scraper::sources`): the page
+//! URL, and optionally a category and a venue for every event. The venue
+//! is used when an event doesn't name its `location`.
+//!
+//! ## What Is Read
+//! - Every block on the page: one object, an array of them, or a
+//!   `@graph`; any object whose `@type` ends in `Event` (`MusicEvent`,
+//!   `TheaterEvent`, ...)
+//! - Each is read as a `services::jsonld::JsonLdEvent`, after smoothing
+//!   over what sites commonly do differently: a `startDate` without an
+//!   offset (Tulsa time), a date without a time (noon), a `PostalAddress`
+//!   object, several `offers` (the price range spans them), an `image`
+//!   list, a price as text
+//! - Events marked `EventCancelled` are skipped; an object that still
+//!   can't be read is logged and skipped
+//!
+//! A page with no `Event` at all is read for the one event its Open Graph
+//! tags describe (`opengraph::fallback_event`), if it says it's an event
+//! page; otherwise it fails the run, like a venue scraper whose selectors
+//! stop matching.
+
+use axum::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use scraper::{Html, Selector};
+use serde_json::{Map, Value};
+
+use crate::models::EventStatus;
+use crate::scraper::dates::{local_to_utc, DEFAULT_START_HOUR, TULSA_TZ};
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::images;
+use crate::scraper::platforms::opengraph;
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+use crate::services::jsonld::JsonLdEvent;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// The script blocks read.
+const SCRIPT_SELECTOR: &str = r#"script[type="application/ld+json"]"#;
+
+/// One configured page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonLdPage {
+    pub source_id: String,
+    /// Credited as the events' `source_name`
+    pub name: String,
+    pub url: String,
+    pub default_category: Option<String>,
+    pub default_venue: Option<String>,
+}
+
+// =============================================================================
+// SCRAPER
+// =============================================================================
+
+/// Scraper for one page of JSON-LD events.
+pub struct JsonLdScraper {
+    page: JsonLdPage,
+}
+
+impl JsonLdScraper {
+    pub fn new(page: JsonLdPage) -> Self {
+        Self { page }
+    }
+}
+
+#[async_trait]
+impl EventScraper for JsonLdScraper {
+    fn name(&self) -> &str {
+        &self.page.name
+    }
+
+    fn source_id(&self) -> &str {
+        &self.page.source_id
+    }
+
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        fetcher.get_events(&self.page.url, |html| parse_page(html, &self.page)).await
+    }
+}
+
+// =============================================================================
+// PARSING
+// =============================================================================
+
+/// Reads every schema.org `Event` on the page, or failing that the one
+/// its Open Graph tags describe.
+pub fn parse_page(html: &str, page: &JsonLdPage) -> Result<Vec<ScrapedEvent>, ScraperError> {
+    parse_json_ld(html, page).or_else(|error| match opengraph::fallback_event(html, &page.url) {
+        Some(event) => Ok(vec![ScrapedEvent {
+            venue: page.default_venue.clone(),
+            category: page.default_category.clone(),
+            ..event
+        }]),
+        None => Err(error),
+    })
+}
+
+/// Reads every schema.org `Event` on the page; a page without one is a
+/// `ScraperError::Parse`.
+pub fn parse_json_ld(html: &str, page: &JsonLdPage) -> Result<Vec<ScrapedEvent>, ScraperError> {
+    let document = Html::parse_document(html);
+    let scripts = Selector::parse(SCRIPT_SELECTOR).expect("valid selector");
+
+    let mut objects = Vec::new();
+    for script in document.select(&scripts) {
+        let text = script.text().collect::<String>();
+        match serde_json::from_str::<Value>(&text) {
+            Ok(value) => collect_events(value, &mut objects),
+            Err(e) => tracing::warn!(url = %page.url, error = %e, "skipping a JSON-LD block that isn't JSON"),
+        }
+    }
+    if objects.is_empty() {
+        return Err(ScraperError::Parse {
+            selector: format!("{} with an Event", SCRIPT_SELECTOR),
+            context: page.url.clone(),
+        });
+    }
+
+    let mut events = Vec::new();
+    for mut object in objects {
+        normalize(&mut object);
+        match serde_json::from_value::<JsonLdEvent>(Value::Object(object)) {
+            Ok(event) => events.extend(to_scraped(event, page)),
+            Err(e) => tracing::warn!(url = %page.url, error = %e, "skipping a JSON-LD event we can't read"),
+        }
+    }
+    Ok(events)
+}
+
+/// Pushes the `Event` objects in `value`: itself, its array items, or its
+/// `@graph`.
+fn collect_events(value: Value, into: &mut Vec<Map<String, Value>>) {
+    match value {
+        Value::Array(items) => items.into_iter().for_each(|item| collect_events(item, into)),
+        Value::Object(mut object) => {
+            if let Some(graph) = object.remove("@graph") {
+                collect_events(graph, into);
+            }
+            let is_event = match object.get("@type") {
+                Some(Value::String(kind)) => kind.ends_with("Event"),
+                Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind.as_str().is_some_and(|kind| kind.ends_with("Event"))),
+                _ => false,
+            };
+            if is_event {
+                into.push(object);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrites the common variations into the shape `JsonLdEvent` reads.
+fn normalize(event: &mut Map<String, Value>) {
+    event.insert("@type".to_string(), Value::String("Event".to_string()));
+    for key in ["startDate", "endDate"] {
+        if let Some(Value::String(date)) = event.get(key) {
+            match read_date(date) {
+                Some(date) => event.insert(key.to_string(), Value::String(date)),
+                None => event.remove(key),
+            };
+        }
+    }
+
+    if let Some(Value::Array(places)) = event.get_mut("location") {
+        let place = places.drain(..).next().unwrap_or(Value::Null);
+        event.insert("location".to_string(), place);
+    }
+    match event.get_mut("location") {
+        Some(Value::Object(place)) => {
+            if let Some(Value::Object(address)) = place.get("address") {
+                let text = ["streetAddress", "addressLocality", "addressRegion", "postalCode"]
+                    .iter()
+                    .filter_map(|part| address.get(*part).and_then(Value::as_str))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                place.insert("address".to_string(), Value::String(text));
+            }
+            // Coordinates as text are left out rather than failing the event
+            let numeric = |geo: &Value| ["latitude", "longitude"].iter().all(|key| geo.get(*key).is_some_and(Value::is_number));
+            if place.get("geo").is_some_and(|geo| !numeric(geo)) {
+                place.remove("geo");
+            }
+        }
+        // A place given as its name
+        Some(Value::String(name)) => {
+            let name = name.clone();
+            event.insert("location".to_string(), serde_json::json!({ "name": name }));
+        }
+        Some(_) => {
+            event.remove("location");
+        }
+        None => {}
+    }
+
+    let image = match event.get("image") {
+        Some(Value::Array(images)) => images.iter().find_map(image_url),
+        Some(image) => image_url(image),
+        None => None,
+    };
+    match image {
+        Some(url) => event.insert("image".to_string(), Value::String(url)),
+        None => event.remove("image"),
+    };
+
+    if let Some(offers) = event.remove("offers") {
+        let offers = match offers {
+            Value::Array(offers) => offers,
+            offer => vec![offer],
+        };
+        let prices: Vec<f64> = offers
+            .iter()
+            .flat_map(|offer| ["price", "lowPrice", "highPrice"].map(|key| offer.get(key).and_then(price)))
+            .flatten()
+            .collect();
+        let availability = offers.iter().find_map(|offer| offer.get("availability").cloned());
+        let low = prices.iter().copied().reduce(f64::min);
+        let high = prices.iter().copied().reduce(f64::max);
+        event.insert(
+            "offers".to_string(),
+            serde_json::json!({ "lowPrice": low, "highPrice": high, "availability": availability }),
+        );
+    }
+}
+
+/// A `startDate` as RFC 3339: with an offset as is, without one in Tulsa
+/// time, a bare date at noon.
+pub(crate) fn read_date(text: &str) -> Option<String> {
+    let text = text.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date.to_rfc3339());
+    }
+    let (date, time) = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|local| (local.date(), local.time()))
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+            Some((date, NaiveTime::from_hms_opt(DEFAULT_START_HOUR, 0, 0)?))
+        })?;
+    Some(local_to_utc(TULSA_TZ, date, time).to_rfc3339())
+}
+
+/// An `image`: a URL, or an `ImageObject` with one.
+fn image_url(image: &Value) -> Option<String> {
+    match image {
+        Value::String(url) => Some(url.clone()),
+        Value::Object(object) => object.get("url").and_then(Value::as_str).map(str::to_string),
+        _ => None,
+    }
+}
+
+/// A price: a number, or text such as `"25.00"` or `"$25"`.
+fn price(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().trim_start_matches('$').replace(',', "").parse().ok(),
+        _ => None,
+    }
+}
+
+/// The event as scraped, or `None` if it's cancelled. One without a `url`
+/// gets the page's, plus its date and name, so each is still unique.
+fn to_scraped(event: JsonLdEvent, page: &JsonLdPage) -> Option<ScrapedEvent> {
+    let slug: String = event
+        .name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let fallback_url = format!("{}#{}-{}", page.url, event.start_date.with_timezone(&TULSA_TZ).format("%Y-%m-%d"), slug);
+    let event = event.into_create_event(&fallback_url);
+    if event.status == EventStatus::Cancelled {
+        return None;
+    }
+    Some(ScrapedEvent {
+        title: event.title,
+        description: event.description,
+        venue: event.venue.or_else(|| page.default_venue.clone()),
+        venue_address: event.venue_address,
+        location: event.location,
+        source_url: event.source_url,
+        start_time: event.start_time,
+        end_time: event.end_time,
+        category: page.default_category.clone(),
+        external_category: None,
+        external_id: None,
+        tags: event.tags,
+        price_min: event.price_min,
+        price_max: event.price_max,
+        is_free: event.is_free,
+        image_url: event.image_url.and_then(|image| images::normalize(&image, Some(&page.url))),
+    })
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn page() -> JsonLdPage {
+        JsonLdPage {
+            source_id: "test_page".to_string(),
+            name: "Test Page".to_string(),
+            url: "https://venue.example/events".to_string(),
+            default_category: Some("music".to_string()),
+            default_venue: Some("The Vanguard".to_string()),
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn reads_events_from_arrays_graphs_and_subtypes() {
+        let html = r#"<html><head>
+            <script type="application/ld+json">{"@type": "Organization", "name": "The Vanguard"}</script>
+            <script type="application/ld+json">[
+              {"@context": "https://schema.org", "@type": "MusicEvent", "name": "Jazz Night",
+               "startDate": "2026-03-14T20:00:00-05:00", "url": "https://venue.example/jazz",
+               "location": {"@type": "Place", "name": "Blue Room",
+                            "address": {"@type": "PostalAddress", "streetAddress": "222 N Main St", "addressLocality": "Tulsa"}},
+               "offers": [{"price": "15.00"}, {"price": 25, "availability": "https://schema.org/SoldOut"}],
+               "image": [{"@type": "ImageObject", "url": "https://venue.example/jazz.jpg"}]},
+              {"@type": "Event", "name": "Called Off", "startDate": "2026-03-15T20:00:00Z",
+               "eventStatus": "https://schema.org/EventCancelled"}
+            ]</script>
+            <script type="application/ld+json">{"@graph": [
+              {"@type": "WebPage"},
+              {"@type": ["Event", "Thing"], "name": "Open Mic", "startDate": "2026-03-16T19:00",
+               "image": "/img/open-mic.jpg?utm_source=calendar"},
+              {"@type": "Event", "name": "Block Party", "startDate": "2026-07-04"},
+              {"@type": "Event", "name": "No Date"}
+            ]}</script>
+            <script type="application/ld+json">{ not json</script>
+        </head></html>"#;
+
+        let events = parse_page(html, &page()).unwrap();
+        let titles: Vec<&str> = events.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(titles, ["Jazz Night", "Open Mic", "Block Party"]);
+
+        let jazz = &events[0];
+        assert_eq!(jazz.start_time, utc("2026-03-15T01:00:00Z"));
+        assert_eq!(jazz.source_url, "https://venue.example/jazz");
+        assert_eq!(jazz.venue.as_deref(), Some("Blue Room"));
+        assert_eq!(jazz.venue_address.as_deref(), Some("222 N Main St, Tulsa"));
+        assert_eq!((jazz.price_min, jazz.price_max), (Some(15.0), Some(25.0)));
+        assert_eq!(jazz.image_url.as_deref(), Some("https://venue.example/jazz.jpg"));
+        assert_eq!(jazz.category.as_deref(), Some("music"));
+
+        // No offset: Tulsa time (CDT in March); no time: noon; no url: the page's
+        let open_mic = &events[1];
+        assert_eq!(open_mic.start_time, utc("2026-03-17T00:00:00Z"));
+        assert_eq!(open_mic.venue.as_deref(), Some("The Vanguard"));
+        assert_eq!(open_mic.source_url, "https://venue.example/events#2026-03-16-open-mic");
+        assert_eq!(open_mic.image_url.as_deref(), Some("https://venue.example/img/open-mic.jpg"));
+        assert_eq!(events[2].start_time, utc("2026-07-04T17:00:00Z"));
+    }
+
+    #[test]
+    fn a_page_without_events_fails() {
+        let html = r#"<script type="application/ld+json">{"@type": "Organization"}</script>"#;
+        assert!(matches!(parse_page(html, &page()), Err(ScraperError::Parse { .. })));
+        assert!(matches!(parse_page("<p>Coming soon</p>", &page()), Err(ScraperError::Parse { .. })));
+    }
+
+    #[test]
+    fn an_event_page_without_json_ld_falls_back_to_its_meta_tags() {
+        let html = r#"<html><head>
+            <meta property="og:title" content="Pumpkin Patch Days">
+            <meta property="event:start_time" content="2026-10-24">
+            </head><body></body></html>"#;
+        let events = parse_page(html, &page()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].title.as_str(), events[0].source_url.as_str()), ("Pumpkin Patch Days", page().url.as_str()));
+        assert_eq!(events[0].start_time, utc("2026-10-24T17:00:00Z"));
+        assert_eq!(events[0].venue, page().default_venue);
+    }
+}
@@ -0,0 +1,565 @@
+//! # Meetup
+//!
+//! Upcoming Meetup events within a radius of Tulsa, from Meetup's GraphQL
+//! API (`eventSearch`), so the city's user groups and clubs are listed
+//! without a scraper per group.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Configuration
+//! ```text
+//! MEETUP_TOKEN=...                # required: without it the scraper isn't registered
+//! MEETUP_API_URL=https://api.meetup.com/gql-ext
+//! MEETUP_RADIUS_MILES=25          # around downtown Tulsa
+//! MEETUP_INCLUDE_ONLINE=false     # true keeps online-only events
+//! ```
+//!
+//! ## Mapping
+//! | Meetup | ScrapedEvent |
+//! |--------|--------------|
+//! | title / description | title / description |
+//! | dateTime / endTime | start_time / end_time |
+//! | venue.name, else group.name | venue |
+//! | venue address, city, state, postal code | venue_address |
+//! | eventUrl | source_url |
+//! | id | external_id |
+//! | group.topicCategory ("Technology") | external_category |
+//! | group.topics | tags |
+//! | feeSettings.amount (none: free) | price_min / price_max, is_free |
+//! | featuredEventPhoto.highResUrl | image_url |
+//!
+//! The scraper guesses no category of its own: Meetup's topic categories
+//! are mapped to ours in `category_mappings` (migration 042 adds the
+//! obvious ones), and the rest are left to the classifier.
+//!
+//! ## Online Events
+//! `eventType` is `PHYSICAL`, `HYBRID` or `ONLINE`. Online-only events
+//! aren't in Tulsa, so they're skipped unless `MEETUP_INCLUDE_ONLINE` is
+//! set; hybrid ones are kept.
+//!
+//! ## Times
+//! Meetup writes local times with their offset, with or without seconds
+//! (`2026-03-24T18:30-05:00`); they're stored in UTC.
+//!
+//! ## Pages
+//! Results come `PAGE_SIZE` at a time; each page's `endCursor` asks for
+//! the next, until `hasNextPage` is false (or `MAX_PAGES`, in case the
+//! cursors loop).
+
+use std::collections::HashSet;
+
+use axum::async_trait;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::images;
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+use crate::services::scheduler::env_u64;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Meetup's GraphQL endpoint.
+pub const API_URL: &str = "https://api.meetup.com/gql-ext";
+
+/// Downtown Tulsa, the center of the search.
+pub const TULSA_LAT: f64 = 36.154;
+pub const TULSA_LON: f64 = -95.9928;
+
+/// How far from downtown to search, unless configured
+/// (`MEETUP_RADIUS_MILES`).
+pub const DEFAULT_RADIUS_MILES: u64 = 25;
+
+/// How far ahead to look.
+pub const HORIZON_DAYS: i64 = 60;
+
+/// Events asked for per request.
+const PAGE_SIZE: usize = 50;
+
+/// Stops a search whose cursors loop.
+const MAX_PAGES: usize = 20;
+
+const SOURCE_ID: &str = "meetup";
+const SOURCE_NAME: &str = "Meetup";
+
+/// The search, one page at a time.
+const SEARCH_QUERY: &str = r#"
+query ($lat: Float!, $lon: Float!, $radius: Float!, $startDate: ZonedDateTime, $endDate: ZonedDateTime, $first: Int!, $after: String) {
+  eventSearch(
+    filter: { lat: $lat, lon: $lon, radius: $radius, startDateRange: $startDate, endDateRange: $endDate }
+    first: $first
+    after: $after
+  ) {
+    pageInfo { hasNextPage endCursor }
+    edges {
+      node {
+        id title description eventUrl dateTime endTime eventType
+        venue { name address city state postalCode }
+        group { name topicCategory { name } topics { name } }
+        feeSettings { amount currency }
+        featuredEventPhoto { highResUrl }
+      }
+    }
+  }
+}
+"#;
+
+/// How the scraper reaches Meetup and what it keeps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeetupConfig {
+    pub token: String,
+    pub api_url: String,
+    pub radius_miles: u64,
+    pub include_online: bool,
+}
+
+impl MeetupConfig {
+    /// From `MEETUP_*` (see "Configuration" above); `None` without a token.
+    pub fn from_env() -> Option<Self> {
+        let token = std::env::var("MEETUP_TOKEN").ok().filter(|token| !token.trim().is_empty())?;
+        Some(Self {
+            token: token.trim().to_string(),
+            api_url: std::env::var("MEETUP_API_URL").unwrap_or_else(|_| API_URL.to_string()),
+            radius_miles: env_u64("MEETUP_RADIUS_MILES", DEFAULT_RADIUS_MILES),
+            include_online: include_online(std::env::var("MEETUP_INCLUDE_ONLINE").ok().as_deref()),
+        })
+    }
+}
+
+/// Whether `MEETUP_INCLUDE_ONLINE` keeps online events (unset: no).
+fn include_online(raw: Option<&str>) -> bool {
+    matches!(
+        raw.map(|raw| raw.trim().to_lowercase()).as_deref(),
+        Some("true" | "1" | "on" | "yes")
+    )
+}
+
+// =============================================================================
+// SCRAPER
+// =============================================================================
+
+/// Scraper for Meetup events around Tulsa.
+pub struct MeetupScraper {
+    config: MeetupConfig,
+}
+
+impl MeetupScraper {
+    pub fn new(config: MeetupConfig) -> Self {
+        Self { config }
+    }
+
+    /// Every event from `now` through the horizon, page by page.
+    async fn crawl(&self, fetcher: &Fetcher, now: DateTime<Utc>) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        let mut seen = HashSet::new();
+        let mut events = Vec::new();
+        let mut after: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let body = json!({
+                "query": SEARCH_QUERY,
+                "variables": {
+                    "lat": TULSA_LAT,
+                    "lon": TULSA_LON,
+                    "radius": self.config.radius_miles as f64,
+                    "startDate": now.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    "endDate": (now + Duration::days(HORIZON_DAYS)).to_rfc3339_opts(SecondsFormat::Secs, true),
+                    "first": PAGE_SIZE,
+                    "after": after,
+                },
+            });
+            let text = fetcher.post_json(&self.config.api_url, &self.config.token, &body).await?;
+            let page = parse_page(&text)?;
+
+            for event in page.events {
+                let Some(event) = event.into_event(self.config.include_online) else { continue };
+                if seen.insert(event.source_url.clone()) {
+                    events.push(event);
+                }
+            }
+            match page.next {
+                Some(cursor) => after = Some(cursor),
+                None => return Ok(events),
+            }
+        }
+
+        tracing::warn!(pages = MAX_PAGES, "Meetup search still had more pages; stopped");
+        Ok(events)
+    }
+}
+
+#[async_trait]
+impl EventScraper for MeetupScraper {
+    fn name(&self) -> &str {
+        SOURCE_NAME
+    }
+
+    fn source_id(&self) -> &str {
+        SOURCE_ID
+    }
+
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        self.crawl(fetcher, Utc::now()).await
+    }
+}
+
+// =============================================================================
+// RESPONSE
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    data: Option<SearchData>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchData {
+    event_search: Connection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Connection {
+    page_info: PageInfo,
+    #[serde(default)]
+    edges: Vec<Edge>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Edge {
+    node: MeetupEvent,
+}
+
+/// One event as the API describes it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MeetupEvent {
+    id: String,
+    title: String,
+    description: Option<String>,
+    event_url: String,
+    date_time: String,
+    end_time: Option<String>,
+    event_type: Option<String>,
+    venue: Option<Venue>,
+    group: Option<Group>,
+    fee_settings: Option<FeeSettings>,
+    featured_event_photo: Option<Photo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Venue {
+    name: Option<String>,
+    address: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    postal_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Group {
+    name: String,
+    topic_category: Option<Named>,
+    #[serde(default)]
+    topics: Vec<Named>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FeeSettings {
+    amount: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Photo {
+    high_res_url: Option<String>,
+}
+
+/// One page of results, and the cursor for the next if there is one.
+#[derive(Debug)]
+struct Page {
+    events: Vec<MeetupEvent>,
+    next: Option<String>,
+}
+
+/// Reads one response.
+///
+/// # Errors
+/// `ScraperError::Parse` if it isn't a search result: not JSON, another
+/// shape, or GraphQL errors (a bad token, a changed schema) instead of data.
+fn parse_page(text: &str) -> Result<Page, ScraperError> {
+    let response: SearchResponse = serde_json::from_str(text).map_err(|e| ScraperError::Parse {
+        selector: "eventSearch".to_string(),
+        context: format!("Meetup response: {}", e),
+    })?;
+    let Some(data) = response.data else {
+        let messages: Vec<String> = response.errors.into_iter().map(|error| error.message).collect();
+        return Err(ScraperError::Parse {
+            selector: "eventSearch".to_string(),
+            context: format!("Meetup errors: {}", messages.join("; ")),
+        });
+    };
+
+    let search = data.event_search;
+    Ok(Page {
+        events: search.edges.into_iter().map(|edge| edge.node).collect(),
+        next: search.page_info.end_cursor.filter(|_| search.page_info.has_next_page),
+    })
+}
+
+impl MeetupEvent {
+    /// The event, or `None` if it's online-only (and those aren't kept)
+    /// or its start can't be read.
+    fn into_event(self, include_online: bool) -> Option<ScrapedEvent> {
+        if !include_online && self.event_type.as_deref() == Some("ONLINE") {
+            tracing::debug!(id = %self.id, "skipping an online Meetup event");
+            return None;
+        }
+        let start = match parse_time(&self.date_time) {
+            Ok(start) => start,
+            Err(e) => {
+                tracing::warn!(id = %self.id, error = %e, "skipping a Meetup event");
+                return None;
+            }
+        };
+
+        let venue_name = self.venue.as_ref().and_then(|venue| venue.name.clone());
+        let group_name = self.group.as_ref().map(|group| group.name.clone());
+        let price = self.fee_settings.as_ref().and_then(|fee| fee.amount);
+        Some(ScrapedEvent {
+            description: self.description,
+            venue: venue_name.or(group_name),
+            venue_address: self.venue.as_ref().and_then(Venue::address),
+            end_time: self.end_time.as_deref().and_then(|end| parse_time(end).ok()),
+            external_category: self.group.as_ref().and_then(|group| group.topic_category.as_ref()).map(|topic| topic.name.clone()),
+            external_id: Some(self.id),
+            tags: self.group.map(|group| group.topics.into_iter().map(|topic| topic.name).collect()).unwrap_or_default(),
+            price_min: price,
+            price_max: price,
+            is_free: Some(self.fee_settings.is_none() || price == Some(0.0)),
+            image_url: self
+                .featured_event_photo
+                .and_then(|photo| photo.high_res_url)
+                .and_then(|image| images::normalize(&image, None)),
+            ..ScrapedEvent::new(&self.title, &self.event_url, start)
+        })
+    }
+}
+
+impl Venue {
+    /// "36 E Cameron St, Tulsa, OK 74103", from whichever parts are given.
+    fn address(&self) -> Option<String> {
+        let region = [&self.state, &self.postal_code]
+            .into_iter()
+            .flatten()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let parts: Vec<&str> = [self.address.as_deref(), self.city.as_deref(), Some(region.as_str())]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// A Meetup time (local, with its offset) in UTC.
+fn parse_time(text: &str) -> Result<DateTime<Utc>, ScraperError> {
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_str(text, "%Y-%m-%dT%H:%M%:z"))
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| ScraperError::DateParse(text.to_string()))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::scraper::persist::persist_scraped_events;
+
+    const PAGE_1: &str = include_str!("../../../tests/fixtures/meetup/search-page1.json");
+    const PAGE_2: &str = include_str!("../../../tests/fixtures/meetup/search-page2.json");
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn config(api_url: &str) -> MeetupConfig {
+        MeetupConfig {
+            token: "test-token".to_string(),
+            api_url: api_url.to_string(),
+            radius_miles: DEFAULT_RADIUS_MILES,
+            include_online: false,
+        }
+    }
+
+    #[test]
+    fn reads_a_search_page() {
+        let page = parse_page(PAGE_1).unwrap();
+        assert_eq!(page.next.as_deref(), Some("MTA="));
+        assert_eq!(page.events.len(), 3);
+
+        let devs = page.events[0].clone().into_event(false).unwrap();
+        assert_eq!(devs.title, "Tulsa Web Devs: Rust in Production");
+        assert_eq!(devs.source_url, "https://www.meetup.com/tulsa-web-devs/events/301234567/");
+        // 6:30 to 8:30 PM CDT, written without seconds
+        assert_eq!(devs.start_time, utc("2026-03-24T23:30:00Z"));
+        assert_eq!(devs.end_time, Some(utc("2026-03-25T01:30:00Z")));
+        assert_eq!(devs.venue.as_deref(), Some("36 Degrees North"));
+        assert_eq!(devs.venue_address.as_deref(), Some("36 E Cameron St, Tulsa, OK 74103"));
+        assert_eq!(devs.external_category.as_deref(), Some("Technology"));
+        assert_eq!(devs.category, None);
+        assert_eq!(devs.tags, ["Web Development", "Rust"]);
+        assert_eq!((devs.is_free, devs.price_min), (Some(true), None));
+        assert_eq!(devs.image_url.as_deref(), Some("https://secure.meetupstatic.com/photos/event/4/1/2/highres_5123.jpeg"));
+
+        // Online-only: skipped unless asked for
+        assert_eq!(page.events[1].clone().into_event(false), None);
+        assert!(page.events[1].clone().into_event(true).is_some());
+
+        // No venue: the group's name stands in
+        let cleanup = page.events[2].clone().into_event(false).unwrap();
+        assert_eq!(cleanup.venue.as_deref(), Some("Tulsa Green Neighbors"));
+        assert_eq!(cleanup.venue_address, None);
+        assert_eq!(cleanup.start_time, utc("2026-03-28T14:00:00Z"));
+        assert_eq!((cleanup.is_free, cleanup.price_min, cleanup.price_max), (Some(false), Some(5.0), Some(5.0)));
+
+        let last = parse_page(PAGE_2).unwrap();
+        assert_eq!((last.events.len(), last.next), (1, None));
+    }
+
+    #[test]
+    fn errors_instead_of_data_are_a_parse_error() {
+        let denied = r#"{ "data": null, "errors": [{ "message": "Not authorized" }] }"#;
+        let Err(ScraperError::Parse { context, .. }) = parse_page(denied) else {
+            panic!("expected a parse error");
+        };
+        assert!(context.contains("Not authorized"), "{}", context);
+        assert!(matches!(parse_page("<html>Bad gateway</html>"), Err(ScraperError::Parse { .. })));
+    }
+
+    #[test]
+    fn times_with_and_without_seconds() {
+        assert_eq!(parse_time("2026-11-07T19:00-06:00").unwrap(), utc("2026-11-08T01:00:00Z"));
+        assert_eq!(parse_time("2026-11-07T19:00:30-06:00").unwrap(), utc("2026-11-08T01:00:30Z"));
+        assert!(matches!(parse_time("next Tuesday"), Err(ScraperError::DateParse(_))));
+        assert!(include_online(Some(" Yes ")));
+        assert!(!include_online(None));
+    }
+
+    #[tokio::test]
+    async fn follows_the_cursor_to_the_last_page() {
+        let server = MockServer::start().await;
+        let search = |after: Value, body: &str| {
+            Mock::given(method("POST"))
+                .and(path("/gql-ext"))
+                .and(header("authorization", "Bearer test-token"))
+                .and(body_partial_json(json!({ "variables": { "after": after } })))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body.to_string()))
+        };
+        search(json!("MTA="), PAGE_2).mount(&server).await;
+        search(Value::Null, PAGE_1).mount(&server).await;
+
+        let scraper = MeetupScraper::new(config(&format!("{}/gql-ext", server.uri())));
+        let events = scraper.crawl(&Fetcher::for_tests(), utc("2026-03-20T12:00:00Z")).await.unwrap();
+
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Tulsa Web Devs: Rust in Production", "Riverside Trash Pickup", "Board Game Night"]);
+        // Two searches, and no robots.txt: it's an API
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn topic_categories_map_to_ours() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Far enough ahead to be upcoming, with URLs of their own
+        let run = Uuid::new_v4();
+        let shift = Utc::now() + Duration::days(10) - utc("2026-03-24T00:00:00Z");
+        let events: Vec<ScrapedEvent> = [PAGE_1, PAGE_2]
+            .into_iter()
+            .flat_map(|page| parse_page(page).unwrap().events)
+            .filter_map(|event| event.into_event(false))
+            .map(|event| ScrapedEvent {
+                source_url: format!("{}?run={}", event.source_url, run),
+                start_time: event.start_time + shift,
+                end_time: event.end_time.map(|end| end + shift),
+                ..event
+            })
+            .collect();
+
+        // The mappings migration 042 adds for Meetup
+        let summary = persist_scraped_events(&pool, events, SOURCE_ID, SOURCE_NAME, false).await;
+        assert_eq!(summary.created, 3);
+
+        let rows: Vec<(String, Option<Vec<String>>)> = sqlx::query_as(
+            "SELECT title, categories FROM events WHERE source_url LIKE $1 ORDER BY title",
+        )
+            .bind(format!("%?run={}", run))
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let categories: Vec<(&str, Option<&str>)> = rows
+            .iter()
+            .map(|(title, categories)| (title.as_str(), categories.as_ref().and_then(|c| c.first()).map(String::as_str)))
+            .collect();
+        assert_eq!(
+            categories,
+            [
+                // "Games" has no mapping: left to the classifier
+                ("Board Game Night", None),
+                ("Riverside Trash Pickup", Some("community")),
+                ("Tulsa Web Devs: Rust in Production", Some("education")),
+            ]
+        );
+
+        sqlx::query("DELETE FROM events WHERE source_url LIKE $1")
+            .bind(format!("%?run={}", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,23 @@
+//! # Platform Scrapers
+//!
+//! Standard formats and event platforms shared by many organizers, each
+//! configured per feed or account rather than written per site.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+
+/// Any iCalendar (`.ics`) feed, configured through `ICAL_FEEDS` or a
+/// `scrape_sources` row.
+pub mod ical;
+/// Any page listing schema.org events as JSON-LD, configured through a
+/// `scrape_sources` row.
+pub mod jsonld;
+/// The event a page describes in its Open Graph and meta tags, for pages
+/// with no JSON-LD.
+pub mod opengraph;
+/// Event pages found through a site's sitemap, each read as JSON-LD or
+/// Open Graph; configured through a `scrape_sources` row.
+pub mod sitemap;
+/// Meetup events around Tulsa, from its GraphQL API; registered when
+/// `MEETUP_TOKEN` is set.
+pub mod meetup;
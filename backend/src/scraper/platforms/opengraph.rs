@@ -0,0 +1,280 @@
+//! # Open Graph and Meta Tags
+//!
+//! Reads the event a page describes in its `<meta>` tags: what plenty of
+//! small venue sites set (for link previews) when they have no JSON-LD.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## What Is Read
+//! Each field from the first of its tags present (`property`, `name` or
+//! `itemprop`, any case):
+//! - title: `og:title`, `twitter:title`
+//! - description: `og:description`, `twitter:description`, `description`
+//! - image: `og:image`, `og:image:url`, `og:image:secure_url`,
+//!   `twitter:image`; normalized against the page (`images::normalize`)
+//! - start and end: `event:start_time` / `event:end_time` (also with an
+//!   `og:` prefix), microdata `startDate` / `endDate`; a time without an
+//!   offset is Tulsa time, a date without a time noon
+//! - `og:url`, the page's canonical address
+//! - `og:site_name`, `og:type`
+//!
+//! A tag given more than once (several `og:image`s) counts the first time;
+//! an empty one doesn't count. Entities a CMS escaped twice
+//! (`Rock &amp;amp; Roll`) are unescaped again. Bodies in a charset other
+//! than UTF-8 are decoded on fetch (`fetch.rs`).
+//!
+//! ## Where It's Used
+//! - The second pass of `scraper::extract`, for links sent in by hand
+//! - `fallback_event`: site scrapers whose selectors find nothing try it
+//!   before failing, for pages that turn out to be a single event. Only a
+//!   page that says it is one (`og:type` of `event`, or an
+//!   `event:start_time` tag) counts: a calendar page's first microdata
+//!   date would otherwise pass as its only event.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+use crate::scraper::images;
+use crate::scraper::platforms::jsonld;
+use crate::scraper::traits::ScrapedEvent;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Tags read for each field, best first (lowercased).
+const TITLE_TAGS: &[&str] = &["og:title", "twitter:title"];
+const DESCRIPTION_TAGS: &[&str] = &["og:description", "twitter:description", "description"];
+const IMAGE_TAGS: &[&str] = &["og:image", "og:image:url", "og:image:secure_url", "twitter:image"];
+const START_TAGS: &[&str] = &["event:start_time", "og:event:start_time", "startdate"];
+const END_TAGS: &[&str] = &["event:end_time", "og:event:end_time", "enddate"];
+
+/// Tags only a page about one event sets.
+const EVENT_START_TAGS: &[&str] = &["event:start_time", "og:event:start_time"];
+
+/// The elements read: meta tags, and microdata dates.
+const META_SELECTOR: &str = "meta[content], [itemprop][datetime]";
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// What a page's meta tags say, every field optional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraph {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Absolute, without tracking parameters
+    pub image_url: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub site_name: Option<String>,
+    /// `og:url`, absolute
+    pub url: Option<String>,
+    /// The page says it's about one event (see "Where It's Used" above)
+    pub is_event: bool,
+}
+
+impl OpenGraph {
+    /// As an event at `source_url`, if there's a title and a start.
+    pub fn to_scraped(&self, source_url: &str) -> Option<ScrapedEvent> {
+        Some(ScrapedEvent {
+            description: self.description.clone(),
+            end_time: self.end_time,
+            image_url: self.image_url.clone(),
+            ..ScrapedEvent::new(self.title.as_deref()?, source_url, self.start_time?)
+        })
+    }
+}
+
+// =============================================================================
+// PARSING
+// =============================================================================
+
+/// Reads the meta tags of the page at `page_url`.
+pub fn parse(html: &str, page_url: &str) -> OpenGraph {
+    read(&Html::parse_document(html), page_url)
+}
+
+/// `parse`, for a page already parsed.
+pub fn read(document: &Html, page_url: &str) -> OpenGraph {
+    let tags = meta_tags(document);
+    let first = |keys: &[&str]| keys.iter().find_map(|key| tags.get(*key).cloned());
+    let date = |keys: &[&str]| keys.iter().find_map(|key| jsonld::read_date(tags.get(*key)?)?.parse().ok());
+
+    OpenGraph {
+        title: first(TITLE_TAGS),
+        description: first(DESCRIPTION_TAGS),
+        image_url: first(IMAGE_TAGS).and_then(|image| images::normalize(&image, Some(page_url))),
+        start_time: date(START_TAGS),
+        end_time: date(END_TAGS),
+        site_name: first(&["og:site_name"]),
+        url: first(&["og:url"]).and_then(|url| resolve(page_url, &url)),
+        is_event: tags.get("og:type").is_some_and(|kind| kind.eq_ignore_ascii_case("event"))
+            || EVENT_START_TAGS.iter().any(|key| tags.contains_key(*key)),
+    }
+}
+
+/// The one event on a page a site scraper's selectors found nothing on,
+/// if the page says it's an event and has a title and a start. It's at
+/// the page's `og:url`, if it gives one: the page fetched may have been a
+/// redirect to it.
+pub fn fallback_event(html: &str, page_url: &str) -> Option<ScrapedEvent> {
+    let page = parse(html, page_url);
+    let event = page.is_event.then(|| page.to_scraped(page.url.as_deref().unwrap_or(page_url))).flatten()?;
+    tracing::warn!(url = %page_url, title = %event.title, "no events matched; read the page's meta tags instead");
+    Some(event)
+}
+
+/// Every tag's content by its `property`, `name` or `itemprop`
+/// (lowercased; the first non-empty one of each wins).
+fn meta_tags(document: &Html) -> HashMap<String, String> {
+    let selector = Selector::parse(META_SELECTOR).expect("valid selector");
+    let mut tags = HashMap::new();
+    for element in document.select(&selector) {
+        let value = element.value();
+        let key = value.attr("property").or_else(|| value.attr("name")).or_else(|| value.attr("itemprop"));
+        let content = value.attr("content").or_else(|| value.attr("datetime")).map(clean);
+        if let (Some(key), Some(content)) = (key, content.filter(|content| !content.is_empty())) {
+            tags.entry(key.trim().to_lowercase()).or_insert(content);
+        }
+    }
+    tags
+}
+
+/// A tag's content trimmed, whitespace collapsed, and entities escaped
+/// twice unescaped.
+fn clean(content: &str) -> String {
+    let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !content.contains('&') {
+        return content;
+    }
+
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content.as_str();
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let decoded = rest[1..].find(';').filter(|end| *end <= 8).and_then(|end| Some((entity(&rest[1..=end])?, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character an entity (without `&` and `;`) stands for.
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// `url` as an absolute http(s) URL, relative to `page_url`.
+fn resolve(page_url: &str, url: &str) -> Option<String> {
+    let resolved = match Url::parse(url) {
+        Ok(absolute) => absolute,
+        Err(_) => Url::parse(page_url).ok()?.join(url).ok()?,
+    };
+    matches!(resolved.scheme(), "http" | "https").then(|| resolved.to_string())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VENUE_PAGE: &str = include_str!("../../../tests/fixtures/pages/og-venue.html");
+    const NO_TAGS: &str = include_str!("../../../tests/fixtures/pages/no-og.html");
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn reads_a_venue_event_page() {
+        let url = "https://www.thevanguardtulsa.com/shows/2026/11/21/hosty-duo";
+        let page = parse(VENUE_PAGE, url);
+        assert!(page.is_event);
+        assert_eq!(page.title.as_deref(), Some("Hosty Duo — Rock & Roll Revue"));
+        assert_eq!(page.description.as_deref(), Some("Tulsa’s favorite two-piece, with openers. 21+."));
+        assert_eq!(page.site_name.as_deref(), Some("The Vanguard"));
+        assert_eq!(page.url.as_deref(), Some(url));
+        // The first of three og:images, relative to the page
+        assert_eq!(page.image_url.as_deref(), Some("https://www.thevanguardtulsa.com/media/hosty-duo.jpg"));
+        // No offset: Tulsa time
+        assert_eq!((page.start_time, page.end_time), (Some(utc("2026-11-22T02:00:00Z")), None));
+
+        // The calendar redirected here: the event is at its og:url
+        let event = fallback_event(VENUE_PAGE, "https://www.thevanguardtulsa.com/calendar").unwrap();
+        assert_eq!((event.title.as_str(), event.source_url.as_str()), ("Hosty Duo — Rock & Roll Revue", url));
+        assert_eq!(event.start_time, utc("2026-11-22T02:00:00Z"));
+    }
+
+    #[test]
+    fn a_page_without_tags_reads_as_nothing() {
+        let page = parse(NO_TAGS, "https://blank.example/events");
+        assert_eq!(page, OpenGraph::default());
+        assert_eq!(page.to_scraped("https://blank.example/events"), None);
+        assert_eq!(fallback_event(NO_TAGS, "https://blank.example/events"), None);
+    }
+
+    #[test]
+    fn only_a_page_that_says_its_an_event_is_a_fallback() {
+        // A calendar: microdata dates, but no event tags of its own
+        let calendar = r#"<html><head><meta property="og:title" content="Calendar"></head><body>
+            <div itemscope><time itemprop="startDate" datetime="2026-11-01T20:00">Nov 1</time></div>
+            </body></html>"#;
+        let page = parse(calendar, "https://venue.example/calendar");
+        assert!(!page.is_event && page.to_scraped("https://venue.example/calendar").is_some());
+        assert_eq!(fallback_event(calendar, "https://venue.example/calendar"), None);
+    }
+
+    #[test]
+    fn urls_resolve_against_the_page() {
+        let page = "https://venue.example/shows/one";
+        assert_eq!(resolve(page, "/img/a.jpg").as_deref(), Some("https://venue.example/img/a.jpg"));
+        assert_eq!(resolve(page, "b.jpg").as_deref(), Some("https://venue.example/shows/b.jpg"));
+        assert_eq!(resolve(page, "//cdn.example/c.jpg").as_deref(), Some("https://cdn.example/c.jpg"));
+        assert_eq!(resolve(page, "https://cdn.example/d.jpg").as_deref(), Some("https://cdn.example/d.jpg"));
+        assert_eq!(resolve(page, "data:image/png;base64,AAAA"), None);
+    }
+
+    #[test]
+    fn entities_escaped_twice_are_unescaped() {
+        assert_eq!(clean("Rock &amp; Roll"), "Rock & Roll");
+        assert_eq!(clean("Tulsa&#8217;s  best\n night"), "Tulsa’s best night");
+        assert_eq!(clean("&#x1F3B8; &quot;live&quot;"), "🎸 \"live\"");
+        // Not entities
+        assert_eq!(clean("AT&T Pavilion & more; free"), "AT&T Pavilion & more; free");
+        assert_eq!(clean("&bogus; &#xZZ;"), "&bogus; &#xZZ;");
+    }
+}
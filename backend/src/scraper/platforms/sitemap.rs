@@ -0,0 +1,783 @@
+//! # Sitemap Discovery
+//!
+//! Finds a site's event pages through its sitemap, for sites with a page
+//! per event but no listing worth scraping, and reads each page with the
+//! generic JSON-LD / Open Graph reader (`jsonld::parse_page`).
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Configuration
+//! A `sitemap` row in `scrape_sources` (see `scraper::sources`): `url` is
+//! the sitemap, or any page of the site (its `/sitemap.xml` is used), and
+//! `config` may set
+//! - `pattern`: only URLs whose path contains it are read (default
+//!   `/events/`); the path that is just the pattern, the listing, isn't
+//! - `max_urls`: the most pages fetched in one run (default
+//!   `DEFAULT_MAX_URLS`, at most `MAX_URLS_LIMIT`)
+//!
+//! `default_category` and `default_venue` apply as for a `jsonld` row.
+//!
+//! ## Discovery
+//! - A sitemap index is followed into its sitemaps, up to `MAX_DEPTH`
+//!   levels and `MAX_SITEMAPS` files in all
+//! - Gzipped sitemaps (`.xml.gz`) are unpacked, up to `MAX_OUTPUT`
+//! - A nested sitemap that can't be fetched is skipped (the fetcher
+//!   records it, so the run doesn't cancel its pages' events); one that
+//!   can't be read fails the run, as the root sitemap failing does
+//! - Pages are read newest `lastmod` first, each once
+//!
+//! ## Unchanged Pages
+//! A page whose `lastmod` is older than its fetch cache entry isn't
+//! requested at all (`Fetcher::unchanged_since`) and doesn't count toward
+//! `max_urls`. A `lastmod` that's only a date is taken as the end of that
+//! day (Tulsa), so a page changed later that day isn't missed. Pages
+//! without one are fetched conditionally, like any page (`get_events`).
+//!
+//! Pages past `max_urls` wait for a later run; the events they had when
+//! last read are kept (`Fetcher::defer`). A page with no event on it is
+//! logged and skipped.
+
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+
+use axum::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use flate2::read::MultiGzDecoder;
+use quick_xml::events::Event as XmlEvent;
+use quick_xml::Reader;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::scraper::dates::{local_to_utc, TULSA_TZ};
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::platforms::jsonld::{self, JsonLdPage};
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// URLs read unless a source's `config` says otherwise.
+pub const DEFAULT_PATTERN: &str = "/events/";
+
+/// Pages fetched in one run unless a source's `config` says otherwise.
+pub const DEFAULT_MAX_URLS: usize = 50;
+
+/// The most `max_urls` may be.
+pub const MAX_URLS_LIMIT: usize = 500;
+
+/// Levels of sitemap indexes followed, the root included.
+const MAX_DEPTH: usize = 3;
+
+/// Sitemap files read in one run.
+const MAX_SITEMAPS: usize = 50;
+
+/// The most a gzipped sitemap may unpack to (the protocol's limit, 50 MB),
+/// so a small file that unpacks to gigabytes fails instead of filling memory.
+pub const MAX_OUTPUT: usize = 50 * 1024 * 1024;
+
+/// The first two bytes of a gzip file.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// A `sitemap` source's `config`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SitemapConfig {
+    #[serde(default = "default_pattern")]
+    pub pattern: String,
+    #[serde(default = "default_max_urls")]
+    pub max_urls: usize,
+}
+
+fn default_pattern() -> String {
+    DEFAULT_PATTERN.to_string()
+}
+
+fn default_max_urls() -> usize {
+    DEFAULT_MAX_URLS
+}
+
+impl Default for SitemapConfig {
+    fn default() -> Self {
+        Self { pattern: default_pattern(), max_urls: default_max_urls() }
+    }
+}
+
+impl SitemapConfig {
+    /// Reads and checks a source's `config`.
+    ///
+    /// # Errors
+    /// What's wrong with it, for an admin to fix.
+    pub fn from_value(config: &Value) -> Result<Self, String> {
+        let config: Self = serde_json::from_value(config.clone()).map_err(|e| e.to_string())?;
+        if config.pattern.trim().is_empty() {
+            return Err("pattern must not be empty".to_string());
+        }
+        if !(1..=MAX_URLS_LIMIT).contains(&config.max_urls) {
+            return Err(format!("max_urls must be 1-{}", MAX_URLS_LIMIT));
+        }
+        Ok(config)
+    }
+}
+
+/// One configured site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapSite {
+    pub source_id: String,
+    /// Credited as the events' `source_name`
+    pub name: String,
+    /// The sitemap, or a page of the site
+    pub url: String,
+    pub default_category: Option<String>,
+    pub default_venue: Option<String>,
+    pub config: SitemapConfig,
+}
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// One `<url>` or `<sitemap>` of a sitemap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapEntry {
+    /// Absolute
+    pub loc: String,
+    pub lastmod: Option<DateTime<Utc>>,
+}
+
+/// A sitemap file: an index of more sitemaps, or a set of pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sitemap {
+    Index(Vec<SitemapEntry>),
+    UrlSet(Vec<SitemapEntry>),
+}
+
+// =============================================================================
+// DISCOVERY
+// =============================================================================
+
+/// Finds the pages a site's sitemaps list that match a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapDiscovery {
+    pub sitemap_url: String,
+    pub pattern: String,
+    /// Pages fetched in one run (see "Unchanged Pages" above)
+    pub max_urls: usize,
+}
+
+impl SitemapDiscovery {
+    /// Discovery for the site at `url` (see "Configuration" above).
+    pub fn new(url: &str, config: &SitemapConfig) -> Self {
+        Self { sitemap_url: sitemap_url(url), pattern: config.pattern.clone(), max_urls: config.max_urls }
+    }
+
+    /// Every page the sitemaps list that matches the pattern, newest
+    /// `lastmod` first (pages without one last, in sitemap order).
+    ///
+    /// # Errors
+    /// - `ScraperError::Http` or `ScraperError::Disallowed` if the root
+    ///   sitemap can't be fetched
+    /// - `ScraperError::Parse` if a sitemap isn't one, or won't unpack
+    pub async fn discover(&self, fetcher: &Fetcher) -> Result<Vec<SitemapEntry>, ScraperError> {
+        let mut queue = VecDeque::from([(self.sitemap_url.clone(), 0)]);
+        let mut sitemaps_read = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut pages = Vec::new();
+
+        while let Some((url, depth)) = queue.pop_front() {
+            if sitemaps_read.len() == MAX_SITEMAPS {
+                tracing::warn!(sitemap = %self.sitemap_url, "more than {} sitemaps; the rest aren't read", MAX_SITEMAPS);
+                break;
+            }
+            if !sitemaps_read.insert(url.clone()) {
+                continue;
+            }
+            let body = match fetcher.get_bytes(&url).await {
+                Ok(body) => body,
+                Err(e) if depth == 0 => return Err(e),
+                Err(e) => {
+                    tracing::warn!(sitemap = %url, error = %e, "couldn't fetch a nested sitemap; skipped");
+                    continue;
+                }
+            };
+
+            match parse_sitemap(&unpack(&body, &url)?, &url)? {
+                Sitemap::Index(sitemaps) if depth + 1 < MAX_DEPTH => {
+                    queue.extend(sitemaps.into_iter().map(|sitemap| (sitemap.loc, depth + 1)));
+                }
+                Sitemap::Index(_) => tracing::warn!(sitemap = %url, "sitemap index nested too deep; not followed"),
+                Sitemap::UrlSet(urls) => {
+                    pages.extend(urls.into_iter().filter(|page| self.matches(&page.loc) && seen.insert(page.loc.clone())));
+                }
+            }
+        }
+
+        // Stable: pages without a lastmod stay in sitemap order, after the rest
+        pages.sort_by_key(|page| Reverse(page.lastmod));
+        Ok(pages)
+    }
+
+    /// Whether `url`'s path contains the pattern, and is more than it.
+    fn matches(&self, url: &str) -> bool {
+        let Ok(url) = Url::parse(url) else { return false };
+        let pattern = self.pattern.trim();
+        matches!(url.scheme(), "http" | "https")
+            && url.path().contains(pattern)
+            && url.path().trim_end_matches('/') != pattern.trim_end_matches('/')
+    }
+}
+
+/// The sitemap of the site at `url`: `url` itself if it names one, else
+/// the site's `/sitemap.xml`.
+fn sitemap_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) if !(parsed.path().ends_with(".xml") || parsed.path().ends_with(".xml.gz")) => {
+            format!("{}/sitemap.xml", parsed.origin().ascii_serialization())
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// A sitemap's text, unpacked first if it's gzipped.
+///
+/// Servers send `.xml.gz` as a file rather than with `Content-Encoding`,
+/// so the HTTP client hands us the compressed bytes.
+fn unpack(body: &[u8], url: &str) -> Result<String, ScraperError> {
+    if !body.starts_with(&GZIP_MAGIC) {
+        return Ok(String::from_utf8_lossy(body).into_owned());
+    }
+    let unreadable = |reason: String| ScraperError::Parse {
+        selector: "gzip".to_string(),
+        context: format!("sitemap {}: {}", url, reason),
+    };
+
+    let mut xml = Vec::new();
+    MultiGzDecoder::new(body)
+        .take(MAX_OUTPUT as u64 + 1)
+        .read_to_end(&mut xml)
+        .map_err(|e| unreadable(e.to_string()))?;
+    if xml.len() > MAX_OUTPUT {
+        return Err(unreadable(format!("unpacks to more than {} bytes", MAX_OUTPUT)));
+    }
+    Ok(String::from_utf8_lossy(&xml).into_owned())
+}
+
+// =============================================================================
+// PARSING
+// =============================================================================
+
+/// Reads a sitemap, or sitemap index, fetched from `url` (relative
+/// `<loc>`s, which the protocol doesn't allow, are taken relative to it).
+///
+/// # Errors
+/// `ScraperError::Parse` if it isn't XML, or isn't a `<urlset>` or
+/// `<sitemapindex>`.
+pub fn parse_sitemap(xml: &str, url: &str) -> Result<Sitemap, ScraperError> {
+    let parse_error = |context: String| ScraperError::Parse { selector: "urlset, sitemapindex".to_string(), context };
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let base = Url::parse(url).ok();
+    let mut root = None;
+    let mut entries = Vec::new();
+    // The `<url>` or `<sitemap>` being read, and its child being read:
+    // only direct children count (`<image:loc>` isn't the page's)
+    let mut entry: Option<RawEntry> = None;
+    let mut field = String::new();
+    let mut depth = 0;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| parse_error(format!("sitemap {} at byte {}: {}", url, reader.buffer_position(), e)))?;
+        match event {
+            XmlEvent::Start(tag) => {
+                depth += 1;
+                let name = String::from_utf8_lossy(tag.local_name().as_ref()).into_owned();
+                match depth {
+                    1 => root = Some(name),
+                    2 if matches!(name.as_str(), "url" | "sitemap") => entry = Some(Default::default()),
+                    3 => field = name,
+                    _ => {}
+                }
+            }
+            XmlEvent::Empty(tag) if depth == 0 => {
+                root = Some(String::from_utf8_lossy(tag.local_name().as_ref()).into_owned());
+            }
+            XmlEvent::Text(text) if depth == 3 => {
+                if let (Some(entry), Ok(text)) = (entry.as_mut(), text.unescape()) {
+                    entry.push(&field, &text);
+                }
+            }
+            XmlEvent::CData(data) if depth == 3 => {
+                if let Some(entry) = entry.as_mut() {
+                    entry.push(&field, &String::from_utf8_lossy(&data));
+                }
+            }
+            XmlEvent::End(_) => {
+                match depth {
+                    2 => entries.extend(entry.take().and_then(|entry| entry.read(base.as_ref()))),
+                    3 => field.clear(),
+                    _ => {}
+                }
+                depth -= 1;
+            }
+            // Cut short: the pages after the cut would be taken for gone
+            XmlEvent::Eof if depth > 0 => return Err(parse_error(format!("sitemap {} ends early", url))),
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+    }
+
+    match root.as_deref() {
+        Some("urlset") => Ok(Sitemap::UrlSet(entries)),
+        Some("sitemapindex") => Ok(Sitemap::Index(entries)),
+        _ => Err(parse_error(format!("sitemap {}", url))),
+    }
+}
+
+/// An entry's text, as read.
+#[derive(Default)]
+struct RawEntry {
+    loc: String,
+    lastmod: String,
+}
+
+impl RawEntry {
+    /// Adds text of the child element `field`; only `<loc>` and
+    /// `<lastmod>` are kept.
+    fn push(&mut self, field: &str, text: &str) {
+        match field {
+            "loc" => self.loc.push_str(text),
+            "lastmod" => self.lastmod.push_str(text),
+            _ => {}
+        }
+    }
+
+    /// The entry, if its `loc` is a URL. A `lastmod` that can't be read is
+    /// left out.
+    fn read(self, base: Option<&Url>) -> Option<SitemapEntry> {
+        let loc = self.loc.trim();
+        let loc = match Url::parse(loc) {
+            Ok(absolute) => absolute,
+            Err(_) => base?.join(loc).ok()?,
+        };
+        Some(SitemapEntry { loc: loc.to_string(), lastmod: parse_lastmod(&self.lastmod) })
+    }
+}
+
+/// A `lastmod` in any W3C datetime form a sitemap uses: full, without
+/// seconds, or a date alone (the end of that day, Tulsa time).
+pub fn parse_lastmod(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Some(at.with_timezone(&Utc));
+    }
+    if let Ok(at) = DateTime::parse_from_str(text, "%Y-%m-%dT%H:%M%:z") {
+        return Some(at.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+    Some(local_to_utc(TULSA_TZ, date + Duration::days(1), NaiveTime::MIN))
+}
+
+// =============================================================================
+// SCRAPER
+// =============================================================================
+
+/// Scraper for one site's event pages, found through its sitemap.
+pub struct SitemapScraper {
+    site: SitemapSite,
+    discovery: SitemapDiscovery,
+}
+
+impl SitemapScraper {
+    pub fn new(site: SitemapSite) -> Self {
+        let discovery = SitemapDiscovery::new(&site.url, &site.config);
+        Self { site, discovery }
+    }
+
+    /// The page at `url`, read as a `jsonld` source's page would be.
+    fn page(&self, url: &str) -> JsonLdPage {
+        JsonLdPage {
+            source_id: self.site.source_id.clone(),
+            name: self.site.name.clone(),
+            url: url.to_string(),
+            default_category: self.site.default_category.clone(),
+            default_venue: self.site.default_venue.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl EventScraper for SitemapScraper {
+    fn name(&self) -> &str {
+        &self.site.name
+    }
+
+    fn source_id(&self) -> &str {
+        &self.site.source_id
+    }
+
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        let pages = self.discovery.discover(fetcher).await?;
+        // Like a venue scraper whose selectors stop matching
+        if pages.is_empty() {
+            return Err(ScraperError::Parse {
+                selector: format!("<loc> with {}", self.discovery.pattern),
+                context: self.discovery.sitemap_url.clone(),
+            });
+        }
+
+        let mut events = Vec::new();
+        let mut fetched = 0;
+        let mut pages_read = 0;
+        let mut last_error = None;
+        for entry in pages {
+            if let Some(lastmod) = entry.lastmod {
+                if fetcher.unchanged_since(&entry.loc, lastmod).await {
+                    continue;
+                }
+            }
+            if fetched == self.discovery.max_urls {
+                fetcher.defer(&entry.loc).await;
+                continue;
+            }
+            fetched += 1;
+
+            let page = self.page(&entry.loc);
+            match fetcher.get_events(&entry.loc, |html| jsonld::parse_page(html, &page)).await {
+                Ok(found) => {
+                    pages_read += 1;
+                    events.extend(found);
+                }
+                Err(ScraperError::Parse { .. }) => {
+                    pages_read += 1;
+                    tracing::info!(url = %entry.loc, "no event on a sitemap page; skipped");
+                }
+                // Counted by the fetcher; the other pages still run
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        // Not one page: the site is down, not a page missing
+        match last_error {
+            Some(e) if pages_read == 0 => Err(e),
+            _ => Ok(events),
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::scraper::fetch::FetchPool;
+
+    const INDEX: &str = include_str!("../../../tests/fixtures/sitemap/sitemap.xml");
+    const PAGES: &str = include_str!("../../../tests/fixtures/sitemap/pages.xml");
+    const EVENTS: &str = include_str!("../../../tests/fixtures/sitemap/events-2026.xml");
+    const EVENTS_GZ: &[u8] = include_bytes!("../../../tests/fixtures/sitemap/events-2026.xml.gz");
+
+    /// Where the fixtures' pages are.
+    const SITE: &str = "https://www.tulsapac.example";
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn site(url: &str, config: SitemapConfig) -> SitemapSite {
+        SitemapSite {
+            source_id: "tulsa_pac".to_string(),
+            name: "Tulsa PAC".to_string(),
+            url: url.to_string(),
+            default_category: Some("arts".to_string()),
+            default_venue: Some("Tulsa Performing Arts Center".to_string()),
+            config,
+        }
+    }
+
+    /// A page with one JSON-LD event at `url`.
+    fn event_page(name: &str, url: &str) -> String {
+        format!(
+            r#"<html><head><script type="application/ld+json">
+            {{"@type": "TheaterEvent", "name": "{}", "startDate": "2026-12-05T19:30:00-06:00", "url": "{}"}}
+            </script></head></html>"#,
+            name, url
+        )
+    }
+
+    /// `data` gzipped, as a server would send a `.xml.gz`.
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn reads_an_index_and_its_sitemaps() {
+        let Sitemap::Index(sitemaps) = parse_sitemap(INDEX, &format!("{}/sitemap.xml", SITE)).unwrap() else {
+            panic!("not an index");
+        };
+        assert_eq!(
+            sitemaps,
+            [
+                SitemapEntry {
+                    loc: format!("{}/sitemap-pages.xml", SITE),
+                    lastmod: Some(utc("2026-09-30T14:02:11Z")),
+                },
+                // A date alone: the end of that day in Tulsa
+                SitemapEntry {
+                    loc: format!("{}/sitemaps/events-2026.xml.gz", SITE),
+                    lastmod: Some(utc("2026-10-15T05:00:00Z")),
+                },
+            ]
+        );
+
+        // Gzipped as the site serves it
+        let gzipped = format!("{}/sitemaps/events-2026.xml.gz", SITE);
+        assert_eq!(unpack(EVENTS_GZ, &gzipped).unwrap(), EVENTS);
+        assert!(matches!(unpack(&EVENTS_GZ[..100], &gzipped), Err(ScraperError::Parse { .. })));
+
+        let Sitemap::UrlSet(pages) = parse_sitemap(EVENTS, &gzipped).unwrap() else {
+            panic!("not a urlset");
+        };
+        let locs: Vec<&str> = pages.iter().map(|page| page.loc.as_str()).collect();
+        // The image's <loc> isn't a page; &amp; is unescaped
+        assert_eq!(
+            locs,
+            [
+                "https://www.tulsapac.example/events/hamilton/",
+                "https://www.tulsapac.example/events/tulsa-ballet-nutcracker/",
+                "https://www.tulsapac.example/events/tulsa-opera-carmen/?utm_source=sitemap&ref=1",
+                "https://www.tulsapac.example/venues/chapman-music-hall/",
+            ]
+        );
+        assert_eq!(pages[0].lastmod, Some(utc("2026-10-12T21:45:00Z")));
+        assert_eq!(pages[2].lastmod, None);
+    }
+
+
+    #[test]
+    fn gzip_bombs_are_refused() {
+        let url = format!("{}/sitemaps/bomb.xml.gz", SITE);
+        let bomb = gzip(&vec![b' '; MAX_OUTPUT + 1]);
+        assert!(bomb.len() < 1024 * 1024);
+        assert!(matches!(unpack(&bomb, &url), Err(ScraperError::Parse { .. })));
+
+        // Right at the limit is fine, as are several members one after another
+        assert_eq!(unpack(&gzip(&vec![b' '; MAX_OUTPUT]), &url).unwrap().len(), MAX_OUTPUT);
+        let joined = [gzip(b"<urlset>"), gzip(b"</urlset>")].concat();
+        assert_eq!(unpack(&joined, &url).unwrap(), "<urlset></urlset>");
+    }
+    #[test]
+    fn other_xml_isnt_a_sitemap() {
+        assert!(matches!(parse_sitemap("<rss><channel/></rss>", SITE), Err(ScraperError::Parse { .. })));
+        assert!(matches!(parse_sitemap("<urlset><url>", SITE), Err(ScraperError::Parse { .. })));
+        assert_eq!(parse_sitemap("<urlset/>", SITE).unwrap(), Sitemap::UrlSet(Vec::new()));
+        // Relative, against the protocol: taken relative to the sitemap
+        let relative = "<urlset><url><loc>/events/a/</loc><lastmod>soon</lastmod></url></urlset>";
+        assert_eq!(
+            parse_sitemap(relative, &format!("{}/sitemap.xml", SITE)).unwrap(),
+            Sitemap::UrlSet(vec![SitemapEntry { loc: format!("{}/events/a/", SITE), lastmod: None }])
+        );
+    }
+
+    #[test]
+    fn lastmod_takes_every_w3c_form() {
+        assert_eq!(parse_lastmod("2026-10-12T16:45:00-05:00"), Some(utc("2026-10-12T21:45:00Z")));
+        assert_eq!(parse_lastmod(" 2026-10-12T16:45-05:00 "), Some(utc("2026-10-12T21:45:00Z")));
+        assert_eq!(parse_lastmod("2026-10-12T21:45:00.250Z"), Some(utc("2026-10-12T21:45:00.250Z")));
+        // CST by December
+        assert_eq!(parse_lastmod("2026-12-01"), Some(utc("2026-12-02T06:00:00Z")));
+        assert_eq!(parse_lastmod("last week"), None);
+    }
+
+    #[test]
+    fn urls_are_matched_on_their_path() {
+        let discovery = SitemapDiscovery::new(SITE, &SitemapConfig::default());
+        assert_eq!(discovery.sitemap_url, "https://www.tulsapac.example/sitemap.xml");
+        assert!(discovery.matches("https://www.tulsapac.example/events/hamilton/"));
+        assert!(discovery.matches("https://www.tulsapac.example/en/events/hamilton?date=2026-12-05"));
+        // The listing itself, other sections, the pattern only in the query
+        assert!(!discovery.matches("https://www.tulsapac.example/events/"));
+        assert!(!discovery.matches("https://www.tulsapac.example/events"));
+        assert!(!discovery.matches("https://www.tulsapac.example/venues/chapman-music-hall/"));
+        assert!(!discovery.matches("https://www.tulsapac.example/search?q=/events/"));
+        assert!(!discovery.matches("ftp://www.tulsapac.example/events/hamilton/"));
+
+        let gzipped = format!("{}/sitemaps/events-2026.xml.gz", SITE);
+        assert_eq!(sitemap_url(&gzipped), gzipped);
+        assert_eq!(sitemap_url("https://www.tulsapac.example/calendar/"), "https://www.tulsapac.example/sitemap.xml");
+    }
+
+    #[test]
+    fn config_is_checked() {
+        assert_eq!(SitemapConfig::from_value(&serde_json::json!({})), Ok(SitemapConfig::default()));
+        assert_eq!(
+            SitemapConfig::from_value(&serde_json::json!({ "pattern": "/shows/", "max_urls": 10 })),
+            Ok(SitemapConfig { pattern: "/shows/".to_string(), max_urls: 10 })
+        );
+        assert!(SitemapConfig::from_value(&serde_json::json!({ "pattern": " " })).is_err());
+        assert!(SitemapConfig::from_value(&serde_json::json!({ "max_urls": 0 })).is_err());
+        assert!(SitemapConfig::from_value(&serde_json::json!({ "max_urls": 501 })).is_err());
+        assert!(SitemapConfig::from_value(&serde_json::json!({ "patern": "/shows/" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn crawls_nested_and_gzipped_sitemaps_up_to_the_cap() {
+        let server = MockServer::start().await;
+        let here = |xml: &str| xml.replace(SITE, &server.uri());
+        let serve = |at: &str, body: Vec<u8>, times: u64| {
+            Mock::given(method("GET"))
+                .and(path(at.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+                .expect(times)
+        };
+        serve("/sitemap.xml", here(INDEX).into_bytes(), 1).mount(&server).await;
+        serve("/sitemap-pages.xml", here(PAGES).into_bytes(), 1).mount(&server).await;
+        serve("/sitemaps/events-2026.xml.gz", gzip(here(EVENTS).as_bytes()), 1).mount(&server).await;
+
+        let hamilton = format!("{}/events/hamilton/", server.uri());
+        serve("/events/hamilton/", event_page("Hamilton", &hamilton).into_bytes(), 1).mount(&server).await;
+        let nutcracker = r#"<html><head><meta property="og:type" content="event">
+            <meta property="og:title" content="The Nutcracker">
+            <meta property="event:start_time" content="2026-12-12T19:30:00-06:00"></head></html>"#;
+        serve("/events/tulsa-ballet-nutcracker/", nutcracker.as_bytes().to_vec(), 1).mount(&server).await;
+        serve("/events/chamber-music-tulsa-fall-series/", b"<p>Season on sale soon</p>".to_vec(), 1)
+            .mount(&server)
+            .await;
+        // Past the cap: newest first, and it has no lastmod
+        serve("/events/tulsa-opera-carmen/", Vec::new(), 0).mount(&server).await;
+
+        let config = SitemapConfig { max_urls: 3, ..SitemapConfig::default() };
+        let scraper = SitemapScraper::new(site(&format!("{}/", server.uri()), config));
+        let fetcher = Fetcher::for_tests();
+        let events = scraper.scrape(&fetcher).await.unwrap();
+
+        let titles: Vec<&str> = events.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(titles, ["The Nutcracker", "Hamilton"]);
+        assert_eq!(events[1].source_url, hamilton);
+        assert_eq!(events[0].source_url, format!("{}/events/tulsa-ballet-nutcracker/", server.uri()));
+        assert_eq!(events[0].venue.as_deref(), Some("Tulsa Performing Arts Center"));
+        assert_eq!(events[1].category.as_deref(), Some("arts"));
+        // Three sitemaps and three pages; nothing failed
+        assert_eq!(fetcher.pages_fetched(), 6);
+        assert!(fetcher.failed_urls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_missing_nested_sitemap_is_skipped_but_a_missing_root_fails() {
+        let server = MockServer::start().await;
+        let index = INDEX.replace(SITE, &server.uri());
+        Mock::given(method("GET"))
+            .and(path("/sitemap.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(index))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/sitemap-pages.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(PAGES.replace(SITE, &server.uri())))
+            .mount(&server)
+            .await;
+
+        let discovery = SitemapDiscovery::new(&server.uri(), &SitemapConfig::default());
+        let fetcher = Fetcher::for_tests();
+        let pages = discovery.discover(&fetcher).await.unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(fetcher.failed_urls().len(), 1);
+
+        let missing = SitemapDiscovery::new(&format!("{}/missing.xml", server.uri()), &SitemapConfig::default());
+        assert!(matches!(missing.discover(&Fetcher::for_tests()).await, Err(ScraperError::Http(_))));
+    }
+
+    #[tokio::test]
+    async fn pages_unchanged_since_they_were_read_arent_fetched() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let db = sqlx::PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+
+        let server = MockServer::start().await;
+        let run = uuid::Uuid::new_v4();
+        let page = |name: &str| format!("{}/events/{}/{}/", server.uri(), run, name);
+        let (old, today, unknown) = (page("old"), page("today"), page("unknown"));
+        // Changed yesterday; changed some time today; no lastmod
+        let sitemap = format!(
+            "<urlset><url><loc>{}</loc><lastmod>{}</lastmod></url>\
+             <url><loc>{}</loc><lastmod>{}</lastmod></url><url><loc>{}</loc></url></urlset>",
+            old,
+            (Utc::now() - Duration::days(1)).to_rfc3339(),
+            today,
+            Utc::now().with_timezone(&TULSA_TZ).format("%Y-%m-%d"),
+            unknown,
+        );
+        Mock::given(method("GET"))
+            .and(path("/sitemap.xml"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(sitemap))
+            .mount(&server)
+            .await;
+        // Today's page changes between the runs
+        for (url, title) in [(&old, "Show"), (&today, "Show"), (&today, "Show (Moved)"), (&unknown, "Show")] {
+            Mock::given(method("GET"))
+                .and(path(Url::parse(url).unwrap().path()))
+                .respond_with(ResponseTemplate::new(200).set_body_string(event_page(title, url)))
+                .up_to_n_times(1)
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let event: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time, scraped, last_seen_at) \
+             VALUES ('Show', $1, NOW() + INTERVAL '1 week', TRUE, NOW() - INTERVAL '1 week') RETURNING id",
+        )
+            .bind(&unknown)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        let pool = Arc::new(FetchPool::for_tests());
+        let sitemap_url = format!("{}/sitemap.xml", server.uri());
+        let first = Fetcher::new(pool.clone()).with_cache(db.clone());
+        let events = SitemapScraper::new(site(&sitemap_url, SitemapConfig::default())).scrape(&first).await.unwrap();
+        assert_eq!(events.len(), 3);
+        first.commit_cache().await.unwrap();
+
+        // The old page isn't asked for; today's takes the one fetch allowed,
+        // and the page past it keeps its event
+        let config = SitemapConfig { max_urls: 1, ..SitemapConfig::default() };
+        let second = Fetcher::new(pool).with_cache(db.clone());
+        let events = SitemapScraper::new(site(&sitemap_url, config)).scrape(&second).await.unwrap();
+        assert_eq!(events.iter().map(|event| event.source_url.as_str()).collect::<Vec<_>>(), [today.as_str()]);
+        assert_eq!((second.pages_fetched(), second.pages_unchanged()), (2, 1));
+        second.commit_cache().await.unwrap();
+
+        let seen: bool = sqlx::query_scalar("SELECT last_seen_at > NOW() - INTERVAL '1 minute' FROM events WHERE id = $1")
+            .bind(event)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert!(seen, "the deferred page's event wasn't marked seen");
+
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&db).await.unwrap();
+        sqlx::query("DELETE FROM fetch_cache WHERE url = ANY($1)")
+            .bind([old, today, unknown].as_slice())
+            .execute(&db)
+            .await
+            .unwrap();
+    }
+}
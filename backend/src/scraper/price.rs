@@ -0,0 +1,245 @@
+//! # Price Parsing
+//!
+//! Turns the free-form price text found on event pages into the
+//! `price_min` / `price_max` / `is_free` fields of `CreateEvent`.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Supported Formats
+//! | Input | min | max | is_free |
+//! |-------|-----|-----|---------|
+//! | `"Free"`, `"FREE admission"` | 0 | 0 | true |
+//! | `"$10"` | 10 | 10 | false |
+//! | `"$10-15"`, `"$10 – $15"` | 10 | 15 | false |
+//! | `"$1,250.00"` | 1250 | 1250 | false |
+//! | `"Ages 21+ · Doors 7pm · $15"` | 15 | 15 | false |
+//! | `"Free – $25 VIP"` | 0 | 25 | true |
+//! | `"Donations welcome"` | 0 | - | true |
+//! | `"TBA"` | - | - | - (returns `None`) |
+//!
+//! Only `$` amounts are prices (plus the bare upper end of `$10-15`), so
+//! ages, times and dates next to them are ignored. Text that says "free"
+//! alongside paid tiers is free, with the dearest tier as the max.
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Structured price information extracted from a text snippet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedPrice {
+    pub price_min: Option<f64>,
+    pub price_max: Option<f64>,
+    pub is_free: bool,
+}
+
+// =============================================================================
+// PARSER
+// =============================================================================
+
+/// Parses a price string like `"$10–$15"` or `"Free"`.
+///
+/// Returns `None` when the text contains no recognizable price, so callers
+/// can leave the fields empty instead of guessing.
+pub fn parse_price(text: &str) -> Option<ParsedPrice> {
+    let lower = text.to_lowercase();
+
+    // Pay-what-you-can: nothing is required, so treat as free with no max
+    if lower.contains("donation") {
+        return Some(ParsedPrice {
+            price_min: Some(0.0),
+            price_max: None,
+            is_free: true,
+        });
+    }
+
+    let amounts = extract_amounts(&lower);
+    let free = lower.split(|c: char| !c.is_alphabetic()).any(|word| word == "free");
+
+    if amounts.is_empty() {
+        if free {
+            return Some(ParsedPrice {
+                price_min: Some(0.0),
+                price_max: Some(0.0),
+                is_free: true,
+            });
+        }
+        return None;
+    }
+
+    let min = amounts.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = amounts.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    // "Free – $25 VIP": getting in costs nothing
+    if free {
+        return Some(ParsedPrice {
+            price_min: Some(0.0),
+            price_max: Some(max),
+            is_free: true,
+        });
+    }
+
+    Some(ParsedPrice {
+        price_min: Some(min),
+        price_max: Some(max),
+        is_free: max == 0.0,
+    })
+}
+
+/// Pulls the `$` amounts out of the text, ignoring thousands separators.
+///
+/// `"$10 – $15"` → `[10.0, 15.0]`, `"$1,250.00"` → `[1250.0]`. The upper
+/// end of a range may drop its `$` (`"$10-15"` → `[10.0, 15.0]`); any
+/// other bare number (`"Ages 21+"`, `"7pm"`) isn't a price.
+fn extract_amounts(text: &str) -> Vec<f64> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut amounts = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '$' {
+            i += 1;
+            continue;
+        }
+        let Some((amount, end)) = number_at(&chars, i + 1) else {
+            i += 1;
+            continue;
+        };
+        amounts.push(amount);
+        i = end;
+
+        if let Some((upper, end)) = range_end(&chars, end).and_then(|start| number_at(&chars, start)) {
+            amounts.push(upper);
+            i = end;
+        }
+    }
+
+    amounts
+}
+
+/// Where the bare upper end of a range starts, if `start` begins one:
+/// a dash (`-`, `–`, `—`) or `to`, then a digit.
+fn range_end(chars: &[char], start: usize) -> Option<usize> {
+    let skip_spaces = |mut i: usize| {
+        while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+            i += 1;
+        }
+        i
+    };
+
+    let at = skip_spaces(start);
+    let after = match chars.get(at..at + 2) {
+        Some(['t', 'o']) => at + 2,
+        _ if matches!(chars.get(at), Some('-' | '–' | '—')) => at + 1,
+        _ => return None,
+    };
+    let upper = skip_spaces(after);
+    chars.get(upper).is_some_and(|c| c.is_ascii_digit()).then_some(upper)
+}
+
+/// The number starting at `start`, and the index just past it.
+fn number_at(chars: &[char], start: usize) -> Option<(f64, usize)> {
+    let mut digits = String::new();
+    let mut i = start;
+    while let Some(&c) = chars.get(i) {
+        let continues_number = !digits.is_empty()
+            && matches!(c, ',' | '.')
+            && chars.get(i + 1).is_some_and(|next| next.is_ascii_digit());
+
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else if continues_number {
+            if c == '.' {
+                digits.push(c);
+            }
+        } else {
+            break;
+        }
+        i += 1;
+    }
+
+    digits.parse().ok().map(|value| (value, i))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(min: f64, max: f64) -> Option<ParsedPrice> {
+        Some(ParsedPrice {
+            price_min: Some(min),
+            price_max: Some(max),
+            is_free: false,
+        })
+    }
+
+    #[test]
+    fn parses_free() {
+        let free = Some(ParsedPrice {
+            price_min: Some(0.0),
+            price_max: Some(0.0),
+            is_free: true,
+        });
+        assert_eq!(parse_price("Free"), free);
+        assert_eq!(parse_price("FREE admission"), free);
+        assert_eq!(parse_price("$0"), free);
+    }
+
+    #[test]
+    fn parses_single_price() {
+        assert_eq!(parse_price("$10"), price(10.0, 10.0));
+        assert_eq!(parse_price("Tickets: $12.50"), price(12.5, 12.5));
+        assert_eq!(parse_price("$1,250.00"), price(1250.0, 1250.0));
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_price("$10-15"), price(10.0, 15.0));
+        assert_eq!(parse_price("$10–$15"), price(10.0, 15.0));
+        assert_eq!(parse_price("$25 - $10"), price(10.0, 25.0));
+    }
+
+    #[test]
+    fn ignores_numbers_that_arent_prices() {
+        assert_eq!(parse_price("Ages 21+ · $10"), price(10.0, 10.0));
+        assert_eq!(parse_price("Doors 7pm · $15"), price(15.0, 15.0));
+        assert_eq!(parse_price("Show at 8 · $20 to 30"), price(20.0, 30.0));
+        assert_eq!(parse_price("Ages 18+"), None);
+    }
+
+    #[test]
+    fn free_with_paid_tiers_is_free() {
+        let free_to_25 = Some(ParsedPrice {
+            price_min: Some(0.0),
+            price_max: Some(25.0),
+            is_free: true,
+        });
+        assert_eq!(parse_price("Free – $25 VIP"), free_to_25);
+        assert_eq!(parse_price("$10-25, free for members"), free_to_25);
+        // "free" inside another word doesn't count
+        assert_eq!(parse_price("Freestyle night $10"), price(10.0, 10.0));
+    }
+
+    #[test]
+    fn parses_donations_as_free_with_no_max() {
+        assert_eq!(
+            parse_price("Donations welcome"),
+            Some(ParsedPrice {
+                price_min: Some(0.0),
+                price_max: None,
+                is_free: true,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_text_is_none() {
+        assert_eq!(parse_price("TBA"), None);
+        assert_eq!(parse_price(""), None);
+    }
+}
@@ -0,0 +1,366 @@
+//! # Scrape Quarantine
+//!
+//! Scraped events that failed validation (blank title, end before start,
+//! a source URL that isn't one), kept as the scraper reported them instead
+//! of dropped, so a bad parse can be seen and the event saved by hand.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Environment Variables
+//! ```text
+//! QUARANTINE_KEEP_DAYS=30  # days before an untouched row is purged
+//! ```
+//!
+//! ## Lifecycle
+//! 1. `persist_scraped_events` adds an event that fails
+//!    `CreateEvent::validate` (not on dry runs), with the errors
+//! 2. An admin lists the queue (`GET /api/admin/quarantine`) and either
+//!    - retries a row with fixed fields (`POST .../:id/retry`): it is
+//!      validated again and, if it passes, stored like any scraped event
+//!      and removed from the queue; if not, the fixes and the new errors
+//!      are kept on the row
+//!    - or deletes it (`DELETE .../:id`)
+//! 3. Rows not touched for `QUARANTINE_KEEP_DAYS` are purged by the
+//!    archive job (`services::archive`)
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::Pagination;
+use crate::error::AppError;
+use crate::scraper::persist::{self, Saved};
+use crate::scraper::traits::ScrapedEvent;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default days a quarantined event is kept without being touched.
+pub const DEFAULT_QUARANTINE_KEEP_DAYS: u64 = 30;
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// One quarantined event, as stored in `scrape_quarantine`.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct QuarantinedEvent {
+    pub id: Uuid,
+    /// The scraper's `name()`, credited if the event is saved
+    pub source_name: String,
+    /// The `ScrapedEvent`, field for field, with any fixes applied
+    pub raw: Json<Value>,
+    /// Why it failed, the last time it was tried
+    pub error: String,
+    /// Its `source_url` as scraped
+    pub page_url: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One page of the quarantine, newest first.
+#[derive(Debug, Serialize)]
+pub struct QuarantinePage {
+    pub items: Vec<QuarantinedEvent>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+/// What a successful retry did with the event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryOutcome {
+    Created,
+    /// It matched an event we have, which now has its fields
+    Updated,
+    /// It matched an event we have, which already had its fields
+    Unchanged,
+}
+
+/// Result of `POST /api/admin/quarantine/:id/retry`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetryResult {
+    pub event_id: Uuid,
+    pub outcome: RetryOutcome,
+}
+
+impl From<&Saved> for RetryResult {
+    fn from(saved: &Saved) -> Self {
+        let outcome = match saved {
+            Saved::Created(_) => RetryOutcome::Created,
+            Saved::Updated { .. } => RetryOutcome::Updated,
+            Saved::Unchanged(_) => RetryOutcome::Unchanged,
+        };
+        Self { event_id: saved.id(), outcome }
+    }
+}
+
+// =============================================================================
+// QUEUE
+// =============================================================================
+
+/// Quarantines `event`, scraped by `source_name`, for `error`.
+pub async fn add(pool: &PgPool, source_name: &str, event: &ScrapedEvent, error: &str) -> Result<Uuid, sqlx::Error> {
+    let raw = serde_json::to_value(event).expect("a ScrapedEvent serializes");
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO scrape_quarantine (source_name, raw, error, page_url)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+        .bind(source_name)
+        .bind(Json(raw))
+        .bind(error)
+        .bind(&event.source_url)
+        .fetch_one(pool)
+        .await
+}
+
+/// One page of the quarantine, newest first.
+pub async fn list(pool: &PgPool, pagination: Pagination) -> Result<QuarantinePage, sqlx::Error> {
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scrape_quarantine")
+        .fetch_one(pool)
+        .await?;
+
+    let items = sqlx::query_as::<_, QuarantinedEvent>(
+        r#"
+        SELECT * FROM scrape_quarantine
+        ORDER BY created_at DESC, id
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+        .bind(pagination.per_page as i64)
+        .bind(pagination.offset())
+        .fetch_all(pool)
+        .await?;
+
+    Ok(QuarantinePage {
+        items,
+        total,
+        page: pagination.page,
+        per_page: pagination.per_page,
+    })
+}
+
+/// One quarantined event, if it's there.
+pub async fn find(pool: &PgPool, id: Uuid) -> Result<Option<QuarantinedEvent>, sqlx::Error> {
+    sqlx::query_as::<_, QuarantinedEvent>("SELECT * FROM scrape_quarantine WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Applies `fixes` (`ScrapedEvent` fields, by name) to a quarantined event
+/// and tries it again.
+///
+/// # Returns
+/// The stored event's id and what storing it did. The row leaves the
+/// quarantine.
+///
+/// # Errors
+/// - `NotFound` if there's no such row
+/// - `BadRequest` if the fixed fields aren't a `ScrapedEvent` (an unknown
+///   field, a `start_time` that isn't a timestamp); the row is unchanged
+/// - `Validation` if the fixed event still fails; the row keeps the
+///   fixes and the new errors, so the next retry builds on them
+pub async fn retry(pool: &PgPool, id: Uuid, fixes: Map<String, Value>) -> Result<RetryResult, AppError> {
+    let row = find(pool, id).await?.ok_or_else(|| AppError::not_found("quarantined event"))?;
+
+    let mut raw = match row.raw.0 {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    };
+    for (field, value) in fixes {
+        if !SCRAPED_FIELDS.contains(&field.as_str()) {
+            return Err(AppError::BadRequest(format!("unknown field `{}`", field)));
+        }
+        raw.insert(field, value);
+    }
+    let raw = Value::Object(raw);
+    let scraped: ScrapedEvent =
+        serde_json::from_value(raw.clone()).map_err(|e| AppError::BadRequest(format!("invalid fields: {}", e)))?;
+
+    let now = Utc::now();
+    let external_id = scraped.external_id.clone();
+    let event = scraped.into_create_event(&row.source_name);
+    if let Err(errors) = event.validate(now) {
+        let e = AppError::from(errors);
+        sqlx::query("UPDATE scrape_quarantine SET raw = $2, error = $3, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(Json(raw))
+            .bind(e.to_string())
+            .execute(pool)
+            .await?;
+        return Err(e);
+    }
+
+    let saved = persist::save(pool, event, external_id.as_deref(), now, false).await?;
+    delete(pool, id).await?;
+
+    Ok(RetryResult::from(&saved))
+}
+
+/// Fields a retry may fix: those of `ScrapedEvent`.
+const SCRAPED_FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "venue",
+    "venue_address",
+    "location",
+    "source_url",
+    "start_time",
+    "end_time",
+    "category",
+    "external_category",
+    "external_id",
+    "tags",
+    "price_min",
+    "price_max",
+    "is_free",
+    "image_url",
+];
+
+/// Removes a quarantined event. Returns whether it was there.
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM scrape_quarantine WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Removes rows not touched for `keep_days`. Returns how many.
+pub async fn purge(pool: &PgPool, keep_days: u64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM scrape_quarantine WHERE updated_at < NOW() - make_interval(days => $1)")
+        .bind(keep_days as i32)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    use crate::scraper::persist::persist_scraped_events;
+
+    async fn test_pool() -> Option<PgPool> {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return None;
+        };
+        let pool = PgPool::connect(&url).await.expect("connect to test database");
+        sqlx::migrate!("./migrations").run(&pool).await.expect("run migrations");
+        Some(pool)
+    }
+
+    async fn quarantined(pool: &PgPool, url: &str) -> Vec<QuarantinedEvent> {
+        sqlx::query_as("SELECT * FROM scrape_quarantine WHERE page_url = $1")
+            .bind(url)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn retry_fields_are_the_scraped_fields() {
+        let fields = serde_json::to_value(ScrapedEvent::default()).unwrap();
+        let mut names: Vec<&str> = fields.as_object().unwrap().keys().map(String::as_str).collect();
+        let mut expected = SCRAPED_FIELDS.to_vec();
+        names.sort();
+        expected.sort();
+        assert_eq!(names, expected);
+    }
+
+    #[tokio::test]
+    async fn invalid_events_are_fixed_and_retried_into_events() {
+        let Some(pool) = test_pool().await else { return };
+        let run = Uuid::new_v4();
+        let url = format!("https://example.com/events/{}", run);
+        let venue = format!("Quarantine Hall {}", run.simple());
+        let start = Utc::now() + Duration::days(10);
+        let mut event = ScrapedEvent::new("   ", &url, start);
+        event.venue = Some(venue.clone());
+
+        // A dry run doesn't quarantine; a real one does, and counts it skipped
+        let summary = persist_scraped_events(&pool, vec![event.clone()], "quarantine_test", "Quarantine Test", true).await;
+        assert_eq!(summary.skipped, 1);
+        assert!(quarantined(&pool, &url).await.is_empty());
+
+        let summary = persist_scraped_events(&pool, vec![event], "quarantine_test", "Quarantine Test", false).await;
+        assert_eq!(summary.skipped, 1);
+        let rows = quarantined(&pool, &url).await;
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.source_name, "Quarantine Test");
+        assert!(row.error.contains("title"), "{}", row.error);
+        assert_eq!(row.raw.0["venue"], venue.as_str());
+
+        // The wrong shape leaves the row alone
+        let bad = json!({ "start_time": "next Tuesday" }).as_object().unwrap().clone();
+        assert!(matches!(retry(&pool, row.id, bad).await, Err(AppError::BadRequest(_))));
+        let unknown = json!({ "titel": "Typo" }).as_object().unwrap().clone();
+        assert!(matches!(retry(&pool, row.id, unknown).await, Err(AppError::BadRequest(_))));
+
+        // A fix that's still invalid is kept, with the new error
+        let ends_early = json!({ "end_time": start - Duration::hours(1) }).as_object().unwrap().clone();
+        assert!(matches!(retry(&pool, row.id, ends_early).await, Err(AppError::Validation(_))));
+        let row = find(&pool, row.id).await.unwrap().unwrap();
+        assert!(row.error.contains("title") && row.error.contains("end_time"), "{}", row.error);
+        assert!(row.updated_at > row.created_at);
+
+        // Fixing the rest stores it, credited to its source
+        let fixes = json!({ "title": "Quarantine Jazz", "end_time": null }).as_object().unwrap().clone();
+        let result = retry(&pool, row.id, fixes).await.unwrap();
+        assert_eq!(result.outcome, RetryOutcome::Created);
+        assert!(find(&pool, row.id).await.unwrap().is_none());
+
+        let (title, stored_venue, source): (String, Option<String>, Option<String>) =
+            sqlx::query_as("SELECT title, venue, source_name FROM events WHERE id = $1")
+                .bind(result.event_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(title, "Quarantine Jazz");
+        assert_eq!(stored_venue, Some(venue));
+        assert_eq!(source.as_deref(), Some("Quarantine Test"));
+
+        // Gone once retried
+        let fixes = json!({ "title": "Again" }).as_object().unwrap().clone();
+        assert!(matches!(retry(&pool, row.id, fixes).await, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn old_rows_are_purged_and_rows_can_be_deleted() {
+        let Some(pool) = test_pool().await else { return };
+        let url = format!("https://example.com/events/{}", Uuid::new_v4());
+        let event = ScrapedEvent::new("", &url, Utc::now());
+        let old = add(&pool, "Quarantine Test", &event, "invalid fields: title").await.unwrap();
+        let recent = add(&pool, "Quarantine Test", &event, "invalid fields: title").await.unwrap();
+        sqlx::query("UPDATE scrape_quarantine SET updated_at = NOW() - INTERVAL '31 days' WHERE id = $1")
+            .bind(old)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(purge(&pool, DEFAULT_QUARANTINE_KEEP_DAYS).await.unwrap() >= 1);
+        assert!(find(&pool, old).await.unwrap().is_none());
+        assert!(find(&pool, recent).await.unwrap().is_some());
+
+        assert!(delete(&pool, recent).await.unwrap());
+        assert!(!delete(&pool, recent).await.unwrap());
+    }
+}
@@ -0,0 +1,663 @@
+//! # Scraper Registry
+//!
+//! Owns the shared fetch pool and every registered scraper, runs them,
+//! and stores what they find.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## A Run
+//! 1. The scraper fetches and parses its source (`EventScraper::scrape`),
+//!    through a `Fetcher` that honors robots.txt and spaces out requests,
+//!    and can skip pages unchanged since the last run (`cache.rs`)
+//! 2. Each event's image is checked, if the registry checks images
+//!    (`with_image_checks`, see `images.rs`): a broken one is left out
+//! 3. Each event is converted (`into_create_event`), validated and stored
+//!    (see `persist.rs`): a show we already list, by URL or by title, venue
+//!    and time, is updated if anything changed; otherwise it is inserted
+//! 4. If anything was stored, the fuzzy duplicate pass merges or queues
+//!    the same shows listed elsewhere under other titles
+//!    (`services::duplicates`)
+//! 5. The run is reported as a `ScrapeSummary`, and recorded in
+//!    `scrape_runs` (a row written at the start, finished at the end)
+//! 6. Upcoming events the source has stopped listing for a few good runs
+//!    are marked cancelled (`stale.rs`)
+//! 7. The events created are geocoded in the background, if the registry
+//!    has a geocoder (`with_geocoder`, see `services::geocoding`); the run
+//!    doesn't wait, and an event that can't be located keeps null
+//!    coordinates for the admin backfill to retry
+//!
+//! A failing event is logged and counted as skipped, and the rest of the
+//! run goes on. So does a page that can't be fetched after retrying (see
+//! `fetch.rs`); it is listed in `failed_urls`. A failing scrape (site down, markup changed) ends that
+//! scraper's run with `error` set; the other scrapers still run. So does a
+//! panicking scraper: the panic is its run's error.
+//!
+//! ## Parallelism
+//! Scrapers run concurrently, each in its own task, at most
+//! `SCRAPE_PARALLELISM` (default `DEFAULT_PARALLELISM`) at once across the
+//! whole registry: a batch, scheduled runs and manual runs all share the
+//! limit. Requests to any one site are still spaced out by the shared
+//! `FetchPool`, however many scrapers hit it. Runs start, and are
+//! recorded, in the order asked for; summaries come back in that order.
+//!
+//! ## Dry Runs
+//! `RunOptions::dry_run` does everything a run does, each event in a
+//! transaction that is rolled back, so its summary says what a real run
+//! would create and update without touching anything, event by event in
+//! `diff` (see `persist.rs`; also logged as a table). Its `scrape_runs`
+//! row is marked `dry_run` and left out of source health.
+//!
+//! ## Sources
+//! Scrapers written in code are `register`ed when the registry is built.
+//! `reload` then lines the registry up with `scrape_sources` (see
+//! `sources.rs`): registered scrapers whose row is disabled are left out,
+//! and iCal feeds and JSON-LD pages are built from their rows. Until the
+//! first `reload`, every registered scraper runs.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, RwLock};
+
+use chrono::Utc;
+use futures_util::FutureExt;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::scraper::fetch::{FailedUrl, FetchPool, Fetcher};
+use crate::scraper::images;
+use crate::scraper::traits::EventScraper;
+use crate::scraper::persist::{self, ScrapeDiff};
+use crate::scraper::platforms::ical::IcalFeed;
+use crate::scraper::runs;
+use crate::scraper::sources::{self, ScrapeSourceKind};
+use crate::scraper::stale;
+use crate::services::duplicates::{self, DuplicateThresholds};
+use crate::services::geocoding::{self, SharedGeocoder};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Scrapers running at once, unless configured (`SCRAPE_PARALLELISM`).
+pub const DEFAULT_PARALLELISM: usize = 4;
+
+// =============================================================================
+// SUMMARY
+// =============================================================================
+
+/// What one scraper run did.
+///
+/// Events found but neither created, updated nor skipped were unchanged
+/// (or the same show twice).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScrapeSummary {
+    /// The scraper's `source_id`
+    pub source: String,
+    pub found: usize,
+    pub created: usize,
+    pub updated: usize,
+    /// Invalid, or failed to save
+    pub skipped: usize,
+    /// URLs robots.txt kept us from fetching
+    pub disallowed: usize,
+    /// Pages that couldn't be fetched, even after retrying; the scraper
+    /// went on without them
+    pub failed_urls: Vec<FailedUrl>,
+    /// Pages downloaded and read
+    pub pages_fetched: usize,
+    /// Pages skipped as unchanged since the last run (see `cache.rs`)
+    pub pages_unchanged: usize,
+    /// Why the scrape itself failed, if it did
+    pub error: Option<String>,
+    /// What a dry run would have stored, event by event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<ScrapeDiff>,
+}
+
+/// How to run a scraper.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Save nothing (see "Dry Runs" above)
+    pub dry_run: bool,
+    /// The `scrape_batches` row this run belongs to, if any
+    pub batch: Option<Uuid>,
+}
+
+// =============================================================================
+// REGISTRY
+// =============================================================================
+
+/// The scrapers and the fetch pool they share.
+pub struct ScraperRegistry {
+    fetch: Arc<FetchPool>,
+    /// Written in code, in registration order
+    registered: Vec<Arc<dyn EventScraper>>,
+    /// What runs: the enabled registered scrapers, then the rows' own
+    scrapers: RwLock<Vec<Arc<dyn EventScraper>>>,
+    /// A permit per scraper running
+    limit: Arc<Semaphore>,
+    /// Locates the events runs create
+    geocoder: Option<SharedGeocoder>,
+    /// Checks scraped images before they're stored
+    check_images: bool,
+}
+
+impl ScraperRegistry {
+    /// An empty registry whose scrapers fetch through `fetch`, running
+    /// `DEFAULT_PARALLELISM` at once.
+    pub fn new(fetch: FetchPool) -> Self {
+        Self {
+            fetch: Arc::new(fetch),
+            registered: Vec::new(),
+            scrapers: RwLock::default(),
+            limit: Arc::new(Semaphore::new(DEFAULT_PARALLELISM)),
+            geocoder: None,
+            check_images: false,
+        }
+    }
+
+    /// Runs at most `parallelism` scrapers at once (at least one).
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.limit = Arc::new(Semaphore::new(parallelism.max(1)));
+        self
+    }
+
+    /// Geocodes the events each run creates with `geocoder` (`None`:
+    /// leave them as scraped).
+    pub fn with_geocoder(mut self, geocoder: Option<SharedGeocoder>) -> Self {
+        self.geocoder = geocoder;
+        self
+    }
+
+    /// Checks each run's images before storing them (see `images.rs`);
+    /// off unless asked for.
+    pub fn with_image_checks(mut self, check_images: bool) -> Self {
+        self.check_images = check_images;
+        self
+    }
+
+    /// The geocoder runs use, if any.
+    pub fn geocoder(&self) -> Option<SharedGeocoder> {
+        self.geocoder.clone()
+    }
+
+    /// A fetcher on the shared pool, for a one-off request outside any run
+    /// (`scraper::extract`).
+    pub fn fetcher(&self) -> Fetcher {
+        Fetcher::new(self.fetch.clone())
+    }
+
+    /// Adds a scraper; scrapers run in the order they were added.
+    pub fn register(mut self, scraper: impl EventScraper + 'static) -> Self {
+        let scraper: Arc<dyn EventScraper> = Arc::new(scraper);
+        self.registered.push(scraper.clone());
+        self.scrapers.get_mut().expect("scraper list poisoned").push(scraper);
+        self
+    }
+
+    /// The `source_id` of every scraper that runs, in run order.
+    pub fn sources(&self) -> Vec<String> {
+        self.current().iter().map(|scraper| scraper.source_id().to_string()).collect()
+    }
+
+    /// Whether `source` is a scraper written in code (enabled or not).
+    pub fn is_registered(&self, source: &str) -> bool {
+        self.registered.iter().any(|scraper| scraper.source_id() == source)
+    }
+
+    /// Seeds `scrape_sources` with the registered scrapers and `ICAL_FEEDS`,
+    /// then rebuilds the list of scrapers that run from its rows (see
+    /// "Sources" above).
+    ///
+    /// # Returns
+    /// The `source_id`s that now run.
+    pub async fn reload(&self, pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+        let registered: Vec<(&str, &str)> =
+            self.registered.iter().map(|scraper| (scraper.source_id(), scraper.name())).collect();
+        sources::seed(pool, &registered, &IcalFeed::from_env()).await?;
+        let rows = sources::list(pool).await?;
+
+        let mut scrapers: Vec<Arc<dyn EventScraper>> = self
+            .registered
+            .iter()
+            .filter(|scraper| {
+                rows.iter()
+                    .find(|row| row.id == scraper.source_id())
+                    .is_none_or(|row| row.kind == ScrapeSourceKind::Custom && row.enabled)
+            })
+            .cloned()
+            .collect();
+        for row in rows.iter().filter(|row| row.enabled) {
+            match row.build() {
+                Some(scraper) => scrapers.push(scraper),
+                None if row.kind == ScrapeSourceKind::Custom && !self.is_registered(&row.id) => {
+                    tracing::warn!(source = %row.id, "custom scrape source has no scraper in this build; skipping it")
+                }
+                None => {}
+            }
+        }
+
+        let ids: Vec<String> = scrapers.iter().map(|scraper| scraper.source_id().to_string()).collect();
+        *self.scrapers.write().expect("scraper list poisoned") = scrapers;
+        tracing::info!(sources = %ids.join(", "), "scrape sources loaded");
+        Ok(ids)
+    }
+
+    /// The scrapers that run, as of now.
+    fn current(&self) -> Vec<Arc<dyn EventScraper>> {
+        self.scrapers.read().expect("scraper list poisoned").clone()
+    }
+
+    /// Runs every scraper, up to the parallelism limit at once.
+    ///
+    /// # Returns
+    /// A summary per scraper, in run order.
+    pub async fn run_all(&self, pool: &PgPool) -> Vec<ScrapeSummary> {
+        self.run_many(pool, self.current(), RunOptions::default()).await
+    }
+
+    /// Runs the scraper with this `source_id`, or `None` if there isn't one.
+    pub async fn run_one(&self, pool: &PgPool, source: &str) -> Option<ScrapeSummary> {
+        self.run_with(pool, source, RunOptions::default()).await
+    }
+
+    /// `run_one` with `options`.
+    pub async fn run_with(&self, pool: &PgPool, source: &str, options: RunOptions) -> Option<ScrapeSummary> {
+        let scraper = self.current().into_iter().find(|scraper| scraper.source_id() == source)?;
+        self.run_many(pool, vec![scraper], options).await.pop()
+    }
+
+    /// Runs the scrapers with these `source_id`s, in run order, with
+    /// `options`; sources no scraper has are left out.
+    pub async fn run_sources(&self, pool: &PgPool, sources: &[String], options: RunOptions) -> Vec<ScrapeSummary> {
+        let scrapers = self
+            .current()
+            .into_iter()
+            .filter(|scraper| sources.iter().any(|source| source == scraper.source_id()))
+            .collect();
+        self.run_many(pool, scrapers, options).await
+    }
+
+    /// Runs `scrapers` concurrently, each in its own task once it gets a
+    /// permit (see "Parallelism" above), and gathers their summaries in
+    /// the order given.
+    async fn run_many(&self, pool: &PgPool, scrapers: Vec<Arc<dyn EventScraper>>, options: RunOptions) -> Vec<ScrapeSummary> {
+        let sources: Vec<String> = scrapers.iter().map(|scraper| scraper.source_id().to_string()).collect();
+        let mut tasks = JoinSet::new();
+        for (position, scraper) in scrapers.into_iter().enumerate() {
+            let permit = self.limit.clone().acquire_owned().await.expect("the scrape limit is never closed");
+            // Recorded here, in order, so a batch's runs are listed in run order
+            let run_id = runs::record_start(pool, options.batch, scraper.source_id(), options.dry_run)
+                .await
+                .map_err(|e| tracing::warn!(source = %scraper.source_id(), error = %e, "couldn't record a scrape run"))
+                .ok();
+
+            let (fetch, pool, geocoder, check_images) = (self.fetch.clone(), pool.clone(), self.geocoder.clone(), self.check_images);
+            tasks.spawn(async move {
+                let summary = run(&fetch, &pool, geocoder, check_images, scraper.as_ref(), options, run_id).await;
+                drop(permit);
+                (position, summary)
+            });
+        }
+
+        let mut summaries: Vec<Option<ScrapeSummary>> = vec![None; sources.len()];
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((position, summary)) => summaries[position] = Some(summary),
+                Err(e) => tracing::error!(error = %e, "scrape task failed"),
+            }
+        }
+        summaries
+            .into_iter()
+            .zip(sources)
+            .map(|(summary, source)| {
+                summary.unwrap_or_else(|| ScrapeSummary {
+                    source,
+                    error: Some("scrape task failed".to_string()),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Runs `scraper` and finishes its `scrape_runs` row (if it has one). A
+/// panicking scraper fails its run with the panic as the error; a database
+/// too broken to record the run in is logged, and the run goes ahead.
+async fn run(
+    fetch: &Arc<FetchPool>,
+    pool: &PgPool,
+    geocoder: Option<SharedGeocoder>,
+    check_images: bool,
+    scraper: &dyn EventScraper,
+    options: RunOptions,
+    run_id: Option<Uuid>,
+) -> ScrapeSummary {
+    let source = scraper.source_id();
+    let scraped = scrape_and_save(fetch, pool, geocoder, check_images, scraper, options.dry_run);
+    let summary = match AssertUnwindSafe(scraped).catch_unwind().await {
+        Ok(summary) => summary,
+        Err(panic) => {
+            let error = format!("scraper panicked: {}", runs::panic_message(panic));
+            tracing::error!(source = %source, error = %error, "scrape failed");
+            ScrapeSummary { source: source.to_string(), error: Some(error), ..Default::default() }
+        }
+    };
+
+    let Some(id) = run_id else {
+        return summary;
+    };
+    if let Err(e) = runs::record_finish(pool, id, &summary).await {
+        tracing::warn!(source = %source, run = %id, error = %e, "couldn't record a scrape run's outcome");
+        return summary;
+    }
+
+    // Shows the source stopped listing, now this run is on record
+    if !options.dry_run && summary.error.is_none() {
+        match stale::cancel_unlisted(pool, source, scraper.name(), stale::STALE_AFTER_RUNS).await {
+            Ok(cancelled) if !cancelled.is_empty() => {
+                tracing::info!(source = %source, cancelled = cancelled.len(), "cancelled events no longer listed")
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(source = %source, error = %e, "couldn't cancel unlisted events"),
+        }
+    }
+    summary
+}
+
+async fn scrape_and_save(
+    fetch: &Arc<FetchPool>,
+    pool: &PgPool,
+    geocoder: Option<SharedGeocoder>,
+    check_images: bool,
+    scraper: &dyn EventScraper,
+    dry_run: bool,
+) -> ScrapeSummary {
+    let mut summary = ScrapeSummary {
+        source: scraper.source_id().to_string(),
+        ..Default::default()
+    };
+
+    let fetcher = Fetcher::new(fetch.clone()).with_cache(pool.clone());
+    let scraped = scraper.scrape(&fetcher).await;
+    summary.disallowed = fetcher.disallowed();
+    summary.failed_urls = fetcher.failed_urls();
+    summary.pages_fetched = fetcher.pages_fetched();
+    summary.pages_unchanged = fetcher.pages_unchanged();
+
+    let mut events = match scraped {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::error!(source = %summary.source, error = %e, "scrape failed");
+            summary.error = Some(e.to_string());
+            return summary;
+        }
+    };
+    summary.found = events.len();
+
+    // On a fetcher of their own: a broken image isn't a page the run missed
+    if check_images {
+        images::check_images(&Fetcher::new(fetch.clone()), pool, &mut events, dry_run).await;
+    }
+
+    let stored_since = Utc::now();
+    let saved = persist::persist_scraped_events(pool, events, scraper.source_id(), scraper.name(), dry_run).await;
+    summary.created = saved.created;
+    summary.updated = saved.updated;
+    summary.skipped = saved.skipped;
+    if let Some(diff) = &saved.diff {
+        tracing::info!(source = %summary.source, "dry run would store:\n{}", diff);
+    }
+    summary.diff = saved.diff;
+
+    // Only now may the next run skip what this one read
+    if !dry_run {
+        if let Err(e) = fetcher.commit_cache().await {
+            tracing::warn!(source = %summary.source, error = %e, "couldn't update the fetch cache");
+        }
+    }
+
+    // Located in the background: a slow geocoder mustn't hold up the run
+    if let Some(geocoder) = geocoder.filter(|_| !saved.created_ids.is_empty()) {
+        let (pool, source, ids) = (pool.clone(), summary.source.clone(), saved.created_ids.clone());
+        tokio::spawn(async move {
+            match geocoding::locate_events(&pool, geocoder.as_ref(), &ids).await {
+                Ok(report) => tracing::info!(
+                    source = %source,
+                    located = report.located,
+                    not_found = report.not_found,
+                    failed = report.failed,
+                    "geocoded new events"
+                ),
+                Err(e) => tracing::warn!(source = %source, error = %e, "couldn't geocode new events"),
+            }
+        });
+    }
+
+    // Other sources may list the same shows under other titles
+    if !dry_run && saved.created + saved.updated > 0 {
+        match duplicates::detect_duplicates(pool, stored_since, DuplicateThresholds::from_env()).await {
+            Ok(report) if report.examined > 0 => tracing::info!(
+                source = %summary.source,
+                merged = report.merged,
+                flagged = report.flagged,
+                "duplicate pass finished"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(source = %summary.source, error = %e, "duplicate pass failed"),
+        }
+    }
+
+    tracing::info!(
+        source = %summary.source,
+        found = summary.found,
+        created = summary.created,
+        updated = summary.updated,
+        skipped = summary.skipped,
+        disallowed = summary.disallowed,
+        failed_urls = summary.failed_urls.len(),
+        pages_fetched = summary.pages_fetched,
+        pages_unchanged = summary.pages_unchanged,
+        dry_run,
+        "scrape finished"
+    );
+    summary
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::fixture::FixtureScraper;
+    use crate::scraper::traits::ScrapedEvent;
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn fixture_runs_create_then_update_then_leave_alone() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let start = Utc::now() + chrono::Duration::days(3);
+        let url = |n: u32| format!("https://fixture.example/{}/{}", run, n);
+        let events = vec![
+            ScrapedEvent::new("Open Mic", &url(1), start),
+            ScrapedEvent {
+                category: Some("Music".to_string()),
+                ..ScrapedEvent::new("Jazz Night", &url(2), start)
+            },
+            ScrapedEvent::new("   ", &url(3), start),
+        ];
+
+        let registry = ScraperRegistry::new(FetchPool::for_tests())
+            .register(FixtureScraper::new("fixture", events.clone()))
+            .register(FixtureScraper::failing("broken"));
+        assert_eq!(registry.sources(), ["fixture", "broken"]);
+
+        let summaries = registry.run_all(&pool).await;
+        assert_eq!(
+            summaries[0],
+            ScrapeSummary { source: "fixture".to_string(), found: 3, created: 2, skipped: 1, ..Default::default() }
+        );
+        assert_eq!(summaries[1].found, 0);
+        assert!(summaries[1].error.as_deref().unwrap().contains(".event-card"));
+
+        let (source_name, categories): (Option<String>, Option<Vec<String>>) =
+            sqlx::query_as("SELECT source_name, categories FROM events WHERE source_url = $1")
+                .bind(url(2))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(source_name.as_deref(), Some("Fixture fixture"));
+        assert_eq!(categories, Some(vec!["music".to_string()]));
+
+        // Same listings again: nothing to do
+        let again = registry.run_one(&pool, "fixture").await.unwrap();
+        assert_eq!((again.created, again.updated), (0, 0));
+
+        // A dry run reports a new listing without storing it
+        let mut added = events.clone();
+        added.push(ScrapedEvent::new("Late Show", &url(4), start));
+        let registry = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new("fixture", added));
+        let options = RunOptions { dry_run: true, batch: None };
+        let preview = registry.run_with(&pool, "fixture", options).await.unwrap();
+        assert_eq!((preview.found, preview.created, preview.updated), (4, 1, 0));
+
+        // A changed description is written in place
+        let mut changed = events;
+        changed[0].description = Some("Sign-up at 7".to_string());
+        let registry = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new("fixture", changed));
+        let updated = registry.run_one(&pool, "fixture").await.unwrap();
+        assert_eq!((updated.created, updated.updated), (0, 1));
+        assert!(registry.run_one(&pool, "nope").await.is_none());
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://fixture.example/{}/%", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    #[tokio::test]
+    async fn scrapers_run_side_by_side_and_a_panic_stays_in_its_run() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4().simple().to_string();
+        let id = |name: &str| format!("{}_{}", name, run);
+        let delay = std::time::Duration::from_millis(300);
+        let event = ScrapedEvent::new(
+            "Side by Side",
+            &format!("https://fixture.example/{}/1", run),
+            Utc::now() + chrono::Duration::days(2),
+        );
+        let registry = ScraperRegistry::new(FetchPool::for_tests())
+            .with_parallelism(3)
+            .register(FixtureScraper::new(&id("first"), vec![event]).with_delay(delay))
+            .register(FixtureScraper::panicking(&id("buggy")))
+            .register(FixtureScraper::new(&id("second"), Vec::new()).with_delay(delay))
+            .register(FixtureScraper::failing(&id("broken")))
+            .register(FixtureScraper::new(&id("third"), Vec::new()).with_delay(delay));
+
+        // Three slow scrapers at once: about one delay, not three
+        let started = std::time::Instant::now();
+        let summaries = registry.run_all(&pool).await;
+        assert!(started.elapsed() < delay * 2, "took {:?}", started.elapsed());
+
+        let sources: Vec<&str> = summaries.iter().map(|summary| summary.source.as_str()).collect();
+        assert_eq!(sources, [id("first"), id("buggy"), id("second"), id("broken"), id("third")]);
+        assert_eq!((summaries[0].created, summaries[0].error.as_deref()), (1, None));
+        let panicked = format!("scraper panicked: fixture scraper {} panicked", id("buggy"));
+        assert_eq!(summaries[1].error.as_deref(), Some(panicked.as_str()));
+        assert!(summaries[3].error.is_some());
+        assert!(summaries[2].error.is_none() && summaries[4].error.is_none());
+
+        let recorded = runs::list_runs(&pool, Some(&id("buggy")), 1).await.unwrap();
+        assert_eq!(recorded[0].status, runs::ScrapeRunStatus::Failed);
+        assert_eq!(recorded[0].error_message.as_deref(), Some(panicked.as_str()));
+        assert!(recorded[0].finished_at.is_some());
+
+        // One at a time, they queue
+        let serial = ScraperRegistry::new(FetchPool::for_tests())
+            .with_parallelism(1)
+            .register(FixtureScraper::new(&id("fourth"), Vec::new()).with_delay(delay))
+            .register(FixtureScraper::new(&id("fifth"), Vec::new()).with_delay(delay));
+        let started = std::time::Instant::now();
+        serial.run_all(&pool).await;
+        assert!(started.elapsed() >= delay * 2);
+
+        sqlx::query("DELETE FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://fixture.example/{}/%", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn created_events_are_geocoded_after_the_run() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let address = format!("{} Brady St, Tulsa", run);
+        let event = ScrapedEvent {
+            venue: Some(format!("Geocoded Hall {}", run)),
+            venue_address: Some(address.clone()),
+            ..ScrapedEvent::new("Located", &format!("https://fixture.example/{}/1", run), Utc::now() + chrono::Duration::days(3))
+        };
+        let geocoder = Arc::new(geocoding::MockGeocoder::new(&[(&address, 36.159, -95.991)]));
+        let registry = ScraperRegistry::new(FetchPool::for_tests())
+            .with_geocoder(Some(geocoder.clone()))
+            .register(FixtureScraper::new("geocoded", vec![event]));
+
+        let summary = registry.run_one(&pool, "geocoded").await.unwrap();
+        assert_eq!(summary.created, 1);
+
+        let mut located = (None, None);
+        for _ in 0..50 {
+            located = sqlx::query_as::<_, (Option<f64>, Option<f64>)>(
+                "SELECT latitude, longitude FROM events WHERE source_url = $1",
+            )
+                .bind(format!("https://fixture.example/{}/1", run))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            if located.0.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(located, (Some(36.159), Some(-95.991)));
+        assert_eq!(geocoder.asked(), vec![address]);
+
+        sqlx::query("DELETE FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://fixture.example/{}/%", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM venues WHERE name = $1")
+            .bind(format!("Geocoded Hall {}", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+//! # robots.txt
+//!
+//! Reads a site's robots.txt and answers "may we fetch this URL?" for
+//! our user agent. Every scraper request asks first (see `fetch.rs`).
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Rules (RFC 9309)
+//! - We follow the groups naming our product token (`ROBOTS_AGENT`,
+//!   case-insensitive); if none do, the `*` groups. Several groups for
+//!   the same agent count as one.
+//! - The longest matching `Allow`/`Disallow` path wins, `Allow` on a tie.
+//!   Paths may use `*` (any run of characters) and a trailing `$` (end of
+//!   URL). An empty `Disallow` allows everything.
+//! - `Crawl-delay` (seconds) is kept, capped at `MAX_CRAWL_DELAY`.
+//!
+//! ## Fetching
+//! - A robots.txt is cached per origin for `ROBOTS_TTL`
+//! - 4xx (no robots.txt): everything is allowed
+//! - 5xx or no answer: nothing is allowed. This isn't cached; the fetcher
+//!   closes the host for the rest of its run and the next run asks again.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, Url};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// The product token robots.txt groups are matched against (the start
+/// of `fetch::USER_AGENT`).
+pub const ROBOTS_AGENT: &str = "Locate918";
+
+/// How long a fetched robots.txt is trusted.
+pub const ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The longest `Crawl-delay` we honor; a site asking for more gets this.
+pub const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+
+// =============================================================================
+// RULES
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// The part of one robots.txt that applies to us.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+    /// Set for a host we couldn't read robots.txt from
+    deny_all: bool,
+    pub crawl_delay: Option<Duration>,
+}
+
+/// One `User-agent` group while parsing.
+#[derive(Debug, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// No robots.txt: everything is allowed.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// An unreadable robots.txt: nothing is allowed.
+    pub fn deny_all() -> Self {
+        Self { deny_all: true, ..Self::default() }
+    }
+
+    /// The rules in `text` for `agent` (a product token like `ROBOTS_AGENT`).
+    pub fn parse(text: &str, agent: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        // A User-agent line after a rule starts a new group; consecutive
+        // User-agent lines share one
+        let mut in_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let (key, value) = (key.trim().to_lowercase(), value.trim());
+
+            if key == "user-agent" {
+                if !in_agents {
+                    groups.push(Group::default());
+                    in_agents = true;
+                }
+                let token = value.split('/').next().unwrap_or_default().trim().to_lowercase();
+                groups.last_mut().expect("group just pushed").agents.push(token);
+                continue;
+            }
+
+            // Rules before any User-agent line belong to no one
+            let Some(group) = groups.last_mut() else { continue };
+            in_agents = false;
+            match key.as_str() {
+                "allow" | "disallow" if !value.is_empty() => group.rules.push(Rule {
+                    allow: key == "allow",
+                    pattern: value.to_string(),
+                }),
+                "crawl-delay" => {
+                    group.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                        .map(|seconds| Duration::from_secs_f64(seconds.min(MAX_CRAWL_DELAY.as_secs_f64())));
+                }
+                _ => {}
+            }
+        }
+
+        let agent = agent.to_lowercase();
+        let ours: Vec<&Group> = groups.iter().filter(|g| g.agents.contains(&agent)).collect();
+        let chosen = match ours.is_empty() {
+            false => ours,
+            true => groups.iter().filter(|g| g.agents.iter().any(|a| a == "*")).collect(),
+        };
+
+        Self {
+            rules: chosen.iter().flat_map(|group| group.rules.iter().cloned()).collect(),
+            deny_all: false,
+            crawl_delay: chosen.iter().filter_map(|group| group.crawl_delay).max(),
+        }
+    }
+
+    /// Whether `url`'s path (and query) may be fetched.
+    pub fn allows(&self, url: &Url) -> bool {
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path = format!("{}?{}", path, query);
+        }
+        self.allows_path(&path)
+    }
+
+    fn allows_path(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        if self.deny_all {
+            return false;
+        }
+
+        // Longest pattern wins; on a tie, Allow
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Whether a robots.txt path pattern matches the start of `path`.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let Some(rest) = path.strip_prefix(parts[0]) else { return false };
+    if parts.len() == 1 {
+        return !anchored || rest.is_empty();
+    }
+
+    // Each `*` takes the shortest run that lets the next literal match;
+    // an anchored last literal must end the path
+    let mut rest = rest;
+    let last = parts.len() - 1;
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        if i == last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+// =============================================================================
+// CHECKER
+// =============================================================================
+
+/// Fetches robots.txt per origin and keeps it for `ROBOTS_TTL`. Shared by
+/// every run of a registry.
+pub struct RobotsChecker {
+    client: Client,
+    cache: Mutex<HashMap<String, (Instant, Arc<RobotsRules>)>>,
+}
+
+impl RobotsChecker {
+    pub fn new(client: Client) -> Self {
+        Self { client, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// The rules for `url`'s site, or `None` if its robots.txt couldn't be
+    /// read (a 5xx, or no answer).
+    pub async fn rules_for(&self, url: &Url) -> Option<Arc<RobotsRules>> {
+        let origin = url.origin().ascii_serialization();
+        if let Some((fetched, rules)) = self.cache.lock().expect("robots cache lock").get(&origin) {
+            if fetched.elapsed() < ROBOTS_TTL {
+                return Some(rules.clone());
+            }
+        }
+
+        let rules = Arc::new(self.fetch(&origin).await?);
+        self.cache
+            .lock()
+            .expect("robots cache lock")
+            .insert(origin, (Instant::now(), rules.clone()));
+        Some(rules)
+    }
+
+    async fn fetch(&self, origin: &str) -> Option<RobotsRules> {
+        let url = format!("{}/robots.txt", origin);
+        let response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!(url = %url, error = %e, "couldn't fetch robots.txt; skipping the site this run");
+                return None;
+            }
+        };
+
+        let status = response.status();
+        if status.is_server_error() {
+            tracing::warn!(url = %url, status = %status, "robots.txt unavailable; skipping the site this run");
+            return None;
+        }
+        if !status.is_success() {
+            return Some(RobotsRules::allow_all());
+        }
+
+        match response.text().await {
+            Ok(text) => Some(RobotsRules::parse(&text, ROBOTS_AGENT)),
+            Err(e) => {
+                tracing::warn!(url = %url, error = %e, "couldn't read robots.txt; skipping the site this run");
+                None
+            }
+        }
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const ROBOTS: &str = "\
+# Everyone else
+User-agent: *
+Disallow: /private/
+Disallow: /*.pdf$
+Crawl-delay: 2
+
+User-agent: BadBot
+Disallow: /
+
+User-agent: locate918/2.0
+User-agent: OtherBot
+Disallow: /events/drafts
+Allow: /events/drafts/public
+Crawl-delay: 5
+
+Sitemap: https://venue.example/sitemap.xml
+
+User-agent: Locate918
+Disallow: /search?*sort=
+";
+
+    #[test]
+    fn our_groups_win_over_the_wildcard() {
+        let ours = RobotsRules::parse(ROBOTS, ROBOTS_AGENT);
+        assert!(ours.allows_path("/private/tickets"));
+        assert!(!ours.allows_path("/events/drafts/123"));
+        assert!(ours.allows_path("/events/drafts/public/1"));
+        // The second group for us adds to the first
+        assert!(!ours.allows_path("/search?q=jazz&sort=date"));
+        assert!(ours.allows_path("/search?q=jazz"));
+        assert_eq!(ours.crawl_delay, Some(Duration::from_secs(5)));
+
+        let anyone = RobotsRules::parse(ROBOTS, "SomeCrawler");
+        assert!(!anyone.allows_path("/private/tickets"));
+        assert!(!anyone.allows_path("/flyers/show.pdf"));
+        assert!(anyone.allows_path("/flyers/show.pdf?download=1"));
+        assert!(anyone.allows_path("/events/drafts/123"));
+        assert_eq!(anyone.crawl_delay, Some(Duration::from_secs(2)));
+
+        let bad = RobotsRules::parse(ROBOTS, "badbot");
+        assert!(!bad.allows_path("/"));
+        assert!(bad.allows_path("/robots.txt"));
+    }
+
+    #[test]
+    fn odd_files_still_parse() {
+        // No groups at all, rules outside a group, blank Disallow
+        assert!(RobotsRules::parse("", ROBOTS_AGENT).allows_path("/anything"));
+        assert!(RobotsRules::parse("Disallow: /", ROBOTS_AGENT).allows_path("/anything"));
+        let open = RobotsRules::parse("User-agent: *\nDisallow:\n", ROBOTS_AGENT);
+        assert!(open.allows_path("/anything"));
+
+        // Case, spacing and comments; an absurd delay is capped
+        let messy = RobotsRules::parse("USER-AGENT : *  # all\n  disallow :/tmp  \ncrawl-delay: 900\n", ROBOTS_AGENT);
+        assert!(!messy.allows_path("/tmp/x"));
+        assert_eq!(messy.crawl_delay, Some(MAX_CRAWL_DELAY));
+
+        // Longest match wins; Allow breaks a tie
+        let tie = RobotsRules::parse("User-agent: *\nDisallow: /a\nAllow: /a\nDisallow: /b/*/c\n", ROBOTS_AGENT);
+        assert!(tie.allows_path("/a/x"));
+        assert!(!tie.allows_path("/b/one/two/c/d"));
+        assert!(tie.allows_path("/b/one"));
+    }
+
+    #[test]
+    fn patterns_support_wildcards_and_anchors() {
+        assert!(pattern_matches("/events", "/events/1"));
+        assert!(pattern_matches("/*/tickets", "/shows/tickets"));
+        assert!(pattern_matches("/*.ics$", "/cal/feed.ics"));
+        assert!(!pattern_matches("/*.ics$", "/cal/feed.ics?v=2"));
+        assert!(pattern_matches("/exact$", "/exact"));
+        assert!(!pattern_matches("/exact$", "/exactly"));
+        assert!(!pattern_matches("/events", "/news/events"));
+    }
+
+    #[tokio::test]
+    async fn missing_robots_allows_and_a_server_error_blocks() {
+        let missing = MockServer::start().await;
+        let broken = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&broken)
+            .await;
+
+        let checker = RobotsChecker::new(Client::new());
+        let url = |server: &MockServer| Url::parse(&format!("{}/events/", server.uri())).unwrap();
+
+        let rules = checker.rules_for(&url(&missing)).await.unwrap();
+        assert!(rules.allows(&url(&missing)));
+
+        // Not cached: the next run asks again
+        assert!(checker.rules_for(&url(&broken)).await.is_none());
+        assert!(checker.rules_for(&url(&broken)).await.is_none());
+    }
+}
@@ -0,0 +1,646 @@
+//! # Scrape Runs
+//!
+//! What the scrapers did, and background scrapes started from the admin
+//! API.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Runs and Batches
+//! - A **run** (`scrape_runs`) is one scraper scraping once. The registry
+//!   writes its row at the start and fills in the counts, or the error,
+//!   at the end.
+//! - A **batch** (`scrape_batches`) is one trigger: an admin request or a
+//!   scheduled tick, for one source or all of them. `ScrapeRunner` starts
+//!   it in a spawned task, so the caller gets its id at once and polls.
+//!
+//! ## One Run per Source
+//! A source being scraped can't be started again until its batch finishes
+//! (`409 Conflict`): two runs would race to create the same events. The
+//! lock is in-process, so it holds for one server.
+//!
+//! ## Status
+//! - `running` until the scraper (or every scraper in the batch) is done
+//! - `completed` if the scrape succeeded (individual events may still
+//!   have been skipped; see `events_skipped`)
+//! - `failed` if the scrape failed, with `error_message`; a batch fails
+//!   if any of its runs did
+//!
+//! A scraper that panics fails its run (the panic is the error), and so
+//! its batch; the batch's other scrapers, the server and other batches
+//! carry on. Work cut short by a restart is marked `failed` at the next
+//! startup (`mark_interrupted`).
+//!
+//! ## Source Health
+//! `source_health` reports, per source, its last run and last success
+//! and how many runs in a row have failed. A source failing
+//! `SCRAPE_FAILING_AFTER` runs in a row (default 3) is flagged `failing`:
+//! usually its markup changed and its selectors need fixing. Dry runs
+//! don't count.
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::scraper::fetch::{FailedUrl, FetchConfig, FetchPool, Fetcher};
+use crate::scraper::persist::ScrapeDiff;
+use crate::scraper::registry::{RunOptions, ScrapeSummary, ScraperRegistry};
+use crate::services::geocoding::SharedGeocoder;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Failed runs in a row before a source is flagged, unless configured.
+pub const DEFAULT_FAILING_AFTER: u64 = 3;
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// Where a run or batch is.
+///
+/// Stored as the Postgres enum `scrape_run_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "scrape_run_status", rename_all = "snake_case")]
+pub enum ScrapeRunStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// One scraper's run, as stored in `scrape_runs`.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "id": "0b8e1f52-9c3d-4a7e-8f10-6d2c4b9a7e35",
+///   "batch_id": "7d0c9a7e-4b1f-4d6e-9f2a-1c3b5d7e9f01",
+///   "source": "cains_ballroom",
+///   "dry_run": false,
+///   "status": "completed",
+///   "events_found": 24,
+///   "events_created": 3,
+///   "events_updated": 1,
+///   "events_skipped": 0,
+///   "urls_disallowed": 0,
+///   "failed_urls": [],
+///   "diff": null,
+///   "error_message": null,
+///   "started_at": "2026-03-01T15:00:00Z",
+///   "finished_at": "2026-03-01T15:00:04Z"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ScrapeRun {
+    pub id: Uuid,
+    /// `None` for runs outside the admin API
+    pub batch_id: Option<Uuid>,
+    pub source: String,
+    pub dry_run: bool,
+    pub status: ScrapeRunStatus,
+    pub events_found: i32,
+    pub events_created: i32,
+    pub events_updated: i32,
+    /// Invalid, or failed to save
+    pub events_skipped: i32,
+    /// URLs robots.txt kept the scraper from fetching
+    pub urls_disallowed: i32,
+    /// Pages that couldn't be fetched (the run went on without them)
+    pub failed_urls: Json<Vec<FailedUrl>>,
+    /// Pages downloaded and read
+    pub pages_fetched: i32,
+    /// Pages skipped as unchanged since the last run
+    pub pages_unchanged: i32,
+    /// For a dry run, what it would have stored (see `persist::ScrapeDiff`)
+    pub diff: Option<Json<ScrapeDiff>>,
+    /// Why the scrape failed, if it did
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// One trigger's worth of runs, as stored in `scrape_batches`.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "id": "7d0c9a7e-4b1f-4d6e-9f2a-1c3b5d7e9f01",
+///   "source": null,
+///   "dry_run": false,
+///   "status": "running",
+///   "sources_total": 3,
+///   "sources_done": 1,
+///   "error": null,
+///   "started_at": "2026-03-01T15:00:00Z",
+///   "finished_at": null,
+///   "runs": [{ "source": "cains_ballroom", "status": "completed", "events_found": 24, ... },
+///            { "source": "tulsa_city_calendar", "status": "running", ... }]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ScrapeBatch {
+    pub id: Uuid,
+    /// The requested `source_id`; `None` for all scrapers
+    pub source: Option<String>,
+    pub dry_run: bool,
+    pub status: ScrapeRunStatus,
+    pub sources_total: i32,
+    pub sources_done: i32,
+    /// Why the batch stopped early, if it did
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Its runs so far, in run order
+    #[sqlx(skip)]
+    pub runs: Vec<ScrapeRun>,
+}
+
+/// How one source has been doing.
+///
+/// # Example JSON
+/// ```json
+/// {
+///   "source": "cains_ballroom",
+///   "last_run_at": "2026-03-01T15:00:00Z",
+///   "last_status": "failed",
+///   "last_success_at": "2026-02-28T21:00:04Z",
+///   "consecutive_failures": 3,
+///   "failing": true
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SourceHealth {
+    pub source: String,
+    /// `None` if it has never run
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<ScrapeRunStatus>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// Failed runs since the last success
+    pub consecutive_failures: i64,
+    /// At least `SCRAPE_FAILING_AFTER` failures in a row
+    #[sqlx(skip)]
+    pub failing: bool,
+}
+
+// =============================================================================
+// RUNNER
+// =============================================================================
+
+/// The registry plus the sources being scraped right now.
+pub struct ScrapeRunner {
+    registry: ScraperRegistry,
+    running: Mutex<HashSet<String>>,
+    /// Bumped after every `reload`, for the scheduler
+    reloaded: watch::Sender<u64>,
+}
+
+impl ScrapeRunner {
+    pub fn new(registry: ScraperRegistry) -> Self {
+        Self { registry, running: Mutex::new(HashSet::new()), reloaded: watch::channel(0).0 }
+    }
+
+    /// Every `source_id` that runs.
+    pub fn sources(&self) -> Vec<String> {
+        self.registry.sources()
+    }
+
+    /// Whether `source` is a scraper written in code (see
+    /// `ScraperRegistry::is_registered`).
+    pub fn is_registered(&self, source: &str) -> bool {
+        self.registry.is_registered(source)
+    }
+
+    /// The geocoder runs locate new events with (`ScraperRegistry::with_geocoder`).
+    pub fn geocoder(&self) -> Option<SharedGeocoder> {
+        self.registry.geocoder()
+    }
+
+    /// A fetcher on the scrapers' pool (`ScraperRegistry::fetcher`).
+    pub fn fetcher(&self) -> Fetcher {
+        self.registry.fetcher()
+    }
+
+    /// Reloads the sources from `scrape_sources` (`ScraperRegistry::reload`)
+    /// and tells the scheduler. Batches already running keep their scrapers.
+    pub async fn reload(&self, pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+        let sources = self.registry.reload(pool).await?;
+        self.reloaded.send_modify(|generation| *generation += 1);
+        Ok(sources)
+    }
+
+    /// Changes each time the sources are reloaded.
+    pub fn reloads(&self) -> watch::Receiver<u64> {
+        self.reloaded.subscribe()
+    }
+
+    /// Starts scraping `source` (or every source) in the background.
+    ///
+    /// # Returns
+    /// The new batch, still `running`, and the task running it (awaiting
+    /// it waits for the batch to finish; dropping it doesn't stop it).
+    ///
+    /// # Errors
+    /// - `AppError::NotFound` for a source no scraper has
+    /// - `AppError::Conflict` if a requested source is already being scraped
+    pub async fn start(
+        self: &Arc<Self>,
+        pool: &PgPool,
+        source: Option<&str>,
+        dry_run: bool,
+    ) -> Result<(ScrapeBatch, JoinHandle<()>), AppError> {
+        let sources: Vec<String> = match source {
+            Some(source) if !self.registry.sources().iter().any(|known| known == source) => {
+                return Err(AppError::NotFound(format!("no scraper for source {}", source)));
+            }
+            Some(source) => vec![source.to_string()],
+            None => self.registry.sources(),
+        };
+
+        let lock = self.lock(&sources)?;
+        let batch = sqlx::query_as::<_, ScrapeBatch>(
+            "INSERT INTO scrape_batches (source, dry_run, sources_total) VALUES ($1, $2, $3) RETURNING *",
+        )
+            .bind(source)
+            .bind(dry_run)
+            .bind(sources.len() as i32)
+            .fetch_one(pool)
+            .await?;
+
+        let runner = Arc::clone(self);
+        let pool = pool.clone();
+        let id = batch.id;
+        let task = tokio::spawn(async move {
+            // Released when the batch ends, however it ends
+            let _lock = lock;
+
+            // Its own task, so a bug outside the scrapers (which catch
+            // their own panics) fails the batch instead of taking anything
+            // else down
+            let execution = tokio::spawn({
+                let pool = pool.clone();
+                async move { runner.execute(&pool, id, &sources, dry_run).await }
+            });
+            let error = match execution.await {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => e.to_string(),
+                Err(e) if e.is_panic() => format!("scrape batch panicked: {}", panic_message(e.into_panic())),
+                Err(e) => e.to_string(),
+            };
+
+            tracing::error!(batch = %id, error = %error, "scrape batch failed");
+            if let Err(e) = fail_batch(&pool, id, &error).await {
+                tracing::error!(batch = %id, error = %e, "couldn't record a failed scrape batch");
+            }
+        });
+
+        Ok((batch, task))
+    }
+
+    /// Runs the sources (concurrently, see `ScraperRegistry`); each is
+    /// counted off as its run is recorded (`record_finish`).
+    async fn execute(&self, pool: &PgPool, id: Uuid, sources: &[String], dry_run: bool) -> Result<(), sqlx::Error> {
+        let options = RunOptions { dry_run, batch: Some(id) };
+        let summaries = self.registry.run_sources(pool, sources, options).await;
+        let any_failed = summaries.iter().any(|summary| summary.error.is_some());
+
+        let status = if any_failed { ScrapeRunStatus::Failed } else { ScrapeRunStatus::Completed };
+        sqlx::query("UPDATE scrape_batches SET status = $2, finished_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Claims `sources`, or none of them if any is taken.
+    fn lock(self: &Arc<Self>, sources: &[String]) -> Result<RunLock, AppError> {
+        let mut running = self.running.lock().expect("scrape lock poisoned");
+        let busy: Vec<&str> = sources.iter().filter(|s| running.contains(*s)).map(String::as_str).collect();
+        if !busy.is_empty() {
+            return Err(AppError::Conflict(format!("already scraping {}", busy.join(", "))));
+        }
+
+        running.extend(sources.iter().cloned());
+        Ok(RunLock { runner: Arc::clone(self), sources: sources.to_vec() })
+    }
+}
+
+impl Default for ScrapeRunner {
+    /// No scrapers (tests that don't scrape).
+    fn default() -> Self {
+        Self::new(ScraperRegistry::new(FetchPool::new(FetchConfig::default()).expect("the HTTP client builds")))
+    }
+}
+
+/// What a panic said, if it said it with a string.
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("(no message)", |message| message).to_string(),
+    }
+}
+
+/// Sources claimed by a batch, released on drop.
+struct RunLock {
+    runner: Arc<ScrapeRunner>,
+    sources: Vec<String>,
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        // A poisoned lock still has the right contents
+        let mut running = self.runner.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for source in &self.sources {
+            running.remove(source);
+        }
+    }
+}
+
+// =============================================================================
+// RECORDING
+// =============================================================================
+
+/// Writes a run's row as it starts; returns its id.
+pub async fn record_start(pool: &PgPool, batch: Option<Uuid>, source: &str, dry_run: bool) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar("INSERT INTO scrape_runs (batch_id, source, dry_run) VALUES ($1, $2, $3) RETURNING id")
+        .bind(batch)
+        .bind(source)
+        .bind(dry_run)
+        .fetch_one(pool)
+        .await
+}
+
+/// Fills in a finished run's counts and outcome, and counts it off in its
+/// batch.
+pub async fn record_finish(pool: &PgPool, id: Uuid, summary: &ScrapeSummary) -> Result<(), sqlx::Error> {
+    let status = match summary.error {
+        Some(_) => ScrapeRunStatus::Failed,
+        None => ScrapeRunStatus::Completed,
+    };
+    sqlx::query(
+        r#"
+        WITH finished AS (
+            UPDATE scrape_runs
+            SET status = $2, events_found = $3, events_created = $4, events_updated = $5,
+                events_skipped = $6, urls_disallowed = $7, failed_urls = $8, diff = $9,
+                error_message = $10, pages_fetched = $11, pages_unchanged = $12, finished_at = NOW()
+            WHERE id = $1
+            RETURNING batch_id
+        )
+        UPDATE scrape_batches SET sources_done = sources_done + 1
+        WHERE id = (SELECT batch_id FROM finished)
+        "#,
+    )
+        .bind(id)
+        .bind(status)
+        .bind(summary.found as i32)
+        .bind(summary.created as i32)
+        .bind(summary.updated as i32)
+        .bind(summary.skipped as i32)
+        .bind(summary.disallowed as i32)
+        .bind(Json(&summary.failed_urls))
+        .bind(summary.diff.as_ref().map(Json))
+        .bind(&summary.error)
+        .bind(summary.pages_fetched as i32)
+        .bind(summary.pages_unchanged as i32)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Fails a batch that stopped early, and whichever of its runs it stopped
+/// in the middle of.
+async fn fail_batch(pool: &PgPool, id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE scrape_batches SET status = 'failed', error = $2, finished_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        UPDATE scrape_runs SET status = 'failed', error_message = $2, finished_at = NOW()
+        WHERE batch_id = $1 AND status = 'running'
+        "#,
+    )
+        .bind(id)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// Marks batches and runs left `running` by a previous process as
+/// failed. Call before starting any.
+pub async fn mark_interrupted(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let error = "interrupted by a server restart";
+    let batches = sqlx::query(
+        "UPDATE scrape_batches SET status = 'failed', error = $1, finished_at = NOW() WHERE status = 'running'",
+    )
+        .bind(error)
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        "UPDATE scrape_runs SET status = 'failed', error_message = $1, finished_at = NOW() WHERE status = 'running'",
+    )
+        .bind(error)
+        .execute(pool)
+        .await?;
+    Ok(batches.rows_affected())
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// A batch by id, with its runs.
+pub async fn find_batch(pool: &PgPool, id: Uuid) -> Result<Option<ScrapeBatch>, sqlx::Error> {
+    let Some(mut batch) = sqlx::query_as::<_, ScrapeBatch>("SELECT * FROM scrape_batches WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    batch.runs = sqlx::query_as::<_, ScrapeRun>("SELECT * FROM scrape_runs WHERE batch_id = $1 ORDER BY started_at, id")
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+    Ok(Some(batch))
+}
+
+/// A run by id.
+pub async fn find_run(pool: &PgPool, id: Uuid) -> Result<Option<ScrapeRun>, sqlx::Error> {
+    sqlx::query_as::<_, ScrapeRun>("SELECT * FROM scrape_runs WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The latest `limit` runs, of one source or all, newest first.
+pub async fn list_runs(pool: &PgPool, source: Option<&str>, limit: i64) -> Result<Vec<ScrapeRun>, sqlx::Error> {
+    sqlx::query_as::<_, ScrapeRun>(
+        r#"
+        SELECT * FROM scrape_runs
+        WHERE $1::text IS NULL OR source = $1
+        ORDER BY started_at DESC, id
+        LIMIT $2
+        "#,
+    )
+        .bind(source)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+/// Health of each of `sources` (in that order), from their real runs.
+pub async fn source_health(pool: &PgPool, sources: &[&str], failing_after: u64) -> Result<Vec<SourceHealth>, sqlx::Error> {
+    let mut health = sqlx::query_as::<_, SourceHealth>(
+        r#"
+        SELECT s.source, last.started_at AS last_run_at, last.status AS last_status,
+               success.finished_at AS last_success_at, failures.consecutive_failures
+        FROM UNNEST($1::text[]) WITH ORDINALITY AS s(source, position)
+        LEFT JOIN LATERAL (
+            SELECT started_at, status FROM scrape_runs
+            WHERE source = s.source AND NOT dry_run
+            ORDER BY started_at DESC LIMIT 1
+        ) last ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT started_at, finished_at FROM scrape_runs
+            WHERE source = s.source AND NOT dry_run AND status = 'completed'
+            ORDER BY started_at DESC LIMIT 1
+        ) success ON TRUE
+        CROSS JOIN LATERAL (
+            SELECT COUNT(*) AS consecutive_failures FROM scrape_runs
+            WHERE source = s.source AND NOT dry_run AND status = 'failed'
+              AND started_at > COALESCE(success.started_at, '-infinity')
+        ) failures
+        ORDER BY s.position
+        "#,
+    )
+        .bind(sources)
+        .fetch_all(pool)
+        .await?;
+
+    for source in &mut health {
+        source.failing = source.consecutive_failures >= failing_after as i64;
+    }
+    Ok(health)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::fixture::FixtureScraper;
+    use crate::scraper::traits::ScrapedEvent;
+
+    #[tokio::test]
+    async fn a_panicking_scraper_fails_only_its_run() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let buggy = format!("buggy-{}", Uuid::new_v4());
+        let registry = ScraperRegistry::new(FetchPool::for_tests())
+            .register(FixtureScraper::panicking(&buggy))
+            .register(FixtureScraper::new("quiet", Vec::new()));
+        let runner = Arc::new(ScrapeRunner::new(registry));
+
+        let (_, task) = runner.start(&pool, Some(&buggy), false).await.unwrap();
+        task.await.unwrap();
+        let (batch, task) = runner.start(&pool, Some(&buggy), false).await.unwrap();
+        task.await.unwrap();
+
+        let batch = find_batch(&pool, batch.id).await.unwrap().unwrap();
+        let error = format!("scraper panicked: fixture scraper {} panicked", buggy);
+        assert_eq!((batch.status, batch.error, batch.sources_done), (ScrapeRunStatus::Failed, None, 1));
+        assert_eq!(batch.runs[0].status, ScrapeRunStatus::Failed);
+        assert_eq!(batch.runs[0].error_message.as_deref(), Some(error.as_str()));
+        assert!(batch.finished_at.is_some() && batch.runs[0].finished_at.is_some());
+
+        // Alongside it, the other scraper runs to the end
+        let (batch, task) = runner.start(&pool, None, false).await.unwrap();
+        task.await.unwrap();
+        let batch = find_batch(&pool, batch.id).await.unwrap().unwrap();
+        assert_eq!((batch.status, batch.sources_done), (ScrapeRunStatus::Failed, 2));
+        let statuses: Vec<(&str, ScrapeRunStatus)> = batch.runs.iter().map(|run| (run.source.as_str(), run.status)).collect();
+        assert_eq!(statuses, [(buggy.as_str(), ScrapeRunStatus::Failed), ("quiet", ScrapeRunStatus::Completed)]);
+
+        let (batch, task) = runner.start(&pool, Some("quiet"), false).await.unwrap();
+        task.await.unwrap();
+        let batch = find_batch(&pool, batch.id).await.unwrap().unwrap();
+        assert_eq!((batch.status, batch.sources_done), (ScrapeRunStatus::Completed, 1));
+    }
+
+    #[tokio::test]
+    async fn sources_failing_in_a_row_are_flagged() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let (flaky, broken, idle) = (format!("flaky-{}", run), format!("broken-{}", run), format!("idle-{}", run));
+        let event = ScrapedEvent::new(
+            "Open Mic",
+            &format!("https://fixture.example/{}/1", run),
+            Utc::now() + chrono::Duration::days(1),
+        );
+        let working = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new(&flaky, vec![event]));
+        let failing = ScraperRegistry::new(FetchPool::for_tests())
+            .register(FixtureScraper::failing(&flaky))
+            .register(FixtureScraper::failing(&broken));
+
+        // flaky: fails, works, fails twice; broken: fails three times
+        failing.run_all(&pool).await;
+        working.run_one(&pool, &flaky).await.unwrap();
+        failing.run_all(&pool).await;
+        failing.run_all(&pool).await;
+        // Dry runs don't count
+        failing.run_with(&pool, &broken, RunOptions { dry_run: true, batch: None }).await.unwrap();
+
+        // The failure path records why
+        let runs = list_runs(&pool, Some(&broken), 10).await.unwrap();
+        assert_eq!(runs.len(), 4);
+        assert!(runs[0].dry_run);
+        assert_eq!(runs[1].status, ScrapeRunStatus::Failed);
+        assert!(runs[1].error_message.as_deref().unwrap().contains(".event-card"));
+        assert!(runs.iter().all(|run| run.finished_at.is_some() && run.events_found == 0));
+
+        let health = source_health(&pool, &[&flaky, &broken, &idle], 3).await.unwrap();
+        let sources: Vec<&str> = health.iter().map(|h| h.source.as_str()).collect();
+        assert_eq!(sources, [&flaky, &broken, &idle]);
+        assert_eq!((health[0].consecutive_failures, health[0].failing), (2, false));
+        assert!(health[0].last_success_at.is_some());
+        assert_eq!((health[1].consecutive_failures, health[1].failing), (3, true));
+        assert_eq!((health[1].last_status, health[1].last_success_at), (Some(ScrapeRunStatus::Failed), None));
+        assert_eq!((health[2].last_run_at, health[2].consecutive_failures), (None, 0));
+
+        sqlx::query("DELETE FROM events WHERE source_url LIKE $1")
+            .bind(format!("https://fixture.example/{}/%", run))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,470 @@
+//! # Scrape Scheduling
+//!
+//! Runs every registered scraper on its own timer, so listings stay fresh
+//! without anyone calling `POST /api/admin/scrape`. Each source gets a
+//! tokio task; each tick starts a run through `ScrapeRunner`, the same
+//! path (and `scrape_runs` history) as a manual trigger.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Configuration
+//! ```text
+//! SCRAPER_ENABLED=false                       # kill switch: no scheduled scrapes
+//! SCRAPE_INTERVAL_MINUTES=360                 # every source (default: 6 hours)
+//! SCRAPE_INTERVAL_MINUTES_CAINS_BALLROOM=120  # one source (its source_id, uppercased)
+//! SCRAPE_INTERVAL_MINUTES_TULSA_CITY_CALENDAR=off  # never on a timer; manual runs still work
+//! ```
+//! A source's `interval_minutes` in `scrape_sources` beats both (see
+//! `sources.rs`).
+//!
+//! ## Behavior
+//! - Each source first runs after a random delay of up to
+//!   `MAX_START_JITTER` (or its interval, if shorter), so a restart
+//!   doesn't fire every scraper at once
+//! - A run that outlasts the interval delays the next tick instead of
+//!   overlapping it; a source already running from a manual trigger
+//!   skips the tick
+//! - A failing or panicking scraper fails its run (see `runs.rs`); its
+//!   timer, and every other source's, keeps going
+//! - `shutdown` stops new runs and gives the ones in flight
+//!   `SHUTDOWN_GRACE` to finish
+//!
+//! ## Reloads
+//! A supervisor task keeps the timers in line with the runner's sources:
+//! after each `ScrapeRunner::reload` (a source added, disabled or
+//! rescheduled through the admin API), a source no longer running has its
+//! timer stopped (a run in flight finishes), a new one gets a timer, and
+//! one whose interval changed is restarted with the new interval.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::Rng;
+use sqlx::PgPool;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+
+use crate::error::AppError;
+use crate::scraper::runs::ScrapeRunner;
+use crate::scraper::sources;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Minutes between runs of a source, unless configured.
+pub const DEFAULT_INTERVAL_MINUTES: u64 = 360;
+
+/// The longest a source waits for its first run.
+pub const MAX_START_JITTER: Duration = Duration::from_secs(15 * 60);
+
+/// How long shutdown waits for runs in flight.
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// When one source runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceSchedule {
+    pub source: String,
+    pub interval: Duration,
+    /// Until the first run
+    pub first_delay: Duration,
+}
+
+/// Whether `SCRAPER_ENABLED` allows scheduled scrapes (unset: yes).
+fn scheduler_enabled(raw: Option<&str>) -> bool {
+    !matches!(
+        raw.map(|raw| raw.trim().to_lowercase()).as_deref(),
+        Some("false" | "0" | "off" | "no")
+    )
+}
+
+/// `source`'s interval, or `None` if its timer is off. `lookup` reads the
+/// environment.
+fn interval_for(source: &str, lookup: impl Fn(&str) -> Option<String>) -> Option<Duration> {
+    let minutes = |key: &str| {
+        let raw = lookup(key)?;
+        match raw.trim().parse::<u64>() {
+            Ok(minutes) if minutes > 0 => Some(Some(minutes)),
+            _ if raw.trim().eq_ignore_ascii_case("off") => Some(None),
+            _ => {
+                tracing::warn!("Invalid {} '{}', ignoring it", key, raw);
+                None
+            }
+        }
+    };
+
+    let own = format!("SCRAPE_INTERVAL_MINUTES_{}", source.to_uppercase());
+    let minutes = minutes(&own)
+        .or_else(|| minutes("SCRAPE_INTERVAL_MINUTES"))
+        .unwrap_or(Some(DEFAULT_INTERVAL_MINUTES))?;
+    Some(Duration::from_secs(minutes * 60))
+}
+
+/// A random wait before the first run: under `MAX_START_JITTER` and under
+/// the interval.
+fn start_jitter(interval: Duration, rng: &mut impl Rng) -> Duration {
+    let cap = interval.min(MAX_START_JITTER).as_millis() as u64;
+    Duration::from_millis(rng.gen_range(0..cap.max(1)))
+}
+
+// =============================================================================
+// SCHEDULER
+// =============================================================================
+
+/// The supervisor of the per-source timers, and the signal that stops it.
+pub struct ScrapeScheduler {
+    supervisor: Option<JoinHandle<()>>,
+    shutdown: watch::Sender<bool>,
+    /// Each scheduled source's interval, as of the last sync
+    scheduled: Arc<Mutex<BTreeMap<String, Duration>>>,
+}
+
+/// One source's timer task.
+struct Timer {
+    interval: Duration,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl ScrapeScheduler {
+    /// Starts a timer for every source that isn't turned off (none at all
+    /// with `SCRAPER_ENABLED=false`), and keeps them in line with the
+    /// runner's reloads.
+    pub fn start(runner: Arc<ScrapeRunner>, pool: PgPool) -> Self {
+        let (shutdown, stopped) = watch::channel(false);
+        let scheduled = Arc::new(Mutex::new(BTreeMap::new()));
+
+        if !scheduler_enabled(std::env::var("SCRAPER_ENABLED").ok().as_deref()) {
+            tracing::info!("SCRAPER_ENABLED is off; scrapers only run when triggered");
+            return Self { supervisor: None, shutdown, scheduled };
+        }
+
+        let supervisor = tokio::spawn(supervise(runner, pool, stopped, scheduled.clone()));
+        Self { supervisor: Some(supervisor), shutdown, scheduled }
+    }
+
+    /// Each scheduled source and its interval, by `source_id`.
+    #[allow(dead_code)] // Read by tests; the log has the same at each sync
+    pub fn scheduled(&self) -> Vec<(String, Duration)> {
+        let scheduled = self.scheduled.lock().expect("schedule lock poisoned");
+        scheduled.iter().map(|(source, interval)| (source.clone(), *interval)).collect()
+    }
+
+    /// Stops scheduling and waits up to `grace` for runs in flight. Runs
+    /// still going after that are cut off with the process.
+    pub async fn shutdown(self, grace: Duration) {
+        let _ = self.shutdown.send(true);
+        let Some(supervisor) = self.supervisor else { return };
+        if tokio::time::timeout(grace, supervisor).await.is_err() {
+            tracing::warn!("scrapes still running at shutdown; they'll be marked failed at the next start");
+        }
+    }
+}
+
+/// Syncs the timers now and after every reload, until `shutdown`; then
+/// stops them all and waits for their runs.
+async fn supervise(
+    runner: Arc<ScrapeRunner>,
+    pool: PgPool,
+    mut shutdown: watch::Receiver<bool>,
+    scheduled: Arc<Mutex<BTreeMap<String, Duration>>>,
+) {
+    let mut reloads = runner.reloads();
+    let mut timers: HashMap<String, Timer> = HashMap::new();
+    let mut retired: Vec<JoinHandle<()>> = Vec::new();
+
+    loop {
+        match wanted(&runner, &pool).await {
+            Ok(wanted) => {
+                sync(&mut timers, &mut retired, wanted, &runner, &pool);
+                let mut scheduled = scheduled.lock().expect("schedule lock poisoned");
+                *scheduled = timers.iter().map(|(source, timer)| (source.clone(), timer.interval)).collect();
+            }
+            // Keep the timers we have; the next reload tries again
+            Err(e) => tracing::warn!(error = %e, "couldn't read scrape source intervals; schedule unchanged"),
+        }
+
+        tokio::select! {
+            changed = reloads.changed() => if changed.is_err() { break },
+            _ = shutdown.changed() => break,
+        }
+    }
+
+    for timer in timers.values() {
+        let _ = timer.stop.send(true);
+    }
+    let tasks = timers.into_values().map(|timer| timer.task).chain(retired);
+    futures_util::future::join_all(tasks).await;
+}
+
+/// The interval of each of the runner's sources that has a timer: its
+/// row's `interval_minutes`, else the environment's.
+async fn wanted(runner: &ScrapeRunner, pool: &PgPool) -> Result<HashMap<String, Duration>, sqlx::Error> {
+    let configured: HashMap<String, i32> = sources::list(pool)
+        .await?
+        .into_iter()
+        .filter_map(|row| Some((row.id, row.interval_minutes?)))
+        .collect();
+    let lookup = |key: &str| std::env::var(key).ok();
+
+    Ok(runner
+        .sources()
+        .into_iter()
+        .filter_map(|source| {
+            let interval = match configured.get(&source) {
+                Some(&minutes) if minutes > 0 => Duration::from_secs(minutes as u64 * 60),
+                _ => interval_for(&source, lookup)?,
+            };
+            Some((source, interval))
+        })
+        .collect())
+}
+
+/// Stops the timers not `wanted` (or wanted at another interval), moving
+/// them to `retired` to finish their runs, and starts the missing ones.
+fn sync(
+    timers: &mut HashMap<String, Timer>,
+    retired: &mut Vec<JoinHandle<()>>,
+    wanted: HashMap<String, Duration>,
+    runner: &Arc<ScrapeRunner>,
+    pool: &PgPool,
+) {
+    retired.retain(|task| !task.is_finished());
+
+    let stale: Vec<String> = timers
+        .iter()
+        .filter(|(source, timer)| wanted.get(*source) != Some(&timer.interval))
+        .map(|(source, _)| source.clone())
+        .collect();
+    for source in stale {
+        let timer = timers.remove(&source).expect("listed above");
+        let _ = timer.stop.send(true);
+        retired.push(timer.task);
+        tracing::info!(source = %source, "unscheduled scraper");
+    }
+
+    let mut rng = rand::thread_rng();
+    for (source, interval) in wanted {
+        if timers.contains_key(&source) {
+            continue;
+        }
+        let schedule = SourceSchedule { source: source.clone(), interval, first_delay: start_jitter(interval, &mut rng) };
+        tracing::info!(
+            source = %schedule.source,
+            every_minutes = schedule.interval.as_secs() / 60,
+            first_in_secs = schedule.first_delay.as_secs(),
+            "scheduled scraper"
+        );
+
+        let (stop, stopped) = watch::channel(false);
+        let runner = runner.clone();
+        let pool = pool.clone();
+        let task = tokio::spawn(async move {
+            let source = schedule.source.clone();
+            drive(&schedule, stopped, || scrape_once(&runner, &pool, &source)).await;
+        });
+        timers.insert(source, Timer { interval, stop, task });
+    }
+}
+
+/// Calls `run` after `first_delay`, then every `interval`, until
+/// `shutdown` changes. A run in progress finishes first.
+async fn drive<F, Fut>(schedule: &SourceSchedule, mut shutdown: watch::Receiver<bool>, mut run: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut ticker = interval_at(Instant::now() + schedule.first_delay, schedule.interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => run().await,
+            _ = shutdown.changed() => return,
+        }
+    }
+}
+
+/// One scheduled run of `source`, waiting for it to finish.
+async fn scrape_once(runner: &Arc<ScrapeRunner>, pool: &PgPool, source: &str) {
+    match runner.start(pool, Some(source), false).await {
+        Ok((batch, task)) => {
+            if let Err(e) = task.await {
+                tracing::error!(batch = %batch.id, source = %source, error = %e, "scheduled scrape task failed");
+            }
+        }
+        Err(AppError::Conflict(_)) => {
+            tracing::info!(source = %source, "already scraping; skipping this scheduled run");
+        }
+        Err(e) => tracing::error!(source = %source, error = %e, "couldn't start a scheduled scrape"),
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::scraper::fetch::FetchPool;
+    use crate::scraper::fixture::FixtureScraper;
+    use crate::scraper::registry::ScraperRegistry;
+    use crate::scraper::sources::UpdateScrapeSource;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn schedule(interval: Duration, first_delay: Duration) -> SourceSchedule {
+        SourceSchedule { source: "fixture".to_string(), interval, first_delay }
+    }
+
+    async fn sleep_minutes(minutes: u32) {
+        tokio::time::sleep(MINUTE * minutes).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn runs_after_the_jitter_then_every_interval_until_shutdown() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let (shutdown, stopped) = watch::channel(false);
+        let counter = runs.clone();
+        let task = tokio::spawn(async move {
+            let schedule = schedule(MINUTE * 60, MINUTE * 5);
+            drive(&schedule, stopped, || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {}
+            })
+            .await;
+        });
+
+        let at = |minutes| {
+            let runs = runs.clone();
+            async move {
+                sleep_minutes(minutes).await;
+                runs.load(Ordering::SeqCst)
+            }
+        };
+        let runs_by = [at(4).await, at(2).await, at(58).await, at(2).await, at(60).await];
+        // Minutes 4, 6, 64, 66, 126: first at 5, then 65 and 125
+        assert_eq!(runs_by, [0, 1, 1, 2, 3]);
+
+        shutdown.send(true).unwrap();
+        task.await.unwrap();
+        sleep_minutes(120).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_runs_delay_the_next_instead_of_overlapping() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let running = Arc::new(AtomicUsize::new(0));
+        let (shutdown, stopped) = watch::channel(false);
+        let (counter, active) = (started.clone(), running.clone());
+        let task = tokio::spawn(async move {
+            let schedule = schedule(MINUTE * 60, Duration::ZERO);
+            drive(&schedule, stopped, || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let active = active.clone();
+                async move {
+                    assert_eq!(active.fetch_add(1, Ordering::SeqCst), 0, "runs overlapped");
+                    sleep_minutes(90).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                }
+            })
+            .await;
+        });
+
+        // Runs at 0 and 90 (the tick at 60 waited), so two by minute 100
+        sleep_minutes(100).await;
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+
+        // Shutdown lets the run in flight finish
+        shutdown.send(true).unwrap();
+        task.await.unwrap();
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn intervals_come_from_the_environment() {
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("SCRAPE_INTERVAL_MINUTES", "120"),
+            ("SCRAPE_INTERVAL_MINUTES_CAINS_BALLROOM", "30"),
+            ("SCRAPE_INTERVAL_MINUTES_TULSA_CITY", "off"),
+            ("SCRAPE_INTERVAL_MINUTES_BROKEN", "soon"),
+        ]);
+        let lookup = |key: &str| env.get(key).map(|value| value.to_string());
+
+        assert_eq!(interval_for("cains_ballroom", lookup), Some(MINUTE * 30));
+        assert_eq!(interval_for("tulsa_city", lookup), None);
+        assert_eq!(interval_for("guthrie_green", lookup), Some(MINUTE * 120));
+        assert_eq!(interval_for("broken", lookup), Some(MINUTE * 120));
+        assert_eq!(interval_for("anything", |_| None), Some(MINUTE * DEFAULT_INTERVAL_MINUTES as u32));
+
+        assert!(scheduler_enabled(None));
+        assert!(scheduler_enabled(Some("true")));
+        assert!(!scheduler_enabled(Some("false")));
+        assert!(!scheduler_enabled(Some(" OFF ")));
+    }
+
+    /// Waits for the scheduler to have `source` at `expected`.
+    async fn settled(scheduler: &ScrapeScheduler, source: &str, expected: Option<Duration>) {
+        let interval = || scheduler.scheduled().into_iter().find(|(id, _)| id == source).map(|(_, interval)| interval);
+        for _ in 0..100 {
+            if interval() == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("{} stayed at {:?}, expected {:?}", source, interval(), expected);
+    }
+
+    #[tokio::test]
+    async fn timers_follow_source_changes_without_a_restart() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let source = format!("timer_{}", uuid::Uuid::new_v4().simple());
+        let registry = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new(&source, Vec::new()));
+        let runner = Arc::new(ScrapeRunner::new(registry));
+        runner.reload(&pool).await.unwrap();
+        let scheduler = ScrapeScheduler::start(runner.clone(), pool.clone());
+        settled(&scheduler, &source, interval_for(&source, |key| std::env::var(key).ok())).await;
+        let change = |changes: UpdateScrapeSource| {
+            let (pool, runner, source) = (pool.clone(), runner.clone(), source.clone());
+            async move {
+                sources::update(&pool, &source, &changes).await.unwrap().unwrap();
+                runner.reload(&pool).await.unwrap();
+            }
+        };
+
+        change(UpdateScrapeSource { interval_minutes: Some(45), ..Default::default() }).await;
+        settled(&scheduler, &source, Some(MINUTE * 45)).await;
+        change(UpdateScrapeSource { enabled: Some(false), ..Default::default() }).await;
+        settled(&scheduler, &source, None).await;
+
+        scheduler.shutdown(SHUTDOWN_GRACE).await;
+        sources::delete(&pool, &source).await.unwrap();
+    }
+
+    #[test]
+    fn first_runs_are_spread_out() {
+        let mut rng = StdRng::seed_from_u64(918);
+        let delays: Vec<Duration> = (0..50).map(|_| start_jitter(MINUTE * 360, &mut rng)).collect();
+        assert!(delays.iter().all(|delay| *delay < MAX_START_JITTER));
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+
+        assert!(start_jitter(MINUTE * 2, &mut rng) < MINUTE * 2);
+    }
+}
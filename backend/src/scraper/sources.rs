@@ -0,0 +1,435 @@
+//! # Scrape Sources
+//!
+//! Every source the scrapers know, as a row of `scrape_sources`, so adding
+//! an iCal feed or rescheduling a scraper is an admin request instead of a
+//! code change and a restart (`/api/admin/sources`).
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Kinds
+//! | Kind | Scraper | Needs |
+//! |------|---------|-------|
+//! | `ical` | `platforms::ical` | `url` of the `.ics` feed |
+//! | `jsonld` | `platforms::jsonld` | `url` of the page |
+//! | `sitemap` | `platforms::sitemap` | `url` of the sitemap, or of the site; `config` may set `pattern` and `max_urls` |
+//! | `custom` | written in code (Cain's, the city calendar, Meetup) | its row only turns it on or off and sets its interval |
+//! | `eventbrite` | none yet; rows are rejected | |
+//!
+//! `ical`, `jsonld` and `sitemap` sources are built from their row alone,
+//! with `default_category` and `default_venue` applied to every event. A
+//! `sitemap` row's `config` is checked when it's added; one changed into
+//! something unreadable later is skipped at load, with a warning.
+//!
+//! ## Loading
+//! `ScraperRegistry::reload` (at startup, and after every change through
+//! the admin API) first `seed`s a `custom` row for each scraper in code and
+//! an `ical` row for each `ICAL_FEEDS` entry, unless one exists, so an
+//! admin's edits are never overwritten; then it builds the enabled rows.
+//! The schedule follows (`schedule.rs`): a disabled source's timer stops,
+//! a new one's starts, without a restart.
+//!
+//! ## Interval
+//! `interval_minutes`, when set, beats `SCRAPE_INTERVAL_MINUTES_<ID>` and
+//! `SCRAPE_INTERVAL_MINUTES`. Setting it to 0 in an update clears it.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::{FromRow, PgPool};
+
+use crate::models::{is_http_url, FieldError};
+use crate::scraper::platforms::ical::{IcalFeed, IcalScraper};
+use crate::scraper::platforms::jsonld::{JsonLdPage, JsonLdScraper};
+use crate::scraper::platforms::sitemap::{SitemapConfig, SitemapScraper, SitemapSite};
+use crate::scraper::traits::EventScraper;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Longest accepted source id.
+pub const MAX_ID_CHARS: usize = 64;
+
+// =============================================================================
+// MODELS
+// =============================================================================
+
+/// What builds a source's scraper.
+///
+/// Stored as the Postgres enum `scrape_source_kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "scrape_source_kind", rename_all = "snake_case")]
+pub enum ScrapeSourceKind {
+    Ical,
+    Jsonld,
+    Eventbrite,
+    Custom,
+    Sitemap,
+}
+
+/// One source, as stored in `scrape_sources`.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ScrapeSource {
+    /// Its `source_id` (`scrape_runs.source`)
+    pub id: String,
+    /// Credited as its events' `source_name`
+    pub name: String,
+    pub kind: ScrapeSourceKind,
+    pub url: Option<String>,
+    pub default_category: Option<String>,
+    pub default_venue: Option<String>,
+    pub enabled: bool,
+    /// `None`: the environment's (see "Interval" above)
+    pub interval_minutes: Option<i32>,
+    pub config: Json<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for adding a source.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateScrapeSource {
+    pub id: String,
+    pub name: String,
+    pub kind: ScrapeSourceKind,
+    pub url: Option<String>,
+    pub default_category: Option<String>,
+    pub default_venue: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    pub interval_minutes: Option<i32>,
+    #[serde(default)]
+    pub config: Option<Value>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Request body for changing a source. Fields left out are unchanged;
+/// `id` and `kind` can't be changed.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateScrapeSource {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub default_category: Option<String>,
+    pub default_venue: Option<String>,
+    pub enabled: Option<bool>,
+    /// 0 clears it
+    pub interval_minutes: Option<i32>,
+    pub config: Option<Value>,
+}
+
+impl CreateScrapeSource {
+    /// Checks the fields; every problem is reported.
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        let id_chars = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+        if self.id.is_empty() || self.id.len() > MAX_ID_CHARS || !self.id.chars().all(id_chars) {
+            errors.push(FieldError::new(
+                "id",
+                format!("must be 1-{} lowercase letters, digits or underscores", MAX_ID_CHARS),
+            ));
+        }
+        if self.name.trim().is_empty() {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        match (self.kind, self.url.as_deref()) {
+            (ScrapeSourceKind::Eventbrite, _) => {
+                errors.push(FieldError::new("kind", "there is no eventbrite scraper yet"));
+            }
+            (ScrapeSourceKind::Custom, _) => {}
+            (_, None) => errors.push(FieldError::new("url", "is required for this kind")),
+            (_, Some(url)) if !is_http_url(url) => {
+                errors.push(FieldError::new("url", "must be an http(s) URL"));
+            }
+            _ => {}
+        }
+        if self.interval_minutes.is_some_and(|minutes| minutes <= 0) {
+            errors.push(FieldError::new("interval_minutes", "must be positive"));
+        }
+        match &self.config {
+            Some(config) if !config.is_object() => errors.push(FieldError::new("config", "must be an object")),
+            config if self.kind == ScrapeSourceKind::Sitemap => {
+                if let Err(e) = SitemapConfig::from_value(config.as_ref().unwrap_or(&Value::Object(Default::default()))) {
+                    errors.push(FieldError::new("config", e));
+                }
+            }
+            _ => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl UpdateScrapeSource {
+    /// Checks the fields given; every problem is reported.
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        if self.name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        if self.url.as_deref().is_some_and(|url| !is_http_url(url)) {
+            errors.push(FieldError::new("url", "must be an http(s) URL"));
+        }
+        if self.interval_minutes.is_some_and(|minutes| minutes < 0) {
+            errors.push(FieldError::new("interval_minutes", "must be positive (or 0 to clear it)"));
+        }
+        if self.config.as_ref().is_some_and(|config| !config.is_object()) {
+            errors.push(FieldError::new("config", "must be an object"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl ScrapeSource {
+    /// The scraper this row describes, for the kinds built from a row
+    /// (`None` for `custom` and `eventbrite`).
+    pub fn build(&self) -> Option<Arc<dyn EventScraper>> {
+        let url = self.url.clone()?;
+        match self.kind {
+            ScrapeSourceKind::Ical => Some(Arc::new(IcalScraper::new(IcalFeed {
+                source_id: self.id.clone(),
+                name: self.name.clone(),
+                url,
+                default_category: self.default_category.clone(),
+                default_venue: self.default_venue.clone(),
+            }))),
+            ScrapeSourceKind::Jsonld => Some(Arc::new(JsonLdScraper::new(JsonLdPage {
+                source_id: self.id.clone(),
+                name: self.name.clone(),
+                url,
+                default_category: self.default_category.clone(),
+                default_venue: self.default_venue.clone(),
+            }))),
+            ScrapeSourceKind::Sitemap => match SitemapConfig::from_value(&self.config) {
+                Ok(config) => Some(Arc::new(SitemapScraper::new(SitemapSite {
+                    source_id: self.id.clone(),
+                    name: self.name.clone(),
+                    url,
+                    default_category: self.default_category.clone(),
+                    default_venue: self.default_venue.clone(),
+                    config,
+                }))),
+                Err(e) => {
+                    tracing::warn!(source = %self.id, error = %e, "sitemap source's config can't be read; skipping it");
+                    None
+                }
+            },
+            ScrapeSourceKind::Eventbrite | ScrapeSourceKind::Custom => None,
+        }
+    }
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// Every source, by id.
+pub async fn list(pool: &PgPool) -> Result<Vec<ScrapeSource>, sqlx::Error> {
+    sqlx::query_as::<_, ScrapeSource>("SELECT * FROM scrape_sources ORDER BY id")
+        .fetch_all(pool)
+        .await
+}
+
+/// A source by id.
+pub async fn find(pool: &PgPool, id: &str) -> Result<Option<ScrapeSource>, sqlx::Error> {
+    sqlx::query_as::<_, ScrapeSource>("SELECT * FROM scrape_sources WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Adds a validated source. An id already taken is a unique violation
+/// (`AppError::Conflict`).
+pub async fn create(pool: &PgPool, source: &CreateScrapeSource) -> Result<ScrapeSource, sqlx::Error> {
+    sqlx::query_as::<_, ScrapeSource>(
+        r#"
+        INSERT INTO scrape_sources
+            (id, name, kind, url, default_category, default_venue, enabled, interval_minutes, config)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, COALESCE($9, '{}'::jsonb))
+        RETURNING *
+        "#,
+    )
+        .bind(&source.id)
+        .bind(source.name.trim())
+        .bind(source.kind)
+        .bind(&source.url)
+        .bind(&source.default_category)
+        .bind(&source.default_venue)
+        .bind(source.enabled)
+        .bind(source.interval_minutes)
+        .bind(source.config.as_ref().map(Json))
+        .fetch_one(pool)
+        .await
+}
+
+/// Applies a validated update; `None` if there's no such source.
+pub async fn update(pool: &PgPool, id: &str, changes: &UpdateScrapeSource) -> Result<Option<ScrapeSource>, sqlx::Error> {
+    sqlx::query_as::<_, ScrapeSource>(
+        r#"
+        UPDATE scrape_sources SET
+            name = COALESCE($2, name),
+            url = COALESCE($3, url),
+            default_category = COALESCE($4, default_category),
+            default_venue = COALESCE($5, default_venue),
+            enabled = COALESCE($6, enabled),
+            interval_minutes = CASE WHEN $7::int IS NULL THEN interval_minutes ELSE NULLIF($7, 0) END,
+            config = COALESCE($8, config)
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+        .bind(id)
+        .bind(changes.name.as_deref().map(str::trim))
+        .bind(&changes.url)
+        .bind(&changes.default_category)
+        .bind(&changes.default_venue)
+        .bind(changes.enabled)
+        .bind(changes.interval_minutes)
+        .bind(changes.config.as_ref().map(Json))
+        .fetch_optional(pool)
+        .await
+}
+
+/// Removes a source; false if there was none. Its events and run history
+/// stay.
+pub async fn delete(pool: &PgPool, id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM scrape_sources WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Adds a `custom` row for each scraper in code (`(source_id, name)`) and
+/// an `ical` row for each feed, skipping ids that already have one.
+pub async fn seed(pool: &PgPool, custom: &[(&str, &str)], feeds: &[IcalFeed]) -> Result<(), sqlx::Error> {
+    for (id, name) in custom {
+        sqlx::query("INSERT INTO scrape_sources (id, name, kind) VALUES ($1, $2, 'custom') ON CONFLICT (id) DO NOTHING")
+            .bind(id)
+            .bind(name)
+            .execute(pool)
+            .await?;
+    }
+    for feed in feeds {
+        sqlx::query(
+            r#"
+            INSERT INTO scrape_sources (id, name, kind, url, default_category, default_venue)
+            VALUES ($1, $2, 'ical', $3, $4, $5)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+            .bind(&feed.source_id)
+            .bind(&feed.name)
+            .bind(&feed.url)
+            .bind(&feed.default_category)
+            .bind(&feed.default_venue)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(kind: ScrapeSourceKind, url: Option<&str>) -> CreateScrapeSource {
+        CreateScrapeSource {
+            id: "guthrie_green".to_string(),
+            name: "Guthrie Green".to_string(),
+            kind,
+            url: url.map(str::to_string),
+            default_category: None,
+            default_venue: None,
+            enabled: true,
+            interval_minutes: None,
+            config: None,
+        }
+    }
+
+    fn fields(result: Result<(), Vec<FieldError>>) -> Vec<String> {
+        result.err().unwrap_or_default().into_iter().map(|error| error.field).collect()
+    }
+
+    #[test]
+    fn sources_are_validated_by_kind() {
+        let feed = "https://www.guthriegreen.com/events.ics";
+        assert!(create(ScrapeSourceKind::Ical, Some(feed)).validate().is_ok());
+        assert!(create(ScrapeSourceKind::Custom, None).validate().is_ok());
+        assert_eq!(fields(create(ScrapeSourceKind::Jsonld, None).validate()), ["url"]);
+        assert_eq!(fields(create(ScrapeSourceKind::Ical, Some("ftp://x/events.ics")).validate()), ["url"]);
+        assert_eq!(fields(create(ScrapeSourceKind::Eventbrite, Some(feed)).validate()), ["kind"]);
+
+        let site = "https://www.tulsapac.example/";
+        assert!(create(ScrapeSourceKind::Sitemap, Some(site)).validate().is_ok());
+        let capped = CreateScrapeSource {
+            config: Some(serde_json::json!({ "pattern": "/shows/", "max_urls": 10_000 })),
+            ..create(ScrapeSourceKind::Sitemap, Some(site))
+        };
+        assert_eq!(fields(capped.validate()), ["config"]);
+
+        let bad = CreateScrapeSource {
+            id: "Guthrie Green".to_string(),
+            name: " ".to_string(),
+            interval_minutes: Some(0),
+            config: Some(Value::Bool(true)),
+            ..create(ScrapeSourceKind::Ical, Some(feed))
+        };
+        assert_eq!(fields(bad.validate()), ["id", "name", "interval_minutes", "config"]);
+
+        let update = UpdateScrapeSource { interval_minutes: Some(0), ..Default::default() };
+        assert!(update.validate().is_ok());
+        let update = UpdateScrapeSource { url: Some("events.ics".to_string()), ..Default::default() };
+        assert_eq!(fields(update.validate()), ["url"]);
+    }
+
+    #[test]
+    fn generic_kinds_build_their_scraper() {
+        let row = |kind| ScrapeSource {
+            id: "gg".to_string(),
+            name: "Guthrie Green".to_string(),
+            kind,
+            url: Some("https://www.guthriegreen.com/events.ics".to_string()),
+            default_category: None,
+            default_venue: None,
+            enabled: true,
+            interval_minutes: None,
+            config: Json(Value::Object(Default::default())),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        for kind in [ScrapeSourceKind::Ical, ScrapeSourceKind::Jsonld, ScrapeSourceKind::Sitemap] {
+            let scraper = row(kind).build().unwrap();
+            assert_eq!((scraper.source_id(), scraper.name()), ("gg", "Guthrie Green"));
+        }
+        let unreadable = ScrapeSource {
+            config: Json(serde_json::json!({ "max_urls": "all" })),
+            ..row(ScrapeSourceKind::Sitemap)
+        };
+        assert!(unreadable.build().is_none());
+        assert!(row(ScrapeSourceKind::Custom).build().is_none());
+        assert!(row(ScrapeSourceKind::Eventbrite).build().is_none());
+    }
+}
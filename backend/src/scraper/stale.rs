@@ -0,0 +1,248 @@
+//! # Unlisted Events
+//!
+//! A venue that cancels a show usually just deletes it from its site.
+//! Every scrape stamps `last_seen_at` on the events it finds (see
+//! `persist.rs`); after a good run, this source's upcoming events it
+//! hasn't listed for `STALE_AFTER_RUNS` good runs are marked cancelled,
+//! never deleted, and everyone who saved one is notified.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Good Runs
+//! Only real runs that completed, found something (or found their pages
+//! unchanged, see `cache.rs`) and fetched every page count: a run that
+//! found nothing, or couldn't fetch a month of the calendar, more likely
+//! means the site broke than that every show was called off.
+//!
+//! ## What Is Never Cancelled
+//! - Events created through the API (`scraped` is false), even if a
+//!   scraper later matched them
+//! - Another source's events (a source only cancels its `source_name`)
+//! - Events that already started
+//! - Events seen by any source since: a merged duplicate's listing counts
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::services::analytics;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Good runs in a row that must miss an event before it's cancelled.
+pub const STALE_AFTER_RUNS: i64 = 3;
+
+/// `notifications.kind` written for savers of a cancelled event.
+pub const NOTIFICATION_KIND: &str = "cancelled";
+
+// =============================================================================
+// JOB
+// =============================================================================
+
+/// Cancels the events `source_name` (the scraper with `source_id`) stopped
+/// listing: upcoming, scraped, and not seen since the oldest of its last
+/// `after_runs` good runs started. Does nothing until it has that many.
+///
+/// # Returns
+/// The ids of the events cancelled.
+pub async fn cancel_unlisted(
+    pool: &PgPool,
+    source_id: &str,
+    source_name: &str,
+    after_runs: i64,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let cutoff: Option<chrono::DateTime<chrono::Utc>> = sqlx::query_scalar(
+        r#"
+        SELECT started_at FROM scrape_runs
+        WHERE source = $1 AND NOT dry_run AND status = 'completed'
+          AND (events_found > 0 OR pages_unchanged > 0) AND failed_urls = '[]'::jsonb
+        ORDER BY started_at DESC
+        OFFSET $2 - 1 LIMIT 1
+        "#,
+    )
+        .bind(source_id)
+        .bind(after_runs)
+        .fetch_optional(pool)
+        .await?;
+    let Some(cutoff) = cutoff else {
+        return Ok(Vec::new());
+    };
+
+    let mut tx = pool.begin().await?;
+    let cancelled: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE events
+        SET status = 'cancelled'
+        WHERE scraped AND source_name = $1
+          AND status <> 'cancelled'
+          AND start_time > NOW()
+          AND last_seen_at < $2
+        RETURNING id
+        "#,
+    )
+        .bind(source_name)
+        .bind(cutoff)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    if !cancelled.is_empty() {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (user_id, kind, event_id, title, body)
+            SELECT DISTINCT ui.user_id, $1, events.id, events.title,
+                   'Cancelled: no longer listed by ' || $3
+            FROM user_interactions ui
+            JOIN events ON events.id = ui.event_id
+            WHERE ui.event_id = ANY($2)
+              AND ui.interaction_type = ANY($4)
+            ON CONFLICT (user_id, event_id, kind) DO NOTHING
+            "#,
+        )
+            .bind(NOTIFICATION_KIND)
+            .bind(&cancelled)
+            .bind(source_name)
+            .bind(analytics::spellings_of("save"))
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(cancelled)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    use crate::scraper::fetch::FetchPool;
+    use crate::scraper::fixture::FixtureScraper;
+    use crate::scraper::registry::ScraperRegistry;
+    use crate::scraper::traits::ScrapedEvent;
+
+    async fn status(pool: &PgPool, url: &str) -> String {
+        sqlx::query_scalar("SELECT status::text FROM events WHERE source_url = $1")
+            .bind(url)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn events_a_source_stops_listing_are_cancelled() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let source = format!("stale_{}", run.simple());
+        let url = |slug: &str| format!("https://stale.example/{}/{}", run, slug);
+        let start = Utc::now() + Duration::days(20);
+        let kept = ScrapedEvent::new("Still On", &url("kept"), start);
+        let dropped = ScrapedEvent::new("Called Off", &url("dropped"), start);
+        let scrape = |events: Vec<ScrapedEvent>| {
+            let (pool, source) = (pool.clone(), source.clone());
+            async move {
+                let registry = ScraperRegistry::new(FetchPool::for_tests()).register(FixtureScraper::new(&source, events));
+                let summary = registry.run_one(&pool, &source).await.unwrap();
+                assert_eq!(summary.error, None);
+            }
+        };
+
+        // A hand-made event credited to the same source, and a saver
+        let manual: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, source_name, start_time) VALUES ('Manual', $1, $2, $3) RETURNING id",
+        )
+            .bind(url("manual"))
+            .bind(format!("Fixture {}", source))
+            .bind(start)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let user: Uuid = sqlx::query_scalar("INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id")
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        scrape(vec![kept.clone(), dropped.clone()]).await;
+        let dropped_id: Uuid = sqlx::query_scalar("SELECT id FROM events WHERE source_url = $1")
+            .bind(url("dropped"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO user_interactions (user_id, event_id, interaction_type) VALUES ($1, $2, 'saved')")
+            .bind(user)
+            .bind(dropped_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Gone from the site: still listed by us until three good runs miss it
+        scrape(vec![kept.clone()]).await;
+        scrape(vec![kept.clone()]).await;
+        assert_eq!(status(&pool, &url("dropped")).await, "scheduled");
+        scrape(vec![kept.clone()]).await;
+        assert_eq!(status(&pool, &url("dropped")).await, "cancelled");
+        assert_eq!(status(&pool, &url("kept")).await, "scheduled");
+        assert_eq!(status(&pool, &url("manual")).await, "scheduled");
+
+        let notified: Vec<(Uuid, String)> =
+            sqlx::query_as("SELECT event_id, kind FROM notifications WHERE user_id = $1")
+                .bind(user)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(notified, vec![(dropped_id, NOTIFICATION_KIND.to_string())]);
+
+        // A run that finds nothing doesn't count (or cancel)
+        scrape(Vec::new()).await;
+        assert_eq!(status(&pool, &url("kept")).await, "scheduled");
+        assert!(cancel_unlisted(&pool, &source, &format!("Fixture {}", source), STALE_AFTER_RUNS)
+            .await
+            .unwrap()
+            .is_empty());
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(manual).execute(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn seeing_an_event_leaves_updated_at_alone() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let id: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) VALUES ('Seen', $1, NOW()) RETURNING id",
+        )
+            .bind(format!("https://stale.example/{}", Uuid::new_v4()))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let updated_at = |pool: PgPool| async move {
+            sqlx::query_scalar::<_, chrono::DateTime<Utc>>("SELECT updated_at FROM events WHERE id = $1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+        let before = updated_at(pool.clone()).await;
+
+        sqlx::query("UPDATE events SET last_seen_at = NOW() WHERE id = $1").bind(id).execute(&pool).await.unwrap();
+        assert_eq!(updated_at(pool.clone()).await, before);
+        sqlx::query("UPDATE events SET title = 'Seen Again' WHERE id = $1").bind(id).execute(&pool).await.unwrap();
+        assert!(updated_at(pool.clone()).await > before);
+    }
+}
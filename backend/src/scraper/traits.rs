@@ -0,0 +1,179 @@
+//! # Scraper Types
+//!
+//! The contract every scraper implements, what it returns, and how it fails.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Names
+//! - `source_id()` - stable machine key (`"cains_ballroom"`), used to pick
+//!   a scraper (`run_one`) and to log its runs
+//! - `name()` - human name (`"Cain's Ballroom"`), stored as the events'
+//!   `source_name` so every event credits where it came from
+
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CreateEvent, EventStatus};
+use crate::scraper::fetch::Fetcher;
+
+// =============================================================================
+// TRAIT
+// =============================================================================
+
+/// A source of events: a venue site, a city calendar, a platform API.
+#[async_trait]
+pub trait EventScraper: Send + Sync {
+    /// Human-readable name, stored as `source_name` on its events.
+    fn name(&self) -> &str;
+
+    /// Stable key for this source (`snake_case`).
+    fn source_id(&self) -> &str;
+
+    /// Fetches and parses the source's current listings.
+    ///
+    /// Every request goes through `fetcher`, so robots.txt, the request
+    /// delay, connection pooling and the User-Agent are the same for every
+    /// scraper.
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError>;
+}
+
+// =============================================================================
+// ERROR TYPE
+// =============================================================================
+
+/// Reasons a scrape (or one scraped event) fails.
+#[derive(Debug, thiserror::Error)]
+pub enum ScraperError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The page didn't have the markup we expected (usually a redesign).
+    #[error("nothing matched `{selector}` ({context})")]
+    Parse { selector: String, context: String },
+
+    /// A date or time we couldn't read; carries the original text.
+    #[error("could not parse the date \"{0}\"")]
+    DateParse(String),
+
+    #[error("invalid event: {0}")]
+    Validation(String),
+
+    /// robots.txt doesn't let us fetch this URL (see `robots.rs`).
+    #[error("robots.txt disallows {0}")]
+    Disallowed(String),
+}
+
+// =============================================================================
+// SCRAPED EVENT
+// =============================================================================
+
+/// One event as a scraper found it, before validation and storage.
+///
+/// Narrower than `CreateEvent`: scrapers report what the page says, and
+/// `into_create_event` adds what the registry knows (the source).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrapedEvent {
+    pub title: String,
+    pub description: Option<String>,
+    pub venue: Option<String>,
+    pub venue_address: Option<String>,
+    pub location: Option<String>,
+    /// The event's own page on the source site
+    pub source_url: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    /// Our category name (`"music"`), if the scraper can tell
+    pub category: Option<String>,
+    /// The source's own label (`"Parks & Recreation"`); a mapping for it
+    /// beats `category` (see `categories.rs`)
+    #[serde(default)]
+    pub external_category: Option<String>,
+    /// The source's own id for the event (a Meetup id, an iCal UID),
+    /// kept with the listing in `event_sources`
+    #[serde(default)]
+    pub external_id: Option<String>,
+    pub tags: Vec<String>,
+    pub price_min: Option<f64>,
+    pub price_max: Option<f64>,
+    pub is_free: Option<bool>,
+    pub image_url: Option<String>,
+}
+
+impl ScrapedEvent {
+    /// An event with just the required fields; set the rest directly.
+    pub fn new(title: &str, source_url: &str, start_time: DateTime<Utc>) -> Self {
+        Self {
+            title: title.to_string(),
+            source_url: source_url.to_string(),
+            start_time,
+            ..Default::default()
+        }
+    }
+
+    /// Converts to a `CreateEvent` credited to `source_name`.
+    ///
+    /// Text fields are trimmed (and dropped if blank); the result still
+    /// needs `CreateEvent::validate`.
+    pub fn into_create_event(self, source_name: &str) -> CreateEvent {
+        CreateEvent {
+            title: self.title.trim().to_string(),
+            description: non_blank(self.description),
+            venue: non_blank(self.venue),
+            venue_id: None,
+            venue_address: non_blank(self.venue_address),
+            location: non_blank(self.location),
+            source_url: self.source_url.trim().to_string(),
+            source_name: Some(source_name.to_string()),
+            start_time: self.start_time,
+            end_time: self.end_time,
+            categories: non_blank(self.category).map(|category| vec![category.to_lowercase()]),
+            tags: self.tags,
+            price_min: self.price_min,
+            price_max: self.price_max,
+            is_free: self.is_free,
+            outdoor: false,
+            family_friendly: false,
+            image_url: non_blank(self.image_url),
+            status: EventStatus::Scheduled,
+            latitude: None,
+            longitude: None,
+        }
+    }
+}
+
+/// `Some` trimmed text, or `None` if blank.
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversion_credits_the_source_and_drops_blank_fields() {
+        let start = "2026-03-14T01:00:00Z".parse().unwrap();
+        let scraped = ScrapedEvent {
+            description: Some("   ".to_string()),
+            venue: Some(" Cain's Ballroom ".to_string()),
+            category: Some("Music".to_string()),
+            ..ScrapedEvent::new("  Turnpike Troubadours ", "https://example.com/show", start)
+        };
+
+        let event = scraped.into_create_event("Cain's Ballroom");
+        assert_eq!(event.title, "Turnpike Troubadours");
+        assert_eq!(event.source_name.as_deref(), Some("Cain's Ballroom"));
+        assert_eq!(event.venue.as_deref(), Some("Cain's Ballroom"));
+        assert_eq!(event.description, None);
+        assert_eq!(event.categories, Some(vec!["music".to_string()]));
+        assert_eq!(event.start_time, start);
+    }
+}
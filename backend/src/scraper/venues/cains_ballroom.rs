@@ -0,0 +1,332 @@
+//! # Cain's Ballroom
+//!
+//! Scrapes the calendar at cainsballroom.com/events/, a server-rendered
+//! page with one `article.event-card` per show.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+//!
+//! ## Markup
+//! ```html
+//! <article class="event-card">
+//!   <a class="event-card__image" href="/events/red-dirt-revival/"><img src="/wp-content/uploads/red-dirt.jpg"></a>
+//!   <h2 class="event-card__title"><a href="/events/red-dirt-revival/">Red Dirt Revival</a></h2>
+//!   <p class="event-card__support">With Jason Boland &amp; The Stragglers</p>
+//!   <p class="event-card__date">Wed, Dec 30</p>
+//!   <p class="event-card__time">Doors 7:00 PM / Show 8:00 PM</p>
+//!   <p class="event-card__price">$35 &ndash; $55</p>
+//!   <a class="event-card__tickets" href="https://www.ticketmaster.com/...">Buy Tickets</a>
+//! </article>
+//! ```
+//!
+//! ## Dates and Times
+//! The card's date and time are read together by `dates::parse_event_when`:
+//! - Dates have no year. Each gets its next occurrence from the scrape
+//!   date, so a January show read in December lands in the coming year;
+//!   a December show still listed in early January is the one just past.
+//! - Times give doors and show; we use the show time. A card without a
+//!   time starts at `DEFAULT_SHOW_HOUR`.
+//! - All times are Tulsa local time.
+//!
+//! ## Images
+//! The card's `<img>`: its `src`, or where a lazy-loading placeholder
+//! stands in, its `data-src` / `data-lazy-src`; normalized against the
+//! calendar (`images::normalize`). A card without one has no image.
+//!
+//! ## Skipped Cards
+//! A card without a title, a readable date or any link is logged and
+//! skipped. A page with no cards at all is a `ScraperError::Parse`: the
+//! markup has probably changed. (Unless it's one show's page, say the
+//! calendar redirecting to a headliner: then its Open Graph tags are read
+//! for that show, see `opengraph::fallback_event`.)
+
+use axum::async_trait;
+use chrono::{NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+
+use crate::scraper::dates::{parse_event_when, TULSA_TZ};
+use crate::scraper::fetch::Fetcher;
+use crate::scraper::images;
+use crate::scraper::platforms::opengraph;
+use crate::scraper::price::parse_price;
+use crate::scraper::traits::{EventScraper, ScrapedEvent, ScraperError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// The live site.
+pub const BASE_URL: &str = "https://www.cainsballroom.com";
+
+/// The calendar page, relative to the base URL.
+const CALENDAR_PATH: &str = "/events/";
+
+pub const VENUE: &str = "Cain's Ballroom";
+const VENUE_ADDRESS: &str = "423 N Main St, Tulsa, OK 74103";
+const VENUE_TZ: Tz = TULSA_TZ;
+
+/// Start hour (local) for a show whose card gives no time.
+pub const DEFAULT_SHOW_HOUR: u32 = 20;
+
+/// CSS selectors, all relative to the card except `CARD`.
+mod selectors {
+    pub const CARD: &str = "article.event-card";
+    pub const TITLE: &str = ".event-card__title";
+    pub const LINK: &str = ".event-card__title a[href]";
+    pub const SUPPORT: &str = ".event-card__support";
+    pub const DATE: &str = ".event-card__date";
+    pub const TIME: &str = ".event-card__time";
+    pub const PRICE: &str = ".event-card__price";
+    pub const TICKETS: &str = "a.event-card__tickets[href]";
+    pub const IMAGE: &str = ".event-card__image img";
+}
+
+/// `<img>` attributes an image is read from, in order: lazy-loading
+/// scripts put a placeholder in `src` and the image in one of the others.
+const IMAGE_ATTRIBUTES: &[&str] = &["src", "data-src", "data-lazy-src"];
+
+// =============================================================================
+// SCRAPER
+// =============================================================================
+
+/// Scraper for Cain's Ballroom's calendar.
+pub struct CainsBallroomScraper {
+    base_url: String,
+}
+
+impl CainsBallroomScraper {
+    pub fn new() -> Self {
+        Self::with_base_url(BASE_URL)
+    }
+
+    /// Points the scraper somewhere else (a mock server in tests).
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self { base_url: base_url.trim_end_matches('/').to_string() }
+    }
+}
+
+impl Default for CainsBallroomScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventScraper for CainsBallroomScraper {
+    fn name(&self) -> &str {
+        VENUE
+    }
+
+    fn source_id(&self) -> &str {
+        "cains_ballroom"
+    }
+
+    async fn scrape(&self, fetcher: &Fetcher) -> Result<Vec<ScrapedEvent>, ScraperError> {
+        let url = format!("{}{}", self.base_url, CALENDAR_PATH);
+        let today = Utc::now().with_timezone(&VENUE_TZ).date_naive();
+        fetcher.get_events(&url, |html| parse_events(html, &self.base_url, today)).await
+    }
+}
+
+// =============================================================================
+// PARSING
+// =============================================================================
+
+/// Parses the calendar page into events.
+///
+/// `base_url` resolves relative links; `today` (venue-local) picks the
+/// year for each date.
+pub fn parse_events(html: &str, base_url: &str, today: NaiveDate) -> Result<Vec<ScrapedEvent>, ScraperError> {
+    let base = Url::parse(base_url).map_err(|e| ScraperError::Validation(format!("base URL {}: {}", base_url, e)))?;
+    let document = Html::parse_document(html);
+
+    let card = selector(selectors::CARD);
+    let cards: Vec<ElementRef> = document.select(&card).collect();
+    if cards.is_empty() {
+        if let Some(event) = opengraph::fallback_event(html, &format!("{}{}", base_url, CALENDAR_PATH)) {
+            return Ok(vec![ScrapedEvent {
+                venue: Some(VENUE.to_string()),
+                venue_address: Some(VENUE_ADDRESS.to_string()),
+                location: Some("Tulsa, OK".to_string()),
+                category: Some("music".to_string()),
+                ..event
+            }]);
+        }
+        return Err(ScraperError::Parse {
+            selector: selectors::CARD.to_string(),
+            context: "Cain's Ballroom calendar".to_string(),
+        });
+    }
+
+    Ok(cards.into_iter().filter_map(|card| parse_card(card, &base, today)).collect())
+}
+
+/// One card, or `None` (logged) if it's missing what an event needs.
+fn parse_card(card: ElementRef, base: &Url, today: NaiveDate) -> Option<ScrapedEvent> {
+    let Some(title) = text(card, selectors::TITLE) else {
+        tracing::warn!("Cain's Ballroom: card without a title");
+        return None;
+    };
+
+    let when = format!(
+        "{} {}",
+        text(card, selectors::DATE).unwrap_or_default(),
+        text(card, selectors::TIME).unwrap_or_default()
+    );
+    let (start_time, end_time) = match parse_event_when(&when, today) {
+        Ok(when) => when.to_utc(VENUE_TZ, NaiveTime::from_hms_opt(DEFAULT_SHOW_HOUR, 0, 0).expect("valid hour")),
+        Err(e) => {
+            tracing::warn!(title = %title, error = %e, "Cain's Ballroom: unreadable date");
+            return None;
+        }
+    };
+
+    // The show's own page, or failing that where to buy tickets
+    let link = href(card, selectors::LINK).or_else(|| href(card, selectors::TICKETS));
+    let Some(source_url) = link.and_then(|link| base.join(&link).ok()) else {
+        tracing::warn!(title = %title, "Cain's Ballroom: card without a link");
+        return None;
+    };
+
+    let price = text(card, selectors::PRICE).and_then(|price| parse_price(&price));
+    let image_url = card.select(&selector(selectors::IMAGE)).next().and_then(|image| {
+        IMAGE_ATTRIBUTES
+            .iter()
+            .find_map(|attribute| images::normalize(image.value().attr(attribute)?, Some(base.as_str())))
+    });
+
+    Some(ScrapedEvent {
+        description: text(card, selectors::SUPPORT),
+        venue: Some(VENUE.to_string()),
+        venue_address: Some(VENUE_ADDRESS.to_string()),
+        location: Some("Tulsa, OK".to_string()),
+        category: Some("music".to_string()),
+        price_min: price.and_then(|p| p.price_min),
+        price_max: price.and_then(|p| p.price_max),
+        is_free: price.map(|p| p.is_free),
+        image_url,
+        end_time,
+        ..ScrapedEvent::new(&title, source_url.as_str(), start_time)
+    })
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("valid selector")
+}
+
+/// The whitespace-collapsed text of the first match, if not blank.
+fn text(card: ElementRef, css: &str) -> Option<String> {
+    let element = card.select(&selector(css)).next()?;
+    let text = element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+fn href(card: ElementRef, css: &str) -> Option<String> {
+    card.select(&selector(css)).next()?.value().attr("href").map(str::to_string)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    const CALENDAR: &str = include_str!("../../../tests/fixtures/cains_ballroom/calendar.html");
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_the_calendar_fixture() {
+        let events = parse_events(CALENDAR, BASE_URL, date(2026, 12, 28)).unwrap();
+
+        // "Date TBD" is skipped
+        let titles: Vec<&str> = events.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Red Dirt Revival", "New Year's Eve Bash", "Open Jam", "The Midnight Ramblers"]);
+
+        let revival = &events[0];
+        assert_eq!(revival.source_url, "https://www.cainsballroom.com/events/red-dirt-revival/");
+        assert_eq!(revival.start_time, utc("2026-12-31T02:00:00Z"));
+        assert_eq!(revival.description.as_deref(), Some("With Jason Boland & The Stragglers"));
+        assert_eq!((revival.price_min, revival.price_max, revival.is_free), (Some(35.0), Some(55.0), Some(false)));
+        assert_eq!(revival.venue.as_deref(), Some("Cain's Ballroom"));
+        assert_eq!(revival.category.as_deref(), Some("music"));
+        assert_eq!(revival.image_url.as_deref(), Some("https://www.cainsballroom.com/wp-content/uploads/red-dirt.jpg"));
+
+        // Behind a lazy-loading placeholder, protocol-relative and tracked
+        assert_eq!(events[1].image_url.as_deref(), Some("https://cdn.cainsballroom.com/uploads/nye-2026.png"));
+        // Sizes stay, tracking goes
+        assert_eq!(events[2].image_url.as_deref(), Some("https://www.cainsballroom.com/wp-content/uploads/open-jam.jpg?w=640"));
+
+        assert_eq!(events[1].start_time, utc("2027-01-01T03:00:00Z"));
+        assert_eq!(events[2].is_free, Some(true));
+        assert_eq!(events[2].source_url, "https://www.cainsballroom.com/events/open-jam-january/");
+
+        // No page of its own: the ticket link stands in; "TBA" has no price
+        let ramblers = &events[3];
+        assert_eq!(ramblers.source_url, "https://www.ticketmaster.com/event/0C006130");
+        assert_eq!(ramblers.start_time, utc("2027-01-18T01:30:00Z"));
+        assert_eq!((ramblers.price_min, ramblers.is_free), (None, None));
+        assert_eq!(ramblers.image_url, None);
+    }
+
+    #[test]
+    fn years_roll_over_both_ways() {
+        let parse_date = |text: &str, today| parse_event_when(text, today).ok().map(|when| when.date);
+        // December page in late December: January shows are next year
+        assert_eq!(parse_date("Fri, Jan 9", date(2026, 12, 28)), Some(date(2027, 1, 9)));
+        // Same page still up in early January: December shows just happened
+        assert_eq!(parse_date("Wed, Dec 30", date(2027, 1, 3)), Some(date(2026, 12, 30)));
+        assert_eq!(parse_date("Saturday, January 17th", date(2027, 1, 3)), Some(date(2027, 1, 17)));
+        assert_eq!(parse_date("Sept 5", date(2026, 8, 1)), Some(date(2026, 9, 5)));
+        assert_eq!(parse_date("Date TBD", date(2026, 8, 1)), None);
+        assert_eq!(parse_date("Feb 30", date(2026, 8, 1)), None);
+    }
+
+    #[test]
+    fn show_times_win_over_doors() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        let cases = [
+            ("Doors 7:00 PM / Show 8:00 PM", time(20, 0)),
+            ("Doors 8pm | Show 9pm", time(21, 0)),
+            ("Show 7:30 PM", time(19, 30)),
+            ("8:00", time(20, 0)),
+            ("All ages, 21+ to drink", None),
+        ];
+        for (text, expected) in cases {
+            let when = parse_event_when(&format!("Wed, Dec 30 {}", text), date(2026, 12, 1)).unwrap();
+            assert_eq!(when.start, expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn a_page_without_cards_is_a_parse_error() {
+        let error = parse_events("<html><body>Maintenance</body></html>", BASE_URL, date(2026, 1, 1)).unwrap_err();
+        assert!(matches!(error, ScraperError::Parse { ref selector, .. } if selector == selectors::CARD));
+    }
+
+    #[test]
+    fn a_single_show_page_is_read_from_its_meta_tags() {
+        let html = r#"<html><head>
+            <meta property="og:type" content="event">
+            <meta property="og:title" content="Turnpike Troubadours">
+            <meta property="og:url" content="https://www.cainsballroom.com/events/turnpike-troubadours/">
+            <meta property="event:start_time" content="2026-02-14T20:00:00-06:00">
+            </head><body><h1>Turnpike Troubadours</h1></body></html>"#;
+        let events = parse_events(html, BASE_URL, date(2026, 1, 1)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source_url, "https://www.cainsballroom.com/events/turnpike-troubadours/");
+        assert_eq!(events[0].start_time, utc("2026-02-15T02:00:00Z"));
+        assert_eq!(events[0].venue.as_deref(), Some(VENUE));
+    }
+}
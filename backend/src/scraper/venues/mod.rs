@@ -0,0 +1,9 @@
+//! # Venue Scrapers
+//!
+//! One scraper per venue website, each parsing that site's own markup.
+//!
+//! ## Owner
+//! Skylar (Data Engineer)
+
+/// Cain's Ballroom's server-rendered calendar.
+pub mod cains_ballroom;
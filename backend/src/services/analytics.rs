@@ -0,0 +1,294 @@
+//! # Analytics Service
+//!
+//! Turns raw user interactions into popularity signals.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Interaction Weights
+//! Not all interactions mean the same thing. Attending says far more than a
+//! click, and a dismissal is a negative signal:
+//!
+//! | Interaction | Weight |
+//! |-------------|--------|
+//! | attend / attended | +5 |
+//! | save / saved | +3 |
+//! | view / clicked | +1 |
+//! | dismiss / dismissed | -2 |
+//!
+//! Both the short and past-tense spellings are accepted because clients
+//! have sent both. Anything else scores 0.
+//!
+//! `INTERACTION_WEIGHTS` is the single source of truth. Use `weight_for` in
+//! Rust and `weight_sql` inside queries so the two never drift apart.
+//!
+//! ## Per-Event Stats
+//! `build_event_stats` turns per-type, per-day counts into `EventStats`
+//! with every type and day filled in.
+//!
+//! ## Per-User Stats
+//! `weekly_streak` counts consecutive active weeks for `UserStats`. Weeks
+//! start on Monday, like Postgres `date_trunc('week', ...)`.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use uuid::Uuid;
+
+use crate::models::{DailyInteractionCounts, EventStats, InteractionCounts};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Score contributed by each interaction type.
+pub const INTERACTION_WEIGHTS: &[(&str, i32)] = &[
+    ("attend", 5),
+    ("attended", 5),
+    ("save", 3),
+    ("saved", 3),
+    ("view", 1),
+    ("clicked", 1),
+    ("dismiss", -2),
+    ("dismissed", -2),
+];
+
+/// Spellings stored for the same kind of interaction. Older clients send
+/// the past tense, newer ones the bare verb.
+pub const INTERACTION_SPELLINGS: &[&[&str]] = &[
+    &["view", "clicked"],
+    &["save", "saved"],
+    &["attend", "attended"],
+    &["dismiss", "dismissed"],
+];
+
+/// Default minutes within which repeat views of the same event are folded
+/// into one row (`VIEW_DEDUPE_MINUTES` overrides).
+pub const DEFAULT_VIEW_DEDUPE_MINUTES: u64 = 30;
+
+/// How far back interactions count toward trending.
+pub const TRENDING_WINDOW_DAYS: i32 = 7;
+
+/// Number of days in the per-event daily breakdown (including today).
+pub const STATS_WINDOW_DAYS: i64 = 14;
+
+/// Number of categories in a user's `top_categories`.
+pub const TOP_CATEGORIES_LIMIT: i64 = 5;
+
+// =============================================================================
+// SCORING
+// =============================================================================
+
+/// Weight of a single interaction type (0 for unknown types).
+pub fn weight_for(interaction_type: &str) -> i32 {
+    INTERACTION_WEIGHTS
+        .iter()
+        .find(|(name, _)| *name == interaction_type)
+        .map_or(0, |(_, weight)| *weight)
+}
+
+/// SQL `CASE` expression mapping an interaction type column to its weight.
+///
+/// ```text
+/// weight_sql("ui.interaction_type")
+/// -> CASE ui.interaction_type WHEN 'attend' THEN 5 ... ELSE 0 END
+/// ```
+pub fn weight_sql(column: &str) -> String {
+    let arms: Vec<String> = INTERACTION_WEIGHTS
+        .iter()
+        .map(|(name, weight)| format!("WHEN '{}' THEN {}", name, weight))
+        .collect();
+
+    format!("CASE {} {} ELSE 0 END", column, arms.join(" "))
+}
+
+/// Every stored spelling of `interaction_type` (`"save"` -> save, saved).
+/// Unknown types only match themselves.
+pub fn spellings_of(interaction_type: &str) -> Vec<String> {
+    INTERACTION_SPELLINGS
+        .iter()
+        .find(|group| group.contains(&interaction_type))
+        .map(|group| group.iter().map(|s| s.to_string()).collect())
+        .unwrap_or_else(|| vec![interaction_type.to_string()])
+}
+
+/// `spellings_of` as a SQL array literal (`ARRAY['save', 'saved']`), for
+/// queries built as one string with no bind parameters (`search::search_sql`).
+/// Pass a known type: the spellings are constants, an unknown type isn't.
+pub fn spellings_sql(interaction_type: &str) -> String {
+    let quoted: Vec<String> = spellings_of(interaction_type)
+        .iter()
+        .map(|spelling| format!("'{}'", spelling.replace('\'', "''")))
+        .collect();
+    format!("ARRAY[{}]", quoted.join(", "))
+}
+
+/// True for both spellings of a view.
+pub fn is_view(interaction_type: &str) -> bool {
+    INTERACTION_SPELLINGS[0].contains(&interaction_type)
+}
+
+// =============================================================================
+// PER-EVENT STATS
+// =============================================================================
+
+/// Adds `count` interactions of `interaction_type` to the matching total.
+pub fn tally(counts: &mut InteractionCounts, interaction_type: &str, count: i64) {
+    match interaction_type {
+        "view" | "clicked" => counts.views += count,
+        "save" | "saved" => counts.saves += count,
+        "attend" | "attended" => counts.attends += count,
+        "dismiss" | "dismissed" => counts.dismisses += count,
+        _ => {}
+    }
+}
+
+/// Builds stats from `(interaction_type, local day, count)` rows.
+///
+/// `totals` covers every row; `daily` covers the `STATS_WINDOW_DAYS` days
+/// ending on `today`, oldest first, with zero-filled days.
+pub fn build_event_stats(
+    event_id: Uuid,
+    rows: &[(String, NaiveDate, i64)],
+    today: NaiveDate,
+) -> EventStats {
+    let first_day = today - Duration::days(STATS_WINDOW_DAYS - 1);
+
+    let mut totals = InteractionCounts::default();
+    let mut daily: Vec<DailyInteractionCounts> = (0..STATS_WINDOW_DAYS)
+        .map(|offset| DailyInteractionCounts {
+            date: first_day + Duration::days(offset),
+            counts: InteractionCounts::default(),
+        })
+        .collect();
+
+    for (interaction_type, day, count) in rows {
+        tally(&mut totals, interaction_type, *count);
+
+        if (first_day..=today).contains(day) {
+            let index = (*day - first_day).num_days() as usize;
+            tally(&mut daily[index].counts, interaction_type, *count);
+        }
+    }
+
+    EventStats {
+        event_id,
+        totals,
+        daily,
+    }
+}
+
+// =============================================================================
+// PER-USER STATS
+// =============================================================================
+
+/// Monday of the week containing `day`.
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(i64::from(day.weekday().num_days_from_monday()))
+}
+
+/// Number of consecutive active weeks ending at the current week.
+///
+/// `active_weeks` holds the Monday of every week with activity, in any
+/// order. A week with no activity yet doesn't break the streak until it's
+/// over, so the count may end at last week instead.
+pub fn weekly_streak(active_weeks: &[NaiveDate], today: NaiveDate) -> i64 {
+    let mut week = week_start(today);
+    if !active_weeks.contains(&week) {
+        week -= Duration::weeks(1);
+    }
+
+    let mut streak = 0;
+    while active_weeks.contains(&week) {
+        streak += 1;
+        week -= Duration::weeks(1);
+    }
+    streak
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_cover_both_forms() {
+        assert_eq!(spellings_of("saved"), ["save", "saved"]);
+        assert_eq!(spellings_of("clicked"), ["view", "clicked"]);
+        assert_eq!(spellings_of("shared"), ["shared"]);
+        assert!(is_view("clicked") && is_view("view") && !is_view("save"));
+        assert_eq!(spellings_sql("dismiss"), "ARRAY['dismiss', 'dismissed']");
+    }
+
+    #[test]
+    fn weights_match_the_documented_scale() {
+        assert_eq!(weight_for("attended"), 5);
+        assert_eq!(weight_for("save"), 3);
+        assert_eq!(weight_for("saved"), 3);
+        assert_eq!(weight_for("clicked"), 1);
+        assert_eq!(weight_for("dismissed"), -2);
+        assert_eq!(weight_for("shared"), 0);
+    }
+
+    #[test]
+    fn sql_case_covers_every_weight() {
+        let sql = weight_sql("interaction_type");
+
+        assert!(sql.starts_with("CASE interaction_type "));
+        assert!(sql.ends_with(" ELSE 0 END"));
+        for (name, weight) in INTERACTION_WEIGHTS {
+            assert!(sql.contains(&format!("WHEN '{}' THEN {}", name, weight)));
+        }
+    }
+
+    #[test]
+    fn streak_counts_back_from_this_or_last_week() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Wednesday; its week starts Monday the 12th
+        let today = day(2026, 1, 14);
+        let this_week = day(2026, 1, 12);
+        let last_week = day(2026, 1, 5);
+        let two_weeks_ago = day(2025, 12, 29);
+
+        assert_eq!(week_start(today), this_week);
+        assert_eq!(weekly_streak(&[], today), 0);
+        assert_eq!(weekly_streak(&[two_weeks_ago, this_week, last_week], today), 3);
+        // nothing yet this week: last week's streak still counts
+        assert_eq!(weekly_streak(&[last_week, two_weeks_ago], today), 2);
+        // a gap ends the streak
+        assert_eq!(weekly_streak(&[this_week, two_weeks_ago], today), 1);
+        assert_eq!(weekly_streak(&[two_weeks_ago], today), 0);
+    }
+
+    #[test]
+    fn stats_fill_missing_types_and_days() {
+        let today = NaiveDate::from_ymd_opt(2026, 1, 17).unwrap();
+        let yesterday = today - Duration::days(1);
+        let last_month = today - Duration::days(30);
+        let rows = vec![
+            ("view".to_string(), today, 5),
+            ("clicked".to_string(), today, 2),
+            ("saved".to_string(), yesterday, 1),
+            ("attend".to_string(), last_month, 4),
+            ("shared".to_string(), today, 9),
+        ];
+
+        let stats = build_event_stats(Uuid::nil(), &rows, today);
+
+        assert_eq!(
+            stats.totals,
+            InteractionCounts { views: 7, saves: 1, attends: 4, dismisses: 0 }
+        );
+        assert_eq!(stats.daily.len(), STATS_WINDOW_DAYS as usize);
+        assert_eq!(stats.daily[0].date, today - Duration::days(13));
+
+        let last = &stats.daily[13];
+        assert_eq!(last.date, today);
+        assert_eq!(last.counts, InteractionCounts { views: 7, ..Default::default() });
+        assert_eq!(stats.daily[12].counts.saves, 1);
+
+        // Outside the window: counted in totals only
+        assert!(stats.daily.iter().all(|day| day.counts.attends == 0));
+    }
+}
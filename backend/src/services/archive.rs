@@ -0,0 +1,90 @@
+//! # Event Archiving
+//!
+//! Soft-archives events that ended a while ago so list and search queries
+//! stop scanning them. Archived rows are kept: interactions, the user
+//! profile and direct links by id still work.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Environment Variables
+//! ```text
+//! ARCHIVE_AFTER_DAYS=30        # days after an event ends before archiving
+//! ARCHIVE_INTERVAL_MINUTES=60  # how often the job runs
+//! QUARANTINE_KEEP_DAYS=30      # days before a quarantined scrape is purged
+//! ```
+//!
+//! The same job purges the scrape quarantine (`scraper::quarantine`).
+//!
+//! ## Undoing
+//! `PATCH /api/events/:id` with `{"archived": false}` clears `archived_at`.
+//! The job also skips events updated within the last `ARCHIVE_AFTER_DAYS`,
+//! so an unarchived event stays live for that long before it's eligible
+//! again.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use super::scheduler;
+use crate::scraper::quarantine::{self, DEFAULT_QUARANTINE_KEEP_DAYS};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default days after an event ends before it is archived.
+pub const DEFAULT_ARCHIVE_AFTER_DAYS: u64 = 30;
+
+/// Default minutes between archive runs.
+pub const DEFAULT_ARCHIVE_INTERVAL_MINUTES: u64 = 60;
+
+// =============================================================================
+// JOB
+// =============================================================================
+
+/// Stamps `archived_at` on events that ended more than `after_days` ago.
+///
+/// Events without an `end_time` are treated as ending at `start_time`.
+/// Recently updated events (including ones just unarchived) are skipped.
+/// Returns the number of events archived by this run.
+pub async fn archive_ended_events(pool: &PgPool, after_days: u64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE events
+        SET archived_at = NOW()
+        WHERE archived_at IS NULL
+          AND COALESCE(end_time, start_time) < NOW() - make_interval(days => $1)
+          AND updated_at < NOW() - make_interval(days => $1)
+        "#,
+    )
+        .bind(after_days as i32)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Starts the periodic archive job using the environment configuration.
+pub fn spawn_archiver(pool: PgPool) {
+    let after_days = scheduler::env_u64("ARCHIVE_AFTER_DAYS", DEFAULT_ARCHIVE_AFTER_DAYS);
+    let minutes = scheduler::env_u64("ARCHIVE_INTERVAL_MINUTES", DEFAULT_ARCHIVE_INTERVAL_MINUTES);
+    let keep_days = scheduler::env_u64("QUARANTINE_KEEP_DAYS", DEFAULT_QUARANTINE_KEEP_DAYS);
+
+    scheduler::spawn_periodic(
+        "archive",
+        Duration::from_secs(minutes * 60),
+        pool,
+        move |pool| async move {
+            let archived = archive_ended_events(&pool, after_days).await?;
+            if archived > 0 {
+                tracing::info!(archived, after_days, "archived ended events");
+            }
+            let purged = quarantine::purge(&pool, keep_days).await?;
+            if purged > 0 {
+                tracing::info!(purged, keep_days, "purged quarantined scraped events");
+            }
+            Ok::<_, sqlx::Error>(())
+        },
+    );
+}
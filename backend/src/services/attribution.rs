@@ -0,0 +1,213 @@
+//! # Event Source Attribution
+//!
+//! Every place an event is listed (`event_sources`), and which of them is
+//! its primary link (`events.source_url`).
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Listings
+//! An event has a row for each URL it's listed under:
+//! - its own, written with it (`routes::events::insert_event`)
+//! - each one a scrape matches to it (`scraper::persist`), stamped
+//!   `last_seen_at` every time the source lists it again
+//! - every one a merge folds in (`services::merge`)
+//!
+//! `GET /api/events/:id` returns them as `sources`, the primary first.
+//!
+//! ## The Primary Listing
+//! `events.source_url` (and `source_name`) is the listing we link to.
+//! Whenever an event gains a listing, the best one becomes primary:
+//! 1. The venue's own site (the host of its venue's `website`)
+//! 2. Any other listing (the city calendar, a promoter)
+//! 3. An aggregator (`AGGREGATOR_HOSTS`: Eventbrite, Meetup, ...)
+//!
+//! Ties keep the current primary, then go to the listing seen first, so
+//! equals don't take turns.
+
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use sqlx::{PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::models::EventSource;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Sites that list other people's events. A listing on one (or a
+/// subdomain) ranks last.
+pub const AGGREGATOR_HOSTS: &[&str] = &[
+    "allevents.in",
+    "axs.com",
+    "bandsintown.com",
+    "eventbrite.com",
+    "facebook.com",
+    "livenation.com",
+    "meetup.com",
+    "songkick.com",
+    "ticketmaster.com",
+];
+
+// =============================================================================
+// RANKING
+// =============================================================================
+
+/// How good a listing is as the primary link, worst first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SourceRank {
+    Aggregator,
+    Listing,
+    /// The venue's own site
+    Venue,
+}
+
+/// The rank of a listing at `source_url`, for an event at a venue whose
+/// site is `venue_website` (with or without a scheme).
+pub fn rank(source_url: &str, venue_website: Option<&str>) -> SourceRank {
+    let Some(listed) = host(source_url) else {
+        return SourceRank::Listing;
+    };
+    if venue_website.and_then(host).is_some_and(|site| site == listed) {
+        SourceRank::Venue
+    } else if AGGREGATOR_HOSTS
+        .iter()
+        .any(|aggregator| listed == *aggregator || listed.ends_with(&format!(".{}", aggregator)))
+    {
+        SourceRank::Aggregator
+    } else {
+        SourceRank::Listing
+    }
+}
+
+/// A URL's host, lowercased and without `www.`.
+fn host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let parsed = if url.contains("://") { Url::parse(url) } else { Url::parse(&format!("https://{}", url)) }.ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").map(str::to_string).unwrap_or(host))
+}
+
+// =============================================================================
+// QUERIES
+// =============================================================================
+
+/// Records that `event_id` is listed at `source_url` as of `seen_at`.
+///
+/// # Returns
+/// Whether the listing is new to the event (then `choose_primary`).
+pub async fn record(
+    conn: &mut PgConnection,
+    event_id: Uuid,
+    source_url: &str,
+    source_name: Option<&str>,
+    external_id: Option<&str>,
+    seen_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    // xmax is 0 only on a row this statement inserted
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO event_sources (event_id, source_url, source_name, external_id, first_seen_at, last_seen_at)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (event_id, source_url) DO UPDATE
+        SET source_name = COALESCE(EXCLUDED.source_name, event_sources.source_name),
+            external_id = COALESCE(EXCLUDED.external_id, event_sources.external_id),
+            last_seen_at = GREATEST(event_sources.last_seen_at, EXCLUDED.last_seen_at)
+        RETURNING xmax = 0
+        "#,
+    )
+        .bind(event_id)
+        .bind(source_url)
+        .bind(source_name)
+        .bind(external_id)
+        .bind(seen_at)
+        .fetch_one(conn)
+        .await
+}
+
+/// Makes the event's best listing its primary one (see "The Primary
+/// Listing" above). A URL another event has as its own is passed over.
+///
+/// # Returns
+/// Whether the primary changed.
+pub async fn choose_primary(conn: &mut PgConnection, event_id: Uuid) -> Result<bool, sqlx::Error> {
+    let website: Option<String> = sqlx::query_scalar(
+        "SELECT v.website FROM events e JOIN venues v ON v.id = e.venue_id WHERE e.id = $1",
+    )
+        .bind(event_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .flatten();
+
+    let listings: Vec<(String, Option<String>, bool, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT s.source_url, s.source_name, s.source_url = e.source_url, s.first_seen_at
+        FROM event_sources s
+        JOIN events e ON e.id = s.event_id
+        WHERE s.event_id = $1
+          AND NOT EXISTS (SELECT 1 FROM events other WHERE other.source_url = s.source_url AND other.id <> s.event_id)
+        "#,
+    )
+        .bind(event_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let best = listings
+        .into_iter()
+        .min_by_key(|(url, _, current, seen)| (Reverse(rank(url, website.as_deref())), !current, *seen, url.clone()));
+    let Some((url, name, false, _)) = best else {
+        return Ok(false);
+    };
+
+    sqlx::query("UPDATE events SET source_url = $2, source_name = COALESCE($3, source_name) WHERE id = $1")
+        .bind(event_id)
+        .bind(&url)
+        .bind(&name)
+        .execute(&mut *conn)
+        .await?;
+    tracing::info!(event_id = %event_id, source_url = %url, "new primary listing");
+    Ok(true)
+}
+
+/// Every listing of `event_id`, the primary first, then as first seen.
+pub async fn list(executor: impl PgExecutor<'_>, event_id: Uuid) -> Result<Vec<EventSource>, sqlx::Error> {
+    sqlx::query_as::<_, EventSource>(
+        r#"
+        SELECT s.source_name, s.source_url, s.external_id, s.source_url = e.source_url AS "primary",
+               s.first_seen_at, s.last_seen_at
+        FROM event_sources s
+        JOIN events e ON e.id = s.event_id
+        WHERE s.event_id = $1
+        ORDER BY s.source_url = e.source_url DESC, s.first_seen_at, s.source_url
+        "#,
+    )
+        .bind(event_id)
+        .fetch_all(executor)
+        .await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn venue_sites_outrank_listings_and_aggregators() {
+        let cains = Some("cainsballroom.com");
+        assert_eq!(rank("https://www.cainsballroom.com/events/turnpike", cains), SourceRank::Venue);
+        assert_eq!(rank("https://www.cainsballroom.com/events/turnpike", None), SourceRank::Listing);
+        assert_eq!(rank("https://www.cityoftulsa.org/calendar/event/4117", cains), SourceRank::Listing);
+        assert_eq!(rank("https://www.eventbrite.com/e/turnpike-tickets-123", cains), SourceRank::Aggregator);
+        assert_eq!(rank("https://tulsa.eventbrite.com/e/123", None), SourceRank::Aggregator);
+        // Only whole host names count
+        assert_eq!(rank("https://noteventbrite.com/e/123", None), SourceRank::Listing);
+        assert_eq!(rank("not a url", cains), SourceRank::Listing);
+        assert!(SourceRank::Venue > SourceRank::Listing && SourceRank::Listing > SourceRank::Aggregator);
+    }
+}
@@ -0,0 +1,109 @@
+//! # Category Taxonomy
+//!
+//! Which category names a preference may use. Events carry free-form
+//! category tags from scrapers, so the taxonomy is every category on a
+//! live (non-archived) event plus `BASE_CATEGORIES`, which keeps the
+//! common ones valid while the events table is empty or quiet.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Normalization
+//! Names are compared trimmed and lowercased ("Live Music " = "live music"),
+//! and preferences are stored that way.
+
+use std::collections::BTreeSet;
+
+use sqlx::PgConnection;
+
+use crate::db::NOT_ARCHIVED_FILTER;
+use crate::models::FieldError;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Categories that are always valid, whatever events exist right now.
+pub const BASE_CATEGORIES: &[&str] = &[
+    "art",
+    "comedy",
+    "community",
+    "concerts",
+    "dance",
+    "education",
+    "family",
+    "festival",
+    "film",
+    "food",
+    "jazz",
+    "live music",
+    "music",
+    "nightlife",
+    "outdoors",
+    "rock",
+    "sports",
+    "theater",
+];
+
+// =============================================================================
+// TAXONOMY
+// =============================================================================
+
+/// Canonical form of a category name.
+pub fn normalize(category: &str) -> String {
+    category.trim().to_lowercase()
+}
+
+/// Every valid category name, sorted.
+pub async fn known_categories(conn: &mut PgConnection) -> Result<BTreeSet<String>, sqlx::Error> {
+    let from_events: Vec<String> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT DISTINCT LOWER(TRIM(c))
+        FROM events, unnest(categories) AS c
+        WHERE {} AND TRIM(c) <> ''
+        "#,
+        NOT_ARCHIVED_FILTER
+    ))
+        .fetch_all(conn)
+        .await?;
+
+    Ok(BASE_CATEGORIES
+        .iter()
+        .map(|c| c.to_string())
+        .chain(from_events)
+        .collect())
+}
+
+/// `None` if `category` (already normalized) is known, else a 422 entry
+/// for `field` that lists the valid names.
+pub fn check_known(field: &str, category: &str, known: &BTreeSet<String>) -> Option<FieldError> {
+    if known.contains(category) {
+        return None;
+    }
+
+    let valid: Vec<&str> = known.iter().map(String::as_str).collect();
+    Some(FieldError::new(
+        field,
+        format!("unknown category \"{}\"; valid categories: {}", category, valid.join(", ")),
+    ))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_categories_list_the_valid_ones() {
+        let known: BTreeSet<String> = ["jazz", "art"].iter().map(|c| c.to_string()).collect();
+
+        assert!(check_known("category", &normalize(" JAZZ "), &known).is_none());
+
+        let error = check_known("category", "asdfgh", &known).unwrap();
+        assert_eq!(error.field, "category");
+        assert_eq!(error.message, "unknown category \"asdfgh\"; valid categories: art, jazz");
+    }
+}
@@ -0,0 +1,275 @@
+//! # Chat History
+//!
+//! Stored turns of chat sessions. The chat route loads a session's recent
+//! turns into the model's context before each message and appends the new
+//! exchange after it, so follow-ups like "show me more like the second
+//! one" have something to refer to.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Sessions
+//! A session is a UUID the client sends back with each message. Turns are
+//! stored with the signed-in user (or none), and every lookup matches on
+//! both: someone else's session id finds nothing.
+//!
+//! ## Context Budget
+//! At most `HISTORY_TURNS` turns are loaded, then the oldest are dropped
+//! until the rest fit `HISTORY_TOKEN_BUDGET` (`sanitize::estimate_tokens`).
+//! Only text goes to the model; `tool_calls` are kept for the history
+//! endpoint and debugging.
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::ChatMessage;
+use crate::services::llm_provider::{LlmMessage, ToolCall};
+use crate::services::sanitize;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Most earlier turns considered for a message's context.
+pub const HISTORY_TURNS: i64 = 20;
+
+/// Estimated tokens of earlier turns sent with a message.
+pub const HISTORY_TOKEN_BUDGET: usize = 2000;
+
+/// `role` of the user's turns.
+pub const ROLE_USER: &str = "user";
+
+/// `role` of the assistant's turns.
+pub const ROLE_ASSISTANT: &str = "assistant";
+
+const MESSAGE_COLUMNS: &str = "id, session_id, role, content, tool_calls, created_at";
+
+// =============================================================================
+// READING
+// =============================================================================
+
+/// Every turn of `session_id` that belongs to `user_id`, oldest first.
+pub async fn load(pool: &PgPool, session_id: Uuid, user_id: Option<Uuid>) -> Result<Vec<ChatMessage>, sqlx::Error> {
+    sqlx::query_as::<_, ChatMessage>(&format!(
+        r#"
+        SELECT {}
+        FROM chat_messages
+        WHERE session_id = $1 AND user_id IS NOT DISTINCT FROM $2
+        ORDER BY created_at
+        "#,
+        MESSAGE_COLUMNS
+    ))
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// The session's recent turns as model messages, trimmed to the budget.
+pub async fn context(pool: &PgPool, session_id: Uuid, user_id: Option<Uuid>) -> Result<Vec<LlmMessage>, sqlx::Error> {
+    let mut recent = sqlx::query_as::<_, ChatMessage>(&format!(
+        r#"
+        SELECT {}
+        FROM chat_messages
+        WHERE session_id = $1 AND user_id IS NOT DISTINCT FROM $2
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+        MESSAGE_COLUMNS
+    ))
+        .bind(session_id)
+        .bind(user_id)
+        .bind(HISTORY_TURNS)
+        .fetch_all(pool)
+        .await?;
+    recent.reverse();
+
+    Ok(fit_to_budget(recent, HISTORY_TOKEN_BUDGET)
+        .into_iter()
+        .map(|turn| match turn.role.as_str() {
+            ROLE_ASSISTANT => LlmMessage::model(turn.content),
+            _ => LlmMessage::user(turn.content),
+        })
+        .collect())
+}
+
+/// The newest of `turns` (oldest first) whose estimated tokens fit
+/// `budget`. The result starts with a user turn, as providers expect.
+fn fit_to_budget(turns: Vec<ChatMessage>, budget: usize) -> Vec<ChatMessage> {
+    let mut used = 0;
+    let keep = turns
+        .iter()
+        .rev()
+        .take_while(|turn| {
+            used += sanitize::estimate_tokens(&turn.content);
+            used <= budget
+        })
+        .count();
+
+    let dropped = turns.len() - keep;
+    turns
+        .into_iter()
+        .skip(dropped)
+        .skip_while(|turn| turn.role != ROLE_USER)
+        .collect()
+}
+
+// =============================================================================
+// WRITING
+// =============================================================================
+
+/// One question and its answer, as `append` stores them.
+pub struct Exchange<'a> {
+    /// When the user's message arrived
+    pub asked_at: DateTime<Utc>,
+    pub message: &'a str,
+    pub reply: &'a str,
+    /// Tools the model ran for the reply
+    pub tool_calls: &'a [ToolCall],
+    /// `llm::format_profile_for_llm` of the user, if signed in
+    pub profile_snapshot: Option<&'a str>,
+}
+
+/// Stores one exchange in the session; returns the id of the reply's row.
+pub async fn append(
+    pool: &PgPool,
+    session_id: Uuid,
+    user_id: Option<Uuid>,
+    exchange: Exchange<'_>,
+) -> Result<Uuid, sqlx::Error> {
+    let tool_calls: Option<Value> = (!exchange.tool_calls.is_empty()).then(|| {
+        exchange
+            .tool_calls
+            .iter()
+            .map(|call| json!({ "name": call.name, "args": call.args }))
+            .collect()
+    });
+    let reply_id = Uuid::new_v4();
+
+    sqlx::query(
+        r#"
+        INSERT INTO chat_messages (id, session_id, user_id, role, content, tool_calls, profile_snapshot, created_at)
+        VALUES (gen_random_uuid(), $1, $2, $3, $4, NULL, NULL, $5),
+               ($6, $1, $2, $7, $8, $9, $10, $11)
+        "#,
+    )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(ROLE_USER)
+        .bind(exchange.message)
+        .bind(exchange.asked_at)
+        .bind(reply_id)
+        .bind(ROLE_ASSISTANT)
+        .bind(exchange.reply)
+        .bind(tool_calls)
+        .bind(exchange.profile_snapshot)
+        // After the question even when the clock hasn't moved
+        .bind(Utc::now().max(exchange.asked_at + chrono::Duration::microseconds(1)))
+        .execute(pool)
+        .await?;
+
+    Ok(reply_id)
+}
+
+/// Deletes the session's turns that belong to `user_id`; returns how many.
+pub async fn clear(pool: &PgPool, session_id: Uuid, user_id: Option<Uuid>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM chat_messages WHERE session_id = $1 AND user_id IS NOT DISTINCT FROM $2")
+        .bind(session_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            id: Uuid::new_v4(),
+            session_id: Uuid::nil(),
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn oldest_turns_go_first_when_over_budget() {
+        let turns = vec![
+            turn(ROLE_USER, &"a".repeat(400)),
+            turn(ROLE_ASSISTANT, &"b".repeat(400)),
+            turn(ROLE_USER, &"c".repeat(40)),
+            turn(ROLE_ASSISTANT, &"d".repeat(40)),
+        ];
+
+        let contents = |kept: Vec<ChatMessage>| kept.iter().map(|t| t.content.chars().next().unwrap()).collect::<String>();
+        assert_eq!(contents(fit_to_budget(turns.clone(), 1000)), "abcd");
+        assert_eq!(contents(fit_to_budget(turns.clone(), 30)), "cd");
+        // "b" fits but would open the context with an assistant turn
+        assert_eq!(contents(fit_to_budget(turns.clone(), 150)), "cd");
+        assert_eq!(contents(fit_to_budget(turns, 5)), "");
+    }
+
+    #[tokio::test]
+    async fn sessions_are_stored_and_scoped_to_their_user() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let session = Uuid::new_v4();
+        let search = ToolCall { id: None, name: "search_events".to_string(), args: json!({ "query": "jazz" }) };
+        let exchange = |message, reply, tool_calls| Exchange {
+            asked_at: Utc::now(),
+            message,
+            reply,
+            tool_calls,
+            profile_snapshot: None,
+        };
+        let searches = [search];
+        append(&pool, session, None, exchange("any jazz?", "Two shows.", &searches)).await.unwrap();
+        let second = append(&pool, session, None, exchange("the second one?", "It's at 8.", &[])).await.unwrap();
+
+        let stored = load(&pool, session, None).await.unwrap();
+        let roles: Vec<&str> = stored.iter().map(|t| t.role.as_str()).collect();
+        assert_eq!(roles, [ROLE_USER, ROLE_ASSISTANT, ROLE_USER, ROLE_ASSISTANT]);
+        assert_eq!(stored[1].tool_calls, Some(json!([{ "name": "search_events", "args": { "query": "jazz" } }])));
+        assert_eq!(stored[3].tool_calls, None);
+        assert_eq!(stored[3].id, second);
+
+        let messages = context(&pool, session, None).await.unwrap();
+        assert_eq!(messages, [
+            LlmMessage::user("any jazz?"),
+            LlmMessage::model("Two shows."),
+            LlmMessage::user("the second one?"),
+            LlmMessage::model("It's at 8."),
+        ]);
+
+        // A signed-in user doesn't see an anonymous session, or clear it
+        let user: Uuid = sqlx::query_scalar("INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id")
+            .bind(format!("{}@example.com", session))
+            .bind(session.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(load(&pool, session, Some(user)).await.unwrap().is_empty());
+        assert_eq!(clear(&pool, session, Some(user)).await.unwrap(), 0);
+
+        assert_eq!(clear(&pool, session, None).await.unwrap(), 4);
+        assert!(context(&pool, session, None).await.unwrap().is_empty());
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+    }
+}
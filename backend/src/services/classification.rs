@@ -0,0 +1,269 @@
+//! # Category Classification
+//!
+//! Gives uncategorized events a category. Scraped listings often arrive
+//! without one, which hides them from category filters and preference
+//! scoring; this job asks the model (`llm::classify_event_category`) for
+//! one per event.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Environment Variables
+//! ```text
+//! CATEGORY_CLASSIFICATION_INTERVAL_MINUTES=60  # how often the job runs
+//! ```
+//!
+//! ## Rules
+//! - Only events with no categories (NULL or empty) are touched, so a
+//!   category from the source or an editor is never overwritten
+//! - Results are written as a one-element array with
+//!   `category_source = 'llm'`; editing an event's categories clears it
+//! - Answers are cached in `category_classifications` by an md5 of title
+//!   and description, so a re-scraped listing costs nothing
+//! - At most `CLASSIFY_BATCH_SIZE` events per run, newest first
+//! - If the model is unavailable the run stops; other failures skip the
+//!   event until the next run
+
+use std::time::Duration;
+
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::scheduler;
+use crate::models::ClassificationReport;
+use crate::services::llm::{self, LlmError};
+use crate::services::llm_provider::SharedProvider;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Most events classified per run.
+pub const CLASSIFY_BATCH_SIZE: i64 = 100;
+
+/// Default minutes between classification runs.
+pub const DEFAULT_CLASSIFICATION_INTERVAL_MINUTES: u64 = 60;
+
+/// `events.category_source` of categories the model chose.
+pub const CATEGORY_SOURCE_LLM: &str = "llm";
+
+// =============================================================================
+// JOB
+// =============================================================================
+
+#[derive(Debug, FromRow)]
+struct Uncategorized {
+    id: Uuid,
+    title: String,
+    description: Option<String>,
+    content_hash: String,
+    cached: Option<String>,
+}
+
+/// Classifies up to `CLASSIFY_BATCH_SIZE` uncategorized events.
+pub async fn classify_uncategorized(pool: &PgPool, provider: &SharedProvider) -> Result<ClassificationReport, LlmError> {
+    let events = sqlx::query_as::<_, Uncategorized>(
+        r#"
+        SELECT u.id, u.title, u.description, u.content_hash, c.category AS cached
+        FROM (
+            SELECT id, title, description, created_at,
+                   md5(title || E'\n' || COALESCE(description, '')) AS content_hash
+            FROM events
+            WHERE (categories IS NULL OR cardinality(categories) = 0)
+              AND archived_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT $1
+        ) u
+        LEFT JOIN category_classifications c USING (content_hash)
+        ORDER BY u.created_at DESC
+        "#,
+    )
+        .bind(CLASSIFY_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+    let mut report = ClassificationReport { examined: events.len(), ..Default::default() };
+
+    for event in events {
+        let category = match event.cached {
+            Some(category) => {
+                report.cached += 1;
+                category
+            }
+            None => match llm::classify_event_category(provider.as_ref(), pool, &event.title, event.description.as_deref()).await {
+                Ok(category) => {
+                    remember(pool, &event.content_hash, &category, provider.model()).await?;
+                    category
+                }
+                Err(e) if e.is_unavailable() => return Err(e),
+                Err(e) => {
+                    tracing::warn!(event_id = %event.id, error = %e, "could not classify event");
+                    report.failed += 1;
+                    continue;
+                }
+            },
+        };
+
+        // Re-checked here: an editor may have categorized it meanwhile
+        report.classified += sqlx::query(
+            r#"
+            UPDATE events
+            SET categories = ARRAY[$2], category_source = $3, updated_at = NOW()
+            WHERE id = $1 AND (categories IS NULL OR cardinality(categories) = 0)
+            "#,
+        )
+            .bind(event.id)
+            .bind(&category)
+            .bind(CATEGORY_SOURCE_LLM)
+            .execute(pool)
+            .await?
+            .rows_affected();
+    }
+
+    Ok(report)
+}
+
+/// Caches the category for content with this hash.
+async fn remember(pool: &PgPool, content_hash: &str, category: &str, model: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO category_classifications (content_hash, category, model)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (content_hash) DO NOTHING
+        "#,
+    )
+        .bind(content_hash)
+        .bind(category)
+        .bind(model)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Starts the periodic classification job using the environment
+/// configuration.
+pub fn spawn_classifier(pool: PgPool, provider: SharedProvider) {
+    let minutes = scheduler::env_u64(
+        "CATEGORY_CLASSIFICATION_INTERVAL_MINUTES",
+        DEFAULT_CLASSIFICATION_INTERVAL_MINUTES,
+    );
+
+    scheduler::spawn_periodic(
+        "category_classification",
+        Duration::from_secs(minutes * 60),
+        pool,
+        move |pool| {
+            let provider = provider.clone();
+            async move {
+                let report = classify_uncategorized(&pool, &provider).await?;
+                if report.examined > 0 {
+                    tracing::info!(
+                        examined = report.examined,
+                        classified = report.classified,
+                        cached = report.cached,
+                        failed = report.failed,
+                        "classified events"
+                    );
+                }
+                Ok::<_, LlmError>(())
+            }
+        },
+    );
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::services::llm_provider::{LlmResponse, MockProvider};
+
+    async fn insert_event(pool: &PgPool, title: &str, categories: Option<Vec<&str>>) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO events (title, description, source_url, start_time, categories) \
+             VALUES ($1, 'Doors at 7.', $2, NOW() + INTERVAL '1 day', $3) RETURNING id",
+        )
+            .bind(title)
+            .bind(format!("https://venue.example/{}", Uuid::new_v4()))
+            .bind(categories)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn category_of(pool: &PgPool, id: Uuid) -> (Option<Vec<String>>, Option<String>) {
+        sqlx::query_as("SELECT categories, category_source FROM events WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn uncategorized_events_are_classified_once_per_listing() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4().simple().to_string();
+        let quartet = format!("Quartet {}", run);
+        let pottery = format!("Pottery Wheel {}", run);
+        let mystery = format!("Mystery {}", run);
+
+        // Answers for this test's listings only; anything else other tests
+        // left uncategorized fails and is skipped
+        let fixtures = [(quartet.clone(), "Jazz"), (pottery.clone(), "art."), (mystery.clone(), "astrology")];
+        let model: SharedProvider = Arc::new(MockProvider::new(move |messages, _| {
+            let listing = messages[1].text();
+            fixtures
+                .iter()
+                .find(|(title, _)| listing.contains(title.as_str()))
+                .map(|(_, answer)| LlmResponse::text(*answer))
+                .ok_or_else(|| LlmError::EmptyResponse("not a fixture".to_string()))
+        }));
+
+        let jazz = insert_event(&pool, &quartet, None).await;
+        let art = insert_event(&pool, &pottery, Some(vec![])).await;
+        let other = insert_event(&pool, &mystery, None).await;
+        let manual = insert_event(&pool, &format!("Quartet {} (edited)", run), Some(vec!["concerts"])).await;
+
+        let report = classify_uncategorized(&pool, &model).await.unwrap();
+        assert!(report.classified >= 3);
+        assert_eq!(category_of(&pool, jazz).await, (Some(vec!["jazz".to_string()]), Some("llm".to_string())));
+        assert_eq!(category_of(&pool, art).await, (Some(vec!["art".to_string()]), Some("llm".to_string())));
+        assert_eq!(category_of(&pool, other).await.0, Some(vec!["other".to_string()]));
+        assert_eq!(category_of(&pool, manual).await, (Some(vec!["concerts".to_string()]), None));
+
+        // The same listing scraped again is answered from the cache; the
+        // model would refuse
+        let rescraped = insert_event(&pool, &quartet, None).await;
+        let refusing: SharedProvider = Arc::new(MockProvider::new(|_, _| Err(LlmError::EmptyResponse("no".to_string()))));
+        let report = classify_uncategorized(&pool, &refusing).await.unwrap();
+        assert!(report.cached >= 1);
+        assert_eq!(category_of(&pool, rescraped).await.0, Some(vec!["jazz".to_string()]));
+
+        // An unavailable model stops the run
+        let offline: SharedProvider = Arc::new(MockProvider::new(|_, _| Err(LlmError::ServiceUnavailable)));
+        let fresh = insert_event(&pool, &format!("Unseen {}", run), None).await;
+        assert!(matches!(classify_uncategorized(&pool, &offline).await, Err(LlmError::ServiceUnavailable)));
+
+        for id in [jazz, art, other, manual, rescraped, fresh] {
+            sqlx::query("DELETE FROM events WHERE id = $1").bind(id).execute(&pool).await.unwrap();
+        }
+        for title in [&quartet, &pottery, &mystery] {
+            sqlx::query("DELETE FROM category_classifications WHERE content_hash = md5($1 || E'\\n' || 'Doors at 7.')")
+                .bind(title)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! # Local Date/Time Helpers
+//!
+//! Timestamps are stored in UTC, but "tonight" and "which day is this event
+//! on" only make sense in Tulsa local time. This module converts local-time windows into
+//! UTC ranges that can be used in queries.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Environment Variables
+//! ```text
+//! LOCAL_TIMEZONE=America/Chicago   # IANA name, default America/Chicago
+//! ```
+//!
+//! ## Relative Dates
+//! `resolve_relative` turns phrases like "tonight", "this weekend" or
+//! "friday" into concrete local ranges. The LLM passes the phrase through
+//! and this code does the calendar math: models get month boundaries
+//! wrong and don't know what time it is in Tulsa.
+//!
+//! ## Why Not Just Subtract 6 Hours?
+//! Tulsa is UTC-6 in winter and UTC-5 in summer. chrono-tz knows the DST
+//! rules, so 5 PM local is always 5 PM local regardless of the date.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use std::env;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Timezone used when `LOCAL_TIMEZONE` is unset or invalid.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::America::Chicago;
+
+/// Local hour when "tonight" begins (5 PM).
+pub const EVENING_START_HOUR: u32 = 17;
+
+/// Local hour the next morning when "tonight" ends (4 AM).
+pub const NIGHT_END_HOUR: u32 = 4;
+
+/// The phrases `resolve_relative` understands, for prompts and errors.
+pub const RELATIVE_PHRASES: &str = "today, tonight, tomorrow, tomorrow night, this weekend, next weekend, \
+    this week, next week, this month, next month, friday, next friday, friday night";
+
+/// Reads the local timezone from `LOCAL_TIMEZONE`, defaulting to Chicago.
+pub fn local_timezone() -> Tz {
+    match env::var("LOCAL_TIMEZONE") {
+        Ok(name) => name.parse().unwrap_or_else(|_| {
+            tracing::warn!("Invalid LOCAL_TIMEZONE '{}', using {}", name, DEFAULT_TIMEZONE);
+            DEFAULT_TIMEZONE
+        }),
+        Err(_) => DEFAULT_TIMEZONE,
+    }
+}
+
+// =============================================================================
+// WINDOWS
+// =============================================================================
+
+/// Converts a local wall-clock date and hour to UTC.
+///
+/// If the local time doesn't exist or is ambiguous (DST transitions), the
+/// earliest valid instant is used.
+pub fn local_to_utc(tz: Tz, date: NaiveDate, hour: u32) -> DateTime<Utc> {
+    let naive = date.and_time(NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or_default());
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| tz.from_utc_datetime(&naive))
+        .with_timezone(&Utc)
+}
+
+/// UTC range `[start, end)` for "tonight": 5 PM to 4 AM local time.
+///
+/// After midnight but before 4 AM it's still the same night, so 1:30 AM on
+/// Saturday returns Friday 5 PM → Saturday 4 AM.
+pub fn tonight_window(now: DateTime<Utc>, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let local = now.with_timezone(&tz);
+    let mut evening = local.date_naive();
+    if local.hour() < NIGHT_END_HOUR {
+        evening -= Duration::days(1);
+    }
+
+    (
+        local_to_utc(tz, evening, EVENING_START_HOUR),
+        local_to_utc(tz, evening + Duration::days(1), NIGHT_END_HOUR),
+    )
+}
+
+// =============================================================================
+// MONTHS
+// =============================================================================
+
+/// Parses a `YYYY-MM` month string into the first day of that month.
+///
+/// Returns `None` for anything else (`"2026-13"`, `"2026-2"`, `"Feb"`).
+pub fn parse_month(month: &str) -> Option<NaiveDate> {
+    let (year, month) = month.split_once('-')?;
+    if year.len() != 4 || month.len() != 2 {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)
+}
+
+/// First day of the month after `first`.
+pub fn next_month(first: NaiveDate) -> NaiveDate {
+    let (year, month) = if first.month() == 12 {
+        (first.year() + 1, 1)
+    } else {
+        (first.year(), first.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(first)
+}
+
+/// UTC range `[start, end)` covering a whole local calendar month.
+pub fn month_window(first: NaiveDate, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    (local_to_utc(tz, first, 0), local_to_utc(tz, next_month(first), 0))
+}
+
+// =============================================================================
+// RELATIVE DATES
+// =============================================================================
+
+/// UTC range `[start, end)` a relative date phrase means at `now`, or
+/// `None` for a phrase it doesn't know.
+///
+/// Case and extra spaces don't matter. Days run midnight to midnight
+/// local time; nights run 5 PM to 4 AM like `tonight_window`; weeks start
+/// on Monday; a weekend is Friday 5 PM to Monday midnight.
+///
+/// "this weekend" asked on a weekend is the one in progress. A weekday
+/// name is the next such day, today included ("friday" on a Friday is
+/// today); "next friday" is the first Friday after today.
+pub fn resolve_relative(phrase: &str, now: DateTime<Utc>, tz: Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let phrase = phrase.trim().to_lowercase();
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+
+    let today = now.with_timezone(&tz).date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let days = |first: NaiveDate, count: i64| (local_to_utc(tz, first, 0), local_to_utc(tz, first + Duration::days(count), 0));
+    let night = |date: NaiveDate| {
+        (
+            local_to_utc(tz, date, EVENING_START_HOUR),
+            local_to_utc(tz, date + Duration::days(1), NIGHT_END_HOUR),
+        )
+    };
+    let weekend = |monday: NaiveDate| {
+        (
+            local_to_utc(tz, monday + Duration::days(4), EVENING_START_HOUR),
+            local_to_utc(tz, monday + Duration::days(7), 0),
+        )
+    };
+    // The next `weekday` on or after `from`
+    let next = |weekday: Weekday, from: NaiveDate| {
+        let ahead = (7 + weekday.num_days_from_monday() as i64 - from.weekday().num_days_from_monday() as i64) % 7;
+        from + Duration::days(ahead)
+    };
+    let weekday = |word: &str| word.parse::<Weekday>().ok();
+
+    match words.as_slice() {
+        ["today"] => Some(days(today, 1)),
+        ["tonight"] | ["this", "evening"] => Some(tonight_window(now, tz)),
+        ["tomorrow"] => Some(days(today + Duration::days(1), 1)),
+        ["tomorrow", "night" | "evening"] => Some(night(today + Duration::days(1))),
+        ["this", "weekend"] | ["weekend"] => Some(weekend(monday)),
+        ["next", "weekend"] => Some(weekend(monday + Duration::days(7))),
+        ["this", "week"] => Some((local_to_utc(tz, today, 0), local_to_utc(tz, monday + Duration::days(7), 0))),
+        ["next", "week"] => Some(days(monday + Duration::days(7), 7)),
+        ["this", "month"] => Some(month_window(today.with_day(1)?, tz)),
+        ["next", "month"] => Some(month_window(next_month(today.with_day(1)?), tz)),
+        [day] | ["this" | "on", day] => Some(days(next(weekday(day)?, today), 1)),
+        ["next", day] => Some(days(next(weekday(day)?, today + Duration::days(1)), 1)),
+        [day, "night" | "evening"] | ["this" | "on", day, "night" | "evening"] => Some(night(next(weekday(day)?, today))),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    const TULSA: Tz = chrono_tz::America::Chicago;
+
+    #[test]
+    fn friday_evening_in_winter() {
+        // Fri 2026-01-23 8:00 PM CST (UTC-6)
+        let (start, end) = tonight_window(utc(2026, 1, 24, 2, 0), TULSA);
+
+        assert_eq!(start, utc(2026, 1, 23, 23, 0)); // Fri 5 PM CST
+        assert_eq!(end, utc(2026, 1, 24, 10, 0)); // Sat 4 AM CST
+    }
+
+    #[test]
+    fn after_midnight_is_still_the_same_night() {
+        // Sat 2026-01-24 1:30 AM CST
+        let (start, end) = tonight_window(utc(2026, 1, 24, 7, 30), TULSA);
+
+        assert_eq!(start, utc(2026, 1, 23, 23, 0));
+        assert_eq!(end, utc(2026, 1, 24, 10, 0));
+    }
+
+    #[test]
+    fn four_am_rolls_over_to_the_next_evening() {
+        // Sat 2026-01-24 4:00 AM CST exactly
+        let (start, end) = tonight_window(utc(2026, 1, 24, 10, 0), TULSA);
+
+        assert_eq!(start, utc(2026, 1, 24, 23, 0)); // Sat 5 PM CST
+        assert_eq!(end, utc(2026, 1, 25, 10, 0));
+    }
+
+    #[test]
+    fn morning_looks_ahead_to_this_evening() {
+        // Sat 2026-01-24 9:00 AM CST
+        let (start, _) = tonight_window(utc(2026, 1, 24, 15, 0), TULSA);
+
+        assert_eq!(start, utc(2026, 1, 24, 23, 0));
+    }
+
+    #[test]
+    fn parses_months() {
+        assert_eq!(parse_month("2026-02"), NaiveDate::from_ymd_opt(2026, 2, 1));
+        assert_eq!(parse_month("2026-13"), None);
+        assert_eq!(parse_month("2026-2"), None);
+        assert_eq!(parse_month("26-02"), None);
+        assert_eq!(parse_month("February"), None);
+    }
+
+    #[test]
+    fn month_window_uses_local_midnight() {
+        let first = NaiveDate::from_ymd_opt(2026, 12, 1).unwrap();
+        let (start, end) = month_window(first, TULSA);
+
+        assert_eq!(start, utc(2026, 12, 1, 6, 0)); // Dec 1 midnight CST
+        assert_eq!(end, utc(2027, 1, 1, 6, 0)); // rolls into next year
+    }
+
+    #[test]
+    fn summer_uses_daylight_time() {
+        // Fri 2026-07-17 8:00 PM CDT (UTC-5)
+        let (start, end) = tonight_window(utc(2026, 7, 18, 1, 0), TULSA);
+
+        assert_eq!(start, utc(2026, 7, 17, 22, 0));
+        assert_eq!(end, utc(2026, 7, 18, 9, 0));
+    }
+
+    /// A Tulsa wall-clock time, in UTC.
+    fn local(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        TULSA.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn resolves_relative_phrases() {
+        // 2026-01-23 is a Friday
+        let friday_evening = local(2026, 1, 23, 19);
+        let wednesday = local(2026, 1, 21, 12);
+        let saturday = local(2026, 1, 24, 11);
+        let sunday = local(2026, 1, 25, 15);
+        let monday = local(2026, 1, 19, 9);
+
+        let cases = [
+            // (phrase, asked at, start, end)
+            ("today", friday_evening, local(2026, 1, 23, 0), local(2026, 1, 24, 0)),
+            ("tonight", friday_evening, local(2026, 1, 23, 17), local(2026, 1, 24, 4)),
+            ("this evening", wednesday, local(2026, 1, 21, 17), local(2026, 1, 22, 4)),
+            ("tomorrow", friday_evening, local(2026, 1, 24, 0), local(2026, 1, 25, 0)),
+            ("tomorrow", local(2026, 1, 31, 10), local(2026, 2, 1, 0), local(2026, 2, 2, 0)),
+            ("tomorrow night", local(2026, 1, 31, 10), local(2026, 2, 1, 17), local(2026, 2, 2, 4)),
+            ("this weekend", wednesday, local(2026, 1, 23, 17), local(2026, 1, 26, 0)),
+            ("this weekend", friday_evening, local(2026, 1, 23, 17), local(2026, 1, 26, 0)),
+            ("weekend", saturday, local(2026, 1, 23, 17), local(2026, 1, 26, 0)),
+            // Asked on Sunday: the weekend in progress, not next one
+            ("this weekend", sunday, local(2026, 1, 23, 17), local(2026, 1, 26, 0)),
+            ("next weekend", sunday, local(2026, 1, 30, 17), local(2026, 2, 2, 0)),
+            // Across a month boundary
+            ("this weekend", local(2026, 1, 29, 12), local(2026, 1, 30, 17), local(2026, 2, 2, 0)),
+            ("this week", wednesday, local(2026, 1, 21, 0), local(2026, 1, 26, 0)),
+            ("next week", sunday, local(2026, 1, 26, 0), local(2026, 2, 2, 0)),
+            ("this month", wednesday, local(2026, 1, 1, 0), local(2026, 2, 1, 0)),
+            ("next month", local(2026, 12, 15, 12), local(2027, 1, 1, 0), local(2027, 2, 1, 0)),
+            // Asked on a Friday evening: today
+            ("Friday", friday_evening, local(2026, 1, 23, 0), local(2026, 1, 24, 0)),
+            ("friday", saturday, local(2026, 1, 30, 0), local(2026, 1, 31, 0)),
+            ("this friday", monday, local(2026, 1, 23, 0), local(2026, 1, 24, 0)),
+            ("on sat", monday, local(2026, 1, 24, 0), local(2026, 1, 25, 0)),
+            ("next friday", friday_evening, local(2026, 1, 30, 0), local(2026, 1, 31, 0)),
+            ("next friday", monday, local(2026, 1, 23, 0), local(2026, 1, 24, 0)),
+            ("saturday night", monday, local(2026, 1, 24, 17), local(2026, 1, 25, 4)),
+            ("  This   WEEKEND ", wednesday, local(2026, 1, 23, 17), local(2026, 1, 26, 0)),
+        ];
+
+        for (phrase, now, start, end) in cases {
+            assert_eq!(resolve_relative(phrase, now, TULSA), Some((start, end)), "{:?} at {}", phrase, now);
+        }
+
+        for unknown in ["", "someday", "next blue moon", "friday morning", "last week"] {
+            assert_eq!(resolve_relative(unknown, wednesday, TULSA), None, "{:?}", unknown);
+        }
+    }
+
+    #[test]
+    fn weekend_spanning_daylight_saving_ends_at_local_midnight() {
+        // DST starts Sun 2026-03-08; asked Thu 2026-03-05
+        let (start, end) = resolve_relative("this weekend", local(2026, 3, 5, 12), TULSA).unwrap();
+
+        assert_eq!(start, utc(2026, 3, 6, 23, 0)); // Fri 5 PM CST
+        assert_eq!(end, utc(2026, 3, 9, 5, 0)); // Mon midnight CDT
+    }
+}
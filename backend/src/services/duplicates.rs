@@ -0,0 +1,369 @@
+//! # Fuzzy Duplicate Detection
+//!
+//! Finds the same show listed by two sources under different titles
+//! ("Jazz Night at The Blue Note" and "Blue Note Jazz Night"), which the
+//! scraper's exact matching (`scraper::persist`) can't see. Runs after
+//! every scrape that stored something.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Environment Variables
+//! ```text
+//! DUPLICATE_MERGE_SIMILARITY=0.8   # at or above: merged automatically
+//! DUPLICATE_REVIEW_SIMILARITY=0.5  # at or above: queued for review
+//! ```
+//!
+//! ## A Pass
+//! 1. Pairs events from different sources at the same venue (normalized
+//!    name) starting within `MAX_START_GAP` of each other, at least one of
+//!    them stored since the scrape began, neither archived
+//! 2. Scores each pair with pg_trgm `similarity` of the normalized titles
+//! 3. Merges the pairs at or above the merge threshold, most alike first,
+//!    into the listing we had first (`merge::merge_events`: nothing is
+//!    lost, and the duplicate's URL is recorded in `event_sources`)
+//! 4. Writes the pairs in the review band to `duplicate_candidates`, for
+//!    `GET /api/admin/duplicates`
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::{Pagination, EVENT_COLUMNS};
+use crate::models::{DuplicateCandidate, DuplicateCandidatePage, DuplicateReport, Event};
+use crate::services::merge::{self, MergeError};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default similarity at which a pair is merged without asking.
+pub const DEFAULT_MERGE_SIMILARITY: f32 = 0.8;
+
+/// Default similarity at which a pair is queued for review.
+pub const DEFAULT_REVIEW_SIMILARITY: f32 = 0.5;
+
+/// How far apart two listings of a show may start.
+pub const MAX_START_GAP: Duration = Duration::hours(2);
+
+/// Where the similarity bands start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DuplicateThresholds {
+    pub merge: f32,
+    pub review: f32,
+}
+
+impl DuplicateThresholds {
+    /// `DUPLICATE_MERGE_SIMILARITY` and `DUPLICATE_REVIEW_SIMILARITY`, or
+    /// the defaults. A review threshold above the merge one is lowered to it.
+    pub fn from_env() -> Self {
+        let merge = env_similarity("DUPLICATE_MERGE_SIMILARITY", DEFAULT_MERGE_SIMILARITY);
+        let review = env_similarity("DUPLICATE_REVIEW_SIMILARITY", DEFAULT_REVIEW_SIMILARITY);
+        Self { merge, review: review.min(merge) }
+    }
+
+    /// What to do with a pair this alike.
+    fn verdict(&self, similarity: f32) -> Option<Verdict> {
+        if similarity >= self.merge {
+            Some(Verdict::Merge)
+        } else if similarity >= self.review {
+            Some(Verdict::Review)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for DuplicateThresholds {
+    fn default() -> Self {
+        Self { merge: DEFAULT_MERGE_SIMILARITY, review: DEFAULT_REVIEW_SIMILARITY }
+    }
+}
+
+/// A similarity (0 to 1) from the environment, or `default`.
+fn env_similarity(key: &str, default: f32) -> f32 {
+    match std::env::var(key) {
+        Ok(raw) => match raw.trim().parse::<f32>() {
+            Ok(value) if (0.0..=1.0).contains(&value) => value,
+            _ => {
+                tracing::warn!("Invalid {} '{}', using {}", key, raw, default);
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Merge,
+    Review,
+}
+
+// =============================================================================
+// DETECTION
+// =============================================================================
+
+#[derive(Debug, FromRow)]
+struct Pair {
+    event_id: Uuid,
+    duplicate_id: Uuid,
+    similarity: f32,
+}
+
+/// Merges or flags similar pairs involving events stored since `since`.
+pub async fn detect_duplicates(
+    pool: &PgPool,
+    since: DateTime<Utc>,
+    thresholds: DuplicateThresholds,
+) -> Result<DuplicateReport, MergeError> {
+    let pairs = sqlx::query_as::<_, Pair>(
+        r#"
+        SELECT a.id AS event_id, b.id AS duplicate_id,
+               similarity(normalize_event_title(a.title), normalize_event_title(b.title)) AS similarity
+        FROM events a
+        JOIN events b
+          ON normalize_venue_name(b.venue) = normalize_venue_name(a.venue)
+         AND b.start_time BETWEEN a.start_time - $2 AND a.start_time + $2
+         AND (b.created_at, b.id) > (a.created_at, a.id)
+         AND b.source_name IS DISTINCT FROM a.source_name
+        WHERE a.venue IS NOT NULL AND b.venue IS NOT NULL
+          AND a.archived_at IS NULL AND b.archived_at IS NULL
+          AND (a.updated_at >= $1 OR b.updated_at >= $1)
+          AND similarity(normalize_event_title(a.title), normalize_event_title(b.title)) >= $3
+        ORDER BY similarity DESC, a.created_at
+        "#,
+    )
+        .bind(since)
+        .bind(MAX_START_GAP)
+        .bind(thresholds.review)
+        .fetch_all(pool)
+        .await?;
+
+    let mut report = DuplicateReport { examined: pairs.len(), ..Default::default() };
+    let mut merged_away = HashSet::new();
+
+    for pair in pairs {
+        // One of them was folded into a closer match already
+        if merged_away.contains(&pair.event_id) || merged_away.contains(&pair.duplicate_id) {
+            continue;
+        }
+
+        match thresholds.verdict(pair.similarity) {
+            Some(Verdict::Merge) => match merge::merge_events(pool, pair.event_id, pair.duplicate_id).await {
+                Ok(merged) => {
+                    tracing::info!(
+                        event = %pair.event_id,
+                        duplicate = %pair.duplicate_id,
+                        similarity = pair.similarity,
+                        url = %merged.merged_source_url,
+                        "merged a duplicate listing"
+                    );
+                    merged_away.insert(pair.duplicate_id);
+                    report.merged += 1;
+                }
+                // Deleted or merged by someone else in the meantime
+                Err(MergeError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            },
+            Some(Verdict::Review) => {
+                report.flagged += sqlx::query(
+                    r#"
+                    INSERT INTO duplicate_candidates (event_id, duplicate_id, similarity)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (event_id, duplicate_id) DO UPDATE SET similarity = EXCLUDED.similarity
+                    "#,
+                )
+                    .bind(pair.event_id)
+                    .bind(pair.duplicate_id)
+                    .bind(pair.similarity)
+                    .execute(pool)
+                    .await?
+                    .rows_affected();
+            }
+            None => {}
+        }
+    }
+
+    Ok(report)
+}
+
+// =============================================================================
+// REVIEW QUEUE
+// =============================================================================
+
+#[derive(Debug, FromRow)]
+struct CandidateRow {
+    id: Uuid,
+    event_id: Uuid,
+    duplicate_id: Uuid,
+    similarity: f32,
+    created_at: DateTime<Utc>,
+}
+
+/// One page of the review queue, most alike first, each pair with both
+/// events in full.
+pub async fn list_candidates(pool: &PgPool, pagination: Pagination) -> Result<DuplicateCandidatePage, sqlx::Error> {
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM duplicate_candidates")
+        .fetch_one(pool)
+        .await?;
+
+    let rows = sqlx::query_as::<_, CandidateRow>(
+        r#"
+        SELECT id, event_id, duplicate_id, similarity, created_at
+        FROM duplicate_candidates
+        ORDER BY similarity DESC, created_at, id
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+        .bind(pagination.per_page as i64)
+        .bind(pagination.offset())
+        .fetch_all(pool)
+        .await?;
+
+    let ids: Vec<Uuid> = rows.iter().flat_map(|row| [row.event_id, row.duplicate_id]).collect();
+    let events: HashMap<Uuid, Event> =
+        sqlx::query_as::<_, Event>(&format!("SELECT {} FROM events WHERE id = ANY($1)", EVENT_COLUMNS))
+            .bind(&ids)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|event| (event.id, event))
+            .collect();
+
+    // Both events exist: deleting either deletes the row
+    let candidates = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(DuplicateCandidate {
+                id: row.id,
+                similarity: row.similarity,
+                created_at: row.created_at,
+                event: events.get(&row.event_id).cloned()?,
+                duplicate: events.get(&row.duplicate_id).cloned()?,
+            })
+        })
+        .collect();
+
+    Ok(DuplicateCandidatePage {
+        candidates,
+        total,
+        page: pagination.page,
+        per_page: pagination.per_page,
+    })
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_picks_a_band() {
+        let thresholds = DuplicateThresholds::default();
+        assert_eq!(thresholds.verdict(1.0), Some(Verdict::Merge));
+        assert_eq!(thresholds.verdict(0.8), Some(Verdict::Merge));
+        assert_eq!(thresholds.verdict(0.74), Some(Verdict::Review));
+        assert_eq!(thresholds.verdict(0.5), Some(Verdict::Review));
+        assert_eq!(thresholds.verdict(0.38), None);
+
+        let strict = DuplicateThresholds { merge: 1.0, review: 0.9 };
+        assert_eq!(strict.verdict(0.95), Some(Verdict::Review));
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn near_duplicates_are_merged_or_queued() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // A venue of our own, so other tests' events never pair with these
+        let run = Uuid::new_v4();
+        let venue = format!("The Blue Note {}", run);
+        let since = Utc::now();
+        let base = since + Duration::days(5);
+        let insert = |title: &'static str, source: &'static str, hours: i64, minutes: i64| {
+            let (pool, venue) = (pool.clone(), venue.clone());
+            async move {
+                sqlx::query_scalar::<_, Uuid>(
+                    "INSERT INTO events (title, venue, source_name, source_url, start_time) \
+                     VALUES ($1, $2, $3, $4, $5) RETURNING id",
+                )
+                    .bind(title)
+                    .bind(&venue)
+                    .bind(source)
+                    .bind(format!("https://{}.example/{}/{}", source.to_lowercase(), run, Uuid::new_v4()))
+                    .bind(base + Duration::hours(hours) + Duration::minutes(minutes))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Each pair four hours from the next, so only partners are compared
+        let ramblers = insert("The Midnight Ramblers", "Venue", 0, 0).await;
+        let ramblers_dup = insert("Midnight Ramblers", "Eventbrite", 0, 30).await;
+        let jazz = insert("Jazz Night at The Blue Note", "Eventbrite", 4, 0).await;
+        let jazz_dup = insert("Blue Note Jazz Night", "Venue", 4, 0).await;
+        let nye = insert("New Year's Eve Bash", "Venue", 8, 0).await;
+        let nye_dup = insert("NEW YEARS EVE BASH 2027", "Eventbrite", 9, 0).await;
+        let jam = insert("Open Jam", "Venue", 12, 0).await;
+        let mic = insert("Open Mic", "Eventbrite", 12, 0).await;
+        // Same title, but three hours apart, or from the same source
+        let early = insert("Songwriter Showcase", "Venue", 16, 0).await;
+        let late = insert("Songwriter Showcase", "Eventbrite", 19, 0).await;
+        let matinee = insert("Holiday Pops", "Venue", 24, 0).await;
+        let evening = insert("Holiday Pops", "Venue", 24, 30).await;
+
+        let report = detect_duplicates(&pool, since, DuplicateThresholds::default()).await.unwrap();
+        assert_eq!((report.examined, report.merged, report.flagged), (3, 1, 2));
+
+        let remaining: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM events WHERE venue = $1")
+            .bind(&venue)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(!remaining.contains(&ramblers_dup));
+        for id in [ramblers, jazz, jazz_dup, nye, nye_dup, jam, mic, early, late, matinee, evening] {
+            assert!(remaining.contains(&id));
+        }
+        let absorbed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event_sources WHERE event_id = $1")
+            .bind(ramblers)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(absorbed, 1);
+
+        let flagged: Vec<(Uuid, Uuid)> =
+            sqlx::query_as("SELECT event_id, duplicate_id FROM duplicate_candidates WHERE event_id = ANY($1) ORDER BY similarity DESC")
+                .bind(&remaining)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(flagged, [(jazz, jazz_dup), (nye, nye_dup)]);
+
+        // Running again changes nothing
+        let again = detect_duplicates(&pool, since, DuplicateThresholds::default()).await.unwrap();
+        assert_eq!(again.merged, 0);
+
+        let page = list_candidates(&pool, Pagination::new(Some(1), Some(100))).await.unwrap();
+        let queued = page.candidates.iter().find(|c| c.event.id == jazz).unwrap();
+        assert_eq!(queued.duplicate.title, "Blue Note Jazz Night");
+        assert!(queued.similarity > 0.5 && queued.similarity < 0.8);
+
+        sqlx::query("DELETE FROM events WHERE venue = $1")
+            .bind(&venue)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
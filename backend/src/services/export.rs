@@ -0,0 +1,196 @@
+//! # User Data Export
+//!
+//! Builds the "download my data" document for `GET /api/users/:id/export`.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Document
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "exported_at": "2026-02-01T18:00:00Z",
+//!   "user": { ... },
+//!   "preferences": [ ... ],
+//!   "interactions": [
+//!     { "id": "...", "event_id": "...", "event_title": "Jazz Night",
+//!       "interaction_type": "save", "created_at": "..." }
+//!   ]
+//! }
+//! ```
+//! Bump `EXPORT_SCHEMA_VERSION` whenever the shape changes, so a future
+//! import can tell old exports apart.
+//!
+//! ## Streaming
+//! Interaction history can be long, so it is never collected into a `Vec`.
+//! A task reads rows from the database cursor and sends each one as a JSON
+//! chunk through a bounded channel; the response body is that channel.
+//! A slow client pauses the query instead of growing memory.
+//!
+//! If the database fails partway through, the status line has already gone
+//! out, so the body is cut short (invalid JSON) and the error is logged.
+
+use axum::body::{Body, Bytes};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use uuid::Uuid;
+
+use crate::models::{ExportedInteraction, User, UserPreference};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Version of the export document's shape.
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Serialized interactions buffered ahead of a slow client.
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Closes the `interactions` array and the document.
+const DOCUMENT_TAIL: &[u8] = b"]}";
+
+// =============================================================================
+// DOCUMENT PIECES
+// =============================================================================
+
+/// Everything up to and including the opening `[` of `interactions`.
+pub fn document_head(
+    user: &User,
+    preferences: &[UserPreference],
+    exported_at: DateTime<Utc>,
+) -> serde_json::Result<Vec<u8>> {
+    Ok(format!(
+        r#"{{"schema_version":{},"exported_at":{},"user":{},"preferences":{},"interactions":["#,
+        EXPORT_SCHEMA_VERSION,
+        serde_json::to_string(&exported_at)?,
+        serde_json::to_string(user)?,
+        serde_json::to_string(preferences)?,
+    )
+        .into_bytes())
+}
+
+/// One array element, with a leading comma unless it's the first.
+pub fn interaction_chunk(interaction: &ExportedInteraction, first: bool) -> serde_json::Result<Vec<u8>> {
+    let mut chunk = if first { Vec::new() } else { vec![b','] };
+    serde_json::to_writer(&mut chunk, interaction)?;
+    Ok(chunk)
+}
+
+// =============================================================================
+// STREAMING
+// =============================================================================
+
+/// Streams the export document for a user whose row and preferences have
+/// already been loaded (so a missing user is a 404, not a broken stream).
+pub fn stream_user_export(pool: PgPool, user: User, preferences: Vec<UserPreference>) -> Body {
+    let (tx, rx) = mpsc::channel::<Result<Bytes, sqlx::Error>>(CHANNEL_CAPACITY);
+
+    tokio::spawn(async move {
+        let head = document_head(&user, &preferences, Utc::now()).expect("export head serializes");
+        if tx.send(Ok(Bytes::from(head))).await.is_err() {
+            return;
+        }
+
+        let mut rows = sqlx::query_as::<_, ExportedInteraction>(
+            r#"
+            SELECT ui.id, ui.event_id, e.title AS event_title, ui.interaction_type, ui.created_at
+            FROM user_interactions ui
+            JOIN events e ON e.id = ui.event_id
+            WHERE ui.user_id = $1
+            ORDER BY ui.created_at ASC, ui.id ASC
+            "#,
+        )
+            .bind(user.id)
+            .fetch(&pool);
+
+        let mut first = true;
+        while let Some(row) = rows.next().await {
+            let chunk = match row {
+                Ok(interaction) => interaction_chunk(&interaction, first).expect("interaction serializes"),
+                Err(e) => {
+                    tracing::error!(user_id = %user.id, error = %e, "data export failed");
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            first = false;
+
+            // Client went away; stop reading rows
+            if tx.send(Ok(Bytes::from(chunk))).await.is_err() {
+                return;
+            }
+        }
+
+        let _ = tx.send(Ok(Bytes::from_static(DOCUMENT_TAIL))).await;
+    });
+
+    Body::from_stream(ReceiverStream::new(rx))
+}
+
+/// `attachment` filename for a user's export.
+pub fn export_filename(user_id: Uuid) -> String {
+    format!("locate918-export-{}.json", user_id)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PreferenceSource;
+
+    #[test]
+    fn pieces_form_one_json_document() {
+        let now = Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            email: "jordan@example.com".to_string(),
+            name: Some("Jordan".to_string()),
+            location_preference: None,
+            radius_miles: None,
+            home_latitude: None,
+            home_longitude: None,
+            preferred_radius_km: None,
+            price_max: None,
+            family_friendly_only: false,
+            is_guest: false,
+            email_verified_at: None,
+            calendar_token: "token".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        let preferences = vec![UserPreference {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            category: "jazz".to_string(),
+            weight: 3,
+            source: PreferenceSource::Explicit,
+            created_at: now,
+        }];
+        let interaction = |interaction_type: &str| ExportedInteraction {
+            id: Uuid::new_v4(),
+            event_id: Uuid::new_v4(),
+            event_title: "Jazz Night".to_string(),
+            interaction_type: interaction_type.to_string(),
+            created_at: now,
+        };
+
+        let mut document = document_head(&user, &preferences, now).unwrap();
+        document.extend(interaction_chunk(&interaction("view"), true).unwrap());
+        document.extend(interaction_chunk(&interaction("save"), false).unwrap());
+        document.extend(DOCUMENT_TAIL);
+
+        let parsed: serde_json::Value = serde_json::from_slice(&document).unwrap();
+        assert_eq!(parsed["schema_version"], EXPORT_SCHEMA_VERSION);
+        assert_eq!(parsed["user"]["email"], "jordan@example.com");
+        assert_eq!(parsed["preferences"][0]["category"], "jazz");
+        assert_eq!(parsed["interactions"][1]["interaction_type"], "save");
+        assert_eq!(parsed["interactions"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,191 @@
+//! # Geographic Helpers
+//!
+//! Distance math for "events near me" searches.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Haversine Formula
+//! Computes the great-circle distance between two points on a sphere:
+//! ```text
+//! a = sin²(Δlat / 2) + cos(lat1) · cos(lat2) · sin²(Δlng / 2)
+//! d = 2R · asin(√a)
+//! ```
+//! Treating the Earth as a sphere is accurate to ~0.5%, which is plenty for
+//! "within 8 km" filtering across a metro area.
+//!
+//! The same formula exists twice: `haversine_km` in Rust (used for the
+//! recommendations' "near home" boost) and `haversine_sql` for
+//! filtering/sorting inside Postgres. Keep them in sync; the tests check
+//! both, the SQL one through a real radius search.
+
+// =============================================================================
+// CONSTANTS
+// =============================================================================
+
+/// Mean Earth radius in kilometers.
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// =============================================================================
+// VALIDATION
+// =============================================================================
+
+/// Returns true if the coordinates are within valid WGS84 ranges.
+pub fn is_valid_coordinate(lat: f64, lng: f64) -> bool {
+    (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lng)
+}
+
+// =============================================================================
+// DISTANCE
+// =============================================================================
+
+/// Great-circle distance between two points, in kilometers.
+pub fn haversine_km(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lng = (lng2 - lng1).to_radians();
+
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// SQL expression for the distance (km) from a fixed point to each row.
+///
+/// Uses the `latitude`/`longitude` columns of the events table. The point
+/// is formatted into the SQL as numeric literals; callers must pass values
+/// that passed `is_valid_coordinate` (finite floats, so no injection risk).
+///
+/// `LEAST(1.0, ...)` guards against floating-point error pushing the
+/// argument of ASIN slightly above 1 for identical points.
+pub fn haversine_sql(lat: f64, lng: f64) -> String {
+    format!(
+        "(2 * {r} * ASIN(LEAST(1.0, SQRT(\
+            POWER(SIN(RADIANS(latitude - ({lat})) / 2), 2) + \
+            COS(RADIANS({lat})) * COS(RADIANS(latitude)) * \
+            POWER(SIN(RADIANS(longitude - ({lng})) / 2), 2)))))",
+        r = EARTH_RADIUS_KM,
+        lat = lat,
+        lng = lng
+    )
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fixed Tulsa-area reference points
+    const DOWNTOWN: (f64, f64) = (36.1540, -95.9928);
+    const BOK_CENTER: (f64, f64) = (36.1524, -95.9967);
+    const CAINS_BALLROOM: (f64, f64) = (36.1591, -95.9936);
+    const TULSA_AIRPORT: (f64, f64) = (36.1984, -95.8881);
+    const OKLAHOMA_CITY: (f64, f64) = (35.4676, -97.5164);
+
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        haversine_km(a.0, a.1, b.0, b.1)
+    }
+
+    #[test]
+    fn same_point_is_zero() {
+        assert_eq!(distance(DOWNTOWN, DOWNTOWN), 0.0);
+    }
+
+    #[test]
+    fn short_downtown_walk() {
+        // BOK Center to Cain's Ballroom is under a kilometer
+        let d = distance(BOK_CENTER, CAINS_BALLROOM);
+        assert!((d - 0.795).abs() < 0.01, "got {}", d);
+    }
+
+    #[test]
+    fn downtown_to_airport() {
+        let d = distance(DOWNTOWN, TULSA_AIRPORT);
+        assert!((d - 10.62).abs() < 0.05, "got {}", d);
+        assert!(d > 8.0, "airport should fall outside an 8 km radius");
+    }
+
+    #[test]
+    fn tulsa_to_oklahoma_city_is_symmetric() {
+        let there = distance(DOWNTOWN, OKLAHOMA_CITY);
+        let back = distance(OKLAHOMA_CITY, DOWNTOWN);
+        assert!((there - 157.16).abs() < 0.1, "got {}", there);
+        assert!((there - back).abs() < 1e-9);
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set, e.g.
+    /// `TEST_DATABASE_URL=postgres://postgres@localhost/locate918_test cargo test`.
+    #[tokio::test]
+    async fn radius_search_keeps_events_just_inside() {
+        use crate::models::EventWithDistance;
+        use crate::services::search::{search_sql, SearchQuery};
+        use sqlx::PgPool;
+        use uuid::Uuid;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Due north of downtown, a degree of latitude is R·π/180 km
+        let km_north = |km: f64| (DOWNTOWN.0 + (km / EARTH_RADIUS_KM).to_degrees(), DOWNTOWN.1);
+        let run = Uuid::new_v4();
+        let mut ids = Vec::new();
+        for (title, (lat, lng)) in [
+            ("inside", km_north(7.95)),
+            ("outside", km_north(8.05)),
+            ("airport", TULSA_AIRPORT),
+        ] {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO events (title, source_url, start_time, latitude, longitude) \
+                 VALUES ($1, $2, NOW() + INTERVAL '1 day', $3, $4) RETURNING id",
+            )
+                .bind(format!("{} {}", title, run))
+                .bind(format!("https://venue.example/{}/{}", run, title))
+                .bind(lat)
+                .bind(lng)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        let search = |radius_km: f64| {
+            let pool = pool.clone();
+            async move {
+                let params = SearchQuery { q: Some(run.to_string()), ..Default::default() };
+                sqlx::query_as::<_, EventWithDistance>(&search_sql(&params, Some((DOWNTOWN.0, DOWNTOWN.1, radius_km))))
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let within_8 = search(8.0).await;
+        let titles: Vec<&str> = within_8.iter().map(|found| found.event.title.as_str()).collect();
+        assert_eq!(titles, [format!("inside {}", run)]);
+        assert!((within_8[0].distance_km - 7.95).abs() < 1e-6, "got {}", within_8[0].distance_km);
+
+        // Nearest first, and the SQL agrees with the Rust formula
+        let within_20 = search(20.0).await;
+        assert_eq!(within_20.len(), 3);
+        assert!(within_20.windows(2).all(|pair| pair[0].distance_km <= pair[1].distance_km));
+        let airport = within_20.iter().find(|found| found.event.id == ids[2]).unwrap();
+        assert!((airport.distance_km - distance(DOWNTOWN, TULSA_AIRPORT)).abs() < 1e-6);
+
+        sqlx::query("DELETE FROM events WHERE id = ANY($1)").bind(&ids).execute(&pool).await.unwrap();
+    }
+
+    #[test]
+    fn validates_coordinate_ranges() {
+        assert!(is_valid_coordinate(36.15, -95.99));
+        assert!(!is_valid_coordinate(91.0, 0.0));
+        assert!(!is_valid_coordinate(0.0, -181.0));
+        assert!(!is_valid_coordinate(f64::NAN, 0.0));
+    }
+}
@@ -0,0 +1,617 @@
+//! # Geocoding
+//!
+//! Turns venue and event addresses into coordinates, so scraped events
+//! (which only come with address text) show up in radius searches (see
+//! `geo.rs`).
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Geocoders
+//! | `GEOCODER` | Geocoder |
+//! |------------|----------|
+//! | `nominatim` (default) | `NominatimGeocoder` - OpenStreetMap's search API |
+//! | `off` | none: events keep the coordinates they came with |
+//!
+//! Callers go through the `Geocoder` trait; tests use `MockGeocoder`,
+//! which knows a fixed list of addresses.
+//!
+//! ## Nominatim Usage Policy
+//! At most one request a second (`MIN_INTERVAL`; requests queue for their
+//! turn), a `User-Agent` saying who we are (`fetch::USER_AGENT`), and no
+//! address asked about twice: every answer is cached.
+//!
+//! ## Cache
+//! Answers are kept in `geocode_cache` under the normalized address
+//! (`address_key`), so "423 N. Main St." and "423 n main st" are one
+//! lookup. An address the geocoder doesn't know is asked about again
+//! after `MISS_TTL`. A lookup that fails (timeout, 5xx) isn't cached: the
+//! event keeps null coordinates and the next pass tries again.
+//!
+//! ## Which Address
+//! An event at a venue with coordinates takes the venue's. Otherwise its
+//! venue's address (or the event's `venue_address`) is looked up, and the
+//! venue gets the answer too; failing that, its `location`.
+//!
+//! ## When It Runs
+//! - After a scrape creates events, in the background (see
+//!   `scraper/registry.rs`), so a slow geocoder never holds up a run
+//! - `POST /api/admin/events/geocode` (`backfill`): venues, then events,
+//!   still without coordinates
+//!
+//! ## Environment Variables
+//! ```text
+//! GEOCODER=nominatim                                 # nominatim | off
+//! NOMINATIM_URL=https://nominatim.openstreetmap.org
+//! GEOCODER_EMAIL=ops@locate918.com                   # contact sent with each request
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::async_trait;
+use serde::Deserialize;
+use sqlx::{FromRow, PgPool};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::models::GeocodeReport;
+use crate::scraper::fetch::USER_AGENT;
+use crate::services::geo;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// The public Nominatim instance, unless configured (`NOMINATIM_URL`).
+pub const DEFAULT_NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org";
+
+/// Least time between two Nominatim requests (its usage policy).
+pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long "not found" is believed before the address is asked again.
+pub const MISS_TTL: chrono::Duration = chrono::Duration::days(30);
+
+/// Most addresses one backfill sends to the geocoder, unless asked.
+pub const DEFAULT_BACKFILL_LOOKUPS: usize = 50;
+
+/// How long one Nominatim request may take.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The Tulsa metro (west, north, east, south): results there are
+/// preferred, not required.
+const TULSA_VIEWBOX: &str = "-96.30,36.40,-95.60,35.80";
+
+// =============================================================================
+// GEOCODER INTERFACE
+// =============================================================================
+
+/// A point in WGS84 decimal degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Why a lookup failed (as opposed to finding nothing).
+#[derive(Debug, thiserror::Error)]
+pub enum GeocodeError {
+    #[error("geocoder request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("geocoder answered {0}")]
+    Status(reqwest::StatusCode),
+}
+
+/// Something that can find an address on the map.
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+    /// Where `address` is, or `None` if the geocoder doesn't know it.
+    async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeocodeError>;
+}
+
+/// The geocoder as shared by the scrapers and the admin backfill.
+pub type SharedGeocoder = Arc<dyn Geocoder>;
+
+/// The geocoder chosen by `GEOCODER` (default Nominatim), or `None` if
+/// geocoding is off. An unknown name, or a client that can't be built,
+/// is logged and turns geocoding off; nothing else depends on it.
+pub fn from_env() -> Option<SharedGeocoder> {
+    match std::env::var("GEOCODER").ok().map(|name| name.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some("nominatim") => {
+            let url = std::env::var("NOMINATIM_URL").unwrap_or_else(|_| DEFAULT_NOMINATIM_URL.to_string());
+            match NominatimGeocoder::new(&url) {
+                Ok(nominatim) => {
+                    let email = std::env::var("GEOCODER_EMAIL").ok().filter(|email| !email.is_empty());
+                    Some(Arc::new(nominatim.with_email(email)))
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "couldn't build the geocoder; geocoding is off");
+                    None
+                }
+            }
+        }
+        Some("off") => None,
+        Some(other) => {
+            tracing::error!(geocoder = other, "unknown GEOCODER; geocoding is off");
+            None
+        }
+    }
+}
+
+// =============================================================================
+// NOMINATIM
+// =============================================================================
+
+/// OpenStreetMap's geocoder, one request at a time.
+pub struct NominatimGeocoder {
+    client: reqwest::Client,
+    base_url: String,
+    email: Option<String>,
+    min_interval: Duration,
+    /// When the next request may go out; held while one is in flight
+    next_request: Mutex<Instant>,
+}
+
+/// One search result; Nominatim sends coordinates as strings.
+#[derive(Debug, Deserialize)]
+struct NominatimPlace {
+    lat: String,
+    lon: String,
+}
+
+impl NominatimGeocoder {
+    /// A geocoder for the Nominatim instance at `base_url`.
+    pub fn new(base_url: &str) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            email: None,
+            min_interval: MIN_INTERVAL,
+            next_request: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Sends `email` with each request, as the usage policy asks of
+    /// heavy users.
+    pub fn with_email(self, email: Option<String>) -> Self {
+        Self { email, ..self }
+    }
+
+    /// Spaces requests `min_interval` apart instead of `MIN_INTERVAL`.
+    #[cfg(test)]
+    pub fn with_min_interval(self, min_interval: Duration) -> Self {
+        Self { min_interval, ..self }
+    }
+}
+
+#[async_trait]
+impl Geocoder for NominatimGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeocodeError> {
+        let mut next_request = self.next_request.lock().await;
+        tokio::time::sleep_until(*next_request).await;
+
+        let mut query = vec![
+            ("q", address),
+            ("format", "jsonv2"),
+            ("limit", "1"),
+            ("countrycodes", "us"),
+            ("viewbox", TULSA_VIEWBOX),
+        ];
+        if let Some(email) = &self.email {
+            query.push(("email", email));
+        }
+        let sent = self.client.get(format!("{}/search", self.base_url)).query(&query).send().await;
+        *next_request = Instant::now() + self.min_interval;
+
+        let response = sent?;
+        if !response.status().is_success() {
+            return Err(GeocodeError::Status(response.status()));
+        }
+        let places: Vec<NominatimPlace> = response.json().await?;
+        Ok(places.first().and_then(|place| {
+            let latitude = place.lat.parse().ok()?;
+            let longitude = place.lon.parse().ok()?;
+            geo::is_valid_coordinate(latitude, longitude).then_some(Coordinates { latitude, longitude })
+        }))
+    }
+}
+
+// =============================================================================
+// MOCK
+// =============================================================================
+
+/// A geocoder that knows a fixed list of addresses; no network.
+#[cfg(test)]
+pub struct MockGeocoder {
+    known: std::collections::HashMap<String, Coordinates>,
+    failing: bool,
+    asked: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl MockGeocoder {
+    /// Finds each `(address, latitude, longitude)`, and nothing else.
+    pub fn new(known: &[(&str, f64, f64)]) -> Self {
+        Self {
+            known: known
+                .iter()
+                .map(|&(address, latitude, longitude)| (address_key(address), Coordinates { latitude, longitude }))
+                .collect(),
+            failing: false,
+            asked: Default::default(),
+        }
+    }
+
+    /// Fails every lookup, as a geocoder that's down would.
+    pub fn failing() -> Self {
+        Self { failing: true, ..Self::new(&[]) }
+    }
+
+    /// The addresses asked about so far, in order.
+    pub fn asked(&self) -> Vec<String> {
+        self.asked.lock().expect("mock geocoder lock").clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Geocoder for MockGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeocodeError> {
+        self.asked.lock().expect("mock geocoder lock").push(address.to_string());
+        if self.failing {
+            return Err(GeocodeError::Status(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        }
+        Ok(self.known.get(&address_key(address)).copied())
+    }
+}
+
+// =============================================================================
+// CACHE
+// =============================================================================
+
+/// The cache key for `address`: lowercased, every run of anything but
+/// letters and digits one space, trimmed.
+pub fn address_key(address: &str) -> String {
+    address
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What one address came to.
+enum Lookup {
+    Found(Coordinates),
+    NotFound,
+    /// The geocoder failed; nothing was cached
+    Failed,
+    /// Not cached, and this pass may not ask the geocoder any more
+    Deferred,
+}
+
+/// Looks `address` up in the cache, else asks `geocoder` (if `lookups`
+/// allows) and caches the answer.
+async fn look_up(pool: &PgPool, geocoder: &dyn Geocoder, address: &str, lookups: &mut usize) -> Result<Lookup, sqlx::Error> {
+    let key = address_key(address);
+    if key.is_empty() {
+        return Ok(Lookup::NotFound);
+    }
+
+    let cached: Option<(Option<f64>, Option<f64>)> = sqlx::query_as(
+        r#"
+        SELECT latitude, longitude FROM geocode_cache
+        WHERE address_key = $1 AND (latitude IS NOT NULL OR geocoded_at > NOW() - $2)
+        "#,
+    )
+        .bind(&key)
+        .bind(MISS_TTL)
+        .fetch_optional(pool)
+        .await?;
+    let found = match cached {
+        Some(cached) => cached.0.zip(cached.1).map(|(latitude, longitude)| Coordinates { latitude, longitude }),
+        None if *lookups == 0 => return Ok(Lookup::Deferred),
+        None => {
+            *lookups -= 1;
+            let found = match geocoder.geocode(address.trim()).await {
+                Ok(found) => found,
+                Err(e) => {
+                    tracing::warn!(address, error = %e, "geocoding failed");
+                    return Ok(Lookup::Failed);
+                }
+            };
+            sqlx::query(
+                r#"
+                INSERT INTO geocode_cache (address_key, query, latitude, longitude)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (address_key) DO UPDATE
+                SET query = EXCLUDED.query, latitude = EXCLUDED.latitude,
+                    longitude = EXCLUDED.longitude, geocoded_at = NOW()
+                "#,
+            )
+                .bind(&key)
+                .bind(address.trim())
+                .bind(found.map(|at| at.latitude))
+                .bind(found.map(|at| at.longitude))
+                .execute(pool)
+                .await?;
+            found
+        }
+    };
+    Ok(found.map_or(Lookup::NotFound, Lookup::Found))
+}
+
+// =============================================================================
+// JOBS
+// =============================================================================
+
+/// An event without coordinates, and what we know about where it is.
+#[derive(Debug, FromRow)]
+struct Unlocated {
+    id: Uuid,
+    venue_id: Option<Uuid>,
+    venue_latitude: Option<f64>,
+    venue_longitude: Option<f64>,
+    /// The venue's address, else the event's own `venue_address`
+    address: Option<String>,
+    location: Option<String>,
+}
+
+/// Gives the events `ids` that lack coordinates their venue's, or looks
+/// their address up. Failures leave an event's coordinates null.
+pub async fn locate_events(pool: &PgPool, geocoder: &dyn Geocoder, ids: &[Uuid]) -> Result<GeocodeReport, sqlx::Error> {
+    let (mut report, mut lookups) = (GeocodeReport::default(), usize::MAX);
+    locate(pool, geocoder, Some(ids), &mut lookups, &mut report).await?;
+    Ok(report)
+}
+
+/// Locates every venue, then every event, still without coordinates,
+/// sending at most `max_lookups` addresses to the geocoder (cached
+/// answers are free). Whatever is left, or failed, waits for the next one.
+pub async fn backfill(pool: &PgPool, geocoder: &dyn Geocoder, max_lookups: usize) -> Result<GeocodeReport, sqlx::Error> {
+    let mut report = GeocodeReport::default();
+    let mut lookups = max_lookups;
+
+    let venues: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT id, address FROM venues
+        WHERE (latitude IS NULL OR longitude IS NULL) AND NULLIF(trim(address), '') IS NOT NULL
+        ORDER BY name
+        "#,
+    )
+        .fetch_all(pool)
+        .await?;
+    for (id, address) in venues {
+        match look_up(pool, geocoder, &address, &mut lookups).await? {
+            Lookup::Deferred => continue,
+            Lookup::Found(at) => {
+                set_venue(pool, id, at).await?;
+                report.located += 1;
+            }
+            Lookup::NotFound => report.not_found += 1,
+            Lookup::Failed => report.failed += 1,
+        }
+        report.examined += 1;
+    }
+
+    locate(pool, geocoder, None, &mut lookups, &mut report).await?;
+    Ok(report)
+}
+
+/// Locates the events `ids` (every event, for `None`) that lack
+/// coordinates and have something to go on.
+async fn locate(
+    pool: &PgPool,
+    geocoder: &dyn Geocoder,
+    ids: Option<&[Uuid]>,
+    lookups: &mut usize,
+    report: &mut GeocodeReport,
+) -> Result<(), sqlx::Error> {
+    // Upcoming events first: they're the ones people search for
+    let events: Vec<Unlocated> = sqlx::query_as(
+        r#"
+        SELECT e.id, e.venue_id, v.latitude AS venue_latitude, v.longitude AS venue_longitude,
+               COALESCE(NULLIF(trim(v.address), ''), NULLIF(trim(e.venue_address), '')) AS address,
+               NULLIF(trim(e.location), '') AS location
+        FROM events e
+        LEFT JOIN venues v ON v.id = e.venue_id
+        WHERE (e.latitude IS NULL OR e.longitude IS NULL)
+          AND ($1::uuid[] IS NULL OR e.id = ANY($1))
+          AND (v.latitude IS NOT NULL
+               OR COALESCE(NULLIF(trim(v.address), ''), NULLIF(trim(e.venue_address), ''),
+                           NULLIF(trim(e.location), '')) IS NOT NULL)
+        ORDER BY e.start_time < NOW(), e.start_time
+        "#,
+    )
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+    for event in events {
+        let at = match event.venue_latitude.zip(event.venue_longitude) {
+            Some((latitude, longitude)) => Lookup::Found(Coordinates { latitude, longitude }),
+            None => {
+                let mut found = Lookup::NotFound;
+                if let Some(address) = &event.address {
+                    found = look_up(pool, geocoder, address, lookups).await?;
+                    if let (Lookup::Found(at), Some(venue)) = (&found, event.venue_id) {
+                        set_venue(pool, venue, *at).await?;
+                    }
+                }
+                if let (Lookup::NotFound, Some(location)) = (&found, &event.location) {
+                    found = look_up(pool, geocoder, location, lookups).await?;
+                }
+                found
+            }
+        };
+
+        match at {
+            Lookup::Deferred => continue,
+            Lookup::Found(at) => {
+                sqlx::query("UPDATE events SET latitude = $2, longitude = $3 WHERE id = $1")
+                    .bind(event.id)
+                    .bind(at.latitude)
+                    .bind(at.longitude)
+                    .execute(pool)
+                    .await?;
+                report.located += 1;
+            }
+            Lookup::NotFound => report.not_found += 1,
+            Lookup::Failed => report.failed += 1,
+        }
+        report.examined += 1;
+    }
+    Ok(())
+}
+
+/// Gives venue `id` coordinates, unless it already has some.
+async fn set_venue(pool: &PgPool, id: Uuid, at: Coordinates) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE venues SET latitude = $2, longitude = $3 WHERE id = $1 AND (latitude IS NULL OR longitude IS NULL)",
+    )
+        .bind(id)
+        .bind(at.latitude)
+        .bind(at.longitude)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn address_keys_ignore_case_and_punctuation() {
+        assert_eq!(address_key(" 423 N. Main St., Tulsa "), "423 n main st tulsa");
+        assert_eq!(address_key("423 n main st tulsa"), address_key("423 N MAIN ST - TULSA"));
+        assert_eq!(address_key("  ,. "), "");
+    }
+
+    #[tokio::test]
+    async fn nominatim_identifies_itself_and_waits_its_turn() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "423 N Main St, Tulsa"))
+            .and(query_param("email", "ops@example.com"))
+            .and(header("user-agent", USER_AGENT))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"[{ "place_id": 1, "lat": "36.1619", "lon": "-95.9914", "display_name": "Cain's Ballroom" }]"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "Nowhere"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "Down"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let nominatim = NominatimGeocoder::new(&server.uri())
+            .unwrap()
+            .with_email(Some("ops@example.com".to_string()))
+            .with_min_interval(Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        assert_eq!(
+            nominatim.geocode("423 N Main St, Tulsa").await.unwrap(),
+            Some(Coordinates { latitude: 36.1619, longitude: -95.9914 })
+        );
+        assert_eq!(nominatim.geocode("Nowhere").await.unwrap(), None);
+        assert!(matches!(nominatim.geocode("Down").await, Err(GeocodeError::Status(status)) if status.as_u16() == 503));
+        assert!(started.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn failures_stay_null_until_a_later_backfill() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let hall = format!("{} Main St, Tulsa", run);
+        let park = format!("{} Park Loop, Tulsa", run);
+        let venue: Uuid = sqlx::query_scalar("INSERT INTO venues (name, address) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Geocode Hall {}", run))
+            .bind(&hall)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let event = |location: Option<String>, venue: Option<Uuid>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, Uuid>(
+                    "INSERT INTO events (title, source_url, start_time, location, venue_id) VALUES ('Show', $1, NOW() + INTERVAL '1 day', $2, $3) RETURNING id",
+                )
+                    .bind(format!("https://geocode.example/{}", Uuid::new_v4()))
+                    .bind(location)
+                    .bind(venue)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        let at_hall = event(None, Some(venue)).await;
+        let in_park = event(Some(park.clone()), None).await;
+        let lost = event(Some(format!("{} Nowhere", run)), None).await;
+        let ids = [at_hall, in_park, lost];
+        let coordinates = |id: Uuid| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_as::<_, (Option<f64>, Option<f64>)>("SELECT latitude, longitude FROM events WHERE id = $1")
+                    .bind(id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // A geocoder that's down leaves everything as it was
+        let report = locate_events(&pool, &MockGeocoder::failing(), &ids).await.unwrap();
+        assert_eq!((report.examined, report.failed), (3, 3));
+        assert_eq!(coordinates(at_hall).await, (None, None));
+
+        // The backfill gets them, venue included
+        let geocoder = MockGeocoder::new(&[(&hall, 36.16, -95.99), (&park, 36.13, -95.97)]);
+        backfill(&pool, &geocoder, usize::MAX).await.unwrap();
+        assert_eq!(coordinates(at_hall).await, (Some(36.16), Some(-95.99)));
+        assert_eq!(coordinates(in_park).await, (Some(36.13), Some(-95.97)));
+        assert_eq!(coordinates(lost).await, (None, None));
+        let venue_at: (Option<f64>, Option<f64>) = sqlx::query_as("SELECT latitude, longitude FROM venues WHERE id = $1")
+            .bind(venue)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(venue_at, (Some(36.16), Some(-95.99)));
+
+        // Answers, found or not, are cached: nothing of ours is asked twice
+        let again = MockGeocoder::new(&[]);
+        let report = locate_events(&pool, &again, &ids).await.unwrap();
+        assert_eq!((report.examined, report.not_found), (1, 1));
+        assert_eq!(again.asked(), Vec::<String>::new());
+
+        sqlx::query("DELETE FROM events WHERE id = ANY($1)").bind(&ids[..]).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM venues WHERE id = $1").bind(venue).execute(&pool).await.unwrap();
+    }
+}
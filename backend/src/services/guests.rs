@@ -0,0 +1,112 @@
+//! # Guest Accounts
+//!
+//! Guests are user rows made before signup so the chat can learn from
+//! them. Claiming a guest either turns the row into a real account or, if
+//! the email is already registered, folds it into that account.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Merging
+//! `merge_into` moves everything the guest owns onto the target account
+//! and deletes the guest, inside the caller's transaction:
+//!
+//! | Data | Rule |
+//! |------|------|
+//! | Preferences | per category, the weight with the larger magnitude wins (ties keep the account's) |
+//! | Interactions | re-pointed to the account as they are |
+//! | Follows | added unless the account already follows the same thing |
+//! | Saved searches | re-pointed |
+//! | Notifications | re-pointed unless the account already has the same one |
+//! | Chat history | re-pointed |
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Domain of guest placeholder emails. `.invalid` is reserved (RFC 2606),
+/// so these can never receive mail or clash with a real address.
+pub const GUEST_EMAIL_DOMAIN: &str = "guest.locate918.invalid";
+
+/// Placeholder email for a new guest.
+pub fn guest_email(token: Uuid) -> String {
+    format!("guest-{}@{}", token.simple(), GUEST_EMAIL_DOMAIN)
+}
+
+// =============================================================================
+// MERGE
+// =============================================================================
+
+/// Moves the guest's data onto `target` and deletes the guest.
+pub async fn merge_into(conn: &mut PgConnection, guest: Uuid, target: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_preferences (user_id, category, weight, source)
+        SELECT $2, category, weight, source FROM user_preferences WHERE user_id = $1
+        ON CONFLICT (user_id, category) DO UPDATE
+        SET weight = EXCLUDED.weight, source = EXCLUDED.source
+        WHERE ABS(EXCLUDED.weight) > ABS(user_preferences.weight)
+        "#,
+    )
+        .bind(guest)
+        .bind(target)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE user_interactions SET user_id = $2 WHERE user_id = $1")
+        .bind(guest)
+        .bind(target)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_follows (user_id, target_type, target_value, created_at, last_notified_at)
+        SELECT $2, target_type, target_value, created_at, last_notified_at FROM user_follows WHERE user_id = $1
+        ON CONFLICT (user_id, target_type, target_value) DO NOTHING
+        "#,
+    )
+        .bind(guest)
+        .bind(target)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE saved_searches SET user_id = $2 WHERE user_id = $1")
+        .bind(guest)
+        .bind(target)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE chat_messages SET user_id = $2 WHERE user_id = $1")
+        .bind(guest)
+        .bind(target)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE notifications n SET user_id = $2
+        WHERE n.user_id = $1
+          AND NOT EXISTS (
+              SELECT 1 FROM notifications t
+              WHERE t.user_id = $2 AND t.event_id = n.event_id AND t.kind = n.kind
+          )
+        "#,
+    )
+        .bind(guest)
+        .bind(target)
+        .execute(&mut *conn)
+        .await?;
+
+    // Whatever wasn't moved (duplicate follows and notifications, guest-only
+    // preference rows that lost) goes with the guest
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(guest)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,298 @@
+//! # iCalendar (ICS) Builder
+//!
+//! Renders events as RFC 5545 iCalendar data so users can add them to
+//! Google Calendar, Apple Calendar, Outlook, etc.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Usage
+//! ```rust
+//! // Single event download
+//! let body = IcsCalendar::new()
+//!     .add_event(&event)
+//!     .build();
+//!
+//! // Subscription feed
+//! let body = events
+//!     .iter()
+//!     .fold(IcsCalendar::new().name("My Events"), |cal, e| cal.add_event(e))
+//!     .build();
+//! ```
+//!
+//! ## Output
+//! ```text
+//! BEGIN:VCALENDAR
+//! VERSION:2.0
+//! PRODID:-//Locate918//Events//EN
+//! CALSCALE:GREGORIAN
+//! BEGIN:VEVENT
+//! UID:550e8400-e29b-41d4-a716-446655440000@locate918
+//! DTSTAMP:20260117T120000Z
+//! DTSTART:20260125T200000Z
+//! DTEND:20260125T230000Z
+//! SUMMARY:Jazz Night at The Blue Note
+//! LOCATION:The Blue Note\, Downtown Tulsa
+//! URL:https://thebluenote.com/events/jazz-night
+//! DESCRIPTION:Live jazz music featuring local artists
+//! STATUS:CONFIRMED
+//! END:VEVENT
+//! END:VCALENDAR
+//! ```
+//!
+//! ## RFC 5545 Notes
+//! - Lines end with CRLF and are folded at 75 octets
+//! - TEXT values escape `\`, `;`, `,` and newlines
+//! - All times are written in UTC (`...Z` suffix)
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{Event, EventStatus};
+
+// =============================================================================
+// CONSTANTS
+// =============================================================================
+
+/// Product identifier written to every calendar.
+const PRODID: &str = "-//Locate918//Events//EN";
+
+/// Duration assumed for events without an `end_time`.
+const DEFAULT_DURATION_HOURS: i64 = 2;
+
+/// Maximum line length in octets before folding (RFC 5545 §3.1).
+const MAX_LINE_OCTETS: usize = 75;
+
+// =============================================================================
+// CALENDAR BUILDER
+// =============================================================================
+
+/// Builds a VCALENDAR containing zero or more VEVENTs.
+#[derive(Debug, Default)]
+pub struct IcsCalendar {
+    lines: Vec<String>,
+}
+
+impl IcsCalendar {
+    /// Creates an empty calendar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the display name calendar apps show for a subscribed feed.
+    pub fn name(mut self, name: &str) -> Self {
+        self.lines.push(format!("X-WR-CALNAME:{}", escape_text(name)));
+        self
+    }
+
+    /// Appends an event as a VEVENT component.
+    pub fn add_event(mut self, event: &Event) -> Self {
+        let end = event
+            .end_time
+            .unwrap_or(event.start_time + Duration::hours(DEFAULT_DURATION_HOURS));
+
+        self.lines.push("BEGIN:VEVENT".to_string());
+        self.lines.push(format!("UID:{}@locate918", event.id));
+        self.lines.push(format!("DTSTAMP:{}", format_datetime(event.updated_at)));
+        self.lines.push(format!("DTSTART:{}", format_datetime(event.start_time)));
+        self.lines.push(format!("DTEND:{}", format_datetime(end)));
+        self.lines.push(format!("SUMMARY:{}", escape_text(&event.title)));
+
+        let location: Vec<&str> = [event.venue.as_deref(), event.location.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.trim().is_empty())
+            .collect();
+        if !location.is_empty() {
+            self.lines.push(format!("LOCATION:{}", escape_text(&location.join(", "))));
+        }
+
+        // URL is a URI value, not TEXT, so it isn't escaped
+        self.lines.push(format!("URL:{}", event.source_url));
+
+        if let Some(ref description) = event.description {
+            self.lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+
+        // Subscribed calendars update in place, so a cancellation shows up
+        let status = match event.status {
+            EventStatus::Cancelled => "CANCELLED",
+            EventStatus::Postponed => "TENTATIVE",
+            EventStatus::Scheduled | EventStatus::SoldOut => "CONFIRMED",
+        };
+        self.lines.push(format!("STATUS:{}", status));
+
+        self.lines.push("END:VEVENT".to_string());
+        self
+    }
+
+    /// Renders the calendar with CRLF line endings and folded long lines.
+    pub fn build(self) -> String {
+        let mut out = String::new();
+        let header = [
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            format!("PRODID:{}", PRODID),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+
+        for line in header.iter().chain(self.lines.iter()) {
+            out.push_str(&fold_line(line));
+            out.push_str("\r\n");
+        }
+        out.push_str("END:VCALENDAR\r\n");
+        out
+    }
+}
+
+// =============================================================================
+// FORMATTING HELPERS
+// =============================================================================
+
+/// Escapes a TEXT property value (RFC 5545 §3.3.11).
+///
+/// Backslashes, semicolons and commas are backslash-escaped; newlines
+/// (including CRLF) become the literal two characters `\n`.
+pub fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                out.push_str("\\n");
+            }
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Formats a timestamp as an ICS UTC date-time, e.g. `20260125T200000Z`.
+pub fn format_datetime(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Folds a content line longer than 75 octets (RFC 5545 §3.1).
+///
+/// Continuation lines start with a single space. Splits never land inside
+/// a multi-byte UTF-8 character.
+fn fold_line(line: &str) -> String {
+    if line.len() <= MAX_LINE_OCTETS {
+        return line.to_string();
+    }
+
+    let mut out = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut current = 0;
+    let mut limit = MAX_LINE_OCTETS;
+
+    for c in line.chars() {
+        if current + c.len_utf8() > limit {
+            out.push_str("\r\n ");
+            current = 0;
+            // Continuation lines lose one octet to the leading space
+            limit = MAX_LINE_OCTETS - 1;
+        }
+        out.push(c);
+        current += c.len_utf8();
+    }
+
+    out
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn sample_event() -> Event {
+        let created = Utc.with_ymd_and_hms(2026, 1, 17, 12, 0, 0).unwrap();
+        Event {
+            id: Uuid::nil(),
+            title: "Jazz Night".to_string(),
+            description: Some("Live jazz, drinks; good times\nBring friends".to_string()),
+            venue: Some("The Blue Note".to_string()),
+            venue_id: None,
+            venue_address: None,
+            location: Some("Downtown Tulsa".to_string()),
+            source_url: "https://example.com/jazz?a=1,2".to_string(),
+            source_name: None,
+            start_time: Utc.with_ymd_and_hms(2026, 1, 25, 20, 0, 0).unwrap(),
+            end_time: None,
+            categories: None,
+            tags: vec![],
+            price_min: None,
+            price_max: None,
+            is_free: false,
+            outdoor: false,
+            family_friendly: false,
+            image_url: None,
+            status: EventStatus::Scheduled,
+            latitude: None,
+            longitude: None,
+            archived_at: None,
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    #[test]
+    fn escapes_commas_semicolons_and_backslashes() {
+        assert_eq!(escape_text(r"a,b;c\d"), r"a\,b\;c\\d");
+    }
+
+    #[test]
+    fn escapes_newlines() {
+        assert_eq!(escape_text("line1\nline2\r\nline3"), r"line1\nline2\nline3");
+    }
+
+    #[test]
+    fn folds_long_lines_on_char_boundaries() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(80));
+        let folded = fold_line(&line);
+
+        for (i, part) in folded.split("\r\n").enumerate() {
+            assert!(part.len() <= MAX_LINE_OCTETS);
+            assert_eq!(i > 0, part.starts_with(' '));
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn renders_event_with_default_duration() {
+        let ics = IcsCalendar::new().add_event(&sample_event()).build();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:00000000-0000-0000-0000-000000000000@locate918\r\n"));
+        assert!(ics.contains("DTSTART:20260125T200000Z\r\n"));
+        assert!(ics.contains("DTEND:20260125T220000Z\r\n"));
+        assert!(ics.contains("LOCATION:The Blue Note\\, Downtown Tulsa\r\n"));
+        assert!(ics.contains("URL:https://example.com/jazz?a=1,2\r\n"));
+        assert!(ics.contains("DESCRIPTION:Live jazz\\, drinks\\; good times\\nBring friends\r\n"));
+        assert!(ics.contains("STATUS:CONFIRMED\r\n"));
+    }
+
+    #[test]
+    fn marks_cancelled_events() {
+        let event = Event {
+            status: EventStatus::Cancelled,
+            ..sample_event()
+        };
+        let ics = IcsCalendar::new().add_event(&event).build();
+
+        assert!(ics.contains("STATUS:CANCELLED\r\n"));
+    }
+}
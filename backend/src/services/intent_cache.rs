@@ -0,0 +1,232 @@
+//! # Intent Cache
+//!
+//! "What's happening this weekend?" is asked over and over, and parsing it
+//! costs a model call every time. This cache keeps the parsed
+//! `SearchParams` (never the reply) for a while, so a repeat skips the
+//! intent call but still gets a fresh database search and its own
+//! personalized answer.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Keys
+//! | Part | Why |
+//! |------|-----|
+//! | normalized message | case, spacing and end punctuation don't change the intent |
+//! | local day | the prompt's `{today}` anchors any date the model resolves ("the 14th") |
+//! | preference fingerprint | users with different preferences never share an entry |
+//!
+//! Relative phrases stay phrases in `SearchParams::when` and are resolved
+//! at search time, so a cached "this weekend" is still right on Sunday.
+//!
+//! ## Limits
+//! Entries live `INTENT_CACHE_TTL` (10 minutes). At `INTENT_CACHE_CAPACITY`
+//! expired entries are dropped first, then the oldest. Hits and misses are
+//! counted for `GET /api/admin/llm/usage`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+
+use crate::models::{IntentCacheStats, UserProfile};
+use crate::services::llm::SearchParams;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// How long a parsed intent is reused.
+pub const INTENT_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Most intents kept at once.
+pub const INTENT_CACHE_CAPACITY: usize = 1000;
+
+// =============================================================================
+// KEY
+// =============================================================================
+
+/// What a cached intent is valid for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntentKey {
+    message: String,
+    day: NaiveDate,
+    fingerprint: u64,
+}
+
+impl IntentKey {
+    /// The key for `message`, parsed on local day `day` for `profile`.
+    pub fn new(message: &str, day: NaiveDate, profile: Option<&UserProfile>) -> Self {
+        Self {
+            message: normalize_message(message),
+            day,
+            fingerprint: preference_fingerprint(profile),
+        }
+    }
+}
+
+/// Lowercased, single-spaced, without trailing `?`, `!` or `.`.
+fn normalize_message(message: &str) -> String {
+    message
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['?', '!', '.'])
+        .trim_end()
+        .to_lowercase()
+}
+
+/// A hash of the profile's category preferences (0 when anonymous).
+/// Order doesn't matter; categories and weights do.
+fn preference_fingerprint(profile: Option<&UserProfile>) -> u64 {
+    let Some(profile) = profile else {
+        return 0;
+    };
+
+    let mut preferences: Vec<(&str, i32)> = profile
+        .preferences
+        .iter()
+        .map(|p| (p.category.as_str(), p.weight))
+        .collect();
+    preferences.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    preferences.hash(&mut hasher);
+    hasher.finish()
+}
+
+// =============================================================================
+// CACHE
+// =============================================================================
+
+/// Parsed intents by key, shared through the router state.
+pub struct IntentCache {
+    entries: Mutex<HashMap<IntentKey, (Instant, SearchParams)>>,
+    ttl: Duration,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl IntentCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached intent for `key`, if it hasn't expired. Counts a hit or
+    /// a miss.
+    pub fn get(&self, key: &IntentKey) -> Option<SearchParams> {
+        let entries = self.entries.lock().expect("intent cache lock");
+        let found = entries
+            .get(key)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, params)| params.clone());
+
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// Stores `params` for `key`, making room if the cache is full.
+    pub fn insert(&self, key: IntentKey, params: SearchParams) {
+        let mut entries = self.entries.lock().expect("intent cache lock");
+
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries.iter().min_by_key(|(_, (stored, _))| *stored).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(key, (Instant::now(), params));
+    }
+
+    /// Hit and miss counts since startup, and the current size.
+    pub fn stats(&self) -> IntentCacheStats {
+        IntentCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().expect("intent cache lock").len(),
+        }
+    }
+}
+
+impl Default for IntentCache {
+    fn default() -> Self {
+        Self::new(INTENT_CACHE_TTL, INTENT_CACHE_CAPACITY)
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, 23).unwrap()
+    }
+
+    fn jazz() -> SearchParams {
+        SearchParams { query: Some("jazz".to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn keys_ignore_case_spacing_and_punctuation() {
+        assert_eq!(
+            IntentKey::new("What's happening  this weekend?", day(), None),
+            IntentKey::new("what's happening this weekend", day(), None)
+        );
+        assert_ne!(
+            IntentKey::new("jazz this weekend", day(), None),
+            IntentKey::new("jazz this weekend", day().succ_opt().unwrap(), None)
+        );
+    }
+
+    #[test]
+    fn entries_expire_and_are_counted() {
+        let cache = IntentCache::default();
+        let key = IntentKey::new("jazz?", day(), None);
+
+        assert!(cache.get(&key).is_none());
+        cache.insert(key.clone(), jazz());
+        assert_eq!(cache.get(&key).unwrap().query.as_deref(), Some("jazz"));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        let expired = IntentCache::new(Duration::ZERO, 10);
+        expired.insert(key.clone(), jazz());
+        assert!(expired.get(&key).is_none());
+    }
+
+    #[test]
+    fn full_caches_drop_the_oldest_entry() {
+        let cache = IntentCache::new(INTENT_CACHE_TTL, 2);
+        let keys: Vec<IntentKey> = ["a", "b", "c"].iter().map(|m| IntentKey::new(m, day(), None)).collect();
+
+        for key in &keys {
+            cache.insert(key.clone(), jazz());
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        assert_eq!(cache.stats().entries, 2);
+        assert!(cache.get(&keys[0]).is_none());
+        assert!(cache.get(&keys[2]).is_some());
+    }
+}
@@ -0,0 +1,406 @@
+//! # schema.org JSON-LD
+//!
+//! Maps our `Event` to a schema.org `Event` object so event pages can be
+//! indexed by search engines and read by other calendar tools.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Both Directions
+//! The same types are used for reading: many venue sites embed
+//! `<script type="application/ld+json">` blocks, and the scraper can
+//! deserialize those into `JsonLdEvent` and call `into_create_event`.
+//!
+//! ## Output
+//! ```json
+//! {
+//!   "@context": "https://schema.org",
+//!   "@type": "Event",
+//!   "name": "Jazz Night",
+//!   "startDate": "2026-01-25T20:00:00Z",
+//!   "endDate": "2026-01-25T23:00:00Z",
+//!   "eventStatus": "https://schema.org/EventScheduled",
+//!   "location": { "@type": "Place", "name": "The Blue Note", "address": "..." },
+//!   "url": "https://thebluenote.com/events/jazz-night",
+//!   "isAccessibleForFree": false,
+//!   "offers": { "@type": "AggregateOffer", "lowPrice": 10.0, "highPrice": 15.0,
+//!               "priceCurrency": "USD", "availability": "https://schema.org/InStock" }
+//! }
+//! ```
+//!
+//! ## Status Mapping
+//! | EventStatus | schema.org |
+//! |-------------|------------|
+//! | scheduled | `eventStatus: EventScheduled` |
+//! | cancelled | `eventStatus: EventCancelled` |
+//! | postponed | `eventStatus: EventPostponed` |
+//! | sold_out | `eventStatus: EventScheduled` + `offers.availability: SoldOut` |
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CreateEvent, Event, EventStatus};
+
+// =============================================================================
+// CONSTANTS
+// =============================================================================
+
+const SCHEMA_CONTEXT: &str = "https://schema.org";
+const SCHEMA_PREFIX: &str = "https://schema.org/";
+
+/// Ticket prices are always in US dollars.
+const PRICE_CURRENCY: &str = "USD";
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// A schema.org `Event`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLdEvent {
+    #[serde(rename = "@context", default = "default_context")]
+    pub context: String,
+
+    #[serde(rename = "@type", default = "default_event_type")]
+    pub kind: String,
+
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    pub start_date: DateTime<Utc>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<DateTime<Utc>>,
+
+    /// Full URL such as `https://schema.org/EventCancelled`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_status: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<JsonLdPlace>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_accessible_for_free: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offers: Option<JsonLdOffer>,
+}
+
+/// A schema.org `Place` (the venue).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLdPlace {
+    #[serde(rename = "@type", default = "default_place_type")]
+    pub kind: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Plain-text address (schema.org also allows a PostalAddress object)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<JsonLdGeo>,
+}
+
+/// A schema.org `GeoCoordinates`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonLdGeo {
+    #[serde(rename = "@type", default = "default_geo_type")]
+    pub kind: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// A schema.org `AggregateOffer` (ticket price range).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLdOffer {
+    #[serde(rename = "@type", default = "default_offer_type")]
+    pub kind: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_price: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_price: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_currency: Option<String>,
+
+    /// Full URL such as `https://schema.org/SoldOut`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<String>,
+}
+
+fn default_context() -> String {
+    SCHEMA_CONTEXT.to_string()
+}
+
+fn default_event_type() -> String {
+    "Event".to_string()
+}
+
+fn default_place_type() -> String {
+    "Place".to_string()
+}
+
+fn default_geo_type() -> String {
+    "GeoCoordinates".to_string()
+}
+
+fn default_offer_type() -> String {
+    "AggregateOffer".to_string()
+}
+
+// =============================================================================
+// EVENT -> JSON-LD
+// =============================================================================
+
+impl From<&Event> for JsonLdEvent {
+    fn from(event: &Event) -> Self {
+        let event_status = match event.status {
+            EventStatus::Scheduled | EventStatus::SoldOut => "EventScheduled",
+            EventStatus::Cancelled => "EventCancelled",
+            EventStatus::Postponed => "EventPostponed",
+        };
+
+        let has_place = event.venue.is_some()
+            || event.venue_address.is_some()
+            || event.latitude.is_some();
+        let location = has_place.then(|| JsonLdPlace {
+            kind: default_place_type(),
+            name: event.venue.clone(),
+            address: event.venue_address.clone(),
+            geo: event
+                .latitude
+                .zip(event.longitude)
+                .map(|(latitude, longitude)| JsonLdGeo {
+                    kind: default_geo_type(),
+                    latitude,
+                    longitude,
+                }),
+        });
+
+        let sold_out = event.status == EventStatus::SoldOut;
+        let has_offer = event.price_min.is_some() || event.price_max.is_some() || sold_out;
+        let offers = has_offer.then(|| JsonLdOffer {
+            kind: default_offer_type(),
+            low_price: event.price_min,
+            high_price: event.price_max,
+            price_currency: Some(PRICE_CURRENCY.to_string()),
+            availability: Some(schema_url(if sold_out { "SoldOut" } else { "InStock" })),
+        });
+
+        JsonLdEvent {
+            context: default_context(),
+            kind: default_event_type(),
+            name: event.title.clone(),
+            description: event.description.clone(),
+            start_date: event.start_time,
+            end_date: event.end_time,
+            event_status: Some(schema_url(event_status)),
+            location,
+            url: Some(event.source_url.clone()),
+            image: event.image_url.clone(),
+            is_accessible_for_free: Some(event.is_free),
+            offers,
+        }
+    }
+}
+
+// =============================================================================
+// JSON-LD -> CREATE EVENT
+// =============================================================================
+
+impl JsonLdEvent {
+    /// Converts a parsed JSON-LD event into a `CreateEvent` for ingestion.
+    ///
+    /// `page_url` is used as the `source_url` when the JSON-LD has no `url`.
+    pub fn into_create_event(self, page_url: &str) -> CreateEvent {
+        let sold_out = self
+            .offers
+            .as_ref()
+            .and_then(|offer| offer.availability.as_deref())
+            .is_some_and(|availability| schema_name(availability) == "SoldOut");
+
+        let status = match self.event_status.as_deref().map(schema_name) {
+            Some("EventCancelled") => EventStatus::Cancelled,
+            Some("EventPostponed") => EventStatus::Postponed,
+            _ if sold_out => EventStatus::SoldOut,
+            _ => EventStatus::Scheduled,
+        };
+
+        let (venue, venue_address, geo) = match self.location {
+            Some(place) => (place.name, place.address, place.geo),
+            None => (None, None, None),
+        };
+        let (price_min, price_max) = self
+            .offers
+            .map_or((None, None), |offer| (offer.low_price, offer.high_price));
+
+        CreateEvent {
+            title: self.name,
+            description: self.description,
+            venue,
+            venue_id: None,
+            venue_address,
+            location: None,
+            source_url: self.url.unwrap_or_else(|| page_url.to_string()),
+            source_name: None,
+            start_time: self.start_date,
+            end_time: self.end_date,
+            categories: None,
+            tags: vec![],
+            price_min,
+            price_max,
+            is_free: self.is_accessible_for_free,
+            outdoor: false,
+            family_friendly: false,
+            image_url: self.image,
+            status,
+            latitude: geo.as_ref().map(|g| g.latitude),
+            longitude: geo.as_ref().map(|g| g.longitude),
+        }
+    }
+}
+
+/// `"EventCancelled"` -> `"https://schema.org/EventCancelled"`.
+fn schema_url(name: &str) -> String {
+    format!("{}{}", SCHEMA_PREFIX, name)
+}
+
+/// Strips the schema.org prefix, accepting `http://`, `https://` or none.
+fn schema_name(value: &str) -> &str {
+    value.rsplit('/').next().unwrap_or(value)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn sample_event() -> Event {
+        let created = Utc.with_ymd_and_hms(2026, 1, 17, 12, 0, 0).unwrap();
+        Event {
+            id: Uuid::nil(),
+            title: "Jazz Night".to_string(),
+            description: Some("Live jazz".to_string()),
+            venue: Some("The Blue Note".to_string()),
+            venue_id: None,
+            venue_address: Some("2303 E 3rd St, Tulsa, OK".to_string()),
+            location: Some("Downtown Tulsa".to_string()),
+            source_url: "https://thebluenote.com/events/jazz-night".to_string(),
+            source_name: None,
+            start_time: Utc.with_ymd_and_hms(2026, 1, 25, 20, 0, 0).unwrap(),
+            end_time: Some(Utc.with_ymd_and_hms(2026, 1, 25, 23, 0, 0).unwrap()),
+            categories: None,
+            tags: vec![],
+            price_min: Some(10.0),
+            price_max: Some(15.0),
+            is_free: false,
+            outdoor: false,
+            family_friendly: false,
+            image_url: Some("https://thebluenote.com/jazz.jpg".to_string()),
+            status: EventStatus::Scheduled,
+            latitude: Some(36.1591),
+            longitude: Some(-95.9936),
+            archived_at: None,
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    /// Serializes to JSON text and parses it back, like a scraper would.
+    fn round_trip(event: &Event) -> CreateEvent {
+        let json = serde_json::to_string(&JsonLdEvent::from(event)).unwrap();
+        serde_json::from_str::<JsonLdEvent>(&json)
+            .unwrap()
+            .into_create_event("https://fallback.example")
+    }
+
+    #[test]
+    fn serializes_schema_org_field_names() {
+        let json = serde_json::to_value(JsonLdEvent::from(&sample_event())).unwrap();
+
+        assert_eq!(json["@context"], "https://schema.org");
+        assert_eq!(json["@type"], "Event");
+        assert_eq!(json["name"], "Jazz Night");
+        assert_eq!(json["startDate"], "2026-01-25T20:00:00Z");
+        assert_eq!(json["endDate"], "2026-01-25T23:00:00Z");
+        assert_eq!(json["eventStatus"], "https://schema.org/EventScheduled");
+        assert_eq!(json["location"]["@type"], "Place");
+        assert_eq!(json["location"]["name"], "The Blue Note");
+        assert_eq!(json["url"], "https://thebluenote.com/events/jazz-night");
+        assert_eq!(json["offers"]["lowPrice"], 10.0);
+    }
+
+    #[test]
+    fn round_trips_event_fields() {
+        let event = sample_event();
+        let parsed = round_trip(&event);
+
+        assert_eq!(parsed.title, event.title);
+        assert_eq!(parsed.description, event.description);
+        assert_eq!(parsed.venue, event.venue);
+        assert_eq!(parsed.venue_address, event.venue_address);
+        assert_eq!(parsed.source_url, event.source_url);
+        assert_eq!(parsed.start_time, event.start_time);
+        assert_eq!(parsed.end_time, event.end_time);
+        assert_eq!(parsed.price_min, event.price_min);
+        assert_eq!(parsed.price_max, event.price_max);
+        assert_eq!(parsed.is_free, Some(event.is_free));
+        assert_eq!(parsed.image_url, event.image_url);
+        assert_eq!(parsed.status, event.status);
+        assert_eq!(parsed.latitude, event.latitude);
+        assert_eq!(parsed.longitude, event.longitude);
+    }
+
+    #[test]
+    fn round_trips_every_status() {
+        for status in [
+            EventStatus::Scheduled,
+            EventStatus::Cancelled,
+            EventStatus::Postponed,
+            EventStatus::SoldOut,
+        ] {
+            let event = Event { status, ..sample_event() };
+            assert_eq!(round_trip(&event).status, status);
+        }
+    }
+
+    #[test]
+    fn reads_minimal_third_party_json_ld() {
+        let json = r#"{
+            "@context": "http://schema.org",
+            "@type": "Event",
+            "name": "Open Mic",
+            "startDate": "2026-02-06T19:00:00-06:00",
+            "eventStatus": "EventCancelled"
+        }"#;
+        let parsed = serde_json::from_str::<JsonLdEvent>(json)
+            .unwrap()
+            .into_create_event("https://venue.example/open-mic");
+
+        assert_eq!(parsed.title, "Open Mic");
+        assert_eq!(parsed.start_time, Utc.with_ymd_and_hms(2026, 2, 7, 1, 0, 0).unwrap());
+        assert_eq!(parsed.source_url, "https://venue.example/open-mic");
+        assert_eq!(parsed.status, EventStatus::Cancelled);
+        assert_eq!(parsed.venue, None);
+    }
+}
@@ -0,0 +1,172 @@
+//! # LLM Health
+//!
+//! Whether chat's model answers, so ops hear about it before users do.
+//! `LlmHealthCheck` sends the configured provider a one-word prompt and
+//! keeps the result for `LLM_HEALTH_TTL`, so probes hitting
+//! `GET /api/health/llm` (or `GET /api/ready`) cost at most one model
+//! call per five minutes.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Semantics
+//! - `ok` is the latest ping's outcome; a spent daily budget or missing
+//!   API key counts as not ok, since chat is degraded either way
+//! - `last_error` is the latest failure, kept after the model recovers
+//!   so a flapping provider is visible
+//! - Concurrent checks with a stale result wait for one ping instead of
+//!   each sending their own
+//!
+//! ## Time
+//! `check_at` takes the time as an argument, so tests can run the clock.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::services::llm_provider::{GenerateOptions, LlmMessage, LlmProvider};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// How long a ping's result is reused.
+pub const LLM_HEALTH_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How long a ping may take before it counts as failed.
+pub const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the ping asks.
+const PING_PROMPT: &str = "Reply with the word: ok";
+
+// =============================================================================
+// HEALTH
+// =============================================================================
+
+/// The model's health as of the latest ping.
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmHealth {
+    pub provider: String,
+    pub model: String,
+    pub ok: bool,
+    /// How long the latest ping took (until the timeout, if it hung)
+    pub latency_ms: u64,
+    /// The latest failure, even if a later ping succeeded
+    pub last_error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Cached pings, shared through the router state.
+pub struct LlmHealthCheck {
+    ttl: Duration,
+    /// The latest result and when it was taken
+    last: Mutex<Option<(Instant, LlmHealth)>>,
+}
+
+impl LlmHealthCheck {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, last: Mutex::new(None) }
+    }
+
+    /// `provider`'s health, pinging it if the cached result is stale.
+    pub async fn check(&self, provider: &dyn LlmProvider) -> LlmHealth {
+        self.check_at(provider, Instant::now()).await
+    }
+
+    /// `check` as of `now`.
+    pub async fn check_at(&self, provider: &dyn LlmProvider, now: Instant) -> LlmHealth {
+        // Held across the ping: concurrent callers wait for its result
+        let mut last = self.last.lock().await;
+        if let Some((pinged, health)) = last.as_ref() {
+            if now.saturating_duration_since(*pinged) < self.ttl {
+                return health.clone();
+            }
+        }
+
+        let previous_error = last.as_ref().and_then(|(_, health)| health.last_error.clone());
+        let health = ping(provider, previous_error).await;
+        if !health.ok {
+            tracing::warn!(provider = %health.provider, error = ?health.last_error, "LLM health check failed");
+        }
+
+        *last = Some((now, health.clone()));
+        health
+    }
+}
+
+impl Default for LlmHealthCheck {
+    fn default() -> Self {
+        Self::new(LLM_HEALTH_TTL)
+    }
+}
+
+/// One ping, carrying `previous_error` forward if it succeeds.
+async fn ping(provider: &dyn LlmProvider, previous_error: Option<String>) -> LlmHealth {
+    let started = Instant::now();
+    let messages = [LlmMessage::user(PING_PROMPT)];
+    let result = tokio::time::timeout(PING_TIMEOUT, provider.generate(&messages, &[], GenerateOptions::default())).await;
+    let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("no answer within {} seconds", PING_TIMEOUT.as_secs())),
+    };
+
+    LlmHealth {
+        provider: provider.name().to_string(),
+        model: provider.model().to_string(),
+        ok: error.is_none(),
+        latency_ms,
+        last_error: error.or(previous_error),
+        checked_at: Utc::now(),
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use crate::services::llm::LlmError;
+    use crate::services::llm_provider::{LlmResponse, MockProvider};
+
+    #[tokio::test]
+    async fn pings_are_cached_and_failures_remembered() {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let switch = healthy.clone();
+        let model = MockProvider::new(move |_, _| match switch.load(Ordering::SeqCst) {
+            true => Ok(LlmResponse::text("ok")),
+            false => Err(LlmError::ServiceUnavailable),
+        });
+        let check = LlmHealthCheck::default();
+        let start = Instant::now();
+
+        let first = check.check_at(&model, start).await;
+        assert!(first.ok && first.last_error.is_none());
+        assert_eq!((first.provider.as_str(), first.model.as_str()), ("mock", "mock"));
+
+        // Within the TTL the failure isn't seen, and nothing is sent
+        healthy.store(false, Ordering::SeqCst);
+        assert!(check.check_at(&model, start + Duration::from_secs(60)).await.ok);
+        assert_eq!(model.calls(), 1);
+
+        let failing = check.check_at(&model, start + LLM_HEALTH_TTL).await;
+        assert!(!failing.ok);
+        assert_eq!(failing.last_error.as_deref(), Some(LlmError::ServiceUnavailable.to_string().as_str()));
+        assert_eq!(model.calls(), 2);
+
+        // Recovered, with the failure still on record
+        healthy.store(true, Ordering::SeqCst);
+        let recovered = check.check_at(&model, start + LLM_HEALTH_TTL * 2).await;
+        assert!(recovered.ok);
+        assert_eq!(recovered.last_error, failing.last_error);
+    }
+}
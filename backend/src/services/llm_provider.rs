@@ -0,0 +1,1315 @@
+//! # LLM Providers
+//!
+//! One interface over the chat models we can talk to, so the chat flow in
+//! `services::llm` doesn't care whose model answers.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Providers
+//! | `LLM_PROVIDER` | Provider | Default `LLM_MODEL` |
+//! |----------------|----------|---------------------|
+//! | `gemini` (default) | `GeminiProvider` - Google `generateContent` | `gemini-1.5-flash` |
+//! | `openai` | `OpenAiProvider` - any `/chat/completions` API (OpenAI, Groq, Ollama) | `gpt-4o-mini` |
+//! | `mock` | `MockProvider` - echoes the message (tests, offline frontend work) | - |
+//!
+//! The provider is built once at startup (`from_env`) and reaches handlers
+//! through the router state (`routes::AppState`), so tests hand the chat
+//! routes a `MockProvider` instead of needing an API key.
+//!
+//! ## Conversation Model
+//! A request is a list of `LlmMessage`s (system, user and model turns)
+//! plus the tools the model may call. A model turn holds text and/or
+//! `ToolCall`s; the user turn after it answers each call with a
+//! `ToolResult`. Each provider translates to and from its own wire format.
+//!
+//! ## Retries
+//! Rate limits (429) and server errors (500/502/503) are retried: up to
+//! `RetryPolicy::max_attempts` tries, waiting the server's `Retry-After`
+//! or an exponential backoff with jitter in between. Other errors (400,
+//! 401, ...) fail at once. A request that never gets through is
+//! `LlmError::RateLimited`, which chat answers with a 503.
+//!
+//! ## Wire Fixtures
+//! The Gemini wire types mirror Google's JSON; the fixtures under
+//! `tests/fixtures/gemini/` are captured payloads that the tests parse, so
+//! schema drift shows up in `cargo test` instead of in production.
+//!
+//! ## Environment Variables
+//! ```text
+//! LLM_PROVIDER=gemini                       # gemini | openai | mock
+//! LLM_MODEL=gemini-1.5-flash                # model for the chosen provider
+//! GEMINI_API_KEY=...                        # gemini; without it, chat is a 503
+//! OPENAI_BASE_URL=https://api.openai.com/v1 # Groq: https://api.groq.com/openai/v1
+//!                                           # Ollama: http://localhost:11434/v1
+//! OPENAI_API_KEY=...                        # openai; not needed for Ollama
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::async_trait;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::services::llm::LlmError;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Gemini API root; the model is part of the path.
+const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Gemini model when `LLM_MODEL` isn't set.
+const DEFAULT_GEMINI_MODEL: &str = "gemini-1.5-flash";
+
+/// OpenAI-compatible API root when `OPENAI_BASE_URL` isn't set.
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// OpenAI-compatible model when `LLM_MODEL` isn't set.
+const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+
+/// Sampling temperature. Low: answers should stick to the events we hand
+/// the model.
+const TEMPERATURE: f64 = 0.2;
+
+/// Longest reply we ask for, in tokens.
+const MAX_OUTPUT_TOKENS: u32 = 1024;
+
+/// When and how often a failed request is tried again.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Tries in total, the first one included
+    pub max_attempts: u32,
+    /// Backoff before the second try; doubled for each one after
+    pub base_delay: Duration,
+    /// Longest wait between tries, `Retry-After` included. Someone is
+    /// waiting on the chat reply.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+/// A set, non-empty environment variable.
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+// =============================================================================
+// CONVERSATION TYPES
+// =============================================================================
+
+/// Who a message is from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Instructions for the model
+    System,
+    /// The person chatting, or our answers to the model's tool calls
+    User,
+    /// The model's own earlier turns
+    Model,
+}
+
+/// One piece of a message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Text(String),
+    ToolCall(ToolCall),
+    ToolResult(ToolResult),
+}
+
+/// A tool call the model asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    /// The provider's id for the call, echoed on the result (OpenAI).
+    /// Gemini matches results by name and has none.
+    pub id: Option<String>,
+    pub name: String,
+    pub args: Value,
+}
+
+/// Our answer to a `ToolCall`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolResult {
+    pub call_id: Option<String>,
+    pub name: String,
+    pub response: Value,
+}
+
+/// One turn of the conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmMessage {
+    pub role: Role,
+    pub parts: Vec<Part>,
+}
+
+impl LlmMessage {
+    pub fn system(text: impl Into<String>) -> Self {
+        Self { role: Role::System, parts: vec![Part::Text(text.into())] }
+    }
+
+    pub fn user(text: impl Into<String>) -> Self {
+        Self { role: Role::User, parts: vec![Part::Text(text.into())] }
+    }
+
+    pub fn model(text: impl Into<String>) -> Self {
+        Self { role: Role::Model, parts: vec![Part::Text(text.into())] }
+    }
+
+    /// The user turn that answers a model turn's tool calls.
+    pub fn tool_results(results: Vec<ToolResult>) -> Self {
+        Self {
+            role: Role::User,
+            parts: results.into_iter().map(Part::ToolResult).collect(),
+        }
+    }
+
+    /// Concatenated text parts.
+    pub fn text(&self) -> String {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A tool the model may call. `parameters` is a JSON schema for the
+/// arguments object; `None` for a tool without arguments.
+#[derive(Debug, Clone)]
+pub struct ToolSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: Option<Value>,
+}
+
+/// Per-request switches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenerateOptions {
+    /// Keep the tools declared (the history mentions them) but make the
+    /// model answer in text.
+    pub tools_disabled: bool,
+    /// Ask for a bare JSON object (a request, not a guarantee).
+    pub json: bool,
+}
+
+/// Tokens billed for one call, as the provider reports them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenUsage {
+    /// Prompt tokens (history, tools and system prompt included)
+    pub tokens_in: i32,
+    /// Generated tokens
+    pub tokens_out: i32,
+}
+
+/// The model's turn: text, tool calls, or both.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LlmResponse {
+    pub text: String,
+    pub tool_calls: Vec<ToolCall>,
+    /// `None` if the provider didn't say
+    pub usage: Option<TokenUsage>,
+}
+
+impl LlmResponse {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self { text: text.into(), ..Default::default() }
+    }
+
+    pub fn tool_call(name: impl Into<String>, args: Value) -> Self {
+        Self {
+            tool_calls: vec![ToolCall { id: None, name: name.into(), args }],
+            ..Default::default()
+        }
+    }
+
+    /// This turn as history for the next request.
+    pub fn to_message(&self) -> LlmMessage {
+        let text = (!self.text.is_empty()).then(|| Part::Text(self.text.clone()));
+        LlmMessage {
+            role: Role::Model,
+            parts: text.into_iter().chain(self.tool_calls.iter().cloned().map(Part::ToolCall)).collect(),
+        }
+    }
+
+    /// The reply text; a turn with only tool calls has none.
+    pub fn into_text(self) -> Result<String, LlmError> {
+        if self.text.trim().is_empty() {
+            return Err(LlmError::EmptyResponse("tool call instead of text".to_string()));
+        }
+        Ok(self.text)
+    }
+}
+
+// =============================================================================
+// PROVIDER INTERFACE
+// =============================================================================
+
+/// Something that can produce the model's next turn.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Short name for logs ("gemini", "openai", "mock").
+    fn name(&self) -> &str;
+
+    /// The model requests go to, as recorded in `llm_calls`.
+    fn model(&self) -> &str;
+
+    /// The model's next turn after `messages`, with `tools` on offer.
+    ///
+    /// # Errors
+    /// * `LlmError::MissingApiKey` - the provider needs a key that isn't set
+    /// * `LlmError::ServiceUnavailable` - the API can't be reached
+    /// * `LlmError::RateLimited` - 429/5xx on every retry
+    /// * `LlmError::Api` - other non-200 status, with the provider's message
+    /// * `LlmError::EmptyResponse` - blocked, or neither text nor tool calls
+    /// * `LlmError::HttpError` - network failure or an unparseable body
+    async fn generate(
+        &self,
+        messages: &[LlmMessage],
+        tools: &[ToolSpec],
+        options: GenerateOptions,
+    ) -> Result<LlmResponse, LlmError>;
+}
+
+/// The provider as router state.
+pub type SharedProvider = Arc<dyn LlmProvider>;
+
+/// The provider chosen by `LLM_PROVIDER` (default Gemini).
+///
+/// A missing API key isn't an error here: the server still starts and
+/// chat answers 503 until the key is set. An unknown provider name is.
+pub fn from_env() -> Result<SharedProvider, LlmError> {
+    let model = env_var("LLM_MODEL");
+
+    match env_var("LLM_PROVIDER").map(|name| name.trim().to_lowercase()).as_deref() {
+        None | Some("gemini") => Ok(Arc::new(GeminiProvider::from_env(model))),
+        Some("openai") => Ok(Arc::new(OpenAiProvider::from_env(model))),
+        Some("mock") => Ok(Arc::new(MockProvider::echo())),
+        Some(other) => Err(LlmError::UnknownProvider(other.to_string())),
+    }
+}
+
+/// Sends `request`, trying again on 429/500/502/503 as `policy` allows.
+///
+/// # Returns
+/// * `Ok(Response)` - a 2xx response
+/// * `Err(LlmError::RateLimited)` - still a retryable status after the
+///   last attempt
+/// * `Err(LlmError::Api)` - any other non-2xx status (not retried)
+/// * `Err(LlmError::ServiceUnavailable)` - couldn't connect, or timed out
+/// * `Err(LlmError::HttpError)` - any other transport failure
+async fn send_with_retry(provider: &str, policy: RetryPolicy, request: RequestBuilder) -> Result<Response, LlmError> {
+    let mut attempt = 1;
+    loop {
+        // JSON bodies are in memory, so the builder can always be copied
+        let response = match request.try_clone().expect("request body is not a stream").send().await {
+            Ok(response) => response,
+            Err(e) if e.is_connect() || e.is_timeout() => {
+                tracing::warn!(provider, error = %e, "LLM API unreachable");
+                return Err(LlmError::ServiceUnavailable);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let status = response.status();
+
+        if status.is_success() {
+            if attempt > 1 {
+                tracing::info!(provider, attempts = attempt, "LLM request succeeded after retrying");
+            }
+            return Ok(response);
+        }
+
+        if !is_retryable(status) {
+            let body = response.text().await.unwrap_or_default();
+            return Err(api_error(status.as_u16(), &body));
+        }
+
+        if attempt >= policy.max_attempts {
+            tracing::warn!(provider, attempts = attempt, status = status.as_u16(), "LLM request gave up");
+            return Err(LlmError::RateLimited { attempts: attempt, status: status.as_u16() });
+        }
+
+        let delay = retry_after(response.headers())
+            .unwrap_or_else(|| backoff(policy, attempt, rand::thread_rng().gen_range(0.5..=1.0)))
+            .min(policy.max_delay);
+        tracing::warn!(
+            provider,
+            attempt,
+            status = status.as_u16(),
+            delay_ms = delay.as_millis() as u64,
+            "LLM request failed; retrying"
+        );
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Statuses worth another try: rate limits and transient server errors.
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+    )
+}
+
+/// The wait after failed attempt `attempt`: `base_delay` doubled per
+/// attempt, scaled by `jitter` (0.5-1.0) so clients don't retry in step.
+fn backoff(policy: RetryPolicy, attempt: u32, jitter: f64) -> Duration {
+    policy.base_delay.saturating_mul(1 << (attempt - 1).min(16)).mul_f64(jitter)
+}
+
+/// `Retry-After` in seconds. The HTTP-date form isn't used by the APIs
+/// we call and falls back to backoff.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds: u64 = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Turns a non-200 response into `LlmError::Api`, keeping the provider's
+/// own message when the body has one (Gemini and OpenAI both send
+/// `{"error": {"message": ...}}`).
+fn api_error(status: u16, body: &str) -> LlmError {
+    let message = serde_json::from_str::<ErrorBody>(body)
+        .map(|parsed| parsed.error.message)
+        .unwrap_or_else(|_| body.trim().to_string());
+
+    LlmError::Api { status, message }
+}
+
+/// Body of a non-200 response.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorDetail {
+    message: String,
+}
+
+// =============================================================================
+// GEMINI
+// =============================================================================
+
+/// Google Gemini through `generateContent`.
+pub struct GeminiProvider {
+    http: Client,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    retry: RetryPolicy,
+}
+
+impl GeminiProvider {
+    /// `model` (default `gemini-1.5-flash`), keyed by `GEMINI_API_KEY`.
+    pub fn from_env(model: Option<String>) -> Self {
+        let model = model.unwrap_or_else(|| DEFAULT_GEMINI_MODEL.to_string());
+        Self {
+            model: model.clone(),
+            ..Self::with_endpoint(
+                format!("{}/models/{}:generateContent", GEMINI_API_BASE, model),
+                env_var("GEMINI_API_KEY"),
+            )
+        }
+    }
+
+    /// A provider for the `generateContent` URL `url` (tests point this at
+    /// a stand-in server).
+    pub fn with_endpoint(url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            http: Client::new(),
+            url: url.into(),
+            api_key,
+            model: DEFAULT_GEMINI_MODEL.to_string(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Replaces the default retry policy.
+    #[allow(dead_code)] // Used by tests
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait]
+impl LlmProvider for GeminiProvider {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn generate(
+        &self,
+        messages: &[LlmMessage],
+        tools: &[ToolSpec],
+        options: GenerateOptions,
+    ) -> Result<LlmResponse, LlmError> {
+        let api_key = self.api_key.as_deref().ok_or(LlmError::MissingApiKey)?;
+
+        let request = self
+            .http
+            .post(&self.url)
+            .header("x-goog-api-key", api_key)
+            .json(&GeminiRequest::new(messages, tools, options));
+
+        let response = send_with_retry(self.name(), self.retry, request).await?;
+        response.json::<GeminiResponse>().await?.into_response()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Gemini wire types (generateContent)
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<GeminiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<ToolConfig>,
+    generation_config: GenerationConfig,
+}
+
+/// One turn of the conversation. `role` is "user" or "model"; the system
+/// instruction has none.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+/// Text, a call the model wants made, or our answer to one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_call: Option<FunctionCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_response: Option<FunctionResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FunctionCall {
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FunctionResponse {
+    name: String,
+    response: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiTool {
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionDeclaration {
+    name: &'static str,
+    description: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolConfig {
+    function_calling_config: FunctionCallingConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionCallingConfig {
+    /// "AUTO" (the default) or "NONE" (text only)
+    mode: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    temperature: f64,
+    max_output_tokens: u32,
+    /// "application/json" asks for a bare JSON reply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<&'static str>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    prompt_feedback: Option<PromptFeedback>,
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    content: Option<GeminiContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    block_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: i32,
+    #[serde(default)]
+    candidates_token_count: i32,
+}
+
+impl GeminiRequest {
+    fn new(messages: &[LlmMessage], tools: &[ToolSpec], options: GenerateOptions) -> Self {
+        let system: Vec<GeminiPart> = messages
+            .iter()
+            .filter(|message| message.role == Role::System)
+            .flat_map(|message| message.parts.iter().map(GeminiPart::from))
+            .collect();
+
+        let contents = messages
+            .iter()
+            .filter(|message| message.role != Role::System)
+            .map(|message| GeminiContent {
+                role: Some(if message.role == Role::Model { "model" } else { "user" }.to_string()),
+                parts: message.parts.iter().map(GeminiPart::from).collect(),
+            })
+            .collect();
+
+        let tools = if tools.is_empty() {
+            Vec::new()
+        } else {
+            vec![GeminiTool {
+                function_declarations: tools
+                    .iter()
+                    .map(|tool| FunctionDeclaration {
+                        name: tool.name,
+                        description: tool.description,
+                        parameters: tool.parameters.clone(),
+                    })
+                    .collect(),
+            }]
+        };
+
+        Self {
+            contents,
+            system_instruction: (!system.is_empty()).then_some(GeminiContent { role: None, parts: system }),
+            tools,
+            tool_config: options.tools_disabled.then_some(ToolConfig {
+                function_calling_config: FunctionCallingConfig { mode: "NONE" },
+            }),
+            generation_config: GenerationConfig {
+                temperature: TEMPERATURE,
+                max_output_tokens: MAX_OUTPUT_TOKENS,
+                response_mime_type: options.json.then_some("application/json"),
+            },
+        }
+    }
+}
+
+impl From<&Part> for GeminiPart {
+    fn from(part: &Part) -> Self {
+        match part {
+            Part::Text(text) => Self { text: Some(text.clone()), ..Default::default() },
+            Part::ToolCall(call) => Self {
+                function_call: Some(FunctionCall { name: call.name.clone(), args: call.args.clone() }),
+                ..Default::default()
+            },
+            Part::ToolResult(result) => Self {
+                function_response: Some(FunctionResponse {
+                    name: result.name.clone(),
+                    response: result.response.clone(),
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl GeminiResponse {
+    /// The first candidate's text and function calls, or why there are
+    /// neither.
+    fn into_response(self) -> Result<LlmResponse, LlmError> {
+        let Some(candidate) = self.candidates.into_iter().next() else {
+            let reason = self
+                .prompt_feedback
+                .and_then(|feedback| feedback.block_reason)
+                .unwrap_or_else(|| "no candidates".to_string());
+            return Err(LlmError::EmptyResponse(reason));
+        };
+
+        let mut response = LlmResponse {
+            usage: self.usage_metadata.map(|usage| TokenUsage {
+                tokens_in: usage.prompt_token_count,
+                tokens_out: usage.candidates_token_count,
+            }),
+            ..Default::default()
+        };
+        for part in candidate.content.map(|content| content.parts).unwrap_or_default() {
+            if let Some(text) = part.text {
+                response.text.push_str(&text);
+            }
+            if let Some(call) = part.function_call {
+                response.tool_calls.push(ToolCall { id: None, name: call.name, args: call.args });
+            }
+        }
+
+        if response.text.trim().is_empty() && response.tool_calls.is_empty() {
+            return Err(LlmError::EmptyResponse(
+                candidate.finish_reason.unwrap_or_else(|| "empty text".to_string()),
+            ));
+        }
+        Ok(response)
+    }
+}
+
+// =============================================================================
+// OPENAI-COMPATIBLE
+// =============================================================================
+
+/// Any API speaking OpenAI's `/chat/completions`: OpenAI itself, Groq,
+/// or a local Ollama.
+pub struct OpenAiProvider {
+    http: Client,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    retry: RetryPolicy,
+}
+
+impl OpenAiProvider {
+    /// `model` (default `gpt-4o-mini`) at `OPENAI_BASE_URL`, keyed by
+    /// `OPENAI_API_KEY` when set.
+    pub fn from_env(model: Option<String>) -> Self {
+        let base = env_var("OPENAI_BASE_URL").unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string());
+        Self::with_endpoint(
+            format!("{}/chat/completions", base.trim_end_matches('/')),
+            env_var("OPENAI_API_KEY"),
+            model.unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+        )
+    }
+
+    /// A provider for the `/chat/completions` URL `url`.
+    pub fn with_endpoint(url: impl Into<String>, api_key: Option<String>, model: impl Into<String>) -> Self {
+        Self {
+            http: Client::new(),
+            url: url.into(),
+            api_key,
+            model: model.into(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Replaces the default retry policy.
+    #[allow(dead_code)] // Used by tests
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn generate(
+        &self,
+        messages: &[LlmMessage],
+        tools: &[ToolSpec],
+        options: GenerateOptions,
+    ) -> Result<LlmResponse, LlmError> {
+        let mut request = self.http.post(&self.url);
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let request = request.json(&OpenAiRequest::new(&self.model, messages, tools, options));
+
+        let response = send_with_retry(self.name(), self.retry, request).await?;
+        response.json::<OpenAiResponse>().await?.into_response()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// OpenAI wire types (chat/completions)
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+struct OpenAiRequest<'a> {
+    model: &'a str,
+    messages: Vec<OpenAiMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'static str>,
+    temperature: f64,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<Value>,
+}
+
+/// `role` is "system", "user", "assistant" or "tool".
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAiMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAiToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAiToolCall {
+    id: String,
+    #[serde(rename = "type", default = "function_type")]
+    kind: String,
+    function: OpenAiFunctionCall,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+/// `arguments` is a JSON document in a string.
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAiFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    #[serde(default)]
+    choices: Vec<OpenAiChoice>,
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiUsage {
+    #[serde(default)]
+    prompt_tokens: i32,
+    #[serde(default)]
+    completion_tokens: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChoice {
+    message: OpenAiMessage,
+    finish_reason: Option<String>,
+}
+
+/// Call ids are required on this API; a call from elsewhere (Gemini) is
+/// identified by its tool name.
+fn call_id(id: &Option<String>, name: &str) -> String {
+    id.clone().unwrap_or_else(|| format!("call_{}", name))
+}
+
+impl<'a> OpenAiRequest<'a> {
+    fn new(model: &'a str, messages: &[LlmMessage], tools: &[ToolSpec], options: GenerateOptions) -> Self {
+        let mut wire = Vec::with_capacity(messages.len());
+        for message in messages {
+            let results: Vec<&ToolResult> = message
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::ToolResult(result) => Some(result),
+                    _ => None,
+                })
+                .collect();
+
+            // Each tool result is its own "tool" message
+            if !results.is_empty() {
+                wire.extend(results.into_iter().map(|result| OpenAiMessage {
+                    role: "tool".to_string(),
+                    content: Some(result.response.to_string()),
+                    tool_calls: Vec::new(),
+                    tool_call_id: Some(call_id(&result.call_id, &result.name)),
+                }));
+                continue;
+            }
+
+            let tool_calls = message
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::ToolCall(call) => Some(OpenAiToolCall {
+                        id: call_id(&call.id, &call.name),
+                        kind: function_type(),
+                        function: OpenAiFunctionCall { name: call.name.clone(), arguments: call.args.to_string() },
+                    }),
+                    _ => None,
+                })
+                .collect();
+            let text = message.text();
+
+            wire.push(OpenAiMessage {
+                role: match message.role {
+                    Role::System => "system",
+                    Role::User => "user",
+                    Role::Model => "assistant",
+                }
+                .to_string(),
+                content: (!text.is_empty()).then_some(text),
+                tool_calls,
+                tool_call_id: None,
+            });
+        }
+
+        Self {
+            model,
+            messages: wire,
+            tools: tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "type": "function",
+                        "function": {
+                            "name": tool.name,
+                            "description": tool.description,
+                            "parameters": tool.parameters.clone().unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+                        }
+                    })
+                })
+                .collect(),
+            tool_choice: (options.tools_disabled && !tools.is_empty()).then_some("none"),
+            temperature: TEMPERATURE,
+            max_tokens: MAX_OUTPUT_TOKENS,
+            response_format: options.json.then(|| json!({ "type": "json_object" })),
+        }
+    }
+}
+
+impl OpenAiResponse {
+    fn into_response(self) -> Result<LlmResponse, LlmError> {
+        let choice = self
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| LlmError::EmptyResponse("no choices".to_string()))?;
+
+        let response = LlmResponse {
+            text: choice.message.content.unwrap_or_default(),
+            tool_calls: choice
+                .message
+                .tool_calls
+                .into_iter()
+                .map(|call| ToolCall {
+                    id: Some(call.id),
+                    name: call.function.name,
+                    // Unparseable arguments reach the tool as a string, and
+                    // the tool reports them back as invalid
+                    args: serde_json::from_str(&call.function.arguments)
+                        .unwrap_or(Value::String(call.function.arguments)),
+                })
+                .collect(),
+            usage: self.usage.map(|usage| TokenUsage {
+                tokens_in: usage.prompt_tokens,
+                tokens_out: usage.completion_tokens,
+            }),
+        };
+
+        if response.text.trim().is_empty() && response.tool_calls.is_empty() {
+            return Err(LlmError::EmptyResponse(
+                choice.finish_reason.unwrap_or_else(|| "empty text".to_string()),
+            ));
+        }
+        Ok(response)
+    }
+}
+
+// =============================================================================
+// MOCK
+// =============================================================================
+
+type MockReply = dyn Fn(&[LlmMessage], GenerateOptions) -> Result<LlmResponse, LlmError> + Send + Sync;
+
+/// A provider that answers with a function of the conversation; no
+/// network, no key.
+pub struct MockProvider {
+    reply: Box<MockReply>,
+    calls: AtomicUsize,
+}
+
+impl MockProvider {
+    /// Answers every request with `reply(messages, options)`.
+    pub fn new(
+        reply: impl Fn(&[LlmMessage], GenerateOptions) -> Result<LlmResponse, LlmError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            reply: Box::new(reply),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// Answers with `responses` in order, then fails.
+    #[allow(dead_code)] // Used by tests
+    pub fn scripted(responses: Vec<LlmResponse>) -> Self {
+        let script = Mutex::new(VecDeque::from(responses));
+        Self::new(move |_, _| {
+            script
+                .lock()
+                .expect("mock script lock")
+                .pop_front()
+                .ok_or_else(|| LlmError::ServiceError("mock script ran out of responses".to_string()))
+        })
+    }
+
+    /// Repeats the last user message back (`LLM_PROVIDER=mock`).
+    pub fn echo() -> Self {
+        Self::new(|messages, _| {
+            let said = messages
+                .iter()
+                .rev()
+                .find(|message| message.role == Role::User)
+                .map(LlmMessage::text)
+                .unwrap_or_default();
+            Ok(LlmResponse::text(format!("(mock) You said: {}", said)))
+        })
+    }
+
+    /// Requests answered so far.
+    #[allow(dead_code)] // Used by tests
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn model(&self) -> &str {
+        "mock"
+    }
+
+    async fn generate(
+        &self,
+        messages: &[LlmMessage],
+        _tools: &[ToolSpec],
+        options: GenerateOptions,
+    ) -> Result<LlmResponse, LlmError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        (self.reply)(messages, options)
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "request" => include_str!("../../tests/fixtures/gemini/generate_content_request.json"),
+            "response" => include_str!("../../tests/fixtures/gemini/generate_content_response.json"),
+            "blocked" => include_str!("../../tests/fixtures/gemini/blocked_response.json"),
+            "error" => include_str!("../../tests/fixtures/gemini/error_response.json"),
+            _ => unreachable!("unknown fixture {}", name),
+        }
+    }
+
+    fn search_tool() -> ToolSpec {
+        ToolSpec {
+            name: "search_events",
+            description: "Search events",
+            parameters: Some(json!({ "type": "object", "properties": { "query": { "type": "string" } } })),
+        }
+    }
+
+    /// A model turn that called `search_events`, and our answer to it.
+    fn tool_round() -> Vec<LlmMessage> {
+        let call = LlmResponse {
+            tool_calls: vec![ToolCall { id: Some("call_1".to_string()), name: "search_events".to_string(), args: json!({ "query": "jazz" }) }],
+            ..Default::default()
+        };
+        vec![
+            call.to_message(),
+            LlmMessage::tool_results(vec![ToolResult {
+                call_id: Some("call_1".to_string()),
+                name: "search_events".to_string(),
+                response: json!({ "count": 0 }),
+            }]),
+        ]
+    }
+
+    #[test]
+    fn gemini_request_matches_the_captured_payload() {
+        let messages = [
+            LlmMessage::system("You help people find events in Tulsa."),
+            LlmMessage::user("Any jazz this weekend?"),
+        ];
+        let request = GeminiRequest::new(&messages, &[], GenerateOptions::default());
+
+        let ours = serde_json::to_value(&request).unwrap();
+        let captured: Value = serde_json::from_str(fixture("request")).unwrap();
+        assert_eq!(ours, captured);
+
+        let bare = serde_json::to_value(GeminiRequest::new(&[LlmMessage::user("hi")], &[], GenerateOptions::default())).unwrap();
+        assert!(bare.get("systemInstruction").is_none());
+    }
+
+    #[test]
+    fn gemini_request_carries_tools_and_their_history() {
+        let mut messages = vec![LlmMessage::user("jazz?")];
+        messages.extend(tool_round());
+        let options = GenerateOptions { tools_disabled: true, json: true };
+        let request = serde_json::to_value(GeminiRequest::new(&messages, &[search_tool()], options)).unwrap();
+
+        assert_eq!(request["tools"][0]["functionDeclarations"][0]["name"], "search_events");
+        assert_eq!(request["contents"][1]["role"], "model");
+        assert_eq!(request["contents"][1]["parts"][0]["functionCall"]["args"]["query"], "jazz");
+        assert!(request["contents"][1]["parts"][0].get("text").is_none());
+        assert_eq!(request["contents"][2]["parts"][0]["functionResponse"]["response"]["count"], 0);
+        assert_eq!(request["toolConfig"]["functionCallingConfig"]["mode"], "NONE");
+        assert_eq!(request["generationConfig"]["responseMimeType"], "application/json");
+    }
+
+    #[test]
+    fn reads_the_first_candidates_text() {
+        let response: GeminiResponse = serde_json::from_str(fixture("response")).unwrap();
+        let response = response.into_response().unwrap();
+        assert_eq!(response.text, "Jazz Night at the Blue Note starts Friday at 8 PM.");
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.usage, Some(TokenUsage { tokens_in: 18, tokens_out: 14 }));
+    }
+
+    #[test]
+    fn reads_gemini_function_calls() {
+        let response: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [{ "functionCall": { "name": "search_events", "args": { "query": "jazz" } } }]
+                },
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+
+        let response = response.into_response().unwrap();
+        assert_eq!(response, LlmResponse::tool_call("search_events", json!({ "query": "jazz" })));
+        assert!(response.into_text().is_err());
+    }
+
+    #[test]
+    fn blocked_prompts_are_an_empty_response() {
+        let response: GeminiResponse = serde_json::from_str(fixture("blocked")).unwrap();
+        assert!(matches!(response.into_response(), Err(LlmError::EmptyResponse(reason)) if reason == "SAFETY"));
+    }
+
+    #[test]
+    fn api_errors_keep_status_and_message() {
+        let error = api_error(400, fixture("error"));
+        assert!(matches!(
+            &error,
+            LlmError::Api { status: 400, message } if message == "API key not valid. Please pass a valid API key."
+        ));
+
+        // Not JSON (a proxy's error page): the raw body is the message
+        let error = api_error(502, "Bad Gateway\n");
+        assert!(matches!(&error, LlmError::Api { status: 502, message } if message == "Bad Gateway"));
+    }
+
+    #[test]
+    fn openai_request_splits_tool_results_into_tool_messages() {
+        let mut messages = vec![LlmMessage::system("Be brief."), LlmMessage::user("jazz?")];
+        messages.extend(tool_round());
+        let request = serde_json::to_value(OpenAiRequest::new("llama3", &messages, &[search_tool()], GenerateOptions::default())).unwrap();
+
+        assert_eq!(request["model"], "llama3");
+        assert_eq!(request["messages"][0], json!({ "role": "system", "content": "Be brief." }));
+        assert_eq!(request["messages"][2]["role"], "assistant");
+        assert_eq!(request["messages"][2]["content"], Value::Null);
+        assert_eq!(request["messages"][2]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(request["messages"][2]["tool_calls"][0]["function"]["arguments"], r#"{"query":"jazz"}"#);
+        assert_eq!(request["messages"][3], json!({ "role": "tool", "content": r#"{"count":0}"#, "tool_call_id": "call_1" }));
+        assert_eq!(request["tools"][0]["function"]["name"], "search_events");
+        assert!(request.get("tool_choice").is_none());
+        assert!(request.get("response_format").is_none());
+    }
+
+    #[test]
+    fn reads_openai_tool_calls() {
+        let response: OpenAiResponse = serde_json::from_value(json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_abc",
+                        "type": "function",
+                        "function": { "name": "search_events", "arguments": "{\"query\": \"jazz\"}" }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": { "prompt_tokens": 210, "completion_tokens": 17, "total_tokens": 227 }
+        }))
+        .unwrap();
+
+        let response = response.into_response().unwrap();
+        assert_eq!(response.tool_calls[0].id.as_deref(), Some("call_abc"));
+        assert_eq!(response.tool_calls[0].args, json!({ "query": "jazz" }));
+        assert_eq!(response.usage, Some(TokenUsage { tokens_in: 210, tokens_out: 17 }));
+
+        let empty: OpenAiResponse = serde_json::from_value(json!({
+            "choices": [{ "message": { "role": "assistant", "content": "" }, "finish_reason": "length" }]
+        }))
+        .unwrap();
+        assert!(matches!(empty.into_response(), Err(LlmError::EmptyResponse(reason)) if reason == "length"));
+    }
+
+    #[tokio::test]
+    async fn mock_follows_its_script() {
+        let mock = MockProvider::scripted(vec![
+            LlmResponse::tool_call("list_categories", json!({})),
+            LlmResponse::text("done"),
+        ]);
+        let messages = [LlmMessage::user("hi")];
+
+        let first = mock.generate(&messages, &[], GenerateOptions::default()).await.unwrap();
+        assert_eq!(first.tool_calls[0].name, "list_categories");
+        assert_eq!(mock.generate(&messages, &[], GenerateOptions::default()).await.unwrap().text, "done");
+        assert!(mock.generate(&messages, &[], GenerateOptions::default()).await.is_err());
+        assert_eq!(mock.calls(), 3);
+
+        let echo = MockProvider::echo().generate(&messages, &[], GenerateOptions::default()).await.unwrap();
+        assert_eq!(echo.text, "(mock) You said: hi");
+    }
+
+    /// Retries without the waiting.
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limits_are_retried_until_they_clear() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(fixture("response")))
+            .mount(&server)
+            .await;
+
+        let provider = GeminiProvider::with_endpoint(server.uri(), Some("test-key".to_string())).with_retry(fast_retry());
+        let response = provider.generate(&[LlmMessage::user("jazz?")], &[], GenerateOptions::default()).await.unwrap();
+
+        assert_eq!(response.text, "Jazz Night at the Blue Note starts Friday at 8 PM.");
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_stop_at_the_limit_and_skip_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(429)).mount(&server).await;
+
+        let provider = OpenAiProvider::with_endpoint(server.uri(), None, "test-model").with_retry(fast_retry());
+        let error = provider.generate(&[LlmMessage::user("hi")], &[], GenerateOptions::default()).await.unwrap_err();
+        assert!(matches!(error, LlmError::RateLimited { attempts: 3, status: 429 }));
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string(fixture("error")))
+            .mount(&server)
+            .await;
+
+        let provider = GeminiProvider::with_endpoint(server.uri(), Some("bad-key".to_string())).with_retry(fast_retry());
+        let error = provider.generate(&[LlmMessage::user("hi")], &[], GenerateOptions::default()).await.unwrap_err();
+        assert!(matches!(error, LlmError::Api { status: 401, .. }));
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn backoff_doubles_and_respects_retry_after() {
+        let policy = RetryPolicy::default();
+        assert_eq!(backoff(policy, 1, 1.0), Duration::from_millis(500));
+        assert_eq!(backoff(policy, 2, 1.0), Duration::from_millis(1000));
+        assert_eq!(backoff(policy, 3, 0.5), Duration::from_millis(1000));
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn gemini_without_a_key_fails_before_any_request() {
+        // Nothing listens on port 9 (discard); the key check comes first
+        let provider = GeminiProvider::with_endpoint("http://127.0.0.1:9/", None);
+        let error = provider.generate(&[LlmMessage::user("hi")], &[], GenerateOptions::default()).await.unwrap_err();
+        assert!(matches!(error, LlmError::MissingApiKey));
+    }
+}
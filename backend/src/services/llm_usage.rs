@@ -0,0 +1,296 @@
+//! # LLM Usage
+//!
+//! What chat costs. Every request to the model goes through `generate`,
+//! which times it and writes a row to `llm_calls`: who asked (if signed
+//! in), what for, which model and chat prompt version, tokens in and out,
+//! latency and whether it worked. `usage_report` sums the rows for `GET /api/admin/llm/usage`.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Grouping
+//! One chat message can take several calls (tool rounds, then the answer).
+//! They share the `request_id` of their `CallContext`, so the report can
+//! count requests as well as calls.
+//!
+//! ## Traces
+//! Each call is also appended to its context's `CallLog`. The chat flow
+//! shares one log between its chat and intent calls, and returns it as
+//! part of the debug trace (`llm::ChatTrace`).
+//!
+//! ## Failures
+//! Recording is best effort: if the insert fails it is logged and the
+//! reply still goes out. Failed calls are recorded too (`success = false`,
+//! no token counts).
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{DailyLlmUsage, IntentCacheStats, LlmUsageCounts, LlmUsageReport};
+use crate::services::dates;
+use crate::services::llm::LlmError;
+use crate::services::llm_provider::{GenerateOptions, LlmMessage, LlmProvider, LlmResponse, ToolSpec};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// `purpose` of the calls behind a chat message.
+pub const PURPOSE_CHAT: &str = "chat";
+
+/// `purpose` of the calls behind `llm::parse_user_intent`.
+pub const PURPOSE_INTENT: &str = "intent";
+
+/// `purpose` of the calls behind `llm::classify_event_category`.
+pub const PURPOSE_CLASSIFY: &str = "classify";
+
+/// Aggregates shared by the totals and the per-day rows.
+const USAGE_COUNTS: &str = r#"
+    COUNT(*) AS calls,
+    COUNT(*) FILTER (WHERE NOT success) AS failed,
+    COUNT(DISTINCT request_id) AS requests,
+    COALESCE(SUM(tokens_in), 0)::BIGINT AS tokens_in,
+    COALESCE(SUM(tokens_out), 0)::BIGINT AS tokens_out,
+    AVG(latency_ms)::FLOAT8 AS avg_latency_ms
+"#;
+
+// =============================================================================
+// RECORDING
+// =============================================================================
+
+/// One model call, as `generate` records it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCall {
+    pub purpose: &'static str,
+    pub model: String,
+    /// `SystemPrompt::version` for chat calls; `None` for the fixed
+    /// intent and classification prompts
+    pub prompt_version: Option<String>,
+    /// `None` if the call failed or the provider didn't say
+    pub tokens_in: Option<i32>,
+    pub tokens_out: Option<i32>,
+    pub latency_ms: i32,
+    pub success: bool,
+}
+
+/// Calls made under one or more contexts, in order. Clones share the log.
+#[derive(Debug, Clone, Default)]
+pub struct CallLog(Arc<Mutex<Vec<ModelCall>>>);
+
+impl CallLog {
+    fn push(&self, call: ModelCall) {
+        self.0.lock().expect("call log lock").push(call);
+    }
+
+    /// Every call logged so far.
+    pub fn calls(&self) -> Vec<ModelCall> {
+        self.0.lock().expect("call log lock").clone()
+    }
+}
+
+/// Who a group of calls is for and why.
+#[derive(Debug, Clone)]
+pub struct CallContext {
+    /// Shared by every call made for one chat message, intent parse or
+    /// classification
+    pub request_id: Uuid,
+    /// `None` for anonymous chat
+    pub user_id: Option<Uuid>,
+    /// `PURPOSE_CHAT`, `PURPOSE_INTENT` or `PURPOSE_CLASSIFY`
+    pub purpose: &'static str,
+    /// Where `generate` appends its calls
+    pub log: CallLog,
+    /// Version of the system prompt the calls use, if it can change
+    pub prompt_version: Option<String>,
+}
+
+impl CallContext {
+    /// A context for a new request, with a log of its own.
+    pub fn new(purpose: &'static str, user_id: Option<Uuid>) -> Self {
+        Self {
+            request_id: Uuid::new_v4(),
+            user_id,
+            purpose,
+            log: CallLog::default(),
+            prompt_version: None,
+        }
+    }
+
+    /// This context, appending to `log` instead.
+    pub fn with_log(self, log: &CallLog) -> Self {
+        Self { log: log.clone(), ..self }
+    }
+
+    /// This context, recording `version` as the calls' prompt.
+    pub fn with_prompt_version(self, version: &str) -> Self {
+        Self { prompt_version: Some(version.to_string()), ..self }
+    }
+}
+
+/// `provider.generate(...)`, recorded in `llm_calls` and `context.log`.
+///
+/// The result is the provider's, whatever happens to the record.
+pub async fn generate(
+    provider: &dyn LlmProvider,
+    pool: &PgPool,
+    context: &CallContext,
+    messages: &[LlmMessage],
+    tools: &[ToolSpec],
+    options: GenerateOptions,
+) -> Result<LlmResponse, LlmError> {
+    let started = Instant::now();
+    let result = provider.generate(messages, tools, options).await;
+    let latency_ms = i32::try_from(started.elapsed().as_millis()).unwrap_or(i32::MAX);
+
+    let usage = result.as_ref().ok().and_then(|response| response.usage);
+    let call = ModelCall {
+        purpose: context.purpose,
+        model: provider.model().to_string(),
+        prompt_version: context.prompt_version.clone(),
+        tokens_in: usage.map(|u| u.tokens_in),
+        tokens_out: usage.map(|u| u.tokens_out),
+        latency_ms,
+        success: result.is_ok(),
+    };
+
+    let recorded = sqlx::query(
+        r#"
+        INSERT INTO llm_calls (request_id, user_id, purpose, model, prompt_version, tokens_in, tokens_out, latency_ms, success)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+        .bind(context.request_id)
+        .bind(context.user_id)
+        .bind(call.purpose)
+        .bind(&call.model)
+        .bind(&call.prompt_version)
+        .bind(call.tokens_in)
+        .bind(call.tokens_out)
+        .bind(call.latency_ms)
+        .bind(call.success)
+        .execute(pool)
+        .await;
+
+    if let Err(e) = recorded {
+        tracing::warn!(error = %e, request_id = %context.request_id, "could not record LLM call");
+    }
+    context.log.push(call);
+
+    result
+}
+
+// =============================================================================
+// REPORTING
+// =============================================================================
+
+/// Totals and per-day counts for calls made at or after `since`, with the
+/// intent cache's counters. Days are local calendar days.
+pub async fn usage_report(
+    pool: &PgPool,
+    since: DateTime<Utc>,
+    intent_cache: IntentCacheStats,
+) -> Result<LlmUsageReport, sqlx::Error> {
+    let totals = sqlx::query_as::<_, LlmUsageCounts>(&format!(
+        "SELECT {} FROM llm_calls WHERE created_at >= $1",
+        USAGE_COUNTS
+    ))
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+    let daily = sqlx::query_as::<_, DailyLlmUsage>(&format!(
+        r#"
+        SELECT (created_at AT TIME ZONE $2)::date AS date, {}
+        FROM llm_calls
+        WHERE created_at >= $1
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        USAGE_COUNTS
+    ))
+        .bind(since)
+        .bind(dates::local_timezone().name())
+        .fetch_all(pool)
+        .await?;
+
+    Ok(LlmUsageReport { since, totals, daily, intent_cache })
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm_provider::{MockProvider, TokenUsage};
+
+    #[tokio::test]
+    async fn calls_are_recorded_and_summed() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Far enough in the future that no other test's rows count
+        let since = Utc::now() + chrono::Duration::days(3650);
+        let context = CallContext::new(PURPOSE_CHAT, None).with_prompt_version("test-0001");
+
+        let model = MockProvider::new(|messages, _| {
+            if messages[0].text() == "fail" {
+                return Err(LlmError::EmptyResponse("SAFETY".to_string()));
+            }
+            let mut response = LlmResponse::text("ok");
+            response.usage = Some(TokenUsage { tokens_in: 100, tokens_out: 20 });
+            Ok(response)
+        });
+        for message in ["hi", "again", "fail"] {
+            let _ = generate(&model, &pool, &context, &[LlmMessage::user(message)], &[], GenerateOptions::default()).await;
+        }
+
+        let rows: Vec<(String, Option<i32>, bool, Option<String>)> = sqlx::query_as(
+            "SELECT model, tokens_in, success, prompt_version FROM llm_calls WHERE request_id = $1 ORDER BY created_at",
+        )
+            .bind(context.request_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], ("mock".to_string(), Some(100), true, Some("test-0001".to_string())));
+        assert_eq!(rows[2], ("mock".to_string(), None, false, Some("test-0001".to_string())));
+        let logged = context.log.calls();
+        assert_eq!(logged.len(), 3);
+        assert_eq!((logged[0].tokens_in, logged[2].success), (Some(100), false));
+
+        // Date the rows into the window, then sum them
+        sqlx::query("UPDATE llm_calls SET created_at = $2 WHERE request_id = $1")
+            .bind(context.request_id)
+            .bind(since)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report = usage_report(&pool, since, IntentCacheStats::default()).await.unwrap();
+        assert_eq!(report.totals.calls, 3);
+        assert_eq!(report.totals.failed, 1);
+        assert_eq!(report.totals.requests, 1);
+        assert_eq!(report.totals.tokens_in, 200);
+        assert_eq!(report.totals.tokens_out, 40);
+        assert!(report.totals.avg_latency_ms.is_some());
+        assert_eq!(report.daily.len(), 1);
+        assert_eq!(report.daily[0].counts.calls, 3);
+
+        sqlx::query("DELETE FROM llm_calls WHERE request_id = $1")
+            .bind(context.request_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
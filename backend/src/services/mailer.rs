@@ -0,0 +1,144 @@
+//! # Outgoing Email
+//!
+//! Sends transactional mail (for now, email verification links).
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Implementations
+//! | Mailer | When | What it does |
+//! |--------|------|--------------|
+//! | `SmtpMailer` | `SMTP_HOST` is set | STARTTLS relay with optional login |
+//! | `LogMailer` | otherwise | writes the message to the log (dev) |
+//!
+//! Callers go through the `Mailer` trait (`mailer::from_env()`), so tests
+//! and dev setups never need a mail server.
+//!
+//! ## Environment Variables
+//! ```text
+//! SMTP_HOST=smtp.example.com
+//! SMTP_PORT=587
+//! SMTP_USERNAME=...
+//! SMTP_PASSWORD=...
+//! MAIL_FROM="Locate918 <no-reply@locate918.com>"
+//! ```
+
+use axum::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::error::AppError;
+use crate::services::scheduler;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// SMTP submission port (STARTTLS).
+const DEFAULT_SMTP_PORT: u64 = 587;
+
+/// Sender when `MAIL_FROM` isn't set.
+const DEFAULT_MAIL_FROM: &str = "Locate918 <no-reply@locate918.com>";
+
+// =============================================================================
+// MAILER INTERFACE
+// =============================================================================
+
+/// A plain-text message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Something that can deliver an `Email`.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), AppError>;
+}
+
+/// The configured mailer: SMTP when `SMTP_HOST` is set, else the log.
+///
+/// A set but unusable configuration (bad `MAIL_FROM`, unknown host) is a
+/// 503 rather than a silent fallback to the log.
+pub fn from_env() -> Result<Box<dyn Mailer>, AppError> {
+    match std::env::var("SMTP_HOST").ok().filter(|host| !host.is_empty()) {
+        Some(host) => Ok(Box::new(SmtpMailer::new(&host)?)),
+        None => Ok(Box::new(LogMailer)),
+    }
+}
+
+// =============================================================================
+// LOG
+// =============================================================================
+
+/// Dev mailer: logs each message instead of sending it.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> Result<(), AppError> {
+        tracing::info!(to = %email.to, subject = %email.subject, "email (not sent; SMTP_HOST unset):\n{}", email.body);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// SMTP
+// =============================================================================
+
+/// Delivers through an SMTP relay.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Builds a relay to `host` from the `SMTP_*` and `MAIL_FROM` variables.
+    pub fn new(host: &str) -> Result<Self, AppError> {
+        let unavailable = |what: &str| {
+            tracing::error!(host, "{}; email is off", what);
+            AppError::Unavailable("email is not configured".to_string())
+        };
+
+        let from = std::env::var("MAIL_FROM")
+            .unwrap_or_else(|_| DEFAULT_MAIL_FROM.to_string())
+            .parse::<Mailbox>()
+            .map_err(|_| unavailable("MAIL_FROM is not a valid address"))?;
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|_| unavailable("SMTP_HOST is not usable"))?
+            .port(scheduler::env_u64("SMTP_PORT", DEFAULT_SMTP_PORT) as u16);
+
+        if let (Ok(username), Ok(password)) = (std::env::var("SMTP_USERNAME"), std::env::var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self { transport: builder.build(), from })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), AppError> {
+        let to = email
+            .to
+            .parse::<Mailbox>()
+            .map_err(|_| AppError::invalid("email", "must be an email address"))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .body(email.body)
+            .map_err(|e| AppError::Upstream(format!("building email: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::Upstream(format!("SMTP: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,509 @@
+//! # Duplicate Event Merging
+//!
+//! Folds a duplicate event into a canonical one. Used by the admin merge
+//! endpoint, and later by the scraper's cross-source deduplication.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## What a Merge Does (one transaction)
+//! 1. Fills the canonical event's empty fields from the duplicate
+//!    (canonical values always win when both are set)
+//! 2. Adds the duplicate's tags to the canonical event
+//! 3. Moves every user interaction to the canonical event
+//! 4. Moves the duplicate's notifications, except where the user already
+//!    has one of the same kind for the canonical event
+//! 5. Re-points review queue pairs (`duplicate_candidates`) with a third
+//!    event at the canonical one; the pair being merged is dropped
+//! 6. Adds every listing of the duplicate (`event_sources`: its own
+//!    `source_url` and any it had already absorbed) to the canonical event's
+//! 7. Deletes the duplicate, then picks the best listing of the union as
+//!    the primary (`attribution::choose_primary`)
+//!
+//! Nothing is lost: users who saved either listing still see the event,
+//! and their notifications about it stay in their inbox.
+
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::db::EVENT_COLUMNS;
+use crate::models::{Event, MergeReport};
+use crate::services::attribution;
+
+// =============================================================================
+// ERROR TYPE
+// =============================================================================
+
+/// Reasons a merge can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("an event cannot be merged into itself")]
+    SameEvent,
+
+    #[error("event {0} not found")]
+    NotFound(Uuid),
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// =============================================================================
+// MERGE
+// =============================================================================
+
+/// Merges `duplicate_id` into `canonical_id` and returns a report.
+pub async fn merge_events(
+    pool: &PgPool,
+    canonical_id: Uuid,
+    duplicate_id: Uuid,
+) -> Result<MergeReport, MergeError> {
+    if canonical_id == duplicate_id {
+        return Err(MergeError::SameEvent);
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // Lock both rows so a concurrent merge or update can't interleave
+    let canonical = lock_event(&mut tx, canonical_id).await?;
+    let duplicate = lock_event(&mut tx, duplicate_id).await?;
+
+    let merged = fill_missing_fields(canonical, &duplicate);
+
+    sqlx::query(
+        r#"
+        UPDATE events
+        SET description = $2, venue = $3, venue_id = $4, venue_address = $5,
+            location = $6, source_name = $7, end_time = $8, categories = $9,
+            price_min = $10, price_max = $11, image_url = $12,
+            latitude = $13, longitude = $14, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+        .bind(merged.id)
+        .bind(&merged.description)
+        .bind(&merged.venue)
+        .bind(merged.venue_id)
+        .bind(&merged.venue_address)
+        .bind(&merged.location)
+        .bind(&merged.source_name)
+        .bind(merged.end_time)
+        .bind(&merged.categories)
+        .bind(merged.price_min)
+        .bind(merged.price_max)
+        .bind(&merged.image_url)
+        .bind(merged.latitude)
+        .bind(merged.longitude)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_tags (event_id, tag)
+        SELECT $1, tag FROM event_tags WHERE event_id = $2
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+        .bind(canonical_id)
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let interactions_moved = sqlx::query("UPDATE user_interactions SET event_id = $1 WHERE event_id = $2")
+        .bind(canonical_id)
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    // One notification of each kind per user and event: where the user has
+    // both, the canonical event's stays and the duplicate's goes with it
+    sqlx::query(
+        r#"
+        UPDATE notifications n SET event_id = $1
+        WHERE n.event_id = $2
+          AND NOT EXISTS (
+              SELECT 1 FROM notifications t
+              WHERE t.event_id = $1 AND t.user_id = n.user_id AND t.kind = n.kind
+          )
+        "#,
+    )
+        .bind(canonical_id)
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // Pairs of the duplicate and a third event become pairs with the
+    // canonical one, unless that pair is already queued (either way round)
+    for (column, other) in [("event_id", "duplicate_id"), ("duplicate_id", "event_id")] {
+        sqlx::query(&format!(
+            r#"
+            UPDATE duplicate_candidates c SET {column} = $1
+            WHERE c.{column} = $2
+              AND c.{other} <> $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM duplicate_candidates t
+                  WHERE (t.event_id = $1 AND t.duplicate_id = c.{other})
+                     OR (t.duplicate_id = $1 AND t.event_id = c.{other})
+              )
+            "#,
+            column = column,
+            other = other,
+        ))
+            .bind(canonical_id)
+            .bind(duplicate_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // Every listing of the duplicate, its own included; a listing both had
+    // keeps the earliest and latest sightings
+    sqlx::query(
+        r#"
+        INSERT INTO event_sources (event_id, source_url, source_name, external_id, merged_from, first_seen_at, last_seen_at)
+        SELECT $1, source_url, source_name, external_id,
+               CASE WHEN source_url = $3 THEN $2 ELSE merged_from END,
+               first_seen_at, last_seen_at
+        FROM event_sources
+        WHERE event_id = $2
+        ON CONFLICT (event_id, source_url) DO UPDATE
+        SET source_name = COALESCE(event_sources.source_name, EXCLUDED.source_name),
+            external_id = COALESCE(event_sources.external_id, EXCLUDED.external_id),
+            first_seen_at = LEAST(event_sources.first_seen_at, EXCLUDED.first_seen_at),
+            last_seen_at = GREATEST(event_sources.last_seen_at, EXCLUDED.last_seen_at)
+        "#,
+    )
+        .bind(canonical_id)
+        .bind(duplicate_id)
+        .bind(&duplicate.source_url)
+        .execute(&mut *tx)
+        .await?;
+
+    // In case the duplicate's own listing was never recorded
+    sqlx::query(
+        r#"
+        INSERT INTO event_sources (event_id, source_url, source_name, merged_from, first_seen_at, last_seen_at)
+        VALUES ($1, $3, $4, $2, $5, $5)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+        .bind(canonical_id)
+        .bind(duplicate_id)
+        .bind(&duplicate.source_url)
+        .bind(&duplicate.source_name)
+        .bind(duplicate.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+    // Cascades to the duplicate's tags and event_sources rows, and whatever
+    // wasn't moved above
+    sqlx::query("DELETE FROM events WHERE id = $1")
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // The duplicate's listing may be the better link (its URL is free now)
+    attribution::choose_primary(&mut tx, canonical_id).await?;
+
+    let event = lock_event(&mut tx, canonical_id).await?;
+    tx.commit().await?;
+
+    Ok(MergeReport {
+        event,
+        merged_from: duplicate_id,
+        merged_source_url: duplicate.source_url,
+        interactions_moved,
+    })
+}
+
+/// Loads an event and locks its row until the transaction ends.
+async fn lock_event(conn: &mut PgConnection, id: Uuid) -> Result<Event, MergeError> {
+    sqlx::query_as::<_, Event>(&format!(
+        "SELECT {} FROM events WHERE id = $1 FOR UPDATE",
+        EVENT_COLUMNS
+    ))
+        .bind(id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or(MergeError::NotFound(id))
+}
+
+/// Returns `canonical` with its empty optional fields taken from `duplicate`.
+///
+/// Identity (`id`, `title`, `source_url`, `start_time`), flags and status
+/// always stay the canonical event's.
+pub fn fill_missing_fields(canonical: Event, duplicate: &Event) -> Event {
+    // A linked venue and its name travel together
+    let (venue, venue_id) = if canonical.venue.is_none() && canonical.venue_id.is_none() {
+        (duplicate.venue.clone(), duplicate.venue_id)
+    } else {
+        (canonical.venue, canonical.venue_id)
+    };
+
+    // Coordinates travel together too: never mix one event's lat with another's lng
+    let (latitude, longitude) = match (canonical.latitude, canonical.longitude) {
+        (Some(lat), Some(lng)) => (Some(lat), Some(lng)),
+        _ => (duplicate.latitude, duplicate.longitude),
+    };
+
+    let categories = match canonical.categories {
+        Some(ref c) if !c.is_empty() => canonical.categories,
+        _ => duplicate.categories.clone(),
+    };
+
+    Event {
+        description: canonical.description.or_else(|| duplicate.description.clone()),
+        venue,
+        venue_id,
+        venue_address: canonical.venue_address.or_else(|| duplicate.venue_address.clone()),
+        location: canonical.location.or_else(|| duplicate.location.clone()),
+        source_name: canonical.source_name.or_else(|| duplicate.source_name.clone()),
+        end_time: canonical.end_time.or(duplicate.end_time),
+        categories,
+        price_min: canonical.price_min.or(duplicate.price_min),
+        price_max: canonical.price_max.or(duplicate.price_max),
+        image_url: canonical.image_url.or_else(|| duplicate.image_url.clone()),
+        latitude,
+        longitude,
+        ..canonical
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventStatus;
+    use chrono::{TimeZone, Utc};
+
+    fn event(id: u128, source_url: &str) -> Event {
+        let created = Utc.with_ymd_and_hms(2026, 1, 17, 12, 0, 0).unwrap();
+        Event {
+            id: Uuid::from_u128(id),
+            title: "Jazz Night".to_string(),
+            description: None,
+            venue: None,
+            venue_id: None,
+            venue_address: None,
+            location: None,
+            source_url: source_url.to_string(),
+            source_name: None,
+            start_time: Utc.with_ymd_and_hms(2026, 1, 25, 20, 0, 0).unwrap(),
+            end_time: None,
+            categories: None,
+            tags: vec![],
+            price_min: None,
+            price_max: None,
+            is_free: false,
+            outdoor: false,
+            family_friendly: false,
+            image_url: None,
+            status: EventStatus::Scheduled,
+            latitude: None,
+            longitude: None,
+            archived_at: None,
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    #[test]
+    fn fills_only_missing_fields() {
+        let canonical = Event {
+            description: Some("Canonical description".to_string()),
+            price_min: Some(10.0),
+            categories: Some(vec![]),
+            ..event(1, "https://venue.example/jazz")
+        };
+        let duplicate = Event {
+            description: Some("Duplicate description".to_string()),
+            venue: Some("The Blue Note".to_string()),
+            end_time: Some(Utc.with_ymd_and_hms(2026, 1, 25, 23, 0, 0).unwrap()),
+            categories: Some(vec!["music".to_string()]),
+            price_min: Some(5.0),
+            price_max: Some(15.0),
+            status: EventStatus::SoldOut,
+            ..event(2, "https://tickets.example/jazz")
+        };
+
+        let merged = fill_missing_fields(canonical, &duplicate);
+
+        assert_eq!(merged.id, Uuid::from_u128(1));
+        assert_eq!(merged.source_url, "https://venue.example/jazz");
+        assert_eq!(merged.description.as_deref(), Some("Canonical description"));
+        assert_eq!(merged.venue.as_deref(), Some("The Blue Note"));
+        assert_eq!(merged.end_time, duplicate.end_time);
+        assert_eq!(merged.categories, Some(vec!["music".to_string()]));
+        assert_eq!(merged.price_min, Some(10.0));
+        assert_eq!(merged.price_max, Some(15.0));
+        assert_eq!(merged.status, EventStatus::Scheduled);
+    }
+
+    #[test]
+    fn coordinates_are_never_mixed() {
+        let canonical = Event {
+            latitude: Some(36.15),
+            ..event(1, "https://a.example")
+        };
+        let duplicate = Event {
+            latitude: Some(36.20),
+            longitude: Some(-95.90),
+            ..event(2, "https://b.example")
+        };
+
+        let merged = fill_missing_fields(canonical, &duplicate);
+
+        assert_eq!((merged.latitude, merged.longitude), (Some(36.20), Some(-95.90)));
+    }
+
+    /// Runs against a real database when `TEST_DATABASE_URL` is set, e.g.
+    /// `TEST_DATABASE_URL=postgres://postgres@localhost/locate918_test cargo test`.
+    #[tokio::test]
+    async fn merge_preserves_interactions_and_leaves_no_orphans() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let insert_event = |url: String, description: Option<&'static str>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, Uuid>(
+                    "INSERT INTO events (title, source_url, start_time, description) \
+                     VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day', $2) RETURNING id",
+                )
+                    .bind(url)
+                    .bind(description)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        let canonical = insert_event(format!("https://venue.example/{}", run), None).await;
+        let duplicate = insert_event(format!("https://tickets.example/{}", run), Some("From tickets")).await;
+        let other = insert_event(format!("https://other.example/{}", run), None).await;
+
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        for (event_id, kind) in [(canonical, "view"), (duplicate, "save"), (duplicate, "attend")] {
+            sqlx::query("INSERT INTO user_interactions (user_id, event_id, interaction_type) VALUES ($1, $2, $3)")
+                .bind(user)
+                .bind(event_id)
+                .bind(kind)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        // Both events matched a saved search; only the duplicate has a reminder
+        for (event_id, kind) in [(canonical, "saved_search"), (duplicate, "saved_search"), (duplicate, "reminder")] {
+            sqlx::query("INSERT INTO notifications (user_id, event_id, kind, title) VALUES ($1, $2, $3, 'Jazz Night')")
+                .bind(user)
+                .bind(event_id)
+                .bind(kind)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        // Queued for review: the pair being merged, and the duplicate with a third listing
+        for (event_id, duplicate_id) in [(canonical, duplicate), (other, duplicate)] {
+            sqlx::query("INSERT INTO duplicate_candidates (event_id, duplicate_id, similarity) VALUES ($1, $2, 0.6)")
+                .bind(event_id)
+                .bind(duplicate_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO event_tags (event_id, tag) VALUES ($1, 'jazz')")
+            .bind(duplicate)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Each listed elsewhere too, the duplicate twice
+        let seen = chrono::Utc::now();
+        let mut conn = pool.acquire().await.unwrap();
+        for (event_id, listing) in [
+            (canonical, format!("https://venue.example/{}", run)),
+            (duplicate, format!("https://tickets.example/{}", run)),
+            (duplicate, format!("https://calendar.example/{}", run)),
+        ] {
+            attribution::record(&mut conn, event_id, &listing, None, None, seen).await.unwrap();
+        }
+        drop(conn);
+
+        assert!(matches!(
+            merge_events(&pool, canonical, canonical).await,
+            Err(MergeError::SameEvent)
+        ));
+        assert!(matches!(
+            merge_events(&pool, canonical, Uuid::nil()).await,
+            Err(MergeError::NotFound(id)) if id.is_nil()
+        ));
+
+        let report = merge_events(&pool, canonical, duplicate).await.unwrap();
+        assert_eq!(report.interactions_moved, 2);
+        assert_eq!(report.event.description.as_deref(), Some("From tickets"));
+        assert_eq!(report.event.tags, vec!["jazz".to_string()]);
+
+        let count = |sql: &'static str, id: Uuid| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(sql)
+                    .bind(id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(count("SELECT COUNT(*) FROM user_interactions WHERE user_id = $1", user).await, 3);
+        assert_eq!(count("SELECT COUNT(*) FROM user_interactions WHERE event_id = $1", canonical).await, 3);
+        assert_eq!(count("SELECT COUNT(*) FROM events WHERE id = $1", duplicate).await, 0);
+        assert_eq!(count("SELECT COUNT(*) FROM event_tags WHERE event_id = $1", duplicate).await, 0);
+        assert_eq!(count("SELECT COUNT(*) FROM event_sources WHERE event_id = $1", duplicate).await, 0);
+        assert_eq!(count("SELECT COUNT(*) FROM event_sources WHERE merged_from = $1", duplicate).await, 1);
+
+        // One notification per kind survives, on the canonical event
+        let kinds: Vec<String> = sqlx::query_scalar(
+            "SELECT kind FROM notifications WHERE user_id = $1 AND event_id = $2 ORDER BY kind",
+        )
+            .bind(user)
+            .bind(canonical)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(kinds, ["reminder", "saved_search"]);
+        assert_eq!(count("SELECT COUNT(*) FROM notifications WHERE user_id = $1", user).await, 2);
+
+        // The third listing is now queued against the canonical event
+        let pairs: Vec<(Uuid, Uuid)> = sqlx::query_as(
+            "SELECT event_id, duplicate_id FROM duplicate_candidates WHERE event_id = ANY($1) OR duplicate_id = ANY($1)",
+        )
+            .bind(vec![canonical, duplicate, other])
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(pairs, [(other, canonical)]);
+
+        // The union of both events' listings; the canonical's own stays primary
+        let sources = attribution::list(&pool, canonical).await.unwrap();
+        let urls: Vec<&str> = sources.iter().map(|source| source.source_url.as_str()).collect();
+        assert_eq!(urls.len(), 3);
+        assert_eq!(urls[0], format!("https://venue.example/{}", run));
+        assert!(urls.contains(&format!("https://calendar.example/{}", run).as_str()));
+        assert!(sources[0].primary && !sources[1].primary);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = ANY($1)").bind(vec![canonical, other]).execute(&pool).await.unwrap();
+    }
+}
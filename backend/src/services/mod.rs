@@ -6,6 +6,38 @@
 //!
 //! ## Current Submodules
 //! - `llm` - Large Language Model integration (Ben's domain)
+//! - `llm_provider` - Pluggable model backends (Gemini, OpenAI-compatible, mock)
+//! - `llm_usage` - Per-call token and latency log for LLM requests
+//! - `prompt` - The chat system prompt, loaded from a file and reloadable
+//! - `llm_health` - Cached pings of the chat model for health checks
+//! - `intent_cache` - Reuses parsed chat intents for repeated questions
+//! - `rate_limit` - Per-client chat limits and a daily model-call budget
+//! - `sanitize` - Cleans and delimits untrusted text for LLM prompts
+//! - `chat_history` - Stored chat sessions and the context they feed the model
+//! - `suggestions` - Starter prompts for the chat box, from event aggregates
+//! - `classification` - Fills in categories for uncategorized events with the LLM
+//! - `ics` - iCalendar rendering for calendar exports
+//! - `geo` - Distance math for radius searches
+//! - `geocoding` - Addresses to coordinates (Nominatim, cached) for venues and events
+//! - `analytics` - Interaction weights and trending scores
+//! - `dates` - Local-time windows ("tonight") converted to UTC
+//! - `jsonld` - schema.org Event mapping (export and scraping)
+//! - `scheduler` - Interval-driven background jobs
+//! - `archive` - Soft-archives long-finished events
+//! - `merge` - Folds duplicate events into one
+//! - `attribution` - Every place an event is listed, and which one we link to
+//! - `duplicates` - Finds the same show under different titles, merges or queues it
+//! - `preferences` - Learns category preferences from interactions
+//! - `export` - Streams a user's "download my data" document
+//! - `oauth` - "Sign in with Google" code exchange
+//! - `search` - Event search filters shared by routes and jobs
+//! - `saved_searches` - Notifies users about new events matching saved searches
+//! - `reminders` - Reminds attendees about events starting soon
+//! - `recommendation` - Deterministic event ranking for a user
+//! - `guests` - Guest accounts and merging them into real ones
+//! - `mailer` - Outgoing email (SMTP, or the log in dev)
+//! - `verification` - Signed email verification links
+//! - `categories` - Valid category names for preferences
 //!
 //! ## Architecture
 //! ```text
@@ -29,9 +61,8 @@
 //!
 //! ## Future Services
 //! As the app grows, consider adding:
-//! - `notification` - Push notifications for saved events
-//! - `analytics` - Track popular events, user trends
-//! - `geocoding` - Convert addresses to coordinates for location search
+//! - `notification` - Push/email delivery for the `notifications` table
+//!   (email can go through `mailer`)
 //!
 //! ## Owner
 //! Will (Coordinator/Backend Lead) - module structure
@@ -49,4 +80,176 @@
 /// - Personalized recommendations
 ///
 /// Owner: Ben (AI Engineer)
-pub mod llm;
\ No newline at end of file
+pub mod llm;
+
+/// `LlmProvider` trait with Gemini, OpenAI-compatible and mock
+/// implementations, chosen by `LLM_PROVIDER`.
+///
+/// Owner: Ben (AI Engineer)
+pub mod llm_provider;
+
+/// Records every LLM call (tokens, latency, outcome) and sums them up
+/// for the admin usage report.
+///
+/// Owner: Ben (AI Engineer)
+pub mod llm_usage;
+
+/// In-memory TTL cache of parsed intents (`SearchParams`).
+///
+/// Owner: Ben (AI Engineer)
+pub mod intent_cache;
+
+/// Token buckets for `POST /api/chat` and a daily cap on model calls.
+///
+/// Owner: Ben (AI Engineer)
+pub mod rate_limit;
+
+/// Strips markup and instruction-like lines from scraped and user text
+/// before it goes into a prompt.
+///
+/// Owner: Ben (AI Engineer)
+pub mod sanitize;
+
+/// Chat sessions: stored turns, trimmed to a token budget for the model.
+///
+/// Owner: Ben (AI Engineer)
+pub mod chat_history;
+
+/// Starter prompts for an empty chat box, built from cheap aggregates and
+/// cached per user.
+///
+/// Owner: Ben (AI Engineer)
+pub mod suggestions;
+
+/// Background job that asks the LLM to categorize uncategorized events.
+///
+/// Owner: Ben (AI Engineer)
+pub mod classification;
+
+/// The chat system prompt: loaded from `LLM_SYSTEM_PROMPT_FILE` (or the
+/// environment, or built in), versioned and validated, reloadable.
+///
+/// Owner: Ben (AI Engineer)
+pub mod prompt;
+
+/// Cached health pings of the chat model, for `GET /api/health/llm` and
+/// readiness.
+///
+/// Owner: Ben (AI Engineer)
+pub mod llm_health;
+
+/// iCalendar (RFC 5545) rendering.
+///
+/// Turns events into `.ics` data for "Add to calendar" downloads
+/// and subscription feeds.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod ics;
+
+/// Geographic helpers (Haversine distance in Rust and SQL).
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod geo;
+
+/// Venue and event addresses to coordinates, cached per address.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod geocoding;
+
+/// Popularity signals derived from user interactions.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod analytics;
+
+/// Local timezone handling for time-of-day windows.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod dates;
+
+/// schema.org `Event` JSON-LD types, shared by the export endpoint and
+/// the scraper's JSON-LD extractor.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod jsonld;
+
+/// Periodic background jobs (tokio intervals). The scraper schedule will
+/// run on the same mechanism.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod scheduler;
+
+/// Background job that soft-archives events long after they end.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod archive;
+
+/// Duplicate event merging (admin endpoint and scraper dedup).
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod merge;
+
+/// Every listing of an event and the choice of its primary link.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod attribution;
+
+/// Fuzzy cross-source duplicate detection after each scrape.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod duplicates;
+
+/// Background job that infers category preferences from interactions.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod preferences;
+
+/// Streamed "download my data" export of a user's records.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod export;
+
+/// OAuth identity providers (Google sign-in).
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod oauth;
+
+/// Event search parameters and their SQL filter conditions.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod search;
+
+/// Background job that turns saved-search matches into notifications.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod saved_searches;
+
+/// Background job that reminds attendees about upcoming events.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod reminders;
+
+/// Scores upcoming events against a user's preferences, follows and
+/// history (no LLM involved).
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod recommendation;
+
+/// Guest account placeholders and the claim-time merge.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod guests;
+
+/// `Mailer` trait with SMTP and log-only implementations.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod mailer;
+
+/// Email verification tokens and the message that carries them.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod verification;
+
+/// The category taxonomy preferences are checked against.
+///
+/// Owner: Will (Coordinator/Backend Lead)
+pub mod categories;
@@ -0,0 +1,198 @@
+//! # OAuth Sign-In
+//!
+//! "Sign in with Google" via the OAuth 2.0 authorization code flow.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Flow
+//! ```text
+//! GET /api/auth/google           -> 302 to Google's consent screen (with state)
+//! Google                         -> 302 to /api/auth/google/callback?code=..&state=..
+//! callback: exchange_code(code)  -> POST oauth2.googleapis.com/token
+//!                                   GET  openidconnect.googleapis.com/v1/userinfo
+//!           find or create user  -> 302 to FRONTEND_URL/auth/callback#token=..
+//! ```
+//!
+//! ## Environment Variables
+//! ```text
+//! GOOGLE_CLIENT_ID=...
+//! GOOGLE_CLIENT_SECRET=...
+//! GOOGLE_REDIRECT_URL=http://localhost:3000/api/auth/google/callback
+//! ```
+//! Without the client id and secret the Google routes answer 503.
+//!
+//! ## Testing
+//! Handlers talk to Google only through `OAuthProvider`, so tests swap in
+//! a provider that returns a canned identity.
+
+use axum::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::error::AppError;
+
+// =============================================================================
+// PROVIDER INTERFACE
+// =============================================================================
+
+/// Who the provider says signed in.
+#[derive(Debug, Clone)]
+pub struct OAuthIdentity {
+    /// Stable account id at the provider (Google's `sub`). Survives email
+    /// changes, so it's what repeat logins are matched on.
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+/// An OAuth 2.0 identity provider.
+#[async_trait]
+pub trait OAuthProvider: Send + Sync {
+    /// Value stored in `users.oauth_provider`.
+    fn name(&self) -> &'static str;
+
+    /// Consent screen URL to redirect the browser to.
+    fn authorize_url(&self, state: &str) -> String;
+
+    /// Trades the callback's `code` for the signed-in identity.
+    async fn exchange_code(&self, code: &str) -> Result<OAuthIdentity, AppError>;
+}
+
+// =============================================================================
+// GOOGLE
+// =============================================================================
+
+const GOOGLE_AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const GOOGLE_USERINFO_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+
+/// Google's OAuth client, configured from the environment.
+pub struct GoogleOAuth {
+    client: Client,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleToken {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleUserInfo {
+    sub: String,
+    email: String,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+}
+
+impl GoogleOAuth {
+    /// Reads `GOOGLE_CLIENT_ID`, `GOOGLE_CLIENT_SECRET` and
+    /// `GOOGLE_REDIRECT_URL`. 503 if the id or secret is missing.
+    pub fn from_env() -> Result<Self, AppError> {
+        let var = |key: &str| std::env::var(key).ok().filter(|value| !value.is_empty());
+
+        let (Some(client_id), Some(client_secret)) = (var("GOOGLE_CLIENT_ID"), var("GOOGLE_CLIENT_SECRET")) else {
+            tracing::error!("GOOGLE_CLIENT_ID / GOOGLE_CLIENT_SECRET not set; Google sign-in is off");
+            return Err(AppError::Unavailable("Google sign-in is not configured".to_string()));
+        };
+
+        Ok(Self {
+            client: Client::new(),
+            client_id,
+            client_secret,
+            redirect_url: var("GOOGLE_REDIRECT_URL")
+                .unwrap_or_else(|| "http://localhost:3000/api/auth/google/callback".to_string()),
+        })
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GoogleOAuth {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn authorize_url(&self, state: &str) -> String {
+        let url = reqwest::Url::parse_with_params(
+            GOOGLE_AUTHORIZE_URL,
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", self.redirect_url.as_str()),
+                ("response_type", "code"),
+                ("scope", "openid email profile"),
+                ("state", state),
+                ("prompt", "select_account"),
+            ],
+        )
+            .expect("Google authorize URL is valid");
+        url.to_string()
+    }
+
+    async fn exchange_code(&self, code: &str) -> Result<OAuthIdentity, AppError> {
+        let token: GoogleToken = self
+            .client
+            .post(GOOGLE_TOKEN_URL)
+            .form(&[
+                ("code", code),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_url),
+                ("grant_type", "authorization_code"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let info: GoogleUserInfo = self
+            .client
+            .get(GOOGLE_USERINFO_URL)
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(OAuthIdentity {
+            subject: info.sub,
+            email: info.email,
+            email_verified: info.email_verified,
+            name: info.name,
+        })
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authorize_url_carries_client_and_state() {
+        let google = GoogleOAuth {
+            client: Client::new(),
+            client_id: "client-123".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_url: "http://localhost:3000/api/auth/google/callback".to_string(),
+        };
+
+        let url = reqwest::Url::parse(&google.authorize_url("st&te")).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.host_str(), Some("accounts.google.com"));
+        assert_eq!(params["client_id"], "client-123");
+        assert_eq!(params["state"], "st&te");
+        assert_eq!(params["redirect_uri"], "http://localhost:3000/api/auth/google/callback");
+        assert!(!params.contains_key("client_secret"));
+    }
+}
@@ -0,0 +1,339 @@
+//! # Preference Learning
+//!
+//! Infers category preferences from what users actually do. Attending,
+//! saving and viewing jazz events says "likes jazz" even if the user never
+//! touched the preferences screen; dismissing comedy shows says the
+//! opposite.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Environment Variables
+//! ```text
+//! PREFERENCE_LEARNING_INTERVAL_MINUTES=60  # how often the job runs
+//! ```
+//!
+//! ## Scoring
+//! Over the last `LEARNING_WINDOW_DAYS`, each interaction adds its
+//! `analytics::weight_for` score to every category of its event. Every
+//! `POINTS_PER_WEIGHT` points is one step of preference weight, capped at
+//! `±MAX_INFERRED_WEIGHT` so inference never claims "love" or "hate":
+//! ```text
+//! 2 saves + 1 view of jazz events  = 7 points  -> weight +2
+//! 3 dismissed comedy events        = -6 points -> weight -2
+//! ```
+//!
+//! ## Explicit Wins
+//! Rows are tagged with `source`. The job only inserts or updates
+//! `inferred` rows, so a weight the user set is never overwritten. Setting
+//! a preference through the API turns an inferred row into an explicit
+//! one. Inferred rows whose signal has faded to zero are removed.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{analytics, scheduler};
+use crate::models::LearningReport;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Days of interaction history the job looks at.
+pub const LEARNING_WINDOW_DAYS: i32 = 60;
+
+/// Interaction points that make up one step of inferred weight.
+pub const POINTS_PER_WEIGHT: i64 = 3;
+
+/// Largest preference weight in either direction. Explicit weights outside
+/// it are rejected (422) and the recommendation scorer clamps to it.
+pub const MAX_PREFERENCE_WEIGHT: i32 = 5;
+
+/// Largest inferred weight in either direction (below
+/// `MAX_PREFERENCE_WEIGHT`, so explicit choices can always outweigh it).
+pub const MAX_INFERRED_WEIGHT: i32 = 3;
+
+/// Default minutes between learning runs.
+pub const DEFAULT_LEARNING_INTERVAL_MINUTES: u64 = 60;
+
+// =============================================================================
+// SCORING
+// =============================================================================
+
+/// Interaction count for one user, event category and interaction type.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategoryActivity {
+    pub user_id: Uuid,
+    pub category: String,
+    pub interaction_type: String,
+    pub count: i64,
+}
+
+/// A preference weight the job wants to store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredPreference {
+    pub user_id: Uuid,
+    pub category: String,
+    pub weight: i32,
+}
+
+/// Turns interaction counts into inferred weights.
+///
+/// Categories whose score rounds to zero are left out. The result is
+/// sorted by user, then category.
+pub fn infer_weights(activity: &[CategoryActivity]) -> Vec<InferredPreference> {
+    let mut points: BTreeMap<(Uuid, &str), i64> = BTreeMap::new();
+    for row in activity {
+        let score = row.count * i64::from(analytics::weight_for(&row.interaction_type));
+        *points.entry((row.user_id, row.category.as_str())).or_default() += score;
+    }
+
+    points
+        .into_iter()
+        .filter_map(|((user_id, category), points)| {
+            let max = i64::from(MAX_INFERRED_WEIGHT);
+            let weight = (points / POINTS_PER_WEIGHT).clamp(-max, max) as i32;
+            (weight != 0).then(|| InferredPreference {
+                user_id,
+                category: category.to_string(),
+                weight,
+            })
+        })
+        .collect()
+}
+
+// =============================================================================
+// JOB
+// =============================================================================
+
+/// Recomputes every user's inferred preferences.
+///
+/// Runs in one transaction: upserts the new weights (skipping explicit
+/// rows), then drops inferred rows that no longer score.
+pub async fn learn_preferences(pool: &PgPool) -> Result<LearningReport, sqlx::Error> {
+    let activity = sqlx::query_as::<_, CategoryActivity>(
+        r#"
+        SELECT ui.user_id, c.category, ui.interaction_type, COUNT(*) AS count
+        FROM user_interactions ui
+        JOIN events e ON e.id = ui.event_id
+        CROSS JOIN LATERAL unnest(e.categories) AS c(category)
+        WHERE ui.created_at > NOW() - make_interval(days => $1)
+        GROUP BY ui.user_id, c.category, ui.interaction_type
+        "#,
+    )
+        .bind(LEARNING_WINDOW_DAYS)
+        .fetch_all(pool)
+        .await?;
+
+    let users = activity
+        .iter()
+        .map(|row| row.user_id)
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+
+    let inferred = infer_weights(&activity);
+    let user_ids: Vec<Uuid> = inferred.iter().map(|p| p.user_id).collect();
+    let categories: Vec<String> = inferred.iter().map(|p| p.category.clone()).collect();
+    let weights: Vec<i32> = inferred.iter().map(|p| p.weight).collect();
+
+    let mut tx = pool.begin().await?;
+
+    let upserted = sqlx::query(
+        r#"
+        INSERT INTO user_preferences (id, user_id, category, weight, source, created_at)
+        SELECT gen_random_uuid(), p.user_id, p.category, p.weight, 'inferred', NOW()
+        FROM unnest($1::uuid[], $2::text[], $3::int[]) AS p(user_id, category, weight)
+        ON CONFLICT (user_id, category)
+        DO UPDATE SET weight = EXCLUDED.weight
+        WHERE user_preferences.source = 'inferred'
+          AND user_preferences.weight <> EXCLUDED.weight
+        "#,
+    )
+        .bind(&user_ids)
+        .bind(&categories)
+        .bind(&weights)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM user_preferences up
+        WHERE up.source = 'inferred'
+          AND NOT EXISTS (
+              SELECT 1
+              FROM unnest($1::uuid[], $2::text[]) AS p(user_id, category)
+              WHERE p.user_id = up.user_id AND p.category = up.category
+          )
+        "#,
+    )
+        .bind(&user_ids)
+        .bind(&categories)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(LearningReport { users, upserted, removed })
+}
+
+/// Starts the periodic learning job using the environment configuration.
+pub fn spawn_preference_learner(pool: PgPool) {
+    let minutes = scheduler::env_u64(
+        "PREFERENCE_LEARNING_INTERVAL_MINUTES",
+        DEFAULT_LEARNING_INTERVAL_MINUTES,
+    );
+
+    scheduler::spawn_periodic(
+        "preference_learning",
+        Duration::from_secs(minutes * 60),
+        pool,
+        |pool| async move {
+            let report = learn_preferences(&pool).await?;
+            if report.upserted > 0 || report.removed > 0 {
+                tracing::info!(
+                    users = report.users,
+                    upserted = report.upserted,
+                    removed = report.removed,
+                    "learned preferences"
+                );
+            }
+            Ok::<_, sqlx::Error>(())
+        },
+    );
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(user_id: Uuid, category: &str, interaction_type: &str, count: i64) -> CategoryActivity {
+        CategoryActivity {
+            user_id,
+            category: category.to_string(),
+            interaction_type: interaction_type.to_string(),
+            count,
+        }
+    }
+
+    fn weight_of(inferred: &[InferredPreference], user_id: Uuid, category: &str) -> Option<i32> {
+        inferred
+            .iter()
+            .find(|p| p.user_id == user_id && p.category == category)
+            .map(|p| p.weight)
+    }
+
+    #[test]
+    fn scores_a_synthetic_history() {
+        let fan = Uuid::new_v4();
+        let critic = Uuid::new_v4();
+        let history = vec![
+            // 2 saves + 1 view = 7 points -> +2
+            activity(fan, "jazz", "save", 2),
+            activity(fan, "jazz", "view", 1),
+            // past-tense spellings count the same: 3 * -2 = -6 -> -2
+            activity(fan, "comedy", "dismissed", 3),
+            // 2 views = 2 points -> rounds to 0, left out
+            activity(fan, "theater", "view", 2),
+            // 4 attends = 20 points -> capped at +3
+            activity(critic, "jazz", "attend", 4),
+            // save then dismiss cancels out
+            activity(critic, "sports", "save", 2),
+            activity(critic, "sports", "dismiss", 3),
+        ];
+
+        let inferred = infer_weights(&history);
+
+        assert_eq!(weight_of(&inferred, fan, "jazz"), Some(2));
+        assert_eq!(weight_of(&inferred, fan, "comedy"), Some(-2));
+        assert_eq!(weight_of(&inferred, fan, "theater"), None);
+        assert_eq!(weight_of(&inferred, critic, "jazz"), Some(MAX_INFERRED_WEIGHT));
+        assert_eq!(weight_of(&inferred, critic, "sports"), None);
+        assert_eq!(inferred.len(), 3);
+    }
+
+    #[test]
+    fn caps_negative_weights() {
+        let user = Uuid::new_v4();
+        let inferred = infer_weights(&[activity(user, "opera", "dismiss", 50)]);
+
+        assert_eq!(weight_of(&inferred, user, "opera"), Some(-MAX_INFERRED_WEIGHT));
+    }
+
+    #[test]
+    fn ignores_unknown_interaction_types() {
+        let user = Uuid::new_v4();
+        let inferred = infer_weights(&[activity(user, "jazz", "shared", 10)]);
+
+        assert!(inferred.is_empty());
+    }
+
+    #[tokio::test]
+    async fn never_overwrites_explicit_preferences() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let (user_id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let (event_id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO events (title, source_url, start_time, categories) \
+             VALUES ('Jazz Night', $1, NOW() + INTERVAL '1 day', ARRAY['jazz', 'blues']) RETURNING id",
+        )
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO user_interactions (user_id, event_id, interaction_type) VALUES ($1, $2, 'attend')",
+        )
+            .bind(user_id)
+            .bind(event_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO user_preferences (user_id, category, weight) VALUES ($1, 'jazz', -4)",
+        )
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        learn_preferences(&pool).await.unwrap();
+
+        let rows: Vec<(String, i32, String)> = sqlx::query_as(
+            "SELECT category, weight, source::text FROM user_preferences WHERE user_id = $1 ORDER BY category",
+        )
+            .bind(user_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                ("blues".to_string(), 1, "inferred".to_string()),
+                ("jazz".to_string(), -4, "explicit".to_string()),
+            ]
+        );
+    }
+}
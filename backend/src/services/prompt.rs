@@ -0,0 +1,216 @@
+//! # System Prompt
+//!
+//! The chat system prompt, loadable without a rebuild. `PromptStore`
+//! holds the prompt in use; chat reads it per message, and
+//! `POST /api/admin/llm/prompt/reload` re-reads the file in place.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Sources
+//! In order of preference:
+//! 1. `LLM_SYSTEM_PROMPT_FILE` - a path, read at startup and on reload
+//! 2. `LLM_SYSTEM_PROMPT` - the prompt itself (no reload)
+//! 3. `llm::SYSTEM_PROMPT` - built in
+//!
+//! A configured prompt that can't be read or fails validation at startup
+//! is logged and the built-in one is used instead.
+//!
+//! ## Validation
+//! The prompt must still describe every chat tool by name and explain the
+//! untrusted-text markers (`llm::missing_prompt_sections`). A reload that
+//! fails this keeps the current prompt.
+//!
+//! ## Versions
+//! Each prompt has a `version`: where it came from and a hash of its text
+//! (`file-3f9a0c12`). It is stored with every chat call in `llm_calls`
+//! and shown in the chat debug trace, so answers can be compared across
+//! prompt changes.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+use crate::services::llm;
+
+// =============================================================================
+// PROMPT
+// =============================================================================
+
+/// A validated system prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SystemPrompt {
+    pub text: String,
+    /// `<source>-<hash>`, e.g. `builtin-1b2c3d4e`
+    pub version: String,
+}
+
+impl SystemPrompt {
+    /// `text` from `source` ("file", "env", "builtin"), if it passes
+    /// validation.
+    pub fn new(text: &str, source: &str) -> Result<Self, PromptError> {
+        let text = text.trim();
+        let missing = llm::missing_prompt_sections(text);
+        if !missing.is_empty() {
+            return Err(PromptError::Missing(missing));
+        }
+
+        Ok(Self {
+            version: format!("{}-{:08x}", source, fnv1a(text) as u32),
+            text: text.to_string(),
+        })
+    }
+
+    /// `llm::SYSTEM_PROMPT`.
+    pub fn builtin() -> Self {
+        Self::new(llm::SYSTEM_PROMPT, "builtin").expect("the built-in prompt is valid")
+    }
+
+    /// The prompt in `path`.
+    pub fn read(path: &Path) -> Result<Self, PromptError> {
+        let text = std::fs::read_to_string(path).map_err(|e| PromptError::Read(format!("{}: {}", path.display(), e)))?;
+        Self::new(&text, "file")
+    }
+}
+
+impl Default for SystemPrompt {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Why a prompt wasn't loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum PromptError {
+    #[error("could not read the prompt file {0}")]
+    Read(String),
+
+    #[error("prompt is missing required sections: {}", .0.join(", "))]
+    Missing(Vec<String>),
+
+    #[error("LLM_SYSTEM_PROMPT_FILE is not set; there is nothing to reload")]
+    NoFile,
+}
+
+/// 64-bit FNV-1a: stable across builds, unlike `DefaultHasher`.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// =============================================================================
+// STORE
+// =============================================================================
+
+/// The prompt in use, shared through the router state.
+pub struct PromptStore {
+    /// Re-read by `reload`
+    file: Option<PathBuf>,
+    current: RwLock<Arc<SystemPrompt>>,
+}
+
+impl PromptStore {
+    pub fn new(file: Option<PathBuf>, prompt: SystemPrompt) -> Self {
+        Self {
+            file,
+            current: RwLock::new(Arc::new(prompt)),
+        }
+    }
+
+    /// The prompt from `LLM_SYSTEM_PROMPT_FILE` or `LLM_SYSTEM_PROMPT`,
+    /// falling back to the built-in one.
+    pub fn from_env() -> Self {
+        let file = std::env::var("LLM_SYSTEM_PROMPT_FILE").ok().filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+
+        let configured = match (&file, std::env::var("LLM_SYSTEM_PROMPT")) {
+            (Some(path), _) => Some(SystemPrompt::read(path)),
+            (None, Ok(text)) if !text.trim().is_empty() => Some(SystemPrompt::new(&text, "env")),
+            _ => None,
+        };
+        let prompt = match configured {
+            Some(Ok(prompt)) => {
+                tracing::info!(version = %prompt.version, "loaded the system prompt");
+                prompt
+            }
+            Some(Err(e)) => {
+                tracing::error!(error = %e, "using the built-in system prompt");
+                SystemPrompt::builtin()
+            }
+            None => SystemPrompt::builtin(),
+        };
+
+        Self::new(file, prompt)
+    }
+
+    /// The prompt to use now.
+    pub fn current(&self) -> Arc<SystemPrompt> {
+        self.current.read().expect("prompt lock").clone()
+    }
+
+    /// Re-reads the prompt file and swaps it in if it is valid; otherwise
+    /// the current prompt stays.
+    pub fn reload(&self) -> Result<Arc<SystemPrompt>, PromptError> {
+        let path = self.file.as_deref().ok_or(PromptError::NoFile)?;
+        let prompt = Arc::new(SystemPrompt::read(path)?);
+
+        let mut current = self.current.write().expect("prompt lock");
+        if current.version != prompt.version {
+            tracing::info!(from = %current.version, to = %prompt.version, "reloaded the system prompt");
+        }
+        *current = prompt.clone();
+        Ok(prompt)
+    }
+}
+
+impl Default for PromptStore {
+    fn default() -> Self {
+        Self::new(None, SystemPrompt::builtin())
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_follow_the_text() {
+        let builtin = SystemPrompt::builtin();
+        assert!(builtin.version.starts_with("builtin-"));
+        assert_eq!(builtin, SystemPrompt::builtin());
+
+        let edited = SystemPrompt::new(&format!("{}\nBe brief.", llm::SYSTEM_PROMPT), "env").unwrap();
+        assert!(edited.version.starts_with("env-"));
+        assert_ne!(edited.version[4..], builtin.version[8..]);
+
+        let error = SystemPrompt::new("You are Tully. Be nice.", "env").unwrap_err();
+        assert!(matches!(&error, PromptError::Missing(missing) if missing.iter().any(|m| m == "search_events")));
+    }
+
+    #[test]
+    fn reloads_only_swap_in_valid_prompts() {
+        let path = std::env::temp_dir().join(format!("prompt-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, llm::SYSTEM_PROMPT).unwrap();
+        let store = PromptStore::new(Some(path.clone()), SystemPrompt::read(&path).unwrap());
+        let first = store.current();
+
+        std::fs::write(&path, format!("{}\nAlways mention parking.", llm::SYSTEM_PROMPT)).unwrap();
+        let reloaded = store.reload().unwrap();
+        assert_ne!(reloaded.version, first.version);
+        assert!(store.current().text.ends_with("Always mention parking."));
+
+        // The tool descriptions are gone: refused, and the last good one stays
+        std::fs::write(&path, "You are Tully.").unwrap();
+        assert!(matches!(store.reload(), Err(PromptError::Missing(_))));
+        assert_eq!(store.current(), reloaded);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(store.reload(), Err(PromptError::Read(_))));
+        assert!(matches!(PromptStore::default().reload(), Err(PromptError::NoFile)));
+    }
+}
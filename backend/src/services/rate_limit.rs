@@ -0,0 +1,288 @@
+//! # Chat Rate Limits
+//!
+//! Every chat message costs model calls, and the API quota is shared by
+//! everyone. Two limits keep one client, or one busy day, from spending
+//! all of it.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Per Client
+//! `ChatRateLimiter` gives each signed-in user with a verified email, or
+//! each IP address for anonymous, guest and unverified chat, a token
+//! bucket holding `CHAT_REQUESTS_PER_MINUTE` requests, refilled evenly over
+//! the minute. When the bucket is empty `POST /api/chat` answers
+//! `429 Too Many Requests` with a `Retry-After` header. Requests carrying the admin key (`X-Admin-Key`) aren't limited.
+//!
+//! ## Per Day
+//! `DailyCallBudget` counts model calls (chat, intent parsing and
+//! classification alike) per local day. Once `LLM_DAILY_CALL_BUDGET` is
+//! spent, `BudgetedProvider` fails further calls with
+//! `LlmError::BudgetExhausted`, which counts as unavailable: chat answers
+//! with the `degraded` plain search instead of refusing, and the
+//! classifier waits for the next day.
+//!
+//! ## Environment Variables
+//! ```text
+//! CHAT_REQUESTS_PER_MINUTE=10    # per verified user, or per IP otherwise
+//! LLM_DAILY_CALL_BUDGET=5000     # model calls per local day (unset: no limit)
+//! ```
+//!
+//! ## Time
+//! Both limits take the time as an argument (`Instant`, local date), so
+//! tests can run the clock instead of sleeping.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::async_trait;
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::services::dates;
+use crate::services::llm::LlmError;
+use crate::services::llm_provider::{GenerateOptions, LlmMessage, LlmProvider, LlmResponse, SharedProvider, ToolSpec};
+use crate::services::scheduler;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default chat requests per client per minute.
+pub const DEFAULT_CHAT_REQUESTS_PER_MINUTE: u64 = 10;
+
+/// Clients tracked before idle (full) buckets are forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+// =============================================================================
+// PER CLIENT
+// =============================================================================
+
+/// Who a chat request counts against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    User(Uuid),
+    /// Anonymous, guest or unverified chat, by remote address
+    Ip(IpAddr),
+}
+
+/// Token buckets for chat requests, shared through the router state.
+///
+/// A bucket is kept as the time it will be full again: each request
+/// pushes that a token's worth (a minute / `per_minute`) later, and a
+/// request fits while it is less than a minute away. Durations stay
+/// whole nanoseconds, with no float drift.
+pub struct ChatRateLimiter {
+    per_minute: u64,
+    full_at: Mutex<HashMap<ClientKey, Instant>>,
+}
+
+impl ChatRateLimiter {
+    pub fn new(per_minute: u64) -> Self {
+        Self {
+            per_minute,
+            full_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A limiter with `CHAT_REQUESTS_PER_MINUTE`, or the default.
+    pub fn from_env() -> Self {
+        Self::new(scheduler::env_u64("CHAT_REQUESTS_PER_MINUTE", DEFAULT_CHAT_REQUESTS_PER_MINUTE))
+    }
+
+    /// Takes one request from `client`'s bucket at `now`.
+    ///
+    /// # Returns
+    /// * `Ok(())` - the request may go ahead
+    /// * `Err(wait)` - the bucket is empty; a request fits again after `wait`
+    pub fn check(&self, client: ClientKey, now: Instant) -> Result<(), Duration> {
+        let window = Duration::from_secs(60);
+        let token = window / self.per_minute as u32;
+        let mut full_at = self.full_at.lock().expect("rate limiter lock");
+
+        if full_at.len() >= MAX_TRACKED_CLIENTS && !full_at.contains_key(&client) {
+            // A full bucket is the same as no bucket
+            full_at.retain(|_, full| *full > now);
+        }
+
+        let full = full_at.get(&client).copied().unwrap_or(now).max(now);
+        let refilling = full - now;
+        if refilling + token > window {
+            return Err(refilling + token - window);
+        }
+
+        full_at.insert(client, full + token);
+        Ok(())
+    }
+}
+
+impl Default for ChatRateLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHAT_REQUESTS_PER_MINUTE)
+    }
+}
+
+// =============================================================================
+// PER DAY
+// =============================================================================
+
+/// Model calls allowed per local day, across every user and purpose.
+pub struct DailyCallBudget {
+    /// `None`: no limit
+    limit: Option<u64>,
+    /// The day being counted and the calls made on it
+    spent: Mutex<(NaiveDate, u64)>,
+}
+
+impl DailyCallBudget {
+    pub fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            spent: Mutex::new((NaiveDate::MIN, 0)),
+        }
+    }
+
+    /// The budget from `LLM_DAILY_CALL_BUDGET`; unset (or invalid) means
+    /// no limit.
+    pub fn from_env() -> Self {
+        let limit = match std::env::var("LLM_DAILY_CALL_BUDGET") {
+            Ok(raw) => match raw.trim().parse::<u64>() {
+                Ok(limit) if limit > 0 => Some(limit),
+                _ => {
+                    tracing::warn!("Invalid LLM_DAILY_CALL_BUDGET '{}', not limiting model calls", raw);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+        Self::new(limit)
+    }
+
+    /// Counts one call on `today`. `false` (and nothing counted) if the
+    /// day's budget is already spent.
+    pub fn try_spend(&self, today: NaiveDate) -> bool {
+        let Some(limit) = self.limit else {
+            return true;
+        };
+
+        let mut spent = self.spent.lock().expect("call budget lock");
+        if spent.0 != today {
+            *spent = (today, 0);
+        }
+        if spent.1 >= limit {
+            return false;
+        }
+        spent.1 += 1;
+        true
+    }
+}
+
+/// A provider whose calls count against a `DailyCallBudget`.
+pub struct BudgetedProvider {
+    inner: SharedProvider,
+    budget: DailyCallBudget,
+}
+
+impl BudgetedProvider {
+    pub fn new(inner: SharedProvider, budget: DailyCallBudget) -> Self {
+        Self { inner, budget }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for BudgetedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    async fn generate(
+        &self,
+        messages: &[LlmMessage],
+        tools: &[ToolSpec],
+        options: GenerateOptions,
+    ) -> Result<LlmResponse, LlmError> {
+        let today = Utc::now().with_timezone(&dates::local_timezone()).date_naive();
+        if !self.budget.try_spend(today) {
+            return Err(LlmError::BudgetExhausted);
+        }
+        self.inner.generate(messages, tools, options).await
+    }
+}
+
+/// `provider`, held to `LLM_DAILY_CALL_BUDGET` if one is set.
+pub fn with_daily_budget(provider: SharedProvider) -> SharedProvider {
+    let budget = DailyCallBudget::from_env();
+    match budget.limit {
+        Some(limit) => {
+            tracing::info!(limit, "model calls limited per day");
+            Arc::new(BudgetedProvider::new(provider, budget))
+        }
+        None => provider,
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm_provider::MockProvider;
+
+    #[test]
+    fn each_client_gets_its_own_bucket() {
+        let limiter = ChatRateLimiter::new(3);
+        let start = Instant::now();
+        let sam = ClientKey::User(Uuid::new_v4());
+        let anonymous = ClientKey::Ip("203.0.113.9".parse().unwrap());
+
+        for _ in 0..3 {
+            assert_eq!(limiter.check(sam, start), Ok(()));
+        }
+        // One token comes back every 20 seconds
+        assert_eq!(limiter.check(sam, start), Err(Duration::from_secs(20)));
+        assert_eq!(limiter.check(anonymous, start), Ok(()));
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(limiter.check(sam, later), Err(Duration::from_secs(10)));
+        assert_eq!(limiter.check(sam, start + Duration::from_secs(20)), Ok(()));
+        assert!(limiter.check(sam, start + Duration::from_secs(20)).is_err());
+
+        // A long pause refills the bucket, but no further than full
+        let tomorrow = start + Duration::from_secs(86_400);
+        for _ in 0..3 {
+            assert_eq!(limiter.check(sam, tomorrow), Ok(()));
+        }
+        assert!(limiter.check(sam, tomorrow).is_err());
+    }
+
+    #[tokio::test]
+    async fn the_daily_budget_resets_each_day() {
+        let monday = NaiveDate::from_ymd_opt(2026, 1, 19).unwrap();
+        let budget = DailyCallBudget::new(Some(2));
+
+        assert!(budget.try_spend(monday));
+        assert!(budget.try_spend(monday));
+        assert!(!budget.try_spend(monday));
+        assert!(budget.try_spend(monday.succ_opt().unwrap()));
+
+        let unlimited = DailyCallBudget::new(None);
+        assert!((0..1000).all(|_| unlimited.try_spend(monday)));
+
+        // A spent budget looks like an unavailable model
+        let model = Arc::new(MockProvider::new(|_, _| Ok(LlmResponse::text("hi"))));
+        let budgeted = BudgetedProvider::new(model.clone(), DailyCallBudget::new(Some(1)));
+        let messages = [LlmMessage::user("hello")];
+        assert!(budgeted.generate(&messages, &[], GenerateOptions::default()).await.is_ok());
+        let spent = budgeted.generate(&messages, &[], GenerateOptions::default()).await.unwrap_err();
+        assert!(matches!(spent, LlmError::BudgetExhausted) && spent.is_unavailable());
+        assert_eq!(model.calls(), 1);
+    }
+}
@@ -0,0 +1,404 @@
+//! # Recommendations
+//!
+//! Ranks upcoming events for a user without calling the LLM. Scoring is a
+//! set of pure functions over a `RecommendationProfile` and candidate
+//! events, so it can be tested without a database; `recommend` loads both
+//! and ranks. The chat flow can call `recommend` to pre-rank candidates
+//! before handing them to the model.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Scoring
+//! | Signal | Points |
+//! |--------|--------|
+//! | Each event category with a preference | + that weight (clamped to ±`MAX_PREFERENCE_WEIGHT`) |
+//! | At a followed venue | + `FOLLOWED_VENUE_BOOST` |
+//! | In a followed category | + `FOLLOWED_CATEGORY_BOOST` (once) |
+//! | A category the user recently engaged with | + `RECENT_CATEGORY_BOOST` (once) |
+//! | Within the user's radius of their home point | + `NEARBY_BOOST` |
+//! | Already viewed | − `VIEWED_PENALTY` |
+//! | Dismissed (and not saved since) | − `DISMISSED_PENALTY` |
+//!
+//! Follows outrank any single preference on purpose: they're explicit
+//! subscriptions. Ties go to the event starting soonest.
+//!
+//! ## Candidates
+//! The `CANDIDATE_LIMIT` soonest upcoming events that aren't cancelled,
+//! archived, or dismissed by the user (same rule as the list and search
+//! endpoints, see `db::not_dismissed_by`).
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::preferences::MAX_PREFERENCE_WEIGHT;
+use super::{analytics, geo};
+use crate::db::{
+    not_dismissed_by, EVENT_COLUMNS, NOT_ARCHIVED_FILTER, NOT_CANCELLED_FILTER, UPCOMING_FILTER, USER_COLUMNS,
+};
+use crate::error::AppError;
+use crate::models::{Event, FollowTarget, User};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Points for an event at a followed venue.
+pub const FOLLOWED_VENUE_BOOST: i32 = 10;
+
+/// Points for an event in at least one followed category.
+pub const FOLLOWED_CATEGORY_BOOST: i32 = 8;
+
+/// Points for an event in a category the user recently engaged with.
+pub const RECENT_CATEGORY_BOOST: i32 = 1;
+
+/// Points for an event within the user's radius of home.
+pub const NEARBY_BOOST: i32 = 2;
+
+/// Penalty for an event the user already viewed.
+pub const VIEWED_PENALTY: i32 = 2;
+
+/// Penalty for a dismissed event (only reachable when callers pass
+/// candidates that weren't filtered).
+pub const DISMISSED_PENALTY: i32 = 20;
+
+/// Days of interactions that count as "recent" engagement.
+pub const RECENT_DAYS: i32 = 30;
+
+/// Radius used for `NEARBY_BOOST` when the user set a home point but no
+/// `preferred_radius_km`.
+pub const DEFAULT_NEARBY_KM: f64 = 15.0;
+
+/// Upcoming events considered per request.
+pub const CANDIDATE_LIMIT: i64 = 500;
+
+/// Default and largest number of recommendations returned.
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 50;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Everything about a user that scoring looks at.
+#[derive(Debug, Clone, Default)]
+pub struct RecommendationProfile {
+    /// Category -> preference weight
+    pub category_weights: HashMap<String, i32>,
+    pub followed_venues: HashSet<Uuid>,
+    pub followed_categories: HashSet<String>,
+    /// Categories of events the user viewed, saved or attended lately
+    pub recent_categories: HashSet<String>,
+    pub viewed: HashSet<Uuid>,
+    pub dismissed: HashSet<Uuid>,
+    /// Home point and radius in km
+    pub home: Option<(f64, f64, f64)>,
+}
+
+/// An event with its recommendation score. Serializes as a flat Event
+/// object with an extra `score` field.
+#[derive(Debug, Serialize)]
+pub struct RecommendedEvent {
+    #[serde(flatten)]
+    pub event: Event,
+    pub score: i32,
+}
+
+// =============================================================================
+// SCORING
+// =============================================================================
+
+/// Scores one event for the profile (see the module docs for the table).
+pub fn score_event(profile: &RecommendationProfile, event: &Event) -> i32 {
+    let categories = event.categories.as_deref().unwrap_or_default();
+    let mut score = 0;
+
+    score += categories
+        .iter()
+        .filter_map(|c| profile.category_weights.get(c))
+        .map(|weight| weight.clamp(&-MAX_PREFERENCE_WEIGHT, &MAX_PREFERENCE_WEIGHT))
+        .sum::<i32>();
+
+    if event.venue_id.is_some_and(|id| profile.followed_venues.contains(&id)) {
+        score += FOLLOWED_VENUE_BOOST;
+    }
+    if categories.iter().any(|c| profile.followed_categories.contains(c)) {
+        score += FOLLOWED_CATEGORY_BOOST;
+    }
+    if categories.iter().any(|c| profile.recent_categories.contains(c)) {
+        score += RECENT_CATEGORY_BOOST;
+    }
+
+    if let (Some((lat, lng, radius_km)), Some(event_lat), Some(event_lng)) =
+        (profile.home, event.latitude, event.longitude)
+    {
+        if geo::haversine_km(lat, lng, event_lat, event_lng) <= radius_km {
+            score += NEARBY_BOOST;
+        }
+    }
+
+    if profile.viewed.contains(&event.id) {
+        score -= VIEWED_PENALTY;
+    }
+    if profile.dismissed.contains(&event.id) {
+        score -= DISMISSED_PENALTY;
+    }
+
+    score
+}
+
+/// Scores every candidate and returns the best `limit`, highest score
+/// first, then soonest start, then id (so equal inputs give equal output).
+pub fn rank(profile: &RecommendationProfile, events: Vec<Event>, limit: usize) -> Vec<RecommendedEvent> {
+    let mut scored: Vec<RecommendedEvent> = events
+        .into_iter()
+        .map(|event| RecommendedEvent {
+            score: score_event(profile, &event),
+            event,
+        })
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.event.start_time.cmp(&b.event.start_time))
+            .then(a.event.id.cmp(&b.event.id))
+    });
+    scored.truncate(limit);
+    scored
+}
+
+// =============================================================================
+// LOADING
+// =============================================================================
+
+/// Builds a user's profile from the database. 404 if the user doesn't
+/// exist.
+pub async fn load_profile(pool: &PgPool, user_id: Uuid) -> Result<RecommendationProfile, AppError> {
+    let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("user"))?;
+
+    let category_weights: Vec<(String, i32)> =
+        sqlx::query_as("SELECT category, weight FROM user_preferences WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+    let follows: Vec<(FollowTarget, String)> =
+        sqlx::query_as("SELECT target_type, target_value FROM user_follows WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+    let recent_categories: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT c.category
+        FROM user_interactions ui
+        JOIN events e ON e.id = ui.event_id
+        CROSS JOIN LATERAL unnest(e.categories) AS c(category)
+        WHERE ui.user_id = $1
+          AND ui.created_at > NOW() - make_interval(days => $2)
+          AND ui.interaction_type <> ALL($3)
+        "#,
+    )
+        .bind(user_id)
+        .bind(RECENT_DAYS)
+        .bind(analytics::spellings_of("dismiss"))
+        .fetch_all(pool)
+        .await?;
+
+    let viewed: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT DISTINCT event_id FROM user_interactions WHERE user_id = $1 AND interaction_type = ANY($2)",
+    )
+        .bind(user_id)
+        .bind(analytics::spellings_of("view"))
+        .fetch_all(pool)
+        .await?;
+
+    let mut profile = RecommendationProfile {
+        category_weights: category_weights.into_iter().collect(),
+        recent_categories: recent_categories.into_iter().collect(),
+        viewed: viewed.into_iter().collect(),
+        home: match (user.home_latitude, user.home_longitude) {
+            (Some(lat), Some(lng)) => Some((lat, lng, user.preferred_radius_km.unwrap_or(DEFAULT_NEARBY_KM))),
+            _ => None,
+        },
+        ..Default::default()
+    };
+    for (target_type, value) in follows {
+        match target_type {
+            FollowTarget::Venue => {
+                if let Ok(id) = value.parse() {
+                    profile.followed_venues.insert(id);
+                }
+            }
+            FollowTarget::Category => {
+                profile.followed_categories.insert(value);
+            }
+        }
+    }
+
+    Ok(profile)
+}
+
+/// Loads the soonest upcoming events the user hasn't dismissed.
+pub async fn load_candidates(pool: &PgPool, user_id: Uuid) -> Result<Vec<Event>, sqlx::Error> {
+    sqlx::query_as::<_, Event>(&format!(
+        r#"
+        SELECT {}
+        FROM events
+        WHERE {} AND {} AND {} AND {}
+        ORDER BY start_time ASC, id ASC
+        LIMIT $2
+        "#,
+        EVENT_COLUMNS,
+        UPCOMING_FILTER,
+        NOT_CANCELLED_FILTER,
+        NOT_ARCHIVED_FILTER,
+        not_dismissed_by("$1", "$3", "$4")
+    ))
+        .bind(user_id)
+        .bind(CANDIDATE_LIMIT)
+        .bind(analytics::spellings_of("dismiss"))
+        .bind(analytics::spellings_of("save"))
+        .fetch_all(pool)
+        .await
+}
+
+/// Loads a user's profile and candidates and returns the top `limit`.
+pub async fn recommend(pool: &PgPool, user_id: Uuid, limit: usize) -> Result<Vec<RecommendedEvent>, AppError> {
+    let profile = load_profile(pool, user_id).await?;
+    let candidates = load_candidates(pool, user_id).await?;
+    Ok(rank(&profile, candidates, limit))
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventStatus;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    fn start(hours: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap() + Duration::hours(hours)
+    }
+
+    fn event(title: &str, hours: i64, categories: &[&str]) -> Event {
+        Event {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            venue: None,
+            venue_id: None,
+            venue_address: None,
+            location: None,
+            source_url: format!("https://example.com/{}", title),
+            source_name: None,
+            start_time: start(hours),
+            end_time: None,
+            categories: Some(categories.iter().map(|c| c.to_string()).collect()),
+            tags: vec![],
+            price_min: None,
+            price_max: None,
+            is_free: false,
+            outdoor: false,
+            family_friendly: false,
+            image_url: None,
+            status: EventStatus::Scheduled,
+            latitude: None,
+            longitude: None,
+            archived_at: None,
+            created_at: start(-100),
+            updated_at: start(-100),
+        }
+    }
+
+    fn titles(ranked: &[RecommendedEvent]) -> Vec<&str> {
+        ranked.iter().map(|r| r.event.title.as_str()).collect()
+    }
+
+    #[test]
+    fn preferences_add_and_subtract() {
+        let profile = RecommendationProfile {
+            category_weights: HashMap::from([("jazz".to_string(), 5), ("comedy".to_string(), -3)]),
+            ..Default::default()
+        };
+
+        assert_eq!(score_event(&profile, &event("a", 1, &["jazz", "blues"])), 5);
+        assert_eq!(score_event(&profile, &event("b", 1, &["jazz", "comedy"])), 2);
+        assert_eq!(score_event(&profile, &event("c", 1, &[])), 0);
+    }
+
+    #[test]
+    fn follows_outrank_preferences() {
+        let venue = Uuid::new_v4();
+        let profile = RecommendationProfile {
+            category_weights: HashMap::from([("jazz".to_string(), 5)]),
+            followed_venues: HashSet::from([venue]),
+            followed_categories: HashSet::from(["punk".to_string()]),
+            ..Default::default()
+        };
+        let mut at_venue = event("venue", 3, &["rock"]);
+        at_venue.venue_id = Some(venue);
+
+        let ranked = rank(
+            &profile,
+            vec![event("jazz", 1, &["jazz"]), at_venue, event("punk", 2, &["punk"])],
+            10,
+        );
+
+        assert_eq!(titles(&ranked), ["venue", "punk", "jazz"]);
+    }
+
+    #[test]
+    fn history_nudges_and_penalizes() {
+        let viewed = event("viewed", 1, &["jazz"]);
+        let dismissed = event("dismissed", 1, &["jazz"]);
+        let profile = RecommendationProfile {
+            recent_categories: HashSet::from(["jazz".to_string()]),
+            viewed: HashSet::from([viewed.id]),
+            dismissed: HashSet::from([dismissed.id]),
+            ..Default::default()
+        };
+
+        assert_eq!(score_event(&profile, &event("fresh", 1, &["jazz"])), RECENT_CATEGORY_BOOST);
+        assert_eq!(score_event(&profile, &viewed), RECENT_CATEGORY_BOOST - VIEWED_PENALTY);
+        assert_eq!(score_event(&profile, &dismissed), RECENT_CATEGORY_BOOST - DISMISSED_PENALTY);
+    }
+
+    #[test]
+    fn nearby_events_get_a_boost() {
+        let profile = RecommendationProfile {
+            home: Some((36.154, -95.993, 5.0)),
+            ..Default::default()
+        };
+        let mut near = event("near", 1, &[]);
+        (near.latitude, near.longitude) = (Some(36.16), Some(-95.99));
+        let mut far = event("far", 1, &[]);
+        (far.latitude, far.longitude) = (Some(35.47), Some(-97.52));
+
+        assert_eq!(score_event(&profile, &near), NEARBY_BOOST);
+        assert_eq!(score_event(&profile, &far), 0);
+        assert_eq!(score_event(&profile, &event("unknown", 1, &[])), 0);
+    }
+
+    #[test]
+    fn ties_go_to_the_soonest_event() {
+        let ranked = rank(
+            &RecommendationProfile::default(),
+            vec![event("later", 5, &[]), event("soon", 1, &[]), event("middle", 3, &[])],
+            2,
+        );
+
+        assert_eq!(titles(&ranked), ["soon", "middle"]);
+    }
+}
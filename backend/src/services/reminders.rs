@@ -0,0 +1,165 @@
+//! # Event Reminders
+//!
+//! Reminds users about events they said they're attending. Every run finds
+//! `attend` interactions for events starting within the look-ahead window
+//! and writes one `reminder` notification per user and event.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Environment Variables
+//! ```text
+//! REMINDER_LOOKAHEAD_HOURS=24   # how far ahead an event gets a reminder
+//! REMINDER_INTERVAL_MINUTES=60  # how often the job runs
+//! ```
+//!
+//! ## Once Only
+//! "Already reminded" is the unique index on
+//! `notifications (user_id, event_id, kind)`: the insert skips conflicts,
+//! so overlapping runs (or two app instances) can't remind twice.
+//! Cancelled events are skipped.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use super::{analytics, dates, scheduler};
+use crate::db::NOT_CANCELLED_FILTER;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default hours ahead of an event's start that its reminder goes out.
+pub const DEFAULT_REMINDER_LOOKAHEAD_HOURS: u64 = 24;
+
+/// Default minutes between reminder runs.
+pub const DEFAULT_REMINDER_INTERVAL_MINUTES: u64 = 60;
+
+/// `notifications.kind` written by this job.
+pub const NOTIFICATION_KIND: &str = "reminder";
+
+// =============================================================================
+// JOB
+// =============================================================================
+
+/// Writes reminders for attended events starting in the next
+/// `lookahead_hours`. Returns the number of reminders created.
+pub async fn send_reminders(pool: &PgPool, lookahead_hours: u64) -> Result<u64, sqlx::Error> {
+    let timezone = dates::local_timezone();
+
+    let result = sqlx::query(&format!(
+        r#"
+        INSERT INTO notifications (user_id, kind, event_id, title, body)
+        SELECT DISTINCT ui.user_id, $1, events.id, events.title,
+               'Starts at ' || to_char(events.start_time AT TIME ZONE $4, 'FMHH12:MI AM')
+               || COALESCE(' at ' || events.location, '')
+        FROM user_interactions ui
+        JOIN events ON events.id = ui.event_id
+        WHERE ui.interaction_type = ANY($2)
+          AND events.start_time > NOW()
+          AND events.start_time <= NOW() + make_interval(hours => $3)
+          AND {}
+        ON CONFLICT (user_id, event_id, kind) DO NOTHING
+        "#,
+        NOT_CANCELLED_FILTER
+    ))
+        .bind(NOTIFICATION_KIND)
+        .bind(analytics::spellings_of("attend"))
+        .bind(lookahead_hours as i32)
+        .bind(timezone.name())
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Starts the periodic reminder job using the environment configuration.
+pub fn spawn_reminder_sender(pool: PgPool) {
+    let lookahead_hours = scheduler::env_u64("REMINDER_LOOKAHEAD_HOURS", DEFAULT_REMINDER_LOOKAHEAD_HOURS);
+    let minutes = scheduler::env_u64("REMINDER_INTERVAL_MINUTES", DEFAULT_REMINDER_INTERVAL_MINUTES);
+
+    scheduler::spawn_periodic(
+        "event_reminders",
+        Duration::from_secs(minutes * 60),
+        pool,
+        move |pool| async move {
+            let created = send_reminders(&pool, lookahead_hours).await?;
+            if created > 0 {
+                tracing::info!(created, "sent event reminders");
+            }
+            Ok::<_, sqlx::Error>(())
+        },
+    );
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn reminds_each_attendee_once() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let event = |slug: &'static str, starts_in: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let id: Uuid = sqlx::query_scalar(&format!(
+                    "INSERT INTO events (title, source_url, start_time) \
+                     VALUES ('Jazz Night', $1, NOW() + INTERVAL '{}') RETURNING id",
+                    starts_in
+                ))
+                    .bind(format!("https://venue.example/{}/{}", run, slug))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                sqlx::query(
+                    "INSERT INTO user_interactions (user_id, event_id, interaction_type) \
+                     VALUES ($1, $2, 'attend'), ($1, $2, 'attended')",
+                )
+                    .bind(user)
+                    .bind(id)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+                id
+            }
+        };
+        let tomorrow_morning = event("soon", "10 hours").await;
+        event("later", "3 days").await;
+
+        send_reminders(&pool, 24).await.unwrap();
+        send_reminders(&pool, 24).await.unwrap();
+
+        let reminded: Vec<Option<Uuid>> = sqlx::query_scalar(
+            "SELECT event_id FROM notifications WHERE user_id = $1 AND kind = $2",
+        )
+            .bind(user)
+            .bind(NOTIFICATION_KIND)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(reminded, vec![Some(tomorrow_morning)]);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,365 @@
+//! # Saved Search Notifications
+//!
+//! Checks every saved search and every follow against events created since
+//! it was last checked, and writes a notification for each match
+//! (`saved_search` or `follow`).
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Environment Variables
+//! ```text
+//! SAVED_SEARCH_INTERVAL_MINUTES=15  # how often the job runs
+//! ```
+//!
+//! ## Matching
+//! A saved search is turned into a `SearchQuery` and filtered with the same
+//! `search::filter_conditions` as `GET /api/events/search`, so a match is
+//! exactly what the live search would show (upcoming, not cancelled, not
+//! archived). Only events whose `created_at` falls in
+//! `(last checked, this run]` are considered; a new search starts from its
+//! own `created_at`.
+//!
+//! Follows (a venue or a category) are checked the same way after the
+//! saved searches, with the same upcoming / not cancelled / not archived
+//! rules.
+//!
+//! ## Once Only
+//! The unique index on `notifications (user_id, event_id, kind)` means a
+//! user hears about an event once, even if the job overlaps itself or two
+//! of their searches match it. An event that matched a saved search isn't
+//! repeated as a `follow` notification (and vice versa). Each search's or
+//! follow's notifications and its new `last_notified_at` are committed
+//! together.
+//!
+//! ## Scraper Runs
+//! The scraper schedule should call `notify_saved_searches` right after
+//! each run, so new events reach users without waiting for the timer.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use uuid::Uuid;
+
+use super::scheduler;
+use super::search::{filter_conditions, SearchQuery};
+use crate::db::{NOT_ARCHIVED_FILTER, NOT_CANCELLED_FILTER, SAVED_SEARCH_COLUMNS, UPCOMING_FILTER};
+use crate::models::{FollowTarget, SavedSearch};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Default minutes between matcher runs.
+pub const DEFAULT_SAVED_SEARCH_INTERVAL_MINUTES: u64 = 15;
+
+/// `notifications.kind` written for saved search matches.
+pub const NOTIFICATION_KIND: &str = "saved_search";
+
+/// `notifications.kind` written for new events at a followed venue or in a
+/// followed category.
+pub const FOLLOW_NOTIFICATION_KIND: &str = "follow";
+
+/// Condition skipping events the user was already told about by either
+/// kind. `$1` is the user and `$2` this notification's kind.
+const NOT_ALREADY_NOTIFIED: &str = "NOT EXISTS (SELECT 1 FROM notifications n \
+    WHERE n.user_id = $1 AND n.event_id = events.id AND n.kind IN ('saved_search', 'follow'))";
+
+/// A follow as the matcher needs it: `since` is when it was last checked
+/// (or created).
+#[derive(Debug, sqlx::FromRow)]
+struct WatchedFollow {
+    id: Uuid,
+    user_id: Uuid,
+    target_type: FollowTarget,
+    target_value: String,
+    since: DateTime<Utc>,
+}
+
+// =============================================================================
+// JOB
+// =============================================================================
+
+/// Runs every saved search and follow once. Returns the number of
+/// notifications created.
+pub async fn notify_saved_searches(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let (run_at,): (DateTime<Utc>,) = sqlx::query_as("SELECT NOW()").fetch_one(pool).await?;
+
+    let searches = sqlx::query_as::<_, SavedSearch>(&format!(
+        "SELECT {} FROM saved_searches ORDER BY created_at",
+        SAVED_SEARCH_COLUMNS
+    ))
+        .fetch_all(pool)
+        .await?;
+
+    let mut created = 0;
+    for search in &searches {
+        created += notify_one(pool, search, run_at).await?;
+    }
+
+    let follows = sqlx::query_as::<_, WatchedFollow>(
+        r#"
+        SELECT id, user_id, target_type, target_value, COALESCE(last_notified_at, created_at) AS since
+        FROM user_follows
+        ORDER BY created_at
+        "#,
+    )
+        .fetch_all(pool)
+        .await?;
+
+    for follow in &follows {
+        created += notify_follow(pool, follow, run_at).await?;
+    }
+
+    Ok(created)
+}
+
+/// Records notifications for events matching `search` that were created
+/// after it was last checked and no later than `run_at`.
+async fn notify_one(pool: &PgPool, search: &SavedSearch, run_at: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let since = search.last_notified_at.unwrap_or(search.created_at);
+
+    let mut conditions = filter_conditions(&SearchQuery::from_saved(search));
+    conditions.push("created_at > $3".to_string());
+    conditions.push("created_at <= $4".to_string());
+    conditions.push(NOT_ALREADY_NOTIFIED.to_string());
+
+    let mut tx = pool.begin().await?;
+
+    let created = sqlx::query(&format!(
+        r#"
+        INSERT INTO notifications (user_id, kind, event_id, title, body)
+        SELECT $1, $2, id, title, 'New event matching your saved search'
+        FROM events
+        WHERE {}
+        ON CONFLICT (user_id, event_id, kind) DO NOTHING
+        "#,
+        conditions.join(" AND ")
+    ))
+        .bind(search.user_id)
+        .bind(NOTIFICATION_KIND)
+        .bind(since)
+        .bind(run_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    sqlx::query("UPDATE saved_searches SET last_notified_at = $2 WHERE id = $1")
+        .bind(search.id)
+        .bind(run_at)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+/// Records notifications for events at the followed venue (or in the
+/// followed category) created after the follow was last checked.
+async fn notify_follow(pool: &PgPool, follow: &WatchedFollow, run_at: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let (target, body) = match follow.target_type {
+        FollowTarget::Venue => (
+            "venue_id::text = $5",
+            "'New event at ' || COALESCE(venue, 'a venue you follow')",
+        ),
+        FollowTarget::Category => ("$5 = ANY(categories)", "'New ' || $5 || ' event'"),
+    };
+
+    let mut tx = pool.begin().await?;
+
+    let created = sqlx::query(&format!(
+        r#"
+        INSERT INTO notifications (user_id, kind, event_id, title, body)
+        SELECT $1, $2, id, title, {}
+        FROM events
+        WHERE {}
+          AND created_at > $3 AND created_at <= $4
+          AND {} AND {} AND {} AND {}
+        ON CONFLICT (user_id, event_id, kind) DO NOTHING
+        "#,
+        body, target, UPCOMING_FILTER, NOT_CANCELLED_FILTER, NOT_ARCHIVED_FILTER, NOT_ALREADY_NOTIFIED
+    ))
+        .bind(follow.user_id)
+        .bind(FOLLOW_NOTIFICATION_KIND)
+        .bind(follow.since)
+        .bind(run_at)
+        .bind(&follow.target_value)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    sqlx::query("UPDATE user_follows SET last_notified_at = $2 WHERE id = $1")
+        .bind(follow.id)
+        .bind(run_at)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+/// Starts the periodic matcher using the environment configuration.
+pub fn spawn_saved_search_notifier(pool: PgPool) {
+    let minutes = scheduler::env_u64(
+        "SAVED_SEARCH_INTERVAL_MINUTES",
+        DEFAULT_SAVED_SEARCH_INTERVAL_MINUTES,
+    );
+
+    scheduler::spawn_periodic(
+        "saved_search_notifications",
+        Duration::from_secs(minutes * 60),
+        pool,
+        |pool| async move {
+            let created = notify_saved_searches(&pool).await?;
+            if created > 0 {
+                tracing::info!(created, "saved search notifications");
+            }
+            Ok::<_, sqlx::Error>(())
+        },
+    );
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn new_matching_event_is_notified_once() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let keyword = format!("kw{}", run.simple());
+        let (user_id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO saved_searches (user_id, q) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(&keyword)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let insert_event = |title: String, slug: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let (id,): (Uuid,) = sqlx::query_as(
+                    "INSERT INTO events (title, source_url, start_time) \
+                     VALUES ($1, $2, NOW() + INTERVAL '1 day') RETURNING id",
+                )
+                    .bind(title)
+                    .bind(format!("https://venue.example/{}/{}", run, slug))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                id
+            }
+        };
+        let matching = insert_event(format!("Live {} Night", keyword), "match").await;
+        insert_event("Something else".to_string(), "other").await;
+
+        notify_saved_searches(&pool).await.unwrap();
+        notify_saved_searches(&pool).await.unwrap();
+
+        let rows: Vec<(Option<Uuid>, String)> = sqlx::query_as(
+            "SELECT event_id, kind FROM notifications WHERE user_id = $1",
+        )
+            .bind(user_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(rows, vec![(Some(matching), NOTIFICATION_KIND.to_string())]);
+    }
+
+    #[tokio::test]
+    async fn follows_notify_without_repeating_saved_search_matches() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let category = format!("cat{}", run.simple());
+        let (user_id,): (Uuid,) = sqlx::query_as(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO user_follows (user_id, target_type, target_value) VALUES ($1, 'category', $2)")
+            .bind(user_id)
+            .bind(&category)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO saved_searches (user_id, q) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(format!("Headliner {}", run.simple()))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let insert_event = |title: String, slug: &'static str| {
+            let pool = pool.clone();
+            let category = category.clone();
+            async move {
+                let (id,): (Uuid,) = sqlx::query_as(
+                    "INSERT INTO events (title, source_url, start_time, categories) \
+                     VALUES ($1, $2, NOW() + INTERVAL '1 day', ARRAY[$3]) RETURNING id",
+                )
+                    .bind(title)
+                    .bind(format!("https://venue.example/{}/{}", run, slug))
+                    .bind(category)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                id
+            }
+        };
+        let followed = insert_event("Open mic".to_string(), "followed").await;
+        let both = insert_event(format!("Headliner {}", run.simple()), "both").await;
+
+        notify_saved_searches(&pool).await.unwrap();
+        notify_saved_searches(&pool).await.unwrap();
+
+        let mut rows: Vec<(Option<Uuid>, String)> = sqlx::query_as(
+            "SELECT event_id, kind FROM notifications WHERE user_id = $1",
+        )
+            .bind(user_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        rows.sort_by_key(|(_, kind)| kind.clone());
+
+        assert_eq!(
+            rows,
+            vec![
+                (Some(followed), FOLLOW_NOTIFICATION_KIND.to_string()),
+                (Some(both), NOTIFICATION_KIND.to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! # Background Job Scheduler
+//!
+//! Runs maintenance jobs on a fixed interval inside the server process.
+//! There is no external cron: each job is a tokio task driven by
+//! `tokio::time::interval`.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Usage
+//! ```rust
+//! scheduler::spawn_periodic("archive", Duration::from_secs(3600), pool.clone(), |pool| async move {
+//!     archive::archive_ended_events(&pool, 30).await.map(|_| ())
+//! });
+//! ```
+//!
+//! ## Behavior
+//! - The first run happens immediately at startup, then every `period`
+//! - A run that overlaps the next tick delays it instead of running twice
+//! - Errors are logged and the job keeps its schedule; one bad run
+//!   (e.g. the database restarting) doesn't stop future runs
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+
+// =============================================================================
+// SCHEDULING
+// =============================================================================
+
+/// Spawns `job` to run every `period` for the lifetime of the server.
+///
+/// `name` only appears in log lines.
+pub fn spawn_periodic<F, Fut, E>(
+    name: &'static str,
+    period: Duration,
+    pool: PgPool,
+    job: F,
+) -> JoinHandle<()>
+where
+    F: Fn(PgPool) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display,
+{
+    tokio::spawn(async move {
+        let mut ticker = interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            if let Err(e) = job(pool.clone()).await {
+                tracing::error!(job = name, error = %e, "background job failed");
+            }
+        }
+    })
+}
+
+/// Reads a positive integer from the environment, falling back to `default`.
+pub fn env_u64(key: &str, default: u64) -> u64 {
+    match std::env::var(key) {
+        Ok(raw) => match raw.parse() {
+            Ok(value) if value > 0 => value,
+            _ => {
+                tracing::warn!("Invalid {} '{}', using {}", key, raw, default);
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
@@ -0,0 +1,389 @@
+//! # Event Search Filters
+//!
+//! The filter half of `GET /api/events/search`: the query parameters and
+//! the SQL `WHERE` conditions they turn into. Shared by the search and
+//! calendar endpoints and by the saved-search matcher, so a saved search
+//! finds exactly what the same search would find live. `search_events`
+//! runs a whole search and is what the chat assistant's `search_events`
+//! tool calls.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{not_dismissed_by, EVENT_COLUMNS, NOT_ARCHIVED_FILTER, NOT_CANCELLED_FILTER, UPCOMING_FILTER};
+use crate::models::{Event, SavedSearch};
+use crate::services::{analytics, geo};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// Results returned when `limit` isn't given.
+pub const DEFAULT_SEARCH_LIMIT: i32 = 50;
+
+/// Most results a single search returns.
+pub const MAX_SEARCH_LIMIT: i32 = 100;
+
+// =============================================================================
+// QUERY PARAMETERS
+// =============================================================================
+
+/// Query parameters for the search endpoint.
+///
+/// All fields are optional, allowing flexible search combinations.
+///
+/// # Examples
+/// - `/search?q=jazz` - Text search
+/// - `/search?category=concerts` - Filter by category
+/// - `/search?tag=outdoor&tag=family-friendly` - Events with both tags
+/// - `/search?outdoor=true&family_friendly=true` - Filter by attributes
+/// - `/search?price_max=25` - Filter by price (`max_price` also accepted)
+/// - `/search?free_only=true` - Only free events
+/// - `/search?start_date=2026-01-25&end_date=2026-01-26` - Date range
+/// - `/search?q=jazz&include_past=true` - Also match events that have ended
+/// - `/search?include_dismissed=true` - Signed in, but show dismissed events too
+/// - `/search?lat=36.15&lng=-95.99&radius_km=8` - Within 8 km, nearest first
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchQuery {
+    /// Text to search for in event title and description
+    pub q: Option<String>,
+
+    /// Category to filter by (matches any category in the array)
+    pub category: Option<String>,
+
+    /// Tags to filter by; repeat for several (`?tag=outdoor&tag=free`).
+    /// Events must have all of them.
+    #[serde(default)]
+    pub tag: Vec<String>,
+
+    /// Start of date range (ISO 8601 format)
+    pub start_date: Option<DateTime<Utc>>,
+
+    /// End of date range (ISO 8601 format)
+    pub end_date: Option<DateTime<Utc>>,
+
+    /// Filter by location
+    pub location: Option<String>,
+
+    /// Maximum price filter
+    #[serde(alias = "max_price")]
+    pub price_max: Option<f64>,
+
+    /// Only show free events (default: false)
+    #[serde(default)]
+    pub free_only: bool,
+
+    /// Only show outdoor events
+    pub outdoor: Option<bool>,
+
+    /// Only show family-friendly events
+    pub family_friendly: Option<bool>,
+
+    /// Maximum number of results (default: 50)
+    pub limit: Option<i32>,
+
+    /// Include events that have already ended (default: false)
+    #[serde(default)]
+    pub include_past: bool,
+
+    /// Include cancelled events (default: false)
+    #[serde(default)]
+    pub include_cancelled: bool,
+
+    /// Include soft-archived events (default: false)
+    #[serde(default)]
+    pub include_archived: bool,
+
+    /// Include events `viewer` dismissed (default: false)
+    #[serde(default)]
+    pub include_dismissed: bool,
+
+    /// The signed-in user, set by handlers from the bearer token (never
+    /// from the query string). Their dismissed events are hidden.
+    /// Recommendation and chat searches should set it too.
+    #[serde(skip)]
+    pub viewer: Option<Uuid>,
+
+    /// Latitude of the search point (requires `lng`)
+    pub lat: Option<f64>,
+
+    /// Longitude of the search point (requires `lat`)
+    pub lng: Option<f64>,
+
+    /// Search radius in kilometers (default: 10)
+    pub radius_km: Option<f64>,
+}
+
+impl SearchQuery {
+    /// The live search a saved search stands for (upcoming, not
+    /// cancelled, not archived, like the endpoint's defaults).
+    pub fn from_saved(saved: &SavedSearch) -> Self {
+        SearchQuery {
+            q: saved.q.clone(),
+            category: saved.category.clone(),
+            location: saved.location.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds the WHERE conditions for every non-geographic search filter.
+///
+/// Kept separate from the handlers so the filter logic can be unit tested
+/// without a database. Conditions are ANDed together by the caller.
+pub fn filter_conditions(params: &SearchQuery) -> Vec<String> {
+    let mut conditions: Vec<String> = vec![];
+
+    // Text search
+    if let Some(ref q) = params.q {
+        conditions.push(format!(
+            "(title ILIKE '%{}%' OR description ILIKE '%{}%')",
+            q.replace('\'', "''"), // Basic SQL injection prevention
+            q.replace('\'', "''")
+        ));
+    }
+
+    // Category filter (check if category is in the categories array)
+    if let Some(ref cat) = params.category {
+        conditions.push(format!(
+            "'{}' = ANY(categories)",
+            cat.replace('\'', "''")
+        ));
+    }
+
+    // Tag filter (AND semantics: the event must have every requested tag)
+    for tag in normalize_tags(&params.tag) {
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM event_tags t WHERE t.event_id = events.id AND t.tag = '{}')",
+            tag.replace('\'', "''")
+        ));
+    }
+
+    // Date range
+    if let Some(start) = params.start_date {
+        conditions.push(format!("start_time >= '{}'", start.to_rfc3339()));
+    }
+
+    // Default: hide events that have already ended
+    if !params.include_past {
+        conditions.push(UPCOMING_FILTER.to_string());
+    }
+
+    // Default: hide cancelled events
+    if !params.include_cancelled {
+        conditions.push(NOT_CANCELLED_FILTER.to_string());
+    }
+
+    // Default: hide archived events (only reachable with include_past)
+    if !params.include_archived {
+        conditions.push(NOT_ARCHIVED_FILTER.to_string());
+    }
+
+    // Default: hide what the signed-in user said they're not interested in
+    if let (Some(viewer), false) = (params.viewer, params.include_dismissed) {
+        conditions.push(not_dismissed_by(
+            &format!("'{}'", viewer),
+            &analytics::spellings_sql("dismiss"),
+            &analytics::spellings_sql("save"),
+        ));
+    }
+
+    if let Some(end) = params.end_date {
+        conditions.push(format!("start_time <= '{}'", end.to_rfc3339()));
+    }
+
+    // Location filter
+    if let Some(ref loc) = params.location {
+        conditions.push(format!(
+            "location ILIKE '%{}%'",
+            loc.replace('\'', "''")
+        ));
+    }
+
+    // Price filter (check if at least one price is within budget)
+    if let Some(max_price) = params.price_max {
+        conditions.push(format!(
+            "(is_free OR price_min IS NULL OR price_min <= {})",
+            max_price
+        ));
+    }
+
+    // Free-only filter
+    if params.free_only {
+        conditions.push("is_free = TRUE".to_string());
+    }
+
+    // Outdoor filter
+    if let Some(outdoor) = params.outdoor {
+        conditions.push(format!("outdoor = {}", outdoor));
+    }
+
+    // Family-friendly filter
+    if let Some(ff) = params.family_friendly {
+        conditions.push(format!("family_friendly = {}", ff));
+    }
+
+    conditions
+}
+
+// =============================================================================
+// SEARCH
+// =============================================================================
+
+/// The whole search statement for `params`, soonest first.
+///
+/// With `radius` (`lat`, `lng`, `radius_km`, already validated), only
+/// events with coordinates inside it match, nearest first, and each row
+/// carries a `distance_km` column (read it as `EventWithDistance`).
+pub fn search_sql(params: &SearchQuery, radius: Option<(f64, f64, f64)>) -> String {
+    let limit = params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+    let mut conditions = filter_conditions(params);
+
+    // Radius filter (events without coordinates can't match)
+    let distance = radius.map(|(lat, lng, radius_km)| {
+        let distance = geo::haversine_sql(lat, lng);
+        conditions.push("latitude IS NOT NULL AND longitude IS NOT NULL".to_string());
+        conditions.push(format!("{} <= {}", distance, radius_km));
+        distance
+    });
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let (distance_column, order_by) = match distance {
+        Some(ref d) => (format!(", {} AS distance_km", d), "distance_km ASC, start_time ASC"),
+        None => (String::new(), "start_time ASC"),
+    };
+
+    format!(
+        r#"
+        SELECT {}{}
+        FROM events
+        {}
+        ORDER BY {}
+        LIMIT {}
+        "#,
+        EVENT_COLUMNS, distance_column, where_clause, order_by, limit
+    )
+}
+
+/// Runs a (non-radius) search, exactly as `GET /api/events/search` would.
+pub async fn search_events(pool: &PgPool, params: &SearchQuery) -> Result<Vec<Event>, sqlx::Error> {
+    sqlx::query_as::<_, Event>(&search_sql(params, None))
+        .fetch_all(pool)
+        .await
+}
+
+// =============================================================================
+// TAGS
+// =============================================================================
+
+/// Cleans up tags for storage and matching.
+///
+/// Tags are trimmed, lowercased, de-duplicated and sorted, and blank
+/// entries are dropped, so "Outdoor " and "outdoor" are the same tag.
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = tags
+        .iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    #[tokio::test]
+    async fn dismiss_hides_until_saved_again() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let keyword = format!("kw{}", run.simple());
+        let user: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id",
+        )
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let event: Uuid = sqlx::query_scalar(
+            "INSERT INTO events (title, source_url, start_time) \
+             VALUES ($1, $2, NOW() + INTERVAL '1 day') RETURNING id",
+        )
+            .bind(format!("Jazz {}", keyword))
+            .bind(format!("https://venue.example/{}", run))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let interact = |interaction_type: &'static str, minutes_ago: i32| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query(
+                    "INSERT INTO user_interactions (user_id, event_id, interaction_type, created_at) \
+                     VALUES ($1, $2, $3, NOW() - make_interval(mins => $4))",
+                )
+                    .bind(user)
+                    .bind(event)
+                    .bind(interaction_type)
+                    .bind(minutes_ago)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        };
+        let found = |viewer: Option<Uuid>, include_dismissed: bool| {
+            let pool = pool.clone();
+            let params = SearchQuery {
+                q: Some(keyword.clone()),
+                viewer,
+                include_dismissed,
+                ..Default::default()
+            };
+            async move {
+                let query = format!("SELECT COUNT(*) FROM events WHERE {}", filter_conditions(&params).join(" AND "));
+                sqlx::query_scalar::<_, i64>(&query).fetch_one(&pool).await.unwrap() == 1
+            }
+        };
+
+        assert!(found(Some(user), false).await);
+
+        interact("dismissed", 10).await;
+        assert!(!found(Some(user), false).await);
+        assert!(found(Some(user), true).await);
+        assert!(found(None, false).await, "anonymous searches see everything");
+        assert!(found(Some(Uuid::new_v4()), false).await, "only the dismisser's view changes");
+
+        interact("save", 5).await;
+        assert!(found(Some(user), false).await);
+
+        interact("dismiss", 1).await;
+        assert!(!found(Some(user), false).await);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM events WHERE id = $1").bind(event).execute(&pool).await.unwrap();
+    }
+}
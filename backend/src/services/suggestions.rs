@@ -0,0 +1,400 @@
+//! # Chat Suggestions
+//!
+//! Starter prompts for an empty chat box, built from what's actually on:
+//! the busiest categories this week, the user's favorite category, the
+//! top trending event, and "What's happening this weekend?". Served by
+//! `GET /api/chat/suggestions`.
+//!
+//! ## Owner
+//! Ben (AI Engineer)
+//!
+//! ## Cost
+//! A few aggregate queries, no model calls. Results are cached per user
+//! (one shared entry for anonymous visitors) for `SUGGESTIONS_TTL`.
+//!
+//! ## Fallbacks
+//! Anonymous visitors get no favorite-category prompt. With no upcoming
+//! events at all, every prompt comes from `DEFAULT_SUGGESTIONS`; with a
+//! few, the defaults fill the list up to `MIN_SUGGESTIONS`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{NOT_ARCHIVED_FILTER, NOT_CANCELLED_FILTER, UPCOMING_FILTER};
+use crate::models::ChatSuggestion;
+use crate::services::analytics::{self, TRENDING_WINDOW_DAYS};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// How long a user's suggestions are reused.
+pub const SUGGESTIONS_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Users whose suggestions are kept at once.
+pub const SUGGESTIONS_CACHE_CAPACITY: usize = 1000;
+
+/// Fewest suggestions returned; defaults fill the gap.
+pub const MIN_SUGGESTIONS: usize = 4;
+
+/// Most suggestions returned.
+pub const MAX_SUGGESTIONS: usize = 6;
+
+/// Busiest categories suggested.
+const TOP_CATEGORIES: i64 = 2;
+
+/// Days ahead counted as "this week".
+const WEEK_DAYS: i32 = 7;
+
+/// The generic prompt every list ends with.
+pub const WEEKEND_PROMPT: &str = "What's happening this weekend?";
+
+/// Prompts for when there's no data to build from.
+pub const DEFAULT_SUGGESTIONS: &[&str] = &[
+    WEEKEND_PROMPT,
+    "Any live music tonight?",
+    "Family-friendly things to do",
+    "Something free this week",
+];
+
+/// Emoji shown before a category's prompt; others get `DEFAULT_EMOJI`.
+const CATEGORY_EMOJI: &[(&str, &str)] = &[
+    ("art", "🎨"),
+    ("comedy", "😂"),
+    ("concerts", "🎵"),
+    ("family", "👨‍👩‍👧"),
+    ("festival", "🎪"),
+    ("film", "🎬"),
+    ("food", "🍔"),
+    ("jazz", "🎷"),
+    ("live music", "🎸"),
+    ("music", "🎵"),
+    ("nightlife", "🌙"),
+    ("outdoors", "🌳"),
+    ("sports", "🏟️"),
+    ("theater", "🎭"),
+];
+
+const DEFAULT_EMOJI: &str = "📅";
+
+// =============================================================================
+// BUILDING
+// =============================================================================
+
+/// The data the prompts are made from.
+#[derive(Debug, Default)]
+struct SuggestionData {
+    /// Busiest categories starting within a week, with their event counts
+    top_categories: Vec<(String, i64)>,
+    /// The signed-in user's most liked category
+    favorite: Option<String>,
+    /// Title of the top trending upcoming event
+    trending: Option<String>,
+}
+
+/// Starter prompts for `user_id` (anonymous when `None`), from the cache
+/// if they are fresh.
+pub async fn suggestions(
+    pool: &PgPool,
+    cache: &SuggestionCache,
+    user_id: Option<Uuid>,
+) -> Result<Vec<ChatSuggestion>, sqlx::Error> {
+    if let Some(cached) = cache.get(user_id) {
+        return Ok(cached);
+    }
+
+    let data = SuggestionData {
+        top_categories: top_categories(pool).await?,
+        favorite: match user_id {
+            Some(id) => favorite_category(pool, id).await?,
+            None => None,
+        },
+        trending: trending_title(pool).await?,
+    };
+    let suggestions = compose(&data);
+
+    cache.insert(user_id, suggestions.clone());
+    Ok(suggestions)
+}
+
+async fn top_categories(pool: &PgPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        r#"
+        SELECT LOWER(TRIM(c)) AS category, COUNT(*) AS events
+        FROM events, unnest(categories) AS c
+        WHERE start_time < NOW() + make_interval(days => {})
+          AND TRIM(c) <> '' AND {} AND {} AND {}
+        GROUP BY 1
+        ORDER BY events DESC, category
+        LIMIT $1
+        "#,
+        WEEK_DAYS, UPCOMING_FILTER, NOT_CANCELLED_FILTER, NOT_ARCHIVED_FILTER
+    ))
+        .bind(TOP_CATEGORIES)
+        .fetch_all(pool)
+        .await
+}
+
+async fn favorite_category(pool: &PgPool, user_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT category
+        FROM user_preferences
+        WHERE user_id = $1 AND weight > 0
+        ORDER BY weight DESC, category
+        LIMIT 1
+        "#,
+    )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The top event by the `GET /api/events/trending` score.
+async fn trending_title(pool: &PgPool) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        r#"
+        SELECT events.title
+        FROM events
+        JOIN (
+            SELECT event_id, SUM({})::BIGINT AS score
+            FROM user_interactions
+            WHERE created_at >= NOW() - make_interval(days => {})
+            GROUP BY event_id
+        ) scores ON scores.event_id = events.id
+        WHERE scores.score > 0 AND {} AND {} AND {}
+        ORDER BY scores.score DESC, start_time ASC
+        LIMIT 1
+        "#,
+        analytics::weight_sql("interaction_type"),
+        TRENDING_WINDOW_DAYS,
+        UPCOMING_FILTER,
+        NOT_CANCELLED_FILTER,
+        NOT_ARCHIVED_FILTER
+    ))
+        .fetch_optional(pool)
+        .await
+}
+
+/// Prompts from `data`, topped up from `DEFAULT_SUGGESTIONS` and without
+/// repeats.
+fn compose(data: &SuggestionData) -> Vec<ChatSuggestion> {
+    let mut suggestions = Vec::new();
+
+    for (category, events) in &data.top_categories {
+        let noun = if *events == 1 { "event" } else { "events" };
+        add_new(&mut suggestions, ChatSuggestion {
+            label: format!("{} {}: {} {} this week — see them?", emoji_for(category), capitalize(category), events, noun),
+            message: format!("Show me {} this week", category),
+        });
+    }
+    if let Some(favorite) = &data.favorite {
+        add_new(&mut suggestions, ChatSuggestion {
+            label: format!("{} More {} for you", emoji_for(favorite), favorite),
+            message: format!("Show me {} this week", favorite),
+        });
+    }
+    if let Some(title) = &data.trending {
+        add_new(&mut suggestions, ChatSuggestion {
+            label: format!("🔥 Trending: {}", title),
+            message: format!("Tell me about {}", title),
+        });
+    }
+    add_new(&mut suggestions, ChatSuggestion::plain(WEEKEND_PROMPT));
+
+    for prompt in DEFAULT_SUGGESTIONS {
+        if suggestions.len() >= MIN_SUGGESTIONS {
+            break;
+        }
+        add_new(&mut suggestions, ChatSuggestion::plain(prompt));
+    }
+    suggestions
+}
+
+/// Adds `suggestion` unless the list is full or already sends the same
+/// message.
+fn add_new(suggestions: &mut Vec<ChatSuggestion>, suggestion: ChatSuggestion) {
+    if suggestions.len() < MAX_SUGGESTIONS && !suggestions.iter().any(|s| s.message == suggestion.message) {
+        suggestions.push(suggestion);
+    }
+}
+
+/// `text` with its first letter upper-cased.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn emoji_for(category: &str) -> &'static str {
+    CATEGORY_EMOJI
+        .iter()
+        .find(|(name, _)| *name == category)
+        .map_or(DEFAULT_EMOJI, |(_, emoji)| emoji)
+}
+
+// =============================================================================
+// CACHE
+// =============================================================================
+
+/// Suggestions and when they were built.
+type Entry = (Instant, Vec<ChatSuggestion>);
+
+/// Built suggestions by user (`None`: anonymous), shared through the
+/// router state.
+pub struct SuggestionCache {
+    entries: Mutex<HashMap<Option<Uuid>, Entry>>,
+    ttl: Duration,
+}
+
+impl SuggestionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// The suggestions cached for `user_id`, if they haven't expired.
+    pub fn get(&self, user_id: Option<Uuid>) -> Option<Vec<ChatSuggestion>> {
+        let entries = self.entries.lock().expect("suggestion cache lock");
+        entries
+            .get(&user_id)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, suggestions)| suggestions.clone())
+    }
+
+    /// Stores `suggestions` for `user_id`, dropping expired entries when
+    /// the cache is full.
+    pub fn insert(&self, user_id: Option<Uuid>, suggestions: Vec<ChatSuggestion>) {
+        let mut entries = self.entries.lock().expect("suggestion cache lock");
+
+        if entries.len() >= SUGGESTIONS_CACHE_CAPACITY {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        }
+        if entries.len() >= SUGGESTIONS_CACHE_CAPACITY {
+            // Still full of fresh entries: start over rather than grow
+            entries.clear();
+        }
+
+        entries.insert(user_id, (Instant::now(), suggestions));
+    }
+}
+
+impl Default for SuggestionCache {
+    fn default() -> Self {
+        Self::new(SUGGESTIONS_TTL)
+    }
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(suggestions: &[ChatSuggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.message.as_str()).collect()
+    }
+
+    #[test]
+    fn suggestions_come_from_the_data_then_the_defaults() {
+        let empty = compose(&SuggestionData::default());
+        assert_eq!(messages(&empty), DEFAULT_SUGGESTIONS);
+
+        let data = SuggestionData {
+            top_categories: vec![("concerts".to_string(), 12), ("food".to_string(), 1)],
+            favorite: Some("jazz".to_string()),
+            trending: Some("Jazz Night".to_string()),
+        };
+        let full = compose(&data);
+        assert_eq!(full[0].label, "🎵 Concerts: 12 events this week — see them?");
+        assert_eq!(full[1].label, "🍔 Food: 1 event this week — see them?");
+        assert_eq!(full[2].label, "🎷 More jazz for you");
+        assert_eq!(messages(&full), [
+            "Show me concerts this week",
+            "Show me food this week",
+            "Show me jazz this week",
+            "Tell me about Jazz Night",
+            WEEKEND_PROMPT,
+        ]);
+
+        // A favorite that's already a top category isn't repeated, and the
+        // defaults top the list up
+        let anonymous_quiet = SuggestionData {
+            top_categories: vec![("jazz".to_string(), 2)],
+            favorite: Some("jazz".to_string()),
+            trending: None,
+        };
+        let topped_up = compose(&anonymous_quiet);
+        assert_eq!(messages(&topped_up), [
+            "Show me jazz this week",
+            WEEKEND_PROMPT,
+            "Any live music tonight?",
+            "Family-friendly things to do",
+        ]);
+    }
+
+    #[test]
+    fn cached_suggestions_expire() {
+        let cache = SuggestionCache::default();
+        let suggestions = vec![ChatSuggestion::plain(WEEKEND_PROMPT)];
+
+        assert!(cache.get(None).is_none());
+        cache.insert(None, suggestions.clone());
+        assert_eq!(cache.get(None), Some(suggestions.clone()));
+        assert!(cache.get(Some(Uuid::new_v4())).is_none());
+
+        let expired = SuggestionCache::new(Duration::ZERO);
+        expired.insert(None, suggestions);
+        assert!(expired.get(None).is_none());
+    }
+
+    #[tokio::test]
+    async fn signed_in_users_get_their_favorite_category() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL not set");
+            return;
+        };
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let run = Uuid::new_v4();
+        let user: Uuid = sqlx::query_scalar("INSERT INTO users (email, calendar_token) VALUES ($1, $2) RETURNING id")
+            .bind(format!("{}@example.com", run))
+            .bind(run.simple().to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO user_preferences (user_id, category, weight) VALUES ($1, 'comedy', 5), ($1, 'sports', -5)")
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let cache = SuggestionCache::default();
+        let personal = suggestions(&pool, &cache, Some(user)).await.unwrap();
+        // Disliked categories are never the favorite
+        assert!(personal.iter().any(|s| s.message == "Show me comedy this week"));
+        assert!(!personal.iter().any(|s| s.label.contains("sports for you")));
+        assert!((MIN_SUGGESTIONS..=MAX_SUGGESTIONS).contains(&personal.len()));
+        assert!(personal.iter().any(|s| s.message == WEEKEND_PROMPT));
+
+        let anonymous = suggestions(&pool, &cache, None).await.unwrap();
+        assert!(!anonymous.iter().any(|s| s.label.contains("for you")));
+
+        // Cached: a changed preference shows up only once the entry expires
+        sqlx::query("DELETE FROM user_preferences WHERE user_id = $1").bind(user).execute(&pool).await.unwrap();
+        assert_eq!(suggestions(&pool, &cache, Some(user)).await.unwrap(), personal);
+
+        sqlx::query("DELETE FROM users WHERE id = $1").bind(user).execute(&pool).await.unwrap();
+    }
+}
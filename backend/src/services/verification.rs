@@ -0,0 +1,141 @@
+//! # Email Verification
+//!
+//! Signed links that prove a user can read mail at their address.
+//!
+//! ## Owner
+//! Will (Coordinator/Backend Lead)
+//!
+//! ## Flow
+//! ```text
+//! create/register/claim/email change -> send_verification(user)
+//!                                       mails FRONTEND_URL/verify-email?user=..&token=..
+//! frontend                           -> POST /api/users/:id/verify {"token": ".."}
+//!                                       sets users.email_verified_at
+//! ```
+//!
+//! ## Tokens
+//! HS256 JWTs signed with `JWT_SECRET` like session tokens, but carrying
+//! the email being verified and `aud = "email-verification"`. The audience
+//! keeps a verification token from working as a session token (and vice
+//! versa); the email makes the link dead once the address changes.
+//!
+//! ## Environment Variables
+//! ```text
+//! VERIFICATION_TTL_HOURS=48   # how long a link stays valid
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::error::AppError;
+use crate::models::User;
+use crate::services::mailer::{self, Email};
+use crate::services::scheduler;
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+/// `aud` claim of verification tokens.
+pub const VERIFICATION_AUDIENCE: &str = "email-verification";
+
+/// Default hours a verification link stays valid.
+pub const DEFAULT_VERIFICATION_TTL_HOURS: u64 = 48;
+
+// =============================================================================
+// TOKENS
+// =============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VerificationClaims {
+    sub: Uuid,
+    email: String,
+    aud: String,
+    exp: i64,
+}
+
+/// Signs a token vouching that whoever holds it reads `email`.
+pub fn sign_verification_token(user_id: Uuid, email: &str, secret: &[u8], now: DateTime<Utc>, ttl: Duration) -> String {
+    let claims = VerificationClaims {
+        sub: user_id,
+        email: email.to_string(),
+        aud: VERIFICATION_AUDIENCE.to_string(),
+        exp: (now + ttl).timestamp(),
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).expect("HS256 signing is infallible")
+}
+
+/// Checks a verification token and returns the user id and email it is for.
+pub fn verify_verification_token(token: &str, secret: &[u8]) -> Result<(Uuid, String), AppError> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[VERIFICATION_AUDIENCE]);
+
+    decode::<VerificationClaims>(token, &DecodingKey::from_secret(secret), &validation)
+        .map(|data| (data.claims.sub, data.claims.email))
+        .map_err(|_| AppError::BadRequest("invalid or expired verification token".to_string()))
+}
+
+// =============================================================================
+// SENDING
+// =============================================================================
+
+/// The message carrying a verification link.
+pub fn verification_email(email: &str, link: &str) -> Email {
+    Email {
+        to: email.to_string(),
+        subject: "Confirm your Locate918 email".to_string(),
+        body: format!(
+            "Confirm this address to finish setting up your Locate918 account:\n\n{}\n\n\
+             If you didn't sign up, you can ignore this email.\n",
+            link
+        ),
+    }
+}
+
+/// Mails `user` a fresh verification link pointing at `frontend_url`.
+pub async fn send_verification(user: &User, frontend_url: &str) -> Result<(), AppError> {
+    let hours = scheduler::env_u64("VERIFICATION_TTL_HOURS", DEFAULT_VERIFICATION_TTL_HOURS);
+    let token = sign_verification_token(user.id, &user.email, &auth::secret()?, Utc::now(), Duration::hours(hours as i64));
+    let link = format!("{}/verify-email?user={}&token={}", frontend_url, user.id, token);
+
+    mailer::from_env()?.send(verification_email(&user.email, &link)).await
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    #[test]
+    fn round_trips_user_and_email() {
+        let user = Uuid::new_v4();
+        let token = sign_verification_token(user, "sam@example.com", SECRET, Utc::now(), Duration::hours(1));
+
+        let (id, email) = verify_verification_token(&token, SECRET).unwrap();
+        assert_eq!(id, user);
+        assert_eq!(email, "sam@example.com");
+
+        let expired = sign_verification_token(user, "sam@example.com", SECRET, Utc::now() - Duration::days(3), Duration::hours(1));
+        assert!(verify_verification_token(&expired, SECRET).is_err());
+        assert!(verify_verification_token(&token, b"other-secret").is_err());
+    }
+
+    #[test]
+    fn session_and_verification_tokens_are_not_interchangeable() {
+        let user = Uuid::new_v4();
+        let verification = sign_verification_token(user, "sam@example.com", SECRET, Utc::now(), Duration::hours(1));
+        let (session, _) = auth::sign_token(user, SECRET, Utc::now(), Duration::hours(1));
+
+        assert!(auth::verify_token(&verification, SECRET).is_err());
+        assert!(verify_verification_token(&session, SECRET).is_err());
+    }
+}
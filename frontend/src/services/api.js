@@ -28,8 +28,9 @@ export const fetchEvents = async () => {
         if (!response.ok) {
             throw new Error(`HTTP error! status: ${response.status}`);
         }
-        const events = await response.json();
-        return transformBackendEvents(events);
+        // Paginated response: { events: [...], next_cursor: "..." | null }
+        const page = await response.json();
+        return transformBackendEvents(page.events);
     } catch (error) {
         console.error("Failed to fetch events:", error);
         return [];
@@ -62,6 +63,60 @@ export const searchEvents = async (params = {}) => {
     }
 };
 
+/**
+ * Fetch full details for several events at once (max 100 IDs).
+ * Used to re-hydrate event cards from IDs, e.g. saved events or chat results.
+ * Returned in the same order as `ids`; unknown IDs are skipped.
+ */
+export const fetchEventsByIds = async (ids = []) => {
+    if (USE_MOCKS || ids.length === 0) {
+        return [];
+    }
+
+    try {
+        const response = await fetch(`${RUST_BACKEND_URL}/api/events/batch`, {
+            method: "POST",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({ ids }),
+        });
+        if (!response.ok) {
+            throw new Error(`HTTP error! status: ${response.status}`);
+        }
+        const events = await response.json();
+        return transformBackendEvents(events);
+    } catch (error) {
+        console.error("Failed to fetch events by ID:", error);
+        return [];
+    }
+};
+
+/**
+ * Fetch per-day event counts for a month grid.
+ * `month` is "YYYY-MM"; `filters` accepts the same category/location filters as searchEvents.
+ * Resolves to `{ "2026-02-01": { count, top_titles }, ... }`.
+ */
+export const fetchEventCalendar = async (month, filters = {}) => {
+    if (USE_MOCKS) {
+        return {};
+    }
+
+    try {
+        const queryString = new URLSearchParams(
+            Object.entries({ month, ...filters }).filter(([_, v]) => v != null)
+        ).toString();
+
+        const response = await fetch(`${RUST_BACKEND_URL}/api/events/calendar?${queryString}`);
+        if (!response.ok) {
+            throw new Error(`HTTP error! status: ${response.status}`);
+        }
+        const calendar = await response.json();
+        return calendar.days;
+    } catch (error) {
+        console.error("Failed to fetch event calendar:", error);
+        return {};
+    }
+};
+
 // =============================================================================
 // Smart Search API (Python LLM Service :8001)
 // =============================================================================
@@ -164,6 +219,8 @@ const transformBackendEvents = (events) => {
         originalSource: event.source_name,
         price_min: event.price_min,
         price_max: event.price_max,
+        is_free: event.is_free,
+        status: event.status,
         outdoor: event.outdoor,
         family_friendly: event.family_friendly,
         coordinates: event.coordinates || getDefaultCoordinates(event.location),